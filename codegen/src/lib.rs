@@ -178,6 +178,10 @@ pub fn derive_packet(_item: TokenStream) -> TokenStream {
             write = quote! {
                 buf.#write_fn_ident(&self.#field_name);
             };
+        } else if *parameter_type == PacketParameterType::Slot {
+            write = quote! {
+                buf.#write_fn_ident(self.#field_name.as_ref());
+            };
         } else {
             write = quote! {
                 buf.#write_fn_ident(self.#field_name);
@@ -196,7 +200,7 @@ pub fn derive_packet(_item: TokenStream) -> TokenStream {
 
     let r = quote! {
         impl Packet for #ident {
-            fn read_from(&mut self, mut buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+            fn read_from(&mut self, mut buf: &mut Bytes) -> anyhow::Result<()> {
                 #(#read_code)*
                 Ok(())
             }
@@ -0,0 +1,68 @@
+#[macro_use]
+extern crate criterion;
+
+use criterion::{black_box, Criterion};
+use feather_anvil::region::{create_region, RegionPosition};
+use feather_anvil::{level, player::PlayerData};
+use feather_blocks::BlockId;
+use feather_chunk::Chunk;
+use feather_util::ChunkPosition;
+use std::io::Cursor;
+
+static LEVEL_DAT: &[u8] = include_bytes!("../src/level.dat");
+static PLAYER_DAT: &[u8] = include_bytes!("../src/player.dat");
+
+fn level_dat_parse(c: &mut Criterion) {
+    c.bench_function("level_dat_parse", |b| {
+        b.iter(|| {
+            nbt::from_gzip_reader::<_, level::Root>(Cursor::new(black_box(LEVEL_DAT))).unwrap()
+        });
+    });
+}
+
+fn player_dat_parse(c: &mut Criterion) {
+    c.bench_function("player_dat_parse", |b| {
+        b.iter(|| {
+            nbt::from_gzip_reader::<_, PlayerData>(Cursor::new(black_box(PLAYER_DAT))).unwrap()
+        });
+    });
+}
+
+fn region_chunk_save_and_load(c: &mut Criterion) {
+    feather_blocks::init();
+
+    let pos = ChunkPosition::new(0, 0);
+    let mut chunk = Chunk::new(pos);
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..64 {
+                let block = if y == 63 {
+                    BlockId::grass_block()
+                } else {
+                    BlockId::stone()
+                };
+                chunk.set_block_at(x, y, z, block);
+            }
+        }
+    }
+
+    let dir = std::env::temp_dir().join("feather-anvil-bench");
+    let mut region = create_region(&dir, RegionPosition::from_chunk(pos)).unwrap();
+
+    c.bench_function("region_chunk_save_and_load", |b| {
+        b.iter(|| {
+            region.save_chunk(&chunk, vec![], vec![]).unwrap();
+            region.load_chunk(pos).unwrap()
+        });
+    });
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+criterion_group!(
+    benches,
+    level_dat_parse,
+    player_dat_parse,
+    region_chunk_save_and_load
+);
+criterion_main!(benches);
@@ -0,0 +1,335 @@
+//! Block entity data, as stored in the `TileEntities`
+//! list of a chunk and sent to clients.
+
+use crate::player::InventorySlot;
+use crate::value::to_value;
+use feather_items::ItemStack;
+use feather_util::BlockPosition;
+use nbt::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A block entity in the `TileEntities` list of a chunk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "id")]
+pub enum BlockEntityData {
+    #[serde(rename = "minecraft:chest")]
+    Chest(ContainerData),
+    #[serde(rename = "minecraft:trapped_chest")]
+    TrappedChest(ContainerData),
+    #[serde(rename = "minecraft:dispenser")]
+    Dispenser(ContainerData),
+    #[serde(rename = "minecraft:dropper")]
+    Dropper(ContainerData),
+    #[serde(rename = "minecraft:hopper")]
+    Hopper(ContainerData),
+    #[serde(rename = "minecraft:mob_spawner")]
+    MobSpawner(SpawnerData),
+
+    /// Fallback type for unknown block entities,
+    /// which are not kept when the chunk is saved.
+    #[serde(other)]
+    Unknown,
+}
+
+impl BlockEntityData {
+    /// Returns the position of the block entity,
+    /// or `None` if it is unknown.
+    pub fn position(&self) -> Option<BlockPosition> {
+        match self {
+            BlockEntityData::MobSpawner(data) => Some(data.position()),
+            _ => self.container().map(ContainerData::position),
+        }
+    }
+
+    /// Returns the items of the block entity
+    /// if it is a container.
+    pub fn container(&self) -> Option<&ContainerData> {
+        match self {
+            BlockEntityData::Chest(data)
+            | BlockEntityData::TrappedChest(data)
+            | BlockEntityData::Dispenser(data)
+            | BlockEntityData::Dropper(data)
+            | BlockEntityData::Hopper(data) => Some(data),
+            BlockEntityData::MobSpawner(_) | BlockEntityData::Unknown => None,
+        }
+    }
+
+    /// Returns the items of the block entity
+    /// if it is a container.
+    pub fn container_mut(&mut self) -> Option<&mut ContainerData> {
+        match self {
+            BlockEntityData::Chest(data)
+            | BlockEntityData::TrappedChest(data)
+            | BlockEntityData::Dispenser(data)
+            | BlockEntityData::Dropper(data)
+            | BlockEntityData::Hopper(data) => Some(data),
+            BlockEntityData::MobSpawner(_) | BlockEntityData::Unknown => None,
+        }
+    }
+
+    pub fn into_nbt_value(self) -> Value {
+        let mut map = HashMap::new();
+
+        map.insert(
+            String::from("id"),
+            Value::String(
+                match self {
+                    BlockEntityData::Chest(_) => "minecraft:chest",
+                    BlockEntityData::TrappedChest(_) => "minecraft:trapped_chest",
+                    BlockEntityData::Dispenser(_) => "minecraft:dispenser",
+                    BlockEntityData::Dropper(_) => "minecraft:dropper",
+                    BlockEntityData::Hopper(_) => "minecraft:hopper",
+                    BlockEntityData::MobSpawner(_) => "minecraft:mob_spawner",
+                    BlockEntityData::Unknown => panic!("Cannot write unknown block entities"),
+                }
+                .to_string(),
+            ),
+        );
+
+        match self {
+            BlockEntityData::Chest(data)
+            | BlockEntityData::TrappedChest(data)
+            | BlockEntityData::Dispenser(data)
+            | BlockEntityData::Dropper(data)
+            | BlockEntityData::Hopper(data) => data.write_to_map(&mut map),
+            BlockEntityData::MobSpawner(data) => data.write_to_map(&mut map),
+            BlockEntityData::Unknown => unreachable!(),
+        }
+
+        Value::Compound(map)
+    }
+}
+
+/// Data of a block entity which stores items, such as a chest.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContainerData {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    /// The stacks in the container. Empty slots are left out.
+    #[serde(rename = "Items", default)]
+    pub items: Vec<InventorySlot>,
+}
+
+impl ContainerData {
+    /// Returns the data of an empty container at `pos`.
+    pub fn new(pos: BlockPosition) -> Self {
+        Self {
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+            items: vec![],
+        }
+    }
+
+    pub fn position(&self) -> BlockPosition {
+        BlockPosition::new(self.x, self.y, self.z)
+    }
+
+    /// Returns the stack in a slot of the container.
+    pub fn item(&self, slot: usize) -> Option<ItemStack> {
+        self.items
+            .iter()
+            .find(|item| item.slot as usize == slot)
+            .map(InventorySlot::to_stack)
+    }
+
+    /// Sets the stack in a slot of the container,
+    /// emptying the slot if `stack` is `None`.
+    pub fn set_item(&mut self, slot: usize, stack: Option<ItemStack>) {
+        self.items.retain(|item| item.slot as usize != slot);
+        if let Some(stack) = stack {
+            self.items.push(InventorySlot {
+                count: stack.amount as i8,
+                slot: slot as i8,
+                item: stack.ty.identifier().to_string(),
+                nbt: stack.nbt().cloned(),
+            });
+        }
+    }
+
+    fn write_to_map(self, map: &mut HashMap<String, Value>) {
+        map.insert(String::from("x"), Value::Int(self.x));
+        map.insert(String::from("y"), Value::Int(self.y));
+        map.insert(String::from("z"), Value::Int(self.z));
+
+        let items = self
+            .items
+            .into_iter()
+            .map(|item| {
+                let mut map = HashMap::new();
+                map.insert(String::from("Count"), Value::Byte(item.count));
+                map.insert(String::from("Slot"), Value::Byte(item.slot));
+                map.insert(String::from("id"), Value::String(item.item));
+                if let Some(nbt) = item.nbt {
+                    let tag = to_value(&nbt).expect("item tags are valid NBT");
+                    map.insert(String::from("tag"), tag);
+                }
+                Value::Compound(map)
+            })
+            .collect();
+        map.insert(String::from("Items"), Value::List(items));
+    }
+}
+
+/// Data of a mob spawner.
+///
+/// The block entity's `id` is part of `BlockEntityData`,
+/// which should be used to write spawners.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnerData {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    /// The entity spawned next, which clients
+    /// display spinning inside the spawner.
+    #[serde(rename = "SpawnData")]
+    pub spawn_data: SpawnerEntity,
+    /// Ticks until the next spawn attempt.
+    #[serde(rename = "Delay")]
+    pub delay: i16,
+    #[serde(rename = "MinSpawnDelay")]
+    pub min_spawn_delay: i16,
+    #[serde(rename = "MaxSpawnDelay")]
+    pub max_spawn_delay: i16,
+    #[serde(rename = "SpawnCount")]
+    pub spawn_count: i16,
+    #[serde(rename = "MaxNearbyEntities")]
+    pub max_nearby_entities: i16,
+    /// Spawning only happens while a player is within this
+    /// many blocks of the spawner.
+    #[serde(rename = "RequiredPlayerRange")]
+    pub required_player_range: i16,
+    #[serde(rename = "SpawnRange")]
+    pub spawn_range: i16,
+}
+
+/// The entity spawned by a spawner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnerEntity {
+    /// The namespaced identifier of the entity.
+    pub id: String,
+}
+
+impl SpawnerData {
+    /// Returns the data of a spawner at `pos` which spawns the
+    /// given entity, with the settings of a vanilla spawner.
+    pub fn new(pos: BlockPosition, entity: impl Into<String>) -> Self {
+        Self {
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+            spawn_data: SpawnerEntity { id: entity.into() },
+            delay: 20,
+            min_spawn_delay: 200,
+            max_spawn_delay: 800,
+            spawn_count: 4,
+            max_nearby_entities: 6,
+            required_player_range: 16,
+            spawn_range: 4,
+        }
+    }
+
+    pub fn position(&self) -> BlockPosition {
+        BlockPosition::new(self.x, self.y, self.z)
+    }
+
+    fn write_to_map(self, map: &mut HashMap<String, Value>) {
+        map.insert(String::from("x"), Value::Int(self.x));
+        map.insert(String::from("y"), Value::Int(self.y));
+        map.insert(String::from("z"), Value::Int(self.z));
+
+        let mut spawn_data = HashMap::new();
+        spawn_data.insert(String::from("id"), Value::String(self.spawn_data.id));
+        map.insert(String::from("SpawnData"), Value::Compound(spawn_data));
+
+        map.insert(String::from("Delay"), Value::Short(self.delay));
+        map.insert(
+            String::from("MinSpawnDelay"),
+            Value::Short(self.min_spawn_delay),
+        );
+        map.insert(
+            String::from("MaxSpawnDelay"),
+            Value::Short(self.max_spawn_delay),
+        );
+        map.insert(String::from("SpawnCount"), Value::Short(self.spawn_count));
+        map.insert(
+            String::from("MaxNearbyEntities"),
+            Value::Short(self.max_nearby_entities),
+        );
+        map.insert(
+            String::from("RequiredPlayerRange"),
+            Value::Short(self.required_player_range),
+        );
+        map.insert(String::from("SpawnRange"), Value::Short(self.spawn_range));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_items::Item;
+
+    fn write_and_read(block_entity: BlockEntityData) -> BlockEntityData {
+        let mut blob = nbt::Blob::new();
+        if let Value::Compound(map) = block_entity.into_nbt_value() {
+            for (name, value) in map {
+                blob.insert(name, value).unwrap();
+            }
+        }
+        let mut buf = vec![];
+        blob.to_writer(&mut buf).unwrap();
+        nbt::from_reader(buf.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn spawner_roundtrip() {
+        let data = SpawnerData::new(BlockPosition::new(10, 40, -3), "minecraft:zombie");
+        let read = write_and_read(BlockEntityData::MobSpawner(data.clone()));
+
+        assert_eq!(read, BlockEntityData::MobSpawner(data));
+        assert_eq!(read.position(), Some(BlockPosition::new(10, 40, -3)));
+    }
+
+    #[test]
+    fn spawner_packet_data() {
+        let data = SpawnerData::new(BlockPosition::new(10, 40, -3), "minecraft:zombie");
+
+        let mut buf = vec![];
+        nbt::to_writer(&mut buf, &BlockEntityData::MobSpawner(data.clone()), None).unwrap();
+        let read: BlockEntityData = nbt::from_reader(buf.as_slice()).unwrap();
+
+        assert_eq!(read, BlockEntityData::MobSpawner(data));
+    }
+
+    #[test]
+    fn container_roundtrip() {
+        let mut data = ContainerData::new(BlockPosition::new(1, 64, -7));
+        data.set_item(3, Some(ItemStack::new(Item::Diamond, 5)));
+        data.set_item(26, Some(ItemStack::new(Item::Stone, 64)));
+        data.set_item(26, None);
+        assert_eq!(data.item(3), Some(ItemStack::new(Item::Diamond, 5)));
+        assert_eq!(data.item(26), None);
+
+        let mut sword = ItemStack::new(Item::IronSword, 1);
+        sword.set_damage(40);
+        data.set_item(5, Some(sword.clone()));
+        assert_eq!(data.item(5), Some(sword));
+
+        let read = write_and_read(BlockEntityData::Chest(data.clone()));
+        assert_eq!(read, BlockEntityData::Chest(data));
+    }
+
+    #[test]
+    fn unknown_block_entities() {
+        let mut blob = nbt::Blob::new();
+        blob.insert("id", "minecraft:sign").unwrap();
+        let mut buf = vec![];
+        blob.to_writer(&mut buf).unwrap();
+
+        let read: BlockEntityData = nbt::from_reader(buf.as_slice()).unwrap();
+        assert_eq!(read, BlockEntityData::Unknown);
+        assert_eq!(read.position(), None);
+    }
+}
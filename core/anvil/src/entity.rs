@@ -1,5 +1,6 @@
+use crate::value::to_value;
 use arrayvec::ArrayVec;
-use feather_items::Item;
+use feather_items::{Item, ItemNbt};
 use feather_util::{vec3, Position, Vec3d};
 use nbt::Value;
 use serde::{Deserialize, Serialize};
@@ -10,6 +11,7 @@ use thiserror::Error;
 pub enum EntityDataKind {
     Item,
     Arrow,
+    ItemFrame,
     Cow,
     Pig,
     Chicken,
@@ -20,6 +22,14 @@ pub enum EntityDataKind {
     Rabbit,
     Squid,
     Donkey,
+    Creeper,
+    Zombie,
+    Skeleton,
+    Spider,
+    Enderman,
+    Witch,
+    Villager,
+    ZombiePigman,
     Unknown,
 }
 
@@ -28,6 +38,7 @@ impl<'a> From<&'a EntityData> for EntityDataKind {
         match data {
             EntityData::Arrow(_) => EntityDataKind::Arrow,
             EntityData::Item(_) => EntityDataKind::Item,
+            EntityData::ItemFrame(_) => EntityDataKind::ItemFrame,
             EntityData::Cow(_) => EntityDataKind::Cow,
             EntityData::Pig(_) => EntityDataKind::Pig,
             EntityData::Chicken(_) => EntityDataKind::Chicken,
@@ -38,6 +49,14 @@ impl<'a> From<&'a EntityData> for EntityDataKind {
             EntityData::Rabbit(_) => EntityDataKind::Rabbit,
             EntityData::Squid(_) => EntityDataKind::Squid,
             EntityData::Donkey(_) => EntityDataKind::Donkey,
+            EntityData::Creeper(_) => EntityDataKind::Creeper,
+            EntityData::Zombie(_) => EntityDataKind::Zombie,
+            EntityData::Skeleton(_) => EntityDataKind::Skeleton,
+            EntityData::Spider(_) => EntityDataKind::Spider,
+            EntityData::Enderman(_) => EntityDataKind::Enderman,
+            EntityData::Witch(_) => EntityDataKind::Witch,
+            EntityData::Villager(_) => EntityDataKind::Villager,
+            EntityData::ZombiePigman(_) => EntityDataKind::ZombiePigman,
             EntityData::Unknown => EntityDataKind::Unknown,
         }
     }
@@ -50,26 +69,44 @@ pub enum EntityData {
     Item(ItemEntityData),
     #[serde(rename = "minecraft:arrow")]
     Arrow(ArrowEntityData),
+    #[serde(rename = "minecraft:item_frame")]
+    ItemFrame(ItemFrameData),
     #[serde(rename = "minecraft:cow")]
-    Cow(AnimalData),
+    Cow(MobData),
     #[serde(rename = "minecraft:pig")]
-    Pig(AnimalData),
+    Pig(MobData),
     #[serde(rename = "minecraft:chicken")]
-    Chicken(AnimalData),
+    Chicken(MobData),
     #[serde(rename = "minecraft:sheep")]
-    Sheep(AnimalData),
+    Sheep(SheepData),
     #[serde(rename = "minecraft:horse")]
-    Horse(AnimalData),
+    Horse(MobData),
     #[serde(rename = "minecraft:llama")]
-    Llama(AnimalData),
-    #[serde(rename = "minectaft:mooshroom")]
-    Mooshroom(AnimalData),
-    #[serde(rename = "minecraft:rabbit}")]
-    Rabbit(AnimalData),
+    Llama(MobData),
+    #[serde(rename = "minecraft:mooshroom")]
+    Mooshroom(MobData),
+    #[serde(rename = "minecraft:rabbit")]
+    Rabbit(MobData),
     #[serde(rename = "minecraft:squid")]
-    Squid(AnimalData),
+    Squid(MobData),
     #[serde(rename = "minecraft:donkey")]
-    Donkey(AnimalData),
+    Donkey(MobData),
+    #[serde(rename = "minecraft:creeper")]
+    Creeper(CreeperData),
+    #[serde(rename = "minecraft:zombie")]
+    Zombie(MobData),
+    #[serde(rename = "minecraft:skeleton")]
+    Skeleton(MobData),
+    #[serde(rename = "minecraft:spider")]
+    Spider(MobData),
+    #[serde(rename = "minecraft:enderman")]
+    Enderman(MobData),
+    #[serde(rename = "minecraft:witch")]
+    Witch(MobData),
+    #[serde(rename = "minecraft:villager")]
+    Villager(MobData),
+    #[serde(rename = "minecraft:zombie_pigman")]
+    ZombiePigman(MobData),
 
     /// Fallback type for unknown entities
     #[serde(other)]
@@ -86,6 +123,7 @@ impl EntityData {
                 match self {
                     EntityData::Item(_) => "minecraft:item",
                     EntityData::Arrow(_) => "minecraft:arrow",
+                    EntityData::ItemFrame(_) => "minecraft:item_frame",
                     EntityData::Cow(_) => "minecraft:cow",
                     EntityData::Pig(_) => "minecraft:pig",
                     EntityData::Chicken(_) => "minecraft:chicken",
@@ -96,6 +134,14 @@ impl EntityData {
                     EntityData::Rabbit(_) => "minecraft:rabbit",
                     EntityData::Squid(_) => "minecraft:squid",
                     EntityData::Donkey(_) => "minecraft:donkey",
+                    EntityData::Creeper(_) => "minecraft:creeper",
+                    EntityData::Zombie(_) => "minecraft:zombie",
+                    EntityData::Skeleton(_) => "minecraft:skeleton",
+                    EntityData::Spider(_) => "minecraft:spider",
+                    EntityData::Enderman(_) => "minecraft:enderman",
+                    EntityData::Witch(_) => "minecraft:witch",
+                    EntityData::Villager(_) => "minecraft:villager",
+                    EntityData::ZombiePigman(_) => "minecraft:zombie_pigman",
                     EntityData::Unknown => panic!("Cannot write unknown entities"),
                 }
                 .to_string(),
@@ -105,6 +151,7 @@ impl EntityData {
         match self {
             EntityData::Item(data) => data.write_to_map(&mut map),
             EntityData::Arrow(data) => data.write_to_map(&mut map),
+            EntityData::ItemFrame(data) => data.write_to_map(&mut map),
             EntityData::Cow(data) => data.write_to_map(&mut map),
             EntityData::Pig(data) => data.write_to_map(&mut map),
             EntityData::Chicken(data) => data.write_to_map(&mut map),
@@ -115,6 +162,14 @@ impl EntityData {
             EntityData::Rabbit(data) => data.write_to_map(&mut map),
             EntityData::Squid(data) => data.write_to_map(&mut map),
             EntityData::Donkey(data) => data.write_to_map(&mut map),
+            EntityData::Creeper(data) => data.write_to_map(&mut map),
+            EntityData::Zombie(data) => data.write_to_map(&mut map),
+            EntityData::Skeleton(data) => data.write_to_map(&mut map),
+            EntityData::Spider(data) => data.write_to_map(&mut map),
+            EntityData::Enderman(data) => data.write_to_map(&mut map),
+            EntityData::Witch(data) => data.write_to_map(&mut map),
+            EntityData::Villager(data) => data.write_to_map(&mut map),
+            EntityData::ZombiePigman(data) => data.write_to_map(&mut map),
             EntityData::Unknown => unreachable!(),
         }
 
@@ -202,15 +257,71 @@ impl Default for BaseEntityData {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AnimalData {
+/// Tags common to all mobs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MobData {
     #[serde(flatten)]
     pub base: BaseEntityData,
+
+    #[serde(rename = "Health", default)]
+    pub health: Option<f32>,
+    /// The mob's name as a JSON text component.
+    #[serde(rename = "CustomName", default)]
+    pub custom_name: Option<String>,
 }
 
-impl AnimalData {
+impl MobData {
     fn write_to_map(self, map: &mut HashMap<String, Value>) {
         self.base.write_to_map(map);
+
+        if let Some(health) = self.health {
+            map.insert(String::from("Health"), Value::Float(health));
+        }
+        if let Some(custom_name) = self.custom_name {
+            map.insert(String::from("CustomName"), Value::String(custom_name));
+        }
+    }
+}
+
+/// Data for a Creeper entity (`minecraft:creeper`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreeperData {
+    #[serde(flatten)]
+    pub mob: MobData,
+
+    // TODO: Change this field to `bool` when issue with hematite_nbt is resolved.
+    // See: https://github.com/PistonDevelopers/hematite_nbt/issues/43
+    #[serde(rename = "powered", default)]
+    pub powered: u8,
+}
+
+impl CreeperData {
+    fn write_to_map(self, map: &mut HashMap<String, Value>) {
+        self.mob.write_to_map(map);
+
+        map.insert(String::from("powered"), Value::Byte(self.powered as i8));
+    }
+}
+
+/// Data for a Sheep entity (`minecraft:sheep`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SheepData {
+    #[serde(flatten)]
+    pub mob: MobData,
+
+    /// ID of the dye color of the sheep's wool.
+    #[serde(rename = "Color", default)]
+    pub color: i8,
+    #[serde(rename = "Sheared", default)]
+    pub sheared: u8,
+}
+
+impl SheepData {
+    fn write_to_map(self, map: &mut HashMap<String, Value>) {
+        self.mob.write_to_map(map);
+
+        map.insert(String::from("Color"), Value::Byte(self.color));
+        map.insert(String::from("Sheared"), Value::Byte(self.sheared as i8));
     }
 }
 
@@ -221,12 +332,18 @@ pub struct ItemData {
     pub count: u8,
     #[serde(rename = "id")]
     pub item: String,
+    #[serde(rename = "tag", default, skip_serializing_if = "Option::is_none")]
+    pub nbt: Option<ItemNbt>,
 }
 
 impl ItemData {
     fn write_to_map(self, map: &mut HashMap<String, Value>) {
         map.insert(String::from("Count"), Value::Byte(self.count as i8));
         map.insert(String::from("id"), Value::String(self.item));
+        if let Some(nbt) = self.nbt {
+            let tag = to_value(&nbt).expect("item tags are valid NBT");
+            map.insert(String::from("tag"), tag);
+        }
     }
 }
 
@@ -235,6 +352,7 @@ impl Default for ItemData {
         Self {
             count: 0,
             item: Item::Air.identifier().to_string(),
+            nbt: None,
         }
     }
 }
@@ -284,6 +402,20 @@ pub struct ArrowEntityData {
     // See: https://github.com/PistonDevelopers/hematite_nbt/issues/43
     #[serde(rename = "crit")]
     pub critical: u8,
+    /// 0 if the arrow cannot be picked up, 1 if it can be picked
+    /// up by any player, and 2 if only creative players may pick it up.
+    #[serde(rename = "pickup", default)]
+    pub pickup: u8,
+    /// Damage dealt per block/tick of speed.
+    #[serde(rename = "damage", default = "default_arrow_damage")]
+    pub damage: f64,
+    /// Number of ticks the arrow has been stuck in the ground.
+    #[serde(rename = "life", default)]
+    pub life: i16,
+}
+
+fn default_arrow_damage() -> f64 {
+    2.0
 }
 
 impl ArrowEntityData {
@@ -291,12 +423,61 @@ impl ArrowEntityData {
         self.entity.write_to_map(map);
 
         map.insert(String::from("crit"), Value::Byte(self.critical as i8));
+        map.insert(String::from("pickup"), Value::Byte(self.pickup as i8));
+        map.insert(String::from("life"), Value::Short(self.life));
+    }
+}
+
+/// Data for an Item Frame entity (`minecraft:item_frame`).
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ItemFrameData {
+    // Inherit base entity data
+    #[serde(flatten)]
+    pub entity: BaseEntityData,
+
+    // Item frame-specific tags
+    /// Position of the block the frame is in, next
+    /// to the block it hangs on.
+    #[serde(rename = "TileX")]
+    pub tile_x: i32,
+    #[serde(rename = "TileY")]
+    pub tile_y: i32,
+    #[serde(rename = "TileZ")]
+    pub tile_z: i32,
+    /// Direction the frame faces, from 0 (down) to 5 (east).
+    #[serde(rename = "Facing")]
+    pub facing: u8,
+    #[serde(rename = "Item", default)]
+    pub item: Option<ItemData>,
+    /// Rotation of the item, in eighths of a full turn.
+    #[serde(rename = "ItemRotation", default)]
+    pub item_rotation: u8,
+}
+
+impl ItemFrameData {
+    fn write_to_map(self, map: &mut HashMap<String, Value>) {
+        self.entity.write_to_map(map);
+
+        map.insert(String::from("TileX"), Value::Int(self.tile_x));
+        map.insert(String::from("TileY"), Value::Int(self.tile_y));
+        map.insert(String::from("TileZ"), Value::Int(self.tile_z));
+        map.insert(String::from("Facing"), Value::Byte(self.facing as i8));
+        if let Some(item_data) = self.item {
+            let mut item = HashMap::new();
+            item_data.write_to_map(&mut item);
+            map.insert(String::from("Item"), Value::Compound(item));
+        }
+        map.insert(
+            String::from("ItemRotation"),
+            Value::Byte(self.item_rotation as i8),
+        );
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use feather_items::Tag;
     use feather_util::position;
 
     #[test]
@@ -339,4 +520,101 @@ mod tests {
         assert_eq!(data.read_position(), Ok(pos));
         assert_eq!(data.read_velocity(), Ok(vel));
     }
+
+    #[test]
+    fn write_mob_data() {
+        let data = EntityData::Creeper(CreeperData {
+            mob: MobData {
+                base: BaseEntityData::default(),
+                health: Some(15.0),
+                custom_name: Some(String::from("{\"text\":\"Bob\"}")),
+            },
+            powered: 1,
+        });
+
+        let map = match data.into_nbt_value() {
+            Value::Compound(map) => map,
+            value => panic!("expected compound, got {:?}", value),
+        };
+        assert_eq!(
+            map.get("id"),
+            Some(&Value::String(String::from("minecraft:creeper")))
+        );
+        assert_eq!(map.get("Health"), Some(&Value::Float(15.0)));
+        assert_eq!(
+            map.get("CustomName"),
+            Some(&Value::String(String::from("{\"text\":\"Bob\"}")))
+        );
+        assert_eq!(map.get("powered"), Some(&Value::Byte(1)));
+    }
+
+    #[test]
+    fn write_item_frame_data() {
+        let data = EntityData::ItemFrame(ItemFrameData {
+            tile_x: 4,
+            tile_y: 65,
+            tile_z: -2,
+            facing: 3,
+            item: Some(ItemData {
+                count: 1,
+                item: Item::Compass.identifier().to_owned(),
+                nbt: None,
+            }),
+            item_rotation: 5,
+            ..Default::default()
+        });
+
+        let map = match data.into_nbt_value() {
+            Value::Compound(map) => map,
+            value => panic!("expected compound, got {:?}", value),
+        };
+        assert_eq!(
+            map.get("id"),
+            Some(&Value::String(String::from("minecraft:item_frame")))
+        );
+        assert_eq!(map.get("TileY"), Some(&Value::Int(65)));
+        assert_eq!(map.get("Facing"), Some(&Value::Byte(3)));
+        assert_eq!(map.get("ItemRotation"), Some(&Value::Byte(5)));
+        match map.get("Item") {
+            Some(Value::Compound(item)) => assert_eq!(
+                item.get("id"),
+                Some(&Value::String(String::from("minecraft:compass")))
+            ),
+            value => panic!("expected compound, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn write_item_nbt() {
+        let mut nbt = ItemNbt {
+            damage: 3,
+            unbreakable: true,
+            ..Default::default()
+        };
+        nbt.other.insert(String::from("RepairCost"), Tag::Int(2));
+        let data = EntityData::Item(ItemEntityData {
+            item: ItemData {
+                count: 1,
+                item: Item::Shears.identifier().to_owned(),
+                nbt: Some(nbt),
+            },
+            ..Default::default()
+        });
+
+        let map = match data.into_nbt_value() {
+            Value::Compound(map) => map,
+            value => panic!("expected compound, got {:?}", value),
+        };
+        let tag = match map.get("Item") {
+            Some(Value::Compound(item)) => item.get("tag"),
+            value => panic!("expected compound, got {:?}", value),
+        };
+        let tag = match tag {
+            Some(Value::Compound(tag)) => tag,
+            value => panic!("expected compound, got {:?}", value),
+        };
+        assert_eq!(tag.get("Damage"), Some(&Value::Int(3)));
+        assert_eq!(tag.get("Unbreakable"), Some(&Value::Byte(1)));
+        assert_eq!(tag.get("RepairCost"), Some(&Value::Int(2)));
+    }
 }
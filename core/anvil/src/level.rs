@@ -46,6 +46,10 @@ pub struct LevelData {
     pub difficulty_locked: i8,
     #[serde(rename = "GameType")]
     pub game_type: i32,
+    /// Gamerules, stored as strings by name.
+    #[serde(default)]
+    #[serde(rename = "GameRules")]
+    pub game_rules: HashMap<String, String>,
 
     pub hardcore: bool,
 
@@ -173,6 +177,14 @@ impl LevelData {
             _ => LevelGeneratorType::Default,
         }
     }
+
+    /// Returns the value of a boolean gamerule,
+    /// or `default` if the rule is not set.
+    pub fn bool_game_rule(&self, name: &str, default: bool) -> bool {
+        self.game_rules
+            .get(name)
+            .map_or(default, |value| value == "true")
+    }
 }
 
 #[cfg(test)]
@@ -193,6 +205,7 @@ mod tests {
         assert_eq!(level.difficulty, 1);
         assert_eq!(level.difficulty_locked, 0);
         assert_eq!(level.game_type, 0);
+        assert!(level.bool_game_rule("naturalRegeneration", false));
         assert!(!level.hardcore);
         assert!(level.initialized);
         assert_eq!(level.last_played, 1_560_968_104_655);
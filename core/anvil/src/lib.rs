@@ -1,8 +1,11 @@
 //! Module containing functions for loading and saving to
 //! world saves. Currently includes region file loading,
-//! player data loading, and level data loading.
+//! player data loading, level data loading, and map data loading.
 
+pub mod block_entity;
 pub mod entity;
 pub mod level;
+pub mod map;
 pub mod player;
 pub mod region;
+pub mod value;
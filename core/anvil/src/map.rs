@@ -0,0 +1,220 @@
+//! Implements loading and saving of map item data,
+//! stored in `data/map_<id>.dat` inside the world directory.
+
+use nbt::{Blob, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Cursor, ErrorKind};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+/// Number of pixels along each side of a map.
+pub const MAP_SIZE: usize = 128;
+
+/// Root tag of a map file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MapRoot {
+    data: MapData,
+}
+
+/// Represents the contents of a map file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapData {
+    /// Zoom level, where each pixel covers `2^scale` blocks.
+    pub scale: i8,
+    pub dimension: i32,
+    #[serde(rename = "xCenter")]
+    pub x_center: i32,
+    #[serde(rename = "zCenter")]
+    pub z_center: i32,
+    #[serde(rename = "trackingPosition")]
+    #[serde(default)]
+    pub tracking_position: bool,
+    #[serde(rename = "unlimitedTracking")]
+    #[serde(default)]
+    pub unlimited_tracking: bool,
+    #[serde(default)]
+    pub banners: Vec<MapBanner>,
+    /// Map colors of the pixels, row by row.
+    pub colors: Vec<i8>,
+}
+
+/// A banner marked on a map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapBanner {
+    #[serde(rename = "Pos")]
+    pub pos: MapBannerPos,
+    /// Identifier of the banner's dye color, e.g. `white`.
+    #[serde(rename = "Color")]
+    pub color: String,
+    /// Custom name of the banner as a chat component.
+    #[serde(rename = "Name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapBannerPos {
+    #[serde(rename = "X")]
+    pub x: i32,
+    #[serde(rename = "Y")]
+    pub y: i32,
+    #[serde(rename = "Z")]
+    pub z: i32,
+}
+
+/// Root tag of `idcounts.dat`, which stores the last
+/// map ID handed out.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct IdCountsRoot {
+    data: IdCounts,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct IdCounts {
+    #[serde(default)]
+    map: i32,
+}
+
+/// Loads the map with the given ID. Returns `Ok(None)`
+/// if it doesn't exist.
+pub async fn load_map_data(world_dir: &Path, id: i32) -> anyhow::Result<Option<MapData>> {
+    let buf = match tokio::fs::read(map_path(world_dir, id)).await {
+        Ok(buf) => buf,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let root: MapRoot = nbt::from_gzip_reader(Cursor::new(&buf))?;
+    Ok(Some(root.data))
+}
+
+pub async fn save_map_data(world_dir: &Path, id: i32, data: &MapData) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(world_dir.join("data")).await?;
+
+    let mut buf = vec![];
+    map_data_to_blob(data).to_gzip_writer(&mut buf)?;
+
+    let mut file = tokio::fs::File::create(map_path(world_dir, id)).await?;
+    file.write_all(&buf).await?;
+    Ok(())
+}
+
+/// Loads the last map ID handed out, or `None`
+/// if no maps have been created in this world.
+pub async fn load_last_map_id(world_dir: &Path) -> anyhow::Result<Option<i32>> {
+    let buf = match tokio::fs::read(id_counts_path(world_dir)).await {
+        Ok(buf) => buf,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let root: IdCountsRoot = nbt::from_gzip_reader(Cursor::new(&buf))?;
+    Ok(Some(root.data.map))
+}
+
+pub async fn save_last_map_id(world_dir: &Path, id: i32) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(world_dir.join("data")).await?;
+
+    let mut buf = vec![];
+    let root = IdCountsRoot {
+        data: IdCounts { map: id },
+    };
+    nbt::to_gzip_writer(&mut buf, &root, None)?;
+
+    let mut file = tokio::fs::File::create(id_counts_path(world_dir)).await?;
+    file.write_all(&buf).await?;
+    Ok(())
+}
+
+/// Converts map data to a blob by hand, since `colors`
+/// would otherwise be written as a list rather than a byte
+/// array. See https://github.com/PistonDevelopers/hematite_nbt/issues/27.
+fn map_data_to_blob(data: &MapData) -> Blob {
+    let mut map = HashMap::new();
+    map.insert(String::from("scale"), Value::Byte(data.scale));
+    map.insert(String::from("dimension"), Value::Int(data.dimension));
+    map.insert(String::from("xCenter"), Value::Int(data.x_center));
+    map.insert(String::from("zCenter"), Value::Int(data.z_center));
+    map.insert(
+        String::from("trackingPosition"),
+        Value::Byte(data.tracking_position as i8),
+    );
+    map.insert(
+        String::from("unlimitedTracking"),
+        Value::Byte(data.unlimited_tracking as i8),
+    );
+    map.insert(
+        String::from("colors"),
+        Value::ByteArray(data.colors.clone()),
+    );
+
+    let banners = data
+        .banners
+        .iter()
+        .map(|banner| {
+            let mut pos = HashMap::new();
+            pos.insert(String::from("X"), Value::Int(banner.pos.x));
+            pos.insert(String::from("Y"), Value::Int(banner.pos.y));
+            pos.insert(String::from("Z"), Value::Int(banner.pos.z));
+
+            let mut map = HashMap::new();
+            map.insert(String::from("Pos"), Value::Compound(pos));
+            map.insert(String::from("Color"), Value::String(banner.color.clone()));
+            if let Some(name) = &banner.name {
+                map.insert(String::from("Name"), Value::String(name.clone()));
+            }
+            Value::Compound(map)
+        })
+        .collect();
+    map.insert(String::from("banners"), Value::List(banners));
+
+    let mut blob = Blob::new();
+    blob.insert("data", Value::Compound(map)).unwrap();
+    blob
+}
+
+fn map_path(world_dir: &Path, id: i32) -> PathBuf {
+    world_dir.join("data").join(format!("map_{}.dat", id))
+}
+
+fn id_counts_path(world_dir: &Path) -> PathBuf {
+    world_dir.join("data").join("idcounts.dat")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_data_roundtrip() {
+        let data = MapData {
+            scale: 2,
+            dimension: 0,
+            x_center: 64,
+            z_center: -448,
+            tracking_position: true,
+            unlimited_tracking: false,
+            banners: vec![MapBanner {
+                pos: MapBannerPos {
+                    x: 70,
+                    y: 64,
+                    z: -400,
+                },
+                color: String::from("red"),
+                name: None,
+            }],
+            colors: vec![4; MAP_SIZE * MAP_SIZE],
+        };
+
+        let mut buf = vec![];
+        map_data_to_blob(&data).to_writer(&mut buf).unwrap();
+        let root: MapRoot = nbt::from_reader(buf.as_slice()).unwrap();
+
+        assert_eq!(root.data.x_center, 64);
+        assert_eq!(root.data.z_center, -448);
+        assert!(root.data.tracking_position);
+        assert_eq!(root.data.banners, data.banners);
+        assert_eq!(root.data.colors, data.colors);
+    }
+}
@@ -3,7 +3,8 @@ use feather_inventory::{
     SlotIndex, HOTBAR_SIZE, INVENTORY_SIZE, SLOT_ARMOR_MAX, SLOT_ARMOR_MIN, SLOT_HOTBAR_OFFSET,
     SLOT_INVENTORY_OFFSET, SLOT_OFFHAND,
 };
-use feather_items::{Item, ItemStack};
+use feather_items::{Item, ItemNbt, ItemStack};
+use feather_util::BlockPosition;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tokio::io::AsyncWriteExt;
@@ -21,6 +22,74 @@ pub struct PlayerData {
     pub gamemode: i32,
     #[serde(rename = "Inventory")]
     pub inventory: Vec<InventorySlot>,
+
+    #[serde(rename = "XpLevel")]
+    #[serde(default)]
+    pub xp_level: i32,
+    /// Progress towards the next level, from 0 to 1.
+    #[serde(rename = "XpP")]
+    #[serde(default)]
+    pub xp_progress: f32,
+    #[serde(rename = "XpTotal")]
+    #[serde(default)]
+    pub xp_total: i32,
+
+    /// Position of the bed at which the player respawns, if any.
+    #[serde(rename = "SpawnX")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spawn_x: Option<i32>,
+    #[serde(rename = "SpawnY")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spawn_y: Option<i32>,
+    #[serde(rename = "SpawnZ")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spawn_z: Option<i32>,
+
+    #[serde(rename = "recipeBook")]
+    #[serde(default)]
+    pub recipe_book: PlayerRecipeBook,
+}
+
+impl PlayerData {
+    /// Returns the position of the player's bed spawn, if they have one.
+    pub fn bed_spawn(&self) -> Option<BlockPosition> {
+        Some(BlockPosition::new(
+            self.spawn_x?,
+            self.spawn_y?,
+            self.spawn_z?,
+        ))
+    }
+
+    /// Sets the position of the player's bed spawn.
+    pub fn set_bed_spawn(&mut self, spawn: Option<BlockPosition>) {
+        self.spawn_x = spawn.map(|pos| pos.x);
+        self.spawn_y = spawn.map(|pos| pos.y);
+        self.spawn_z = spawn.map(|pos| pos.z);
+    }
+}
+
+/// The recipes a player has unlocked and the
+/// state of their recipe book interface.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlayerRecipeBook {
+    #[serde(default)]
+    pub recipes: Vec<String>,
+    /// Unlocked recipes which the player has not yet viewed.
+    #[serde(rename = "toBeDisplayed")]
+    #[serde(default)]
+    pub to_be_displayed: Vec<String>,
+    #[serde(rename = "isGuiOpen")]
+    #[serde(default)]
+    pub is_gui_open: bool,
+    #[serde(rename = "isFilteringCraftable")]
+    #[serde(default)]
+    pub is_filtering_craftable: bool,
+    #[serde(rename = "isFurnaceGuiOpen")]
+    #[serde(default)]
+    pub is_furnace_gui_open: bool,
+    #[serde(rename = "isFurnaceFilteringCraftable")]
+    #[serde(default)]
+    pub is_furnace_filtering_craftable: bool,
 }
 
 /// Represents a single inventory slot (including position index).
@@ -32,20 +101,28 @@ pub struct InventorySlot {
     pub slot: i8,
     #[serde(rename = "id")]
     pub item: String,
+    #[serde(rename = "tag")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nbt: Option<ItemNbt>,
 }
 
 impl InventorySlot {
     /// Converts a slot to an ItemStack.
     pub fn to_stack(&self) -> ItemStack {
-        ItemStack {
-            ty: Item::from_identifier(self.item.as_str()).unwrap_or(Item::Air),
-            amount: self.count as u8,
+        let stack = ItemStack::new(
+            Item::from_identifier(self.item.as_str()).unwrap_or(Item::Air),
+            self.count as u8,
+        );
+        match &self.nbt {
+            Some(nbt) => stack.with_nbt(nbt.clone()),
+            None => stack,
         }
     }
 
     /// Converts a network protocol index, item, and count
     /// to an `InventorySlot`.
-    pub fn from_network_index(network: SlotIndex, stack: ItemStack) -> Self {
+    pub fn from_network_index(network: SlotIndex, stack: &ItemStack) -> Self {
         let slot = if SLOT_HOTBAR_OFFSET <= network && network < SLOT_HOTBAR_OFFSET + HOTBAR_SIZE {
             // Hotbar
             (network - SLOT_HOTBAR_OFFSET) as i8
@@ -65,6 +142,7 @@ impl InventorySlot {
             count: stack.amount as i8,
             slot,
             item: stack.ty.identifier().to_string(),
+            nbt: stack.nbt().cloned(),
         }
     }
 
@@ -149,6 +227,7 @@ mod tests {
             count: 1,
             slot: 2,
             item: String::from(Item::Feather.identifier()),
+            nbt: None,
         };
 
         let item_stack = slot.to_stack();
@@ -156,12 +235,28 @@ mod tests {
         assert_eq!(item_stack.amount, 1);
     }
 
+    #[test]
+    fn test_convert_item_nbt() {
+        let mut stack = ItemStack::new(Item::IronPickaxe, 1);
+        stack.set_damage(20);
+        stack.set_display_name(Some(String::from("{\"text\":\"Digger\"}")));
+
+        let slot = InventorySlot::from_network_index(SLOT_HOTBAR_OFFSET, &stack);
+        assert_eq!(slot.to_stack(), stack);
+
+        let mut buf = vec![];
+        nbt::to_writer(&mut buf, &slot, None).unwrap();
+        let read: InventorySlot = nbt::from_reader(buf.as_slice()).unwrap();
+        assert_eq!(read, slot);
+    }
+
     #[test]
     fn test_convert_item_unknown_type() {
         let slot = InventorySlot {
             count: 1,
             slot: 2,
             item: String::from("invalid:identifier"),
+            nbt: None,
         };
 
         let item_stack = slot.to_stack();
@@ -195,10 +290,11 @@ mod tests {
                 slot: src,
                 count: 1,
                 item: String::from(Item::Stone.identifier()),
+                nbt: None,
             };
             assert_eq!(slot.convert_index().unwrap(), expected);
             assert_eq!(
-                InventorySlot::from_network_index(expected, ItemStack::new(Item::Stone, 1)),
+                InventorySlot::from_network_index(expected, &ItemStack::new(Item::Stone, 1)),
                 slot
             );
         }
@@ -209,6 +305,7 @@ mod tests {
                 slot: *invalid_slot as i8,
                 count: 1,
                 item: String::from("invalid:identifier"),
+                nbt: None,
             };
             assert!(slot.convert_index().is_none());
         }
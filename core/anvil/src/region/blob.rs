@@ -32,7 +32,12 @@ fn level_to_value(level: ChunkLevel) -> Value {
     let sections = level.sections.into_iter().map(section_to_value).collect();
     map.insert(String::from("Sections"), Value::List(sections));
 
-    map.insert(String::from("TileEntities"), Value::List(vec![])); // TODO
+    let block_entities = level
+        .block_entities
+        .into_iter()
+        .map(|block_entity| block_entity.into_nbt_value())
+        .collect();
+    map.insert(String::from("TileEntities"), Value::List(block_entities));
     map.insert(String::from("ToBeTicked"), Value::List(vec![])); // TODO
 
     let mut liquids_to_be_ticked = vec![];
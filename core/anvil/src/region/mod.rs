@@ -1,6 +1,7 @@
 //! This module implements the loading and saving
 //! of Anvil region files.
 
+use crate::block_entity::BlockEntityData;
 use crate::entity::EntityData;
 use bitvec::{bitvec, vec::BitVec};
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
@@ -57,6 +58,8 @@ pub struct ChunkLevel {
     biomes: Vec<i32>,
     #[serde(rename = "Entities")]
     entities: Vec<EntityData>,
+    #[serde(rename = "TileEntities", default)]
+    block_entities: Vec<BlockEntityData>,
     #[serde(rename = "Heightmaps")]
     heightmaps: Vec<i64>,
 }
@@ -125,7 +128,7 @@ impl RegionHandle {
     pub fn load_chunk(
         &mut self,
         mut pos: ChunkPosition,
-    ) -> Result<(Chunk, Vec<EntityData>), Error> {
+    ) -> Result<(Chunk, Vec<EntityData>, Vec<BlockEntityData>), Error> {
         // Get a copy of the original position before clipping
         let original_pos = pos;
         // Clip chunk position to region-local coordinates.
@@ -208,7 +211,14 @@ impl RegionHandle {
 
         chunk.recalculate_heightmap();
 
-        Ok((chunk, level.entities.to_vec()))
+        let block_entities = level
+            .block_entities
+            .iter()
+            .filter(|block_entity| **block_entity != BlockEntityData::Unknown)
+            .cloned()
+            .collect();
+
+        Ok((chunk, level.entities.to_vec(), block_entities))
     }
 
     /// Saves the given chunk to this region file. The header will be updated
@@ -216,7 +226,12 @@ impl RegionHandle {
     ///
     /// Behavior may be unexpected if this region file does not contain the given
     /// chunk position.
-    pub fn save_chunk(&mut self, chunk: &Chunk, entities: Vec<EntityData>) -> Result<(), Error> {
+    pub fn save_chunk(
+        &mut self,
+        chunk: &Chunk,
+        entities: Vec<EntityData>,
+        block_entities: Vec<BlockEntityData>,
+    ) -> Result<(), Error> {
         let chunk_pos = chunk.position();
 
         let (local_x, local_z) = (chunk_pos.x % 32, chunk_pos.z % 32);
@@ -230,7 +245,7 @@ impl RegionHandle {
         }
 
         // Write chunk to `ChunkRoot` tag.
-        let root = chunk_to_chunk_root(chunk, entities);
+        let root = chunk_to_chunk_root(chunk, entities, block_entities);
 
         let blob = blob::chunk_root_to_blob(root);
 
@@ -352,7 +367,11 @@ fn read_section_into_chunk(section: &LevelSection, chunk: &mut Chunk) -> Result<
     Ok(())
 }
 
-fn chunk_to_chunk_root(chunk: &Chunk, entities: Vec<EntityData>) -> ChunkRoot {
+fn chunk_to_chunk_root(
+    chunk: &Chunk,
+    entities: Vec<EntityData>,
+    block_entities: Vec<BlockEntityData>,
+) -> ChunkRoot {
     let heightmaps: Vec<i64> = chunk
         .heightmaps()
         .iter()
@@ -391,6 +410,7 @@ fn chunk_to_chunk_root(chunk: &Chunk, entities: Vec<EntityData>) -> ChunkRoot {
                 .map(|biome| biome.protocol_id())
                 .collect(),
             entities,
+            block_entities,
             heightmaps,
         },
         data_version: DATA_VERSION,
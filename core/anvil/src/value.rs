@@ -0,0 +1,480 @@
+//! Conversion of serializable types to NBT values, for
+//! data which is written into hand-built compounds, such
+//! as the tags of items inside entities and block entities.
+
+use nbt::Value;
+use serde::ser::{self, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use thiserror::Error;
+
+/// An error converting a type to an NBT value.
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub struct ValueError(String);
+
+impl ser::Error for ValueError {
+    fn custom<T: Display>(msg: T) -> Self {
+        ValueError(msg.to_string())
+    }
+}
+
+/// Converts a value to NBT through its `Serialize` implementation.
+///
+/// Structs and maps become compounds, whose `None` fields are left
+/// out. Non-empty sequences of bytes, ints or longs become arrays,
+/// as NBT stores them that way; other sequences become lists.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, ValueError> {
+    value
+        .serialize(ValueSerializer)?
+        .ok_or_else(|| ValueError(String::from("no value to convert")))
+}
+
+fn unsupported(what: &str) -> ValueError {
+    ValueError(format!("{} cannot be converted to NBT", what))
+}
+
+/// Produces a value, or `None` for absent ones
+/// such as `None` fields, which are skipped.
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Option<Value>;
+    type Error = ValueError;
+
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = ser::Impossible<Option<Value>, ValueError>;
+    type SerializeMap = CompoundSerializer;
+    type SerializeStruct = CompoundSerializer;
+    type SerializeStructVariant = ser::Impossible<Option<Value>, ValueError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::Byte(v as i8)))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::Byte(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::Short(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::Int(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::Long(v)))
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("an unsigned integer"))
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("an unsigned integer"))
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("an unsigned integer"))
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("an unsigned integer"))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::Float(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::Double(v)))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::String(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::String(v.to_owned())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::ByteArray(v.iter().map(|&b| b as i8).collect())))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::String(variant.to_owned())))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("an enum variant with data"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SeqSerializer(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(unsupported("an enum variant with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(CompoundSerializer::default())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(CompoundSerializer::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(unsupported("an enum variant with data"))
+    }
+}
+
+struct SeqSerializer(Vec<Value>);
+
+impl SeqSerializer {
+    fn finish(self) -> Value {
+        let values = self.0;
+        let array = match values.first() {
+            Some(Value::Byte(_)) => array_of(&values, |value| match value {
+                Value::Byte(x) => Some(*x),
+                _ => None,
+            })
+            .map(Value::ByteArray),
+            Some(Value::Int(_)) => array_of(&values, |value| match value {
+                Value::Int(x) => Some(*x),
+                _ => None,
+            })
+            .map(Value::IntArray),
+            Some(Value::Long(_)) => array_of(&values, |value| match value {
+                Value::Long(x) => Some(*x),
+                _ => None,
+            })
+            .map(Value::LongArray),
+            _ => None,
+        };
+        array.unwrap_or(Value::List(values))
+    }
+}
+
+/// Returns the elements of `values` if `element`
+/// accepts all of them.
+fn array_of<T>(values: &[Value], element: impl Fn(&Value) -> Option<T>) -> Option<Vec<T>> {
+    values.iter().map(element).collect()
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Option<Value>;
+    type Error = ValueError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.0.extend(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(self.finish()))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Option<Value>;
+    type Error = ValueError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Option<Value>;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+#[derive(Default)]
+struct CompoundSerializer {
+    map: HashMap<String, Value>,
+    key: Option<String>,
+}
+
+impl CompoundSerializer {
+    fn insert(&mut self, key: String, value: Option<Value>) {
+        if let Some(value) = value {
+            self.map.insert(key, value);
+        }
+    }
+}
+
+impl ser::SerializeMap for CompoundSerializer {
+    type Ok = Option<Value>;
+    type Error = ValueError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ValueError> {
+        match key.serialize(ValueSerializer)? {
+            Some(Value::String(key)) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(unsupported("a compound key which is not a string")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ValueError(String::from("value serialized before its key")))?;
+        let value = value.serialize(ValueSerializer)?;
+        self.insert(key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::Compound(self.map)))
+    }
+}
+
+impl ser::SerializeStruct for CompoundSerializer {
+    type Ok = Option<Value>;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ValueError> {
+        let value = value.serialize(ValueSerializer)?;
+        self.insert(key.to_owned(), value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Value::Compound(self.map)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_items::{
+        EntityTagNbt, FireworkExplosionNbt, FireworksNbt, FoodEffectNbt, ItemNbt, Tag,
+    };
+
+    /// Writes a value to NBT bytes and reads it back through serde.
+    fn write_and_read<T: serde::de::DeserializeOwned>(value: Value) -> T {
+        let mut blob = nbt::Blob::new();
+        match value {
+            Value::Compound(map) => {
+                for (name, value) in map {
+                    blob.insert(name, value).unwrap();
+                }
+            }
+            value => panic!("expected compound, got {:?}", value),
+        }
+        let mut buf = vec![];
+        blob.to_writer(&mut buf).unwrap();
+        nbt::from_reader(buf.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn item_nbt_roundtrip() {
+        let mut nbt = ItemNbt {
+            damage: 12,
+            unbreakable: true,
+            can_destroy: vec![String::from("minecraft:stone")],
+            ..Default::default()
+        };
+        nbt.other.insert(String::from("RepairCost"), Tag::Int(2));
+
+        let value = to_value(&nbt).unwrap();
+        assert_eq!(write_and_read::<ItemNbt>(value), nbt);
+    }
+
+    #[test]
+    fn effects_roundtrip() {
+        let nbt = ItemNbt {
+            effects: vec![
+                FoodEffectNbt {
+                    id: 16,
+                    duration: 200,
+                    amplifier: 0,
+                },
+                FoodEffectNbt {
+                    id: 1,
+                    duration: 160,
+                    amplifier: 1,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let value = to_value(&nbt).unwrap();
+        assert_eq!(write_and_read::<ItemNbt>(value), nbt);
+    }
+
+    #[test]
+    fn map_roundtrip() {
+        let nbt = ItemNbt {
+            map: Some(7),
+            map_scale_direction: 1,
+            ..Default::default()
+        };
+
+        let value = to_value(&nbt).unwrap();
+        assert_eq!(write_and_read::<ItemNbt>(value), nbt);
+    }
+
+    #[test]
+    fn book_roundtrip() {
+        let nbt = ItemNbt {
+            pages: vec![
+                String::from(r#"{"text":"first"}"#),
+                String::from(r#"{"text":"second"}"#),
+            ],
+            title: Some(String::from("Notes")),
+            author: Some(String::from("Alex")),
+            generation: 1,
+            resolved: true,
+            ..Default::default()
+        };
+
+        let value = to_value(&nbt).unwrap();
+        assert_eq!(write_and_read::<ItemNbt>(value), nbt);
+    }
+
+    #[test]
+    fn fireworks_roundtrip() {
+        let nbt = ItemNbt {
+            fireworks: Some(FireworksNbt {
+                flight: 2,
+                explosions: vec![FireworkExplosionNbt {
+                    ty: 1,
+                    colors: vec![0xFF0000, 0x00FF00],
+                    fade_colors: vec![0x0000FF],
+                    flicker: true,
+                    trail: false,
+                }],
+            }),
+            ..Default::default()
+        };
+
+        let value = to_value(&nbt).unwrap();
+        assert_eq!(write_and_read::<ItemNbt>(value), nbt);
+    }
+
+    #[test]
+    fn entity_tag_roundtrip() {
+        let nbt = ItemNbt {
+            entity_tag: Some(EntityTagNbt {
+                id: Some(String::from("minecraft:zombie_horse")),
+                custom_name: Some(String::from(r#"{"text":"Bones"}"#)),
+                custom_name_visible: true,
+                silent: true,
+            }),
+            ..Default::default()
+        };
+
+        let value = to_value(&nbt).unwrap();
+        assert_eq!(write_and_read::<ItemNbt>(value), nbt);
+    }
+
+    #[test]
+    fn none_is_left_out() {
+        let value = to_value(&ItemNbt::default()).unwrap();
+        assert_eq!(value, Value::Compound(HashMap::new()));
+    }
+
+    #[test]
+    fn number_sequences_become_arrays() {
+        assert_eq!(
+            to_value(&vec![1i32, 2, 3]).unwrap(),
+            Value::IntArray(vec![1, 2, 3])
+        );
+        assert_eq!(to_value(&vec![4i64]).unwrap(), Value::LongArray(vec![4]));
+        assert_eq!(
+            to_value(&vec![1i16]).unwrap(),
+            Value::List(vec![Value::Short(1)])
+        );
+        assert_eq!(to_value(&Vec::<i32>::new()).unwrap(), Value::List(vec![]));
+    }
+}
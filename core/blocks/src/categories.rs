@@ -125,6 +125,19 @@ impl BlockId {
         }
     }
 
+    /// Returns whether entities inside this block are in water.
+    pub fn is_water(self) -> bool {
+        match self.kind() {
+            BlockKind::Water
+            | BlockKind::BubbleColumn
+            | BlockKind::Kelp
+            | BlockKind::KelpPlant
+            | BlockKind::Seagrass
+            | BlockKind::TallSeagrass => true,
+            _ => self.waterlogged().unwrap_or(false),
+        }
+    }
+
     pub fn is_leaves(self) -> bool {
         match self.kind() {
             BlockKind::AcaciaLeaves
@@ -168,4 +181,530 @@ impl BlockId {
             _ => 0,
         }
     }
+
+    /// Returns the blast resistance of this block, which
+    /// determines how well it withstands explosions.
+    pub fn blast_resistance(self) -> f32 {
+        // TODO: there are likely a few missing in this list
+        match self.kind() {
+            BlockKind::Bedrock
+            | BlockKind::CommandBlock
+            | BlockKind::RepeatingCommandBlock
+            | BlockKind::ChainCommandBlock
+            | BlockKind::Barrier
+            | BlockKind::EndPortal
+            | BlockKind::EndPortalFrame
+            | BlockKind::EndGateway
+            | BlockKind::StructureBlock => 3600000.0,
+            BlockKind::Obsidian
+            | BlockKind::EnchantingTable
+            | BlockKind::Anvil
+            | BlockKind::ChippedAnvil
+            | BlockKind::DamagedAnvil => 1200.0,
+            BlockKind::EnderChest => 600.0,
+            BlockKind::Water | BlockKind::Lava | BlockKind::BubbleColumn => 100.0,
+            BlockKind::EndStone | BlockKind::EndStoneBricks | BlockKind::DragonEgg => 9.0,
+            BlockKind::Stone
+            | BlockKind::Granite
+            | BlockKind::PolishedGranite
+            | BlockKind::Diorite
+            | BlockKind::PolishedDiorite
+            | BlockKind::Andesite
+            | BlockKind::PolishedAndesite
+            | BlockKind::Cobblestone
+            | BlockKind::MossyCobblestone
+            | BlockKind::Bricks
+            | BlockKind::StoneBricks
+            | BlockKind::MossyStoneBricks
+            | BlockKind::CrackedStoneBricks
+            | BlockKind::ChiseledStoneBricks
+            | BlockKind::NetherBricks
+            | BlockKind::RedNetherBricks
+            | BlockKind::NetherBrickFence
+            | BlockKind::NetherBrickStairs
+            | BlockKind::NetherBrickSlab
+            | BlockKind::CobblestoneStairs
+            | BlockKind::BrickStairs
+            | BlockKind::StoneBrickStairs
+            | BlockKind::CobblestoneSlab
+            | BlockKind::BrickSlab
+            | BlockKind::StoneBrickSlab
+            | BlockKind::StoneSlab
+            | BlockKind::SmoothStone
+            | BlockKind::CobblestoneWall
+            | BlockKind::MossyCobblestoneWall
+            | BlockKind::IronBlock
+            | BlockKind::GoldBlock
+            | BlockKind::DiamondBlock
+            | BlockKind::EmeraldBlock
+            | BlockKind::CoalBlock
+            | BlockKind::RedstoneBlock
+            | BlockKind::IronBars
+            | BlockKind::Jukebox
+            | BlockKind::Prismarine
+            | BlockKind::PrismarineBricks
+            | BlockKind::DarkPrismarine
+            | BlockKind::PrismarineStairs
+            | BlockKind::PrismarineBrickStairs
+            | BlockKind::DarkPrismarineStairs
+            | BlockKind::PrismarineSlab
+            | BlockKind::PrismarineBrickSlab
+            | BlockKind::DarkPrismarineSlab
+            | BlockKind::PurpurBlock
+            | BlockKind::PurpurPillar
+            | BlockKind::PurpurStairs
+            | BlockKind::PurpurSlab
+            | BlockKind::DeadTubeCoralBlock
+            | BlockKind::DeadBrainCoralBlock
+            | BlockKind::DeadBubbleCoralBlock
+            | BlockKind::DeadFireCoralBlock
+            | BlockKind::DeadHornCoralBlock
+            | BlockKind::TubeCoralBlock
+            | BlockKind::BrainCoralBlock
+            | BlockKind::BubbleCoralBlock
+            | BlockKind::FireCoralBlock
+            | BlockKind::HornCoralBlock => 6.0,
+            BlockKind::IronDoor | BlockKind::IronTrapdoor | BlockKind::Spawner => 5.0,
+            BlockKind::Hopper => 4.8,
+            BlockKind::Terracotta
+            | BlockKind::WhiteTerracotta
+            | BlockKind::OrangeTerracotta
+            | BlockKind::MagentaTerracotta
+            | BlockKind::LightBlueTerracotta
+            | BlockKind::YellowTerracotta
+            | BlockKind::LimeTerracotta
+            | BlockKind::PinkTerracotta
+            | BlockKind::GrayTerracotta
+            | BlockKind::LightGrayTerracotta
+            | BlockKind::CyanTerracotta
+            | BlockKind::PurpleTerracotta
+            | BlockKind::BlueTerracotta
+            | BlockKind::BrownTerracotta
+            | BlockKind::GreenTerracotta
+            | BlockKind::RedTerracotta
+            | BlockKind::BlackTerracotta => 4.2,
+            BlockKind::Dispenser
+            | BlockKind::Dropper
+            | BlockKind::Furnace
+            | BlockKind::Observer => 3.5,
+            BlockKind::GoldOre
+            | BlockKind::IronOre
+            | BlockKind::CoalOre
+            | BlockKind::LapisOre
+            | BlockKind::DiamondOre
+            | BlockKind::EmeraldOre
+            | BlockKind::RedstoneOre
+            | BlockKind::NetherQuartzOre
+            | BlockKind::LapisBlock
+            | BlockKind::Beacon
+            | BlockKind::Conduit
+            | BlockKind::Cocoa
+            | BlockKind::PetrifiedOakSlab
+            | BlockKind::OakPlanks
+            | BlockKind::SprucePlanks
+            | BlockKind::BirchPlanks
+            | BlockKind::JunglePlanks
+            | BlockKind::AcaciaPlanks
+            | BlockKind::DarkOakPlanks
+            | BlockKind::OakStairs
+            | BlockKind::SpruceStairs
+            | BlockKind::BirchStairs
+            | BlockKind::JungleStairs
+            | BlockKind::AcaciaStairs
+            | BlockKind::DarkOakStairs
+            | BlockKind::OakSlab
+            | BlockKind::SpruceSlab
+            | BlockKind::BirchSlab
+            | BlockKind::JungleSlab
+            | BlockKind::AcaciaSlab
+            | BlockKind::DarkOakSlab
+            | BlockKind::OakFenceGate
+            | BlockKind::SpruceFenceGate
+            | BlockKind::BirchFenceGate
+            | BlockKind::JungleFenceGate
+            | BlockKind::AcaciaFenceGate
+            | BlockKind::DarkOakFenceGate
+            | BlockKind::OakFence
+            | BlockKind::SpruceFence
+            | BlockKind::BirchFence
+            | BlockKind::JungleFence
+            | BlockKind::AcaciaFence
+            | BlockKind::DarkOakFence
+            | BlockKind::OakDoor
+            | BlockKind::SpruceDoor
+            | BlockKind::BirchDoor
+            | BlockKind::JungleDoor
+            | BlockKind::AcaciaDoor
+            | BlockKind::DarkOakDoor
+            | BlockKind::OakTrapdoor
+            | BlockKind::SpruceTrapdoor
+            | BlockKind::BirchTrapdoor
+            | BlockKind::JungleTrapdoor
+            | BlockKind::AcaciaTrapdoor
+            | BlockKind::DarkOakTrapdoor => 3.0,
+            BlockKind::Chest | BlockKind::TrappedChest | BlockKind::CraftingTable => 2.5,
+            BlockKind::Cauldron
+            | BlockKind::BoneBlock
+            | BlockKind::OakLog
+            | BlockKind::SpruceLog
+            | BlockKind::BirchLog
+            | BlockKind::JungleLog
+            | BlockKind::AcaciaLog
+            | BlockKind::DarkOakLog
+            | BlockKind::StrippedSpruceLog
+            | BlockKind::StrippedBirchLog
+            | BlockKind::StrippedJungleLog
+            | BlockKind::StrippedAcaciaLog
+            | BlockKind::StrippedDarkOakLog
+            | BlockKind::StrippedOakLog
+            | BlockKind::OakWood
+            | BlockKind::SpruceWood
+            | BlockKind::BirchWood
+            | BlockKind::JungleWood
+            | BlockKind::AcaciaWood
+            | BlockKind::DarkOakWood
+            | BlockKind::StrippedOakWood
+            | BlockKind::StrippedSpruceWood
+            | BlockKind::StrippedBirchWood
+            | BlockKind::StrippedJungleWood
+            | BlockKind::StrippedAcaciaWood
+            | BlockKind::StrippedDarkOakWood
+            | BlockKind::ShulkerBox
+            | BlockKind::WhiteShulkerBox
+            | BlockKind::OrangeShulkerBox
+            | BlockKind::MagentaShulkerBox
+            | BlockKind::LightBlueShulkerBox
+            | BlockKind::YellowShulkerBox
+            | BlockKind::LimeShulkerBox
+            | BlockKind::PinkShulkerBox
+            | BlockKind::GrayShulkerBox
+            | BlockKind::LightGrayShulkerBox
+            | BlockKind::CyanShulkerBox
+            | BlockKind::PurpleShulkerBox
+            | BlockKind::BlueShulkerBox
+            | BlockKind::BrownShulkerBox
+            | BlockKind::GreenShulkerBox
+            | BlockKind::RedShulkerBox
+            | BlockKind::BlackShulkerBox => 2.0,
+            BlockKind::WhiteConcrete
+            | BlockKind::OrangeConcrete
+            | BlockKind::MagentaConcrete
+            | BlockKind::LightBlueConcrete
+            | BlockKind::YellowConcrete
+            | BlockKind::LimeConcrete
+            | BlockKind::PinkConcrete
+            | BlockKind::GrayConcrete
+            | BlockKind::LightGrayConcrete
+            | BlockKind::CyanConcrete
+            | BlockKind::PurpleConcrete
+            | BlockKind::BlueConcrete
+            | BlockKind::BrownConcrete
+            | BlockKind::GreenConcrete
+            | BlockKind::RedConcrete
+            | BlockKind::BlackConcrete => 1.8,
+            BlockKind::Bookshelf => 1.5,
+            BlockKind::WhiteGlazedTerracotta
+            | BlockKind::OrangeGlazedTerracotta
+            | BlockKind::MagentaGlazedTerracotta
+            | BlockKind::LightBlueGlazedTerracotta
+            | BlockKind::YellowGlazedTerracotta
+            | BlockKind::LimeGlazedTerracotta
+            | BlockKind::PinkGlazedTerracotta
+            | BlockKind::GrayGlazedTerracotta
+            | BlockKind::LightGrayGlazedTerracotta
+            | BlockKind::CyanGlazedTerracotta
+            | BlockKind::PurpleGlazedTerracotta
+            | BlockKind::BlueGlazedTerracotta
+            | BlockKind::BrownGlazedTerracotta
+            | BlockKind::GreenGlazedTerracotta
+            | BlockKind::RedGlazedTerracotta
+            | BlockKind::BlackGlazedTerracotta => 1.4,
+            BlockKind::Pumpkin
+            | BlockKind::CarvedPumpkin
+            | BlockKind::JackOLantern
+            | BlockKind::Melon
+            | BlockKind::NetherWartBlock => 1.0,
+            BlockKind::Sandstone
+            | BlockKind::ChiseledSandstone
+            | BlockKind::CutSandstone
+            | BlockKind::SandstoneStairs
+            | BlockKind::SandstoneSlab
+            | BlockKind::SmoothSandstone
+            | BlockKind::RedSandstone
+            | BlockKind::ChiseledRedSandstone
+            | BlockKind::CutRedSandstone
+            | BlockKind::RedSandstoneStairs
+            | BlockKind::RedSandstoneSlab
+            | BlockKind::SmoothRedSandstone
+            | BlockKind::QuartzBlock
+            | BlockKind::ChiseledQuartzBlock
+            | BlockKind::QuartzPillar
+            | BlockKind::QuartzStairs
+            | BlockKind::QuartzSlab
+            | BlockKind::SmoothQuartz
+            | BlockKind::NoteBlock
+            | BlockKind::WhiteWool
+            | BlockKind::OrangeWool
+            | BlockKind::MagentaWool
+            | BlockKind::LightBlueWool
+            | BlockKind::YellowWool
+            | BlockKind::LimeWool
+            | BlockKind::PinkWool
+            | BlockKind::GrayWool
+            | BlockKind::LightGrayWool
+            | BlockKind::CyanWool
+            | BlockKind::PurpleWool
+            | BlockKind::BlueWool
+            | BlockKind::BrownWool
+            | BlockKind::GreenWool
+            | BlockKind::RedWool
+            | BlockKind::BlackWool => 0.8,
+            BlockKind::GrassPath => 0.65,
+            BlockKind::GrassBlock
+            | BlockKind::Gravel
+            | BlockKind::Farmland
+            | BlockKind::Clay
+            | BlockKind::Mycelium
+            | BlockKind::Sponge
+            | BlockKind::WetSponge => 0.6,
+            BlockKind::Dirt
+            | BlockKind::CoarseDirt
+            | BlockKind::Podzol
+            | BlockKind::Sand
+            | BlockKind::RedSand
+            | BlockKind::SoulSand
+            | BlockKind::Ice
+            | BlockKind::PackedIce
+            | BlockKind::FrostedIce
+            | BlockKind::Cake
+            | BlockKind::HayBlock
+            | BlockKind::MagmaBlock
+            | BlockKind::WhiteConcretePowder
+            | BlockKind::OrangeConcretePowder
+            | BlockKind::MagentaConcretePowder
+            | BlockKind::LightBlueConcretePowder
+            | BlockKind::YellowConcretePowder
+            | BlockKind::LimeConcretePowder
+            | BlockKind::PinkConcretePowder
+            | BlockKind::GrayConcretePowder
+            | BlockKind::LightGrayConcretePowder
+            | BlockKind::CyanConcretePowder
+            | BlockKind::PurpleConcretePowder
+            | BlockKind::BlueConcretePowder
+            | BlockKind::BrownConcretePowder
+            | BlockKind::GreenConcretePowder
+            | BlockKind::RedConcretePowder
+            | BlockKind::BlackConcretePowder
+            | BlockKind::StoneButton
+            | BlockKind::OakButton
+            | BlockKind::SpruceButton
+            | BlockKind::BirchButton
+            | BlockKind::JungleButton
+            | BlockKind::AcaciaButton
+            | BlockKind::DarkOakButton => 0.5,
+            BlockKind::Netherrack | BlockKind::Cactus => 0.4,
+            BlockKind::Glass
+            | BlockKind::GlassPane
+            | BlockKind::Glowstone
+            | BlockKind::SeaLantern
+            | BlockKind::RedstoneLamp
+            | BlockKind::WhiteStainedGlassPane
+            | BlockKind::OrangeStainedGlassPane
+            | BlockKind::MagentaStainedGlassPane
+            | BlockKind::LightBlueStainedGlassPane
+            | BlockKind::YellowStainedGlassPane
+            | BlockKind::LimeStainedGlassPane
+            | BlockKind::PinkStainedGlassPane
+            | BlockKind::GrayStainedGlassPane
+            | BlockKind::LightGrayStainedGlassPane
+            | BlockKind::CyanStainedGlassPane
+            | BlockKind::PurpleStainedGlassPane
+            | BlockKind::BlueStainedGlassPane
+            | BlockKind::BrownStainedGlassPane
+            | BlockKind::GreenStainedGlassPane
+            | BlockKind::RedStainedGlassPane
+            | BlockKind::BlackStainedGlassPane
+            | BlockKind::WhiteStainedGlass
+            | BlockKind::OrangeStainedGlass
+            | BlockKind::MagentaStainedGlass
+            | BlockKind::LightBlueStainedGlass
+            | BlockKind::YellowStainedGlass
+            | BlockKind::LimeStainedGlass
+            | BlockKind::PinkStainedGlass
+            | BlockKind::GrayStainedGlass
+            | BlockKind::LightGrayStainedGlass
+            | BlockKind::CyanStainedGlass
+            | BlockKind::PurpleStainedGlass
+            | BlockKind::BlueStainedGlass
+            | BlockKind::BrownStainedGlass
+            | BlockKind::GreenStainedGlass
+            | BlockKind::RedStainedGlass
+            | BlockKind::BlackStainedGlass => 0.3,
+            BlockKind::SnowBlock
+            | BlockKind::WhiteBed
+            | BlockKind::OrangeBed
+            | BlockKind::MagentaBed
+            | BlockKind::LightBlueBed
+            | BlockKind::YellowBed
+            | BlockKind::LimeBed
+            | BlockKind::PinkBed
+            | BlockKind::GrayBed
+            | BlockKind::LightGrayBed
+            | BlockKind::CyanBed
+            | BlockKind::PurpleBed
+            | BlockKind::BlueBed
+            | BlockKind::BrownBed
+            | BlockKind::GreenBed
+            | BlockKind::RedBed
+            | BlockKind::BlackBed
+            | BlockKind::OakLeaves
+            | BlockKind::SpruceLeaves
+            | BlockKind::BirchLeaves
+            | BlockKind::JungleLeaves
+            | BlockKind::AcaciaLeaves
+            | BlockKind::DarkOakLeaves => 0.2,
+            BlockKind::Snow
+            | BlockKind::WhiteCarpet
+            | BlockKind::OrangeCarpet
+            | BlockKind::MagentaCarpet
+            | BlockKind::LightBlueCarpet
+            | BlockKind::YellowCarpet
+            | BlockKind::LimeCarpet
+            | BlockKind::PinkCarpet
+            | BlockKind::GrayCarpet
+            | BlockKind::LightGrayCarpet
+            | BlockKind::CyanCarpet
+            | BlockKind::PurpleCarpet
+            | BlockKind::BlueCarpet
+            | BlockKind::BrownCarpet
+            | BlockKind::GreenCarpet
+            | BlockKind::RedCarpet
+            | BlockKind::BlackCarpet => 0.1,
+            _ => {
+                if self.is_solid() {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// Returns the hardness of this block, which determines how
+    /// long it takes to break. Blocks which cannot be broken,
+    /// such as bedrock, have a hardness of -1.
+    ///
+    /// Most blocks which withstand explosions poorly are as hard
+    /// as they are resistant to explosions, so only the blocks
+    /// for which this is not the case are listed.
+    pub fn hardness(self) -> f32 {
+        // TODO: there are likely a few missing in this list
+        let identifier = self.identifier();
+        match self.kind() {
+            BlockKind::Bedrock
+            | BlockKind::CommandBlock
+            | BlockKind::RepeatingCommandBlock
+            | BlockKind::ChainCommandBlock
+            | BlockKind::Barrier
+            | BlockKind::EndPortal
+            | BlockKind::EndPortalFrame
+            | BlockKind::EndGateway
+            | BlockKind::StructureBlock
+            | BlockKind::NetherPortal => -1.0,
+            BlockKind::Obsidian => 50.0,
+            BlockKind::EnderChest => 22.5,
+            BlockKind::Anvil
+            | BlockKind::ChippedAnvil
+            | BlockKind::DamagedAnvil
+            | BlockKind::EnchantingTable
+            | BlockKind::IronBlock
+            | BlockKind::DiamondBlock
+            | BlockKind::EmeraldBlock
+            | BlockKind::CoalBlock
+            | BlockKind::RedstoneBlock
+            | BlockKind::IronBars => 5.0,
+            BlockKind::Cobweb => 4.0,
+            BlockKind::GoldBlock
+            | BlockKind::EndStone
+            | BlockKind::EndStoneBricks
+            | BlockKind::DragonEgg
+            | BlockKind::Hopper
+            | BlockKind::Observer => 3.0,
+            BlockKind::Cobblestone
+            | BlockKind::MossyCobblestone
+            | BlockKind::CobblestoneStairs
+            | BlockKind::CobblestoneWall
+            | BlockKind::MossyCobblestoneWall
+            | BlockKind::Bricks
+            | BlockKind::BrickStairs
+            | BlockKind::NetherBricks
+            | BlockKind::RedNetherBricks
+            | BlockKind::NetherBrickStairs
+            | BlockKind::SmoothStone
+            | BlockKind::Jukebox
+            | BlockKind::OakStairs
+            | BlockKind::SpruceStairs
+            | BlockKind::BirchStairs
+            | BlockKind::JungleStairs
+            | BlockKind::AcaciaStairs
+            | BlockKind::DarkOakStairs => 2.0,
+            BlockKind::Stone
+            | BlockKind::Granite
+            | BlockKind::PolishedGranite
+            | BlockKind::Diorite
+            | BlockKind::PolishedDiorite
+            | BlockKind::Andesite
+            | BlockKind::PolishedAndesite
+            | BlockKind::StoneBricks
+            | BlockKind::MossyStoneBricks
+            | BlockKind::CrackedStoneBricks
+            | BlockKind::ChiseledStoneBricks
+            | BlockKind::StoneBrickStairs
+            | BlockKind::Prismarine
+            | BlockKind::PrismarineBricks
+            | BlockKind::DarkPrismarine
+            | BlockKind::PrismarineStairs
+            | BlockKind::PrismarineBrickStairs
+            | BlockKind::DarkPrismarineStairs
+            | BlockKind::PrismarineSlab
+            | BlockKind::PrismarineBrickSlab
+            | BlockKind::DarkPrismarineSlab
+            | BlockKind::PurpurBlock
+            | BlockKind::PurpurPillar
+            | BlockKind::PurpurStairs => 1.5,
+            BlockKind::Sign | BlockKind::WallSign => 1.0,
+            BlockKind::Rail
+            | BlockKind::PoweredRail
+            | BlockKind::DetectorRail
+            | BlockKind::ActivatorRail => 0.7,
+            BlockKind::Lever
+            | BlockKind::BrewingStand
+            | BlockKind::StonePressurePlate
+            | BlockKind::LightWeightedPressurePlate
+            | BlockKind::HeavyWeightedPressurePlate => 0.5,
+            BlockKind::Ladder => 0.4,
+            BlockKind::Cocoa
+            | BlockKind::Vine
+            | BlockKind::DaylightDetector
+            | BlockKind::BrownMushroomBlock
+            | BlockKind::RedMushroomBlock
+            | BlockKind::MushroomStem => 0.2,
+            BlockKind::Tnt | BlockKind::SlimeBlock | BlockKind::FlowerPot => 0.0,
+            _ if identifier.ends_with("_slab")
+                || identifier.ends_with("_planks")
+                || identifier.ends_with("_fence")
+                || identifier.ends_with("_fence_gate") =>
+            {
+                2.0
+            }
+            _ if identifier.ends_with("coral_block") => 1.5,
+            _ if identifier.ends_with("terracotta")
+                && !identifier.ends_with("glazed_terracotta") =>
+            {
+                1.25
+            }
+            _ if identifier.ends_with("_pressure_plate") => 0.5,
+            _ => self.blast_resistance(),
+        }
+    }
 }
@@ -57,7 +57,7 @@ use once_cell::sync::Lazy;
 pub use crate::generated::table::*;
 pub use crate::generated::BlockKind;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 impl Default for BlockKind {
     fn default() -> Self {
@@ -65,6 +65,22 @@ impl Default for BlockKind {
     }
 }
 
+impl BlockKind {
+    /// Returns the identifier of this kind of block, such as `minecraft:stone`.
+    pub fn identifier(self) -> &'static str {
+        BlockId {
+            kind: self,
+            state: 0,
+        }
+        .identifier()
+    }
+
+    /// Returns all kinds of block.
+    pub fn all() -> impl Iterator<Item = BlockKind> {
+        (0..).scan((), |_, id| BlockKind::from_u16(id))
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
 pub struct BlockId {
     kind: BlockKind,
@@ -90,6 +106,44 @@ impl BlockId {
     }
 }
 
+impl BlockId {
+    /// Returns whether this block has a property with the given
+    /// name, as used in vanilla block states, such as `facing`.
+    pub fn has_property(self, name: &str) -> bool {
+        self.property_name(name).is_some()
+    }
+
+    /// Returns this block with a property, named as in vanilla block
+    /// states, set to a new value. Returns `None` if the block has
+    /// no such property or the value is not valid for it.
+    pub fn with_property(self, name: &str, value: &str) -> Option<Self> {
+        let name = self.property_name(name)?;
+        let mut properties: BTreeMap<String, String> = self
+            .to_properties_map()
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value.to_owned()))
+            .collect();
+        properties.insert(name.to_owned(), value.to_owned());
+        Self::from_identifier_and_properties(self.identifier(), &properties)
+    }
+
+    /// Returns the generated name of the property with the given
+    /// vanilla name. The two differ when vanilla uses one name for
+    /// several sets of values, as with `facing_cardinal` for `facing`,
+    /// and when the vanilla name is a Rust keyword, as with `type`.
+    fn property_name(self, vanilla: &str) -> Option<&'static str> {
+        let vanilla = if vanilla == "type" { "kind" } else { vanilla };
+        let properties = self.to_properties_map();
+        if let Some((&name, _)) = properties.get_key_value(vanilla) {
+            return Some(name);
+        }
+        properties.keys().copied().find(|name| {
+            (name.starts_with(vanilla) && name[vanilla.len()..].starts_with('_'))
+                || (name.ends_with(vanilla) && name[..name.len() - vanilla.len()].ends_with('_'))
+        })
+    }
+}
+
 impl From<BlockId> for u32 {
     fn from(id: BlockId) -> Self {
         ((id.kind as u32) << 16) | id.state as u32
@@ -139,6 +193,34 @@ mod tests {
         assert_eq!(block.instrument(), Some(Instrument::Basedrum));
     }
 
+    #[test]
+    fn vanilla_property_names() {
+        let furnace = BlockId::furnace();
+        assert!(furnace.has_property("facing") && furnace.has_property("lit"));
+        assert!(!furnace.has_property("power"));
+
+        let furnace = furnace.with_property("facing", "north").unwrap();
+        assert_eq!(furnace.facing_cardinal(), Some(FacingCardinal::North));
+        assert!(furnace.with_property("facing", "up").is_none());
+
+        let slab = BlockId::oak_slab().with_property("type", "top").unwrap();
+        assert_eq!(slab.slab_kind(), Some(SlabKind::Top));
+
+        let rail = BlockId::powered_rail()
+            .with_property("powered", "true")
+            .and_then(|rail| rail.with_property("shape", "east_west"))
+            .unwrap();
+        assert_eq!(rail.powered(), Some(true));
+        assert_eq!(rail.powered_rail_shape(), Some(PoweredRailShape::EastWest));
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(BlockKind::all().next(), Some(BlockKind::Air));
+        assert_eq!(BlockKind::NoteBlock.identifier(), "minecraft:note_block");
+        assert!(BlockKind::all().any(|kind| kind == BlockKind::StructureBlock));
+    }
+
     #[test]
     fn vanilla_ids() {
         let block = BlockId::rose_bush().with_half_upper_lower(HalfUpperLower::Lower);
@@ -4,8 +4,8 @@
 
 use bitflags::bitflags;
 use feather_inventory::Slot;
-use feather_util::{BlockPosition, Direction};
-use std::collections::BTreeMap;
+use feather_util::{BlockPosition, Direction, Hand};
+use std::collections::{BTreeMap, BTreeSet};
 use uuid::Uuid;
 
 type OptUuid = Option<Uuid>;
@@ -20,9 +20,33 @@ pub const META_INDEX_IS_SILENT: u8 = 4;
 pub const META_INDEX_NO_GRAVITY: u8 = 5;
 
 pub const META_INDEX_ITEM_SLOT: u8 = 6;
+pub const META_INDEX_LIVING_HAND_STATES: u8 = 6;
+
+/// 0x01 if the mob's AI is disabled.
+pub const META_INDEX_MOB_FLAGS: u8 = 11;
 
 pub const META_INDEX_FALLING_BLOCK_SPAWN_POSITION: u8 = 7;
 
+pub const META_INDEX_ARROW_FLAGS: u8 = 6;
+pub const META_INDEX_TRIDENT_LOYALTY: u8 = 8;
+
+pub const META_INDEX_PRIMED_TNT_FUSE: u8 = 6;
+
+pub const META_INDEX_FIREWORK_ITEM: u8 = 6;
+pub const META_INDEX_FIREWORK_BOOSTED_ENTITY: u8 = 7;
+
+pub const META_INDEX_ITEM_FRAME_ITEM: u8 = 6;
+/// Rotation of the item in an item frame, from 0 to 7.
+pub const META_INDEX_ITEM_FRAME_ROTATION: u8 = 7;
+
+/// -1 while a creeper is idle and 1 while its fuse is burning.
+pub const META_INDEX_CREEPER_STATE: u8 = 12;
+pub const META_INDEX_CREEPER_CHARGED: u8 = 13;
+pub const META_INDEX_CREEPER_IGNITED: u8 = 14;
+
+/// Wool color in the lowest four bits, and 0x10 if sheared.
+pub const META_INDEX_SHEEP_FLAGS: u8 = 13;
+
 bitflags! {
     pub struct EntityBitMask: u8 {
         const ON_FIRE = 0x01;
@@ -108,7 +132,7 @@ impl ToMetaEntry for bool {
 
 impl ToMetaEntry for Slot {
     fn to_meta_entry(&self) -> MetaEntry {
-        MetaEntry::Slot(*self)
+        MetaEntry::Slot(self.clone())
     }
 }
 
@@ -136,15 +160,24 @@ impl ToMetaEntry for OptChat {
     }
 }
 
+/// An entity's metadata.
+///
+/// Values changed through `set` and the typed setters are
+/// marked dirty, so that only changed indices need to be
+/// sent to clients; see `take_dirty`. Values added while
+/// building the metadata with `with` are not marked dirty,
+/// since they are sent in full when the entity is spawned.
 #[derive(Clone, Debug)]
 pub struct EntityMetadata {
     pub values: BTreeMap<u8, MetaEntry>,
+    dirty: BTreeSet<u8>,
 }
 
 impl EntityMetadata {
     pub fn new() -> Self {
         Self {
             values: BTreeMap::new(),
+            dirty: BTreeSet::new(),
         }
     }
 
@@ -167,12 +200,18 @@ impl EntityMetadata {
         self
     }
 
+    /// Sets the value at the given index, marking
+    /// it dirty if it changed.
     pub fn set(&mut self, index: u8, entry: impl ToMetaEntry) {
-        self.values.insert(index, entry.to_meta_entry());
+        let entry = entry.to_meta_entry();
+        if self.values.get(&index) != Some(&entry) {
+            self.values.insert(index, entry);
+            self.dirty.insert(index);
+        }
     }
 
     pub fn with(mut self, index: u8, entry: impl ToMetaEntry) -> Self {
-        self.set(index, entry);
+        self.values.insert(index, entry.to_meta_entry());
         self
     }
 
@@ -183,6 +222,86 @@ impl EntityMetadata {
     pub fn iter(&self) -> impl Iterator<Item = (u8, &MetaEntry)> {
         self.values.iter().map(|(key, entry)| (*key, entry))
     }
+
+    /// Returns whether any values have changed
+    /// since the last call to `take_dirty`.
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Returns metadata containing only the values which have
+    /// changed since the last call, or `None` if none have.
+    pub fn take_dirty(&mut self) -> Option<EntityMetadata> {
+        if self.dirty.is_empty() {
+            return None;
+        }
+
+        let mut changed = EntityMetadata::new();
+        for index in std::mem::take(&mut self.dirty) {
+            if let Some(entry) = self.values.get(&index) {
+                changed.values.insert(index, entry.clone());
+            }
+        }
+        Some(changed)
+    }
+
+    /// Returns the entity bit mask.
+    ///
+    /// Protocol 1.13.2 has no pose index; crouching, swimming,
+    /// and elytra flight are indicated by flags in this mask.
+    pub fn flags(&self) -> EntityBitMask {
+        match self.values.get(&META_INDEX_ENTITY_BITMASK) {
+            Some(MetaEntry::Byte(bits)) => EntityBitMask::from_bits_truncate(*bits as u8),
+            _ => EntityBitMask::empty(),
+        }
+    }
+
+    /// Sets or clears flags in the entity bit mask.
+    pub fn set_flag(&mut self, flag: EntityBitMask, value: bool) {
+        let mut flags = self.flags();
+        flags.set(flag, value);
+        self.set(META_INDEX_ENTITY_BITMASK, flags.bits());
+    }
+
+    /// Sets the remaining air, in ticks.
+    pub fn set_air(&mut self, air: i32) {
+        self.set(META_INDEX_AIR, air);
+    }
+
+    /// Sets the custom name, a JSON text component.
+    pub fn set_custom_name(&mut self, name: Option<String>) {
+        self.set(META_INDEX_CUSTOM_NAME, name);
+    }
+
+    pub fn set_custom_name_visible(&mut self, visible: bool) {
+        self.set(META_INDEX_IS_CUSTOM_NAME_VISIBLE, visible);
+    }
+
+    pub fn set_silent(&mut self, silent: bool) {
+        self.set(META_INDEX_IS_SILENT, silent);
+    }
+
+    pub fn set_no_gravity(&mut self, no_gravity: bool) {
+        self.set(META_INDEX_NO_GRAVITY, no_gravity);
+    }
+
+    /// Sets the hand with which a living entity is
+    /// using an item, e.g. raising a shield or drawing a bow.
+    pub fn set_active_hand(&mut self, hand: Option<Hand>) {
+        let states: u8 = match hand {
+            None => 0,
+            Some(Hand::Main) => 0x01,
+            Some(Hand::Off) => 0x03,
+        };
+        self.set(META_INDEX_LIVING_HAND_STATES, states);
+    }
+
+    /// Sets whether a living entity is spinning
+    /// from a Riptide trident.
+    pub fn set_spin_attack(&mut self, spinning: bool) {
+        let states: u8 = if spinning { 0x04 } else { 0 };
+        self.set(META_INDEX_LIVING_HAND_STATES, states);
+    }
 }
 
 impl Default for EntityMetadata {
@@ -190,3 +309,54 @@ impl Default for EntityMetadata {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_values_are_not_dirty() {
+        let mut meta = EntityMetadata::entity_base().with(META_INDEX_ITEM_SLOT, Slot::None);
+        assert!(!meta.is_dirty());
+        assert!(meta.take_dirty().is_none());
+    }
+
+    #[test]
+    fn only_changed_values_are_dirty() {
+        let mut meta = EntityMetadata::entity_base();
+        meta.set_silent(false);
+        assert!(!meta.is_dirty());
+
+        meta.set_flag(EntityBitMask::CROUCHED, true);
+        meta.set_air(300);
+
+        let changed = meta.take_dirty().unwrap();
+        let indices: Vec<_> = changed.iter().map(|(index, _)| index).collect();
+        assert_eq!(indices, vec![META_INDEX_ENTITY_BITMASK, META_INDEX_AIR]);
+        assert!(!meta.is_dirty());
+    }
+
+    #[test]
+    fn flags_are_combined() {
+        let mut meta = EntityMetadata::entity_base();
+        meta.set_flag(EntityBitMask::CROUCHED, true);
+        meta.set_flag(EntityBitMask::ON_FIRE, true);
+        meta.set_flag(EntityBitMask::CROUCHED, false);
+        assert_eq!(meta.flags(), EntityBitMask::ON_FIRE);
+    }
+
+    #[test]
+    fn active_hand_states() {
+        let mut meta = EntityMetadata::new();
+        meta.set_active_hand(Some(Hand::Off));
+        assert_eq!(
+            meta.get(META_INDEX_LIVING_HAND_STATES),
+            Some(MetaEntry::Byte(0x03))
+        );
+        meta.set_active_hand(None);
+        assert_eq!(
+            meta.get(META_INDEX_LIVING_HAND_STATES),
+            Some(MetaEntry::Byte(0))
+        );
+    }
+}
@@ -5,6 +5,9 @@ use once_cell::sync::Lazy;
 use smallvec::{Array, SmallVec};
 use std::cmp::min;
 
+mod window;
+pub use window::*;
+
 pub type SlotIndex = usize;
 
 // Constants representing various standard inventory slot indices
@@ -286,6 +289,11 @@ impl Inventory {
         self.items[index].take()
     }
 
+    /// Swaps the items at the given slot indices.
+    pub fn swap_items(&mut self, a: SlotIndex, b: SlotIndex) {
+        self.items.swap(a, b);
+    }
+
     /// Attempts to insert the given item into a player
     /// inventory.
     ///
@@ -297,7 +305,7 @@ impl Inventory {
         // First, look for slots already having the type.
         for slot in COLLECT_SEARCH_ORDER.iter() {
             if let Some(slot_item) = self.item_at(*slot).cloned() {
-                if slot_item.ty == item.ty {
+                if slot_item.stacks_with(&item) {
                     self.add_to_stack(&mut item, slot_item, *slot, &mut affected_slots);
 
                     if item.amount == 0 {
@@ -310,7 +318,10 @@ impl Inventory {
         for slot in COLLECT_SEARCH_ORDER.iter() {
             let slot_item = self.item_at(*slot).cloned();
             if slot_item.is_none() {
-                let fake = ItemStack::new(item.ty, 0);
+                let fake = ItemStack {
+                    amount: 0,
+                    ..item.clone()
+                };
                 self.add_to_stack(&mut item, fake, *slot, &mut affected_slots);
                 if item.amount == 0 {
                     return (affected_slots, 0);
@@ -318,7 +329,7 @@ impl Inventory {
             }
 
             if let Some(slot_item) = slot_item {
-                if slot_item.ty == item.ty {
+                if slot_item.stacks_with(&item) {
                     self.add_to_stack(&mut item, slot_item, *slot, &mut affected_slots);
 
                     if item.amount == 0 {
@@ -342,7 +353,14 @@ impl Inventory {
         let added = min(item.amount, max_size(item.ty) - slot_item.amount);
         item.amount -= added;
 
-        self.set_item_at(slot, ItemStack::new(slot_item.ty, slot_item.amount + added));
+        let amount = slot_item.amount + added;
+        self.set_item_at(
+            slot,
+            ItemStack {
+                amount,
+                ..slot_item
+            },
+        );
         affected_slots.push(slot);
     }
 
@@ -380,6 +398,17 @@ mod tests {
         assert!(inv.item_at(0).is_none());
     }
 
+    #[test]
+    fn test_swap_items() {
+        let mut inv = Inventory::new(InventoryType::Player, 46);
+        let item = ItemStack::new(Item::Shield, 1);
+        inv.set_item_at(36, item.clone());
+
+        inv.swap_items(36, 45);
+        assert_eq!(inv.item_at(36), None);
+        assert_eq!(inv.item_at(45), Some(&item));
+    }
+
     #[test]
     fn test_collect_item_basic() {
         let mut inv = Inventory::new(InventoryType::Player, 46);
@@ -0,0 +1,790 @@
+//! Windows, through which players move items between slots
+//! by clicking, and the state machine which interprets those clicks.
+//!
+//! The slots of a window are provided by an implementation of
+//! `WindowContents`. A `ClickState` applies clicks to them, keeping
+//! track of the stack on the player's cursor and of drags in progress.
+
+use crate::{max_size, Slot, SlotIndex};
+use feather_items::ItemStack;
+use smallvec::SmallVec;
+use std::cmp::min;
+
+/// Slot index sent for clicks outside of a window.
+pub const SLOT_OUTSIDE: i16 = -999;
+
+/// The slots of a window on which clicks operate.
+pub trait WindowContents {
+    /// Returns the number of slots in the window.
+    fn slot_count(&self) -> SlotIndex;
+
+    /// Returns the item in a slot.
+    fn item(&self, slot: SlotIndex) -> Slot;
+
+    /// Sets the item in a slot.
+    fn set_item(&mut self, slot: SlotIndex, item: Slot);
+
+    /// Returns whether a stack may be put into a slot. Items may
+    /// still be taken out of slots which accept nothing, such as
+    /// crafting outputs, but only as a whole stack.
+    fn accepts(&self, slot: SlotIndex, stack: &ItemStack) -> bool;
+
+    /// Returns the largest stack of an item which a slot may hold.
+    fn max_stack(&self, _slot: SlotIndex, stack: &ItemStack) -> u8 {
+        max_size(stack.ty)
+    }
+
+    /// Returns the window slot of a hotbar slot from 0 to 8.
+    fn hotbar_slot(&self, hotbar: SlotIndex) -> SlotIndex;
+
+    /// Returns the slots to which a shift click on `slot` moves
+    /// `stack`, in order of priority.
+    fn quick_move_targets(&self, slot: SlotIndex, stack: &ItemStack) -> SmallVec<[SlotIndex; 36]>;
+}
+
+/// A mouse button used to click a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// A click on a window, as sent in the Click Window packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Click {
+    /// Picks up or puts down the items in a slot.
+    Pickup {
+        slot: SlotIndex,
+        button: MouseButton,
+    },
+    /// Throws the cursor's items, or one of them, out of the window.
+    DropCursor { all: bool },
+    /// Moves a stack to another part of the window with a shift click.
+    QuickMove { slot: SlotIndex },
+    /// Swaps a slot with a hotbar slot using a number key.
+    HotbarSwap { slot: SlotIndex, hotbar: SlotIndex },
+    /// Copies a stack onto the cursor in creative mode.
+    Clone { slot: SlotIndex },
+    /// Throws the items in a slot, or one of them, out of the window.
+    Drop { slot: SlotIndex, all: bool },
+    /// Starts spreading the cursor's items over several slots.
+    DragStart(MouseButton),
+    /// Adds a slot to the drag in progress.
+    DragAdd(SlotIndex),
+    /// Spreads the cursor's items over the slots of the drag.
+    DragEnd,
+    /// Collects items of the cursor's type with a double click.
+    Collect,
+}
+
+impl Click {
+    /// Interprets the mode, button, and slot of a Click Window
+    /// packet, returning `None` for combinations which are invalid.
+    pub fn from_packet(mode: i32, button: u8, slot: i16) -> Option<Self> {
+        let outside = slot == SLOT_OUTSIDE;
+        let index = if slot >= 0 {
+            Some(slot as SlotIndex)
+        } else {
+            None
+        };
+
+        let click = match (mode, button) {
+            (0, 0) | (0, 1) if outside => Click::DropCursor { all: button == 0 },
+            (0, 0) => Click::Pickup {
+                slot: index?,
+                button: MouseButton::Left,
+            },
+            (0, 1) => Click::Pickup {
+                slot: index?,
+                button: MouseButton::Right,
+            },
+            (1, 0) | (1, 1) => Click::QuickMove { slot: index? },
+            (2, 0..=8) => Click::HotbarSwap {
+                slot: index?,
+                hotbar: SlotIndex::from(button),
+            },
+            (3, 2) => Click::Clone { slot: index? },
+            (4, 0) | (4, 1) => Click::Drop {
+                slot: index?,
+                all: button == 1,
+            },
+            (5, 0) if outside => Click::DragStart(MouseButton::Left),
+            (5, 4) if outside => Click::DragStart(MouseButton::Right),
+            (5, 8) if outside => Click::DragStart(MouseButton::Middle),
+            (5, 1) | (5, 5) | (5, 9) => Click::DragAdd(index?),
+            (5, 2) | (5, 6) | (5, 10) if outside => Click::DragEnd,
+            (6, 0) if index.is_some() => Click::Collect,
+            _ => return None,
+        };
+        Some(click)
+    }
+
+    /// Returns the slot this click is on, if any.
+    pub fn slot(&self) -> Option<SlotIndex> {
+        match *self {
+            Click::Pickup { slot, .. }
+            | Click::QuickMove { slot }
+            | Click::HotbarSwap { slot, .. }
+            | Click::Clone { slot }
+            | Click::Drop { slot, .. }
+            | Click::DragAdd(slot) => Some(slot),
+            _ => None,
+        }
+    }
+}
+
+/// The result of a click which was applied to a window.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClickOutcome {
+    /// Slots whose contents changed.
+    pub changed: SmallVec<[SlotIndex; 4]>,
+    /// Stacks which were thrown out of the window.
+    pub dropped: SmallVec<[ItemStack; 1]>,
+}
+
+/// A drag in progress, which spreads the cursor's
+/// items over the slots it passed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Drag {
+    button: MouseButton,
+    slots: SmallVec<[SlotIndex; 9]>,
+}
+
+/// The state of a player's clicks on a window: the stack
+/// on their cursor and the drag they are performing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClickState {
+    /// The stack held on the cursor.
+    pub cursor: Slot,
+    drag: Option<Drag>,
+}
+
+/// Returns whether two stacks may be merged into one.
+fn can_stack(a: &ItemStack, b: &ItemStack) -> bool {
+    a.stacks_with(b)
+}
+
+/// Returns `stack` with the given amount, or no stack if it is zero.
+fn with_amount(stack: &ItemStack, amount: u8) -> Slot {
+    if amount == 0 {
+        None
+    } else {
+        Some(ItemStack {
+            amount,
+            ..stack.clone()
+        })
+    }
+}
+
+impl ClickState {
+    /// Applies a click to a window.
+    ///
+    /// Returns `None` if the click is invalid, leaving
+    /// the window unchanged.
+    pub fn click(
+        &mut self,
+        contents: &mut impl WindowContents,
+        click: Click,
+        creative: bool,
+    ) -> Option<ClickOutcome> {
+        if let Some(slot) = click.slot() {
+            if slot >= contents.slot_count() {
+                self.drag = None;
+                return None;
+            }
+        }
+
+        let mut outcome = ClickOutcome::default();
+        match click {
+            Click::DragStart(button) => {
+                self.drag = None;
+                if self.cursor.is_none() || (button == MouseButton::Middle && !creative) {
+                    return None;
+                }
+                self.drag = Some(Drag {
+                    button,
+                    slots: SmallVec::new(),
+                });
+            }
+            Click::DragAdd(slot) => self.drag_add(&*contents, slot)?,
+            Click::DragEnd => self.drag_end(contents, &mut outcome)?,
+            click => {
+                // Any other click ends a drag without spreading its items.
+                if self.drag.take().is_some() {
+                    return None;
+                }
+
+                match click {
+                    Click::Pickup { slot, button } => {
+                        self.pickup(contents, slot, button, &mut outcome)
+                    }
+                    Click::DropCursor { all } => self.drop_cursor(all, &mut outcome),
+                    Click::QuickMove { slot } => quick_move(contents, slot, &mut outcome),
+                    Click::HotbarSwap { slot, hotbar } => {
+                        hotbar_swap(contents, slot, hotbar, &mut outcome)
+                    }
+                    Click::Clone { slot } => {
+                        if creative && self.cursor.is_none() {
+                            if let Some(stack) = contents.item(slot) {
+                                self.cursor = with_amount(&stack, max_size(stack.ty));
+                            }
+                        }
+                    }
+                    Click::Drop { slot, all } => {
+                        if self.cursor.is_none() {
+                            drop_slot(contents, slot, all, &mut outcome);
+                        }
+                    }
+                    Click::Collect => self.collect(contents),
+                    Click::DragStart(_) | Click::DragAdd(_) | Click::DragEnd => unreachable!(),
+                }
+            }
+        }
+
+        Some(outcome)
+    }
+
+    fn pickup(
+        &mut self,
+        contents: &mut impl WindowContents,
+        slot: SlotIndex,
+        button: MouseButton,
+        outcome: &mut ClickOutcome,
+    ) {
+        let right = button == MouseButton::Right;
+
+        match (self.cursor.clone(), contents.item(slot)) {
+            (None, None) => return,
+            (None, Some(stack)) => {
+                // Right clicks take the larger half,
+                // except from output slots.
+                let taken = if right && contents.accepts(slot, &stack) {
+                    (stack.amount + 1) / 2
+                } else {
+                    stack.amount
+                };
+                self.cursor = with_amount(&stack, taken);
+                contents.set_item(slot, with_amount(&stack, stack.amount - taken));
+            }
+            (Some(cursor), None) => {
+                if !contents.accepts(slot, &cursor) {
+                    return;
+                }
+                let amount = if right { 1 } else { cursor.amount };
+                let placed = min(amount, contents.max_stack(slot, &cursor));
+                contents.set_item(slot, with_amount(&cursor, placed));
+                self.cursor = with_amount(&cursor, cursor.amount - placed);
+            }
+            (Some(cursor), Some(stack)) if can_stack(&cursor, &stack) => {
+                if contents.accepts(slot, &cursor) {
+                    let amount = if right { 1 } else { cursor.amount };
+                    let space = contents
+                        .max_stack(slot, &stack)
+                        .saturating_sub(stack.amount);
+                    let placed = min(amount, space);
+                    contents.set_item(slot, with_amount(&stack, stack.amount + placed));
+                    self.cursor = with_amount(&cursor, cursor.amount - placed);
+                } else {
+                    // Output slots are emptied onto the cursor
+                    // if all of their items fit on it.
+                    let total = u16::from(cursor.amount) + u16::from(stack.amount);
+                    if total > u16::from(max_size(cursor.ty)) {
+                        return;
+                    }
+                    self.cursor = with_amount(&cursor, total as u8);
+                    contents.set_item(slot, None);
+                }
+            }
+            (Some(cursor), Some(stack)) => {
+                if !contents.accepts(slot, &cursor)
+                    || cursor.amount > contents.max_stack(slot, &cursor)
+                {
+                    return;
+                }
+                contents.set_item(slot, Some(cursor));
+                self.cursor = Some(stack);
+            }
+        }
+
+        outcome.changed.push(slot);
+    }
+
+    fn drop_cursor(&mut self, all: bool, outcome: &mut ClickOutcome) {
+        let cursor = match self.cursor.clone() {
+            Some(cursor) => cursor,
+            None => return,
+        };
+
+        let dropped = if all { cursor.amount } else { 1 };
+        outcome.dropped.extend(with_amount(&cursor, dropped));
+        self.cursor = with_amount(&cursor, cursor.amount - dropped);
+    }
+
+    fn drag_add(&mut self, contents: &impl WindowContents, slot: SlotIndex) -> Option<()> {
+        let cursor = self.cursor.as_ref()?;
+        let drag = self.drag.as_mut()?;
+
+        let fits = contents
+            .item(slot)
+            .map_or(true, |stack| can_stack(&stack, cursor));
+        if fits && contents.accepts(slot, cursor) && !drag.slots.contains(&slot) {
+            drag.slots.push(slot);
+        }
+        Some(())
+    }
+
+    fn drag_end(
+        &mut self,
+        contents: &mut impl WindowContents,
+        outcome: &mut ClickOutcome,
+    ) -> Option<()> {
+        let drag = self.drag.take()?;
+        let cursor = self.cursor.clone()?;
+        if drag.slots.is_empty() {
+            return Some(());
+        }
+
+        let per_slot = match drag.button {
+            MouseButton::Left => (usize::from(cursor.amount) / drag.slots.len()) as u8,
+            MouseButton::Right => 1,
+            MouseButton::Middle => max_size(cursor.ty),
+        };
+        // Middle drags, which are only allowed in creative mode,
+        // create items rather than taking them from the cursor.
+        let consumes = drag.button != MouseButton::Middle;
+
+        let mut remaining = cursor.amount;
+        for slot in drag.slots {
+            let existing = contents.item(slot).map_or(0, |stack| stack.amount);
+            let space = contents.max_stack(slot, &cursor).saturating_sub(existing);
+            let mut added = min(per_slot, space);
+            if consumes {
+                added = min(added, remaining);
+                remaining -= added;
+            }
+
+            if added > 0 {
+                contents.set_item(slot, with_amount(&cursor, existing + added));
+                outcome.changed.push(slot);
+            }
+        }
+
+        self.cursor = with_amount(&cursor, remaining);
+        Some(())
+    }
+
+    fn collect(&mut self, contents: &mut impl WindowContents) {
+        let mut cursor = match self.cursor.clone() {
+            Some(cursor) => cursor,
+            None => return,
+        };
+        let max = max_size(cursor.ty);
+
+        // Partial stacks are collected before full ones.
+        for take_full in &[false, true] {
+            for slot in 0..contents.slot_count() {
+                if cursor.amount >= max {
+                    break;
+                }
+
+                let stack = match contents.item(slot) {
+                    Some(stack) if can_stack(&stack, &cursor) => stack,
+                    _ => continue,
+                };
+                let full = stack.amount >= contents.max_stack(slot, &stack);
+                if full != *take_full || !contents.accepts(slot, &stack) {
+                    continue;
+                }
+
+                let taken = min(stack.amount, max - cursor.amount);
+                cursor.amount += taken;
+                contents.set_item(slot, with_amount(&stack, stack.amount - taken));
+            }
+        }
+
+        self.cursor = Some(cursor);
+    }
+}
+
+fn quick_move(contents: &mut impl WindowContents, slot: SlotIndex, outcome: &mut ClickOutcome) {
+    let stack = match contents.item(slot) {
+        Some(stack) => stack,
+        None => return,
+    };
+    let targets = contents.quick_move_targets(slot, &stack);
+    let mut remaining = stack.amount;
+
+    if !contents.accepts(slot, &stack)
+        && quick_move_space(contents, slot, &stack, &targets) < remaining
+    {
+        return;
+    }
+
+    // Stacks already holding the item are filled before empty slots.
+    for fill_empty in &[false, true] {
+        for &target in &targets {
+            if remaining == 0 {
+                break;
+            }
+            if target == slot || target >= contents.slot_count() {
+                continue;
+            }
+
+            let existing = contents.item(target);
+            let existing_amount = match existing {
+                Some(existing) if !fill_empty && can_stack(&existing, &stack) => existing.amount,
+                None if *fill_empty => 0,
+                _ => continue,
+            };
+            if !contents.accepts(target, &stack) {
+                continue;
+            }
+
+            let space = contents
+                .max_stack(target, &stack)
+                .saturating_sub(existing_amount);
+            let moved = min(space, remaining);
+            if moved > 0 {
+                remaining -= moved;
+                contents.set_item(target, with_amount(&stack, existing_amount + moved));
+                outcome.changed.push(target);
+            }
+        }
+    }
+
+    if remaining != stack.amount {
+        contents.set_item(slot, with_amount(&stack, remaining));
+        outcome.changed.push(slot);
+    }
+}
+
+/// Returns the number of items of `stack` which its quick move targets can hold.
+fn quick_move_space(
+    contents: &impl WindowContents,
+    slot: SlotIndex,
+    stack: &ItemStack,
+    targets: &[SlotIndex],
+) -> u8 {
+    let space: u32 = targets
+        .iter()
+        .filter(|&&target| {
+            target != slot && target < contents.slot_count() && contents.accepts(target, stack)
+        })
+        .map(|&target| {
+            let existing = match contents.item(target) {
+                Some(existing) if can_stack(&existing, stack) => existing.amount,
+                Some(_) => return 0,
+                None => 0,
+            };
+            u32::from(contents.max_stack(target, stack).saturating_sub(existing))
+        })
+        .sum();
+    min(space, u32::from(u8::max_value())) as u8
+}
+
+fn hotbar_swap(
+    contents: &mut impl WindowContents,
+    slot: SlotIndex,
+    hotbar: SlotIndex,
+    outcome: &mut ClickOutcome,
+) {
+    let hotbar = contents.hotbar_slot(hotbar);
+    if hotbar == slot || hotbar >= contents.slot_count() {
+        return;
+    }
+
+    let clicked = contents.item(slot);
+    let held = contents.item(hotbar);
+
+    let fits = |target: SlotIndex, stack: &Slot| {
+        stack.as_ref().map_or(true, |stack| {
+            contents.accepts(target, stack) && stack.amount <= contents.max_stack(target, stack)
+        })
+    };
+    // Items may only be taken out of slots which accept nothing.
+    if !fits(hotbar, &clicked) || (held.is_some() && !fits(slot, &held)) {
+        return;
+    }
+
+    contents.set_item(slot, held);
+    contents.set_item(hotbar, clicked);
+    outcome.changed.push(slot);
+    outcome.changed.push(hotbar);
+}
+
+fn drop_slot(
+    contents: &mut impl WindowContents,
+    slot: SlotIndex,
+    all: bool,
+    outcome: &mut ClickOutcome,
+) {
+    let stack = match contents.item(slot) {
+        Some(stack) => stack,
+        None => return,
+    };
+
+    let dropped = if all || !contents.accepts(slot, &stack) {
+        stack.amount
+    } else {
+        1
+    };
+    outcome.dropped.extend(with_amount(&stack, dropped));
+    contents.set_item(slot, with_amount(&stack, stack.amount - dropped));
+    outcome.changed.push(slot);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_items::Item;
+
+    /// A window whose first slot is an output slot
+    /// and whose last nine slots are the hotbar.
+    struct TestWindow(Vec<Slot>);
+
+    impl TestWindow {
+        fn new() -> Self {
+            TestWindow(vec![None; 19])
+        }
+    }
+
+    impl WindowContents for TestWindow {
+        fn slot_count(&self) -> SlotIndex {
+            self.0.len()
+        }
+
+        fn item(&self, slot: SlotIndex) -> Slot {
+            self.0[slot].clone()
+        }
+
+        fn set_item(&mut self, slot: SlotIndex, item: Slot) {
+            self.0[slot] = item;
+        }
+
+        fn accepts(&self, slot: SlotIndex, _stack: &ItemStack) -> bool {
+            slot != 0
+        }
+
+        fn hotbar_slot(&self, hotbar: SlotIndex) -> SlotIndex {
+            10 + hotbar
+        }
+
+        fn quick_move_targets(
+            &self,
+            slot: SlotIndex,
+            _stack: &ItemStack,
+        ) -> SmallVec<[SlotIndex; 36]> {
+            if slot >= 10 {
+                (1..10).collect()
+            } else {
+                (10..19).collect()
+            }
+        }
+    }
+
+    fn stack(amount: u8) -> Slot {
+        Some(ItemStack::new(Item::Stone, amount))
+    }
+
+    fn left(slot: SlotIndex) -> Click {
+        Click::Pickup {
+            slot,
+            button: MouseButton::Left,
+        }
+    }
+
+    #[test]
+    fn parse_clicks() {
+        assert_eq!(
+            Click::from_packet(0, 1, 5),
+            Some(Click::Pickup {
+                slot: 5,
+                button: MouseButton::Right
+            })
+        );
+        assert_eq!(
+            Click::from_packet(0, 0, SLOT_OUTSIDE),
+            Some(Click::DropCursor { all: true })
+        );
+        assert_eq!(
+            Click::from_packet(2, 3, 1),
+            Some(Click::HotbarSwap { slot: 1, hotbar: 3 })
+        );
+        assert_eq!(Click::from_packet(5, 1, 2), Some(Click::DragAdd(2)));
+        assert_eq!(Click::from_packet(2, 9, 1), None);
+        assert_eq!(Click::from_packet(0, 0, -1), None);
+    }
+
+    #[test]
+    fn pickup_and_place() {
+        let mut window = TestWindow::new();
+        let mut state = ClickState::default();
+        window.0[1] = stack(5);
+
+        let right = Click::Pickup {
+            slot: 1,
+            button: MouseButton::Right,
+        };
+        state.click(&mut window, right, false).unwrap();
+        assert_eq!(state.cursor, stack(3));
+        assert_eq!(window.0[1], stack(2));
+
+        state.click(&mut window, left(2), false).unwrap();
+        assert_eq!(state.cursor, None);
+        assert_eq!(window.0[2], stack(3));
+
+        // Items cannot be put into the output slot,
+        // but may be taken from it.
+        window.0[0] = stack(4);
+        state.click(&mut window, left(1), false).unwrap();
+        state.click(&mut window, left(0), false).unwrap();
+        assert_eq!(state.cursor, stack(6));
+        assert_eq!(window.0[0], None);
+    }
+
+    #[test]
+    fn output_taken_whole() {
+        let mut window = TestWindow::new();
+        let mut state = ClickState::default();
+        window.0[0] = stack(4);
+
+        let right = Click::Pickup {
+            slot: 0,
+            button: MouseButton::Right,
+        };
+        state.click(&mut window, right, false).unwrap();
+        assert_eq!(state.cursor, stack(4));
+        assert_eq!(window.0[0], None);
+
+        // Shift clicks leave the output alone unless all of it fits.
+        window.0[0] = stack(4);
+        for slot in 11..19 {
+            window.0[slot] = stack(64);
+        }
+        window.0[10] = stack(62);
+        state
+            .click(&mut window, Click::QuickMove { slot: 0 }, false)
+            .unwrap();
+        assert_eq!(window.0[0], stack(4));
+        assert_eq!(window.0[10], stack(62));
+
+        let outcome = state
+            .click(
+                &mut window,
+                Click::Drop {
+                    slot: 0,
+                    all: false,
+                },
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            outcome.dropped.as_slice(),
+            &[ItemStack::new(Item::Stone, 4)]
+        );
+    }
+
+    #[test]
+    fn stacks_with_different_nbt_are_swapped() {
+        let mut window = TestWindow::new();
+        let mut state = ClickState::default();
+        let mut named = ItemStack::new(Item::Stone, 1);
+        named.set_display_name(Some(String::from("{\"text\":\"Rock\"}")));
+        window.0[1] = stack(5);
+        state.cursor = Some(named.clone());
+
+        state.click(&mut window, left(1), false).unwrap();
+        assert_eq!(window.0[1], Some(named));
+        assert_eq!(state.cursor, stack(5));
+    }
+
+    #[test]
+    fn quick_move_fills_stacks_first() {
+        let mut window = TestWindow::new();
+        let mut state = ClickState::default();
+        window.0[1] = stack(40);
+        window.0[12] = stack(60);
+
+        let outcome = state
+            .click(&mut window, Click::QuickMove { slot: 1 }, false)
+            .unwrap();
+        assert_eq!(window.0[12], stack(64));
+        assert_eq!(window.0[10], stack(36));
+        assert_eq!(window.0[1], None);
+        assert_eq!(outcome.changed.as_slice(), &[12, 10, 1]);
+    }
+
+    #[test]
+    fn hotbar_swap_respects_slots() {
+        let mut window = TestWindow::new();
+        let mut state = ClickState::default();
+        window.0[3] = stack(1);
+        window.0[10] = Some(ItemStack::new(Item::Dirt, 2));
+
+        state
+            .click(&mut window, Click::HotbarSwap { slot: 3, hotbar: 0 }, false)
+            .unwrap();
+        assert_eq!(window.0[3], Some(ItemStack::new(Item::Dirt, 2)));
+        assert_eq!(window.0[10], stack(1));
+
+        // The hotbar item would have to go into the output slot.
+        window.0[0] = stack(1);
+        state
+            .click(&mut window, Click::HotbarSwap { slot: 0, hotbar: 0 }, false)
+            .unwrap();
+        assert_eq!(window.0[0], stack(1));
+    }
+
+    #[test]
+    fn drag_splits_cursor() {
+        let mut window = TestWindow::new();
+        let mut state = ClickState::default();
+        state.cursor = stack(10);
+        window.0[2] = stack(62);
+
+        state
+            .click(&mut window, Click::DragStart(MouseButton::Left), false)
+            .unwrap();
+        for &slot in &[0, 1, 2, 3] {
+            state
+                .click(&mut window, Click::DragAdd(slot), false)
+                .unwrap();
+        }
+        state.click(&mut window, Click::DragEnd, false).unwrap();
+
+        assert_eq!(window.0[0], None);
+        assert_eq!(window.0[1], stack(3));
+        assert_eq!(window.0[2], stack(64));
+        assert_eq!(window.0[3], stack(3));
+        assert_eq!(state.cursor, stack(2));
+
+        // Middle drags are only allowed in creative mode.
+        assert!(state
+            .click(&mut window, Click::DragStart(MouseButton::Middle), false)
+            .is_none());
+    }
+
+    #[test]
+    fn collect_and_drop() {
+        let mut window = TestWindow::new();
+        let mut state = ClickState::default();
+        state.cursor = stack(1);
+        window.0[1] = stack(64);
+        window.0[2] = stack(10);
+
+        state.click(&mut window, Click::Collect, false).unwrap();
+        assert_eq!(state.cursor, stack(64));
+        assert_eq!(window.0[2], None);
+        assert_eq!(window.0[1], stack(11));
+
+        let outcome = state
+            .click(&mut window, Click::DropCursor { all: false }, false)
+            .unwrap();
+        assert_eq!(
+            outcome.dropped.as_slice(),
+            &[ItemStack::new(Item::Stone, 1)]
+        );
+        assert_eq!(state.cursor, stack(63));
+    }
+}
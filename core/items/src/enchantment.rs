@@ -0,0 +1,184 @@
+//! Enchantments which can be applied to item stacks.
+
+/// An enchantment.
+///
+/// https://minecraft.gamepedia.com/Enchanting#Summary_of_enchantments
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Enchantment {
+    Protection,
+    FireProtection,
+    FeatherFalling,
+    BlastProtection,
+    ProjectileProtection,
+    Respiration,
+    AquaAffinity,
+    Thorns,
+    DepthStrider,
+    FrostWalker,
+    BindingCurse,
+    Sharpness,
+    Smite,
+    BaneOfArthropods,
+    Knockback,
+    FireAspect,
+    Looting,
+    SweepingEdge,
+    Efficiency,
+    SilkTouch,
+    Unbreaking,
+    Fortune,
+    Power,
+    Punch,
+    Flame,
+    Infinity,
+    LuckOfTheSea,
+    Lure,
+    Loyalty,
+    Impaling,
+    Riptide,
+    Channeling,
+    Mending,
+    VanishingCurse,
+}
+
+impl Enchantment {
+    /// Returns the namespaced identifier of this enchantment,
+    /// as stored in item NBT.
+    pub fn identifier(self) -> &'static str {
+        match self {
+            Enchantment::Protection => "minecraft:protection",
+            Enchantment::FireProtection => "minecraft:fire_protection",
+            Enchantment::FeatherFalling => "minecraft:feather_falling",
+            Enchantment::BlastProtection => "minecraft:blast_protection",
+            Enchantment::ProjectileProtection => "minecraft:projectile_protection",
+            Enchantment::Respiration => "minecraft:respiration",
+            Enchantment::AquaAffinity => "minecraft:aqua_affinity",
+            Enchantment::Thorns => "minecraft:thorns",
+            Enchantment::DepthStrider => "minecraft:depth_strider",
+            Enchantment::FrostWalker => "minecraft:frost_walker",
+            Enchantment::BindingCurse => "minecraft:binding_curse",
+            Enchantment::Sharpness => "minecraft:sharpness",
+            Enchantment::Smite => "minecraft:smite",
+            Enchantment::BaneOfArthropods => "minecraft:bane_of_arthropods",
+            Enchantment::Knockback => "minecraft:knockback",
+            Enchantment::FireAspect => "minecraft:fire_aspect",
+            Enchantment::Looting => "minecraft:looting",
+            Enchantment::SweepingEdge => "minecraft:sweeping",
+            Enchantment::Efficiency => "minecraft:efficiency",
+            Enchantment::SilkTouch => "minecraft:silk_touch",
+            Enchantment::Unbreaking => "minecraft:unbreaking",
+            Enchantment::Fortune => "minecraft:fortune",
+            Enchantment::Power => "minecraft:power",
+            Enchantment::Punch => "minecraft:punch",
+            Enchantment::Flame => "minecraft:flame",
+            Enchantment::Infinity => "minecraft:infinity",
+            Enchantment::LuckOfTheSea => "minecraft:luck_of_the_sea",
+            Enchantment::Lure => "minecraft:lure",
+            Enchantment::Loyalty => "minecraft:loyalty",
+            Enchantment::Impaling => "minecraft:impaling",
+            Enchantment::Riptide => "minecraft:riptide",
+            Enchantment::Channeling => "minecraft:channeling",
+            Enchantment::Mending => "minecraft:mending",
+            Enchantment::VanishingCurse => "minecraft:vanishing_curse",
+        }
+    }
+
+    /// Returns the enchantment with the given namespaced identifier.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|enchantment| enchantment.identifier() == identifier)
+    }
+
+    /// Returns the highest level of this enchantment
+    /// obtainable in survival.
+    pub fn max_level(self) -> u32 {
+        match self {
+            Enchantment::Protection
+            | Enchantment::FireProtection
+            | Enchantment::FeatherFalling
+            | Enchantment::BlastProtection
+            | Enchantment::ProjectileProtection
+            | Enchantment::Unbreaking => 4,
+            Enchantment::Sharpness
+            | Enchantment::Smite
+            | Enchantment::BaneOfArthropods
+            | Enchantment::Efficiency
+            | Enchantment::Power
+            | Enchantment::Impaling => 5,
+            Enchantment::Respiration
+            | Enchantment::Thorns
+            | Enchantment::DepthStrider
+            | Enchantment::Looting
+            | Enchantment::SweepingEdge
+            | Enchantment::Fortune
+            | Enchantment::LuckOfTheSea
+            | Enchantment::Lure
+            | Enchantment::Loyalty
+            | Enchantment::Riptide => 3,
+            Enchantment::FrostWalker
+            | Enchantment::Knockback
+            | Enchantment::FireAspect
+            | Enchantment::Punch => 2,
+            _ => 1,
+        }
+    }
+
+    /// Returns all enchantments.
+    pub fn all() -> &'static [Enchantment] {
+        use Enchantment::*;
+        &[
+            Protection,
+            FireProtection,
+            FeatherFalling,
+            BlastProtection,
+            ProjectileProtection,
+            Respiration,
+            AquaAffinity,
+            Thorns,
+            DepthStrider,
+            FrostWalker,
+            BindingCurse,
+            Sharpness,
+            Smite,
+            BaneOfArthropods,
+            Knockback,
+            FireAspect,
+            Looting,
+            SweepingEdge,
+            Efficiency,
+            SilkTouch,
+            Unbreaking,
+            Fortune,
+            Power,
+            Punch,
+            Flame,
+            Infinity,
+            LuckOfTheSea,
+            Lure,
+            Loyalty,
+            Impaling,
+            Riptide,
+            Channeling,
+            Mending,
+            VanishingCurse,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier_round_trip() {
+        for enchantment in Enchantment::all() {
+            assert_eq!(
+                Enchantment::from_identifier(enchantment.identifier()),
+                Some(*enchantment)
+            );
+        }
+        assert_eq!(Enchantment::from_identifier("minecraft:stone"), None);
+    }
+}
@@ -5,9 +5,20 @@ use num_traits::{FromPrimitive, ToPrimitive};
 #[macro_use]
 extern crate num_derive;
 
+mod enchantment;
 mod item;
+mod nbt;
+mod snbt;
+mod tag;
 
+pub use enchantment::Enchantment;
 pub use item::Item;
+pub use nbt::{
+    EnchantmentNbt, EntityEffectNbt, EntityTagNbt, FireworkExplosionNbt, FireworksNbt,
+    FoodEffectNbt, ItemDisplay, ItemNbt,
+};
+pub use snbt::SnbtError;
+pub use tag::Tag;
 
 impl Item {
     /// Retrieves the 1.13.2 protocol ID for this item.
@@ -25,18 +36,62 @@ impl Item {
     {
         Item::from_i32(id)
     }
+
+    /// Returns the number of uses this item can take before
+    /// it breaks, or `None` if it does not wear down.
+    pub fn max_durability(self) -> Option<u32> {
+        use Item::*;
+        let durability = match self {
+            WoodenSword | WoodenShovel | WoodenPickaxe | WoodenAxe | WoodenHoe => 59,
+            StoneSword | StoneShovel | StonePickaxe | StoneAxe | StoneHoe => 131,
+            IronSword | IronShovel | IronPickaxe | IronAxe | IronHoe => 250,
+            GoldenSword | GoldenShovel | GoldenPickaxe | GoldenAxe | GoldenHoe => 32,
+            DiamondSword | DiamondShovel | DiamondPickaxe | DiamondAxe | DiamondHoe => 1561,
+            LeatherHelmet => 55,
+            LeatherChestplate => 80,
+            LeatherLeggings => 75,
+            LeatherBoots => 65,
+            ChainmailHelmet | IronHelmet => 165,
+            ChainmailChestplate | IronChestplate => 240,
+            ChainmailLeggings | IronLeggings => 225,
+            ChainmailBoots | IronBoots => 195,
+            GoldenHelmet => 77,
+            GoldenChestplate => 112,
+            GoldenLeggings => 105,
+            GoldenBoots => 91,
+            DiamondHelmet => 363,
+            DiamondChestplate => 528,
+            DiamondLeggings => 495,
+            DiamondBoots => 429,
+            TurtleHelmet => 275,
+            FlintAndSteel | FishingRod => 64,
+            Shears => 238,
+            Bow => 384,
+            Trident => 250,
+            Shield => 336,
+            Elytra => 432,
+            CarrotOnAStick => 25,
+            _ => return None,
+        };
+        Some(durability)
+    }
 }
 
 /// Represents an item stack.
 ///
 /// An item stack includes a type, an amount, and a bunch of properties (enchantments, etc.)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ItemStack {
     /// The type of this item.
     pub ty: Item,
     /// The number of items in this stack.
     pub amount: u8,
-    // TODO enchantments, more
+    /// The NBT tags of this stack, or `None` if it has none.
+    ///
+    /// Use the typed accessors below rather than
+    /// setting this directly, so that a stack without
+    /// tags always compares equal to a plain stack.
+    pub nbt: Option<Box<ItemNbt>>,
 }
 
 impl Default for ItemStack {
@@ -47,7 +102,212 @@ impl Default for ItemStack {
 
 impl ItemStack {
     pub const fn new(ty: Item, amount: u8) -> Self {
-        Self { ty, amount }
+        Self {
+            ty,
+            amount,
+            nbt: None,
+        }
+    }
+
+    /// Returns this stack with the given NBT tags.
+    pub fn with_nbt(mut self, nbt: ItemNbt) -> Self {
+        self.set_nbt(nbt);
+        self
+    }
+
+    /// Returns this stack's NBT tags, if it has any.
+    pub fn nbt(&self) -> Option<&ItemNbt> {
+        self.nbt.as_deref()
+    }
+
+    /// Replaces this stack's NBT tags.
+    pub fn set_nbt(&mut self, nbt: ItemNbt) {
+        self.nbt = if nbt.is_empty() {
+            None
+        } else {
+            Some(Box::new(nbt))
+        };
+    }
+
+    /// Modifies this stack's NBT tags, removing
+    /// the compound if it is left empty.
+    pub fn modify_nbt<R>(&mut self, f: impl FnOnce(&mut ItemNbt) -> R) -> R {
+        let mut nbt = self.nbt.take().map(|nbt| *nbt).unwrap_or_default();
+        let result = f(&mut nbt);
+        self.set_nbt(nbt);
+        result
+    }
+
+    /// Returns whether this stack can be merged with another,
+    /// i.e. whether they have the same type and tags.
+    pub fn stacks_with(&self, other: &ItemStack) -> bool {
+        self.ty == other.ty && self.nbt == other.nbt
+    }
+
+    /// Returns this stack's custom name, as a JSON text component.
+    pub fn display_name(&self) -> Option<&str> {
+        self.nbt()
+            .and_then(|nbt| nbt.display.as_ref())
+            .and_then(|display| display.name.as_deref())
+    }
+
+    /// Sets or removes this stack's custom name.
+    pub fn set_display_name(&mut self, name: Option<String>) {
+        self.modify_nbt(|nbt| {
+            let display = nbt.display.get_or_insert_with(Default::default);
+            display.name = name;
+            if *display == ItemDisplay::default() {
+                nbt.display = None;
+            }
+        });
+    }
+
+    /// Returns the lines of this stack's lore, as JSON text components.
+    pub fn lore(&self) -> &[String] {
+        self.nbt()
+            .and_then(|nbt| nbt.display.as_ref())
+            .map_or(&[], |display| display.lore.as_slice())
+    }
+
+    /// Sets this stack's lore.
+    pub fn set_lore(&mut self, lore: Vec<String>) {
+        self.modify_nbt(|nbt| {
+            let display = nbt.display.get_or_insert_with(Default::default);
+            display.lore = lore;
+            if *display == ItemDisplay::default() {
+                nbt.display = None;
+            }
+        });
+    }
+
+    /// Returns the damage this stack has taken.
+    pub fn damage(&self) -> u32 {
+        self.nbt().map_or(0, |nbt| nbt.damage.max(0) as u32)
+    }
+
+    /// Sets the damage this stack has taken.
+    pub fn set_damage(&mut self, damage: u32) {
+        self.modify_nbt(|nbt| nbt.damage = damage as i32);
+    }
+
+    /// Adds `amount` to the damage this stack has taken,
+    /// returning whether it is now broken. Stacks which
+    /// are unbreakable or do not wear down are unaffected.
+    pub fn wear(&mut self, amount: u32) -> bool {
+        let max = match self.ty.max_durability() {
+            Some(max) if !self.is_unbreakable() => max,
+            _ => return false,
+        };
+        let damage = self.damage() + amount;
+        self.set_damage(damage);
+        damage >= max
+    }
+
+    /// Returns whether this stack never takes damage.
+    pub fn is_unbreakable(&self) -> bool {
+        self.nbt().map_or(false, |nbt| nbt.unbreakable)
+    }
+
+    /// Sets whether this stack never takes damage.
+    pub fn set_unbreakable(&mut self, unbreakable: bool) {
+        self.modify_nbt(|nbt| nbt.unbreakable = unbreakable);
+    }
+
+    /// Returns the ID of the map drawn on this stack, if it is a filled map.
+    pub fn map_id(&self) -> Option<i32> {
+        self.nbt().and_then(|nbt| nbt.map)
+    }
+
+    /// Sets the ID of the map drawn on this stack.
+    pub fn set_map_id(&mut self, id: Option<i32>) {
+        self.modify_nbt(|nbt| nbt.map = id);
+    }
+
+    /// Returns the pages of a book.
+    pub fn pages(&self) -> &[String] {
+        self.nbt().map_or(&[], |nbt| nbt.pages.as_slice())
+    }
+
+    /// Sets the pages of a book.
+    pub fn set_pages(&mut self, pages: Vec<String>) {
+        self.modify_nbt(|nbt| nbt.pages = pages);
+    }
+
+    /// Returns the flight duration of a firework rocket,
+    /// which is 0 if it has none.
+    pub fn flight_duration(&self) -> u32 {
+        self.nbt()
+            .and_then(|nbt| nbt.fireworks.as_ref())
+            .map_or(0, |fireworks| fireworks.flight.max(0) as u32)
+    }
+
+    /// Returns the explosions of a firework rocket.
+    pub fn firework_explosions(&self) -> &[FireworkExplosionNbt] {
+        self.nbt()
+            .and_then(|nbt| nbt.fireworks.as_ref())
+            .map_or(&[], |fireworks| fireworks.explosions.as_slice())
+    }
+
+    /// Returns the tags applied to the entity spawned by a spawn egg.
+    pub fn entity_tag(&self) -> Option<&EntityTagNbt> {
+        self.nbt().and_then(|nbt| nbt.entity_tag.as_ref())
+    }
+
+    /// Returns the enchantments on this stack and their levels.
+    ///
+    /// Unknown enchantments are skipped.
+    pub fn enchantments(&self) -> impl Iterator<Item = (Enchantment, u32)> + '_ {
+        self.nbt()
+            .into_iter()
+            .flat_map(|nbt| nbt.enchantments.iter())
+            .filter_map(|entry| {
+                Some((
+                    Enchantment::from_identifier(&entry.id)?,
+                    entry.lvl.max(0) as u32,
+                ))
+            })
+    }
+
+    /// Sets the level of an enchantment on this stack,
+    /// removing it if the level is 0.
+    pub fn set_enchantment_level(&mut self, enchantment: Enchantment, level: u32) {
+        self.modify_nbt(|nbt| {
+            let id = enchantment.identifier();
+            nbt.enchantments.retain(|entry| entry.id != id);
+            if level > 0 {
+                nbt.enchantments.push(EnchantmentNbt {
+                    id: id.to_owned(),
+                    lvl: level.min(i16::max_value() as u32) as i16,
+                });
+            }
+        });
+    }
+
+    /// Returns the block predicates in this stack's `CanDestroy`
+    /// tag, which name the blocks it may break in adventure mode.
+    pub fn can_destroy(&self) -> impl Iterator<Item = &str> + '_ {
+        self.nbt()
+            .into_iter()
+            .flat_map(|nbt| nbt.can_destroy.iter())
+            .map(String::as_str)
+    }
+
+    /// Returns the block predicates in this stack's `CanPlaceOn`
+    /// tag, which name the blocks it may be placed against in
+    /// adventure mode.
+    pub fn can_place_on(&self) -> impl Iterator<Item = &str> + '_ {
+        self.nbt()
+            .into_iter()
+            .flat_map(|nbt| nbt.can_place_on.iter())
+            .map(String::as_str)
+    }
+
+    /// Returns the level of an enchantment on this
+    /// stack, or 0 if it is not enchanted with it.
+    pub fn enchantment_level(&self, enchantment: Enchantment) -> u32 {
+        self.enchantments()
+            .find(|(e, _)| *e == enchantment)
+            .map_or(0, |(_, level)| level)
     }
 }
 
@@ -61,4 +321,85 @@ mod tests {
         assert_eq!(item.native_protocol_id(), 0);
         assert_eq!(Item::from_native_protocol_id(0), Some(item));
     }
+
+    #[test]
+    fn wear_breaks_at_max_durability() {
+        let mut stack = ItemStack::new(Item::FlintAndSteel, 1);
+        stack.set_damage(62);
+        assert!(!stack.wear(1));
+        assert!(stack.wear(1));
+
+        let mut unbreakable = ItemStack::new(Item::FlintAndSteel, 1);
+        unbreakable.set_damage(63);
+        unbreakable.set_unbreakable(true);
+        assert!(!unbreakable.wear(1));
+        assert_eq!(unbreakable.damage(), 63);
+
+        let mut stone = ItemStack::new(Item::Stone, 1);
+        assert!(!stone.wear(1));
+        assert!(stone.nbt().is_none());
+    }
+
+    #[test]
+    fn typed_nbt_accessors() {
+        let mut stack = ItemStack::new(Item::DiamondSword, 1);
+        assert!(stack.nbt().is_none());
+
+        stack.set_display_name(Some(String::from("{\"text\":\"Excalibur\"}")));
+        stack.set_lore(vec![String::from("{\"text\":\"Sharp\"}")]);
+        stack.set_damage(12);
+        stack.set_unbreakable(true);
+        stack.set_enchantment_level(Enchantment::Sharpness, 5);
+
+        assert_eq!(stack.display_name(), Some("{\"text\":\"Excalibur\"}"));
+        assert_eq!(stack.lore().len(), 1);
+        assert_eq!(stack.damage(), 12);
+        assert!(stack.is_unbreakable());
+        assert_eq!(stack.enchantment_level(Enchantment::Sharpness), 5);
+        assert!(!stack.stacks_with(&ItemStack::new(Item::DiamondSword, 1)));
+    }
+
+    #[test]
+    fn book_pages() {
+        let mut stack = ItemStack::new(Item::WritableBook, 1);
+        assert!(stack.pages().is_empty());
+
+        stack.set_pages(vec![String::from("first"), String::from("second")]);
+        assert_eq!(stack.pages(), &["first", "second"]);
+
+        stack.set_pages(vec![]);
+        assert!(stack.nbt().is_none());
+    }
+
+    #[test]
+    fn firework_tags() {
+        let mut stack = ItemStack::new(Item::FireworkRocket, 1);
+        assert_eq!(stack.flight_duration(), 0);
+        assert!(stack.firework_explosions().is_empty());
+
+        stack.modify_nbt(|nbt| {
+            nbt.fireworks = Some(FireworksNbt {
+                flight: 3,
+                explosions: vec![FireworkExplosionNbt {
+                    colors: vec![0xFF_00_00],
+                    ..Default::default()
+                }],
+            })
+        });
+        assert_eq!(stack.flight_duration(), 3);
+        assert_eq!(stack.firework_explosions().len(), 1);
+    }
+
+    #[test]
+    fn cleared_nbt_is_removed() {
+        let mut stack = ItemStack::new(Item::Stick, 1);
+        stack.set_display_name(Some(String::from("{\"text\":\"Wand\"}")));
+        stack.set_enchantment_level(Enchantment::Knockback, 2);
+
+        stack.set_display_name(None);
+        stack.set_enchantment_level(Enchantment::Knockback, 0);
+
+        assert!(stack.nbt.is_none());
+        assert_eq!(stack, ItemStack::new(Item::Stick, 1));
+    }
 }
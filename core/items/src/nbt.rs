@@ -0,0 +1,288 @@
+//! The NBT compound stored in item stacks.
+//!
+//! Tags the server understands are read into typed fields.
+//! Other tags, such as those of potions or player heads, are
+//! kept as they are so that stacks survive being sent, dropped
+//! and saved.
+
+use crate::{SnbtError, Tag};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The `tag` compound of an item stack.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ItemNbt {
+    /// The damage taken by a tool or armor piece.
+    #[serde(rename = "Damage", default, skip_serializing_if = "is_zero")]
+    pub damage: i32,
+    /// Whether the stack never takes damage.
+    #[serde(
+        rename = "Unbreakable",
+        default,
+        with = "byte_bool",
+        skip_serializing_if = "is_false"
+    )]
+    pub unbreakable: bool,
+    #[serde(rename = "display", default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<ItemDisplay>,
+    #[serde(
+        rename = "Enchantments",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub enchantments: Vec<EnchantmentNbt>,
+    /// Block predicates which this stack may break in adventure mode.
+    #[serde(rename = "CanDestroy", default, skip_serializing_if = "Vec::is_empty")]
+    pub can_destroy: Vec<String>,
+    /// Block predicates which this stack may be
+    /// placed against in adventure mode.
+    #[serde(rename = "CanPlaceOn", default, skip_serializing_if = "Vec::is_empty")]
+    pub can_place_on: Vec<String>,
+    /// Effects applied when the stack is eaten, as on suspicious stews.
+    #[serde(rename = "Effects", default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<FoodEffectNbt>,
+    /// The ID of the map drawn on a filled map.
+    #[serde(rename = "map", default, skip_serializing_if = "Option::is_none")]
+    pub map: Option<i32>,
+    /// Set on filled maps which were crafted with paper
+    /// to zoom out; they are replaced by a map with a
+    /// larger scale once a player has them.
+    #[serde(
+        rename = "map_scale_direction",
+        default,
+        skip_serializing_if = "is_zero"
+    )]
+    pub map_scale_direction: i32,
+    /// The pages of a book. Pages of a book and quill are plain
+    /// text, and those of a written book JSON text components.
+    #[serde(rename = "pages", default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<String>,
+    /// The title of a written book.
+    #[serde(rename = "title", default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The name of the player who signed a written book.
+    #[serde(rename = "author", default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// How many times a written book has been copied,
+    /// from 0 for the original to 3 for a tattered copy.
+    #[serde(rename = "generation", default, skip_serializing_if = "is_zero")]
+    pub generation: i32,
+    /// Whether the text components of a written book's
+    /// pages have been resolved by the server.
+    #[serde(
+        rename = "resolved",
+        default,
+        with = "byte_bool",
+        skip_serializing_if = "is_false"
+    )]
+    pub resolved: bool,
+    /// The flight duration and explosions of a firework rocket.
+    #[serde(rename = "Fireworks", default, skip_serializing_if = "Option::is_none")]
+    pub fireworks: Option<FireworksNbt>,
+    /// Tags applied to the entity spawned by a spawn egg.
+    #[serde(rename = "EntityTag", default, skip_serializing_if = "Option::is_none")]
+    pub entity_tag: Option<EntityTagNbt>,
+    /// Tags which have no field above, by name.
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Tag>,
+}
+
+impl ItemNbt {
+    /// Returns whether no tags are set, in which case
+    /// the stack is sent without a compound.
+    pub fn is_empty(&self) -> bool {
+        *self == ItemNbt::default()
+    }
+
+    /// Reads a stringified compound, such as `{Damage:10}`, from
+    /// the start of `input`. Returns the tags along with the number
+    /// of bytes read; unknown tags are kept in `other`.
+    pub fn read_snbt(input: &str) -> Result<(Self, usize), SnbtError> {
+        crate::snbt::read_compound(input)
+    }
+}
+
+/// The `display` compound of an item stack.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ItemDisplay {
+    /// The custom name, as a JSON text component.
+    #[serde(rename = "Name", default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Lines of lore, each a JSON text component.
+    #[serde(rename = "Lore", default, skip_serializing_if = "Vec::is_empty")]
+    pub lore: Vec<String>,
+}
+
+/// A single entry of the `Enchantments` list.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EnchantmentNbt {
+    /// The namespaced identifier of the enchantment.
+    pub id: String,
+    pub lvl: i16,
+}
+
+/// A single entry of the `Effects` list of a food.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FoodEffectNbt {
+    /// The numeric ID of the status effect.
+    #[serde(rename = "EffectId")]
+    pub id: i8,
+    /// Duration in ticks.
+    #[serde(rename = "EffectDuration", default = "default_food_effect_duration")]
+    pub duration: i32,
+    /// Not used by vanilla, which always applies level I.
+    #[serde(rename = "Amplifier", default, skip_serializing_if = "is_zero_i8")]
+    pub amplifier: i8,
+}
+
+fn default_food_effect_duration() -> i32 {
+    160
+}
+
+/// The `Fireworks` compound of a firework rocket.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FireworksNbt {
+    /// The number of gunpowder the rocket was crafted with.
+    #[serde(rename = "Flight", default)]
+    pub flight: i8,
+    #[serde(rename = "Explosions", default, skip_serializing_if = "Vec::is_empty")]
+    pub explosions: Vec<FireworkExplosionNbt>,
+}
+
+/// A single explosion of a firework rocket,
+/// made from one firework star.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FireworkExplosionNbt {
+    /// The shape of the explosion, from 0 for a small
+    /// ball to 4 for a burst.
+    #[serde(rename = "Type", default)]
+    pub ty: i8,
+    /// RGB colors of the particles.
+    #[serde(rename = "Colors", default)]
+    pub colors: Vec<i32>,
+    /// RGB colors the particles fade to.
+    #[serde(rename = "FadeColors", default, skip_serializing_if = "Vec::is_empty")]
+    pub fade_colors: Vec<i32>,
+    #[serde(
+        rename = "Flicker",
+        default,
+        with = "byte_bool",
+        skip_serializing_if = "is_false"
+    )]
+    pub flicker: bool,
+    #[serde(
+        rename = "Trail",
+        default,
+        with = "byte_bool",
+        skip_serializing_if = "is_false"
+    )]
+    pub trail: bool,
+}
+
+/// The `EntityTag` compound of a spawn egg, which is also
+/// the compound given to `/summon`.
+///
+/// Only tags which the server applies to spawned entities are kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntityTagNbt {
+    /// The namespaced identifier of the entity to spawn
+    /// in place of the egg's own mob.
+    #[serde(rename = "id", default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The custom name of the entity, as a JSON text component.
+    #[serde(
+        rename = "CustomName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub custom_name: Option<String>,
+    #[serde(
+        rename = "CustomNameVisible",
+        default,
+        with = "byte_bool",
+        skip_serializing_if = "is_false"
+    )]
+    pub custom_name_visible: bool,
+    #[serde(
+        rename = "Silent",
+        default,
+        with = "byte_bool",
+        skip_serializing_if = "is_false"
+    )]
+    pub silent: bool,
+    /// Whether the mob's AI is disabled.
+    #[serde(
+        rename = "NoAI",
+        default,
+        with = "byte_bool",
+        skip_serializing_if = "is_false"
+    )]
+    pub no_ai: bool,
+    /// Status effects applied to the entity when it spawns.
+    #[serde(
+        rename = "ActiveEffects",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub active_effects: Vec<EntityEffectNbt>,
+}
+
+impl EntityTagNbt {
+    /// Reads a stringified compound, such as `{NoAI:1b}`, from
+    /// the start of `input`. Returns the tags along with the number
+    /// of bytes read; unknown tags are ignored.
+    pub fn read_snbt(input: &str) -> Result<(Self, usize), SnbtError> {
+        crate::snbt::read_compound(input)
+    }
+}
+
+/// A single entry of the `ActiveEffects` list of an entity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntityEffectNbt {
+    /// The numeric ID of the status effect.
+    #[serde(rename = "Id")]
+    pub id: i8,
+    #[serde(rename = "Amplifier", default)]
+    pub amplifier: i8,
+    /// Duration in ticks.
+    #[serde(rename = "Duration", default)]
+    pub duration: i32,
+    #[serde(
+        rename = "Ambient",
+        default,
+        with = "byte_bool",
+        skip_serializing_if = "is_false"
+    )]
+    pub ambient: bool,
+    #[serde(rename = "ShowParticles", default = "default_true", with = "byte_bool")]
+    pub show_particles: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_zero(x: &i32) -> bool {
+    *x == 0
+}
+
+fn is_zero_i8(x: &i8) -> bool {
+    *x == 0
+}
+
+fn is_false(x: &bool) -> bool {
+    !*x
+}
+
+/// Stores booleans as bytes, which is how they appear in NBT.
+mod byte_bool {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i8(*value as i8)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        Ok(i8::deserialize(deserializer)? != 0)
+    }
+}
@@ -0,0 +1,384 @@
+//! Stringified NBT, the text form of NBT used in commands,
+//! such as `{Damage:10,display:{Name:'{"text":"Sword"}'}}`.
+
+use crate::Tag;
+use serde::de::value::{Error, MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt::{self, Display};
+
+/// An error in stringified NBT, found at
+/// byte offset `cursor` into the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnbtError {
+    pub message: String,
+    pub cursor: usize,
+}
+
+impl Display for SnbtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.cursor)
+    }
+}
+
+impl std::error::Error for SnbtError {}
+
+/// Reads a compound from the start of `input` into `T`, returning
+/// it along with the number of bytes of `input` which were read.
+pub fn read_compound<T: DeserializeOwned>(input: &str) -> Result<(T, usize), SnbtError> {
+    let mut parser = Parser { input, cursor: 0 };
+    if parser.peek() != Some('{') {
+        return Err(parser.error("Expected '{'"));
+    }
+    let tag = parser.read_value()?;
+    let value = T::deserialize(tag).map_err(|e| SnbtError {
+        message: e.to_string(),
+        cursor: 0,
+    })?;
+    Ok((value, parser.cursor))
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    cursor: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: impl Into<String>) -> SnbtError {
+        SnbtError {
+            message: message.into(),
+            cursor: self.cursor,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.cursor..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SnbtError> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.cursor += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'", expected)))
+        }
+    }
+
+    /// Reads a separating comma, returning whether
+    /// the end of the list or compound was reached.
+    fn read_separator(&mut self, end: char) -> Result<bool, SnbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.cursor += 1;
+                Ok(false)
+            }
+            Some(c) if c == end => {
+                self.cursor += 1;
+                Ok(true)
+            }
+            _ => Err(self.error(format!("Expected ',' or '{}'", end))),
+        }
+    }
+
+    fn read_unquoted(&mut self) -> &'a str {
+        let start = self.cursor;
+        while let Some(c) = self.peek() {
+            match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' | '+' => self.cursor += 1,
+                _ => break,
+            }
+        }
+        &self.input[start..self.cursor]
+    }
+
+    fn read_quoted(&mut self) -> Result<String, SnbtError> {
+        let quote = self.peek().unwrap();
+        self.cursor += 1;
+        let mut string = String::new();
+        loop {
+            let c = match self.peek() {
+                Some(c) => c,
+                None => return Err(self.error("Unclosed quoted string")),
+            };
+            self.cursor += c.len_utf8();
+            if c == quote {
+                return Ok(string);
+            }
+            if c == '\\' {
+                match self.peek() {
+                    Some(escaped) if escaped == quote || escaped == '\\' => {
+                        self.cursor += 1;
+                        string.push(escaped);
+                    }
+                    _ => return Err(self.error("Invalid escape sequence")),
+                }
+            } else {
+                string.push(c);
+            }
+        }
+    }
+
+    fn read_key(&mut self) -> Result<String, SnbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') | Some('\'') => self.read_quoted(),
+            _ => {
+                let key = self.read_unquoted();
+                if key.is_empty() {
+                    Err(self.error("Expected key"))
+                } else {
+                    Ok(key.to_owned())
+                }
+            }
+        }
+    }
+
+    fn read_value(&mut self) -> Result<Tag, SnbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.read_compound(),
+            Some('[') => self.read_list(),
+            Some('"') | Some('\'') => self.read_quoted().map(Tag::String),
+            _ => {
+                let token = self.read_unquoted();
+                if token.is_empty() {
+                    Err(self.error("Expected value"))
+                } else {
+                    Ok(parse_unquoted(token).unwrap_or_else(|| Tag::String(token.to_owned())))
+                }
+            }
+        }
+    }
+
+    fn read_compound(&mut self) -> Result<Tag, SnbtError> {
+        self.expect('{')?;
+        let mut entries = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.cursor += 1;
+            return Ok(Tag::Compound(entries));
+        }
+        loop {
+            let key = self.read_key()?;
+            self.expect(':')?;
+            let value = self.read_value()?;
+            entries.push((key, value));
+            if self.read_separator('}')? {
+                return Ok(Tag::Compound(entries));
+            }
+        }
+    }
+
+    fn read_list(&mut self) -> Result<Tag, SnbtError> {
+        self.expect('[')?;
+        let rest = &self.input[self.cursor..];
+        let array = if rest.starts_with("B;") {
+            Some('B')
+        } else if rest.starts_with("I;") {
+            Some('I')
+        } else if rest.starts_with("L;") {
+            Some('L')
+        } else {
+            None
+        };
+        if array.is_some() {
+            self.cursor += 2;
+        }
+
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.cursor += 1;
+        } else {
+            loop {
+                let start = self.cursor;
+                let value = self.read_value()?;
+                if array.is_none() {
+                    if let Some((_, first)) = values.first() {
+                        if std::mem::discriminant(first) != std::mem::discriminant(&value) {
+                            return Err(SnbtError {
+                                message: String::from("Mixed types in list"),
+                                cursor: start,
+                            });
+                        }
+                    }
+                }
+                values.push((start, value));
+                if self.read_separator(']')? {
+                    break;
+                }
+            }
+        }
+
+        let values = values.into_iter();
+        let invalid = |cursor| SnbtError {
+            message: String::from("Invalid array element"),
+            cursor,
+        };
+        Ok(match array {
+            None => Tag::List(values.map(|(_, value)| value).collect()),
+            Some('B') => Tag::ByteArray(
+                values
+                    .map(|(cursor, value)| match value {
+                        Tag::Byte(x) => Ok(x),
+                        _ => Err(invalid(cursor)),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Some('I') => Tag::IntArray(
+                values
+                    .map(|(cursor, value)| match value {
+                        Tag::Int(x) => Ok(x),
+                        _ => Err(invalid(cursor)),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Some(_) => Tag::LongArray(
+                values
+                    .map(|(cursor, value)| match value {
+                        Tag::Long(x) => Ok(x),
+                        Tag::Int(x) => Ok(i64::from(x)),
+                        _ => Err(invalid(cursor)),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+        })
+    }
+}
+
+/// Parses an unquoted number or boolean, returning `None`
+/// if the token is a string.
+fn parse_unquoted(token: &str) -> Option<Tag> {
+    match token {
+        "true" => return Some(Tag::Byte(1)),
+        "false" => return Some(Tag::Byte(0)),
+        _ => (),
+    }
+
+    let (number, suffix) = match token.chars().last() {
+        Some(c) if c.is_ascii_alphabetic() => (&token[..token.len() - 1], Some(c)),
+        _ => (token, None),
+    };
+    match suffix.map(|c| c.to_ascii_lowercase()) {
+        Some('b') => number.parse().ok().map(Tag::Byte),
+        Some('s') => number.parse().ok().map(Tag::Short),
+        Some('l') => number.parse().ok().map(Tag::Long),
+        Some('f') => number.parse().ok().map(Tag::Float),
+        Some('d') => number.parse().ok().map(Tag::Double),
+        Some(_) => None,
+        None if number.contains('.') || number.contains('e') || number.contains('E') => {
+            number.parse().ok().map(Tag::Double)
+        }
+        None => number.parse().ok().map(Tag::Int),
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Tag {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Tag::Byte(x) => visitor.visit_i8(x),
+            Tag::Short(x) => visitor.visit_i16(x),
+            Tag::Int(x) => visitor.visit_i32(x),
+            Tag::Long(x) => visitor.visit_i64(x),
+            Tag::Float(x) => visitor.visit_f32(x),
+            Tag::Double(x) => visitor.visit_f64(x),
+            Tag::String(x) => visitor.visit_string(x),
+            Tag::List(x) => visitor.visit_seq(SeqDeserializer::new(x.into_iter())),
+            Tag::Compound(x) => visitor.visit_map(MapDeserializer::new(x.into_iter())),
+            Tag::ByteArray(x) => visitor.visit_seq(SeqDeserializer::new(x.into_iter())),
+            Tag::IntArray(x) => visitor.visit_seq(SeqDeserializer::new(x.into_iter())),
+            Tag::LongArray(x) => visitor.visit_seq(SeqDeserializer::new(x.into_iter())),
+        }
+    }
+
+    /// Tags which are present are always `Some`.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Tag {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EntityTagNbt, ItemNbt};
+
+    #[test]
+    fn values() {
+        let parse = |input: &str| {
+            let mut parser = Parser { input, cursor: 0 };
+            parser.read_value()
+        };
+        assert_eq!(parse("3b"), Ok(Tag::Byte(3)));
+        assert_eq!(parse("-2S"), Ok(Tag::Short(-2)));
+        assert_eq!(parse("10"), Ok(Tag::Int(10)));
+        assert_eq!(parse("1.5"), Ok(Tag::Double(1.5)));
+        assert_eq!(parse("1.5f"), Ok(Tag::Float(1.5)));
+        assert_eq!(parse("true"), Ok(Tag::Byte(1)));
+        assert_eq!(parse("stone"), Ok(Tag::String(String::from("stone"))));
+        assert_eq!(parse("'it\\'s'"), Ok(Tag::String(String::from("it's"))));
+        assert_eq!(parse("[I; 1, 2]"), Ok(Tag::IntArray(vec![1, 2])));
+        assert!(parse("[1, 2b]").is_err());
+        assert!(parse("{a:1").is_err());
+    }
+
+    #[test]
+    fn item_nbt() {
+        let input = "{Damage:10,Unbreakable:1b,display:{Name:'{\"text\":\"Sword\"}'},\
+                     Enchantments:[{id:\"minecraft:sharpness\",lvl:5s}],Unknown:[L;1L]} 5";
+        let (nbt, len): (ItemNbt, usize) = read_compound(input).unwrap();
+        assert_eq!(&input[len..], " 5");
+        assert_eq!(nbt.damage, 10);
+        assert!(nbt.unbreakable);
+        assert_eq!(
+            nbt.display.unwrap().name.as_deref(),
+            Some("{\"text\":\"Sword\"}")
+        );
+        assert_eq!(nbt.enchantments[0].lvl, 5);
+        assert_eq!(nbt.other["Unknown"], Tag::List(vec![Tag::Long(1)]));
+
+        assert!(read_compound::<ItemNbt>("{Damage:\"ten\"}").is_err());
+        assert!(read_compound::<ItemNbt>("Damage:1").is_err());
+    }
+
+    #[test]
+    fn entity_tag() {
+        let input =
+            "{NoAI:1b,CustomName:'\"Bob\"',ActiveEffects:[{Id:1b,Amplifier:2b,Duration:200}]}";
+        let (tag, len): (EntityTagNbt, usize) = read_compound(input).unwrap();
+        assert_eq!(len, input.len());
+        assert!(tag.no_ai);
+        assert_eq!(tag.custom_name.as_deref(), Some("\"Bob\""));
+
+        let effect = &tag.active_effects[0];
+        assert_eq!((effect.id, effect.amplifier, effect.duration), (1, 2, 200));
+        assert!(effect.show_particles);
+        assert!(!effect.ambient);
+    }
+}
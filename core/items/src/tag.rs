@@ -0,0 +1,138 @@
+//! Untyped NBT tags, kept from item compounds
+//! the server does not understand.
+
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// An NBT tag kept as it was read.
+///
+/// Arrays read through serde become lists of their elements.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    List(Vec<Tag>),
+    Compound(Vec<(String, Tag)>),
+    ByteArray(Vec<i8>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+// Tags never hold NaN in practice, since Minecraft does
+// not write it, so equality is treated as total.
+impl Eq for Tag {}
+
+impl Hash for Tag {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Tag::Byte(x) => x.hash(state),
+            Tag::Short(x) => x.hash(state),
+            Tag::Int(x) => x.hash(state),
+            Tag::Long(x) => x.hash(state),
+            // Positive and negative zero compare equal.
+            Tag::Float(x) => (*x + 0.0).to_bits().hash(state),
+            Tag::Double(x) => (*x + 0.0).to_bits().hash(state),
+            Tag::String(x) => x.hash(state),
+            Tag::List(x) => x.hash(state),
+            Tag::Compound(x) => x.hash(state),
+            Tag::ByteArray(x) => x.hash(state),
+            Tag::IntArray(x) => x.hash(state),
+            Tag::LongArray(x) => x.hash(state),
+        }
+    }
+}
+
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Tag::Byte(x) => serializer.serialize_i8(*x),
+            Tag::Short(x) => serializer.serialize_i16(*x),
+            Tag::Int(x) => serializer.serialize_i32(*x),
+            Tag::Long(x) => serializer.serialize_i64(*x),
+            Tag::Float(x) => serializer.serialize_f32(*x),
+            Tag::Double(x) => serializer.serialize_f64(*x),
+            Tag::String(x) => serializer.serialize_str(x),
+            Tag::List(x) => serializer.collect_seq(x),
+            Tag::Compound(x) => serializer.collect_map(x.iter().map(|(k, v)| (k, v))),
+            Tag::ByteArray(x) => serializer.collect_seq(x),
+            Tag::IntArray(x) => serializer.collect_seq(x),
+            Tag::LongArray(x) => serializer.collect_seq(x),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(TagVisitor)
+    }
+}
+
+struct TagVisitor;
+
+impl<'de> Visitor<'de> for TagVisitor {
+    type Value = Tag;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an NBT tag")
+    }
+
+    fn visit_bool<E: de::Error>(self, x: bool) -> Result<Tag, E> {
+        Ok(Tag::Byte(x as i8))
+    }
+
+    fn visit_i8<E: de::Error>(self, x: i8) -> Result<Tag, E> {
+        Ok(Tag::Byte(x))
+    }
+
+    fn visit_i16<E: de::Error>(self, x: i16) -> Result<Tag, E> {
+        Ok(Tag::Short(x))
+    }
+
+    fn visit_i32<E: de::Error>(self, x: i32) -> Result<Tag, E> {
+        Ok(Tag::Int(x))
+    }
+
+    fn visit_i64<E: de::Error>(self, x: i64) -> Result<Tag, E> {
+        Ok(Tag::Long(x))
+    }
+
+    fn visit_f32<E: de::Error>(self, x: f32) -> Result<Tag, E> {
+        Ok(Tag::Float(x))
+    }
+
+    fn visit_f64<E: de::Error>(self, x: f64) -> Result<Tag, E> {
+        Ok(Tag::Double(x))
+    }
+
+    fn visit_str<E: de::Error>(self, x: &str) -> Result<Tag, E> {
+        Ok(Tag::String(x.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, x: String) -> Result<Tag, E> {
+        Ok(Tag::String(x))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Tag, A::Error> {
+        let mut list = vec![];
+        while let Some(tag) = seq.next_element()? {
+            list.push(tag);
+        }
+        Ok(Tag::List(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Tag, A::Error> {
+        let mut entries = vec![];
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Tag::Compound(entries))
+    }
+}
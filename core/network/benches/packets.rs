@@ -0,0 +1,166 @@
+#[macro_use]
+extern crate criterion;
+
+use bytes::BytesMut;
+use criterion::{black_box, Criterion};
+use feather_blocks::BlockId;
+use feather_chunk::Chunk;
+use feather_network::packets::{
+    ChunkData, EntityRelativeMove, PlayerPositionAndLookServerbound, PluginMessageServerbound,
+};
+use feather_network::{MinecraftCodec, Packet, PacketDirection, PacketStage};
+use feather_util::ChunkPosition;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Creates a chunk resembling generated terrain, with
+/// several sections and a varied palette.
+fn terrain_chunk() -> Chunk {
+    let mut chunk = Chunk::new(ChunkPosition::new(0, 0));
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..64 {
+                let block = match y {
+                    0 => BlockId::bedrock(),
+                    y if y < 60 && (x + y + z) % 7 == 0 => BlockId::coal_ore(),
+                    y if y < 60 => BlockId::stone(),
+                    y if y < 63 => BlockId::dirt(),
+                    _ => BlockId::grass_block(),
+                };
+                chunk.set_block_at(x, y, z, block);
+            }
+        }
+    }
+    chunk
+}
+
+fn position_and_look() -> PlayerPositionAndLookServerbound {
+    PlayerPositionAndLookServerbound {
+        x: 100.5,
+        feet_y: 64.0,
+        z: -35.2,
+        yaw: 90.0,
+        pitch: 12.5,
+        on_ground: true,
+    }
+}
+
+fn chunk_data_encode(c: &mut Criterion) {
+    feather_blocks::init();
+    let packet = ChunkData {
+        chunk: Arc::new(RwLock::new(terrain_chunk())),
+    };
+
+    let mut buf = BytesMut::new();
+    c.bench_function("chunk_data_encode", |b| {
+        b.iter(|| {
+            buf.clear();
+            packet.write_to(&mut buf);
+        });
+    });
+}
+
+fn entity_relative_move_encode(c: &mut Criterion) {
+    let packet = EntityRelativeMove {
+        entity_id: 1234,
+        delta_x: 512,
+        delta_y: -128,
+        delta_z: 40,
+        on_ground: false,
+    };
+
+    let mut buf = BytesMut::new();
+    c.bench_function("entity_relative_move_encode", |b| {
+        b.iter(|| {
+            buf.clear();
+            black_box(&packet).write_to(&mut buf);
+        });
+    });
+}
+
+fn player_position_and_look_decode(c: &mut Criterion) {
+    let mut buf = BytesMut::new();
+    position_and_look().write_to(&mut buf);
+    let buf = buf.freeze();
+
+    c.bench_function("player_position_and_look_decode", |b| {
+        b.iter(|| {
+            let mut packet = PlayerPositionAndLookServerbound::default();
+            packet.read_from(&mut black_box(buf.clone())).unwrap();
+            packet
+        });
+    });
+}
+
+fn plugin_message_decode(c: &mut Criterion) {
+    let packet = PluginMessageServerbound {
+        channel: String::from("minecraft:brand"),
+        data: vec![0xAB; 4096].into(),
+    };
+    let mut buf = BytesMut::new();
+    packet.write_to(&mut buf);
+    let buf = buf.freeze();
+
+    c.bench_function("plugin_message_decode", |b| {
+        b.iter(|| {
+            let mut packet = PluginMessageServerbound::default();
+            packet.read_from(&mut black_box(buf.clone())).unwrap();
+            packet
+        });
+    });
+}
+
+fn codec_encode_chunk_data_compressed(c: &mut Criterion) {
+    feather_blocks::init();
+    let chunk = Arc::new(RwLock::new(terrain_chunk()));
+
+    let mut codec = MinecraftCodec::new(PacketDirection::Serverbound);
+    codec.set_stage(PacketStage::Play);
+    codec.enable_compression(256);
+
+    let mut buf = BytesMut::new();
+    c.bench_function("codec_encode_chunk_data_compressed", |b| {
+        b.iter(|| {
+            buf.clear();
+            let packet = ChunkData {
+                chunk: Arc::clone(&chunk),
+            };
+            codec
+                .encode(Box::new(packet) as Box<dyn Packet>, &mut buf)
+                .unwrap();
+        });
+    });
+}
+
+fn codec_decode_player_position_and_look(c: &mut Criterion) {
+    let mut encoder = MinecraftCodec::new(PacketDirection::Clientbound);
+    encoder.set_stage(PacketStage::Play);
+    let mut encoded = BytesMut::new();
+    encoder
+        .encode(
+            Box::new(position_and_look()) as Box<dyn Packet>,
+            &mut encoded,
+        )
+        .unwrap();
+
+    let mut codec = MinecraftCodec::new(PacketDirection::Serverbound);
+    codec.set_stage(PacketStage::Play);
+    c.bench_function("codec_decode_player_position_and_look", |b| {
+        b.iter(|| {
+            let mut src = encoded.clone();
+            codec.decode(&mut src).unwrap().unwrap()
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    chunk_data_encode,
+    entity_relative_move_encode,
+    player_position_and_look_decode,
+    plugin_message_decode,
+    codec_encode_chunk_data_compressed,
+    codec_decode_player_position_and_look
+);
+criterion_main!(benches);
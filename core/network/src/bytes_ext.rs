@@ -13,6 +13,10 @@ pub enum TryGetError {
     ValueTooLarge,
     #[error("invalid value {0}")]
     InvalidValue(i32),
+    #[error("invalid NBT")]
+    InvalidNbt,
+    #[error("invalid UTF-8 in string")]
+    InvalidString,
 }
 
 type Result<T> = std::result::Result<T, TryGetError>;
@@ -1,16 +1,13 @@
 use crate::bytes_ext::TryGetError;
-use crate::mctypes::{McTypeRead, McTypeWrite};
+use crate::mctypes::{with_slot_format, McTypeRead, McTypeWrite};
 use crate::packet::{PacketDirection, PacketId, PacketStage};
-use crate::{Packet, PacketType};
+use crate::{Packet, PacketType, ProtocolVersion};
 use aes::Aes128;
-use bytes::buf::BufMutExt;
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, Bytes, BytesMut};
 use cfb8::stream_cipher::{NewStreamCipher, StreamCipher};
 use cfb8::Cfb8;
-use flate2::read::ZlibDecoder;
-use flate2::write::ZlibEncoder;
-use flate2::Compression;
-use std::io::{Cursor, Read, Write};
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use std::io::Cursor;
 use thiserror::Error;
 use tokio_util::codec::{Decoder, Encoder};
 
@@ -22,6 +19,8 @@ const MAX_VAR_INT_SIZE: usize = 5;
 const MAX_PACKET_LEN: usize = 1_048_576; // One MB
 /// Maximum possible size of a packet header.
 const HEADER_SIZE: usize = MAX_VAR_INT_SIZE * 2;
+/// Maximum allowed length of a received packet once decompressed.
+const MAX_DECOMPRESSED_LEN: usize = MAX_PACKET_LEN * 2;
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -29,30 +28,49 @@ pub enum Error {
     CompressedPacketTooSmall(usize, usize),
     #[error("Packet length {0} is too large")]
     PacketTooLarge(usize),
+    #[error("Compressed packet claims a decompressed length of {0}, which is too large")]
+    DecompressedPacketTooLarge(usize),
+    #[error("Compressed packet decompressed to {0} bytes, but claimed a length of {1}")]
+    DecompressedLengthMismatch(usize, usize),
     #[error("Invalid packet ID {0} for stage {1:?}")]
     InvalidPacketId(u32, PacketStage),
 }
 
+/// Running totals of the packets and bytes
+/// a codec has encoded and decoded.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodecStats {
+    pub packets_read: u64,
+    /// Bytes read, as received on the wire.
+    pub bytes_read: u64,
+    pub packets_written: u64,
+    /// Bytes written, as sent on the wire.
+    pub bytes_written: u64,
+    /// Bytes written, counting packets at
+    /// their size before compression.
+    pub uncompressed_bytes_written: u64,
+}
+
 /// Codec for encoding and decoding Minecraft packets.
 pub struct MinecraftCodec {
     /// Direction of incoming packets.
     incoming_direction: PacketDirection,
     /// The current stage of this codec.
     stage: PacketStage,
+    /// The protocol version of the other end of the connection.
+    version: ProtocolVersion,
     /// The encrypter, if encryption is enabled.
     encrypter: Option<AesCfb8>,
     /// The decrypter, if encryption is enabled.
     decrypter: Option<AesCfb8>,
-    /// The compression threshold, if compression is enabled.
-    compression_threshold: Option<usize>,
+    /// The compression state, if compression is enabled.
+    compression: Option<CompressionState>,
     /// Cached buffer for writing header data.
     /// Using this avoids reallocations.
     header_buffer: BytesMut,
-    /// Cached buffer into which we write decompressed
-    /// data. Using this avoids reallocations.
-    decompressed_buffer: Vec<u8>,
     /// Index into `src` of next byte to decrypt.
     decrypt_index: usize,
+    stats: CodecStats,
 }
 
 impl MinecraftCodec {
@@ -60,18 +78,19 @@ impl MinecraftCodec {
         Self {
             incoming_direction,
             stage: PacketStage::Handshake,
+            version: ProtocolVersion::NATIVE,
             encrypter: None,
             decrypter: None,
-            compression_threshold: None,
+            compression: None,
             header_buffer: BytesMut::with_capacity(HEADER_SIZE),
-            decompressed_buffer: vec![],
             decrypt_index: 0,
+            stats: CodecStats::default(),
         }
     }
 
     pub fn enable_compression(&mut self, threshold: usize) {
         log::trace!("Enabling compression with threshold {}", threshold);
-        self.compression_threshold = Some(threshold);
+        self.compression = Some(CompressionState::new(threshold));
     }
 
     pub fn enable_encryption(&mut self, key: [u8; 16]) {
@@ -86,12 +105,152 @@ impl MinecraftCodec {
         log::trace!("Setting packet stage to {:?}", stage);
         self.stage = stage;
     }
+
+    pub fn set_version(&mut self, version: ProtocolVersion) {
+        log::trace!("Setting protocol version to {}", version.name());
+        self.version = version;
+    }
+
+    pub fn stats(&self) -> CodecStats {
+        self.stats
+    }
 }
 
-impl Encoder<Box<dyn Packet>> for MinecraftCodec {
-    type Error = anyhow::Error;
+/// Compression state of a connection.
+///
+/// The zlib streams are reset and reused for each packet,
+/// since allocating a new stream is far more expensive than
+/// compressing a typical packet.
+struct CompressionState {
+    /// Packets at least this long are compressed.
+    threshold: usize,
+    compressor: Compress,
+    decompressor: Decompress,
+    /// Cached buffer into which we write compressed
+    /// and decompressed data. Using this avoids reallocations
+    /// when compressing; decompressed packets take ownership
+    /// of the buffer, so it is allocated anew for each.
+    buffer: Vec<u8>,
+}
 
-    fn encode(&mut self, packet: Box<dyn Packet>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+impl CompressionState {
+    fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            compressor: Compress::new(Compression::default(), true),
+            decompressor: Decompress::new(true),
+            buffer: vec![],
+        }
+    }
+
+    /// Compresses `data` into `self.buffer`.
+    fn compress(&mut self, data: &[u8]) -> anyhow::Result<()> {
+        self.compressor.reset();
+        self.buffer.clear();
+
+        loop {
+            let consumed = self.compressor.total_in() as usize;
+            // `compress_vec` only writes into spare capacity.
+            self.buffer.reserve((data.len() - consumed).max(64));
+            let status = self.compressor.compress_vec(
+                &data[consumed..],
+                &mut self.buffer,
+                FlushCompress::Finish,
+            )?;
+            if status == Status::StreamEnd {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Decompresses `data`, which should decompress to exactly
+    /// `data_length` bytes, into `self.buffer`.
+    fn decompress(&mut self, data: &[u8], data_length: usize) -> anyhow::Result<()> {
+        if data_length < self.threshold {
+            return Err(Error::CompressedPacketTooSmall(data_length, self.threshold).into());
+        }
+        if data_length > MAX_DECOMPRESSED_LEN {
+            return Err(Error::DecompressedPacketTooLarge(data_length).into());
+        }
+
+        self.decompressor.reset(true);
+        self.buffer.clear();
+        // `decompress_vec` never grows the buffer, so a malicious
+        // packet cannot make us allocate more than it claims.
+        self.buffer.reserve_exact(data_length);
+        let status =
+            self.decompressor
+                .decompress_vec(data, &mut self.buffer, FlushDecompress::Finish)?;
+
+        let actual_length = self.buffer.len();
+        if status != Status::StreamEnd || actual_length != data_length {
+            return Err(Error::DecompressedLengthMismatch(actual_length, data_length).into());
+        }
+        Ok(())
+    }
+}
+
+/// A packet whose ID and data have been written out,
+/// but which has not yet been framed, compressed, or encrypted.
+///
+/// Preparing packets ahead of sending them lets
+/// a connection know how large its backlog is.
+#[derive(Debug)]
+pub struct RawPacket {
+    ty: PacketType,
+    data: BytesMut,
+}
+
+impl RawPacket {
+    pub fn ty(&self) -> PacketType {
+        self.ty
+    }
+
+    /// Returns the length of the packet before compression.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+impl MinecraftCodec {
+    /// Writes out a packet so that it can be sent later.
+    pub fn prepare(&self, packet: &dyn Packet) -> RawPacket {
+        let ty = packet.ty();
+        let mut data = BytesMut::new();
+        data.push_var_int(self.version.packet_id(ty).0 as i32);
+        with_slot_format(self.version.slot_format(), || packet.write_to(&mut data));
+        RawPacket { ty, data }
+    }
+
+    /// Frames a packet, compressing and encrypting it if enabled,
+    /// and appends it to `buf`. `write` writes the packet's
+    /// ID and data to the buffer it is given.
+    fn encode_with(
+        &mut self,
+        buf: &mut BytesMut,
+        write: impl FnOnce(&mut BytesMut),
+    ) -> anyhow::Result<()> {
+        if buf.is_empty() {
+            return self.encode_frame(buf, write);
+        }
+
+        // Packets may be queued in `buf` before it is flushed.
+        // Frame this one separately, so that only it is encrypted.
+        let mut frame = buf.split_off(buf.len());
+        self.encode_frame(&mut frame, write)?;
+        buf.unsplit(frame);
+        Ok(())
+    }
+
+    fn encode_frame(
+        &mut self,
+        dst: &mut BytesMut,
+        write: impl FnOnce(&mut BytesMut),
+    ) -> anyhow::Result<()> {
         // Reserve space for the packet header (at most 2 * 5 bytes, for length + data length).
         // `header` will contain the first 10 bytes of the buffer, while `dst`
         // still contains the rest.
@@ -108,31 +267,21 @@ impl Encoder<Box<dyn Packet>> for MinecraftCodec {
         assert!(dst.is_empty());
 
         // Write raw packet data to `dst`.
-        let ty = packet.ty();
-        log::trace!("Sending packet with type {:?}", ty);
-        dst.push_var_int(ty.get_id().0 as i32);
-        packet.write_to(dst);
+        write(dst);
+        self.stats.packets_written += 1;
+        self.stats.uncompressed_bytes_written += dst.len() as u64;
 
         // If compression is enabled, we follow a more complex course of action:
         // * Write the raw packet data to `dst`.
         // * If the data is less than the compression threshold, proceed as usual.
-        // * Otherwise, we move forward into the buffer, allocating
-        // another header and then writing the compressed bytes
-        // to the capacity after that.
-        let data_len: Option<usize> = if let Some(threshold) = self.compression_threshold {
+        // * Otherwise, compress the data into the cached buffer
+        // and replace the contents of `dst` with it.
+        let data_len: Option<usize> = if let Some(compression) = self.compression.as_mut() {
             let data_len = dst.len();
-            if data_len >= threshold {
-                // Allocate new header
-                dst.reserve(HEADER_SIZE);
-
-                let uncompressed = dst.split_to(data_len);
-                dst.extend_from_slice(&[0u8; HEADER_SIZE]);
-                header = dst.split_to(HEADER_SIZE);
-
-                assert!(dst.is_empty());
-                // Compress data into `compressed`.
-                let mut encoder = ZlibEncoder::new(dst.writer(), Compression::default());
-                encoder.write_all(uncompressed.as_ref()).unwrap();
+            if data_len >= compression.threshold {
+                compression.compress(&dst[..])?;
+                dst.clear();
+                dst.extend_from_slice(&compression.buffer);
 
                 Some(data_len)
             } else {
@@ -179,11 +328,36 @@ impl Encoder<Box<dyn Packet>> for MinecraftCodec {
         if let Some(crypter) = self.encrypter.as_mut() {
             crypter.encrypt(dst);
         }
+        self.stats.bytes_written += dst.len() as u64;
 
         Ok(())
     }
 }
 
+impl Encoder<Box<dyn Packet>> for MinecraftCodec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, packet: Box<dyn Packet>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let ty = packet.ty();
+        log::trace!("Sending packet with type {:?}", ty);
+        let id = self.version.packet_id(ty).0 as i32;
+        let slot_format = self.version.slot_format();
+        self.encode_with(dst, |buf| {
+            buf.push_var_int(id);
+            with_slot_format(slot_format, || packet.write_to(buf));
+        })
+    }
+}
+
+impl Encoder<RawPacket> for MinecraftCodec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, packet: RawPacket, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        log::trace!("Sending packet with type {:?}", packet.ty);
+        self.encode_with(dst, |buf| buf.extend_from_slice(&packet.data))
+    }
+}
+
 impl Decoder for MinecraftCodec {
     type Item = Box<dyn Packet>;
     type Error = anyhow::Error;
@@ -195,8 +369,8 @@ impl Decoder for MinecraftCodec {
             self.decrypt_index = src.len();
         }
 
-        // Conversion to `Cursor` is required because `Bytes` does
-        // not implement `Buf`.
+        // Peek at the header through a `Cursor`, since
+        // the full packet may not have been received yet.
         let mut cursor = Cursor::new(src.as_ref());
 
         // Read header.
@@ -218,46 +392,42 @@ impl Decoder for MinecraftCodec {
 
         // At this point, we know a full packet has been received.
 
-        // Trim `cursor` and `src` to length of packet.
+        // Split the packet off `src`. This does not copy: `frame`
+        // shares the receive buffer, and packets borrow their
+        // byte arrays from it in turn. The buffer is freed
+        // once every packet read from it has been dropped.
         let position = cursor.position() as usize;
         src.advance(position);
-        cursor = Cursor::new(&src[..length]);
+        let mut frame = src.split_to(length).freeze();
+        self.decrypt_index = src.len();
+        self.stats.packets_read += 1;
+        self.stats.bytes_read += (position + length) as u64;
 
         // If compression is enabled:
         // * Read the data length field. If 0, continue as normal: the packet is not compressed.
-        // * Decompress remaining bytes into `self.decompressed_buffer`.
-        // * Update `cursor` to read from `self.decompressed_buffer`.
-        if let Some(threshold) = self.compression_threshold {
-            let data_length = cursor.try_get_var_int()?;
+        // * Decompress remaining bytes into the cached buffer.
+        // * Read the packet from the decompressed buffer, which it takes ownership of.
+        if let Some(compression) = self.compression.as_mut() {
+            let data_length = frame.try_get_var_int()? as usize;
 
             if data_length != 0 {
-                self.decompressed_buffer.clear();
-
-                let mut decoder = ZlibDecoder::new(cursor);
-                decoder.read_to_end(&mut self.decompressed_buffer)?;
-
-                let actual_data_length = self.decompressed_buffer.len();
-                if actual_data_length < threshold {
-                    return Err(
-                        Error::CompressedPacketTooSmall(actual_data_length, threshold).into(),
-                    );
-                }
+                compression.decompress(&frame, data_length)?;
 
-                cursor = Cursor::new(&self.decompressed_buffer);
+                frame = Bytes::from(std::mem::take(&mut compression.buffer));
             }
         }
 
         // Read packet.
-        let id = cursor.try_get_var_int()? as u32;
+        let id = frame.try_get_var_int()? as u32;
         // If we don't know this packet type, skip the packet.
         let packet_type = {
-            match PacketType::get_from_id(PacketId(id, self.incoming_direction, self.stage)) {
-                Ok(ty) => ty,
-                Err(_) => {
-                    // Advance buffer and stop.
+            match self
+                .version
+                .packet_type(PacketId(id, self.incoming_direction, self.stage))
+            {
+                Some(ty) => ty,
+                None => {
                     log::trace!("Received packet type with unknown ID 0x{:x}; skipping", id);
-                    src.advance(length);
-                    self.decrypt_index = src.len();
                     return Ok(None);
                 }
             }
@@ -266,13 +436,177 @@ impl Decoder for MinecraftCodec {
         log::trace!("Decoding packet with type {:?}", packet_type);
 
         let mut packet = packet_type.get_implementation();
-        packet.read_from(&mut cursor)?;
+        with_slot_format(self.version.slot_format(), || packet.read_from(&mut frame))?;
 
         log::trace!("Received packet with type {:?}", packet_type);
 
-        src.advance(length);
-        self.decrypt_index = src.len();
-
         Ok(Some(packet))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cast_packet;
+    use crate::packets::{
+        ChatMessageServerbound, CreativeInventoryAction, PluginMessageServerbound,
+    };
+    use feather_items::{Item, ItemStack};
+
+    fn round_trip(codec: &mut MinecraftCodec, message: &str) -> (usize, String) {
+        let packet = ChatMessageServerbound {
+            message: message.to_owned(),
+        };
+        let mut buf = BytesMut::new();
+        codec
+            .encode(Box::new(packet) as Box<dyn Packet>, &mut buf)
+            .unwrap();
+        let encoded_len = buf.len();
+
+        let decoded = codec.decode(&mut buf).unwrap().unwrap();
+        assert!(buf.is_empty());
+        let decoded = cast_packet::<ChatMessageServerbound>(decoded);
+        (encoded_len, decoded.message)
+    }
+
+    #[test]
+    fn compression_round_trip() {
+        let mut codec = MinecraftCodec::new(PacketDirection::Serverbound);
+        codec.set_stage(PacketStage::Play);
+        codec.enable_compression(256);
+
+        // Below the threshold, packets are sent uncompressed.
+        let (_, message) = round_trip(&mut codec, "hello");
+        assert_eq!(message, "hello");
+
+        // The compressor is reused across packets.
+        let long = "a".repeat(1000);
+        for _ in 0..3 {
+            let (encoded_len, message) = round_trip(&mut codec, &long);
+            assert_eq!(message, long);
+            assert!(encoded_len < 100);
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_decompressed_length() {
+        let mut codec = MinecraftCodec::new(PacketDirection::Serverbound);
+        codec.set_stage(PacketStage::Play);
+        codec.enable_compression(256);
+
+        let mut buf = BytesMut::new();
+        let packet = ChatMessageServerbound {
+            message: "a".repeat(1000),
+        };
+        codec
+            .encode(Box::new(packet) as Box<dyn Packet>, &mut buf)
+            .unwrap();
+
+        // Overwrite the data length, which follows the
+        // one-byte packet length, with a smaller one.
+        let mut tampered = BytesMut::new();
+        tampered.push_var_int((buf.len() - 1) as i32);
+        tampered.push_var_int(300);
+        tampered.extend_from_slice(&buf[3..]);
+        assert!(codec.decode(&mut tampered).is_err());
+    }
+
+    #[test]
+    fn slots_use_the_connection_version_layout() {
+        let mut codec = MinecraftCodec::new(PacketDirection::Serverbound);
+        codec.set_stage(PacketStage::Play);
+        codec.set_version(ProtocolVersion::V1_13_1);
+
+        let sword = ItemStack::new(Item::DiamondSword, 1);
+        let packet = CreativeInventoryAction {
+            slot: 36,
+            clicked_item: Some(sword.clone()),
+        };
+        let mut buf = BytesMut::new();
+        codec
+            .encode(Box::new(packet) as Box<dyn Packet>, &mut buf)
+            .unwrap();
+
+        // Length, packet ID, and slot index precede the item,
+        // whose ID is a short rather than a boolean and VarInt.
+        let id = Item::DiamondSword.native_protocol_id() as i16;
+        assert_eq!(&buf[4..6], &id.to_be_bytes());
+
+        let decoded = codec.decode(&mut buf).unwrap().unwrap();
+        let decoded = cast_packet::<CreativeInventoryAction>(decoded);
+        assert_eq!(decoded.clicked_item, Some(sword));
+    }
+
+    #[test]
+    fn decoded_data_shares_receive_buffer() {
+        let mut codec = MinecraftCodec::new(PacketDirection::Serverbound);
+        codec.set_stage(PacketStage::Play);
+
+        let packet = PluginMessageServerbound {
+            channel: String::from("feather:test"),
+            data: Bytes::from_static(&[1, 2, 3, 4]),
+        };
+        let mut buf = BytesMut::new();
+        codec
+            .encode(Box::new(packet) as Box<dyn Packet>, &mut buf)
+            .unwrap();
+        let data_start = buf[buf.len() - 4..].as_ptr();
+
+        let decoded = codec.decode(&mut buf).unwrap().unwrap();
+        let decoded = cast_packet::<PluginMessageServerbound>(decoded);
+        assert_eq!(decoded.channel, "feather:test");
+        assert_eq!(decoded.data.as_ref(), &[1, 2, 3, 4]);
+        assert_eq!(decoded.data.as_ptr(), data_start);
+    }
+
+    #[test]
+    fn queued_packets_round_trip() {
+        let key = [7u8; 16];
+        let mut encoder = MinecraftCodec::new(PacketDirection::Serverbound);
+        let mut decoder = MinecraftCodec::new(PacketDirection::Serverbound);
+        for codec in [&mut encoder, &mut decoder].iter_mut() {
+            codec.set_stage(PacketStage::Play);
+            codec.enable_compression(256);
+            codec.enable_encryption(key);
+        }
+
+        let long = "a".repeat(1000);
+        let messages = ["hello", long.as_str(), "world"];
+        let mut buf = BytesMut::new();
+        for (i, message) in messages.iter().enumerate() {
+            let packet = ChatMessageServerbound {
+                message: (*message).to_owned(),
+            };
+            if i == 1 {
+                let raw = encoder.prepare(&packet);
+                encoder.encode(raw, &mut buf).unwrap();
+            } else {
+                encoder
+                    .encode(Box::new(packet) as Box<dyn Packet>, &mut buf)
+                    .unwrap();
+            }
+        }
+
+        for message in messages.iter() {
+            let decoded = decoder.decode(&mut buf).unwrap().unwrap();
+            let decoded = cast_packet::<ChatMessageServerbound>(decoded);
+            assert_eq!(decoded.message, *message);
+        }
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn stats_count_compressed_bytes() {
+        let mut codec = MinecraftCodec::new(PacketDirection::Serverbound);
+        codec.set_stage(PacketStage::Play);
+        codec.enable_compression(256);
+
+        let (encoded_len, _) = round_trip(&mut codec, &"a".repeat(1000));
+        let stats = codec.stats();
+        assert_eq!(stats.packets_written, 1);
+        assert_eq!(stats.packets_read, 1);
+        assert_eq!(stats.bytes_written, encoded_len as u64);
+        assert_eq!(stats.bytes_read, encoded_len as u64);
+        assert!(stats.uncompressed_bytes_written > 1000);
+    }
+}
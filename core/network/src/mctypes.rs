@@ -1,16 +1,53 @@
 use crate::bytes_ext::{BytesExt, BytesMutExt, TryGetError};
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, Bytes, BytesMut};
 use feather_entity_metadata::{EntityMetadata, MetaEntry};
-use feather_items::{Item, ItemStack};
+use feather_items::{Item, ItemNbt, ItemStack};
 use feather_util::BlockPosition;
 use feather_util::Direction;
 use num_traits::FromPrimitive;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::collections::BTreeMap;
-use std::io::Read;
+use std::cell::Cell;
 use uuid::Uuid;
 
+/// How item stacks are laid out in packets.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SlotFormat {
+    /// A boolean telling whether the slot holds an item,
+    /// followed by the item's VarInt ID. Used since 1.13.2.
+    Present,
+    /// The item's ID as a short, which is -1 for an
+    /// empty slot. Used up to 1.13.1.
+    ShortId,
+}
+
+thread_local! {
+    static SLOT_FORMAT: Cell<SlotFormat> = Cell::new(SlotFormat::Present);
+}
+
+/// Restores the previous slot format when dropped.
+struct SlotFormatGuard(SlotFormat);
+
+impl Drop for SlotFormatGuard {
+    fn drop(&mut self) {
+        SLOT_FORMAT.with(|format| format.set(self.0));
+    }
+}
+
+/// Runs `f` with slots read and written in the given format.
+///
+/// Packets are written without knowing the version of the
+/// connection they are sent on, so the codec sets the format
+/// for the duration of each packet instead.
+pub(crate) fn with_slot_format<T>(format: SlotFormat, f: impl FnOnce() -> T) -> T {
+    let _guard = SlotFormatGuard(SLOT_FORMAT.with(|current| current.replace(format)));
+    f()
+}
+
+fn slot_format() -> SlotFormat {
+    SLOT_FORMAT.with(Cell::get)
+}
+
 /// Identifies a type to which Minecraft-specific
 /// types (`VarInt`, `VarLong`, etc.) can be written.
 pub trait McTypeWrite {
@@ -33,7 +70,7 @@ pub trait McTypeWrite {
 
     fn push_nbt<T: Serialize>(&mut self, x: &T);
 
-    fn push_slot(&mut self, slot: Option<ItemStack>);
+    fn push_slot(&mut self, slot: Option<&ItemStack>);
 }
 
 /// Identifies a type from which Minecraft-specified
@@ -57,6 +94,25 @@ pub trait McTypeRead {
     fn try_get_slot(&mut self) -> Result<Option<ItemStack>, TryGetError>;
 }
 
+/// Reads from the `Bytes` of a received packet without copying.
+///
+/// `Bytes` returned by these methods share memory with the
+/// connection's receive buffer, so byte arrays in packets
+/// cost no allocation to decode.
+pub trait BytesRead {
+    /// Returns the next `len` bytes of the buffer.
+    fn try_get_bytes(&mut self, len: usize) -> Result<Bytes, TryGetError>;
+}
+
+impl BytesRead for Bytes {
+    fn try_get_bytes(&mut self, len: usize) -> Result<Bytes, TryGetError> {
+        if self.len() < len {
+            return Err(TryGetError::NotEnoughBytes);
+        }
+        Ok(self.split_to(len))
+    }
+}
+
 impl McTypeWrite for BytesMut {
     fn push_var_int(&mut self, mut x: i32) -> usize {
         let mut bytes_written = 0;
@@ -114,18 +170,30 @@ impl McTypeWrite for BytesMut {
         self.extend_from_slice(&temp);
     }
 
-    fn push_slot(&mut self, slot: Option<ItemStack>) {
-        self.push_bool(slot.is_some());
+    fn push_slot(&mut self, slot: Option<&ItemStack>) {
+        match slot_format() {
+            SlotFormat::Present => {
+                self.push_bool(slot.is_some());
+                if let Some(slot) = slot {
+                    self.push_var_int(slot.ty.native_protocol_id());
+                }
+            }
+            SlotFormat::ShortId => {
+                self.push_i16(slot.map_or(-1, |slot| slot.ty.native_protocol_id() as i16))
+            }
+        }
 
-        if let Some(slot) = slot.as_ref() {
-            self.push_var_int(slot.ty.native_protocol_id());
+        if let Some(slot) = slot {
             self.push_i8(slot.amount as i8);
-            self.push_i8(0x00); // TAG_End - TODO item NBT support
+            match slot.nbt() {
+                Some(nbt) => self.push_nbt(nbt),
+                None => self.push_i8(0x00), // TAG_End
+            }
         }
     }
 }
 
-impl<B: Buf + Read> McTypeRead for B {
+impl<B: Buf> McTypeRead for B {
     /// Reads a `VarInt` from this object, returning
     /// `Some(x)` if successful or `None` if the object
     /// does not contain a valid `VarInt`.
@@ -153,27 +221,33 @@ impl<B: Buf + Read> McTypeRead for B {
 
     /// Reads a string from the object.
     fn try_get_string(&mut self) -> Result<String, TryGetError> {
-        let len = self.try_get_var_int();
-        if let Ok(len) = len {
-            // Check that the client isn't trying
-            // to make the server allocate ridiculous
-            // amounts of memory
-            if len > 32767 {
-                return Err(TryGetError::ValueTooLarge);
-            }
-            if self.remaining() < len as usize {
-                return Err(TryGetError::NotEnoughBytes);
-            }
-
-            let mut result = String::with_capacity(len as usize);
-            self.take(len as u64)
-                .read_to_string(&mut result)
-                .map_err(|_| TryGetError::NotEnoughBytes)?;
-
-            return Ok(result);
+        let len = self.try_get_var_int()?;
+        // Check that the client isn't trying
+        // to make the server allocate ridiculous
+        // amounts of memory
+        if len > 32767 {
+            return Err(TryGetError::ValueTooLarge);
+        }
+        let len = len as usize;
+        if self.remaining() < len {
+            return Err(TryGetError::NotEnoughBytes);
         }
 
-        Err(TryGetError::NotEnoughBytes)
+        // Validate the string where it lies in the buffer, so
+        // the only allocation is the returned `String`. Buffers
+        // which are not contiguous have to be copied out first.
+        let chunk = Buf::bytes(self);
+        let result = if chunk.len() >= len {
+            let result = std::str::from_utf8(&chunk[..len]).map(String::from);
+            self.advance(len);
+            result.map_err(|_| TryGetError::InvalidString)?
+        } else {
+            let mut bytes = vec![0; len];
+            self.copy_to_slice(&mut bytes);
+            String::from_utf8(bytes).map_err(|_| TryGetError::InvalidString)?
+        };
+
+        Ok(result)
     }
 
     fn try_get_position(&mut self) -> Result<BlockPosition, TryGetError> {
@@ -195,29 +269,44 @@ impl<B: Buf + Read> McTypeRead for B {
     }
 
     fn try_get_uuid(&mut self) -> anyhow::Result<Uuid> {
+        if self.remaining() < 16 {
+            return Err(TryGetError::NotEnoughBytes.into());
+        }
         let mut bytes = [0u8; 16];
-        self.read_exact(&mut bytes)?;
+        self.copy_to_slice(&mut bytes);
         Ok(Uuid::from_bytes(bytes))
     }
 
     fn try_get_nbt<D: DeserializeOwned>(&mut self) -> Result<D, nbt::Error> {
-        nbt::from_reader(self)
+        nbt::from_reader(self.reader())
     }
 
     fn try_get_slot(&mut self) -> Result<Option<ItemStack>, TryGetError> {
-        let present = self.try_get_bool()?;
-
-        if !present {
-            return Ok(None);
-        }
-
-        let id = self.try_get_var_int()?;
+        let id = match slot_format() {
+            SlotFormat::Present => {
+                if !self.try_get_bool()? {
+                    return Ok(None);
+                }
+                self.try_get_var_int()?
+            }
+            SlotFormat::ShortId => match self.try_get_i16()? {
+                -1 => return Ok(None),
+                id => i32::from(id),
+            },
+        };
         let ty = Item::from_native_protocol_id(id).ok_or(TryGetError::InvalidValue(id))?;
         let amount = self.try_get_i8()? as u8;
 
-        // TODO NBT support
+        let mut stack = ItemStack::new(ty, amount);
+        if Buf::bytes(self).first() == Some(&0x00) {
+            // TAG_End: no compound
+            self.advance(1);
+        } else {
+            let nbt: ItemNbt = self.try_get_nbt().map_err(|_| TryGetError::InvalidNbt)?;
+            stack.set_nbt(nbt);
+        }
 
-        Ok(Some(ItemStack::new(ty, amount)))
+        Ok(Some(stack))
     }
 }
 
@@ -246,10 +335,10 @@ where
 
 impl<B> EntityMetaRead for B
 where
-    B: Buf + std::io::Read,
+    B: Buf,
 {
     fn try_get_metadata(&mut self) -> anyhow::Result<EntityMetadata> {
-        let mut values = BTreeMap::new();
+        let mut metadata = EntityMetadata::new();
 
         while self.has_remaining() {
             let index = self.try_get_u8()?;
@@ -259,10 +348,10 @@ where
             }
 
             let entry = try_get_entry(self)?;
-            values.insert(index, entry);
+            metadata.values.insert(index, entry);
         }
 
-        Ok(EntityMetadata { values })
+        Ok(metadata)
     }
 }
 
@@ -287,7 +376,7 @@ where
             }
         }
         MetaEntry::Slot(slot) => {
-            buf.push_slot(*slot);
+            buf.push_slot(slot.as_ref());
         }
         MetaEntry::Boolean(x) => buf.push_bool(*x),
         MetaEntry::Rotation(x, y, z) => {
@@ -374,6 +463,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use feather_items::Tag;
     use std::io::Cursor;
 
     #[test]
@@ -383,4 +473,87 @@ mod tests {
         buf.extend_from_slice(&[0xff, 0x01]);
         assert_eq!(Cursor::new(&buf).try_get_var_int(), Ok(255));
     }
+
+    #[test]
+    fn slot_round_trip() {
+        let mut sword = ItemStack::new(Item::DiamondSword, 1);
+        sword.set_damage(100);
+        sword.set_display_name(Some(String::from("{\"text\":\"Slicer\"}")));
+        // Tags the server does not understand are kept.
+        sword.modify_nbt(|nbt| {
+            nbt.other.insert(String::from("RepairCost"), Tag::Int(3));
+            nbt.other.insert(
+                String::from("SkullOwner"),
+                Tag::Compound(vec![(
+                    String::from("Name"),
+                    Tag::String(String::from("Notch")),
+                )]),
+            );
+        });
+        let plain = ItemStack::new(Item::Cobblestone, 12);
+
+        let mut buf = BytesMut::new();
+        buf.push_slot(Some(&sword));
+        buf.push_slot(Some(&plain));
+        buf.push_slot(None);
+
+        let mut cursor = Cursor::new(&buf);
+        assert_eq!(cursor.try_get_slot(), Ok(Some(sword)));
+        assert_eq!(cursor.try_get_slot(), Ok(Some(plain)));
+        assert_eq!(cursor.try_get_slot(), Ok(None));
+        assert_eq!(cursor.remaining(), 0);
+    }
+
+    #[test]
+    fn short_id_slots() {
+        let stone = ItemStack::new(Item::Stone, 3);
+
+        let mut buf = BytesMut::new();
+        with_slot_format(SlotFormat::ShortId, || {
+            buf.push_slot(Some(&stone));
+            buf.push_slot(None);
+        });
+
+        let id = Item::Stone.native_protocol_id() as i16;
+        let mut expected = BytesMut::new();
+        expected.push_i16(id);
+        expected.push_i8(3);
+        expected.push_i8(0x00);
+        expected.push_i16(-1);
+        assert_eq!(buf, expected);
+
+        let mut cursor = Cursor::new(&buf);
+        with_slot_format(SlotFormat::ShortId, || {
+            assert_eq!(cursor.try_get_slot(), Ok(Some(stone)));
+            assert_eq!(cursor.try_get_slot(), Ok(None));
+        });
+        assert_eq!(cursor.remaining(), 0);
+
+        // The format is restored afterwards.
+        assert_eq!(slot_format(), SlotFormat::Present);
+    }
+
+    #[test]
+    fn read_string() {
+        let mut buf = BytesMut::new();
+        buf.push_string("Feather");
+        buf.push_var_int(2);
+        buf.extend_from_slice(&[0xC3, 0x28]);
+
+        let mut bytes = buf.freeze();
+        assert_eq!(bytes.try_get_string(), Ok(String::from("Feather")));
+        assert_eq!(bytes.try_get_string(), Err(TryGetError::InvalidString));
+    }
+
+    #[test]
+    fn read_bytes() {
+        let mut bytes = Bytes::from_static(&[1, 2, 3, 4, 5]);
+        let start = bytes.as_ptr();
+
+        let first = bytes.try_get_bytes(3).unwrap();
+        assert_eq!(first.as_ref(), &[1, 2, 3]);
+        assert_eq!(first.as_ptr(), start);
+        assert_eq!(bytes.try_get_bytes(3), Err(TryGetError::NotEnoughBytes));
+        assert_eq!(bytes.as_ref(), &[4, 5]);
+    }
 }
@@ -1,6 +1,5 @@
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use std::any::Any;
-use std::io::Cursor;
 
 use crate::packets::IMPL_MAP;
 use ahash::AHashMap;
@@ -26,7 +25,10 @@ where
 }
 
 pub trait Packet: AsAny + IntoAny + Send + Sync + Any {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()>;
+    /// Reads the packet's fields from `buf`, which holds the
+    /// packet's data after its ID. Byte arrays may be split off
+    /// `buf` rather than copied.
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()>;
     fn write_to(&self, buf: &mut BytesMut);
     fn ty(&self) -> PacketType;
     fn ty_sized() -> PacketType
@@ -205,6 +207,7 @@ pub enum PacketType {
     UpdateScore,
     SpawnPosition,
     TimeUpdate,
+    Title,
     StopSound,
     SoundEffect,
     PlayerListHeaderAndFooter,
@@ -460,6 +463,11 @@ static PACKET_ID_MAPPINGS: Lazy<AHashMap<PacketId, PacketType>> = Lazy::new(|| {
         PacketType::SpawnObject,
     );
 
+    m.insert(
+        PacketId(0x01, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::SpawnExperienceOrb,
+    );
+
     m.insert(
         PacketId(0x02, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::SpawnGlobalEntity,
@@ -474,21 +482,83 @@ static PACKET_ID_MAPPINGS: Lazy<AHashMap<PacketId, PacketType>> = Lazy::new(|| {
         PacketId(0x06, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::AnimationClientbound,
     );
+    m.insert(
+        PacketId(0x07, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::Statistics,
+    );
+    m.insert(
+        PacketId(0x09, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::UpdateBlockEntity,
+    );
 
+    m.insert(
+        PacketId(0x0D, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::ServerDifficulty,
+    );
     m.insert(
         PacketId(0x0E, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::ChatMessageClientbound,
     );
+    m.insert(
+        PacketId(0x10, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::TabCompleteClientbound,
+    );
+    m.insert(
+        PacketId(0x11, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::DeclareCommands,
+    );
+
+    m.insert(
+        PacketId(0x12, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::ConfirmTransactionClientbound,
+    );
+    m.insert(
+        PacketId(0x13, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::CloseWindowClientbound,
+    );
+    m.insert(
+        PacketId(0x14, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::OpenWindow,
+    );
+    m.insert(
+        PacketId(0x15, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::WindowItems,
+    );
+    m.insert(
+        PacketId(0x16, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::WindowProperty,
+    );
 
     m.insert(
         PacketId(0x17, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::SetSlot,
     );
 
+    m.insert(
+        PacketId(0x18, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::SetCooldown,
+    );
+    m.insert(
+        PacketId(0x19, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::PluginMessageClientbound,
+    );
+    m.insert(
+        PacketId(0x1A, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::NamedSoundEffect,
+    );
+
     m.insert(
         PacketId(0x1B, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::DisconnectPlay,
     );
+    m.insert(
+        PacketId(0x1C, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::EntityStatus,
+    );
+    m.insert(
+        PacketId(0x1E, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::Explosion,
+    );
 
     m.insert(
         PacketId(0x1F, PacketDirection::Clientbound, PacketStage::Play),
@@ -509,6 +579,10 @@ static PACKET_ID_MAPPINGS: Lazy<AHashMap<PacketId, PacketType>> = Lazy::new(|| {
         PacketId(0x0B, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::BlockChange,
     );
+    m.insert(
+        PacketId(0x0F, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::MultiBlockChange,
+    );
 
     m.insert(
         PacketId(0x20, PacketDirection::Clientbound, PacketStage::Play),
@@ -519,12 +593,26 @@ static PACKET_ID_MAPPINGS: Lazy<AHashMap<PacketId, PacketType>> = Lazy::new(|| {
         PacketId(0x22, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::ChunkData,
     );
+    m.insert(
+        PacketId(0x23, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::Effect,
+    );
+
+    m.insert(
+        PacketId(0x24, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::Particle,
+    );
 
     m.insert(
         PacketId(0x25, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::JoinGame,
     );
 
+    m.insert(
+        PacketId(0x26, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::MapData,
+    );
+
     m.insert(
         PacketId(0x28, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::EntityRelativeMove,
@@ -540,6 +628,21 @@ static PACKET_ID_MAPPINGS: Lazy<AHashMap<PacketId, PacketType>> = Lazy::new(|| {
         PacketType::EntityLook,
     );
 
+    m.insert(
+        PacketId(0x2D, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::CraftRecipeResponse,
+    );
+
+    m.insert(
+        PacketId(0x2E, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::PlayerAbilitiesClientbound,
+    );
+
+    m.insert(
+        PacketId(0x2F, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::CombatEvent,
+    );
+
     m.insert(
         PacketId(0x30, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::PlayerInfo,
@@ -550,21 +653,45 @@ static PACKET_ID_MAPPINGS: Lazy<AHashMap<PacketId, PacketType>> = Lazy::new(|| {
         PacketType::PlayerPositionAndLookClientbound,
     );
 
+    m.insert(
+        PacketId(0x34, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::UnlockRecipes,
+    );
+
     m.insert(
         PacketId(0x35, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::DestroyEntities,
     );
+    m.insert(
+        PacketId(0x36, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::RemoveEntityEffect,
+    );
 
     m.insert(
         PacketId(0x37, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::ResourcePackSend,
     );
 
+    m.insert(
+        PacketId(0x38, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::Respawn,
+    );
+
     m.insert(
         PacketId(0x39, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::EntityHeadLook,
     );
 
+    m.insert(
+        PacketId(0x3C, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::Camera,
+    );
+
+    m.insert(
+        PacketId(0x3E, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::DisplayScoreboard,
+    );
+
     m.insert(
         PacketId(0x3F, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::EntityMetadata,
@@ -579,6 +706,20 @@ static PACKET_ID_MAPPINGS: Lazy<AHashMap<PacketId, PacketType>> = Lazy::new(|| {
         PacketId(0x42, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::EntityEquipment,
     );
+    m.insert(
+        PacketId(0x44, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::UpdateHealth,
+    );
+
+    m.insert(
+        PacketId(0x45, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::ScoreboardObjective,
+    );
+
+    m.insert(
+        PacketId(0x48, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::UpdateScore,
+    );
 
     m.insert(
         PacketId(0x49, PacketDirection::Clientbound, PacketStage::Play),
@@ -590,10 +731,31 @@ static PACKET_ID_MAPPINGS: Lazy<AHashMap<PacketId, PacketType>> = Lazy::new(|| {
         PacketType::TimeUpdate,
     );
 
+    m.insert(
+        PacketId(0x4B, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::Title,
+    );
+
     m.insert(
         PacketId(0x4F, PacketDirection::Clientbound, PacketStage::Play),
         PacketType::CollectItem,
     );
+    m.insert(
+        PacketId(0x51, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::Advancements,
+    );
+    m.insert(
+        PacketId(0x52, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::EntityProperties,
+    );
+    m.insert(
+        PacketId(0x53, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::EntityEffect,
+    );
+    m.insert(
+        PacketId(0x54, PacketDirection::Clientbound, PacketStage::Play),
+        PacketType::DeclareRecipes,
+    );
 
     m
 });
@@ -1,9 +1,10 @@
 use crate::bytes_ext::{BytesExt, BytesMutExt};
-use crate::mctypes::{EntityMetaRead, EntityMetaWrite, McTypeRead, McTypeWrite};
+use crate::mctypes::{BytesRead, EntityMetaRead, EntityMetaWrite, McTypeRead, McTypeWrite};
 use crate::packet::{AsAny, PacketBuilder};
 use crate::{Packet, PacketType};
 use ahash::AHashMap;
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{BufMut, Bytes, BytesMut};
+use feather_blocks::BlockId;
 use feather_chunk::Chunk;
 use feather_codegen::{AsAny, Packet};
 use feather_entity_metadata::EntityMetadata;
@@ -14,8 +15,6 @@ use num_traits::{FromPrimitive, ToPrimitive};
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use std::any::Any;
-use std::io::Cursor;
-use std::io::Read;
 use std::sync::Arc;
 use thiserror::Error;
 use uuid::Uuid;
@@ -270,6 +269,11 @@ pub static IMPL_MAP: Lazy<AHashMap<PacketType, PacketBuilder>> = Lazy::new(|| {
         BossBar,
         ServerDifficulty,
         ChatMessageClientbound,
+        MultiBlockChange,
+        TabCompleteClientbound,
+        DeclareCommands,
+        ConfirmTransactionClientbound,
+        CloseWindowClientbound,
         OpenWindow,
         WindowItems,
         WindowProperty,
@@ -288,26 +292,39 @@ pub static IMPL_MAP: Lazy<AHashMap<PacketType, PacketBuilder>> = Lazy::new(|| {
         Effect,
         Particle,
         JoinGame,
+        MapData,
         EntityRelativeMove,
         EntityLookAndRelativeMove,
         EntityLook,
         VehicleMoveClientbound,
         OpenSignEditor,
         CraftRecipeResponse,
+        PlayerAbilitiesClientbound,
         CombatEvent,
         PlayerInfo,
         PlayerPositionAndLookClientbound,
         UseBed,
+        UnlockRecipes,
         DestroyEntities,
         RemoveEntityEffect,
         ResourcePackSend,
         Respawn,
         EntityHeadLook,
+        Camera,
+        DisplayScoreboard,
         EntityVelocity,
         EntityEquipment,
+        UpdateHealth,
+        ScoreboardObjective,
+        UpdateScore,
         SpawnPosition,
         TimeUpdate,
+        Title,
         CollectItem,
+        Advancements,
+        EntityProperties,
+        EntityEffect,
+        DeclareRecipes,
         Response,
         Pong,
     );
@@ -337,6 +354,8 @@ pub enum Error {
     InsufficientArrayLength,
     #[error("invalid handshake next state {0}")]
     InvalidHandshakeState(i32),
+    #[error("invalid recipe book data type {0}")]
+    InvalidRecipeBookData(i32),
 }
 
 // SERVERBOUND
@@ -350,7 +369,7 @@ pub struct Handshake {
 }
 
 impl Packet for Handshake {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.protocol_version = buf.try_get_var_int()? as u32;
         self.server_address = buf.try_get_string()?;
         self.server_port = buf.try_get_u16()?;
@@ -413,37 +432,27 @@ pub struct LoginStart {
 #[derive(Default, AsAny, Clone)]
 pub struct EncryptionResponse {
     pub secret_length: VarInt,
-    pub secret: Vec<u8>,
+    pub secret: Bytes,
     pub verify_token_length: VarInt,
-    pub verify_token: Vec<u8>,
+    pub verify_token: Bytes,
 }
 
 impl Packet for EncryptionResponse {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.secret_length = buf.try_get_var_int()?;
-
-        let mut secret = vec![];
-        for _ in 0..self.secret_length {
-            secret.push(buf.get_u8());
-        }
-        self.secret = secret;
+        self.secret = buf.try_get_bytes(self.secret_length as usize)?;
 
         self.verify_token_length = buf.try_get_var_int()?;
+        self.verify_token = buf.try_get_bytes(self.verify_token_length as usize)?;
 
-        let mut verify_token = vec![];
-        for _ in 0..self.secret_length {
-            verify_token.push(buf.get_u8());
-        }
-
-        self.verify_token = verify_token;
         Ok(())
     }
 
     fn write_to(&self, buf: &mut BytesMut) {
         buf.push_var_int(self.secret.len() as i32);
-        buf.put(self.secret.as_slice());
+        buf.extend_from_slice(&self.secret);
         buf.push_var_int(self.verify_token.len() as i32);
-        buf.put(self.verify_token.as_slice());
+        buf.extend_from_slice(&self.verify_token);
     }
 
     fn ty(&self) -> PacketType {
@@ -539,25 +548,20 @@ pub struct CloseWindowServerbound {
 #[derive(Default, AsAny, Clone)]
 pub struct PluginMessageServerbound {
     pub channel: String,
-    pub data: Vec<u8>,
+    pub data: Bytes,
 }
 
 impl Packet for PluginMessageServerbound {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.channel = buf.try_get_string()?;
-
-        let mut data = Vec::with_capacity(buf.remaining());
-        buf.read(&mut data)
-            .map_err(|_| Error::InsufficientArrayLength)?;
-        self.data = data;
+        self.data = buf.try_get_bytes(buf.len())?;
 
         Ok(())
     }
 
     fn write_to(&self, buf: &mut BytesMut) {
         buf.push_string(&self.channel);
-
-        buf.put(self.data.as_slice());
+        buf.extend_from_slice(&self.data);
     }
 
     fn ty(&self) -> PacketType {
@@ -596,7 +600,7 @@ pub struct UseEntity {
 }
 
 impl Packet for UseEntity {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.target = buf.try_get_var_int()?;
 
         let ty_id = buf.try_get_var_int()?;
@@ -740,7 +744,7 @@ pub struct PlayerDigging {
 }
 
 impl Packet for PlayerDigging {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.status = {
             let id = buf.try_get_var_int()?;
             match id {
@@ -810,7 +814,7 @@ pub struct EntityAction {
 }
 
 impl Packet for EntityAction {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.entity_id = buf.try_get_var_int()?;
         let action_id = buf.try_get_var_int()?;
         self.action_id =
@@ -869,10 +873,84 @@ pub struct SteerVehicle {
     pub flags: u8,
 }
 
-#[derive(Default, AsAny, Packet, Clone)]
+#[derive(Default, AsAny, Clone)]
 pub struct RecipeBookData {
-    pub ty: VarInt,
-    // TODO
+    pub ty: RecipeBookDataType,
+}
+
+impl Packet for RecipeBookData {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
+        let ty_id = buf.try_get_var_int()?;
+        self.ty = match ty_id {
+            0 => RecipeBookDataType::DisplayedRecipe(buf.try_get_string()?),
+            1 => RecipeBookDataType::States {
+                crafting_book_open: buf.try_get_bool()?,
+                crafting_filter_active: buf.try_get_bool()?,
+                smelting_book_open: buf.try_get_bool()?,
+                smelting_filter_active: buf.try_get_bool()?,
+            },
+            i => return Err(Error::InvalidRecipeBookData(i).into()),
+        };
+
+        Ok(())
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        match &self.ty {
+            RecipeBookDataType::DisplayedRecipe(recipe) => {
+                buf.push_var_int(0);
+                buf.push_string(recipe);
+            }
+            RecipeBookDataType::States {
+                crafting_book_open,
+                crafting_filter_active,
+                smelting_book_open,
+                smelting_filter_active,
+            } => {
+                buf.push_var_int(1);
+                buf.push_bool(*crafting_book_open);
+                buf.push_bool(*crafting_filter_active);
+                buf.push_bool(*smelting_book_open);
+                buf.push_bool(*smelting_filter_active);
+            }
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::RecipeBookData
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::RecipeBookData
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecipeBookDataType {
+    /// The player viewed the recipe with the given ID,
+    /// which should no longer be highlighted.
+    DisplayedRecipe(String),
+    /// The player opened or closed a recipe book
+    /// or toggled its filter.
+    States {
+        crafting_book_open: bool,
+        crafting_filter_active: bool,
+        smelting_book_open: bool,
+        smelting_filter_active: bool,
+    },
+}
+
+impl Default for RecipeBookDataType {
+    fn default() -> Self {
+        RecipeBookDataType::DisplayedRecipe(String::new())
+    }
 }
 
 #[derive(Default, AsAny, Packet, Clone)]
@@ -885,10 +963,46 @@ pub struct ResourcePackStatus {
     pub result: VarInt,
 }
 
-#[derive(Default, AsAny, Packet, Clone)]
+#[derive(Default, AsAny, Clone)]
 pub struct AdvancementTab {
     pub action: VarInt,
-    pub tab_id: String,
+    /// The tab opened, sent only for the "opened tab" action.
+    pub tab_id: Option<String>,
+}
+
+/// Advancement Tab action sent when a player opens a tab.
+const ADVANCEMENT_TAB_OPENED: VarInt = 0;
+
+impl Packet for AdvancementTab {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
+        self.action = buf.try_get_var_int()?;
+        if self.action == ADVANCEMENT_TAB_OPENED {
+            self.tab_id = Some(buf.try_get_string()?);
+        }
+        Ok(())
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_var_int(self.action);
+        if let Some(tab_id) = &self.tab_id {
+            buf.push_string(tab_id);
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::AdvancementTab
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::AdvancementTab
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
 }
 
 #[derive(Default, AsAny, Packet, Clone)]
@@ -964,7 +1078,7 @@ pub struct AnimationServerbound {
 }
 
 impl Packet for AnimationServerbound {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         let hand_id = buf.try_get_var_int()?;
         self.hand = match Hand::from_i32(hand_id) {
             Some(hand) => hand,
@@ -1039,7 +1153,7 @@ pub struct PlayerBlockPlacement {
 }
 
 impl Packet for PlayerBlockPlacement {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.location = buf.try_get_position()?;
         let face_id = buf.try_get_var_int()?;
         self.face = Face::from_i32(face_id).ok_or(Error::InvalidFace(face_id))?;
@@ -1094,18 +1208,14 @@ pub struct EncryptionRequest {
 }
 
 impl Packet for EncryptionRequest {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.server_id = buf.try_get_string()?;
 
         let pubkey_len = buf.try_get_var_int()?;
-        for _ in 0..pubkey_len {
-            self.public_key.push(buf.try_get_u8()?);
-        }
+        self.public_key = buf.try_get_bytes(pubkey_len as usize)?.to_vec();
 
         let token_len = buf.try_get_var_int()?;
-        for _ in 0..token_len {
-            self.verify_token.push(buf.try_get_u8()?);
-        }
+        self.verify_token = buf.try_get_bytes(token_len as usize)?.to_vec();
 
         Ok(())
     }
@@ -1240,7 +1350,7 @@ pub struct AnimationClientbound {
 }
 
 impl Packet for AnimationClientbound {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.entity_id = buf.try_get_var_int()?;
         self.animation =
             ClientboundAnimation::from_u8(buf.try_get_u8()?).ok_or(Error::InvalidUseEntity(0))?;
@@ -1270,23 +1380,25 @@ impl Packet for AnimationClientbound {
 
 #[derive(Default, AsAny, Clone)]
 pub struct Statistics {
-    pub statistics: Vec<(VarInt, VarInt)>,
-    pub value: VarInt,
+    /// Category ID, statistic ID and value of each statistic.
+    pub statistics: Vec<(VarInt, VarInt, VarInt)>,
 }
 
 impl Packet for Statistics {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         let num_statistics = buf.try_get_var_int()?;
 
-        if num_statistics > 255 {
+        if num_statistics < 0 {
             return Err(Error::InsufficientArrayLength.into());
         }
 
         for _ in 0..num_statistics {
-            self.statistics
-                .push((buf.try_get_var_int()?, buf.try_get_var_int()?));
+            self.statistics.push((
+                buf.try_get_var_int()?,
+                buf.try_get_var_int()?,
+                buf.try_get_var_int()?,
+            ));
         }
-        self.value = buf.try_get_var_int()?;
         Ok(())
     }
 
@@ -1295,8 +1407,8 @@ impl Packet for Statistics {
         for stat in &self.statistics {
             buf.push_var_int(stat.0);
             buf.push_var_int(stat.1);
+            buf.push_var_int(stat.2);
         }
-        buf.push_var_int(self.value);
     }
 
     fn ty(&self) -> PacketType {
@@ -1322,11 +1434,60 @@ pub struct BlockBreakAnimation {
     pub destroy_stage: i8,
 }
 
-#[derive(Default, AsAny, Packet, Clone)]
+#[derive(Default, AsAny, Clone)]
 pub struct UpdateBlockEntity {
     pub location: BlockPosition,
+    /// What kind of block entity is updated, e.g. 1
+    /// to set the mob displayed in a spawner.
     pub action: u8,
-    // TODO pub data: NbtTag
+    /// The block entity's NBT compound, already encoded.
+    /// Empty if the block entity is removed.
+    pub data: Vec<u8>,
+}
+
+impl UpdateBlockEntity {
+    /// Creates a packet setting the block entity at `location`
+    /// to the NBT representation of `data`.
+    pub fn new<T: serde::Serialize>(location: BlockPosition, action: u8, data: &T) -> Self {
+        let mut buf = BytesMut::new();
+        buf.push_nbt(data);
+        Self {
+            location,
+            action,
+            data: buf.to_vec(),
+        }
+    }
+}
+
+impl Packet for UpdateBlockEntity {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
+        unimplemented!()
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_position(&self.location);
+        buf.push_u8(self.action);
+        if self.data.is_empty() {
+            buf.push_i8(0x00); // TAG_End
+        } else {
+            buf.extend_from_slice(&self.data);
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::UpdateBlockEntity
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::UpdateBlockEntity
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
 }
 
 #[derive(Default, AsAny, Packet, Clone)]
@@ -1350,7 +1511,7 @@ pub struct BossBar {
 }
 
 impl Packet for BossBar {
-    fn read_from(&mut self, _buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
         unimplemented!()
     }
 
@@ -1471,9 +1632,250 @@ pub struct ChatMessageClientbound {
     pub position: u8,
 }
 
-// TODO MultiBlockChange
-// TODO TabCompleteClientbound
-// TODO DeclareCommands
+/// A change to a single block in `MultiBlockChange`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockChangeRecord {
+    /// X coordinate within the chunk in the high four
+    /// bits, and Z coordinate in the low four bits.
+    pub horizontal_position: u8,
+    pub y: u8,
+    pub block_id: VarInt,
+}
+
+impl BlockChangeRecord {
+    /// Creates a record of the block at `pos` being changed to `block_id`.
+    pub fn new(pos: BlockPosition, block_id: VarInt) -> Self {
+        Self {
+            horizontal_position: ((pos.x & 0x0F) << 4 | (pos.z & 0x0F)) as u8,
+            y: pos.y as u8,
+            block_id,
+        }
+    }
+}
+
+/// Changes to several blocks within one chunk.
+#[derive(Default, AsAny, Clone)]
+pub struct MultiBlockChange {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    pub records: Vec<BlockChangeRecord>,
+}
+
+impl Packet for MultiBlockChange {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
+        self.chunk_x = buf.try_get_i32()?;
+        self.chunk_z = buf.try_get_i32()?;
+
+        let count = buf.try_get_var_int()?;
+        for _ in 0..count {
+            self.records.push(BlockChangeRecord {
+                horizontal_position: buf.try_get_u8()?,
+                y: buf.try_get_u8()?,
+                block_id: buf.try_get_var_int()?,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_i32(self.chunk_x);
+        buf.push_i32(self.chunk_z);
+
+        buf.push_var_int(self.records.len() as i32);
+        for record in &self.records {
+            buf.push_u8(record.horizontal_position);
+            buf.push_u8(record.y);
+            buf.push_var_int(record.block_id);
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::MultiBlockChange
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::MultiBlockChange
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
+
+#[derive(Default, AsAny, Clone)]
+pub struct TabCompleteClientbound {
+    pub transaction_id: VarInt,
+    /// Start of the text which the suggestions replace.
+    pub start: VarInt,
+    /// Length of the text which the suggestions replace.
+    pub length: VarInt,
+    /// Suggestions, each with an optional JSON tooltip.
+    pub matches: Vec<(String, Option<String>)>,
+}
+
+impl Packet for TabCompleteClientbound {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
+        self.transaction_id = buf.try_get_var_int()?;
+        self.start = buf.try_get_var_int()?;
+        self.length = buf.try_get_var_int()?;
+
+        let count = buf.try_get_var_int()?;
+        for _ in 0..count {
+            let suggestion = buf.try_get_string()?;
+            let tooltip = if buf.try_get_bool()? {
+                Some(buf.try_get_string()?)
+            } else {
+                None
+            };
+            self.matches.push((suggestion, tooltip));
+        }
+
+        Ok(())
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_var_int(self.transaction_id);
+        buf.push_var_int(self.start);
+        buf.push_var_int(self.length);
+
+        buf.push_var_int(self.matches.len() as i32);
+        for (suggestion, tooltip) in &self.matches {
+            buf.push_string(suggestion);
+            buf.push_bool(tooltip.is_some());
+            if let Some(tooltip) = tooltip {
+                buf.push_string(tooltip);
+            }
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::TabCompleteClientbound
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::TabCompleteClientbound
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
+
+/// A node in the command tree sent in `DeclareCommands`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandNode {
+    pub kind: CommandNodeKind,
+    /// Whether the command is complete at this node.
+    pub executable: bool,
+    /// Indices of the node's children.
+    pub children: Vec<VarInt>,
+    /// Index of the node which parsing continues at.
+    pub redirect: Option<VarInt>,
+    /// Identifier of the suggestions for an argument,
+    /// such as `minecraft:ask_server`.
+    pub suggestions: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandNodeKind {
+    Root,
+    Literal(String),
+    Argument {
+        name: String,
+        /// Identifier of the parser, such as `brigadier:string`.
+        parser: String,
+        /// Properties of the parser, already encoded.
+        properties: Vec<u8>,
+    },
+}
+
+const COMMAND_NODE_LITERAL: u8 = 0x01;
+const COMMAND_NODE_ARGUMENT: u8 = 0x02;
+const COMMAND_NODE_EXECUTABLE: u8 = 0x04;
+const COMMAND_NODE_REDIRECT: u8 = 0x08;
+const COMMAND_NODE_SUGGESTIONS: u8 = 0x10;
+
+#[derive(Default, AsAny, Clone)]
+pub struct DeclareCommands {
+    pub nodes: Vec<CommandNode>,
+    pub root_index: VarInt,
+}
+
+impl Packet for DeclareCommands {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
+        unimplemented!()
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_var_int(self.nodes.len() as i32);
+        for node in &self.nodes {
+            let mut flags = match node.kind {
+                CommandNodeKind::Root => 0,
+                CommandNodeKind::Literal(_) => COMMAND_NODE_LITERAL,
+                CommandNodeKind::Argument { .. } => COMMAND_NODE_ARGUMENT,
+            };
+            if node.executable {
+                flags |= COMMAND_NODE_EXECUTABLE;
+            }
+            if node.redirect.is_some() {
+                flags |= COMMAND_NODE_REDIRECT;
+            }
+            if node.suggestions.is_some() {
+                flags |= COMMAND_NODE_SUGGESTIONS;
+            }
+            buf.push_u8(flags);
+
+            buf.push_var_int(node.children.len() as i32);
+            for &child in &node.children {
+                buf.push_var_int(child);
+            }
+            if let Some(redirect) = node.redirect {
+                buf.push_var_int(redirect);
+            }
+
+            match &node.kind {
+                CommandNodeKind::Root => (),
+                CommandNodeKind::Literal(name) => buf.push_string(name),
+                CommandNodeKind::Argument {
+                    name,
+                    parser,
+                    properties,
+                } => {
+                    buf.push_string(name);
+                    buf.push_string(parser);
+                    buf.extend_from_slice(properties);
+                }
+            }
+
+            if let Some(suggestions) = &node.suggestions {
+                buf.push_string(suggestions);
+            }
+        }
+        buf.push_var_int(self.root_index);
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::DeclareCommands
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::DeclareCommands
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
 
 #[derive(Default, AsAny, Packet, Clone)]
 pub struct ConfirmTransactionClientbound {
@@ -1482,6 +1884,11 @@ pub struct ConfirmTransactionClientbound {
     pub accepted: bool,
 }
 
+#[derive(Default, AsAny, Packet, Clone)]
+pub struct CloseWindowClientbound {
+    pub window_id: u8,
+}
+
 #[derive(Default, AsAny, Packet, Clone)]
 pub struct OpenWindow {
     pub window_id: u8,
@@ -1498,7 +1905,7 @@ pub struct WindowItems {
 }
 
 impl Packet for WindowItems {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.window_id = buf.try_get_u8()?;
         let num_slots = buf.try_get_i16()?;
 
@@ -1514,7 +1921,7 @@ impl Packet for WindowItems {
         buf.push_i16(self.slots.len() as i16);
 
         for slot in &self.slots {
-            buf.push_slot(*slot);
+            buf.push_slot(slot.as_ref());
         }
     }
 
@@ -1561,10 +1968,9 @@ pub struct PluginMessageClientbound {
 }
 
 impl Packet for PluginMessageClientbound {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.channel = buf.try_get_string()?;
-
-        self.data.extend_from_slice(*buf.get_ref());
+        self.data = buf.try_get_bytes(buf.len())?.to_vec();
 
         Ok(())
     }
@@ -1632,7 +2038,7 @@ pub struct Explosion {
 }
 
 impl Packet for Explosion {
-    fn read_from(&mut self, _buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
         unimplemented!()
     }
 
@@ -1694,7 +2100,7 @@ pub struct ChunkData {
 }
 
 impl Packet for ChunkData {
-    fn read_from(&mut self, _buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
         unimplemented!()
     }
 
@@ -1797,44 +2203,400 @@ pub struct Effect {
     pub disable_relative_volume: bool,
 }
 
-#[derive(Default, AsAny, Packet, Clone)]
+#[derive(Default, AsAny, Clone)]
 pub struct Particle {
-    pub particle_id: i32,
+    pub kind: ParticleKind,
+    /// Whether clients display the particle from up to 65536
+    /// blocks away, rather than 32.
     pub long_distance: bool,
     pub x: f32,
     pub y: f32,
     pub z: f32,
+    /// Standard deviations of the particles' positions.
     pub offset_x: f32,
+    pub offset_y: f32,
     pub offset_z: f32,
-    pub particle_data: f32,
-    pub particle_count: i32,
-    // TODO data
+    /// Speed of the particles, or for some kinds of
+    /// particle, another property such as color.
+    pub speed: f32,
+    pub count: i32,
 }
 
-#[derive(Default, AsAny, Packet, Clone, Debug)]
-pub struct JoinGame {
-    pub entity_id: i32,
-    pub gamemode: u8,
-    pub dimension: i32,
-    pub difficulty: u8,
-    pub max_players: u8,
-    pub level_type: String,
-    pub reduced_debug_info: bool,
+impl Packet for Particle {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
+        unimplemented!()
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_i32(self.kind.id());
+        buf.push_bool(self.long_distance);
+        buf.push_f32(self.x);
+        buf.push_f32(self.y);
+        buf.push_f32(self.z);
+        buf.push_f32(self.offset_x);
+        buf.push_f32(self.offset_y);
+        buf.push_f32(self.offset_z);
+        buf.push_f32(self.speed);
+        buf.push_i32(self.count);
+
+        match &self.kind {
+            ParticleKind::Block(block) | ParticleKind::FallingDust(block) => {
+                buf.push_var_int(i32::from(block.vanilla_id()))
+            }
+            ParticleKind::Dust {
+                red,
+                green,
+                blue,
+                scale,
+            } => {
+                buf.push_f32(*red);
+                buf.push_f32(*green);
+                buf.push_f32(*blue);
+                buf.push_f32(*scale);
+            }
+            ParticleKind::Item(item) => buf.push_slot(Some(item)),
+            ParticleKind::Simple(_) => (),
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::Particle
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::Particle
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
 }
 
-// TODO MapData
-// TODO EntityPacket
+/// A kind of particle, along with the data
+/// which some kinds of particle carry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParticleKind {
+    /// Pieces of a block, as shown when it is broken.
+    Block(BlockId),
+    /// Colored redstone dust. Colors range from 0 to 1.
+    Dust {
+        red: f32,
+        green: f32,
+        blue: f32,
+        scale: f32,
+    },
+    FallingDust(BlockId),
+    /// Pieces of an item, as shown when it is eaten or breaks.
+    Item(ItemStack),
+    /// Any other particle, which carries no data.
+    Simple(SimpleParticle),
+}
+
+impl Default for ParticleKind {
+    fn default() -> Self {
+        ParticleKind::Simple(SimpleParticle::Poof)
+    }
+}
 
-#[derive(Default, AsAny, Packet, Clone)]
-pub struct EntityRelativeMove {
-    pub entity_id: VarInt,
-    pub delta_x: i16,
-    pub delta_y: i16,
-    pub delta_z: i16,
-    pub on_ground: bool,
+impl From<SimpleParticle> for ParticleKind {
+    fn from(particle: SimpleParticle) -> Self {
+        ParticleKind::Simple(particle)
+    }
 }
 
-#[derive(Default, AsAny, Packet, Clone)]
+impl ParticleKind {
+    /// Returns the protocol ID of this kind of particle.
+    pub fn id(&self) -> i32 {
+        match self {
+            ParticleKind::Block(_) => 3,
+            ParticleKind::Dust { .. } => 11,
+            ParticleKind::FallingDust(_) => 20,
+            ParticleKind::Item(_) => 27,
+            ParticleKind::Simple(particle) => *particle as i32,
+        }
+    }
+
+    /// Returns the identifier of this kind of particle,
+    /// such as `minecraft:flame`.
+    pub fn identifier(&self) -> &'static str {
+        match self {
+            ParticleKind::Block(_) => "minecraft:block",
+            ParticleKind::Dust { .. } => "minecraft:dust",
+            ParticleKind::FallingDust(_) => "minecraft:falling_dust",
+            ParticleKind::Item(_) => "minecraft:item",
+            ParticleKind::Simple(particle) => particle.identifier(),
+        }
+    }
+}
+
+/// A particle which carries no data, with its protocol ID.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, FromPrimitive, ToPrimitive)]
+pub enum SimpleParticle {
+    AmbientEntityEffect = 0,
+    AngryVillager = 1,
+    Barrier = 2,
+    Bubble = 4,
+    Cloud = 5,
+    Crit = 6,
+    DamageIndicator = 7,
+    DragonBreath = 8,
+    DrippingLava = 9,
+    DrippingWater = 10,
+    Effect = 12,
+    ElderGuardian = 13,
+    EnchantedHit = 14,
+    Enchant = 15,
+    EndRod = 16,
+    EntityEffect = 17,
+    ExplosionEmitter = 18,
+    Explosion = 19,
+    Firework = 21,
+    Fishing = 22,
+    Flame = 23,
+    HappyVillager = 24,
+    Heart = 25,
+    InstantEffect = 26,
+    ItemSlime = 28,
+    ItemSnowball = 29,
+    LargeSmoke = 30,
+    Lava = 31,
+    Mycelium = 32,
+    Note = 33,
+    Poof = 34,
+    Portal = 35,
+    Rain = 36,
+    Smoke = 37,
+    Spit = 38,
+    SquidInk = 39,
+    SweepAttack = 40,
+    TotemOfUndying = 41,
+    Underwater = 42,
+    Splash = 43,
+    Witch = 44,
+    BubblePop = 45,
+    CurrentDown = 46,
+    BubbleColumnUp = 47,
+    Nautilus = 48,
+    Dolphin = 49,
+}
+
+impl SimpleParticle {
+    /// Returns all particles which carry no data.
+    pub fn all() -> impl Iterator<Item = SimpleParticle> {
+        (0..=SimpleParticle::Dolphin as i32).filter_map(SimpleParticle::from_i32)
+    }
+
+    pub fn identifier(self) -> &'static str {
+        match self {
+            SimpleParticle::AmbientEntityEffect => "minecraft:ambient_entity_effect",
+            SimpleParticle::AngryVillager => "minecraft:angry_villager",
+            SimpleParticle::Barrier => "minecraft:barrier",
+            SimpleParticle::Bubble => "minecraft:bubble",
+            SimpleParticle::Cloud => "minecraft:cloud",
+            SimpleParticle::Crit => "minecraft:crit",
+            SimpleParticle::DamageIndicator => "minecraft:damage_indicator",
+            SimpleParticle::DragonBreath => "minecraft:dragon_breath",
+            SimpleParticle::DrippingLava => "minecraft:dripping_lava",
+            SimpleParticle::DrippingWater => "minecraft:dripping_water",
+            SimpleParticle::Effect => "minecraft:effect",
+            SimpleParticle::ElderGuardian => "minecraft:elder_guardian",
+            SimpleParticle::EnchantedHit => "minecraft:enchanted_hit",
+            SimpleParticle::Enchant => "minecraft:enchant",
+            SimpleParticle::EndRod => "minecraft:end_rod",
+            SimpleParticle::EntityEffect => "minecraft:entity_effect",
+            SimpleParticle::ExplosionEmitter => "minecraft:explosion_emitter",
+            SimpleParticle::Explosion => "minecraft:explosion",
+            SimpleParticle::Firework => "minecraft:firework",
+            SimpleParticle::Fishing => "minecraft:fishing",
+            SimpleParticle::Flame => "minecraft:flame",
+            SimpleParticle::HappyVillager => "minecraft:happy_villager",
+            SimpleParticle::Heart => "minecraft:heart",
+            SimpleParticle::InstantEffect => "minecraft:instant_effect",
+            SimpleParticle::ItemSlime => "minecraft:item_slime",
+            SimpleParticle::ItemSnowball => "minecraft:item_snowball",
+            SimpleParticle::LargeSmoke => "minecraft:large_smoke",
+            SimpleParticle::Lava => "minecraft:lava",
+            SimpleParticle::Mycelium => "minecraft:mycelium",
+            SimpleParticle::Note => "minecraft:note",
+            SimpleParticle::Poof => "minecraft:poof",
+            SimpleParticle::Portal => "minecraft:portal",
+            SimpleParticle::Rain => "minecraft:rain",
+            SimpleParticle::Smoke => "minecraft:smoke",
+            SimpleParticle::Spit => "minecraft:spit",
+            SimpleParticle::SquidInk => "minecraft:squid_ink",
+            SimpleParticle::SweepAttack => "minecraft:sweep_attack",
+            SimpleParticle::TotemOfUndying => "minecraft:totem_of_undying",
+            SimpleParticle::Underwater => "minecraft:underwater",
+            SimpleParticle::Splash => "minecraft:splash",
+            SimpleParticle::Witch => "minecraft:witch",
+            SimpleParticle::BubblePop => "minecraft:bubble_pop",
+            SimpleParticle::CurrentDown => "minecraft:current_down",
+            SimpleParticle::BubbleColumnUp => "minecraft:bubble_column_up",
+            SimpleParticle::Nautilus => "minecraft:nautilus",
+            SimpleParticle::Dolphin => "minecraft:dolphin",
+        }
+    }
+
+    /// Returns the particle with the given identifier. The
+    /// `minecraft:` namespace may be omitted.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        let identifier = if identifier.contains(':') {
+            identifier.to_owned()
+        } else {
+            format!("minecraft:{}", identifier)
+        };
+        Self::all().find(|particle| particle.identifier() == identifier)
+    }
+}
+
+#[derive(Default, AsAny, Packet, Clone, Debug)]
+pub struct JoinGame {
+    pub entity_id: i32,
+    pub gamemode: u8,
+    pub dimension: i32,
+    pub difficulty: u8,
+    pub max_players: u8,
+    pub level_type: String,
+    pub reduced_debug_info: bool,
+}
+
+#[derive(Default, AsAny, Clone, Debug)]
+pub struct MapData {
+    pub map_id: VarInt,
+    pub scale: i8,
+    pub tracking_position: bool,
+    pub icons: Vec<MapIcon>,
+    /// The updated rectangle of pixels, if any.
+    pub update: Option<MapUpdate>,
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct MapIcon {
+    pub ty: VarInt,
+    pub x: i8,
+    pub z: i8,
+    /// Rotation in sixteenths of a full turn.
+    pub direction: i8,
+    /// Chat component displayed beneath the icon.
+    pub display_name: Option<String>,
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct MapUpdate {
+    pub columns: u8,
+    pub rows: u8,
+    pub x: u8,
+    pub z: u8,
+    /// Colors of the updated pixels, row by row.
+    pub data: Vec<u8>,
+}
+
+impl Packet for MapData {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
+        self.map_id = buf.try_get_var_int()?;
+        self.scale = buf.try_get_i8()?;
+        self.tracking_position = buf.try_get_bool()?;
+
+        let num_icons = buf.try_get_var_int()?;
+        for _ in 0..num_icons {
+            let ty = buf.try_get_var_int()?;
+            let x = buf.try_get_i8()?;
+            let z = buf.try_get_i8()?;
+            let direction = buf.try_get_i8()?;
+            let display_name = if buf.try_get_bool()? {
+                Some(buf.try_get_string()?)
+            } else {
+                None
+            };
+            self.icons.push(MapIcon {
+                ty,
+                x,
+                z,
+                direction,
+                display_name,
+            });
+        }
+
+        let columns = buf.try_get_u8()?;
+        if columns > 0 {
+            let rows = buf.try_get_u8()?;
+            let x = buf.try_get_u8()?;
+            let z = buf.try_get_u8()?;
+            let len = buf.try_get_var_int()? as usize;
+            let data = buf.try_get_bytes(len)?.to_vec();
+            self.update = Some(MapUpdate {
+                columns,
+                rows,
+                x,
+                z,
+                data,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_var_int(self.map_id);
+        buf.push_i8(self.scale);
+        buf.push_bool(self.tracking_position);
+
+        buf.push_var_int(self.icons.len() as i32);
+        for icon in &self.icons {
+            buf.push_var_int(icon.ty);
+            buf.push_i8(icon.x);
+            buf.push_i8(icon.z);
+            buf.push_i8(icon.direction);
+            buf.push_bool(icon.display_name.is_some());
+            if let Some(name) = &icon.display_name {
+                buf.push_string(name);
+            }
+        }
+
+        match &self.update {
+            Some(update) => {
+                buf.push_u8(update.columns);
+                buf.push_u8(update.rows);
+                buf.push_u8(update.x);
+                buf.push_u8(update.z);
+                buf.push_var_int(update.data.len() as i32);
+                buf.put(update.data.as_slice());
+            }
+            None => buf.push_u8(0),
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::MapData
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::MapData
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
+
+// TODO EntityPacket
+
+#[derive(Default, AsAny, Packet, Clone)]
+pub struct EntityRelativeMove {
+    pub entity_id: VarInt,
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub delta_z: i16,
+    pub on_ground: bool,
+}
+
+#[derive(Default, AsAny, Packet, Clone)]
 pub struct EntityLookAndRelativeMove {
     pub entity_id: VarInt,
     pub delta_x: i16,
@@ -1875,9 +2637,9 @@ pub struct CraftRecipeResponse {
 
 #[derive(Default, AsAny, Packet, Clone)]
 pub struct PlayerAbilitiesClientbound {
-    flags: u8,
-    flying_speed: f32,
-    field_of_view_modifier: f32,
+    pub flags: u8,
+    pub flying_speed: f32,
+    pub field_of_view_modifier: f32,
 }
 
 #[derive(Default, AsAny, Clone)]
@@ -1886,18 +2648,20 @@ pub struct CombatEvent {
 }
 
 impl Packet for CombatEvent {
-    fn read_from(&mut self, _buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
         unimplemented!()
     }
 
     fn write_to(&self, buf: &mut BytesMut) {
         match &self.event {
-            CombatEventType::EnterCombat => (),
+            CombatEventType::EnterCombat => buf.push_var_int(0),
             CombatEventType::EndCombat(duration, entity_id) => {
+                buf.push_var_int(1);
                 buf.push_var_int(*duration);
                 buf.push_i32(*entity_id);
             }
             CombatEventType::EntityDead(player_id, entity_id, message) => {
+                buf.push_var_int(2);
                 buf.push_var_int(*player_id);
                 buf.push_i32(*entity_id);
                 buf.push_string(message);
@@ -1941,7 +2705,7 @@ pub struct PlayerInfo {
 }
 
 impl Packet for PlayerInfo {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         let id = buf.try_get_var_int()?;
         let _ = buf.try_get_var_int()?;
         self.uuid = buf.try_get_uuid()?;
@@ -2092,7 +2856,76 @@ pub struct UseBed {
     pub location: BlockPosition,
 }
 
-// TODO Unlock Recipes
+#[derive(Default, AsAny, Clone)]
+pub struct UnlockRecipes {
+    pub action: UnlockRecipesAction,
+    pub crafting_book_open: bool,
+    pub crafting_filter_active: bool,
+    pub smelting_book_open: bool,
+    pub smelting_filter_active: bool,
+    pub recipe_ids: Vec<String>,
+    /// Recipes to highlight as new. Only sent
+    /// with `UnlockRecipesAction::Init`.
+    pub displayed_recipe_ids: Vec<String>,
+}
+
+impl Packet for UnlockRecipes {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
+        unimplemented!()
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_var_int(self.action as i32);
+        buf.push_bool(self.crafting_book_open);
+        buf.push_bool(self.crafting_filter_active);
+        buf.push_bool(self.smelting_book_open);
+        buf.push_bool(self.smelting_filter_active);
+
+        buf.push_var_int(self.recipe_ids.len() as i32);
+        for id in &self.recipe_ids {
+            buf.push_string(id);
+        }
+
+        if self.action == UnlockRecipesAction::Init {
+            buf.push_var_int(self.displayed_recipe_ids.len() as i32);
+            for id in &self.displayed_recipe_ids {
+                buf.push_string(id);
+            }
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::UnlockRecipes
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::UnlockRecipes
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum UnlockRecipesAction {
+    /// Replaces the client's recipe book with the given recipes.
+    Init = 0,
+    /// Unlocks the given recipes.
+    Add = 1,
+    /// Locks the given recipes.
+    Remove = 2,
+}
+
+impl Default for UnlockRecipesAction {
+    fn default() -> Self {
+        UnlockRecipesAction::Init
+    }
+}
 
 #[derive(Default, AsAny, Clone)]
 pub struct DestroyEntities {
@@ -2100,7 +2933,7 @@ pub struct DestroyEntities {
 }
 
 impl Packet for DestroyEntities {
-    fn read_from(&mut self, _buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
         unimplemented!()
     }
 
@@ -2154,6 +2987,19 @@ pub struct EntityHeadLook {
     pub head_yaw: u8,
 }
 
+#[derive(Default, AsAny, Packet, Clone)]
+pub struct Camera {
+    pub camera_id: VarInt,
+}
+
+#[derive(Default, AsAny, Packet, Clone)]
+pub struct DisplayScoreboard {
+    /// 0 for the player list, 1 for the sidebar, and 2 below names.
+    pub position: i8,
+    /// Name of the objective to display, or empty to clear the slot.
+    pub score_name: String,
+}
+
 #[derive(Default, AsAny, Clone, Debug)]
 pub struct PacketEntityMetadata {
     pub entity_id: VarInt,
@@ -2161,7 +3007,7 @@ pub struct PacketEntityMetadata {
 }
 
 impl Packet for PacketEntityMetadata {
-    fn read_from(&mut self, buf: &mut Cursor<&[u8]>) -> anyhow::Result<()> {
+    fn read_from(&mut self, buf: &mut Bytes) -> anyhow::Result<()> {
         self.entity_id = buf.try_get_var_int()?;
         self.metadata = buf.try_get_metadata()?;
         Ok(())
@@ -2203,6 +3049,143 @@ pub struct EntityEquipment {
     pub item: Slot,
 }
 
+#[derive(Default, AsAny, Packet, Clone)]
+pub struct UpdateHealth {
+    pub health: f32,
+    pub food: VarInt,
+    pub food_saturation: f32,
+}
+
+#[derive(Default, AsAny, Clone)]
+pub struct ScoreboardObjective {
+    pub name: String,
+    pub action: ObjectiveAction,
+}
+
+impl Packet for ScoreboardObjective {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
+        unimplemented!()
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_string(&self.name);
+        match &self.action {
+            ObjectiveAction::Create(display_name, ty) => {
+                buf.push_u8(0);
+                buf.push_string(display_name);
+                buf.push_var_int(ToPrimitive::to_i32(ty).unwrap());
+            }
+            ObjectiveAction::Remove => buf.push_u8(1),
+            ObjectiveAction::Update(display_name, ty) => {
+                buf.push_u8(2);
+                buf.push_string(display_name);
+                buf.push_var_int(ToPrimitive::to_i32(ty).unwrap());
+            }
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::ScoreboardObjective
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::ScoreboardObjective
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
+
+/// An action on a scoreboard objective, carrying the
+/// display name as JSON where applicable.
+#[derive(Clone, Debug)]
+pub enum ObjectiveAction {
+    Create(String, ObjectiveType),
+    Remove,
+    Update(String, ObjectiveType),
+}
+
+impl Default for ObjectiveAction {
+    fn default() -> Self {
+        ObjectiveAction::Remove
+    }
+}
+
+/// How the client renders an objective's scores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+pub enum ObjectiveType {
+    Integer,
+    Hearts,
+}
+
+impl Default for ObjectiveType {
+    fn default() -> Self {
+        ObjectiveType::Integer
+    }
+}
+
+#[derive(Default, AsAny, Clone)]
+pub struct UpdateScore {
+    /// The player name or other entry the score belongs to.
+    pub entity_name: String,
+    /// Name of the objective, or empty when removing the entry's
+    /// scores for all objectives.
+    pub objective_name: String,
+    pub action: ScoreAction,
+}
+
+impl Packet for UpdateScore {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
+        unimplemented!()
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_string(&self.entity_name);
+        match self.action {
+            ScoreAction::Update(value) => {
+                buf.push_u8(0);
+                buf.push_string(&self.objective_name);
+                buf.push_var_int(value);
+            }
+            ScoreAction::Remove => {
+                buf.push_u8(1);
+                buf.push_string(&self.objective_name);
+            }
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::UpdateScore
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::UpdateScore
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreAction {
+    Update(i32),
+    Remove,
+}
+
+impl Default for ScoreAction {
+    fn default() -> Self {
+        ScoreAction::Remove
+    }
+}
+
 // TODO Select Advancement Tab
 // TODO World Border
 
@@ -2217,9 +3200,414 @@ pub struct TimeUpdate {
     pub time_of_day: i64,
 }
 
+#[derive(Default, AsAny, Clone)]
+pub struct Title {
+    pub action: TitleAction,
+}
+
+impl Packet for Title {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
+        unimplemented!()
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_var_int(self.action.id());
+
+        match &self.action {
+            TitleAction::SetTitle(text)
+            | TitleAction::SetSubtitle(text)
+            | TitleAction::SetActionBar(text) => buf.push_string(text),
+            TitleAction::SetTimes {
+                fade_in,
+                stay,
+                fade_out,
+            } => {
+                buf.push_i32(*fade_in);
+                buf.push_i32(*stay);
+                buf.push_i32(*fade_out);
+            }
+            TitleAction::Hide | TitleAction::Reset => (),
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::Title
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::Title
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
+
+/// An action on the title shown to a player. Text is JSON.
+#[derive(Clone, Debug)]
+pub enum TitleAction {
+    SetTitle(String),
+    SetSubtitle(String),
+    SetActionBar(String),
+    /// Timings in ticks.
+    SetTimes {
+        fade_in: i32,
+        stay: i32,
+        fade_out: i32,
+    },
+    /// Hides the title, keeping its text and timings.
+    Hide,
+    /// Hides the title and restores the default timings.
+    Reset,
+}
+
+impl Default for TitleAction {
+    fn default() -> Self {
+        TitleAction::Hide
+    }
+}
+
+impl TitleAction {
+    fn id(&self) -> i32 {
+        match self {
+            TitleAction::SetTitle(_) => 0,
+            TitleAction::SetSubtitle(_) => 1,
+            TitleAction::SetActionBar(_) => 2,
+            TitleAction::SetTimes { .. } => 3,
+            TitleAction::Hide => 4,
+            TitleAction::Reset => 5,
+        }
+    }
+}
+
 #[derive(Default, AsAny, Packet, Clone)]
 pub struct CollectItem {
     pub collected: VarInt,
     pub collector: VarInt,
     pub count: VarInt,
 }
+
+/// Adds and removes the advancements shown to a player,
+/// and updates their progress towards them.
+#[derive(Default, AsAny, Clone, Debug)]
+pub struct Advancements {
+    /// Whether the client should discard its
+    /// advancements before applying this packet.
+    pub reset: bool,
+    pub added: Vec<(String, AdvancementData)>,
+    pub removed: Vec<String>,
+    /// The criteria of each advancement, along with the time at which
+    /// they were achieved, in milliseconds since the UNIX epoch.
+    pub progress: Vec<(String, Vec<(String, Option<i64>)>)>,
+}
+
+/// An advancement sent in the Advancements packet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdvancementData {
+    pub parent: Option<String>,
+    pub display: Option<AdvancementDisplayData>,
+    pub criteria: Vec<String>,
+    /// The advancement is completed once at least one
+    /// criterion of each requirement is achieved.
+    pub requirements: Vec<Vec<String>>,
+}
+
+/// How an advancement is displayed in the advancements screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdvancementDisplayData {
+    /// JSON text.
+    pub title: String,
+    /// JSON text.
+    pub description: String,
+    pub icon: Slot,
+    /// 0 for a task, 1 for a challenge, or 2 for a goal.
+    pub frame: VarInt,
+    /// Background texture of the tab, for root advancements.
+    pub background: Option<String>,
+    pub show_toast: bool,
+    pub hidden: bool,
+    pub x: f32,
+    pub y: f32,
+}
+
+const ADVANCEMENT_FLAG_BACKGROUND: i32 = 0x01;
+const ADVANCEMENT_FLAG_SHOW_TOAST: i32 = 0x02;
+const ADVANCEMENT_FLAG_HIDDEN: i32 = 0x04;
+
+impl Packet for Advancements {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
+        unimplemented!()
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_bool(self.reset);
+
+        buf.push_var_int(self.added.len() as i32);
+        for (id, advancement) in &self.added {
+            buf.push_string(id);
+
+            buf.push_bool(advancement.parent.is_some());
+            if let Some(parent) = &advancement.parent {
+                buf.push_string(parent);
+            }
+
+            buf.push_bool(advancement.display.is_some());
+            if let Some(display) = &advancement.display {
+                buf.push_string(&display.title);
+                buf.push_string(&display.description);
+                buf.push_slot(display.icon.as_ref());
+                buf.push_var_int(display.frame);
+
+                let mut flags = 0;
+                if display.background.is_some() {
+                    flags |= ADVANCEMENT_FLAG_BACKGROUND;
+                }
+                if display.show_toast {
+                    flags |= ADVANCEMENT_FLAG_SHOW_TOAST;
+                }
+                if display.hidden {
+                    flags |= ADVANCEMENT_FLAG_HIDDEN;
+                }
+                buf.push_i32(flags);
+                if let Some(background) = &display.background {
+                    buf.push_string(background);
+                }
+
+                buf.push_f32(display.x);
+                buf.push_f32(display.y);
+            }
+
+            buf.push_var_int(advancement.criteria.len() as i32);
+            for criterion in &advancement.criteria {
+                buf.push_string(criterion);
+            }
+
+            buf.push_var_int(advancement.requirements.len() as i32);
+            for requirement in &advancement.requirements {
+                buf.push_var_int(requirement.len() as i32);
+                for criterion in requirement {
+                    buf.push_string(criterion);
+                }
+            }
+        }
+
+        buf.push_var_int(self.removed.len() as i32);
+        for id in &self.removed {
+            buf.push_string(id);
+        }
+
+        buf.push_var_int(self.progress.len() as i32);
+        for (id, criteria) in &self.progress {
+            buf.push_string(id);
+            buf.push_var_int(criteria.len() as i32);
+            for (criterion, achieved) in criteria {
+                buf.push_string(criterion);
+                buf.push_bool(achieved.is_some());
+                if let Some(achieved) = achieved {
+                    buf.push_i64(*achieved);
+                }
+            }
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::Advancements
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::Advancements
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
+
+#[derive(Default, AsAny, Clone, Debug)]
+pub struct EntityProperties {
+    pub entity_id: VarInt,
+    pub properties: Vec<EntityProperty>,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct EntityProperty {
+    pub key: String,
+    pub value: f64,
+    pub modifiers: Vec<EntityPropertyModifier>,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct EntityPropertyModifier {
+    pub uuid: Uuid,
+    pub amount: f64,
+    pub operation: i8,
+}
+
+impl Packet for EntityProperties {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
+        unimplemented!()
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_var_int(self.entity_id);
+        buf.put_i32(self.properties.len() as i32);
+
+        for property in &self.properties {
+            buf.push_string(&property.key);
+            buf.put_f64(property.value);
+            buf.push_var_int(property.modifiers.len() as i32);
+
+            for modifier in &property.modifiers {
+                buf.push_uuid(&modifier.uuid);
+                buf.put_f64(modifier.amount);
+                buf.put_i8(modifier.operation);
+            }
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::EntityProperties
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::EntityProperties
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
+
+#[derive(Default, AsAny, Packet, Clone)]
+pub struct EntityEffect {
+    pub entity_id: VarInt,
+    pub effect_id: i8,
+    pub amplifier: i8,
+    pub duration: VarInt,
+    /// Bit 0 is set for ambient effects; bit 1 to show particles.
+    pub flags: i8,
+}
+
+#[derive(Default, AsAny, Clone)]
+pub struct DeclareRecipes {
+    pub recipes: Vec<DeclaredRecipe>,
+}
+
+/// A recipe sent in the Declare Recipes packet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclaredRecipe {
+    pub id: String,
+    pub group: String,
+    pub kind: DeclaredRecipeKind,
+}
+
+/// The type-specific data of a declared recipe. Each
+/// ingredient is the list of items it accepts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeclaredRecipeKind {
+    Shapeless {
+        ingredients: Vec<Vec<ItemStack>>,
+        result: ItemStack,
+    },
+    Shaped {
+        width: usize,
+        height: usize,
+        /// The ingredients of each slot, row by row.
+        /// Slots which must be empty accept no items.
+        ingredients: Vec<Vec<ItemStack>>,
+        result: ItemStack,
+    },
+    Smelting {
+        ingredient: Vec<ItemStack>,
+        result: ItemStack,
+        experience: f32,
+        cooking_time: VarInt,
+    },
+}
+
+fn push_ingredient(buf: &mut BytesMut, ingredient: &[ItemStack]) {
+    buf.push_var_int(ingredient.len() as i32);
+    for stack in ingredient {
+        buf.push_slot(Some(stack));
+    }
+}
+
+impl Packet for DeclareRecipes {
+    fn read_from(&mut self, _buf: &mut Bytes) -> anyhow::Result<()> {
+        unimplemented!()
+    }
+
+    fn write_to(&self, buf: &mut BytesMut) {
+        buf.push_var_int(self.recipes.len() as i32);
+
+        for recipe in &self.recipes {
+            buf.push_string(&recipe.id);
+            match &recipe.kind {
+                DeclaredRecipeKind::Shapeless {
+                    ingredients,
+                    result,
+                } => {
+                    buf.push_string("crafting_shapeless");
+                    buf.push_string(&recipe.group);
+                    buf.push_var_int(ingredients.len() as i32);
+                    for ingredient in ingredients {
+                        push_ingredient(buf, ingredient);
+                    }
+                    buf.push_slot(Some(result));
+                }
+                DeclaredRecipeKind::Shaped {
+                    width,
+                    height,
+                    ingredients,
+                    result,
+                } => {
+                    buf.push_string("crafting_shaped");
+                    buf.push_var_int(*width as i32);
+                    buf.push_var_int(*height as i32);
+                    buf.push_string(&recipe.group);
+                    for ingredient in ingredients {
+                        push_ingredient(buf, ingredient);
+                    }
+                    buf.push_slot(Some(result));
+                }
+                DeclaredRecipeKind::Smelting {
+                    ingredient,
+                    result,
+                    experience,
+                    cooking_time,
+                } => {
+                    buf.push_string("smelting");
+                    buf.push_string(&recipe.group);
+                    push_ingredient(buf, ingredient);
+                    buf.push_slot(Some(result));
+                    buf.push_f32(*experience);
+                    buf.push_var_int(*cooking_time);
+                }
+            }
+        }
+    }
+
+    fn ty(&self) -> PacketType {
+        PacketType::DeclareRecipes
+    }
+
+    fn ty_sized() -> PacketType
+    where
+        Self: Sized,
+    {
+        PacketType::DeclareRecipes
+    }
+
+    fn box_clone(&self) -> Box<dyn Packet> {
+        box_clone_impl!(self);
+    }
+}
@@ -0,0 +1,100 @@
+//! Versions of the protocol which clients may connect with.
+
+use crate::mctypes::SlotFormat;
+use crate::packet::{PacketId, PacketType};
+
+/// A version of the Minecraft protocol.
+///
+/// Packets are defined once, against the server's native
+/// version. Each connection's codec is told the version
+/// negotiated in the handshake, and looks up packet IDs
+/// through it so that versions which number their packets
+/// differently can share a server. Versions which lay out
+/// packet fields differently are handled by the codec while
+/// packets are read and written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ProtocolVersion {
+    V1_13_1,
+    V1_13_2,
+}
+
+impl ProtocolVersion {
+    /// The version which packets are defined against.
+    pub const NATIVE: ProtocolVersion = ProtocolVersion::V1_13_2;
+
+    /// All versions clients may connect with, newest first.
+    pub const SUPPORTED: &'static [ProtocolVersion] =
+        &[ProtocolVersion::V1_13_2, ProtocolVersion::V1_13_1];
+
+    /// Returns the version with the given protocol number,
+    /// or `None` if it is not supported.
+    pub fn from_id(id: u32) -> Option<Self> {
+        Self::SUPPORTED
+            .iter()
+            .copied()
+            .find(|version| version.id() == id)
+    }
+
+    /// Returns the protocol number sent in the handshake.
+    pub fn id(self) -> u32 {
+        match self {
+            ProtocolVersion::V1_13_1 => 401,
+            ProtocolVersion::V1_13_2 => 404,
+        }
+    }
+
+    /// Returns the name of the game version.
+    pub fn name(self) -> &'static str {
+        match self {
+            ProtocolVersion::V1_13_1 => "1.13.1",
+            ProtocolVersion::V1_13_2 => "1.13.2",
+        }
+    }
+
+    /// Returns how item stacks are laid out in this version.
+    pub(crate) fn slot_format(self) -> SlotFormat {
+        match self {
+            ProtocolVersion::V1_13_1 => SlotFormat::ShortId,
+            ProtocolVersion::V1_13_2 => SlotFormat::Present,
+        }
+    }
+
+    /// Returns the ID of a packet type in this version.
+    pub fn packet_id(self, ty: PacketType) -> PacketId {
+        match self {
+            // 1.13.1 numbers its packets the same way.
+            ProtocolVersion::V1_13_1 | ProtocolVersion::V1_13_2 => ty.get_id(),
+        }
+    }
+
+    /// Returns the packet type with the given ID in this
+    /// version, or `None` if there is no such packet.
+    pub fn packet_type(self, id: PacketId) -> Option<PacketType> {
+        match self {
+            ProtocolVersion::V1_13_1 | ProtocolVersion::V1_13_2 => PacketType::get_from_id(id).ok(),
+        }
+    }
+}
+
+impl Default for ProtocolVersion {
+    fn default() -> Self {
+        ProtocolVersion::NATIVE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_round_trip() {
+        for &version in ProtocolVersion::SUPPORTED {
+            assert_eq!(ProtocolVersion::from_id(version.id()), Some(version));
+        }
+        assert_eq!(ProtocolVersion::from_id(0), None);
+        assert_eq!(
+            ProtocolVersion::from_id(401),
+            Some(ProtocolVersion::V1_13_1)
+        );
+    }
+}
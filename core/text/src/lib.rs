@@ -2,7 +2,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::borrow::Cow;
 use uuid::Uuid;
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Color {
     DarkRed,
@@ -29,7 +29,7 @@ impl From<Color> for Text {
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Style {
     Bold,
@@ -45,7 +45,7 @@ impl From<Style> for Text {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 /// Represent all possible keybinds in vanilla.
 pub enum Keybind {
     Attack,
@@ -196,7 +196,7 @@ impl From<&Keybind> for String {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 /// Represent all possible translation keys in vanilla.
 pub enum Translate {
     ChatTypeText,
@@ -259,7 +259,7 @@ impl<'a> From<&Translate> for String {
     }
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "action", content = "value")]
 // TODO: Accept any json primitive as string
 pub enum Click {
@@ -272,14 +272,14 @@ pub enum Click {
 }
 
 #[serde_with::skip_serializing_none]
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Entity {
     id: Uuid,
     ty: Option<Cow<'static, str>>,
     name: Cow<'static, str>,
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "action", content = "value")]
 // TODO: Accept any json primitive as string
 pub enum Hover {
@@ -292,7 +292,7 @@ pub enum Hover {
     ShowEntity(Entity),
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 /// Text component can either be Text, Translate, Score, Selector, Keybind, or Nbt.
 pub enum TextValue {
@@ -301,6 +301,7 @@ pub enum TextValue {
     },
     Translate {
         translate: Translate,
+        #[serde(default)]
         with: Vec<Text>,
     },
     Score {
@@ -374,7 +375,7 @@ impl TextValue {
 }
 
 #[serde_with::skip_serializing_none]
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 /// Text json object that holds all styles.
 pub struct TextComponent {
     #[serde(flatten)]
@@ -845,7 +846,7 @@ impl From<Text> for TextComponent {
 }
 
 /// Text can either be a json String, Object, or an Array.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Text {
     String(Cow<'static, str>),
@@ -900,6 +901,52 @@ impl Text {
     pub fn nbt<A: Into<nbt::Blob>>(nbt: A) -> Text {
         Text::from(TextValue::nbt(nbt))
     }
+
+    /// Parses a text component from JSON, as typed in commands.
+    pub fn from_json(json: &str) -> serde_json::Result<Text> {
+        serde_json::from_str(json)
+    }
+
+    /// Returns the text without formatting, for logging.
+    /// Translations are shown as their key followed by their
+    /// arguments, and scores, selectors and keybinds by name.
+    pub fn to_plain(&self) -> String {
+        let mut plain = String::new();
+        self.push_plain(&mut plain);
+        plain
+    }
+
+    fn push_plain(&self, plain: &mut String) {
+        match self {
+            Text::String(text) => plain.push_str(text),
+            Text::Array(texts) => texts.iter().for_each(|text| text.push_plain(plain)),
+            Text::Component(component) => {
+                match &component.value {
+                    TextValue::Text { text } => plain.push_str(text),
+                    TextValue::Translate { translate, with } => {
+                        plain.push_str(&String::from(translate));
+                        if !with.is_empty() {
+                            plain.push_str(" [");
+                            for (i, arg) in with.iter().enumerate() {
+                                if i > 0 {
+                                    plain.push_str(", ");
+                                }
+                                arg.push_plain(plain);
+                            }
+                            plain.push(']');
+                        }
+                    }
+                    TextValue::Score { name, .. } => plain.push_str(name),
+                    TextValue::Selector { selector } => plain.push_str(selector),
+                    TextValue::Keybind { keybind } => plain.push_str(&String::from(keybind)),
+                    TextValue::Nbt { .. } => (),
+                }
+                if let Some(extra) = &component.extra {
+                    extra.iter().for_each(|text| text.push_plain(plain));
+                }
+            }
+        }
+    }
 }
 
 impl From<TextComponent> for Text {
@@ -952,6 +999,7 @@ impl From<Text> for String {
 
 /// Ensures Text is either an Array or Object.
 /// This is required at some places when sending to the client.
+#[derive(Debug, Clone, PartialEq)]
 pub struct TextRoot(Text);
 
 impl From<TextRoot> for String {
@@ -1018,6 +1066,27 @@ macro_rules! impl_operators {
 
 impl_operators!(TextRoot, Text, TextComponent);
 
+/// Replaces formatting codes written with `&`, which is
+/// easier to type than `§`, with the real thing.
+pub fn replace_formatting_codes(text: &str) -> String {
+    let mut formatted = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&code) if c == '&' && is_formatting_code(code) => formatted.push('§'),
+            _ => formatted.push(c),
+        }
+    }
+    formatted
+}
+
+fn is_formatting_code(c: char) -> bool {
+    match c.to_ascii_lowercase() {
+        '0'..='9' | 'a'..='f' | 'k'..='o' | 'r' => true,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1116,4 +1185,36 @@ mod tests {
 
         assert_eq!(root_json, r#"{"text":"hello"}"#);
     }
+
+    #[test]
+    fn text_to_plain() {
+        let text = Text::from("Hello ") * Color::Red + Text::from("world").bold();
+        assert_eq!(text.to_plain(), "Hello world");
+
+        let join = Translate::MultiplayerPlayerJoined * vec!["The_Defman"] * Color::Yellow;
+        assert_eq!(join.to_plain(), "multiplayer.player.joined [The_Defman]");
+    }
+
+    #[test]
+    fn text_from_json() {
+        let text = Text::from_json(r#"{"text":"Hello","color":"gold"}"#).unwrap();
+        assert_eq!(text, Text::from("Hello") * Color::Gold);
+        assert_eq!(Text::from_json(r#""Hello""#).unwrap(), Text::from("Hello"));
+        assert!(Text::from_json("Hello").is_err());
+
+        let translated = Text::from_json(r#"{"translate":"advancements.story.root.title"}"#);
+        assert_eq!(
+            translated.unwrap(),
+            Text::translate_with("advancements.story.root.title", Vec::<Text>::new())
+        );
+    }
+
+    #[test]
+    fn formatting_codes() {
+        assert_eq!(
+            replace_formatting_codes("&aGreen&r and &lbold"),
+            "§aGreen§r and §lbold"
+        );
+        assert_eq!(replace_formatting_codes("Tom & Jerry &z"), "Tom & Jerry &z");
+    }
 }
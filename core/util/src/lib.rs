@@ -67,6 +67,15 @@ impl Difficulty {
             Difficulty::Hard => 3,
         }
     }
+
+    pub fn from_id(id: u8) -> Self {
+        match id {
+            0 => Difficulty::Peaceful,
+            1 => Difficulty::Easy,
+            3 => Difficulty::Hard,
+            _ => Difficulty::Medium,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
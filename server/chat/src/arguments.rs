@@ -0,0 +1,934 @@
+//! Typed parsers for command arguments.
+//!
+//! Each parser corresponds to one of the client's argument types,
+//! so the client highlights and validates arguments the same way
+//! the server parses them.
+
+use crate::EntitySelector;
+use feather_core::blocks::BlockId;
+use feather_core::items::{EntityTagNbt, Item, ItemNbt, ItemStack};
+use feather_core::util::{BlockPosition, Position};
+use feather_server_types::StatusEffect;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// An error encountered while parsing a command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    /// Index in the input at which the error occurred.
+    pub cursor: usize,
+}
+
+impl ParseError {
+    pub fn new(message: impl Display, cursor: usize) -> Self {
+        Self {
+            message: message.to_string(),
+            cursor,
+        }
+    }
+}
+
+/// A cursor over the text of a command.
+#[derive(Debug, Clone)]
+pub struct StringReader<'a> {
+    input: &'a str,
+    cursor: usize,
+}
+
+impl<'a> StringReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, cursor: 0 }
+    }
+
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor;
+    }
+
+    /// Returns the text which has not yet been read.
+    pub fn remaining(&self) -> &'a str {
+        &self.input[self.cursor..]
+    }
+
+    pub fn can_read(&self) -> bool {
+        self.cursor < self.input.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    pub fn skip(&mut self) {
+        if let Some(c) = self.peek() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    pub(crate) fn error(&self, message: impl Display) -> ParseError {
+        ParseError::new(message, self.cursor)
+    }
+
+    /// Skips the given character, failing if it is not next.
+    pub fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.peek() == Some(c) {
+            self.skip();
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'", c)))
+        }
+    }
+
+    /// Reads characters while `predicate` holds.
+    pub fn read_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.cursor;
+        while let Some(c) = self.peek() {
+            if !predicate(c) {
+                break;
+            }
+            self.skip();
+        }
+        &self.input[start..self.cursor]
+    }
+
+    /// Reads up to the next space.
+    pub fn read_word(&mut self) -> &'a str {
+        self.read_while(|c| c != ' ')
+    }
+
+    /// Reads a string which is not quoted, made up of
+    /// the characters allowed in one.
+    pub fn read_unquoted(&mut self) -> &'a str {
+        self.read_while(|c| c.is_ascii_alphanumeric() || "_-.+".contains(c))
+    }
+
+    /// Reads a quoted or unquoted string.
+    pub fn read_string(&mut self) -> Result<String, ParseError> {
+        let quote = match self.peek() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => return Ok(self.read_unquoted().to_owned()),
+        };
+        self.skip();
+
+        let mut string = String::new();
+        let mut escaped = false;
+        while let Some(c) = self.peek() {
+            self.skip();
+            if escaped {
+                if c != quote && c != '\\' {
+                    return Err(self.error(format!("Invalid escape sequence '\\{}'", c)));
+                }
+                string.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                return Ok(string);
+            } else {
+                string.push(c);
+            }
+        }
+        Err(self.error("Unclosed quoted string"))
+    }
+
+    /// Reads a number of type `T`, named `kind` in errors.
+    pub fn read_number<T: FromStr>(&mut self, kind: &str) -> Result<T, ParseError> {
+        let start = self.cursor;
+        let number = self.read_while(|c| c.is_ascii_digit() || c == '.' || c == '-');
+        if number.is_empty() {
+            return Err(self.error(format!("Expected {}", kind)));
+        }
+        number
+            .parse()
+            .map_err(|_| ParseError::new(format!("Invalid {} '{}'", kind, number), start))
+    }
+
+    /// Reads a namespaced identifier, such as `minecraft:stone`,
+    /// adding the `minecraft` namespace if it is missing.
+    pub fn read_identifier(&mut self) -> Result<String, ParseError> {
+        let identifier = self
+            .read_while(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-.:/".contains(c));
+        if identifier.is_empty() {
+            Err(self.error("Expected identifier"))
+        } else if identifier.contains(':') {
+            Ok(identifier.to_owned())
+        } else {
+            Ok(format!("minecraft:{}", identifier))
+        }
+    }
+}
+
+/// A component of a world coordinate, which is
+/// relative to the sender's position if prefixed with `~`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WorldCoordinate {
+    pub value: f64,
+    pub relative: bool,
+}
+
+impl WorldCoordinate {
+    pub fn resolve(self, origin: f64) -> f64 {
+        if self.relative {
+            origin + self.value
+        } else {
+            self.value
+        }
+    }
+
+    /// Reads a coordinate. If `integer` is set, absolute coordinates
+    /// must be integers; if `center` is set, absolute integers refer
+    /// to the center of a block.
+    fn read(reader: &mut StringReader, integer: bool, center: bool) -> Result<Self, ParseError> {
+        if reader.peek() == Some('^') {
+            return Err(reader.error("Cannot mix world & local coordinates"));
+        }
+        let relative = reader.peek() == Some('~');
+        if relative {
+            reader.skip();
+            if !reader.can_read() || reader.peek() == Some(' ') {
+                return Ok(Self {
+                    value: 0.0,
+                    relative,
+                });
+            }
+        }
+
+        let start = reader.cursor();
+        let value = if integer && !relative {
+            f64::from(reader.read_number::<i32>("integer")?)
+        } else {
+            reader.read_number::<f64>("double")?
+        };
+        let is_integer = !reader.input()[start..reader.cursor()].contains('.');
+        let value = if center && !relative && is_integer {
+            value + 0.5
+        } else {
+            value
+        };
+        Ok(Self { value, relative })
+    }
+}
+
+/// A position given in a command, either in world
+/// coordinates or in local coordinates within the
+/// sender's view, prefixed with `^`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Coordinates {
+    World {
+        x: WorldCoordinate,
+        y: WorldCoordinate,
+        z: WorldCoordinate,
+    },
+    Local {
+        left: f64,
+        up: f64,
+        forward: f64,
+    },
+}
+
+impl Coordinates {
+    fn read(reader: &mut StringReader, integer: bool, center: bool) -> Result<Self, ParseError> {
+        if reader.peek() == Some('^') {
+            let read_local = |reader: &mut StringReader| -> Result<f64, ParseError> {
+                if reader.peek() != Some('^') {
+                    return Err(reader.error("Cannot mix world & local coordinates"));
+                }
+                reader.skip();
+                if !reader.can_read() || reader.peek() == Some(' ') {
+                    Ok(0.0)
+                } else {
+                    reader.read_number("double")
+                }
+            };
+            let left = read_local(reader)?;
+            reader.expect(' ')?;
+            let up = read_local(reader)?;
+            reader.expect(' ')?;
+            let forward = read_local(reader)?;
+            Ok(Coordinates::Local { left, up, forward })
+        } else {
+            let x = WorldCoordinate::read(reader, integer, center)?;
+            reader.expect(' ')?;
+            let y = WorldCoordinate::read(reader, integer, false)?;
+            reader.expect(' ')?;
+            let z = WorldCoordinate::read(reader, integer, center)?;
+            Ok(Coordinates::World { x, y, z })
+        }
+    }
+
+    /// Returns the position given by these coordinates,
+    /// relative to `origin`. The returned position keeps
+    /// the rotation of `origin`.
+    pub fn position(&self, origin: Position) -> Position {
+        let mut position = origin;
+        match *self {
+            Coordinates::World { x, y, z } => {
+                position.x = x.resolve(origin.x);
+                position.y = y.resolve(origin.y);
+                position.z = z.resolve(origin.z);
+            }
+            Coordinates::Local { left, up, forward } => {
+                let deg = std::f64::consts::PI / 180.0;
+                let yaw = (f64::from(origin.yaw) + 90.0) * deg;
+                let pitch = -f64::from(origin.pitch) * deg;
+                let pitch_up = (-f64::from(origin.pitch) + 90.0) * deg;
+
+                let forward_axis = [
+                    yaw.cos() * pitch.cos(),
+                    pitch.sin(),
+                    yaw.sin() * pitch.cos(),
+                ];
+                let up_axis = [
+                    yaw.cos() * pitch_up.cos(),
+                    pitch_up.sin(),
+                    yaw.sin() * pitch_up.cos(),
+                ];
+                // The negated cross product of forward and up
+                let left_axis = [
+                    forward_axis[2] * up_axis[1] - forward_axis[1] * up_axis[2],
+                    forward_axis[0] * up_axis[2] - forward_axis[2] * up_axis[0],
+                    forward_axis[1] * up_axis[0] - forward_axis[0] * up_axis[1],
+                ];
+
+                let offset = |axis: usize| {
+                    forward_axis[axis] * forward + up_axis[axis] * up + left_axis[axis] * left
+                };
+                position.x += offset(0);
+                position.y += offset(1);
+                position.z += offset(2);
+            }
+        }
+        position
+    }
+
+    /// Returns the block containing the position
+    /// given by these coordinates.
+    pub fn block_position(&self, origin: Position) -> BlockPosition {
+        self.position(origin).block()
+    }
+}
+
+/// A rotation given in a command, whose yaw and
+/// pitch may be relative to the sender's rotation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rotation {
+    pub yaw: WorldCoordinate,
+    pub pitch: WorldCoordinate,
+}
+
+impl Rotation {
+    /// Returns the yaw and pitch of this rotation.
+    pub fn resolve(&self, origin: Position) -> (f32, f32) {
+        (
+            self.yaw.resolve(f64::from(origin.yaw)) as f32,
+            self.pitch.resolve(f64::from(origin.pitch)) as f32,
+        )
+    }
+}
+
+/// A range of numbers, such as `1..5`, `..10` or `3`,
+/// bounded on at least one side.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Range<T> {
+    pub min: Option<T>,
+    pub max: Option<T>,
+}
+
+pub type IntRange = Range<i32>;
+pub type FloatRange = Range<f64>;
+
+impl<T: PartialOrd + Copy> Range<T> {
+    pub fn contains(&self, value: T) -> bool {
+        self.min.map(|min| value >= min).unwrap_or(true)
+            && self.max.map(|max| value <= max).unwrap_or(true)
+    }
+}
+
+impl<T: FromStr + PartialOrd + Copy> Range<T> {
+    pub fn read(reader: &mut StringReader, kind: &str) -> Result<Self, ParseError> {
+        let start = reader.cursor();
+        let text = reader.read_while(|c| c.is_ascii_digit() || c == '.' || c == '-');
+        let invalid = || ParseError::new(format!("Invalid {} range '{}'", kind, text), start);
+        let bound = |s: &str| -> Result<Option<T>, ParseError> {
+            if s.is_empty() {
+                Ok(None)
+            } else {
+                s.parse().map(Some).map_err(|_| invalid())
+            }
+        };
+
+        let range = match text.find("..") {
+            Some(index) => Range {
+                min: bound(&text[..index])?,
+                max: bound(&text[index + 2..])?,
+            },
+            None => {
+                let value = bound(text)?;
+                Range {
+                    min: value,
+                    max: value,
+                }
+            }
+        };
+
+        match (range.min, range.max) {
+            (None, None) => Err(ParseError::new(format!("Expected {} range", kind), start)),
+            (Some(min), Some(max)) if min > max => Err(ParseError::new(
+                "The minimum of a range must not be greater than its maximum",
+                start,
+            )),
+            _ => Ok(range),
+        }
+    }
+}
+
+/// Reads a block identifier, optionally followed by
+/// its properties, as in `oak_log[axis=x]`.
+fn read_block(reader: &mut StringReader) -> Result<BlockId, ParseError> {
+    let start = reader.cursor();
+    let identifier = reader.read_identifier()?;
+    let mut block = BlockId::from_identifier(&identifier)
+        .ok_or_else(|| ParseError::new(format!("Unknown block type '{}'", identifier), start))?;
+    if reader.peek() != Some('[') {
+        return Ok(block);
+    }
+
+    // Properties not given keep their default values.
+    reader.skip();
+    while reader.peek() != Some(']') {
+        let key_start = reader.cursor();
+        let key = reader.read_unquoted();
+        if !block.has_property(key) {
+            return Err(ParseError::new(
+                format!("Block {} does not accept '{}' property", identifier, key),
+                key_start,
+            ));
+        }
+        reader.expect('=')?;
+        let value_start = reader.cursor();
+        let value = reader.read_unquoted();
+        block = block.with_property(key, value).ok_or_else(|| {
+            ParseError::new(
+                format!(
+                    "Block {} does not accept '{}' for {} property",
+                    identifier, value, key
+                ),
+                value_start,
+            )
+        })?;
+        if reader.peek() == Some(',') {
+            reader.skip();
+        } else if reader.peek() != Some(']') {
+            return Err(reader.error("Expected end of properties"));
+        }
+    }
+    reader.skip();
+    Ok(block)
+}
+
+/// A parser for an argument type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ArgParser {
+    Bool,
+    Integer {
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+    Double {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    /// A single word.
+    Word,
+    /// A word, or a phrase in quotes.
+    Phrase,
+    /// The rest of the command.
+    GreedyString,
+    /// One or more entities. If `single` is set, the argument
+    /// may only select one entity; if `players_only` is set,
+    /// it may only select players.
+    Entity {
+        single: bool,
+        players_only: bool,
+    },
+    /// A block position, which may be relative.
+    BlockPos,
+    /// A precise position, which may be relative.
+    Vec3,
+    /// A yaw and pitch, which may be relative.
+    Rotation,
+    IntRange,
+    FloatRange,
+    /// An item identifier, optionally followed by
+    /// the stack's NBT, as in `diamond_sword{Damage:10}`.
+    Item,
+    /// An item to match stacks against, optionally with NBT
+    /// which matching stacks must have. Parsed into an item
+    /// stack like `Item`.
+    ItemPredicate,
+    /// A block identifier, optionally with properties.
+    Block,
+    /// A status effect identifier.
+    Effect,
+    /// A duration in ticks, which may be given in seconds
+    /// or days with a suffix, as in `10s` or `0.5d`.
+    Time,
+    /// The identifier of an entity type to summon.
+    /// Parsed into a string.
+    EntitySummon,
+    /// A stringified compound of tags to apply
+    /// to an entity, as in `{NoAI:1b}`.
+    EntityTag,
+}
+
+impl ArgParser {
+    /// Parser for any integer.
+    pub const INTEGER: ArgParser = ArgParser::Integer {
+        min: None,
+        max: None,
+    };
+    /// Parser for any number.
+    pub const DOUBLE: ArgParser = ArgParser::Double {
+        min: None,
+        max: None,
+    };
+    /// Parser for one or more entities.
+    pub const ENTITIES: ArgParser = ArgParser::Entity {
+        single: false,
+        players_only: false,
+    };
+    /// Parser for a single entity.
+    pub const ENTITY: ArgParser = ArgParser::Entity {
+        single: true,
+        players_only: false,
+    };
+    /// Parser for one or more players.
+    pub const PLAYERS: ArgParser = ArgParser::Entity {
+        single: false,
+        players_only: true,
+    };
+    /// Parser for a single player.
+    pub const PLAYER: ArgParser = ArgParser::Entity {
+        single: true,
+        players_only: true,
+    };
+
+    /// Parses an argument.
+    pub fn parse(&self, reader: &mut StringReader) -> Result<ArgValue, ParseError> {
+        let start = reader.cursor();
+        let value = match *self {
+            ArgParser::Bool => match reader.read_unquoted() {
+                "true" => ArgValue::Bool(true),
+                "false" => ArgValue::Bool(false),
+                value => {
+                    return Err(ParseError::new(
+                        format!("Invalid bool, expected true or false but found '{}'", value),
+                        start,
+                    ))
+                }
+            },
+            ArgParser::Integer { min, max } => {
+                let value: i32 = reader.read_number("integer")?;
+                check_bounds(value, min, max, "Integer", start)?;
+                ArgValue::Integer(value)
+            }
+            ArgParser::Double { min, max } => {
+                let value: f64 = reader.read_number("double")?;
+                check_bounds(value, min, max, "Double", start)?;
+                ArgValue::Double(value)
+            }
+            ArgParser::Word => {
+                let word = reader.read_unquoted();
+                if word.is_empty() {
+                    return Err(reader.error("Expected string"));
+                }
+                ArgValue::String(word.to_owned())
+            }
+            ArgParser::Phrase => ArgValue::String(reader.read_string()?),
+            ArgParser::GreedyString => {
+                let rest = reader.remaining();
+                reader.set_cursor(reader.input().len());
+                ArgValue::String(rest.to_owned())
+            }
+            ArgParser::Entity {
+                single,
+                players_only,
+            } => ArgValue::Entity(EntitySelector::read(reader, single, players_only)?),
+            ArgParser::BlockPos => ArgValue::Coordinates(Coordinates::read(reader, true, false)?),
+            ArgParser::Vec3 => ArgValue::Coordinates(Coordinates::read(reader, false, true)?),
+            ArgParser::Rotation => {
+                let yaw = WorldCoordinate::read(reader, false, false)?;
+                reader.expect(' ')?;
+                let pitch = WorldCoordinate::read(reader, false, false)?;
+                ArgValue::Rotation(Rotation { yaw, pitch })
+            }
+            ArgParser::IntRange => ArgValue::IntRange(Range::read(reader, "integer")?),
+            ArgParser::FloatRange => ArgValue::FloatRange(Range::read(reader, "float")?),
+            ArgParser::Item | ArgParser::ItemPredicate => {
+                let identifier = reader.read_identifier()?;
+                let item = Item::from_identifier(&identifier).ok_or_else(|| {
+                    ParseError::new(format!("Unknown item '{}'", identifier), start)
+                })?;
+                let mut stack = ItemStack::new(item, 1);
+                if reader.peek() == Some('{') {
+                    let nbt_start = reader.cursor();
+                    let (nbt, len) = ItemNbt::read_snbt(reader.remaining())
+                        .map_err(|e| ParseError::new(e.message, nbt_start + e.cursor))?;
+                    reader.set_cursor(nbt_start + len);
+                    stack.set_nbt(nbt);
+                }
+                ArgValue::Item(stack)
+            }
+            ArgParser::Block => ArgValue::Block(read_block(reader)?),
+            ArgParser::Effect => {
+                let identifier = reader.read_identifier()?;
+                ArgValue::Effect(StatusEffect::from_identifier(&identifier).ok_or_else(|| {
+                    ParseError::new(format!("Unknown effect '{}'", identifier), start)
+                })?)
+            }
+            ArgParser::Time => {
+                let value: f64 = reader.read_number("float")?;
+                let unit = match reader.peek() {
+                    Some('d') => Some(24_000.0),
+                    Some('s') => Some(20.0),
+                    Some('t') => Some(1.0),
+                    _ => None,
+                };
+                if unit.is_some() {
+                    reader.skip();
+                }
+                let ticks = (value * unit.unwrap_or(1.0)).round();
+                if ticks < 0.0 {
+                    return Err(ParseError::new("Tick count must be non-negative", start));
+                }
+                ArgValue::Integer(ticks.min(f64::from(i32::max_value())) as i32)
+            }
+            ArgParser::EntitySummon => ArgValue::String(reader.read_identifier()?),
+            ArgParser::EntityTag => {
+                let (tag, len) = EntityTagNbt::read_snbt(reader.remaining())
+                    .map_err(|e| ParseError::new(e.message, start + e.cursor))?;
+                reader.set_cursor(start + len);
+                ArgValue::EntityTag(tag)
+            }
+        };
+        Ok(value)
+    }
+
+    /// Returns the identifier of the client's parser for this argument.
+    pub fn identifier(&self) -> &'static str {
+        match self {
+            ArgParser::Bool => "brigadier:bool",
+            ArgParser::Integer { .. } => "brigadier:integer",
+            ArgParser::Double { .. } => "brigadier:double",
+            ArgParser::Word | ArgParser::Phrase | ArgParser::GreedyString => "brigadier:string",
+            ArgParser::Entity { .. } => "minecraft:entity",
+            ArgParser::BlockPos => "minecraft:block_pos",
+            ArgParser::Vec3 => "minecraft:vec3",
+            ArgParser::Rotation => "minecraft:rotation",
+            ArgParser::IntRange => "minecraft:int_range",
+            ArgParser::FloatRange => "minecraft:float_range",
+            ArgParser::Item => "minecraft:item_stack",
+            ArgParser::ItemPredicate => "minecraft:item_predicate",
+            ArgParser::Block => "minecraft:block_state",
+            ArgParser::Effect => "minecraft:mob_effect",
+            ArgParser::Time => "minecraft:time",
+            ArgParser::EntitySummon => "minecraft:entity_summon",
+            ArgParser::EntityTag => "minecraft:nbt",
+        }
+    }
+
+    /// Returns the encoded properties of the client's parser.
+    pub fn properties(&self) -> Vec<u8> {
+        fn bounds<T>(min: Option<T>, max: Option<T>, to_bytes: impl Fn(T) -> Vec<u8>) -> Vec<u8> {
+            let flags = min.is_some() as u8 | (max.is_some() as u8) << 1;
+            let mut properties = vec![flags];
+            properties.extend(min.into_iter().chain(max).flat_map(to_bytes));
+            properties
+        }
+
+        match *self {
+            ArgParser::Integer { min, max } => bounds(min, max, |x| x.to_be_bytes().to_vec()),
+            ArgParser::Double { min, max } => bounds(min, max, |x| x.to_be_bytes().to_vec()),
+            ArgParser::Word => vec![0],
+            ArgParser::Phrase => vec![1],
+            ArgParser::GreedyString => vec![2],
+            ArgParser::Entity {
+                single,
+                players_only,
+            } => vec![single as u8 | (players_only as u8) << 1],
+            _ => vec![],
+        }
+    }
+}
+
+fn check_bounds<T: PartialOrd + Display>(
+    value: T,
+    min: Option<T>,
+    max: Option<T>,
+    kind: &str,
+    cursor: usize,
+) -> Result<(), ParseError> {
+    match (min, max) {
+        (Some(min), _) if value < min => Err(ParseError::new(
+            format!("{} must not be less than {}, found {}", kind, min, value),
+            cursor,
+        )),
+        (_, Some(max)) if value > max => Err(ParseError::new(
+            format!("{} must not be more than {}, found {}", kind, max, value),
+            cursor,
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// A parsed argument.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArgValue {
+    Bool(bool),
+    Integer(i32),
+    Double(f64),
+    String(String),
+    Entity(EntitySelector),
+    Coordinates(Coordinates),
+    Rotation(Rotation),
+    IntRange(IntRange),
+    FloatRange(FloatRange),
+    Item(ItemStack),
+    Block(BlockId),
+    Effect(StatusEffect),
+    EntityTag(EntityTagNbt),
+}
+
+/// A type which can be taken from a parsed argument.
+pub trait FromArg: Sized {
+    fn from_arg(value: &ArgValue) -> Option<Self>;
+}
+
+macro_rules! from_arg {
+    ($($ty:ty => $variant:ident,)*) => {
+        $(
+            impl FromArg for $ty {
+                fn from_arg(value: &ArgValue) -> Option<Self> {
+                    match value {
+                        ArgValue::$variant(value) => Some(value.clone()),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_arg! {
+    bool => Bool,
+    i32 => Integer,
+    f64 => Double,
+    String => String,
+    EntitySelector => Entity,
+    Coordinates => Coordinates,
+    Rotation => Rotation,
+    IntRange => IntRange,
+    FloatRange => FloatRange,
+    ItemStack => Item,
+    BlockId => Block,
+    StatusEffect => Effect,
+    EntityTagNbt => EntityTag,
+}
+
+impl FromArg for Item {
+    fn from_arg(value: &ArgValue) -> Option<Self> {
+        match value {
+            ArgValue::Item(stack) => Some(stack.ty),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(parser: ArgParser, input: &str) -> Result<ArgValue, ParseError> {
+        let mut reader = StringReader::new(input);
+        let value = parser.parse(&mut reader)?;
+        assert!(
+            !reader.can_read(),
+            "{} was not consumed",
+            reader.remaining()
+        );
+        Ok(value)
+    }
+
+    fn position(x: f64, y: f64, z: f64) -> Position {
+        Position {
+            x,
+            y,
+            z,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn integers_and_bounds() {
+        let parser = ArgParser::Integer {
+            min: Some(1),
+            max: Some(64),
+        };
+        assert_eq!(parse(parser, "32"), Ok(ArgValue::Integer(32)));
+        assert!(parse(parser, "0").is_err());
+        assert!(parse(parser, "65").is_err());
+        assert!(parse(parser, "x").is_err());
+    }
+
+    #[test]
+    fn quoted_strings() {
+        assert_eq!(
+            parse(ArgParser::Phrase, r#""hello \"world\"""#),
+            Ok(ArgValue::String(String::from("hello \"world\"")))
+        );
+        assert!(parse(ArgParser::Phrase, "\"unclosed").is_err());
+    }
+
+    #[test]
+    fn world_coordinates() {
+        let origin = position(10.0, 64.0, -5.0);
+        let coords = match parse(ArgParser::Vec3, "~ ~1.5 3").unwrap() {
+            ArgValue::Coordinates(coords) => coords,
+            value => panic!("{:?}", value),
+        };
+        let pos = coords.position(origin);
+        assert_eq!((pos.x, pos.y, pos.z), (10.0, 65.5, 3.5));
+
+        let coords = match parse(ArgParser::BlockPos, "~-1 70 ~0.5").unwrap() {
+            ArgValue::Coordinates(coords) => coords,
+            value => panic!("{:?}", value),
+        };
+        assert_eq!(coords.block_position(origin), BlockPosition::new(9, 70, -5));
+
+        assert!(parse(ArgParser::BlockPos, "1.5 2 3").is_err());
+        assert!(parse(ArgParser::Vec3, "~ ^ ~").is_err());
+    }
+
+    #[test]
+    fn local_coordinates() {
+        // Facing south, towards positive Z, so left is positive X.
+        let origin = position(0.0, 0.0, 0.0);
+        let coords = match parse(ArgParser::Vec3, "^1 ^ ^2").unwrap() {
+            ArgValue::Coordinates(coords) => coords,
+            value => panic!("{:?}", value),
+        };
+        let pos = coords.position(origin);
+        assert!((pos.x - 1.0).abs() < 1e-9);
+        assert!(pos.y.abs() < 1e-9);
+        assert!((pos.z - 2.0).abs() < 1e-9);
+
+        assert!(parse(ArgParser::Vec3, "^ ~ ^").is_err());
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(
+            parse(ArgParser::IntRange, "1..5"),
+            Ok(ArgValue::IntRange(Range {
+                min: Some(1),
+                max: Some(5)
+            }))
+        );
+        assert_eq!(
+            parse(ArgParser::FloatRange, "..10.5"),
+            Ok(ArgValue::FloatRange(Range {
+                min: None,
+                max: Some(10.5)
+            }))
+        );
+        let exact = IntRange {
+            min: Some(3),
+            max: Some(3),
+        };
+        assert_eq!(
+            parse(ArgParser::IntRange, "3"),
+            Ok(ArgValue::IntRange(exact))
+        );
+        assert!(exact.contains(3) && !exact.contains(4));
+        assert!(parse(ArgParser::IntRange, "..").is_err());
+        assert!(parse(ArgParser::IntRange, "5..1").is_err());
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(
+            parse(ArgParser::Item, "diamond"),
+            Ok(ArgValue::Item(ItemStack::new(Item::Diamond, 1)))
+        );
+        match parse(ArgParser::Item, "diamond_sword{Damage:10,Unbreakable:1b}") {
+            Ok(ArgValue::Item(stack)) => {
+                assert_eq!(stack.damage(), 10);
+                assert!(stack.is_unbreakable());
+            }
+            value => panic!("{:?}", value),
+        }
+        assert_eq!(
+            parse(ArgParser::Block, "minecraft:stone"),
+            Ok(ArgValue::Block(BlockId::stone()))
+        );
+        let furnace = BlockId::furnace().with_property("facing", "north").unwrap();
+        assert_eq!(
+            parse(ArgParser::Block, "furnace[facing=north]"),
+            Ok(ArgValue::Block(furnace))
+        );
+        assert!(parse(ArgParser::Block, "furnace[facing=up]").is_err());
+        assert!(parse(ArgParser::Block, "stone[facing=north]").is_err());
+        assert_eq!(
+            parse(ArgParser::Effect, "speed"),
+            Ok(ArgValue::Effect(StatusEffect::Speed))
+        );
+        assert!(parse(ArgParser::Item, "not_an_item").is_err());
+        assert!(parse(ArgParser::Item, "stick{Damage:").is_err());
+    }
+
+    #[test]
+    fn summon_arguments() {
+        assert_eq!(
+            parse(ArgParser::EntitySummon, "zombie"),
+            Ok(ArgValue::String(String::from("minecraft:zombie")))
+        );
+        match parse(ArgParser::EntityTag, "{NoAI:1b,Silent:1b}") {
+            Ok(ArgValue::EntityTag(tag)) => assert!(tag.no_ai && tag.silent),
+            value => panic!("{:?}", value),
+        }
+        assert!(parse(ArgParser::EntityTag, "NoAI:1b").is_err());
+    }
+
+    #[test]
+    fn time() {
+        assert_eq!(parse(ArgParser::Time, "100"), Ok(ArgValue::Integer(100)));
+        assert_eq!(parse(ArgParser::Time, "5s"), Ok(ArgValue::Integer(100)));
+        assert_eq!(
+            parse(ArgParser::Time, "0.5d"),
+            Ok(ArgValue::Integer(12_000))
+        );
+        assert!(parse(ArgParser::Time, "-1t").is_err());
+    }
+
+    #[test]
+    fn properties() {
+        assert_eq!(ArgParser::INTEGER.properties(), vec![0]);
+        assert_eq!(
+            ArgParser::Integer {
+                min: None,
+                max: Some(2)
+            }
+            .properties(),
+            vec![2, 0, 0, 0, 2]
+        );
+        assert_eq!(ArgParser::PLAYER.properties(), vec![3]);
+        assert_eq!(ArgParser::GreedyString.properties(), vec![2]);
+    }
+}
@@ -0,0 +1,282 @@
+//! Block logging: recording block changes made by players
+//! and other entities, and the `/lookup` and `/rollback`
+//! commands which query and undo them.
+
+use crate::duration::{format_duration, parse_duration};
+use crate::{CommandCtx, CommandError, CommandRegistration, CommandResult};
+use feather_core::blocks::BlockKind;
+use feather_core::text::{Color, Text};
+use feather_core::util::Position;
+use feather_server_types::{
+    block_log_now, BlockInteractEvent, BlockLog, BlockLogKind, BlockLogQuery, BlockLogRecord,
+    BlockUpdateCause, BlockUpdateEvent, Game, Name, Player, ACTOR_ENTITY, ACTOR_WORLD, TPS,
+};
+use fecs::World;
+use std::sync::Arc;
+
+inventory::submit! {
+    CommandRegistration::operator(
+        "lookup",
+        "/lookup [user:<player>] [radius:<blocks>] [time:<duration>]",
+        &lookup,
+    )
+}
+
+inventory::submit! {
+    CommandRegistration::operator(
+        "rollback",
+        "/rollback [user:<player>] [radius:<blocks>] [time:<duration>]",
+        &rollback,
+    )
+}
+
+/// Maximum number of records listed by `/lookup`.
+const LOOKUP_LIMIT: usize = 10;
+
+/// Maximum radius accepted by `/lookup` and `/rollback`.
+const MAX_RADIUS: i32 = 100;
+
+/// Interval, in ticks, at which new records are appended to the log file.
+const FLUSH_INTERVAL: u64 = TPS * 5;
+
+/// Returns the name with which changes having
+/// the given cause are attributed in the block log.
+pub fn block_log_actor(world: &World, cause: &BlockUpdateCause) -> String {
+    match cause.entity {
+        Some(entity) if world.is_alive(entity) && world.has::<Player>(entity) => {
+            world.get::<Name>(entity).0.clone()
+        }
+        Some(_) => ACTOR_ENTITY.to_owned(),
+        None => ACTOR_WORLD.to_owned(),
+    }
+}
+
+/// Records block changes in the block log.
+#[fecs::event_handler]
+pub fn on_block_update_log_change(
+    event: &BlockUpdateEvent,
+    world: &mut World,
+    block_log: &mut BlockLog,
+) {
+    if !block_log.enabled || event.old == event.new {
+        return;
+    }
+
+    let actor = block_log_actor(world, &event.cause);
+    let kind = if block_log.rolling_back {
+        BlockLogKind::Rollback {
+            old: event.old,
+            new: event.new,
+        }
+    } else {
+        BlockLogKind::BlockChange {
+            old: event.old,
+            new: event.new,
+        }
+    };
+    block_log.record(&actor, event.pos, kind);
+}
+
+/// Records players opening containers in the block log.
+#[fecs::event_handler]
+pub fn on_block_interact_log_container_access(
+    event: &BlockInteractEvent,
+    world: &mut World,
+    block_log: &mut BlockLog,
+) {
+    if !block_log.enabled || !is_container(event.block.kind()) {
+        return;
+    }
+
+    let actor = world.get::<Name>(event.player).0.clone();
+    block_log.record(&actor, event.pos, BlockLogKind::ContainerAccess);
+}
+
+fn is_container(kind: BlockKind) -> bool {
+    match kind {
+        BlockKind::Chest
+        | BlockKind::TrappedChest
+        | BlockKind::Furnace
+        | BlockKind::Dispenser
+        | BlockKind::Dropper
+        | BlockKind::Hopper
+        | BlockKind::BrewingStand
+        | BlockKind::ShulkerBox
+        | BlockKind::WhiteShulkerBox
+        | BlockKind::OrangeShulkerBox
+        | BlockKind::MagentaShulkerBox
+        | BlockKind::LightBlueShulkerBox
+        | BlockKind::YellowShulkerBox
+        | BlockKind::LimeShulkerBox
+        | BlockKind::PinkShulkerBox
+        | BlockKind::GrayShulkerBox
+        | BlockKind::LightGrayShulkerBox
+        | BlockKind::CyanShulkerBox
+        | BlockKind::PurpleShulkerBox
+        | BlockKind::BlueShulkerBox
+        | BlockKind::BrownShulkerBox
+        | BlockKind::GreenShulkerBox
+        | BlockKind::RedShulkerBox
+        | BlockKind::BlackShulkerBox => true,
+        _ => false,
+    }
+}
+
+/// System which periodically appends new records to the block log file.
+#[fecs::system]
+pub fn flush_block_log(game: &mut Game, block_log: &mut BlockLog) {
+    if game.tick_count % FLUSH_INTERVAL != 0 {
+        return;
+    }
+
+    block_log.flush(&game.running_tasks);
+}
+
+/// Parses the `user:`, `radius:`, and `time:` parameters
+/// of the block log commands.
+fn parse_query(ctx: &CommandCtx, args: &[&str]) -> Result<BlockLogQuery, CommandError> {
+    let mut query = BlockLogQuery::default();
+
+    for arg in args {
+        let mut split = arg.splitn(2, ':');
+        let (key, value) = match (split.next(), split.next()) {
+            (Some(key), Some(value)) if !value.is_empty() => (key, value),
+            _ => return Err(CommandError::Usage),
+        };
+
+        match key {
+            "user" | "u" => query.actor = Some(value.to_owned()),
+            "radius" | "r" => {
+                let radius: i32 = value.parse().map_err(|_| CommandError::Usage)?;
+                if radius < 0 || radius > MAX_RADIUS {
+                    return Err(CommandError::message(format!(
+                        "The radius must be between 0 and {} blocks.",
+                        MAX_RADIUS
+                    )));
+                }
+                let center = ctx.world.get::<Position>(ctx.sender).block();
+                query.area = Some((center, radius));
+            }
+            "time" | "t" => {
+                let duration = parse_duration(value).ok_or_else(|| {
+                    CommandError::message(format!(
+                        "Invalid duration \"{}\". Use e.g. 30m, 12h, or 7d12h.",
+                        value
+                    ))
+                })?;
+                query.since = Some(block_log_now().saturating_sub(duration));
+            }
+            _ => return Err(CommandError::Usage),
+        }
+    }
+
+    Ok(query)
+}
+
+fn ensure_enabled(ctx: &CommandCtx) -> CommandResult {
+    if ctx.game.resources.get::<BlockLog>().enabled {
+        Ok(())
+    } else {
+        Err(CommandError::message("Block logging is disabled."))
+    }
+}
+
+/// Describes a record for `/lookup`.
+fn describe(block_log: &BlockLog, record: &BlockLogRecord, now: u64) -> Text {
+    let action = match record.kind {
+        BlockLogKind::BlockChange { old, new } | BlockLogKind::Rollback { old, new } => {
+            let verb = match record.kind {
+                BlockLogKind::Rollback { .. } => "rolled back",
+                _ if new.is_air() => "broke",
+                _ => "placed",
+            };
+            let block = if new.is_air() { old } else { new };
+            format!("{} {}", verb, block.identifier())
+        }
+        BlockLogKind::ContainerAccess => String::from("opened a container"),
+    };
+
+    Text::from(format!(
+        "{} ago ",
+        format_duration(now.saturating_sub(record.time))
+    )) * Color::Gray
+        + Text::from(block_log.actor_name(record.actor).to_owned()) * Color::Gold
+        + Text::from(format!(
+            " {} at {} {} {}",
+            action, record.pos.x, record.pos.y, record.pos.z
+        ))
+}
+
+fn lookup(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    ensure_enabled(ctx)?;
+    let query = parse_query(ctx, args)?;
+
+    let resources = Arc::clone(&ctx.game.resources);
+    let block_log = resources.get::<BlockLog>();
+
+    let now = block_log_now();
+    let records: Vec<_> = block_log.query(&query).take(LOOKUP_LIMIT + 1).collect();
+    if records.is_empty() {
+        ctx.reply(Text::from("No matching block log entries.") * Color::Gray);
+        return Ok(());
+    }
+
+    ctx.reply(Text::from("Block log entries, newest first:") * Color::Gold);
+    for record in records.iter().take(LOOKUP_LIMIT) {
+        ctx.reply(describe(&block_log, record, now));
+    }
+    if records.len() > LOOKUP_LIMIT {
+        ctx.reply(Text::from("More entries exist; narrow the search to see them.") * Color::Gray);
+    }
+
+    Ok(())
+}
+
+fn rollback(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    ensure_enabled(ctx)?;
+    let query = parse_query(ctx, args)?;
+    if query.actor.is_none() && query.area.is_none() {
+        return Err(CommandError::message(
+            "Specify a user or a radius to roll back.",
+        ));
+    }
+
+    // Collect the changes first, since reverting them logs new records.
+    let changes: Vec<_> = {
+        let block_log = ctx.game.resources.get::<BlockLog>();
+        block_log
+            .query(&query)
+            .filter_map(|record| match record.kind {
+                BlockLogKind::BlockChange { old, .. } => Some((record.pos, old)),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let resources = Arc::clone(&ctx.game.resources);
+    resources.get_mut::<BlockLog>().rolling_back = true;
+
+    // Changes are reverted newest first, so each block
+    // ends up in its state before the oldest matching change.
+    let cause = BlockUpdateCause::entity(ctx.sender);
+    let mut reverted = 0;
+    let mut skipped = 0;
+    for (pos, old) in changes {
+        if ctx.game.set_block_at_with_cause(ctx.world, pos, old, cause) {
+            reverted += 1;
+        } else {
+            // The chunk is not loaded.
+            skipped += 1;
+        }
+    }
+
+    resources.get_mut::<BlockLog>().rolling_back = false;
+
+    let mut message = format!("Rolled back {} block changes", reverted);
+    if skipped > 0 {
+        message.push_str(&format!(" ({} in unloaded chunks were skipped)", skipped));
+    }
+    message.push('.');
+    ctx.reply(Text::from(message) * Color::Gray);
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! The `/clear` command, which removes items from inventories.
+
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandError, CommandRegistration,
+    CommandResult, EntitySelector,
+};
+use feather_core::inventory::{Inventory, SLOT_CRAFTING_OUTPUT};
+use feather_core::items::ItemStack;
+use feather_core::text::{Color, Text};
+use feather_server_types::{InventoryUpdateEvent, Name};
+use fecs::Entity;
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/clear [targets] [item] [count]",
+        literal("clear").executes(&clear).then(
+            argument("targets", ArgParser::PLAYERS).executes(&clear).then(
+                argument("item", ArgParser::ItemPredicate)
+                    .executes(&clear)
+                    .then(
+                        argument(
+                            "count",
+                            ArgParser::Integer {
+                                min: Some(0),
+                                max: None,
+                            },
+                        )
+                        .executes(&clear),
+                    ),
+            ),
+        ),
+    )
+}
+
+fn clear(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let targets = match args.get::<EntitySelector>("targets") {
+        Some(targets) => targets.resolve(ctx)?,
+        None => vec![ctx.player()?],
+    };
+    let predicate = args.get::<ItemStack>("item");
+    // A count of 0 only counts the matching items.
+    let max = args.get::<i32>("count").map(|count| count as u32);
+
+    let mut total = 0;
+    for &player in &targets {
+        total += clear_inventory(ctx, player, predicate.as_ref(), max);
+    }
+
+    let players = match targets.as_slice() {
+        [player] => format!("player {}", ctx.world.get::<Name>(*player).0),
+        _ => format!("{} players", targets.len()),
+    };
+    if total == 0 {
+        return Err(CommandError::message(format!(
+            "No items were found on {}",
+            players
+        )));
+    }
+    let message = if max == Some(0) {
+        format!("Found {} matching items on {}", total, players)
+    } else {
+        format!("Removed {} items from {}", total, players)
+    };
+    ctx.reply(Text::from(message) * Color::Gray);
+    Ok(())
+}
+
+/// Removes up to `max` items matching `predicate` from a player's
+/// inventory, returning how many were removed. If `max` is 0, the
+/// inventory is left unchanged and the matching items are counted.
+fn clear_inventory(
+    ctx: &mut CommandCtx,
+    player: Entity,
+    predicate: Option<&ItemStack>,
+    max: Option<u32>,
+) -> u32 {
+    let mut removed = 0;
+    let mut changed = vec![];
+    {
+        let mut inventory = ctx.world.get_mut::<Inventory>(player);
+        // The crafting output only shows what the grid would craft.
+        let slots =
+            (0..inventory.slot_count() as usize).filter(|&slot| slot != SLOT_CRAFTING_OUTPUT);
+        for slot in slots {
+            let mut stack = match inventory.item_at(slot) {
+                Some(stack) if matches_predicate(stack, predicate) => stack.clone(),
+                _ => continue,
+            };
+            if max == Some(0) {
+                removed += u32::from(stack.amount);
+                continue;
+            }
+
+            let amount = match max {
+                Some(max) => (max - removed).min(u32::from(stack.amount)) as u8,
+                None => stack.amount,
+            };
+            removed += u32::from(amount);
+            stack.amount -= amount;
+            inventory.set_item_at(slot, stack);
+            changed.push(slot);
+
+            if Some(removed) == max {
+                break;
+            }
+        }
+    }
+
+    if !changed.is_empty() {
+        ctx.game.handle(
+            ctx.world,
+            InventoryUpdateEvent {
+                slots: changed.into_iter().collect(),
+                player,
+            },
+        );
+    }
+    removed
+}
+
+/// Returns whether a stack matches the item given to `/clear`.
+/// If the item has NBT, matching stacks must have the same NBT.
+fn matches_predicate(stack: &ItemStack, predicate: Option<&ItemStack>) -> bool {
+    match predicate {
+        Some(predicate) if predicate.nbt().is_some() => predicate.stacks_with(stack),
+        Some(predicate) => predicate.ty == stack.ty,
+        None => true,
+    }
+}
@@ -0,0 +1,338 @@
+//! Command registration and dispatch.
+//!
+//! Commands are registered with `inventory::submit!`, which
+//! allows them to be defined in any crate linked into the server:
+//!
+//! ```ignore
+//! inventory::submit! {
+//!     CommandRegistration::new("mute", "/mute <player> [reason]", &mute)
+//! }
+//! ```
+//!
+//! A command registered this way parses its own arguments from
+//! the words following its name. Commands may instead be declared
+//! as a tree of typed arguments; see the `dispatcher` module.
+
+use crate::{parse_command, EntitySelector, Node};
+use feather_core::network::packets::ChatMessageClientbound;
+use feather_core::text::{Color, Text, TextRoot};
+use feather_server_types::{
+    has_permission, CommandEvent, Console, Game, Name, Network, Permission, Player,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Context passed to a command when it is executed.
+pub struct CommandCtx<'a> {
+    pub game: &'a mut Game,
+    pub world: &'a mut World,
+    /// The player who ran the command, or the console.
+    pub sender: Entity,
+}
+
+impl<'a> CommandCtx<'a> {
+    /// Sends a message to the command sender.
+    pub fn reply(&self, text: impl Into<Text>) {
+        send_message(self.world, self.sender, text);
+    }
+
+    /// Returns the sender, for commands which act on the sender
+    /// by default, or an error if it is the console.
+    pub fn player(&self) -> Result<Entity, CommandError> {
+        if self.world.has::<Console>(self.sender) {
+            Err(CommandError::message(
+                "An entity is required to run this command here",
+            ))
+        } else {
+            Ok(self.sender)
+        }
+    }
+
+    /// Returns the name of the command sender.
+    pub fn sender_name(&self) -> String {
+        self.world.get::<Name>(self.sender).0.clone()
+    }
+}
+
+/// An error returned by a command.
+#[derive(Debug)]
+pub enum CommandError {
+    /// The arguments were invalid. The command's
+    /// usage string is sent to the sender.
+    Usage,
+    /// The sender does not have permission to run the command.
+    NoPermission,
+    /// A custom error message which is sent to the sender.
+    Message(String),
+}
+
+impl CommandError {
+    pub fn message(msg: impl Display) -> Self {
+        CommandError::Message(msg.to_string())
+    }
+}
+
+pub type CommandResult = Result<(), CommandError>;
+
+pub trait CommandFn: Fn(&mut CommandCtx, &[&str]) -> CommandResult + Send + Sync + 'static {}
+
+impl<F> CommandFn for F where
+    F: Fn(&mut CommandCtx, &[&str]) -> CommandResult + Send + Sync + 'static
+{
+}
+
+/// The kind of value a command argument takes,
+/// used to suggest values as players type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArgKind {
+    /// The name of an online player.
+    Player,
+    /// A block identifier, such as `minecraft:stone`.
+    Block,
+    /// A status effect identifier, such as `minecraft:speed`.
+    Effect,
+    /// A particle identifier, such as `minecraft:flame`.
+    Particle,
+    /// One of a fixed set of words.
+    Choice(&'static [&'static str]),
+    /// A word with no suggestions.
+    Word,
+    /// The rest of the command. May only be the last argument.
+    Text,
+}
+
+/// How a command is executed.
+pub enum CommandHandler {
+    /// A function which parses the command's arguments itself.
+    Function {
+        /// The kinds of the command's arguments, in order.
+        args: &'static [ArgKind],
+        f: &'static dyn CommandFn,
+    },
+    /// A tree of typed arguments, rooted at
+    /// a literal node for the command's name.
+    Tree(Node),
+}
+
+/// A registration for a command.
+pub struct CommandRegistration {
+    /// The name of the command, without the leading slash.
+    pub name: &'static str,
+    /// Usage string sent to the sender when the
+    /// command is used incorrectly.
+    pub usage: &'static str,
+    /// The operator level needed to run the command, unless
+    /// the permissions provider decides otherwise.
+    pub permission_level: u8,
+    pub handler: CommandHandler,
+}
+
+/// Permission level needed to run operator commands
+/// which do not specify one.
+pub const DEFAULT_OPERATOR_LEVEL: u8 = 2;
+/// Permission level needed to run moderation commands.
+pub const MODERATOR_LEVEL: u8 = 3;
+
+impl CommandRegistration {
+    pub fn new(name: &'static str, usage: &'static str, f: &'static dyn CommandFn) -> Self {
+        Self {
+            name,
+            usage,
+            permission_level: 0,
+            handler: CommandHandler::Function { args: &[], f },
+        }
+    }
+
+    /// Creates a registration for a command which may only be
+    /// run by operators, at the default operator level.
+    pub fn operator(name: &'static str, usage: &'static str, f: &'static dyn CommandFn) -> Self {
+        Self {
+            permission_level: DEFAULT_OPERATOR_LEVEL,
+            ..Self::new(name, usage, f)
+        }
+    }
+
+    /// Creates a registration for a command declared as a tree,
+    /// named by the literal at the root of the tree.
+    pub fn tree(usage: &'static str, root: Node) -> Self {
+        Self {
+            name: root.name(),
+            usage,
+            permission_level: 0,
+            handler: CommandHandler::Tree(root),
+        }
+    }
+
+    /// Creates a registration for a command declared as a tree which
+    /// may only be run by operators, at the default operator level.
+    pub fn operator_tree(usage: &'static str, root: Node) -> Self {
+        Self {
+            permission_level: DEFAULT_OPERATOR_LEVEL,
+            ..Self::tree(usage, root)
+        }
+    }
+
+    /// Sets the kinds of the command's arguments. Commands declared
+    /// as trees take their arguments from the tree instead.
+    pub fn with_args(mut self, kinds: &'static [ArgKind]) -> Self {
+        if let CommandHandler::Function { args, .. } = &mut self.handler {
+            *args = kinds;
+        }
+        self
+    }
+
+    /// Sets the operator level needed to run the command.
+    pub fn with_permission_level(mut self, level: u8) -> Self {
+        self.permission_level = level;
+        self
+    }
+
+    /// Returns the permission node for running the
+    /// command, such as `minecraft.command.ban`.
+    pub fn permission_node(&self) -> String {
+        format!("minecraft.command.{}", self.name)
+    }
+}
+
+inventory::collect!(CommandRegistration);
+
+/// Finds the registered command with the given name.
+pub fn find_command(name: &str) -> Option<&'static CommandRegistration> {
+    inventory::iter::<CommandRegistration>
+        .into_iter()
+        .find(|reg| reg.name.eq_ignore_ascii_case(name))
+}
+
+/// Executes commands sent by players.
+#[fecs::event_handler]
+pub fn on_command_dispatch(event: &CommandEvent, game: &mut Game, world: &mut World) {
+    let args: Vec<&str> = event.command.split_whitespace().collect();
+    let (name, args) = match args.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+
+    let registration = match find_command(name) {
+        Some(registration) => registration,
+        None => {
+            send_message(
+                world,
+                event.sender,
+                Text::from("Unknown command. Type \"/help\" for help.") * Color::Red,
+            );
+            return;
+        }
+    };
+
+    log::info!(
+        "{} issued server command: /{}",
+        world.get::<Name>(event.sender).0,
+        event.command
+    );
+
+    let mut ctx = CommandCtx {
+        game,
+        world,
+        sender: event.sender,
+    };
+
+    let result = if !can_run(ctx.game, ctx.world, event.sender, registration) {
+        Err(CommandError::NoPermission)
+    } else {
+        match &registration.handler {
+            CommandHandler::Function { f, .. } => f(&mut ctx, args),
+            CommandHandler::Tree(root) => parse_command(root, event.command.trim_start())
+                .and_then(|(f, args)| f(&mut ctx, &args)),
+        }
+    };
+
+    match result {
+        Ok(()) => (),
+        Err(CommandError::Usage) => {
+            ctx.reply(Text::from(format!("Usage: {}", registration.usage)) * Color::Red)
+        }
+        Err(CommandError::NoPermission) => {
+            ctx.reply(Text::from("You do not have permission to use this command.") * Color::Red)
+        }
+        Err(CommandError::Message(msg)) => ctx.reply(Text::from(msg) * Color::Red),
+    }
+}
+
+/// Returns whether a player may run a command.
+pub fn can_run(game: &Game, world: &World, player: Entity, reg: &CommandRegistration) -> bool {
+    let node = reg.permission_node();
+    has_permission(
+        game,
+        world,
+        player,
+        Permission::new(&node, reg.permission_level),
+    )
+}
+
+/// Sends a chat message to a single player.
+/// Messages sent to the console are logged.
+pub fn send_message(world: &World, player: Entity, text: impl Into<Text>) {
+    let text = text.into();
+    if world.has::<Console>(player) {
+        log::info!("{}", text.to_plain());
+        return;
+    }
+
+    let packet = ChatMessageClientbound {
+        json_data: TextRoot::from(text).into(),
+        position: 1,
+    };
+
+    if let Some(network) = world.try_get::<Network>(player) {
+        network.send(packet);
+    }
+}
+
+/// Finds the online player with the given name, ignoring case.
+pub fn find_player(world: &World, name: &str) -> Option<Entity> {
+    <Read<Name>>::query()
+        .filter(component::<Player>())
+        .iter_entities(world.inner())
+        .find(|(_, player_name)| player_name.0.eq_ignore_ascii_case(name))
+        .map(|(entity, _)| entity)
+}
+
+/// Finds the players named by a command argument,
+/// which is a player name or a selector such as `@a`.
+pub fn find_targets(ctx: &CommandCtx, target: &str) -> Result<Vec<Entity>, CommandError> {
+    EntitySelector::parse(target, false, true)?.resolve(ctx)
+}
+
+/// Parses a numeric command argument.
+pub fn parse_number<T: FromStr>(arg: &str) -> Result<T, CommandError> {
+    arg.parse()
+        .map_err(|_| CommandError::message(format!("Invalid number \"{}\"", arg)))
+}
+
+/// Parses a coordinate, which is relative to `origin` if prefixed with `~`.
+pub fn parse_coordinate(arg: &str, origin: f64) -> Result<f64, CommandError> {
+    if arg.starts_with('~') {
+        let offset = &arg[1..];
+        if offset.is_empty() {
+            Ok(origin)
+        } else {
+            Ok(origin + parse_number::<f64>(offset)?)
+        }
+    } else {
+        parse_number(arg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_coordinates() {
+        assert_eq!(parse_coordinate("~", 10.0).unwrap(), 10.0);
+        assert_eq!(parse_coordinate("~-2.5", 10.0).unwrap(), 7.5);
+        assert_eq!(parse_coordinate("3", 10.0).unwrap(), 3.0);
+        assert!(parse_coordinate("~x", 10.0).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! Clickable confirm/deny prompts.
+//!
+//! A prompt is sent to a player as a chat message with `[Confirm]`
+//! and `[Deny]` buttons. Each button runs `/confirm <token> <choice>`,
+//! where the token is a random, single-use value bound to the player.
+//! Once a token has been used or has expired, it is rejected.
+
+use crate::{send_message, ArgKind, CommandCtx, CommandError, CommandRegistration, CommandResult};
+use ahash::AHashMap;
+use feather_core::text::{Color, Text, TextComponentBuilder};
+use feather_server_types::{Game, PlayerLeaveEvent, TPS};
+use fecs::{Entity, ResourcesProvider, World};
+use rand::Rng;
+use std::sync::Arc;
+
+inventory::submit! {
+    CommandRegistration::new("confirm", "/confirm <token> <accept|deny>", &confirm)
+        .with_args(&[ArgKind::Word, ArgKind::Choice(&["accept", "deny"])])
+}
+
+/// Default number of ticks after which a prompt expires.
+pub const DEFAULT_CONFIRMATION_TIMEOUT: u64 = 30 * TPS;
+
+pub type ConfirmCallback = Box<dyn FnOnce(&mut CommandCtx) + Send + Sync>;
+
+/// A confirmation prompt which has been sent to a player
+/// and is awaiting their response.
+pub struct PendingConfirmation {
+    /// The player to whom the prompt was sent. Only
+    /// this player may respond to it.
+    pub player: Entity,
+    /// Tick count at which the prompt expires.
+    pub expires_at: u64,
+    on_confirm: ConfirmCallback,
+    on_deny: Option<ConfirmCallback>,
+}
+
+/// Error returned when a confirmation token is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    /// The token does not exist. It may have already been used.
+    Unknown,
+    /// The token belongs to another player.
+    WrongPlayer,
+    /// The token has expired.
+    Expired,
+}
+
+/// Resource storing pending confirmation prompts, keyed by token.
+#[derive(Default)]
+pub struct Confirmations {
+    pending: AHashMap<u64, PendingConfirmation>,
+}
+
+impl Confirmations {
+    /// Inserts a pending confirmation under a new random token,
+    /// returning the token.
+    pub fn insert(&mut self, rng: &mut impl Rng, pending: PendingConfirmation) -> u64 {
+        loop {
+            let token = rng.gen();
+            if !self.pending.contains_key(&token) {
+                self.pending.insert(token, pending);
+                return token;
+            }
+        }
+    }
+
+    /// Consumes the given token. The token is invalidated
+    /// even if an error is returned, unless it belongs to another player.
+    pub fn take(
+        &mut self,
+        token: u64,
+        player: Entity,
+        tick: u64,
+    ) -> Result<PendingConfirmation, TokenError> {
+        match self.pending.get(&token) {
+            None => return Err(TokenError::Unknown),
+            Some(pending) if pending.player != player => return Err(TokenError::WrongPlayer),
+            Some(_) => (),
+        }
+
+        let pending = self.pending.remove(&token).unwrap();
+        if pending.expires_at <= tick {
+            Err(TokenError::Expired)
+        } else {
+            Ok(pending)
+        }
+    }
+
+    /// Removes all confirmations which have expired at the given tick,
+    /// returning the players whose prompts expired.
+    pub fn remove_expired(&mut self, tick: u64) -> Vec<Entity> {
+        let mut expired = vec![];
+        self.pending.retain(|_, pending| {
+            if pending.expires_at <= tick {
+                expired.push(pending.player);
+                false
+            } else {
+                true
+            }
+        });
+        expired
+    }
+
+    /// Removes all confirmations sent to the given player.
+    pub fn remove_player(&mut self, player: Entity) {
+        self.pending.retain(|_, pending| pending.player != player);
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// A confirmation prompt which can be sent to a player.
+pub struct Confirmation {
+    prompt: Text,
+    timeout: u64,
+    on_confirm: ConfirmCallback,
+    on_deny: Option<ConfirmCallback>,
+}
+
+impl Confirmation {
+    /// Creates a new prompt. `on_confirm` is invoked
+    /// when the player clicks `[Confirm]`.
+    pub fn new(
+        prompt: impl Into<Text>,
+        on_confirm: impl FnOnce(&mut CommandCtx) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            prompt: prompt.into(),
+            timeout: DEFAULT_CONFIRMATION_TIMEOUT,
+            on_confirm: Box::new(on_confirm),
+            on_deny: None,
+        }
+    }
+
+    /// Sets a function to invoke when the player clicks `[Deny]`.
+    pub fn on_deny(
+        mut self,
+        on_deny: impl FnOnce(&mut CommandCtx) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_deny = Some(Box::new(on_deny));
+        self
+    }
+
+    /// Sets the number of ticks after which the prompt expires.
+    pub fn timeout(mut self, ticks: u64) -> Self {
+        self.timeout = ticks;
+        self
+    }
+
+    /// Sends the prompt to the given player.
+    pub fn send(self, game: &Game, world: &World, player: Entity) {
+        let pending = PendingConfirmation {
+            player,
+            expires_at: game.tick_count + self.timeout,
+            on_confirm: self.on_confirm,
+            on_deny: self.on_deny,
+        };
+
+        let token = game
+            .resources
+            .get_mut::<Confirmations>()
+            .insert(&mut *game.rng(), pending);
+
+        let confirm = Text::from("[Confirm]")
+            .green()
+            .bold()
+            .on_click_run_command(format!("/confirm {:x} accept", token))
+            .on_hover_show_text("Click to confirm");
+        let deny = Text::from("[Deny]")
+            .red()
+            .bold()
+            .on_click_run_command(format!("/confirm {:x} deny", token))
+            .on_hover_show_text("Click to deny");
+
+        send_message(
+            world,
+            player,
+            self.prompt + Text::from(" ") + confirm + Text::from(" ") + deny,
+        );
+    }
+}
+
+fn confirm(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    let (token, accept) = match args {
+        [token, "accept"] => (token, true),
+        [token, "deny"] => (token, false),
+        _ => return Err(CommandError::Usage),
+    };
+    let token = u64::from_str_radix(token, 16).map_err(|_| CommandError::Usage)?;
+
+    let resources = Arc::clone(&ctx.game.resources);
+    let result = resources
+        .get_mut::<Confirmations>()
+        .take(token, ctx.sender, ctx.game.tick_count);
+
+    let pending = result.map_err(|e| {
+        CommandError::message(match e {
+            TokenError::Unknown => "This prompt is no longer valid.",
+            TokenError::WrongPlayer => "This prompt was not sent to you.",
+            TokenError::Expired => "This prompt has expired.",
+        })
+    })?;
+
+    if accept {
+        (pending.on_confirm)(ctx);
+    } else if let Some(on_deny) = pending.on_deny {
+        on_deny(ctx);
+    } else {
+        ctx.reply(Text::from("Cancelled.") * Color::Gray);
+    }
+
+    Ok(())
+}
+
+/// System which removes expired confirmation prompts.
+#[fecs::system]
+pub fn expire_confirmations(
+    game: &mut Game,
+    world: &mut World,
+    #[default] confirmations: &mut Confirmations,
+) {
+    if game.tick_count % TPS != 0 {
+        return;
+    }
+
+    for player in confirmations.remove_expired(game.tick_count) {
+        if world.is_alive(player) {
+            send_message(
+                world,
+                player,
+                Text::from("A prompt has expired.") * Color::Gray,
+            );
+        }
+    }
+}
+
+/// Discards the prompts of players who leave, since
+/// they can no longer respond to them.
+#[fecs::event_handler]
+pub fn on_player_leave_cancel_confirmations(
+    event: &PlayerLeaveEvent,
+    #[default] confirmations: &mut Confirmations,
+) {
+    confirmations.remove_player(event.player);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+    use std::iter;
+
+    fn pending(player: Entity, expires_at: u64) -> PendingConfirmation {
+        PendingConfirmation {
+            player,
+            expires_at,
+            on_confirm: Box::new(|_| ()),
+            on_deny: None,
+        }
+    }
+
+    fn players() -> (Entity, Entity) {
+        let mut world = World::new();
+        let entities = world.spawn(iter::repeat(()).take(2));
+        (entities[0], entities[1])
+    }
+
+    #[test]
+    fn token_is_single_use() {
+        let (player, _) = players();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut confirmations = Confirmations::default();
+
+        let token = confirmations.insert(&mut rng, pending(player, 100));
+        assert!(confirmations.take(token, player, 0).is_ok());
+        assert_eq!(
+            confirmations.take(token, player, 0).err(),
+            Some(TokenError::Unknown)
+        );
+    }
+
+    #[test]
+    fn token_bound_to_player() {
+        let (player, other) = players();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut confirmations = Confirmations::default();
+
+        let token = confirmations.insert(&mut rng, pending(player, 100));
+        assert_eq!(
+            confirmations.take(token, other, 0).err(),
+            Some(TokenError::WrongPlayer)
+        );
+        // the rightful player may still use it
+        assert!(confirmations.take(token, player, 0).is_ok());
+    }
+
+    #[test]
+    fn token_expiry() {
+        let (player, _) = players();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut confirmations = Confirmations::default();
+
+        let token = confirmations.insert(&mut rng, pending(player, 100));
+        assert_eq!(
+            confirmations.take(token, player, 100).err(),
+            Some(TokenError::Expired)
+        );
+
+        confirmations.insert(&mut rng, pending(player, 100));
+        confirmations.insert(&mut rng, pending(player, 200));
+        assert_eq!(confirmations.remove_expired(150), vec![player]);
+        assert_eq!(confirmations.len(), 1);
+    }
+
+    #[test]
+    fn remove_player_prompts() {
+        let (player, other) = players();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut confirmations = Confirmations::default();
+
+        confirmations.insert(&mut rng, pending(player, 100));
+        confirmations.insert(&mut rng, pending(player, 100));
+        let token = confirmations.insert(&mut rng, pending(other, 100));
+
+        confirmations.remove_player(player);
+        assert_eq!(confirmations.len(), 1);
+        assert!(confirmations.take(token, other, 0).is_ok());
+    }
+}
@@ -0,0 +1,165 @@
+//! The server console: commands typed into the terminal running
+//! the server, which are run by an entity with the `Console`
+//! marker and have their feedback logged.
+//!
+//! Standard input is read a line at a time on its own thread, and
+//! the lines are dispatched on the main thread during the tick.
+//! As the terminal only hands over complete lines, completions
+//! are requested by ending a line with a tab before pressing enter.
+
+use crate::suggest;
+use feather_core::util::Position;
+use feather_server_types::{CommandEvent, Console, Game, Name};
+use fecs::{Entity, EntityBuilder, World};
+use std::io::{self, BufRead};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Mutex;
+use std::thread;
+
+/// Name of the console, as shown in command feedback.
+pub const CONSOLE_NAME: &str = "Server";
+
+/// Resource receiving the lines typed into the console.
+pub struct ConsoleInput {
+    /// The console entity, which sends the commands.
+    pub console: Entity,
+    lines: Mutex<Receiver<String>>,
+}
+
+impl ConsoleInput {
+    /// Creates the console entity and starts
+    /// the thread reading from standard input.
+    pub fn start(game: &Game, world: &mut World) -> io::Result<Self> {
+        let console = create_console(game, world);
+
+        let (tx, rx) = mpsc::channel();
+        thread::Builder::new()
+            .name(String::from("console"))
+            .spawn(move || {
+                let stdin = io::stdin();
+                for line in stdin.lock().lines() {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(e) => {
+                            log::error!("Failed to read from the console: {}", e);
+                            break;
+                        }
+                    };
+                    if tx.send(line).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            console,
+            lines: Mutex::new(rx),
+        })
+    }
+
+    /// Returns the next line typed into the console, if any.
+    fn next_line(&self) -> Option<String> {
+        match self.lines.lock().unwrap().try_recv() {
+            Ok(line) => Some(line),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+}
+
+/// Spawns the console entity. It is positioned at the world
+/// spawn, from which relative coordinates are resolved.
+pub fn create_console(game: &Game, world: &mut World) -> Entity {
+    let pos = Position {
+        x: f64::from(game.level.spawn_x),
+        y: f64::from(game.level.spawn_y),
+        z: f64::from(game.level.spawn_z),
+        pitch: 0.0,
+        yaw: 0.0,
+        on_ground: false,
+    };
+    EntityBuilder::new()
+        .with(Console)
+        .with(Name(CONSOLE_NAME.to_owned()))
+        .with(pos)
+        .build()
+        .spawn_in(world)
+}
+
+/// What to do with a line typed into the console.
+#[derive(Debug, PartialEq, Eq)]
+enum ConsoleLine<'a> {
+    /// Run a command, given without the leading slash.
+    Command(&'a str),
+    /// List completions for the text before the tab.
+    Complete(&'a str),
+    Empty,
+}
+
+impl<'a> ConsoleLine<'a> {
+    fn parse(line: &'a str) -> Self {
+        if let Some(tab) = line.find('\t') {
+            return ConsoleLine::Complete(line[..tab].trim_start());
+        }
+
+        let command = line.trim();
+        let command = if command.starts_with('/') {
+            &command[1..]
+        } else {
+            command
+        };
+        if command.is_empty() {
+            ConsoleLine::Empty
+        } else {
+            ConsoleLine::Command(command)
+        }
+    }
+}
+
+/// System which runs the commands typed into the console
+/// since the last tick.
+#[fecs::system]
+pub fn dispatch_console_commands(game: &mut Game, world: &mut World, input: &ConsoleInput) {
+    while let Some(line) = input.next_line() {
+        match ConsoleLine::parse(&line) {
+            ConsoleLine::Command(command) => game.handle(
+                world,
+                CommandEvent {
+                    sender: input.console,
+                    command: command.to_owned(),
+                },
+            ),
+            ConsoleLine::Complete(text) => {
+                let suggestions = suggest(game, world, input.console, text);
+                if suggestions.matches.is_empty() {
+                    log::info!("No completions for \"{}\"", text);
+                } else {
+                    log::info!("Completions: {}", suggestions.matches.join(", "));
+                }
+            }
+            ConsoleLine::Empty => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lines() {
+        assert_eq!(
+            ConsoleLine::parse("/time set day "),
+            ConsoleLine::Command("time set day")
+        );
+        assert_eq!(
+            ConsoleLine::parse("op Notch"),
+            ConsoleLine::Command("op Notch")
+        );
+        assert_eq!(ConsoleLine::parse("  \t"), ConsoleLine::Complete(""));
+        assert_eq!(
+            ConsoleLine::parse("weather r\t"),
+            ConsoleLine::Complete("weather r")
+        );
+        assert_eq!(ConsoleLine::parse("   "), ConsoleLine::Empty);
+    }
+}
@@ -0,0 +1,106 @@
+//! The `/difficulty` command.
+
+use crate::{
+    literal, Arguments, CommandCtx, CommandError, CommandRegistration, CommandResult, Node,
+};
+use feather_core::text::{Color, Text};
+use feather_core::util::Difficulty;
+use feather_server_types::DifficultyChangeEvent;
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/difficulty [peaceful|easy|normal|hard]",
+        difficulty_tree(),
+    )
+}
+
+/// Difficulties with the literals that select them.
+const DIFFICULTIES: &[(&str, Difficulty)] = &[
+    ("peaceful", Difficulty::Peaceful),
+    ("easy", Difficulty::Easy),
+    ("normal", Difficulty::Medium),
+    ("hard", Difficulty::Hard),
+];
+
+fn difficulty_tree() -> Node {
+    DIFFICULTIES.iter().fold(
+        literal("difficulty").executes(&difficulty),
+        |node, &(name, _)| node.then(literal(name).executes(&difficulty)),
+    )
+}
+
+/// Returns the name of a difficulty as shown to players.
+fn difficulty_name(difficulty: Difficulty) -> &'static str {
+    match difficulty {
+        Difficulty::Peaceful => "Peaceful",
+        Difficulty::Easy => "Easy",
+        Difficulty::Medium => "Normal",
+        Difficulty::Hard => "Hard",
+    }
+}
+
+/// Returns the difficulty selected by the command's arguments, if any.
+fn selected_difficulty(args: &Arguments) -> Option<Difficulty> {
+    let name = *args.literals().first()?;
+    DIFFICULTIES
+        .iter()
+        .find(|(literal, _)| *literal == name)
+        .map(|(_, difficulty)| *difficulty)
+}
+
+fn difficulty(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let current = ctx.game.difficulty();
+    let new = match selected_difficulty(args) {
+        Some(new) => new,
+        None => {
+            ctx.reply(
+                Text::from(format!("The difficulty is {}", difficulty_name(current))) * Color::Gray,
+            );
+            return Ok(());
+        }
+    };
+
+    if new == current {
+        return Err(CommandError::message(format!(
+            "The difficulty did not change; it is already set to {}",
+            difficulty_name(current)
+        )));
+    }
+
+    ctx.game.level.difficulty = new.id() as i8;
+    ctx.game.handle(
+        ctx.world,
+        DifficultyChangeEvent {
+            from: current,
+            to: new,
+        },
+    );
+
+    ctx.reply(
+        Text::from(format!(
+            "The difficulty has been set to {}",
+            difficulty_name(new)
+        )) * Color::Gray,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_command;
+
+    #[test]
+    fn difficulty_literals() {
+        let tree = difficulty_tree();
+
+        let (_, args) = parse_command(&tree, "difficulty").unwrap();
+        assert_eq!(selected_difficulty(&args), None);
+        let (_, args) = parse_command(&tree, "difficulty normal").unwrap();
+        assert_eq!(selected_difficulty(&args), Some(Difficulty::Medium));
+        let (_, args) = parse_command(&tree, "difficulty peaceful").unwrap();
+        assert_eq!(selected_difficulty(&args), Some(Difficulty::Peaceful));
+
+        assert!(parse_command(&tree, "difficulty medium").is_err());
+    }
+}
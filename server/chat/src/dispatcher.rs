@@ -0,0 +1,424 @@
+//! Commands declared as trees of literal and typed argument
+//! nodes, in the style of Mojang's Brigadier.
+//!
+//! A command is parsed by walking from its root literal through
+//! the children matching each argument, and executed by the last
+//! node reached, which receives the parsed arguments by name:
+//!
+//! ```ignore
+//! inventory::submit! {
+//!     CommandRegistration::operator_tree(
+//!         "/netstat [player]",
+//!         literal("netstat")
+//!             .executes(&summary)
+//!             .then(argument("player", ArgParser::PLAYER).executes(&player)),
+//!     )
+//! }
+//! ```
+//!
+//! The same tree is sent to clients in `DeclareCommands`, so
+//! they parse and highlight arguments as the server does.
+
+use crate::{
+    ArgParser, ArgValue, CommandCtx, CommandError, CommandResult, FromArg, ParseError, StringReader,
+};
+use feather_core::network::packets::{CommandNode, CommandNodeKind};
+
+pub trait ExecuteFn:
+    Fn(&mut CommandCtx, &Arguments) -> CommandResult + Send + Sync + 'static
+{
+}
+
+impl<F> ExecuteFn for F where
+    F: Fn(&mut CommandCtx, &Arguments) -> CommandResult + Send + Sync + 'static
+{
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    /// A fixed word.
+    Literal(&'static str),
+    /// An argument parsed by `parser`, whose
+    /// value is passed to the command as `name`.
+    Argument {
+        name: &'static str,
+        parser: ArgParser,
+    },
+}
+
+/// A node in a command tree.
+pub struct Node {
+    pub kind: NodeKind,
+    pub children: Vec<Node>,
+    /// The function run if the command ends at this node.
+    pub executes: Option<&'static dyn ExecuteFn>,
+}
+
+/// Creates a node matching a fixed word.
+pub fn literal(name: &'static str) -> Node {
+    Node {
+        kind: NodeKind::Literal(name),
+        children: vec![],
+        executes: None,
+    }
+}
+
+/// Creates a node for an argument.
+pub fn argument(name: &'static str, parser: ArgParser) -> Node {
+    Node {
+        kind: NodeKind::Argument { name, parser },
+        children: vec![],
+        executes: None,
+    }
+}
+
+impl Node {
+    /// Adds a child node.
+    pub fn then(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Sets the function run if the command ends at this node.
+    pub fn executes(mut self, f: &'static dyn ExecuteFn) -> Self {
+        self.executes = Some(f);
+        self
+    }
+
+    /// Returns the literal or argument name of this node.
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            NodeKind::Literal(name) => name,
+            NodeKind::Argument { name, .. } => name,
+        }
+    }
+}
+
+/// The arguments parsed for a command.
+#[derive(Debug, Default)]
+pub struct Arguments {
+    values: Vec<(&'static str, ArgValue)>,
+    literals: Vec<&'static str>,
+}
+
+impl Arguments {
+    /// Returns the value of the argument with the given name, or
+    /// `None` if the argument was not given or is of another type.
+    pub fn get<T: FromArg>(&self, name: &str) -> Option<T> {
+        self.value(name).and_then(T::from_arg)
+    }
+
+    pub fn value(&self, name: &str) -> Option<&ArgValue> {
+        self.values
+            .iter()
+            .find(|(arg, _)| *arg == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.value(name).is_some()
+    }
+
+    /// Returns the literals matched after the command name, in order.
+    /// Useful when one function executes many literal nodes.
+    pub fn literals(&self) -> &[&'static str] {
+        &self.literals
+    }
+}
+
+/// Why a command failed to parse.
+#[derive(Debug)]
+enum Failure {
+    /// The command ended at a node which does not execute.
+    Incomplete(usize),
+    Error(ParseError),
+}
+
+impl Failure {
+    fn cursor(&self) -> usize {
+        match self {
+            Failure::Incomplete(cursor) => *cursor,
+            Failure::Error(error) => error.cursor,
+        }
+    }
+}
+
+/// Parses `command`, including its name but not the leading
+/// slash, against the tree rooted at `node`. Returns the function
+/// to execute and the parsed arguments.
+pub fn parse_command(
+    node: &Node,
+    command: &str,
+) -> Result<(&'static dyn ExecuteFn, Arguments), CommandError> {
+    let mut reader = StringReader::new(command);
+    // The name has already been matched to find the command.
+    reader.read_word();
+
+    let mut args = Arguments::default();
+    match parse_children(node, &mut reader, &mut args) {
+        Ok(f) => Ok((f, args)),
+        Err(Failure::Incomplete(_)) => Err(CommandError::Usage),
+        Err(Failure::Error(error)) => Err(CommandError::Message(format_error(command, &error))),
+    }
+}
+
+/// Parses the rest of the command from the children of `node`.
+///
+/// A literal which matches the next word takes precedence over
+/// arguments. Otherwise each argument is tried in turn, and if
+/// none leads to an executable node, the failure which got
+/// furthest into the command is returned.
+fn parse_children(
+    node: &Node,
+    reader: &mut StringReader,
+    args: &mut Arguments,
+) -> Result<&'static dyn ExecuteFn, Failure> {
+    if !reader.can_read() {
+        return node
+            .executes
+            .ok_or_else(|| Failure::Incomplete(reader.cursor()));
+    }
+    if node.children.is_empty() {
+        return Err(Failure::Error(ParseError::new(
+            "Incorrect argument for command",
+            reader.cursor(),
+        )));
+    }
+    if reader.peek() != Some(' ') {
+        return Err(Failure::Error(ParseError::new(
+            "Expected whitespace to end one argument, but found trailing data",
+            reader.cursor(),
+        )));
+    }
+    reader.skip();
+
+    let start = reader.cursor();
+    let word = reader.read_word();
+    let literal = node.children.iter().find(|child| match child.kind {
+        NodeKind::Literal(name) => name == word,
+        NodeKind::Argument { .. } => false,
+    });
+    let candidates: Vec<&Node> = match literal {
+        Some(literal) => vec![literal],
+        None => node
+            .children
+            .iter()
+            .filter(|child| match child.kind {
+                NodeKind::Argument { .. } => true,
+                NodeKind::Literal(_) => false,
+            })
+            .collect(),
+    };
+
+    let mut failure: Option<Failure> = None;
+    for child in candidates {
+        reader.set_cursor(start);
+        let (values, literals) = (args.values.len(), args.literals.len());
+        let parsed = match &child.kind {
+            NodeKind::Literal(name) => {
+                reader.read_word();
+                args.literals.push(*name);
+                Ok(())
+            }
+            NodeKind::Argument { name, parser } => parser
+                .parse(reader)
+                .map(|value| args.values.push((*name, value)))
+                .map_err(Failure::Error),
+        };
+
+        match parsed.and_then(|()| parse_children(child, reader, args)) {
+            Ok(f) => return Ok(f),
+            Err(error) => {
+                args.values.truncate(values);
+                args.literals.truncate(literals);
+                failure = match failure {
+                    Some(previous) if previous.cursor() >= error.cursor() => Some(previous),
+                    _ => Some(error),
+                };
+            }
+        }
+    }
+
+    Err(failure
+        .unwrap_or_else(|| Failure::Error(ParseError::new("Unknown or incomplete command", start))))
+}
+
+/// Returns the literals which may follow `words`, the complete words
+/// after the command name, in the tree rooted at `node`. Only literals
+/// are followed, so there are none after an argument.
+pub fn literal_completions(node: &Node, words: &[&str]) -> Vec<&'static str> {
+    let mut node = node;
+    for word in words {
+        let next = node.children.iter().find(|child| match child.kind {
+            NodeKind::Literal(name) => name == *word,
+            NodeKind::Argument { .. } => false,
+        });
+        node = match next {
+            Some(next) => next,
+            None => return vec![],
+        };
+    }
+
+    node.children
+        .iter()
+        .filter_map(|child| match child.kind {
+            NodeKind::Literal(name) => Some(name),
+            NodeKind::Argument { .. } => None,
+        })
+        .collect()
+}
+
+/// Formats a parse error along with the text leading up to it.
+fn format_error(command: &str, error: &ParseError) -> String {
+    /// Number of characters shown before the error.
+    const CONTEXT: usize = 10;
+
+    let command = format!("/{}", command);
+    let cursor = (error.cursor + 1).min(command.len());
+    let mut start = cursor.saturating_sub(CONTEXT);
+    while !command.is_char_boundary(start) {
+        start += 1;
+    }
+    format!(
+        "{}\n{}{}<--[HERE]",
+        error.message,
+        if start > 0 { "..." } else { "" },
+        &command[start..cursor]
+    )
+}
+
+/// Appends `node` and its descendants to a `DeclareCommands`
+/// node list, returning the index of `node`.
+pub(crate) fn declare(node: &Node, nodes: &mut Vec<CommandNode>) -> i32 {
+    let index = nodes.len();
+    let kind = match &node.kind {
+        NodeKind::Literal(name) => CommandNodeKind::Literal((*name).to_owned()),
+        NodeKind::Argument { name, parser } => CommandNodeKind::Argument {
+            name: (*name).to_owned(),
+            parser: parser.identifier().to_owned(),
+            properties: parser.properties(),
+        },
+    };
+    nodes.push(CommandNode {
+        kind,
+        executable: node.executes.is_some(),
+        children: vec![],
+        redirect: None,
+        suggestions: None,
+    });
+
+    for child in &node.children {
+        let child = declare(child, nodes);
+        nodes[index].children.push(child);
+    }
+    index as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noop(_: &mut CommandCtx, _: &Arguments) -> CommandResult {
+        Ok(())
+    }
+
+    fn tree() -> Node {
+        literal("give")
+            .then(
+                argument("targets", ArgParser::PLAYERS).then(
+                    argument("item", ArgParser::Item).executes(&noop).then(
+                        argument(
+                            "count",
+                            ArgParser::Integer {
+                                min: Some(1),
+                                max: None,
+                            },
+                        )
+                        .executes(&noop),
+                    ),
+                ),
+            )
+            .then(literal("help").executes(&noop))
+    }
+
+    #[test]
+    fn parse_arguments() {
+        let tree = tree();
+        let (_, args) = parse_command(&tree, "give @a diamond 5").unwrap();
+        assert_eq!(args.get::<i32>("count"), Some(5));
+        assert!(args.has("targets"));
+        assert_eq!(args.get::<String>("count"), None);
+
+        let (_, args) = parse_command(&tree, "give @a diamond").unwrap();
+        assert!(!args.has("count"));
+    }
+
+    #[test]
+    fn literals_take_precedence() {
+        let tree = literal("test")
+            .then(literal("help").then(argument("page", ArgParser::INTEGER).executes(&noop)))
+            .then(argument("name", ArgParser::Word).executes(&noop));
+        // "help" would be a valid name, but the literal is chosen.
+        match parse_command(&tree, "test help") {
+            Err(CommandError::Usage) => (),
+            _ => panic!(),
+        }
+        let (_, args) = parse_command(&tree, "test other").unwrap();
+        assert_eq!(args.get::<String>("name").as_deref(), Some("other"));
+        assert!(args.literals().is_empty());
+        let (_, args) = parse_command(&tree, "test help 2").unwrap();
+        assert_eq!(args.literals(), ["help"]);
+    }
+
+    #[test]
+    fn incomplete_and_invalid_commands() {
+        let tree = tree();
+        match parse_command(&tree, "give @a") {
+            Err(CommandError::Usage) => (),
+            _ => panic!(),
+        }
+        match parse_command(&tree, "give @a diamond 0") {
+            Err(CommandError::Message(message)) => assert_eq!(
+                message,
+                "Integer must not be less than 1, found 0\n...a diamond <--[HERE]"
+            ),
+            _ => panic!(),
+        }
+        match parse_command(&tree, "give @a diamond 1 more") {
+            Err(CommandError::Message(message)) => {
+                assert!(message.starts_with("Incorrect argument for command"))
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn completes_literals() {
+        let tree = literal("weather")
+            .then(literal("clear").then(literal("now").executes(&noop)))
+            .then(literal("rain").executes(&noop))
+            .then(argument("duration", ArgParser::INTEGER).executes(&noop));
+        assert_eq!(literal_completions(&tree, &[]), vec!["clear", "rain"]);
+        assert_eq!(literal_completions(&tree, &["clear"]), vec!["now"]);
+        assert!(literal_completions(&tree, &["5"]).is_empty());
+    }
+
+    #[test]
+    fn declare_tree() {
+        let mut nodes = vec![];
+        assert_eq!(declare(&tree(), &mut nodes), 0);
+        assert_eq!(nodes.len(), 5);
+        assert_eq!(nodes[0].children, vec![1, 4]);
+        assert!(!nodes[1].executable);
+        assert!(nodes[2].executable);
+        assert_eq!(
+            nodes[3].kind,
+            CommandNodeKind::Argument {
+                name: "count".into(),
+                parser: "brigadier:integer".into(),
+                properties: vec![1, 0, 0, 0, 1],
+            }
+        );
+        assert_eq!(nodes[4].kind, CommandNodeKind::Literal("help".into()));
+    }
+}
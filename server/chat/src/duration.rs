@@ -0,0 +1,121 @@
+//! Parsing and formatting of durations such as `7d12h`,
+//! as used by moderation commands.
+
+use std::fmt::Write;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// Parses a duration string into a number of seconds.
+///
+/// The string consists of one or more integers, each followed by
+/// a unit: `s` (seconds), `m` (minutes), `h` (hours), `d` (days), or
+/// `w` (weeks). For example, `7d12h` is seven and a half days.
+///
+/// Returns `None` if the string is invalid or the duration is zero.
+pub fn parse_duration(s: &str) -> Option<u64> {
+    let mut total = 0u64;
+    let mut number: Option<u64> = None;
+
+    for c in s.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            number = Some(
+                number
+                    .unwrap_or(0)
+                    .checked_mul(10)?
+                    .checked_add(digit as u64)?,
+            );
+            continue;
+        }
+
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => MINUTE,
+            'h' => HOUR,
+            'd' => DAY,
+            'w' => WEEK,
+            _ => return None,
+        };
+
+        total = total.checked_add(number.take()?.checked_mul(unit)?)?;
+    }
+
+    if number.is_some() || total == 0 {
+        // trailing number without unit, or empty duration
+        return None;
+    }
+
+    Some(total)
+}
+
+/// Formats a number of seconds into a human-readable
+/// string, e.g. "7 days, 12 hours".
+pub fn format_duration(mut secs: u64) -> String {
+    if secs == 0 {
+        return String::from("0 seconds");
+    }
+
+    let mut result = String::new();
+    for (unit, name) in &[
+        (WEEK, "week"),
+        (DAY, "day"),
+        (HOUR, "hour"),
+        (MINUTE, "minute"),
+        (1, "second"),
+    ] {
+        let count = secs / unit;
+        if count == 0 {
+            continue;
+        }
+        secs %= unit;
+
+        if !result.is_empty() {
+            result.push_str(", ");
+        }
+        let plural = if count == 1 { "" } else { "s" };
+        write!(result, "{} {}{}", count, name, plural).unwrap();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_single_units() {
+        assert_eq!(parse_duration("30s"), Some(30));
+        assert_eq!(parse_duration("5m"), Some(5 * MINUTE));
+        assert_eq!(parse_duration("2h"), Some(2 * HOUR));
+        assert_eq!(parse_duration("7d"), Some(7 * DAY));
+        assert_eq!(parse_duration("1w"), Some(WEEK));
+    }
+
+    #[test]
+    fn parse_compound() {
+        assert_eq!(parse_duration("7d12h"), Some(7 * DAY + 12 * HOUR));
+        assert_eq!(parse_duration("1h30m15s"), Some(HOUR + 30 * MINUTE + 15));
+        assert_eq!(parse_duration("1D2H"), Some(DAY + 2 * HOUR));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("12"), None);
+        assert_eq!(parse_duration("d"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("0s"), None);
+        assert_eq!(parse_duration("99999999999999999999w"), None);
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(format_duration(0), "0 seconds");
+        assert_eq!(format_duration(1), "1 second");
+        assert_eq!(format_duration(7 * DAY + 12 * HOUR), "1 week, 12 hours");
+        assert_eq!(format_duration(HOUR + 2 * MINUTE), "1 hour, 2 minutes");
+    }
+}
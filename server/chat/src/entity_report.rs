@@ -0,0 +1,78 @@
+//! The `/entityreport` command, which lists the chunks
+//! containing the most entities.
+
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandRegistration, CommandResult,
+};
+use feather_core::text::{Color, Text};
+use feather_server_types::EntityCategory;
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/entityreport [count]",
+        literal("entityreport").executes(&entity_report).then(
+            argument(
+                "count",
+                ArgParser::Integer {
+                    min: Some(1),
+                    max: None,
+                },
+            )
+            .executes(&entity_report),
+        ),
+    )
+}
+
+/// Number of chunks listed if no count is given.
+const DEFAULT_COUNT: usize = 10;
+
+fn entity_report(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let count = args
+        .get::<i32>("count")
+        .map(|count| count as usize)
+        .unwrap_or(DEFAULT_COUNT);
+
+    let mut chunks: Vec<_> = ctx
+        .game
+        .chunk_entities
+        .0
+        .iter()
+        .filter(|(_, entities)| !entities.is_empty())
+        .collect();
+    chunks.sort_unstable_by(|(_, a), (_, b)| b.len().cmp(&a.len()));
+
+    if chunks.is_empty() {
+        ctx.reply(Text::from("There are no entities in loaded chunks.") * Color::Gray);
+        return Ok(());
+    }
+
+    ctx.reply(
+        Text::from(format!(
+            "Top {} chunks by entity count:",
+            count.min(chunks.len())
+        )) * Color::Gold,
+    );
+
+    for (chunk, entities) in chunks.into_iter().take(count) {
+        let (mut mobs, mut items, mut item_frames) = (0, 0, 0);
+        for entity in entities {
+            match ctx.world.try_get::<EntityCategory>(*entity).map(|c| *c) {
+                Some(EntityCategory::Mob) => mobs += 1,
+                Some(EntityCategory::Item) => items += 1,
+                Some(EntityCategory::ItemFrame) => item_frames += 1,
+                None => (),
+            }
+        }
+
+        ctx.reply(Text::from(format!(
+            "Chunk {}: {} entities (mobs: {}, items: {}, item frames: {})",
+            chunk,
+            entities.len(),
+            mobs,
+            items,
+            item_frames
+        )));
+    }
+
+    Ok(())
+}
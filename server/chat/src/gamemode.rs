@@ -0,0 +1,107 @@
+//! The `/gamemode` and `/defaultgamemode` commands.
+
+use crate::{
+    find_player, send_message, ArgKind, CommandCtx, CommandError, CommandRegistration,
+    CommandResult,
+};
+use feather_core::text::{Color, Text};
+use feather_core::util::Gamemode;
+use feather_server_types::Name;
+use feather_server_util::set_gamemode;
+
+inventory::submit! {
+    CommandRegistration::operator("gamemode", "/gamemode <mode> [player]", &gamemode)
+        .with_args(&[ArgKind::Choice(GAMEMODES), ArgKind::Player])
+}
+
+inventory::submit! {
+    CommandRegistration::operator("defaultgamemode", "/defaultgamemode <mode>", &defaultgamemode)
+        .with_args(&[ArgKind::Choice(GAMEMODES)])
+}
+
+/// Gamemode names suggested to players.
+const GAMEMODES: &[&str] = &["survival", "creative", "adventure", "spectator"];
+
+/// Parses a gamemode from its name or numeric ID.
+pub fn parse_gamemode(s: &str) -> Option<Gamemode> {
+    match s.to_lowercase().as_str() {
+        "survival" | "s" | "0" => Some(Gamemode::Survival),
+        "creative" | "c" | "1" => Some(Gamemode::Creative),
+        "adventure" | "a" | "2" => Some(Gamemode::Adventure),
+        "spectator" | "sp" | "3" => Some(Gamemode::Spectator),
+        _ => None,
+    }
+}
+
+/// Returns the name of a gamemode as shown to players.
+fn gamemode_name(gamemode: Gamemode) -> &'static str {
+    match gamemode {
+        Gamemode::Survival => "Survival Mode",
+        Gamemode::Creative => "Creative Mode",
+        Gamemode::Adventure => "Adventure Mode",
+        Gamemode::Spectator => "Spectator Mode",
+    }
+}
+
+fn parse_arg(mode: &str) -> Result<Gamemode, CommandError> {
+    parse_gamemode(mode)
+        .ok_or_else(|| CommandError::message(format!("Unknown game mode \"{}\"", mode)))
+}
+
+fn gamemode(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    let (mode, target) = match args {
+        [mode] => (parse_arg(mode)?, ctx.player()?),
+        [mode, name] => (
+            parse_arg(mode)?,
+            find_player(ctx.world, name)
+                .ok_or_else(|| CommandError::message(format!("Player {} not found", name)))?,
+        ),
+        _ => return Err(CommandError::Usage),
+    };
+
+    set_gamemode(ctx.game, ctx.world, target, mode);
+
+    let name = gamemode_name(mode);
+    if target == ctx.sender {
+        ctx.reply(Text::from(format!("Set own game mode to {}", name)) * Color::Gray);
+    } else {
+        send_message(
+            ctx.world,
+            target,
+            Text::from(format!("Your game mode has been updated to {}", name)) * Color::Gray,
+        );
+        let target_name = ctx.world.get::<Name>(target).0.clone();
+        ctx.reply(Text::from(format!("Set {}'s game mode to {}", target_name, name)) * Color::Gray);
+    }
+    Ok(())
+}
+
+fn defaultgamemode(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    let mode = match args {
+        [mode] => parse_arg(mode)?,
+        _ => return Err(CommandError::Usage),
+    };
+
+    ctx.game.level.game_type = i32::from(mode.id());
+
+    ctx.reply(
+        Text::from(format!(
+            "The default game mode is now {}",
+            gamemode_name(mode)
+        )) * Color::Gray,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_names_and_ids() {
+        assert_eq!(parse_gamemode("Creative"), Some(Gamemode::Creative));
+        assert_eq!(parse_gamemode("sp"), Some(Gamemode::Spectator));
+        assert_eq!(parse_gamemode("2"), Some(Gamemode::Adventure));
+        assert_eq!(parse_gamemode("hardcore"), None);
+    }
+}
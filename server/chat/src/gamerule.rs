@@ -0,0 +1,85 @@
+//! The `/gamerule` command.
+
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandRegistration, CommandResult, Node,
+};
+use feather_core::text::{Color, Text};
+use feather_server_types::{GameRule, GameRuleChangeEvent, GameRuleValue, GAME_RULES};
+
+inventory::submit! {
+    CommandRegistration::operator_tree("/gamerule <rule> [value]", tree())
+}
+
+/// Each rule has its own literal, followed by
+/// a value argument of the rule's type.
+fn tree() -> Node {
+    GAME_RULES.iter().fold(literal("gamerule"), |node, &rule| {
+        let parser = match rule {
+            GameRule::Bool(_) => ArgParser::Bool,
+            GameRule::Int(_) => ArgParser::INTEGER,
+        };
+        node.then(
+            literal(rule.name())
+                .executes(&query)
+                .then(argument("value", parser).executes(&set)),
+        )
+    })
+}
+
+/// Returns the rule named by the literal which was matched.
+fn rule(args: &Arguments) -> GameRule {
+    GameRule::from_name(args.literals()[0]).unwrap()
+}
+
+fn query(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let rule = rule(args);
+    let value = ctx.game.game_rules.get(rule);
+    ctx.reply(
+        Text::from(format!(
+            "Gamerule {} is currently set to: {}",
+            rule.name(),
+            value
+        )) * Color::Gray,
+    );
+    Ok(())
+}
+
+fn set(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let rule = rule(args);
+    let value = match rule {
+        GameRule::Bool(_) => GameRuleValue::Bool(args.get("value").unwrap()),
+        GameRule::Int(_) => GameRuleValue::Int(args.get("value").unwrap()),
+    };
+    ctx.game.game_rules.set(rule, value);
+    ctx.game
+        .handle(ctx.world, GameRuleChangeEvent { rule, value });
+
+    ctx.reply(
+        Text::from(format!("Gamerule {} is now set to: {}", rule.name(), value)) * Color::Gray,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_command;
+
+    #[test]
+    fn parse_rules() {
+        let tree = tree();
+        let (_, args) = parse_command(&tree, "gamerule keepInventory true").unwrap();
+        assert_eq!(
+            rule(&args),
+            GameRule::Bool(feather_server_types::KEEP_INVENTORY)
+        );
+        assert_eq!(args.get::<bool>("value"), Some(true));
+
+        let (_, args) = parse_command(&tree, "gamerule randomTickSpeed").unwrap();
+        assert_eq!(rule(&args).name(), "randomTickSpeed");
+        assert!(!args.has("value"));
+
+        assert!(parse_command(&tree, "gamerule randomTickSpeed true").is_err());
+        assert!(parse_command(&tree, "gamerule mobGriefing 1").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! The `/give` command.
+//!
+//! Clients suggest item identifiers themselves for
+//! the item argument, so none are sent by the server.
+
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandError, CommandRegistration,
+    CommandResult, EntitySelector,
+};
+use feather_core::inventory::{max_size, Inventory};
+use feather_core::items::ItemStack;
+use feather_core::text::{Color, Text};
+use feather_server_types::{InventoryUpdateEvent, ItemDropEvent, Name};
+use std::cmp::min;
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/give <targets> <item> [count]",
+        literal("give").then(
+            argument("targets", ArgParser::PLAYERS).then(
+                argument("item", ArgParser::Item).executes(&give).then(
+                    argument(
+                        "count",
+                        ArgParser::Integer {
+                            min: Some(1),
+                            max: None,
+                        },
+                    )
+                    .executes(&give),
+                ),
+            ),
+        ),
+    )
+}
+
+/// Number of full stacks which may be given at once.
+const MAX_STACKS: u32 = 100;
+
+fn give(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let stack = args.get::<ItemStack>("item").unwrap();
+    let count = args.get::<i32>("count").unwrap_or(1) as u32;
+    let stack_size = u32::from(max_size(stack.ty));
+    if count > stack_size * MAX_STACKS {
+        return Err(CommandError::message(format!(
+            "Can't give more than {} of {}",
+            stack_size * MAX_STACKS,
+            stack.ty.identifier()
+        )));
+    }
+
+    let targets = args
+        .get::<EntitySelector>("targets")
+        .unwrap()
+        .resolve(ctx)?;
+    for &player in &targets {
+        let mut remaining = count;
+        while remaining > 0 {
+            let amount = min(remaining, stack_size);
+            remaining -= amount;
+            let split = ItemStack {
+                amount: amount as u8,
+                ..stack.clone()
+            };
+
+            let (slots, left) = ctx
+                .world
+                .get_mut::<Inventory>(player)
+                .collect_item(split.clone());
+            if !slots.is_empty() {
+                ctx.game
+                    .handle(ctx.world, InventoryUpdateEvent { slots, player });
+            }
+            // Whatever does not fit is dropped by the player
+            if left > 0 {
+                ctx.game.handle(
+                    ctx.world,
+                    ItemDropEvent {
+                        slot: None,
+                        stack: ItemStack {
+                            amount: left,
+                            ..split
+                        },
+                        player,
+                    },
+                );
+            }
+        }
+    }
+
+    let recipient = match targets.as_slice() {
+        [player] => ctx.world.get::<Name>(*player).0.clone(),
+        _ => format!("{} players", targets.len()),
+    };
+    ctx.reply(
+        Text::from(format!(
+            "Gave {} [{}] to {}",
+            count,
+            stack.ty.identifier(),
+            recipient
+        )) * Color::Gray,
+    );
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! The `/kill` command.
+
+use crate::selector::describe_entity;
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandRegistration, CommandResult,
+    EntitySelector,
+};
+use feather_core::text::{Color, Text};
+use feather_server_types::{DamageCause, EntityDamageRequest, Health};
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/kill [targets]",
+        literal("kill")
+            .executes(&kill)
+            .then(argument("targets", ArgParser::ENTITIES).executes(&kill)),
+    )
+}
+
+fn kill(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let targets = match args.get::<EntitySelector>("targets") {
+        Some(targets) => targets.resolve(ctx)?,
+        None => vec![ctx.player()?],
+    };
+    // Computed now, since killed entities may be despawned.
+    let message = match targets.as_slice() {
+        [target] => format!("Killed {}", describe_entity(ctx.world, *target)),
+        _ => format!("Killed {} entities", targets.len()),
+    };
+
+    for &entity in &targets {
+        // Living entities die as usual, with death messages and
+        // drops. Others, such as items, are simply removed.
+        if ctx.world.has::<Health>(entity) {
+            ctx.game.handle(
+                ctx.world,
+                EntityDamageRequest {
+                    entity,
+                    damage: std::f32::MAX,
+                    cause: DamageCause::Void,
+                },
+            );
+        } else {
+            ctx.game.despawn(entity, ctx.world);
+        }
+    }
+
+    ctx.reply(Text::from(message) * Color::Gray);
+    Ok(())
+}
@@ -1 +1,48 @@
 #![forbid(unsafe_code)]
+
+//! Chat handling and commands.
+
+mod arguments;
+mod block_log;
+mod clear;
+mod commands;
+mod confirm;
+mod console;
+mod difficulty;
+mod dispatcher;
+pub mod duration;
+mod entity_report;
+mod gamemode;
+mod gamerule;
+mod give;
+mod kill;
+mod messaging;
+mod moderation;
+mod netstat;
+mod op;
+mod particle;
+mod save;
+mod scoreboard;
+mod selector;
+mod setblock;
+mod stop;
+mod suggestions;
+mod summon;
+mod tag;
+mod teleport;
+mod time;
+mod title;
+mod viewdistance;
+mod weather;
+
+pub use arguments::*;
+pub use block_log::*;
+pub use commands::*;
+pub use confirm::*;
+pub use console::*;
+pub use dispatcher::*;
+pub use gamemode::parse_gamemode;
+pub use moderation::*;
+pub use op::on_player_join_send_permission_level;
+pub use selector::*;
+pub use suggestions::*;
@@ -0,0 +1,233 @@
+//! Private messages with `/msg`, `/tell` and `/w`, replies
+//! with `/reply` and `/r`, emotes with `/me` and broadcasts
+//! with `/say`.
+//!
+//! Players can ignore others' messages with `/ignore`. Ignore
+//! lists are kept until the player leaves the server.
+
+use crate::{
+    argument, literal, send_message, ArgParser, Arguments, CommandCtx, CommandError,
+    CommandRegistration, CommandResult, EntitySelector, Node,
+};
+use feather_core::text::{Color, Style, Text, Translate};
+use feather_server_types::{
+    filter_chat_message, is_ignoring, ChatEvent, ChatPosition, IgnoreList, Name, Uuid,
+};
+use fecs::Entity;
+
+inventory::submit! {
+    CommandRegistration::tree("/msg <targets> <message>", msg_tree("msg"))
+}
+
+inventory::submit! {
+    CommandRegistration::tree("/tell <targets> <message>", msg_tree("tell"))
+}
+
+inventory::submit! {
+    CommandRegistration::tree("/w <targets> <message>", msg_tree("w"))
+}
+
+inventory::submit! {
+    CommandRegistration::tree("/reply <message>", reply_tree("reply"))
+}
+
+inventory::submit! {
+    CommandRegistration::tree("/r <message>", reply_tree("r"))
+}
+
+inventory::submit! {
+    CommandRegistration::tree(
+        "/me <action>",
+        literal("me").then(argument("action", ArgParser::GreedyString).executes(&me)),
+    )
+}
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/say <message>",
+        literal("say").then(argument("message", ArgParser::GreedyString).executes(&say)),
+    )
+}
+
+inventory::submit! {
+    CommandRegistration::tree(
+        "/ignore <player>",
+        literal("ignore").then(argument("player", ArgParser::PLAYER).executes(&ignore)),
+    )
+}
+
+inventory::submit! {
+    CommandRegistration::tree(
+        "/unignore <player>",
+        literal("unignore").then(argument("player", ArgParser::PLAYER).executes(&unignore)),
+    )
+}
+
+fn msg_tree(name: &'static str) -> Node {
+    literal(name).then(
+        argument("targets", ArgParser::PLAYERS)
+            .then(argument("message", ArgParser::GreedyString).executes(&msg)),
+    )
+}
+
+fn reply_tree(name: &'static str) -> Node {
+    literal(name).then(argument("message", ArgParser::GreedyString).executes(&reply))
+}
+
+/// Component storing the player or console a player last
+/// exchanged private messages with, to whom `/reply` is sent.
+#[derive(Copy, Clone, Debug)]
+pub struct LastCorrespondent(pub Entity);
+
+fn msg(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let targets = args
+        .get::<EntitySelector>("targets")
+        .unwrap()
+        .resolve(ctx)?;
+    let message = args.get::<String>("message").unwrap();
+    let message = match filter_chat_message(ctx.game, ctx.world, ctx.sender, message) {
+        Some(message) => message,
+        None => return Ok(()),
+    };
+
+    for target in targets {
+        send_private_message(ctx, target, &message);
+    }
+    Ok(())
+}
+
+fn reply(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let target = match ctx.world.try_get::<LastCorrespondent>(ctx.sender) {
+        Some(correspondent) if ctx.world.is_alive(correspondent.0) => correspondent.0,
+        _ => return Err(CommandError::message("There is nobody to reply to")),
+    };
+    let message = args.get::<String>("message").unwrap();
+    let message = match filter_chat_message(ctx.game, ctx.world, ctx.sender, message) {
+        Some(message) => message,
+        None => return Ok(()),
+    };
+
+    send_private_message(ctx, target, &message);
+    Ok(())
+}
+
+/// Sends a private message from the command sender to `target`,
+/// unless the target ignores the sender. The sender is shown
+/// the message either way, so ignoring goes unnoticed.
+fn send_private_message(ctx: &mut CommandCtx, target: Entity, message: &str) {
+    let sender = ctx.sender;
+    let target_name = ctx.world.get::<Name>(target).0.clone();
+
+    if !is_ignoring(ctx.world, target, sender) {
+        send_message(
+            ctx.world,
+            target,
+            Translate::from("commands.message.display.incoming")
+                * vec![
+                    Text::from(ctx.sender_name()),
+                    Text::from(message.to_owned()),
+                ]
+                * Color::Gray
+                * Style::Italic,
+        );
+        set_last_correspondent(ctx, target, sender);
+    }
+    ctx.reply(
+        Translate::from("commands.message.display.outgoing")
+            * vec![Text::from(target_name), Text::from(message.to_owned())]
+            * Color::Gray
+            * Style::Italic,
+    );
+    set_last_correspondent(ctx, sender, target);
+}
+
+fn set_last_correspondent(ctx: &mut CommandCtx, entity: Entity, correspondent: Entity) {
+    if ctx.world.has::<LastCorrespondent>(entity) {
+        ctx.world.get_mut::<LastCorrespondent>(entity).0 = correspondent;
+    } else {
+        ctx.world
+            .add(entity, LastCorrespondent(correspondent))
+            .unwrap();
+    }
+}
+
+fn me(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let action = args.get::<String>("action").unwrap();
+    let action = match filter_chat_message(ctx.game, ctx.world, ctx.sender, action) {
+        Some(action) => action,
+        None => return Ok(()),
+    };
+
+    let message = Translate::from("chat.type.emote")
+        * vec![Text::from(ctx.sender_name()), Text::from(action)];
+    send_chat(ctx, message);
+    Ok(())
+}
+
+fn say(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let message = args.get::<String>("message").unwrap();
+    let message = Translate::from("chat.type.announcement")
+        * vec![Text::from(ctx.sender_name()), Text::from(message)];
+    send_chat(ctx, message);
+    Ok(())
+}
+
+/// Sends a message from the command sender to all
+/// players who do not ignore them.
+fn send_chat(ctx: &mut CommandCtx, message: Text) {
+    ctx.game.handle(
+        ctx.world,
+        ChatEvent {
+            message,
+            position: ChatPosition::Chat,
+            sender: Some(ctx.sender),
+        },
+    );
+}
+
+/// Returns the UUID and name of the `player` argument,
+/// which the sender cannot ignore or unignore themself.
+fn ignored_player(ctx: &CommandCtx, args: &Arguments) -> Result<(Uuid, String), CommandError> {
+    let player = args
+        .get::<EntitySelector>("player")
+        .unwrap()
+        .resolve_one(ctx)?;
+    if player == ctx.player()? {
+        return Err(CommandError::message("You cannot ignore yourself"));
+    }
+    let name = ctx.world.get::<Name>(player).0.clone();
+    Ok((*ctx.world.get::<Uuid>(player), name))
+}
+
+fn ignore(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let (uuid, name) = ignored_player(ctx, args)?;
+    let sender = ctx.sender;
+    if !ctx.world.has::<IgnoreList>(sender) {
+        ctx.world.add(sender, IgnoreList::default()).unwrap();
+    }
+    if !ctx.world.get_mut::<IgnoreList>(sender).0.insert(uuid) {
+        return Err(CommandError::message(format!(
+            "You are already ignoring {}",
+            name
+        )));
+    }
+
+    ctx.reply(Text::from(format!("You are now ignoring {}", name)) * Color::Gray);
+    Ok(())
+}
+
+fn unignore(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let (uuid, name) = ignored_player(ctx, args)?;
+    let sender = ctx.sender;
+    let removed = ctx.world.has::<IgnoreList>(sender)
+        && ctx.world.get_mut::<IgnoreList>(sender).0.remove(&uuid);
+    if !removed {
+        return Err(CommandError::message(format!(
+            "You are not ignoring {}",
+            name
+        )));
+    }
+
+    ctx.reply(Text::from(format!("You are no longer ignoring {}", name)) * Color::Gray);
+    Ok(())
+}
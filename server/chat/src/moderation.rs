@@ -0,0 +1,426 @@
+//! Moderation commands: `/kick`, `/ban`, `/tempban`, `/ban-ip`,
+//! `/pardon`, `/pardon-ip`, `/mute`, `/tempmute`, and `/unmute`,
+//! plus automatic expiry of temporary punishments.
+
+use crate::duration::{format_duration, parse_duration};
+use crate::{
+    find_player, send_message, ArgKind, CommandCtx, CommandError, CommandRegistration,
+    CommandResult, MODERATOR_LEVEL,
+};
+use feather_core::text::{Color, Text};
+use feather_server_types::{
+    moderation_now, Game, IpBan, Moderation, Name, Player, PlayerChatEvent, Punishment, Uuid, TPS,
+};
+use fecs::{component, Entity, IntoQuery, Read, ResourcesProvider, World};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+inventory::submit! {
+    CommandRegistration::operator("kick", "/kick <player> [reason]", &kick)
+        .with_args(&[ArgKind::Player, ArgKind::Text])
+        .with_permission_level(MODERATOR_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator("ban", "/ban <player> [reason]", &ban)
+        .with_args(&[ArgKind::Player, ArgKind::Text])
+        .with_permission_level(MODERATOR_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator("tempban", "/tempban <player> <duration> [reason]", &tempban)
+        .with_args(&[ArgKind::Player, ArgKind::Word, ArgKind::Text])
+        .with_permission_level(MODERATOR_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator("ban-ip", "/ban-ip <address|player> [reason]", &ban_ip)
+        .with_args(&[ArgKind::Player, ArgKind::Text])
+        .with_permission_level(MODERATOR_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator("pardon", "/pardon <player>", &pardon)
+        .with_args(&[ArgKind::Word])
+        .with_permission_level(MODERATOR_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator("pardon-ip", "/pardon-ip <address>", &pardon_ip)
+        .with_args(&[ArgKind::Word])
+        .with_permission_level(MODERATOR_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator("mute", "/mute <player> [reason]", &mute)
+        .with_args(&[ArgKind::Player, ArgKind::Text])
+        .with_permission_level(MODERATOR_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator("tempmute", "/tempmute <player> <duration> [reason]", &tempmute)
+        .with_args(&[ArgKind::Player, ArgKind::Word, ArgKind::Text])
+        .with_permission_level(MODERATOR_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator("unmute", "/unmute <player>", &unmute)
+        .with_args(&[ArgKind::Player])
+        .with_permission_level(MODERATOR_LEVEL)
+}
+
+/// Returns the message shown to a banned player when they
+/// are kicked or attempt to join.
+pub fn ban_message(ban: &Punishment, now: u64) -> Text {
+    banned_message(
+        "You are banned from this server.",
+        &ban.reason,
+        ban.remaining(now),
+    )
+}
+
+/// Returns the message shown to players whose IP address
+/// is banned when they are kicked or attempt to join.
+pub fn ip_ban_message(ban: &IpBan, now: u64) -> Text {
+    banned_message(
+        "Your IP address is banned from this server.",
+        &ban.reason,
+        ban.remaining(now),
+    )
+}
+
+fn banned_message(headline: &str, reason: &str, remaining: Option<u64>) -> Text {
+    let mut text = Text::from(headline.to_owned()) * Color::Red;
+    if !reason.is_empty() {
+        text = text + Text::from(format!("\nReason: {}", reason));
+    }
+    if let Some(remaining) = remaining {
+        text = text + Text::from(format!("\nExpires in {}", format_duration(remaining)));
+    }
+    text
+}
+
+/// Returns the message sent to a muted player when they attempt to chat.
+pub fn mute_message(mute: &Punishment, now: u64) -> Text {
+    let mut message = String::from("You are muted");
+    if let Some(remaining) = mute.remaining(now) {
+        message.push_str(&format!(" for another {}", format_duration(remaining)));
+    }
+    if !mute.reason.is_empty() {
+        message.push_str(&format!(". Reason: {}", mute.reason));
+    }
+    message.push('.');
+    Text::from(message) * Color::Red
+}
+
+/// Returns the active mute for the given player, if any.
+pub fn active_mute(game: &Game, world: &World, player: Entity) -> Option<Punishment> {
+    let uuid = *world.get::<Uuid>(player);
+    let name = world.get::<Name>(player);
+
+    let moderation = game.resources.get::<Moderation>();
+    moderation
+        .mutes
+        .find(uuid, &name.0, moderation_now())
+        .cloned()
+}
+
+/// Blocks chat messages from muted players, telling them why.
+#[fecs::event_handler]
+pub fn on_player_chat_enforce_mute(event: &PlayerChatEvent, game: &Game, world: &mut World) {
+    if let Some(mute) = active_mute(game, world, event.player) {
+        send_message(world, event.player, mute_message(&mute, moderation_now()));
+        event.outcome.lock().cancelled = true;
+    }
+}
+
+/// Looks up the target of a punishment command. If the player
+/// is online, their UUID is used; otherwise, they are punished by name.
+fn target(ctx: &CommandCtx, name: &str) -> (Option<Entity>, Option<Uuid>, String) {
+    match find_player(ctx.world, name) {
+        Some(player) => (
+            Some(player),
+            Some(*ctx.world.get::<Uuid>(player)),
+            ctx.world.get::<Name>(player).0.clone(),
+        ),
+        None => (None, None, name.to_owned()),
+    }
+}
+
+/// Parses the arguments common to all punishment commands.
+fn punishment(
+    ctx: &CommandCtx,
+    args: &[&str],
+    temporary: bool,
+) -> Result<(Option<Entity>, Punishment), CommandError> {
+    let (name, rest) = args.split_first().ok_or(CommandError::Usage)?;
+
+    let now = moderation_now();
+    let (expires, reason) = if temporary {
+        let (duration, reason) = rest.split_first().ok_or(CommandError::Usage)?;
+        let duration = parse_duration(duration).ok_or_else(|| {
+            CommandError::message(format!(
+                "Invalid duration \"{}\". Use e.g. 30m, 12h, or 7d12h.",
+                duration
+            ))
+        })?;
+        (Some(now + duration), reason)
+    } else {
+        (None, rest)
+    };
+
+    let (player, uuid, name) = target(ctx, name);
+    Ok((
+        player,
+        Punishment {
+            uuid,
+            name,
+            source: ctx.sender_name(),
+            reason: reason.join(" "),
+            created: now,
+            expires,
+        },
+    ))
+}
+
+fn for_duration(punishment: &Punishment) -> String {
+    match punishment.remaining(punishment.created) {
+        Some(remaining) => format!(" for {}", format_duration(remaining)),
+        None => String::new(),
+    }
+}
+
+fn kick(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    let (name, reason) = args.split_first().ok_or(CommandError::Usage)?;
+    let player = find_player(ctx.world, name)
+        .ok_or_else(|| CommandError::message(format!("Player {} is not online", name)))?;
+    let name = ctx.world.get::<Name>(player).0.clone();
+    let reason = if reason.is_empty() {
+        String::from("Kicked by an operator.")
+    } else {
+        reason.join(" ")
+    };
+
+    ctx.game.kick(player, ctx.world, Text::from(reason.clone()));
+    ctx.reply(Text::from(format!("Kicked {}: {}", name, reason)) * Color::Gray);
+    Ok(())
+}
+
+fn ban(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    apply_ban(ctx, args, false)
+}
+
+fn tempban(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    apply_ban(ctx, args, true)
+}
+
+fn apply_ban(ctx: &mut CommandCtx, args: &[&str], temporary: bool) -> CommandResult {
+    let (player, ban) = punishment(ctx, args, temporary)?;
+
+    let resources = Arc::clone(&ctx.game.resources);
+    {
+        let mut moderation = resources.get_mut::<Moderation>();
+        moderation.bans.insert(ban.clone());
+        moderation.bans.save(&ctx.game.running_tasks);
+    }
+
+    if let Some(player) = player {
+        ctx.game
+            .kick(player, ctx.world, ban_message(&ban, ban.created));
+    }
+
+    ctx.reply(Text::from(format!("Banned {}{}", ban.name, for_duration(&ban))) * Color::Gray);
+    Ok(())
+}
+
+fn ban_ip(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    let (target, reason) = args.split_first().ok_or(CommandError::Usage)?;
+    // An online player's name bans the address they are connected from.
+    let ip = match target.parse::<IpAddr>() {
+        Ok(ip) => ip,
+        Err(_) => match find_player(ctx.world, target) {
+            Some(player) => ctx.world.get::<SocketAddr>(player).ip(),
+            None => {
+                return Err(CommandError::message(format!(
+                    "\"{}\" is neither a valid IP address nor an online player",
+                    target
+                )))
+            }
+        },
+    };
+    let ban = IpBan {
+        ip,
+        created: moderation_now(),
+        source: ctx.sender_name(),
+        expires: None,
+        reason: reason.join(" "),
+    };
+
+    let resources = Arc::clone(&ctx.game.resources);
+    {
+        let mut moderation = resources.get_mut::<Moderation>();
+        moderation.ip_bans.insert(ban.clone());
+        moderation.ip_bans.save(&ctx.game.running_tasks);
+    }
+
+    let players: Vec<Entity> = <Read<SocketAddr>>::query()
+        .filter(component::<Player>())
+        .iter_entities(ctx.world.inner())
+        .filter(|(_, addr)| addr.ip() == ip)
+        .map(|(player, _)| player)
+        .collect();
+    for &player in &players {
+        ctx.game
+            .kick(player, ctx.world, ip_ban_message(&ban, ban.created));
+    }
+
+    ctx.reply(
+        Text::from(format!(
+            "Banned IP {}, affecting {} online players",
+            ip,
+            players.len()
+        )) * Color::Gray,
+    );
+    Ok(())
+}
+
+fn pardon(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    let name = match args {
+        [name] => *name,
+        _ => return Err(CommandError::Usage),
+    };
+
+    let resources = Arc::clone(&ctx.game.resources);
+    let removed = {
+        let mut moderation = resources.get_mut::<Moderation>();
+        let removed = moderation.bans.remove(name);
+        if removed.is_some() {
+            moderation.bans.save(&ctx.game.running_tasks);
+        }
+        removed
+    };
+
+    let ban = removed.ok_or_else(|| CommandError::message(format!("{} is not banned", name)))?;
+    ctx.reply(Text::from(format!("Unbanned {}", ban.name)) * Color::Gray);
+    Ok(())
+}
+
+fn pardon_ip(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    let ip = match args {
+        [ip] => ip
+            .parse::<IpAddr>()
+            .map_err(|_| CommandError::message(format!("\"{}\" is not a valid IP address", ip)))?,
+        _ => return Err(CommandError::Usage),
+    };
+
+    let resources = Arc::clone(&ctx.game.resources);
+    let removed = {
+        let mut moderation = resources.get_mut::<Moderation>();
+        let removed = moderation.ip_bans.remove(ip);
+        if removed.is_some() {
+            moderation.ip_bans.save(&ctx.game.running_tasks);
+        }
+        removed
+    };
+
+    removed.ok_or_else(|| CommandError::message(format!("IP {} is not banned", ip)))?;
+    ctx.reply(Text::from(format!("Unbanned IP {}", ip)) * Color::Gray);
+    Ok(())
+}
+
+fn mute(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    apply_mute(ctx, args, false)
+}
+
+fn tempmute(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    apply_mute(ctx, args, true)
+}
+
+fn apply_mute(ctx: &mut CommandCtx, args: &[&str], temporary: bool) -> CommandResult {
+    let (player, mute) = punishment(ctx, args, temporary)?;
+
+    let resources = Arc::clone(&ctx.game.resources);
+    {
+        let mut moderation = resources.get_mut::<Moderation>();
+        moderation.mutes.insert(mute.clone());
+        moderation.mutes.save(&ctx.game.running_tasks);
+    }
+
+    if let Some(player) = player {
+        send_message(ctx.world, player, mute_message(&mute, mute.created));
+    }
+
+    ctx.reply(Text::from(format!("Muted {}{}", mute.name, for_duration(&mute))) * Color::Gray);
+    Ok(())
+}
+
+fn unmute(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    let name = match args {
+        [name] => *name,
+        _ => return Err(CommandError::Usage),
+    };
+
+    let resources = Arc::clone(&ctx.game.resources);
+    let removed = {
+        let mut moderation = resources.get_mut::<Moderation>();
+        let removed = moderation.mutes.remove(name);
+        if removed.is_some() {
+            moderation.mutes.save(&ctx.game.running_tasks);
+        }
+        removed
+    };
+
+    let mute = removed.ok_or_else(|| CommandError::message(format!("{} is not muted", name)))?;
+
+    if let Some(player) = find_player(ctx.world, &mute.name) {
+        send_message(
+            ctx.world,
+            player,
+            Text::from("You are no longer muted.") * Color::Gray,
+        );
+    }
+
+    ctx.reply(Text::from(format!("Unmuted {}", mute.name)) * Color::Gray);
+    Ok(())
+}
+
+/// System which removes expired bans and mutes once per second.
+#[fecs::system]
+pub fn expire_punishments(game: &mut Game, world: &mut World, moderation: &mut Moderation) {
+    if game.tick_count % TPS != 0 {
+        return;
+    }
+
+    let now = moderation_now();
+
+    let bans = moderation.bans.remove_expired(now);
+    if !bans.is_empty() {
+        for ban in &bans {
+            log::info!("Ban of {} has expired", ban.name);
+        }
+        moderation.bans.save(&game.running_tasks);
+    }
+
+    let ip_bans = moderation.ip_bans.remove_expired(now);
+    if !ip_bans.is_empty() {
+        for ban in &ip_bans {
+            log::info!("Ban of IP {} has expired", ban.ip);
+        }
+        moderation.ip_bans.save(&game.running_tasks);
+    }
+
+    let mutes = moderation.mutes.remove_expired(now);
+    if !mutes.is_empty() {
+        for mute in &mutes {
+            log::info!("Mute of {} has expired", mute.name);
+            if let Some(player) = find_player(world, &mute.name) {
+                send_message(
+                    world,
+                    player,
+                    Text::from("You are no longer muted.") * Color::Gray,
+                );
+            }
+        }
+        moderation.mutes.save(&game.running_tasks);
+    }
+}
@@ -0,0 +1,132 @@
+//! The `/netstat` command, which shows traffic on
+//! player connections.
+
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandError, CommandRegistration,
+    CommandResult, EntitySelector,
+};
+use feather_core::text::{Color, Text};
+use feather_server_types::{Name, NetworkStats, PlayerNetworkStats};
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/netstat [player]",
+        literal("netstat")
+            .executes(&summary)
+            .then(argument("player", ArgParser::PLAYER).executes(&player_stats)),
+    )
+}
+
+/// Number of players listed in the summary.
+const TOP_COUNT: usize = 10;
+
+/// Formats a number of bytes with a binary unit.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn player_stats(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let player = args
+        .get::<EntitySelector>("player")
+        .unwrap()
+        .resolve_one(ctx)?;
+    let name = ctx.world.get::<Name>(player).0.clone();
+    let stats = ctx
+        .game
+        .resources
+        .get::<NetworkStats>()
+        .player(player)
+        .copied()
+        .ok_or_else(|| CommandError::message(format!("No statistics for {} yet", name)))?;
+    details(ctx, &name, &stats);
+    Ok(())
+}
+
+fn summary(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    let (total, mut players) = {
+        let stats = ctx.game.resources.get::<NetworkStats>();
+        let players: Vec<_> = stats
+            .players()
+            .map(|(player, stats)| (player, *stats))
+            .collect();
+        (stats.total(), players)
+    };
+    players.sort_unstable_by(|(_, a), (_, b)| b.bytes_out_per_second.cmp(&a.bytes_out_per_second));
+
+    details(ctx, "All players", &total);
+    if players.is_empty() {
+        return Ok(());
+    }
+
+    ctx.reply(
+        Text::from(format!(
+            "Top {} players by outbound traffic:",
+            players.len().min(TOP_COUNT)
+        )) * Color::Gold,
+    );
+    for (player, stats) in players.into_iter().take(TOP_COUNT) {
+        let name = match ctx.world.try_get::<Name>(player) {
+            Some(name) => name.0.clone(),
+            // Disconnected since the last update
+            None => continue,
+        };
+        ctx.reply(Text::from(format!(
+            "{}: {}/s out, {}/s in, {} packets queued",
+            name,
+            format_bytes(stats.bytes_out_per_second),
+            format_bytes(stats.bytes_in_per_second),
+            stats.total.queued_packets
+        )));
+    }
+
+    Ok(())
+}
+
+fn details(ctx: &CommandCtx, title: &str, stats: &PlayerNetworkStats) {
+    let total = &stats.total;
+    ctx.reply(Text::from(format!("Network statistics for {}:", title)) * Color::Gold);
+    ctx.reply(Text::from(format!(
+        "In: {} packets, {} ({}/s)",
+        total.packets_in,
+        format_bytes(total.bytes_in),
+        format_bytes(stats.bytes_in_per_second)
+    )));
+    ctx.reply(Text::from(format!(
+        "Out: {} packets, {} ({}/s)",
+        total.packets_out,
+        format_bytes(total.bytes_out),
+        format_bytes(stats.bytes_out_per_second)
+    )));
+    ctx.reply(Text::from(format!(
+        "Compressed to {:.0}% of {}",
+        total.compression_ratio() * 100.0,
+        format_bytes(total.uncompressed_bytes_out)
+    )));
+    ctx.reply(Text::from(format!(
+        "Queued: {} packets, {}",
+        total.queued_packets,
+        format_bytes(total.queued_bytes)
+    )));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_are_formatted() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
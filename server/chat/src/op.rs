@@ -0,0 +1,150 @@
+//! The `/op` and `/deop` commands, and sending
+//! players their permission level.
+
+use crate::suggestions::send_commands;
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandError, CommandRegistration,
+    CommandResult, EntitySelector, MODERATOR_LEVEL,
+};
+use feather_core::network::packets::EntityStatus;
+use feather_core::text::{Color, Text};
+use feather_server_types::{
+    permission_level, EntityId, Game, Name, Network, Operator, Operators, PlayerJoinEvent, Uuid,
+    MAX_PERMISSION_LEVEL,
+};
+use fecs::{Entity, ResourcesProvider, World};
+use std::sync::Arc;
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/op <targets>",
+        literal("op").then(argument("targets", ArgParser::PLAYERS).executes(&op)),
+    )
+    .with_permission_level(MODERATOR_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/deop <targets>",
+        literal("deop").then(argument("targets", ArgParser::PLAYERS).executes(&deop)),
+    )
+    .with_permission_level(MODERATOR_LEVEL)
+}
+
+/// Entity status which tells the client its permission level
+/// is 0. Statuses for levels 1 to 4 follow consecutively.
+const STATUS_PERMISSION_LEVEL_0: i8 = 24;
+
+/// Sends joining players their permission level.
+#[fecs::event_handler]
+pub fn on_player_join_send_permission_level(
+    event: &PlayerJoinEvent,
+    game: &Game,
+    world: &mut World,
+) {
+    send_permission_level(game, world, event.player);
+}
+
+/// Tells a player's client their permission level, which
+/// decides whether it allows changing gamemode with F3+F4.
+fn send_permission_level(game: &Game, world: &World, player: Entity) {
+    let level = permission_level(game, world, player);
+    world.get::<Network>(player).send(EntityStatus {
+        entity_id: world.get::<EntityId>(player).0,
+        entity_status: STATUS_PERMISSION_LEVEL_0 + level as i8,
+    });
+}
+
+/// Updates a player after their permission
+/// level was changed by `/op` or `/deop`.
+fn permission_level_changed(ctx: &CommandCtx, player: Entity) {
+    send_permission_level(ctx.game, ctx.world, player);
+    send_commands(ctx.game, ctx.world, player);
+}
+
+fn op(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let targets = args
+        .get::<EntitySelector>("targets")
+        .unwrap()
+        .resolve(ctx)?;
+
+    let resources = Arc::clone(&ctx.game.resources);
+    let mut changed = vec![];
+    {
+        let mut operators = resources.get_mut::<Operators>();
+        for &player in &targets {
+            let uuid = *ctx.world.get::<Uuid>(player);
+            if operators.level(uuid) == MAX_PERMISSION_LEVEL {
+                continue;
+            }
+            operators.insert(Operator {
+                uuid,
+                name: ctx.world.get::<Name>(player).0.clone(),
+                level: MAX_PERMISSION_LEVEL,
+                bypasses_player_limit: false,
+            });
+            changed.push(player);
+        }
+        if !changed.is_empty() {
+            operators.save(&ctx.game.running_tasks);
+        }
+    }
+
+    if changed.is_empty() {
+        return Err(CommandError::message(
+            "Nothing changed. The player already is an operator",
+        ));
+    }
+    for &player in &changed {
+        permission_level_changed(ctx, player);
+        let name = ctx.world.get::<Name>(player).0.clone();
+        ctx.reply(Text::from(format!("Made {} a server operator", name)) * Color::Gray);
+    }
+    Ok(())
+}
+
+fn deop(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let targets = args
+        .get::<EntitySelector>("targets")
+        .unwrap()
+        .resolve(ctx)?;
+
+    let resources = Arc::clone(&ctx.game.resources);
+    let mut changed = vec![];
+    {
+        let mut operators = resources.get_mut::<Operators>();
+        for &player in &targets {
+            if operators.remove(*ctx.world.get::<Uuid>(player)).is_some() {
+                changed.push(player);
+            }
+        }
+        if !changed.is_empty() {
+            operators.save(&ctx.game.running_tasks);
+        }
+    }
+
+    if changed.is_empty() {
+        return Err(CommandError::message(
+            "Nothing changed. The player is not an operator",
+        ));
+    }
+    for &player in &changed {
+        permission_level_changed(ctx, player);
+        let name = ctx.world.get::<Name>(player).0.clone();
+        // Operators listed in the configuration
+        // file keep their permissions.
+        if permission_level(ctx.game, ctx.world, player) > 0 {
+            ctx.reply(
+                Text::from(format!(
+                    "{} is still an operator, as listed in feather.toml",
+                    name
+                )) * Color::Gray,
+            );
+        } else {
+            ctx.reply(
+                Text::from(format!("Made {} no longer a server operator", name)) * Color::Gray,
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,193 @@
+//! The `/particle` command.
+
+use crate::{
+    find_targets, parse_coordinate, parse_number, ArgKind, CommandCtx, CommandError,
+    CommandRegistration, CommandResult,
+};
+use feather_core::blocks::BlockId;
+use feather_core::items::{Item, ItemStack};
+use feather_core::network::packets::{Particle, ParticleKind, SimpleParticle};
+use feather_core::text::{Color, Text};
+use feather_core::util::Position;
+use feather_server_types::Network;
+
+inventory::submit! {
+    CommandRegistration::operator(
+        "particle",
+        "/particle <name> [data...] [x y z] [dx dy dz] [speed] [count] [normal|force] [player]",
+        &particle,
+    )
+    .with_args(&[ArgKind::Particle, ArgKind::Text])
+}
+
+/// Identifiers of particles which carry data, and
+/// so are followed by arguments in the command.
+const DATA_PARTICLES: &[&str] = &[
+    "minecraft:block",
+    "minecraft:dust",
+    "minecraft:falling_dust",
+    "minecraft:item",
+];
+
+/// Returns the identifiers of all particles.
+pub(crate) fn particle_identifiers() -> impl Iterator<Item = &'static str> {
+    DATA_PARTICLES
+        .iter()
+        .copied()
+        .chain(SimpleParticle::all().map(SimpleParticle::identifier))
+}
+
+fn namespaced(identifier: &str) -> String {
+    if identifier.contains(':') {
+        identifier.to_owned()
+    } else {
+        format!("minecraft:{}", identifier)
+    }
+}
+
+/// Parses a particle name and its data, returning
+/// the particle and the remaining arguments.
+fn parse_particle<'a, 'b>(
+    args: &'a [&'b str],
+) -> Result<(ParticleKind, &'a [&'b str]), CommandError> {
+    let (name, args) = args.split_first().ok_or(CommandError::Usage)?;
+    let name = namespaced(name);
+
+    let block = |args: &'a [&'b str]| -> Result<(BlockId, &'a [&'b str]), CommandError> {
+        let (block, rest) = args.split_first().ok_or(CommandError::Usage)?;
+        let block = BlockId::from_identifier(&namespaced(block))
+            .ok_or_else(|| CommandError::message(format!("Unknown block \"{}\"", block)))?;
+        Ok((block, rest))
+    };
+
+    match name.as_str() {
+        "minecraft:block" => block(args).map(|(block, rest)| (ParticleKind::Block(block), rest)),
+        "minecraft:falling_dust" => {
+            block(args).map(|(block, rest)| (ParticleKind::FallingDust(block), rest))
+        }
+        "minecraft:dust" => {
+            if args.len() < 4 {
+                return Err(CommandError::Usage);
+            }
+            let kind = ParticleKind::Dust {
+                red: parse_number(args[0])?,
+                green: parse_number(args[1])?,
+                blue: parse_number(args[2])?,
+                scale: parse_number(args[3])?,
+            };
+            Ok((kind, &args[4..]))
+        }
+        "minecraft:item" => {
+            let (item, rest) = args.split_first().ok_or(CommandError::Usage)?;
+            let item = Item::from_identifier(&namespaced(item))
+                .ok_or_else(|| CommandError::message(format!("Unknown item \"{}\"", item)))?;
+            Ok((ParticleKind::Item(ItemStack::new(item, 1)), rest))
+        }
+        _ => SimpleParticle::from_identifier(&name)
+            .map(|particle| (ParticleKind::Simple(particle), args))
+            .ok_or_else(|| CommandError::message(format!("Unknown particle \"{}\"", name))),
+    }
+}
+
+fn particle(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    let (kind, args) = parse_particle(args)?;
+    let sender_pos = *ctx.world.get::<Position>(ctx.sender);
+
+    let mut pos = sender_pos;
+    let mut offset = (0.0, 0.0, 0.0);
+    let mut speed = 0.0;
+    let mut count = 0;
+    let mut long_distance = false;
+    let mut viewers = None;
+
+    let mut args = args.iter();
+    if let (Some(x), Some(y), Some(z)) = (args.next(), args.next(), args.next()) {
+        pos.x = parse_coordinate(x, sender_pos.x)?;
+        pos.y = parse_coordinate(y, sender_pos.y)?;
+        pos.z = parse_coordinate(z, sender_pos.z)?;
+    }
+    if let (Some(dx), Some(dy), Some(dz)) = (args.next(), args.next(), args.next()) {
+        offset = (parse_number(dx)?, parse_number(dy)?, parse_number(dz)?);
+    }
+    if let Some(arg) = args.next() {
+        speed = parse_number(arg)?;
+    }
+    if let Some(arg) = args.next() {
+        count = parse_number(arg)?;
+    }
+    if let Some(&mode) = args.next() {
+        long_distance = match mode {
+            "normal" => false,
+            "force" => true,
+            _ => return Err(CommandError::Usage),
+        };
+    }
+    if let Some(target) = args.next() {
+        viewers = Some(find_targets(ctx, target)?);
+    }
+    if args.next().is_some() {
+        return Err(CommandError::Usage);
+    }
+
+    let identifier = kind.identifier();
+    if viewers.is_none() && !long_distance {
+        ctx.game
+            .spawn_particle(ctx.world, pos, kind, count, offset, speed);
+    } else {
+        let packet = Particle {
+            kind,
+            long_distance,
+            x: pos.x as f32,
+            y: pos.y as f32,
+            z: pos.z as f32,
+            offset_x: offset.0,
+            offset_y: offset.1,
+            offset_z: offset.2,
+            speed,
+            count: count as i32,
+        };
+        match viewers {
+            Some(viewers) => {
+                for viewer in viewers {
+                    ctx.world.get::<Network>(viewer).send(packet.clone());
+                }
+            }
+            None => ctx
+                .game
+                .broadcast_chunk_update(ctx.world, packet, pos.chunk(), None),
+        }
+    }
+
+    ctx.reply(Text::from(format!("Displaying particle {}", identifier)) * Color::Gray);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_particles() {
+        let (kind, rest) = parse_particle(&["flame", "~", "~1", "~"]).unwrap();
+        assert_eq!(kind, ParticleKind::Simple(SimpleParticle::Flame));
+        assert_eq!(rest.len(), 3);
+
+        let (kind, rest) = parse_particle(&["minecraft:dust", "1", "0", "0", "2"]).unwrap();
+        assert_eq!(
+            kind,
+            ParticleKind::Dust {
+                red: 1.0,
+                green: 0.0,
+                blue: 0.0,
+                scale: 2.0
+            }
+        );
+        assert!(rest.is_empty());
+
+        let (kind, _) = parse_particle(&["block", "stone"]).unwrap();
+        assert_eq!(kind, ParticleKind::Block(BlockId::stone()));
+
+        assert!(parse_particle(&["item"]).is_err());
+        assert!(parse_particle(&["not_a_particle"]).is_err());
+    }
+}
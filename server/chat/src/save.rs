@@ -0,0 +1,84 @@
+//! The `/save-all`, `/save-on` and `/save-off` commands.
+
+use crate::{
+    literal, Arguments, CommandCtx, CommandError, CommandRegistration, CommandResult, Node,
+};
+use feather_core::text::{Color, Text};
+use feather_server_types::{AutoSaveRequest, SaveRequest, MAX_PERMISSION_LEVEL};
+use std::sync::Arc;
+
+inventory::submit! {
+    CommandRegistration::operator_tree("/save-all [flush]", save_all_tree())
+        .with_permission_level(MAX_PERMISSION_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator_tree("/save-on", literal("save-on").executes(&save_on))
+        .with_permission_level(MAX_PERMISSION_LEVEL)
+}
+
+inventory::submit! {
+    CommandRegistration::operator_tree("/save-off", literal("save-off").executes(&save_off))
+        .with_permission_level(MAX_PERMISSION_LEVEL)
+}
+
+fn save_all_tree() -> Node {
+    literal("save-all")
+        .executes(&save_all)
+        .then(literal("flush").executes(&save_all))
+}
+
+/// Saves the world over the next few ticks, or at once with `flush`.
+fn save_all(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let flush = args.literals().first() == Some(&"flush");
+    ctx.game.handle(ctx.world, SaveRequest { flush });
+
+    if flush {
+        ctx.reply(Text::from("Saved the game") * Color::Gray);
+    } else {
+        ctx.reply(Text::from("Saving the game (this may take a moment!)") * Color::Gray);
+    }
+    Ok(())
+}
+
+fn save_on(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    set_auto_save(ctx, true)?;
+    ctx.reply(Text::from("Automatic saving is now enabled") * Color::Gray);
+    Ok(())
+}
+
+fn save_off(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    set_auto_save(ctx, false)?;
+    ctx.reply(Text::from("Automatic saving is now disabled") * Color::Gray);
+    Ok(())
+}
+
+fn set_auto_save(ctx: &mut CommandCtx, enabled: bool) -> CommandResult {
+    let request = AutoSaveRequest::new(enabled);
+    let changed = Arc::clone(&request.changed);
+    ctx.game.handle(ctx.world, request);
+
+    if *changed.lock() {
+        Ok(())
+    } else if enabled {
+        Err(CommandError::message("Saving is already turned on"))
+    } else {
+        Err(CommandError::message("Saving is already turned off"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_command;
+
+    #[test]
+    fn flush_literal() {
+        let tree = save_all_tree();
+        let (_, args) = parse_command(&tree, "save-all").unwrap();
+        assert!(args.literals().is_empty());
+        let (_, args) = parse_command(&tree, "save-all flush").unwrap();
+        assert_eq!(args.literals(), &["flush"]);
+        assert!(parse_command(&tree, "save-all now").is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! The `/scoreboard` command, for managing objectives,
+//! the slots they are displayed in, and scores.
+
+use crate::{ArgKind, CommandCtx, CommandError, CommandRegistration, CommandResult};
+use feather_core::text::{Color, Text};
+use feather_server_types::{Criteria, DisplaySlot, Scoreboard, ScoreboardError};
+use feather_server_util::{
+    add_objective, add_score, remove_objective, reset_score, set_display_slot,
+    set_objective_display_name, set_score,
+};
+
+inventory::submit! {
+    CommandRegistration::operator(
+        "scoreboard",
+        "/scoreboard objectives <list|add|remove|setdisplay|modify> ... \
+         or /scoreboard players <list|set|add|remove|reset> ...",
+        &scoreboard,
+    )
+    .with_args(&[
+        ArgKind::Choice(&["objectives", "players"]),
+        ArgKind::Choice(SUBCOMMANDS),
+        ArgKind::Word,
+        ArgKind::Word,
+        ArgKind::Text,
+    ])
+}
+
+const SUBCOMMANDS: &[&str] = &[
+    "list",
+    "add",
+    "remove",
+    "setdisplay",
+    "modify",
+    "set",
+    "reset",
+];
+
+impl From<ScoreboardError> for CommandError {
+    fn from(e: ScoreboardError) -> Self {
+        CommandError::message(e)
+    }
+}
+
+fn scoreboard(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    match args.split_first() {
+        Some((&"objectives", rest)) => objectives(ctx, rest),
+        Some((&"players", rest)) => players(ctx, rest),
+        _ => Err(CommandError::Usage),
+    }
+}
+
+fn objectives(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    let (subcommand, args) = args.split_first().ok_or(CommandError::Usage)?;
+    match (*subcommand, args) {
+        ("list", []) => {
+            let names = {
+                let scoreboard = ctx.game.resources.get::<Scoreboard>();
+                let mut names: Vec<String> = scoreboard
+                    .objectives()
+                    .map(|(name, _)| name.to_owned())
+                    .collect();
+                names.sort();
+                names
+            };
+            if names.is_empty() {
+                ctx.reply(Text::from("There are no objectives") * Color::Gray);
+            } else {
+                ctx.reply(
+                    Text::from(format!(
+                        "There are {} objectives: {}",
+                        names.len(),
+                        names.join(", ")
+                    )) * Color::Gray,
+                );
+            }
+        }
+        ("add", args) if args.len() >= 2 => {
+            let (name, criteria) = (args[0], args[1]);
+            let criteria = Criteria::from_identifier(criteria).ok_or_else(|| {
+                CommandError::message(format!("Unknown criteria \"{}\"", criteria))
+            })?;
+            let display_name = if args.len() > 2 {
+                args[2..].join(" ")
+            } else {
+                name.to_owned()
+            };
+            add_objective(
+                ctx.game,
+                ctx.world,
+                name,
+                Text::from(display_name),
+                criteria,
+            )?;
+            ctx.reply(Text::from(format!("Created objective {}", name)) * Color::Gray);
+        }
+        ("remove", [name]) => {
+            remove_objective(ctx.game, ctx.world, name)?;
+            ctx.reply(Text::from(format!("Removed objective {}", name)) * Color::Gray);
+        }
+        ("setdisplay", args) if args.len() == 1 || args.len() == 2 => {
+            let slot = DisplaySlot::from_identifier(args[0]).ok_or_else(|| {
+                CommandError::message(format!("Unknown display slot \"{}\"", args[0]))
+            })?;
+            let objective = args.get(1).copied();
+            set_display_slot(ctx.game, ctx.world, slot, objective)?;
+
+            let message = match objective {
+                Some(objective) => format!(
+                    "Displaying objective {} in slot {}",
+                    objective,
+                    slot.identifier()
+                ),
+                None => format!("Cleared objectives in slot {}", slot.identifier()),
+            };
+            ctx.reply(Text::from(message) * Color::Gray);
+        }
+        ("modify", args) if args.len() >= 3 && args[1] == "displayname" => {
+            let name = args[0];
+            let display_name = Text::from(args[2..].join(" "));
+            set_objective_display_name(ctx.game, ctx.world, name, display_name)?;
+            ctx.reply(Text::from(format!("Changed the display name of {}", name)) * Color::Gray);
+        }
+        _ => return Err(CommandError::Usage),
+    }
+    Ok(())
+}
+
+fn players(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    match args {
+        ["list"] => {
+            let mut entries: Vec<String> = {
+                let scoreboard = ctx.game.resources.get::<Scoreboard>();
+                scoreboard
+                    .objectives()
+                    .flat_map(|(_, objective)| {
+                        objective.scores().map(|(entry, _)| entry.to_owned())
+                    })
+                    .collect()
+            };
+            entries.sort();
+            entries.dedup();
+            if entries.is_empty() {
+                ctx.reply(Text::from("There are no tracked entries") * Color::Gray);
+            } else {
+                ctx.reply(
+                    Text::from(format!(
+                        "There are {} tracked entries: {}",
+                        entries.len(),
+                        entries.join(", ")
+                    )) * Color::Gray,
+                );
+            }
+        }
+        ["list", target] => {
+            let entry = entry(ctx, target);
+            let mut scores: Vec<(String, i32)> = {
+                let scoreboard = ctx.game.resources.get::<Scoreboard>();
+                scoreboard
+                    .objectives()
+                    .filter_map(|(name, objective)| {
+                        objective
+                            .score(&entry)
+                            .map(|score| (name.to_owned(), score))
+                    })
+                    .collect()
+            };
+            scores.sort();
+            if scores.is_empty() {
+                ctx.reply(Text::from(format!("{} has no scores", entry)) * Color::Gray);
+            } else {
+                ctx.reply(
+                    Text::from(format!("{} has {} scores:", entry, scores.len())) * Color::Gray,
+                );
+                for (objective, score) in scores {
+                    ctx.reply(Text::from(format!("{}: {}", objective, score)) * Color::Gray);
+                }
+            }
+        }
+        [action, target, objective, value]
+            if *action == "set" || *action == "add" || *action == "remove" =>
+        {
+            let entry = entry(ctx, target);
+            let value: i32 = value
+                .parse()
+                .map_err(|_| CommandError::message(format!("Invalid score \"{}\"", value)))?;
+            check_writable(ctx, objective)?;
+
+            let score = match *action {
+                "set" => {
+                    set_score(ctx.game, ctx.world, objective, &entry, value)?;
+                    value
+                }
+                "add" => add_score(ctx.game, ctx.world, objective, &entry, value)?,
+                _ => add_score(ctx.game, ctx.world, objective, &entry, value.wrapping_neg())?,
+            };
+            ctx.reply(
+                Text::from(format!("Set {} for {} to {}", objective, entry, score)) * Color::Gray,
+            );
+        }
+        ["reset", target] => {
+            let entry = entry(ctx, target);
+            reset_score(ctx.game, ctx.world, None, &entry)?;
+            ctx.reply(Text::from(format!("Reset all scores of {}", entry)) * Color::Gray);
+        }
+        ["reset", target, objective] => {
+            let entry = entry(ctx, target);
+            reset_score(ctx.game, ctx.world, Some(objective), &entry)?;
+            ctx.reply(Text::from(format!("Reset score {} of {}", objective, entry)) * Color::Gray);
+        }
+        _ => return Err(CommandError::Usage),
+    }
+    Ok(())
+}
+
+/// Returns the scoreboard entry named by a command argument,
+/// where `@s` refers to the sender.
+fn entry(ctx: &CommandCtx, target: &str) -> String {
+    if target == "@s" {
+        ctx.sender_name()
+    } else {
+        target.to_owned()
+    }
+}
+
+/// Fails if the objective's scores may not be changed by commands.
+fn check_writable(ctx: &CommandCtx, objective: &str) -> Result<(), ScoreboardError> {
+    let scoreboard = ctx.game.resources.get::<Scoreboard>();
+    match scoreboard.objective(objective) {
+        Some(o) if o.criteria.is_read_only() => {
+            Err(ScoreboardError::ReadOnly(objective.to_owned()))
+        }
+        Some(_) => Ok(()),
+        None => Err(ScoreboardError::UnknownObjective(objective.to_owned())),
+    }
+}
@@ -0,0 +1,672 @@
+//! Entity selectors, such as `@e[type=zombie,distance=..10]`,
+//! which choose the entities a command applies to.
+//!
+//! A selector starts from the entities its kind selects (`@a` for
+//! all players, `@e` for all entities, and so on), keeps those
+//! matching its filters, then sorts them and applies its limit.
+
+use crate::{
+    find_player, parse_gamemode, CommandCtx, CommandError, FloatRange, ParseError, StringReader,
+};
+use feather_core::text::Text;
+use feather_core::util::{Gamemode, Position};
+use feather_server_types::{
+    has_permission, CustomName, EntityId, EntityType, Name, Player, Tags, Uuid, SELECTOR_PERMISSION,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+use rand::seq::SliceRandom;
+
+/// Type identifier of players.
+const PLAYER: &str = "minecraft:player";
+
+/// What an entity selector starts from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectorKind {
+    /// The player with the given name.
+    Name(String),
+    /// The entity with the given UUID.
+    Uuid(Uuid),
+    /// `@s`: the sender of the command.
+    Sender,
+    /// `@a`: all players.
+    AllPlayers,
+    /// `@p`: the player nearest to the sender.
+    NearestPlayer,
+    /// `@r`: a random player, or a random entity
+    /// if a type other than players is given.
+    RandomPlayer,
+    /// `@e`: all entities.
+    AllEntities,
+}
+
+/// The order of selected entities, which
+/// decides which are kept by a limit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SelectorSort {
+    Nearest,
+    Furthest,
+    Random,
+    Arbitrary,
+}
+
+/// A condition on the entities a selector selects. Those
+/// which are negated keep the entities not matching them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectorFilter {
+    /// The distance from the selector's origin.
+    Distance(FloatRange),
+    /// A type identifier, such as `minecraft:zombie`.
+    Type { identifier: String, negated: bool },
+    /// The name of a player, or the custom name of another entity.
+    Name { name: String, negated: bool },
+    /// The gamemode of a player. Never matches other entities.
+    Gamemode { gamemode: Gamemode, negated: bool },
+    /// A tag added with `/tag`. The empty tag
+    /// matches entities without any tags.
+    Tag { tag: String, negated: bool },
+}
+
+impl SelectorFilter {
+    fn matches(&self, world: &World, entity: Entity, origin: Position) -> bool {
+        match self {
+            SelectorFilter::Distance(range) => world
+                .try_get::<Position>(entity)
+                .map(|pos| range.contains(pos.distance_to(origin)))
+                .unwrap_or(false),
+            SelectorFilter::Type {
+                identifier,
+                negated,
+            } => (entity_type(world, entity) == Some(identifier.as_str())) != *negated,
+            SelectorFilter::Name { name, negated } => {
+                (entity_name(world, entity).as_ref() == Some(name)) != *negated
+            }
+            SelectorFilter::Gamemode { gamemode, negated } => world
+                .try_get::<Gamemode>(entity)
+                .map(|mode| (*mode == *gamemode) != *negated)
+                .unwrap_or(false),
+            SelectorFilter::Tag { tag, negated } => {
+                let tags = world.try_get::<Tags>(entity);
+                let has_tag = if tag.is_empty() {
+                    tags.map(|tags| tags.0.is_empty()).unwrap_or(true)
+                } else {
+                    tags.map(|tags| tags.0.contains(tag)).unwrap_or(false)
+                };
+                has_tag != *negated
+            }
+        }
+    }
+}
+
+/// Returns the type identifier of an entity.
+fn entity_type(world: &World, entity: Entity) -> Option<&'static str> {
+    if world.has::<Player>(entity) {
+        Some(PLAYER)
+    } else {
+        world.try_get::<EntityType>(entity).map(|ty| ty.0)
+    }
+}
+
+/// Returns the name of a player or the plain
+/// text of another entity's custom name.
+fn entity_name(world: &World, entity: Entity) -> Option<String> {
+    if let Some(name) = world.try_get::<Name>(entity) {
+        return Some(name.0.clone());
+    }
+    world.try_get::<CustomName>(entity).map(|name| {
+        Text::from_json(&name.0)
+            .map(|text| text.to_plain())
+            .unwrap_or_else(|_| name.0.clone())
+    })
+}
+
+/// Returns the name of an entity shown in command
+/// feedback, which is its type if it has no name.
+pub(crate) fn describe_entity(world: &World, entity: Entity) -> String {
+    entity_name(world, entity)
+        .or_else(|| entity_type(world, entity).map(str::to_owned))
+        .unwrap_or_else(|| String::from("entity"))
+}
+
+/// An argument selecting entities: a player name,
+/// an entity UUID, or a selector such as `@a[limit=3]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntitySelector {
+    pub kind: SelectorKind,
+    pub filters: Vec<SelectorFilter>,
+    /// Coordinates of the position distances are
+    /// measured from, instead of the sender's.
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub z: Option<f64>,
+    pub limit: Option<usize>,
+    pub sort: Option<SelectorSort>,
+}
+
+impl EntitySelector {
+    pub fn new(kind: SelectorKind) -> Self {
+        Self {
+            kind,
+            filters: vec![],
+            x: None,
+            y: None,
+            z: None,
+            limit: None,
+            sort: None,
+        }
+    }
+
+    pub fn read(
+        reader: &mut StringReader,
+        single: bool,
+        players_only: bool,
+    ) -> Result<Self, ParseError> {
+        let start = reader.cursor();
+        let mut selector = if reader.peek() == Some('@') {
+            reader.skip();
+            let kind = match reader.peek() {
+                Some('s') => SelectorKind::Sender,
+                Some('a') => SelectorKind::AllPlayers,
+                Some('p') => SelectorKind::NearestPlayer,
+                Some('r') => SelectorKind::RandomPlayer,
+                Some('e') => SelectorKind::AllEntities,
+                _ => {
+                    let name = reader.read_word();
+                    return Err(ParseError::new(
+                        format!("Unknown selector type '@{}'", name),
+                        start,
+                    ));
+                }
+            };
+            reader.skip();
+            let mut selector = Self::new(kind);
+            if reader.peek() == Some('[') {
+                selector.read_options(reader)?;
+            }
+            selector
+        } else {
+            let name = reader.read_word();
+            if name.is_empty() {
+                return Err(reader.error("Expected entity"));
+            }
+            match Uuid::parse_str(name) {
+                Ok(uuid) if name.len() == 36 => Self::new(SelectorKind::Uuid(uuid)),
+                _ => Self::new(SelectorKind::Name(name.to_owned())),
+            }
+        };
+
+        if single && selector.max_results() != Some(1) {
+            return Err(ParseError::new(
+                "Only one entity is allowed, but the provided selector allows more than one",
+                start,
+            ));
+        }
+        if players_only && selector.may_select_non_players() {
+            return Err(ParseError::new(
+                "Only players may be affected by this command, \
+                 but the provided selector includes entities",
+                start,
+            ));
+        }
+        // Filters have been checked, so the default sort is
+        // now known and stored to simplify resolving.
+        if selector.sort.is_none() {
+            selector.sort = Some(match selector.kind {
+                SelectorKind::NearestPlayer => SelectorSort::Nearest,
+                SelectorKind::RandomPlayer => SelectorSort::Random,
+                _ => SelectorSort::Arbitrary,
+            });
+        }
+        Ok(selector)
+    }
+
+    /// Reads the options between square brackets
+    /// following a selector's kind.
+    fn read_options(&mut self, reader: &mut StringReader) -> Result<(), ParseError> {
+        let skip_spaces = |reader: &mut StringReader| {
+            reader.read_while(|c| c == ' ');
+        };
+
+        reader.expect('[')?;
+        skip_spaces(reader);
+        while reader.peek() != Some(']') {
+            let start = reader.cursor();
+            let key = reader.read_unquoted();
+            if key.is_empty() {
+                return Err(reader.error("Expected option"));
+            }
+            skip_spaces(reader);
+            reader.expect('=')?;
+            skip_spaces(reader);
+            self.read_option(key, start, reader)?;
+            skip_spaces(reader);
+
+            match reader.peek() {
+                Some(',') => {
+                    reader.skip();
+                    skip_spaces(reader);
+                }
+                Some(']') => (),
+                _ => return Err(reader.error("Expected end of options")),
+            }
+        }
+        reader.skip();
+        Ok(())
+    }
+
+    fn read_option(
+        &mut self,
+        key: &str,
+        start: usize,
+        reader: &mut StringReader,
+    ) -> Result<(), ParseError> {
+        let not_applicable =
+            || ParseError::new(format!("Option '{}' isn't applicable here", key), start);
+        let read_negation = |reader: &mut StringReader| {
+            let negated = reader.peek() == Some('!');
+            if negated {
+                reader.skip();
+            }
+            negated
+        };
+
+        match key {
+            "distance" => {
+                if self.distance().is_some() {
+                    return Err(not_applicable());
+                }
+                let cursor = reader.cursor();
+                let range = FloatRange::read(reader, "float")?;
+                if range.min.unwrap_or(0.0) < 0.0 || range.max.unwrap_or(0.0) < 0.0 {
+                    return Err(ParseError::new("Distance cannot be negative", cursor));
+                }
+                self.filters.push(SelectorFilter::Distance(range));
+            }
+            "x" | "y" | "z" => {
+                let value = reader.read_number("double")?;
+                let coordinate = match key {
+                    "x" => &mut self.x,
+                    "y" => &mut self.y,
+                    _ => &mut self.z,
+                };
+                if coordinate.is_some() {
+                    return Err(not_applicable());
+                }
+                *coordinate = Some(value);
+            }
+            "limit" => {
+                if self.kind == SelectorKind::Sender || self.limit.is_some() {
+                    return Err(not_applicable());
+                }
+                let cursor = reader.cursor();
+                let limit: i32 = reader.read_number("integer")?;
+                if limit < 1 {
+                    return Err(ParseError::new("Limit must be at least 1", cursor));
+                }
+                self.limit = Some(limit as usize);
+            }
+            "sort" => {
+                if self.kind == SelectorKind::Sender || self.sort.is_some() {
+                    return Err(not_applicable());
+                }
+                let cursor = reader.cursor();
+                self.sort = Some(match reader.read_unquoted() {
+                    "nearest" => SelectorSort::Nearest,
+                    "furthest" => SelectorSort::Furthest,
+                    "random" => SelectorSort::Random,
+                    "arbitrary" => SelectorSort::Arbitrary,
+                    sort => {
+                        return Err(ParseError::new(
+                            format!("Invalid or unknown sort type '{}'", sort),
+                            cursor,
+                        ))
+                    }
+                });
+            }
+            "type" => {
+                let negated = read_negation(reader);
+                let players_only = match self.kind {
+                    SelectorKind::AllPlayers | SelectorKind::NearestPlayer => true,
+                    _ => false,
+                };
+                if players_only || (!negated && self.positive_type().is_some()) {
+                    return Err(not_applicable());
+                }
+                let identifier = reader.read_identifier()?;
+                self.filters.push(SelectorFilter::Type {
+                    identifier,
+                    negated,
+                });
+            }
+            "name" => {
+                let negated = read_negation(reader);
+                let name = reader.read_string()?;
+                self.filters.push(SelectorFilter::Name { name, negated });
+            }
+            "gamemode" => {
+                let negated = read_negation(reader);
+                let cursor = reader.cursor();
+                let mode = reader.read_unquoted();
+                let gamemode = parse_gamemode(mode).ok_or_else(|| {
+                    ParseError::new(format!("Invalid or unknown game mode '{}'", mode), cursor)
+                })?;
+                self.filters
+                    .push(SelectorFilter::Gamemode { gamemode, negated });
+            }
+            "tag" => {
+                let negated = read_negation(reader);
+                let tag = reader.read_unquoted().to_owned();
+                self.filters.push(SelectorFilter::Tag { tag, negated });
+            }
+            _ => return Err(ParseError::new(format!("Unknown option '{}'", key), start)),
+        }
+        Ok(())
+    }
+
+    fn distance(&self) -> Option<FloatRange> {
+        self.filters.iter().find_map(|filter| match filter {
+            SelectorFilter::Distance(range) => Some(*range),
+            _ => None,
+        })
+    }
+
+    /// Returns the type which selected entities must have, if any.
+    fn positive_type(&self) -> Option<&str> {
+        self.filters.iter().find_map(|filter| match filter {
+            SelectorFilter::Type {
+                identifier,
+                negated: false,
+            } => Some(identifier.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Returns the maximum number of entities this selector
+    /// may select, or `None` if there is no maximum.
+    pub fn max_results(&self) -> Option<usize> {
+        match self.kind {
+            SelectorKind::Name(_) | SelectorKind::Uuid(_) | SelectorKind::Sender => Some(1),
+            SelectorKind::NearestPlayer | SelectorKind::RandomPlayer => self.limit.or(Some(1)),
+            SelectorKind::AllPlayers | SelectorKind::AllEntities => self.limit,
+        }
+    }
+
+    /// Returns whether this selector may select entities other than
+    /// players. The sender is assumed to be a player.
+    fn may_select_non_players(&self) -> bool {
+        match self.kind {
+            SelectorKind::Uuid(_) => true,
+            SelectorKind::AllEntities => self.positive_type() != Some(PLAYER),
+            SelectorKind::RandomPlayer => self.positive_type().map_or(false, |ty| ty != PLAYER),
+            _ => false,
+        }
+    }
+
+    /// Parses a selector from a single command argument.
+    pub fn parse(arg: &str, single: bool, players_only: bool) -> Result<Self, CommandError> {
+        let mut reader = StringReader::new(arg);
+        let selector = Self::read(&mut reader, single, players_only)
+            .map_err(|e| CommandError::Message(e.message))?;
+        if reader.can_read() {
+            return Err(CommandError::message(format!(
+                "Invalid selector \"{}\"",
+                arg
+            )));
+        }
+        Ok(selector)
+    }
+
+    /// Returns the entities selected by this selector, failing
+    /// if there are none. Selectors other than names and UUIDs
+    /// need the sender to have `SELECTOR_PERMISSION`.
+    pub fn resolve(&self, ctx: &CommandCtx) -> Result<Vec<Entity>, CommandError> {
+        let world: &World = &*ctx.world;
+        let by_name = match self.kind {
+            SelectorKind::Name(_) | SelectorKind::Uuid(_) => true,
+            _ => false,
+        };
+        if !by_name && !has_permission(ctx.game, world, ctx.sender, SELECTOR_PERMISSION) {
+            return Err(CommandError::message("Selector not allowed"));
+        }
+        let mut origin = *world.get::<Position>(ctx.sender);
+        origin.x = self.x.unwrap_or(origin.x);
+        origin.y = self.y.unwrap_or(origin.y);
+        origin.z = self.z.unwrap_or(origin.z);
+
+        let candidates: Vec<Entity> = match &self.kind {
+            SelectorKind::Name(name) => find_player(world, name).into_iter().collect(),
+            SelectorKind::Uuid(uuid) => <Read<Uuid>>::query()
+                .iter_entities(world.inner())
+                .filter(|(_, entity_uuid)| **entity_uuid == *uuid)
+                .map(|(entity, _)| entity)
+                .collect(),
+            SelectorKind::Sender => ctx.player().into_iter().collect(),
+            SelectorKind::RandomPlayer if self.positive_type().is_some() => all_entities(world),
+            SelectorKind::AllPlayers | SelectorKind::NearestPlayer | SelectorKind::RandomPlayer => {
+                <Read<Position>>::query()
+                    .filter(component::<Player>())
+                    .iter_entities(world.inner())
+                    .map(|(player, _)| player)
+                    .collect()
+            }
+            SelectorKind::AllEntities => all_entities(world),
+        };
+
+        let mut entities: Vec<(Entity, f64)> = candidates
+            .into_iter()
+            .filter(|&entity| {
+                self.filters
+                    .iter()
+                    .all(|filter| filter.matches(world, entity, origin))
+            })
+            .filter_map(|entity| {
+                world
+                    .try_get::<Position>(entity)
+                    .map(|pos| (entity, pos.distance_squared_to(origin)))
+            })
+            .collect();
+
+        match self.sort.unwrap_or(SelectorSort::Arbitrary) {
+            SelectorSort::Nearest => {
+                entities.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+            }
+            SelectorSort::Furthest => {
+                entities.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+            }
+            SelectorSort::Random => entities.shuffle(&mut *ctx.game.rng()),
+            SelectorSort::Arbitrary => (),
+        }
+        if let Some(max) = self.max_results() {
+            entities.truncate(max);
+        }
+
+        if !entities.is_empty() {
+            return Ok(entities.into_iter().map(|(entity, _)| entity).collect());
+        }
+        match &self.kind {
+            SelectorKind::Name(name) => {
+                Err(CommandError::message(format!("Player {} not found", name)))
+            }
+            _ if self.may_select_non_players() => Err(CommandError::message("No entity was found")),
+            _ => Err(CommandError::message("No player was found")),
+        }
+    }
+
+    /// Returns the single entity selected by this selector, which
+    /// should have been parsed as selecting at most one entity.
+    pub fn resolve_one(&self, ctx: &CommandCtx) -> Result<Entity, CommandError> {
+        let entities = self.resolve(ctx)?;
+        if entities.len() > 1 {
+            return Err(CommandError::message(
+                "Only one entity is allowed, but the provided selector allows more than one",
+            ));
+        }
+        Ok(entities[0])
+    }
+}
+
+/// Returns all entities with a position in the world.
+fn all_entities(world: &World) -> Vec<Entity> {
+    <Read<Position>>::query()
+        .filter(component::<EntityId>())
+        .iter_entities(world.inner())
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArgParser, ArgValue, Range};
+
+    fn selector(input: &str) -> Result<EntitySelector, ParseError> {
+        parse_with(ArgParser::ENTITIES, input)
+    }
+
+    fn parse_with(parser: ArgParser, input: &str) -> Result<EntitySelector, ParseError> {
+        let mut reader = StringReader::new(input);
+        match parser.parse(&mut reader)? {
+            ArgValue::Entity(selector) => {
+                assert!(
+                    !reader.can_read(),
+                    "{} was not consumed",
+                    reader.remaining()
+                );
+                Ok(selector)
+            }
+            value => panic!("{:?}", value),
+        }
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(selector("@e").unwrap().kind, SelectorKind::AllEntities);
+        assert_eq!(
+            selector("caelunshun").unwrap().kind,
+            SelectorKind::Name(String::from("caelunshun"))
+        );
+        assert_eq!(selector("@p").unwrap().sort, Some(SelectorSort::Nearest));
+        assert!(selector("@x").is_err());
+    }
+
+    #[test]
+    fn options() {
+        let parsed = selector("@e[type=zombie, distance=..10,limit=2,sort=furthest]").unwrap();
+        assert_eq!(
+            parsed.filters,
+            vec![
+                SelectorFilter::Type {
+                    identifier: String::from("minecraft:zombie"),
+                    negated: false
+                },
+                SelectorFilter::Distance(Range {
+                    min: None,
+                    max: Some(10.0)
+                }),
+            ]
+        );
+        assert_eq!(parsed.limit, Some(2));
+        assert_eq!(parsed.sort, Some(SelectorSort::Furthest));
+
+        let parsed = selector("@a[gamemode=!creative,tag=,name=\"Some Name\",x=5]").unwrap();
+        assert_eq!(
+            parsed.filters,
+            vec![
+                SelectorFilter::Gamemode {
+                    gamemode: Gamemode::Creative,
+                    negated: true
+                },
+                SelectorFilter::Tag {
+                    tag: String::new(),
+                    negated: false
+                },
+                SelectorFilter::Name {
+                    name: String::from("Some Name"),
+                    negated: false
+                },
+            ]
+        );
+        assert_eq!(parsed.x, Some(5.0));
+    }
+
+    #[test]
+    fn invalid_options() {
+        assert!(selector("@e[unknown=1]").is_err());
+        assert!(selector("@e[limit=0]").is_err());
+        assert!(selector("@e[limit=1,limit=2]").is_err());
+        assert!(selector("@e[distance=-1..]").is_err());
+        assert!(selector("@a[type=zombie]").is_err());
+        assert!(selector("@s[sort=nearest]").is_err());
+        assert!(selector("@e[type=zombie,type=skeleton]").is_err());
+        assert!(selector("@e[type=!zombie,type=!skeleton]").is_ok());
+        assert!(selector("@e[limit=1").is_err());
+    }
+
+    #[test]
+    fn single_and_players_only() {
+        assert!(parse_with(ArgParser::PLAYER, "@a").is_err());
+        assert!(parse_with(ArgParser::PLAYER, "@a[limit=1]").is_ok());
+        assert!(parse_with(ArgParser::PLAYER, "@p").is_ok());
+        assert!(parse_with(ArgParser::PLAYERS, "@e").is_err());
+        assert!(parse_with(ArgParser::PLAYERS, "@e[type=player]").is_ok());
+        assert!(parse_with(ArgParser::PLAYERS, "@r[type=cow]").is_err());
+        assert!(parse_with(ArgParser::ENTITY, "@e[limit=1]").is_ok());
+    }
+
+    #[test]
+    fn filters_match_entities() {
+        let mut world = World::new();
+        let zombie = world.spawn(std::iter::once(()))[0];
+        world.add(zombie, EntityType("minecraft:zombie")).unwrap();
+        world.add(zombie, Position::default()).unwrap();
+        world
+            .add(zombie, CustomName(String::from("{\"text\":\"Bob\"}")))
+            .unwrap();
+        let origin = Position {
+            x: 5.0,
+            ..Default::default()
+        };
+
+        let ty = |identifier: &str, negated| SelectorFilter::Type {
+            identifier: identifier.to_owned(),
+            negated,
+        };
+        assert!(ty("minecraft:zombie", false).matches(&world, zombie, origin));
+        assert!(ty("minecraft:cow", true).matches(&world, zombie, origin));
+
+        let near = SelectorFilter::Distance(Range {
+            min: None,
+            max: Some(4.0),
+        });
+        assert!(!near.matches(&world, zombie, origin));
+
+        let name = SelectorFilter::Name {
+            name: String::from("Bob"),
+            negated: false,
+        };
+        assert!(name.matches(&world, zombie, origin));
+
+        let gamemode = SelectorFilter::Gamemode {
+            gamemode: Gamemode::Creative,
+            negated: true,
+        };
+        assert!(!gamemode.matches(&world, zombie, origin));
+
+        let untagged = SelectorFilter::Tag {
+            tag: String::new(),
+            negated: false,
+        };
+        assert!(untagged.matches(&world, zombie, origin));
+        world
+            .add(
+                zombie,
+                Tags(std::iter::once(String::from("boss")).collect()),
+            )
+            .unwrap();
+        assert!(!untagged.matches(&world, zombie, origin));
+        let boss = SelectorFilter::Tag {
+            tag: String::from("boss"),
+            negated: false,
+        };
+        assert!(boss.matches(&world, zombie, origin));
+    }
+}
@@ -0,0 +1,222 @@
+//! The `/setblock` and `/fill` commands.
+//!
+//! Both take a mode after the block: `replace`, the default,
+//! replaces any block; `keep` only replaces air; and `destroy`
+//! breaks the existing block first, dropping its items.
+
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandError, CommandRegistration,
+    CommandResult, Coordinates, ExecuteFn, Node,
+};
+use feather_core::blocks::BlockId;
+use feather_core::text::{Color, Text};
+use feather_core::util::{BlockPosition, ChunkPosition, Position};
+use feather_server_types::{BlockDestroyRequest, BlockUpdateCause, Game};
+use fecs::World;
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/setblock <pos> <block> [destroy|keep|replace]",
+        setblock_tree(),
+    )
+}
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/fill <from> <to> <block> [destroy|keep|replace]",
+        literal("fill").then(
+            argument("from", ArgParser::BlockPos).then(
+                argument("to", ArgParser::BlockPos)
+                    .then(with_modes(argument("block", ArgParser::Block), &fill)),
+            ),
+        ),
+    )
+}
+
+fn setblock_tree() -> Node {
+    literal("setblock").then(
+        argument("pos", ArgParser::BlockPos)
+            .then(with_modes(argument("block", ArgParser::Block), &setblock)),
+    )
+}
+
+/// The most blocks `/fill` may change at once, as in vanilla.
+const MAX_FILL_VOLUME: i64 = 32_768;
+
+/// How existing blocks are treated when placing a block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    Replace,
+    Keep,
+    Destroy,
+}
+
+impl Mode {
+    fn from_args(args: &Arguments) -> Self {
+        match args.literals().first() {
+            Some(&"keep") => Mode::Keep,
+            Some(&"destroy") => Mode::Destroy,
+            _ => Mode::Replace,
+        }
+    }
+}
+
+/// Adds the mode literals to the block argument of a command.
+fn with_modes(node: Node, f: &'static dyn ExecuteFn) -> Node {
+    node.executes(f)
+        .then(literal("destroy").executes(f))
+        .then(literal("keep").executes(f))
+        .then(literal("replace").executes(f))
+}
+
+fn setblock(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let pos = block_position(ctx, args, "pos");
+    let block = args.get::<BlockId>("block").unwrap();
+    check_in_world(pos)?;
+    if ctx.game.block_at(pos).is_none() {
+        return Err(CommandError::message("That position is not loaded"));
+    }
+
+    let cause = BlockUpdateCause::entity(ctx.sender);
+    if !place(
+        ctx.game,
+        ctx.world,
+        pos,
+        block,
+        Mode::from_args(args),
+        cause,
+    ) {
+        return Err(CommandError::message("Could not set the block"));
+    }
+
+    ctx.reply(
+        Text::from(format!(
+            "Changed the block at {}, {}, {}",
+            pos.x, pos.y, pos.z
+        )) * Color::Gray,
+    );
+    Ok(())
+}
+
+fn fill(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let from = block_position(ctx, args, "from");
+    let to = block_position(ctx, args, "to");
+    let block = args.get::<BlockId>("block").unwrap();
+    let mode = Mode::from_args(args);
+
+    let min = BlockPosition::new(from.x.min(to.x), from.y.min(to.y), from.z.min(to.z));
+    let max = BlockPosition::new(from.x.max(to.x), from.y.max(to.y), from.z.max(to.z));
+    let volume =
+        i64::from(max.x - min.x + 1) * i64::from(max.y - min.y + 1) * i64::from(max.z - min.z + 1);
+    if volume > MAX_FILL_VOLUME {
+        return Err(CommandError::message(format!(
+            "Too many blocks in the specified area (maximum {}, specified {})",
+            MAX_FILL_VOLUME, volume
+        )));
+    }
+    check_in_world(min)?;
+    check_in_world(max)?;
+    for x in min.x >> 4..=max.x >> 4 {
+        for z in min.z >> 4..=max.z >> 4 {
+            if ctx
+                .game
+                .chunk_map
+                .chunk_at(ChunkPosition::new(x, z))
+                .is_none()
+            {
+                return Err(CommandError::message("That position is not loaded"));
+            }
+        }
+    }
+
+    // All changes share a cause, and so a batch in the block log.
+    let cause = BlockUpdateCause::entity(ctx.sender);
+    let filled = ctx.game.buffer_block_changes(ctx.world, |game, world| {
+        let mut filled = 0;
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    if place(game, world, BlockPosition::new(x, y, z), block, mode, cause) {
+                        filled += 1;
+                    }
+                }
+            }
+        }
+        filled
+    });
+
+    if filled == 0 {
+        return Err(CommandError::message("No blocks were filled"));
+    }
+    ctx.reply(Text::from(format!("Successfully filled {} blocks", filled)) * Color::Gray);
+    Ok(())
+}
+
+fn block_position(ctx: &CommandCtx, args: &Arguments, name: &str) -> BlockPosition {
+    let origin = *ctx.world.get::<Position>(ctx.sender);
+    args.get::<Coordinates>(name)
+        .unwrap()
+        .block_position(origin)
+}
+
+fn check_in_world(pos: BlockPosition) -> CommandResult {
+    if pos.y < 0 || pos.y > 255 {
+        Err(CommandError::message(
+            "Cannot place blocks outside of the world",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Places a block according to `mode`, returning
+/// whether the block at `pos` was changed.
+fn place(
+    game: &mut Game,
+    world: &mut World,
+    pos: BlockPosition,
+    block: BlockId,
+    mode: Mode,
+    cause: BlockUpdateCause,
+) -> bool {
+    let current = match game.block_at(pos) {
+        Some(current) => current,
+        None => return false,
+    };
+    if current == block || (mode == Mode::Keep && !current.is_air()) {
+        return false;
+    }
+
+    if mode == Mode::Destroy {
+        game.handle(world, BlockDestroyRequest { pos, cause });
+    }
+    if game.block_at(pos) != Some(block) {
+        game.set_block_at_with_cause(world, pos, block, cause);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_command;
+
+    #[test]
+    fn modes() {
+        let tree = setblock_tree();
+
+        let (_, args) = parse_command(&tree, "setblock ~ ~ ~ furnace[facing=north]").unwrap();
+        assert_eq!(Mode::from_args(&args), Mode::Replace);
+        assert_eq!(
+            args.get::<BlockId>("block").map(|block| block.kind()),
+            Some(feather_core::blocks::BlockKind::Furnace)
+        );
+
+        let (_, args) = parse_command(&tree, "setblock 0 64 0 stone keep").unwrap();
+        assert_eq!(Mode::from_args(&args), Mode::Keep);
+        let (_, args) = parse_command(&tree, "setblock 0 64 0 stone destroy").unwrap();
+        assert_eq!(Mode::from_args(&args), Mode::Destroy);
+
+        assert!(parse_command(&tree, "setblock 0 64 0 stone hollow").is_err());
+    }
+}
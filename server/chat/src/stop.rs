@@ -0,0 +1,16 @@
+//! The `/stop` command.
+
+use crate::{literal, Arguments, CommandCtx, CommandRegistration, CommandResult};
+use feather_core::text::{Color, Text};
+use feather_server_types::{ShutdownRequest, MAX_PERMISSION_LEVEL};
+
+inventory::submit! {
+    CommandRegistration::operator_tree("/stop", literal("stop").executes(&stop))
+        .with_permission_level(MAX_PERMISSION_LEVEL)
+}
+
+fn stop(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    ctx.reply(Text::from("Stopping the server") * Color::Gray);
+    ctx.game.handle(ctx.world, ShutdownRequest);
+    Ok(())
+}
@@ -0,0 +1,310 @@
+//! Command suggestions: the command tree sent to clients on
+//! join, and the suggestions returned as players type.
+
+use crate::particle::particle_identifiers;
+use crate::{can_run, declare, literal_completions, ArgKind, CommandHandler, CommandRegistration};
+use feather_core::blocks::BlockKind;
+use feather_core::network::packets::{CommandNode, CommandNodeKind, DeclareCommands};
+use feather_server_types::{Game, Name, Network, Player, PlayerJoinEvent, StatusEffect};
+use fecs::{component, Entity, IntoQuery, Read, World};
+
+/// Suggestions type which makes the client ask the server for suggestions.
+const ASK_SERVER: &str = "minecraft:ask_server";
+/// Parser for arguments, which are all parsed by the server.
+const STRING_PARSER: &str = "brigadier:string";
+/// `brigadier:string` property for a single word.
+const SINGLE_WORD: u8 = 0;
+/// `brigadier:string` property for the rest of the input.
+const GREEDY_PHRASE: u8 = 2;
+
+/// Suggestions for the last word of a partially typed command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestions {
+    /// Index in the text of the word being completed.
+    pub start: usize,
+    /// Length of the word being completed.
+    pub length: usize,
+    pub matches: Vec<String>,
+}
+
+/// Sends joining players the commands they may run, so
+/// their client can highlight syntax and request suggestions.
+#[fecs::event_handler]
+pub fn on_player_join_send_commands(event: &PlayerJoinEvent, game: &Game, world: &mut World) {
+    send_commands(game, world, event.player);
+}
+
+/// Sends a player the commands they may run, as is
+/// needed after their permission level changes.
+pub(crate) fn send_commands(game: &Game, world: &World, player: Entity) {
+    let packet = command_tree(visible_commands(game, world, player));
+    world.get::<Network>(player).send(packet);
+}
+
+/// Returns the commands a player may run.
+fn visible_commands<'a>(
+    game: &'a Game,
+    world: &'a World,
+    player: Entity,
+) -> impl Iterator<Item = &'static CommandRegistration> + 'a {
+    inventory::iter::<CommandRegistration>
+        .into_iter()
+        .filter(move |reg| can_run(game, world, player, reg))
+}
+
+/// Builds the command tree for the given commands.
+///
+/// For commands which parse their own arguments, optional
+/// arguments are not known, so every node is executable.
+/// Those which do not declare their arguments accept any text.
+fn command_tree(commands: impl Iterator<Item = &'static CommandRegistration>) -> DeclareCommands {
+    let mut nodes = vec![CommandNode {
+        kind: CommandNodeKind::Root,
+        executable: false,
+        children: vec![],
+        redirect: None,
+        suggestions: None,
+    }];
+
+    for reg in commands {
+        let kinds = match &reg.handler {
+            CommandHandler::Function { args, .. } => *args,
+            CommandHandler::Tree(root) => {
+                let index = declare(root, &mut nodes);
+                nodes[0].children.push(index);
+                continue;
+            }
+        };
+
+        let literal = nodes.len() as i32;
+        nodes[0].children.push(literal);
+        nodes.push(CommandNode {
+            kind: CommandNodeKind::Literal(reg.name.to_owned()),
+            executable: true,
+            children: vec![],
+            redirect: None,
+            suggestions: None,
+        });
+
+        let names = reg.usage.split_whitespace().skip(1).map(arg_name);
+        let args: Vec<(String, ArgKind)> = if kinds.is_empty() {
+            if reg.usage.split_whitespace().nth(1).is_some() {
+                vec![(String::from("args"), ArgKind::Text)]
+            } else {
+                vec![]
+            }
+        } else {
+            names
+                .chain(std::iter::repeat_with(|| String::from("arg")))
+                .zip(kinds.iter().copied())
+                .collect()
+        };
+
+        let mut parent = literal as usize;
+        for (name, kind) in args {
+            let index = nodes.len();
+            nodes[parent].children.push(index as i32);
+            let (property, suggestions) = match kind {
+                ArgKind::Text => (GREEDY_PHRASE, None),
+                ArgKind::Word => (SINGLE_WORD, None),
+                _ => (SINGLE_WORD, Some(ASK_SERVER.to_owned())),
+            };
+            nodes.push(CommandNode {
+                kind: CommandNodeKind::Argument {
+                    name,
+                    parser: STRING_PARSER.to_owned(),
+                    properties: vec![property],
+                },
+                executable: true,
+                children: vec![],
+                redirect: None,
+                suggestions,
+            });
+            parent = index;
+        }
+    }
+
+    DeclareCommands {
+        nodes,
+        root_index: 0,
+    }
+}
+
+/// Turns an argument in a usage string, such as `[player]`,
+/// into the name shown by the client.
+fn arg_name(usage: &str) -> String {
+    usage
+        .trim_matches(|c| c == '<' || c == '>' || c == '[' || c == ']')
+        .to_owned()
+}
+
+/// Suggests completions for the last word of `text`, a partially
+/// typed command including its leading slash.
+pub fn suggest(game: &Game, world: &World, player: Entity, text: &str) -> Suggestions {
+    let command = if text.starts_with('/') {
+        &text[1..]
+    } else {
+        text
+    };
+    let words: Vec<&str> = command.split(' ').collect();
+    let last = words.last().copied().unwrap_or_default();
+    let start = text.len() - last.len();
+
+    let candidates: Vec<String> = if words.len() == 1 {
+        visible_commands(game, world, player)
+            .map(|reg| reg.name.to_owned())
+            .filter(|command| starts_with_ignore_case(command, last))
+            .collect()
+    } else {
+        // The word being completed is argument `words.len() - 2`.
+        let reg = visible_commands(game, world, player)
+            .find(|reg| reg.name.eq_ignore_ascii_case(words[0]));
+        match reg.map(|reg| &reg.handler) {
+            Some(CommandHandler::Function { args, .. }) => match args.get(words.len() - 2) {
+                Some(&kind) => suggest_arg(world, kind, last),
+                None => vec![],
+            },
+            // Clients suggest the arguments of commands declared as
+            // trees themselves, so only literals are suggested here,
+            // for the console.
+            Some(CommandHandler::Tree(root)) => {
+                literal_completions(root, &words[1..words.len() - 1])
+                    .into_iter()
+                    .filter(|literal| starts_with_ignore_case(literal, last))
+                    .map(str::to_owned)
+                    .collect()
+            }
+            None => vec![],
+        }
+    };
+
+    Suggestions {
+        start,
+        length: last.len(),
+        matches: candidates,
+    }
+}
+
+fn suggest_arg(world: &World, kind: ArgKind, prefix: &str) -> Vec<String> {
+    match kind {
+        ArgKind::Player => <Read<Name>>::query()
+            .filter(component::<Player>())
+            .iter(world.inner())
+            .map(|name| name.0.clone())
+            .filter(|name| starts_with_ignore_case(name, prefix))
+            .collect(),
+        ArgKind::Block => identifiers(BlockKind::all().map(BlockKind::identifier), prefix),
+        ArgKind::Effect => identifiers(StatusEffect::all().map(StatusEffect::identifier), prefix),
+        ArgKind::Particle => identifiers(particle_identifiers(), prefix),
+        ArgKind::Choice(choices) => choices
+            .iter()
+            .filter(|choice| starts_with_ignore_case(choice, prefix))
+            .map(|&choice| choice.to_owned())
+            .collect(),
+        ArgKind::Word | ArgKind::Text => vec![],
+    }
+}
+
+/// Filters namespaced identifiers by a prefix, which
+/// matches with or without the namespace.
+fn identifiers(identifiers: impl Iterator<Item = &'static str>, prefix: &str) -> Vec<String> {
+    identifiers
+        .filter(|id| {
+            starts_with_ignore_case(id, prefix)
+                || starts_with_ignore_case(id.trim_start_matches("minecraft:"), prefix)
+        })
+        .map(str::to_owned)
+        .collect()
+}
+
+fn starts_with_ignore_case(s: &str, prefix: &str) -> bool {
+    s.len() >= prefix.len()
+        && s.is_char_boundary(prefix.len())
+        && s[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_for_declared_and_undeclared_arguments() {
+        fn noop(_: &mut crate::CommandCtx, _: &[&str]) -> crate::CommandResult {
+            Ok(())
+        }
+        let declared = Box::leak(Box::new(
+            CommandRegistration::new("give", "/give <player> <block>", &noop)
+                .with_args(&[ArgKind::Player, ArgKind::Block]),
+        ));
+        let undeclared = Box::leak(Box::new(CommandRegistration::new(
+            "say",
+            "/say <message>",
+            &noop,
+        )));
+        let tree = command_tree(vec![&*declared, &*undeclared].into_iter());
+
+        assert_eq!(tree.nodes[0].children, vec![1, 4]);
+        assert_eq!(tree.nodes[1].kind, CommandNodeKind::Literal("give".into()));
+        assert_eq!(tree.nodes[1].children, vec![2]);
+        assert_eq!(
+            tree.nodes[3].kind,
+            CommandNodeKind::Argument {
+                name: "block".into(),
+                parser: STRING_PARSER.into(),
+                properties: vec![SINGLE_WORD],
+            }
+        );
+        assert_eq!(tree.nodes[3].suggestions.as_deref(), Some(ASK_SERVER));
+        assert_eq!(tree.nodes[5].suggestions, None);
+        match &tree.nodes[5].kind {
+            CommandNodeKind::Argument { properties, .. } => {
+                assert_eq!(properties, &vec![GREEDY_PHRASE])
+            }
+            kind => panic!("{:?}", kind),
+        }
+    }
+
+    #[test]
+    fn tree_commands_are_declared() {
+        use crate::{argument, literal, ArgParser};
+
+        fn noop(_: &mut crate::CommandCtx, _: &crate::Arguments) -> crate::CommandResult {
+            Ok(())
+        }
+        let tree = Box::leak(Box::new(CommandRegistration::tree(
+            "/kill [targets]",
+            literal("kill")
+                .executes(&noop)
+                .then(argument("targets", ArgParser::ENTITIES).executes(&noop)),
+        )));
+        let tree = command_tree(std::iter::once(&*tree));
+
+        assert_eq!(tree.nodes[0].children, vec![1]);
+        assert!(tree.nodes[1].executable);
+        assert_eq!(
+            tree.nodes[2].kind,
+            CommandNodeKind::Argument {
+                name: "targets".into(),
+                parser: "minecraft:entity".into(),
+                properties: vec![0],
+            }
+        );
+    }
+
+    #[test]
+    fn identifiers_match_without_namespace() {
+        let ids = identifiers(StatusEffect::all().map(StatusEffect::identifier), "slow");
+        assert_eq!(ids, vec!["minecraft:slowness", "minecraft:slow_falling"]);
+        let ids = identifiers(
+            BlockKind::all().map(BlockKind::identifier),
+            "minecraft:stone",
+        );
+        assert!(ids.contains(&String::from("minecraft:stone_bricks")));
+    }
+
+    #[test]
+    fn choices_ignore_case() {
+        let choices = suggest_arg(&World::new(), ArgKind::Choice(&["accept", "deny"]), "AC");
+        assert_eq!(choices, vec!["accept"]);
+    }
+}
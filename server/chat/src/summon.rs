@@ -0,0 +1,53 @@
+//! The `/summon` command.
+
+use crate::selector::describe_entity;
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandError, CommandRegistration,
+    CommandResult, Coordinates,
+};
+use feather_core::items::EntityTagNbt;
+use feather_core::text::{Color, Text};
+use feather_core::util::Position;
+use feather_server_types::SummonRequest;
+use std::sync::Arc;
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/summon <entity> [pos] [nbt]",
+        literal("summon").then(
+            argument("entity", ArgParser::EntitySummon)
+                .executes(&summon)
+                .then(
+                    argument("pos", ArgParser::Vec3)
+                        .executes(&summon)
+                        .then(argument("nbt", ArgParser::EntityTag).executes(&summon)),
+                ),
+        ),
+    )
+}
+
+fn summon(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let id = args.get::<String>("entity").unwrap();
+    let origin = *ctx.world.get::<Position>(ctx.sender);
+    let pos = match args.get::<Coordinates>("pos") {
+        Some(coords) => coords.position(origin),
+        None => origin,
+    };
+    // Summoned entities face south, regardless of the sender.
+    let pos = Position {
+        yaw: 0.0,
+        pitch: 0.0,
+        ..pos
+    };
+    let tag = args.get::<EntityTagNbt>("nbt").unwrap_or_default();
+
+    let request = SummonRequest::new(id, pos, tag);
+    let summoned = Arc::clone(&request.summoned);
+    ctx.game.handle(ctx.world, request);
+
+    let entity = *summoned.lock();
+    let entity = entity.ok_or_else(|| CommandError::message("Unable to summon entity"))?;
+    let name = describe_entity(ctx.world, entity);
+    ctx.reply(Text::from(format!("Summoned new {}", name)) * Color::Gray);
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! The `/tag` command, which adds the tags matched
+//! by the `tag` option of entity selectors.
+
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandError, CommandRegistration,
+    CommandResult, EntitySelector,
+};
+use feather_core::text::{Color, Text};
+use feather_server_types::Tags;
+use fecs::Entity;
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/tag <targets> add|remove <name> or /tag <targets> list",
+        literal("tag").then(
+            argument("targets", ArgParser::ENTITIES)
+                .then(literal("add").then(argument("name", ArgParser::Word).executes(&add)))
+                .then(literal("remove").then(argument("name", ArgParser::Word).executes(&remove)))
+                .then(literal("list").executes(&list)),
+        ),
+    )
+}
+
+fn targets(ctx: &CommandCtx, args: &Arguments) -> Result<Vec<Entity>, CommandError> {
+    args.get::<EntitySelector>("targets").unwrap().resolve(ctx)
+}
+
+fn add(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let name = args.get::<String>("name").unwrap();
+    let mut count = 0;
+    for entity in targets(ctx, args)? {
+        if !ctx.world.has::<Tags>(entity) {
+            ctx.world.add(entity, Tags::default()).unwrap();
+        }
+        if ctx.world.get_mut::<Tags>(entity).0.insert(name.clone()) {
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return Err(CommandError::message(
+            "Nothing changed. The entities already have that tag",
+        ));
+    }
+    ctx.reply(Text::from(format!("Added tag '{}' to {} entities", name, count)) * Color::Gray);
+    Ok(())
+}
+
+fn remove(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let name = args.get::<String>("name").unwrap();
+    let mut count = 0;
+    for entity in targets(ctx, args)? {
+        if ctx.world.has::<Tags>(entity) && ctx.world.get_mut::<Tags>(entity).0.remove(&name) {
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return Err(CommandError::message(
+            "Nothing changed. The entities do not have that tag",
+        ));
+    }
+    ctx.reply(Text::from(format!("Removed tag '{}' from {} entities", name, count)) * Color::Gray);
+    Ok(())
+}
+
+fn list(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let entities = targets(ctx, args)?;
+    let mut names: Vec<String> = entities
+        .iter()
+        .filter_map(|&entity| ctx.world.try_get::<Tags>(entity))
+        .flat_map(|tags| tags.0.iter().cloned().collect::<Vec<_>>())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    if names.is_empty() {
+        ctx.reply(Text::from(format!("{} entities have no tags", entities.len())) * Color::Gray);
+    } else {
+        ctx.reply(
+            Text::from(format!(
+                "{} entities have {} total tags: {}",
+                entities.len(),
+                names.len(),
+                names.join(", ")
+            )) * Color::Gray,
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,254 @@
+//! The `/tp` and `/teleport` commands.
+//!
+//! Coordinates and rotations relative with `~` or `^` are
+//! relative to the sender, not to each teleported entity.
+//! Entities can only be teleported within the world they
+//! are in, as there is no other world to teleport them to.
+
+use crate::selector::describe_entity;
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandError, CommandRegistration,
+    CommandResult, Coordinates, EntitySelector, Node, Rotation,
+};
+use feather_core::network::packets::PlayerPositionAndLookClientbound;
+use feather_core::text::{Color, Text};
+use feather_core::util::Position;
+use feather_server_types::{player_eye_height, FallDistance, Network, Player};
+use fecs::{Entity, World};
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/tp [targets] <destination|location> [rotation|facing ...]",
+        tree("tp"),
+    )
+}
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/teleport [targets] <destination|location> [rotation|facing ...]",
+        tree("teleport"),
+    )
+}
+
+fn tree(name: &'static str) -> Node {
+    let facing_entity = argument("facingEntity", ArgParser::ENTITY)
+        .executes(&to_location)
+        .then(literal("eyes").executes(&to_location_facing_eyes))
+        .then(literal("feet").executes(&to_location));
+
+    literal(name)
+        .then(argument("location", ArgParser::Vec3).executes(&to_location))
+        .then(argument("destination", ArgParser::ENTITY).executes(&to_entity))
+        .then(
+            argument("targets", ArgParser::ENTITIES)
+                .then(
+                    argument("location", ArgParser::Vec3)
+                        .executes(&to_location)
+                        .then(argument("rotation", ArgParser::Rotation).executes(&to_location))
+                        .then(
+                            literal("facing")
+                                .then(literal("entity").then(facing_entity))
+                                .then(
+                                    argument("facingLocation", ArgParser::Vec3)
+                                        .executes(&to_location),
+                                ),
+                        ),
+                )
+                .then(argument("destination", ArgParser::ENTITY).executes(&to_entity)),
+        )
+}
+
+/// Returns the entities to teleport, which
+/// are the sender if no targets are given.
+fn targets(ctx: &CommandCtx, args: &Arguments) -> Result<Vec<Entity>, CommandError> {
+    match args.get::<EntitySelector>("targets") {
+        Some(targets) => targets.resolve(ctx),
+        None => Ok(vec![ctx.player()?]),
+    }
+}
+
+fn to_entity(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let targets = targets(ctx, args)?;
+    let destination = args
+        .get::<EntitySelector>("destination")
+        .unwrap()
+        .resolve_one(ctx)?;
+    let pos = *ctx.world.get::<Position>(destination);
+
+    for &target in &targets {
+        teleport(ctx.world, target, pos);
+    }
+
+    let destination = describe_entity(ctx.world, destination);
+    reply(ctx, &targets, &destination);
+    Ok(())
+}
+
+fn to_location(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    teleport_to_location(ctx, args, false)
+}
+
+fn to_location_facing_eyes(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    teleport_to_location(ctx, args, true)
+}
+
+fn teleport_to_location(ctx: &mut CommandCtx, args: &Arguments, eyes: bool) -> CommandResult {
+    let targets = targets(ctx, args)?;
+    let origin = *ctx.world.get::<Position>(ctx.sender);
+    let location = args
+        .get::<Coordinates>("location")
+        .unwrap()
+        .position(origin);
+
+    let rotation = args
+        .get::<Rotation>("rotation")
+        .map(|rotation| rotation.resolve(origin));
+    let facing = match args.get::<EntitySelector>("facingEntity") {
+        Some(selector) => {
+            let entity = selector.resolve_one(ctx)?;
+            let mut pos = *ctx.world.get::<Position>(entity);
+            if eyes {
+                pos.y += eye_height(ctx.world, entity);
+            }
+            Some(pos)
+        }
+        None => args
+            .get::<Coordinates>("facingLocation")
+            .map(|facing| facing.position(origin)),
+    };
+
+    for &target in &targets {
+        let mut pos = location;
+        let current = *ctx.world.get::<Position>(target);
+        let (yaw, pitch) = match (rotation, facing) {
+            (Some(rotation), _) => rotation,
+            (None, Some(facing)) => {
+                let mut eye = pos;
+                eye.y += eye_height(ctx.world, target);
+                look_at(eye, facing)
+            }
+            (None, None) => (current.yaw, current.pitch),
+        };
+        pos.yaw = yaw;
+        pos.pitch = pitch;
+        teleport(ctx.world, target, pos);
+    }
+
+    let destination = format!("{:.2}, {:.2}, {:.2}", location.x, location.y, location.z);
+    reply(ctx, &targets, &destination);
+    Ok(())
+}
+
+/// Moves an entity to `pos`. Players are sent their new
+/// position, and moves of other entities are broadcast
+/// as usual by the movement broadcaster.
+fn teleport(world: &mut World, entity: Entity, mut pos: Position) {
+    pos.on_ground = false;
+    *world.get_mut::<Position>(entity) = pos;
+
+    if let Some(network) = world.try_get::<Network>(entity) {
+        network.send(PlayerPositionAndLookClientbound {
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+            yaw: pos.yaw,
+            pitch: pos.pitch,
+            flags: 0,
+            teleport_id: 0,
+        });
+    }
+
+    if world.has::<FallDistance>(entity) {
+        world.get_mut::<FallDistance>(entity).0 = 0.0;
+    }
+}
+
+fn eye_height(world: &World, entity: Entity) -> f64 {
+    if world.has::<Player>(entity) {
+        player_eye_height(world, entity)
+    } else {
+        0.0
+    }
+}
+
+/// Returns the yaw and pitch with which
+/// an entity at `from` looks at `target`.
+fn look_at(from: Position, target: Position) -> (f32, f32) {
+    let dx = target.x - from.x;
+    let dy = target.y - from.y;
+    let dz = target.z - from.z;
+    let horizontal = (dx * dx + dz * dz).sqrt();
+
+    let yaw = dz.atan2(dx).to_degrees() - 90.0;
+    let pitch = -dy.atan2(horizontal).to_degrees();
+    (yaw as f32, pitch as f32)
+}
+
+fn reply(ctx: &CommandCtx, targets: &[Entity], destination: &str) {
+    let message = match targets {
+        [target] => format!(
+            "Teleported {} to {}",
+            describe_entity(ctx.world, *target),
+            destination
+        ),
+        _ => format!("Teleported {} entities to {}", targets.len(), destination),
+    };
+    ctx.reply(Text::from(message) * Color::Gray);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_command;
+
+    fn position(x: f64, y: f64, z: f64) -> Position {
+        Position {
+            x,
+            y,
+            z,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn look_angles() {
+        let from = position(0.0, 0.0, 0.0);
+        // Yaw 0 looks towards positive Z.
+        assert_eq!(look_at(from, position(0.0, 0.0, 5.0)), (0.0, 0.0));
+        let (yaw, pitch) = look_at(from, position(-5.0, 0.0, 0.0));
+        assert!((yaw - 90.0).abs() < 1e-4);
+        assert_eq!(pitch, 0.0);
+        let (_, pitch) = look_at(from, position(0.0, 5.0, 5.0));
+        assert!((pitch + 45.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn parse_forms() {
+        let tree = tree("tp");
+        let parses = |command: &str| parse_command(&tree, command).map(|(_, args)| args);
+
+        // World and local coordinates cannot be mixed.
+        assert!(parses("tp 1 ~2 ^").is_err());
+
+        let args = parses("tp ~ ~1 ~").unwrap();
+        assert!(args.has("location") && !args.has("targets"));
+
+        let args = parses("tp Notch").unwrap();
+        assert!(args.has("destination") && !args.has("targets"));
+
+        let args = parses("tp @a Notch").unwrap();
+        assert!(args.has("targets") && args.has("destination"));
+
+        let args = parses("tp @e[type=cow] 0 64 0 90 ~").unwrap();
+        assert!(args.has("rotation"));
+
+        let args = parses("tp @s ^ ^ ^5 facing 0 64 0").unwrap();
+        assert!(args.has("facingLocation"));
+
+        let args = parses("tp @s 0 64 0 facing entity Notch eyes").unwrap();
+        assert!(args.has("facingEntity"));
+
+        // The destination must be a single entity.
+        assert!(parses("tp @s @a").is_err());
+    }
+}
@@ -0,0 +1,83 @@
+//! The `/time` command.
+
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandRegistration, CommandResult,
+    ExecuteFn, Node,
+};
+use feather_core::text::{Color, Text};
+use feather_server_util::broadcast_time;
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/time set|add <time> or /time query daytime|gametime|day",
+        literal("time")
+            .then(
+                literal("set")
+                    .then(literal("day").executes(&set_day))
+                    .then(literal("noon").executes(&set_noon))
+                    .then(literal("night").executes(&set_night))
+                    .then(literal("midnight").executes(&set_midnight))
+                    .then(time_argument(&set)),
+            )
+            .then(literal("add").then(time_argument(&add)))
+            .then(
+                literal("query")
+                    .then(literal("daytime").executes(&query_daytime))
+                    .then(literal("gametime").executes(&query_gametime))
+                    .then(literal("day").executes(&query_day)),
+            ),
+    )
+}
+
+fn time_argument(f: &'static dyn ExecuteFn) -> Node {
+    argument("time", ArgParser::Time).executes(f)
+}
+
+fn set(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    set_time(ctx, args.get::<i32>("time").unwrap() as u64)
+}
+
+fn set_day(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    set_time(ctx, 1000)
+}
+
+fn set_noon(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    set_time(ctx, 6000)
+}
+
+fn set_night(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    set_time(ctx, 13_000)
+}
+
+fn set_midnight(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    set_time(ctx, 18_000)
+}
+
+fn add(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let time = ctx.game.time.day_time() + args.get::<i32>("time").unwrap() as u64;
+    set_time(ctx, time)
+}
+
+fn set_time(ctx: &mut CommandCtx, time: u64) -> CommandResult {
+    ctx.game.time.set_day_time(time);
+    broadcast_time(ctx.game, ctx.world);
+    ctx.reply(Text::from(format!("Set the time to {}", time)) * Color::Gray);
+    Ok(())
+}
+
+fn query_daytime(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    query(ctx, ctx.game.time.time_of_day())
+}
+
+fn query_gametime(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    query(ctx, ctx.game.time.world_age())
+}
+
+fn query_day(ctx: &mut CommandCtx, _args: &Arguments) -> CommandResult {
+    query(ctx, ctx.game.time.day())
+}
+
+fn query(ctx: &CommandCtx, time: u64) -> CommandResult {
+    ctx.reply(Text::from(format!("The time is {}", time)) * Color::Gray);
+    Ok(())
+}
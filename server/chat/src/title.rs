@@ -0,0 +1,89 @@
+//! The `/title` command.
+
+use crate::{find_targets, ArgKind, CommandCtx, CommandError, CommandRegistration, CommandResult};
+use feather_core::text::{Color, Text};
+use feather_server_types::{Title, TitleTimes};
+
+inventory::submit! {
+    CommandRegistration::operator(
+        "title",
+        "/title <player> <clear|reset|title|subtitle|actionbar|times> ...",
+        &title,
+    )
+    .with_args(&[
+        ArgKind::Player,
+        ArgKind::Choice(&["clear", "reset", "title", "subtitle", "actionbar", "times"]),
+        ArgKind::Text,
+    ])
+}
+
+/// Parses the text of a title, which may be a JSON text
+/// component or, for convenience, plain text.
+fn parse_text(args: &[&str]) -> Result<Text, CommandError> {
+    if args.is_empty() {
+        return Err(CommandError::Usage);
+    }
+    let text = args.join(" ");
+    Ok(Text::from_json(&text).unwrap_or_else(|_| Text::from(text)))
+}
+
+fn parse_ticks(ticks: &str) -> Result<u32, CommandError> {
+    ticks
+        .parse()
+        .map_err(|_| CommandError::message(format!("Invalid number of ticks \"{}\"", ticks)))
+}
+
+fn title(ctx: &mut CommandCtx, args: &[&str]) -> CommandResult {
+    if args.len() < 2 {
+        return Err(CommandError::Usage);
+    }
+    let targets = find_targets(ctx, args[0])?;
+    let (action, rest) = (args[1], &args[2..]);
+
+    let message = match action {
+        "clear" | "reset" if rest.is_empty() => {
+            for &player in &targets {
+                ctx.game.clear_title(player, ctx.world, action == "reset");
+            }
+            format!("Removed the title for {} players", targets.len())
+        }
+        "title" | "subtitle" => {
+            let text = parse_text(rest)?;
+            let title = if action == "title" {
+                Title::new(text)
+            } else {
+                Title::default().with_subtitle(text)
+            };
+            for &player in &targets {
+                ctx.game.send_title(player, ctx.world, title.clone());
+            }
+            format!("Showing the {} to {} players", action, targets.len())
+        }
+        "actionbar" => {
+            let text = parse_text(rest)?;
+            for &player in &targets {
+                ctx.game.send_action_bar(player, ctx.world, text.clone());
+            }
+            format!("Showing the action bar to {} players", targets.len())
+        }
+        "times" if rest.len() == 3 => {
+            let times = TitleTimes {
+                fade_in: parse_ticks(rest[0])?,
+                stay: parse_ticks(rest[1])?,
+                fade_out: parse_ticks(rest[2])?,
+            };
+            let title = Title {
+                times: Some(times),
+                ..Default::default()
+            };
+            for &player in &targets {
+                ctx.game.send_title(player, ctx.world, title.clone());
+            }
+            format!("Changed the title times for {} players", targets.len())
+        }
+        _ => return Err(CommandError::Usage),
+    };
+
+    ctx.reply(Text::from(message) * Color::Gray);
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! The `/viewdistance` command, which shows or
+//! overrides the view distance of players.
+
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandRegistration, CommandResult,
+    EntitySelector, Node,
+};
+use feather_core::text::{Color, Text};
+use feather_server_types::{Name, ViewDistance, ViewDistanceRequest};
+
+inventory::submit! {
+    CommandRegistration::operator_tree("/viewdistance <targets> [distance]", viewdistance_tree())
+}
+
+/// View distances which may be set, as allowed by vanilla servers.
+const MIN_VIEW_DISTANCE: i32 = 2;
+const MAX_VIEW_DISTANCE: i32 = 32;
+
+fn viewdistance_tree() -> Node {
+    literal("viewdistance").then(
+        argument("targets", ArgParser::PLAYERS)
+            .executes(&query)
+            .then(
+                argument(
+                    "distance",
+                    ArgParser::Integer {
+                        min: Some(MIN_VIEW_DISTANCE),
+                        max: Some(MAX_VIEW_DISTANCE),
+                    },
+                )
+                .executes(&set),
+            ),
+    )
+}
+
+fn query(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let targets = args
+        .get::<EntitySelector>("targets")
+        .unwrap()
+        .resolve(ctx)?;
+    for player in targets {
+        let name = ctx.world.get::<Name>(player).0.clone();
+        let distance = match ctx.world.try_get::<ViewDistance>(player) {
+            Some(distance) => *distance,
+            None => continue,
+        };
+        let message = if distance.current < distance.target {
+            format!(
+                "{} has a view distance of {} chunks, lowered to {} while the server is overloaded",
+                name, distance.target, distance.current
+            )
+        } else {
+            format!("{} has a view distance of {} chunks", name, distance.target)
+        };
+        ctx.reply(Text::from(message) * Color::Gray);
+    }
+    Ok(())
+}
+
+fn set(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    let distance = args.get::<i32>("distance").unwrap() as u8;
+    let targets = args
+        .get::<EntitySelector>("targets")
+        .unwrap()
+        .resolve(ctx)?;
+    for &player in &targets {
+        ctx.game
+            .handle(ctx.world, ViewDistanceRequest { player, distance });
+    }
+
+    let recipient = match targets.as_slice() {
+        [player] => ctx.world.get::<Name>(*player).0.clone(),
+        _ => format!("{} players", targets.len()),
+    };
+    ctx.reply(
+        Text::from(format!(
+            "Set the view distance of {} to {} chunks",
+            recipient, distance
+        )) * Color::Gray,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_command;
+
+    #[test]
+    fn distance_bounds() {
+        let tree = viewdistance_tree();
+        let (_, args) = parse_command(&tree, "viewdistance @a 12").unwrap();
+        assert_eq!(args.get::<i32>("distance"), Some(12));
+        assert!(parse_command(&tree, "viewdistance @a").is_ok());
+        assert!(parse_command(&tree, "viewdistance @a 1").is_err());
+        assert!(parse_command(&tree, "viewdistance @a 33").is_err());
+    }
+}
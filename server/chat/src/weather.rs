@@ -0,0 +1,64 @@
+//! The `/weather` command.
+
+use crate::{
+    argument, literal, ArgParser, Arguments, CommandCtx, CommandRegistration, CommandResult,
+    ExecuteFn, Node,
+};
+use feather_core::text::{Color, Text};
+use feather_server_types::{Weather, WeatherRequest, TPS};
+
+inventory::submit! {
+    CommandRegistration::operator_tree(
+        "/weather clear|rain|thunder [duration]",
+        literal("weather")
+            .then(weather_literal("clear", &clear))
+            .then(weather_literal("rain", &rain))
+            .then(weather_literal("thunder", &thunder)),
+    )
+}
+
+/// Duration of weather set without a duration, in seconds.
+const DEFAULT_DURATION: i32 = 300;
+
+fn weather_literal(name: &'static str, f: &'static dyn ExecuteFn) -> Node {
+    literal(name).executes(f).then(
+        argument(
+            "duration",
+            ArgParser::Integer {
+                min: Some(0),
+                max: Some(1_000_000),
+            },
+        )
+        .executes(f),
+    )
+}
+
+fn clear(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    set_weather(ctx, args, Weather::Clear, "clear")
+}
+
+fn rain(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    set_weather(ctx, args, Weather::Rain, "rain")
+}
+
+fn thunder(ctx: &mut CommandCtx, args: &Arguments) -> CommandResult {
+    set_weather(ctx, args, Weather::Thunder, "rain & thunder")
+}
+
+fn set_weather(
+    ctx: &mut CommandCtx,
+    args: &Arguments,
+    weather: Weather,
+    name: &str,
+) -> CommandResult {
+    let seconds = args.get::<i32>("duration").unwrap_or(DEFAULT_DURATION);
+    ctx.game.handle(
+        ctx.world,
+        WeatherRequest {
+            weather,
+            duration: seconds * TPS as i32,
+        },
+    );
+    ctx.reply(Text::from(format!("Set the weather to {}", name)) * Color::Gray);
+    Ok(())
+}
@@ -6,7 +6,9 @@ use crossbeam::channel::{Receiver, Sender};
 use std::sync::atomic::{AtomicU32, Ordering};
 
 use crate::chunk_worker;
+use crate::AutoSave;
 use ahash::AHashSet;
+use feather_core::anvil::block_entity::BlockEntityData;
 use feather_core::anvil::entity::EntityData;
 use feather_core::chunk::Chunk;
 use feather_core::util::ChunkPosition;
@@ -46,8 +48,11 @@ pub fn chunk_load(
         if let chunk_worker::Reply::LoadedChunk(pos, result) = reply {
             loading_chunks.0.remove(&pos);
             match result {
-                Ok((chunk, entities)) => {
+                Ok((chunk, entities, block_entities)) => {
                     game.chunk_map.insert(chunk);
+                    block_entities
+                        .into_iter()
+                        .for_each(|block_entity| game.block_entities.insert(block_entity));
 
                     entities.into_iter().for_each(|builder| {
                         let entity = builder.build().spawn_in(world);
@@ -126,7 +131,14 @@ pub fn chunk_unload(
     game: &mut Game,
     world: &mut World,
     #[default] chunk_unload_queue: &mut ChunkUnloadQueue,
+    #[default] auto_save: &AutoSave,
 ) {
+    // Unloading a chunk saves it, so chunks stay
+    // loaded while saving is disabled.
+    if !auto_save.is_enabled() {
+        return;
+    }
+
     // Unload chunks which are finished in the queue.
 
     // Since chunks are queued in the back and taken out
@@ -151,6 +163,7 @@ pub fn chunk_unload(
                     },
                 );
                 game.chunk_map.remove(unload.chunk);
+                game.block_entities.remove_chunk(unload.chunk);
                 log::trace!("Unloaded chunk at {}", unload.chunk);
             }
             chunk_unload_queue.queue.pop_front();
@@ -293,10 +306,15 @@ pub fn save_chunk(
     handle: &ChunkWorkerHandle,
     chunk: Arc<RwLock<Chunk>>,
     entities: Vec<EntityData>,
+    block_entities: Vec<BlockEntityData>,
 ) {
     handle
         .sender
-        .send(chunk_worker::Request::SaveChunk(chunk, entities))
+        .send(chunk_worker::Request::SaveChunk(
+            chunk,
+            entities,
+            block_entities,
+        ))
         .unwrap();
 }
 
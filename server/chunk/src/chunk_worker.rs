@@ -6,6 +6,7 @@
 //! instead.
 use ahash::AHashMap;
 use crossbeam::channel::{Receiver, Sender};
+use feather_core::anvil::block_entity::BlockEntityData;
 use feather_core::anvil::entity::EntityData;
 use feather_core::anvil::region;
 use feather_core::anvil::region::{RegionHandle, RegionPosition};
@@ -24,7 +25,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 pub enum Reply {
     LoadedChunk(
         ChunkPosition,
-        anyhow::Result<(Chunk, SmallVec<[EntityBuilder; 4]>)>,
+        anyhow::Result<(Chunk, SmallVec<[EntityBuilder; 4]>, Vec<BlockEntityData>)>,
     ),
     SavedChunk(ChunkPosition),
 }
@@ -32,7 +33,7 @@ pub enum Reply {
 #[derive(Clone)]
 pub enum Request {
     LoadChunk(ChunkPosition),
-    SaveChunk(Arc<RwLock<Chunk>>, Vec<EntityData>),
+    SaveChunk(Arc<RwLock<Chunk>>, Vec<EntityData>, Vec<BlockEntityData>),
     ShutDown,
 }
 
@@ -111,8 +112,8 @@ fn run(mut worker: ChunkWorker) {
     while let Ok(request) = worker.receiver.recv() {
         match request {
             Request::ShutDown => break,
-            Request::SaveChunk(chunk, entities) => {
-                save_chunk(&mut worker, &*chunk.read(), entities);
+            Request::SaveChunk(chunk, entities, block_entities) => {
+                save_chunk(&mut worker, &*chunk.read(), entities, block_entities);
             }
             Request::LoadChunk(pos) => {
                 if let Some(reply) = load_chunk(&mut worker, pos) {
@@ -150,7 +151,7 @@ fn load_chunk_from_handle(
     let result = handle.load_chunk(pos);
 
     match result {
-        Ok((chunk, entities)) => {
+        Ok((chunk, entities, block_entities)) => {
             let entities = entities
                 .into_iter()
                 .filter_map(|entity| entity_loader.load(entity))
@@ -159,7 +160,7 @@ fn load_chunk_from_handle(
             Some(Reply::LoadedChunk(
                 pos,
                 match entities {
-                    Ok(entities) => Ok((chunk, entities)),
+                    Ok(entities) => Ok((chunk, entities, block_entities)),
                     Err(e) => Err(e),
                 },
             ))
@@ -191,16 +192,26 @@ fn schedule_generate_new_chunk(
 /// Generates a new chunk synchronously,
 /// returning a Reply to send to a Sender.
 fn generate_new_chunk(pos: ChunkPosition, generator: &Arc<dyn WorldGenerator>) -> Reply {
-    Reply::LoadedChunk(pos, Ok((generator.generate_chunk(pos), SmallVec::new())))
+    Reply::LoadedChunk(
+        pos,
+        Ok((generator.generate_chunk(pos), SmallVec::new(), vec![])),
+    )
 }
 
 /// Saves the chunk at the specified position.
-fn save_chunk(worker: &mut ChunkWorker, chunk: &Chunk, entities: Vec<EntityData>) {
+fn save_chunk(
+    worker: &mut ChunkWorker,
+    chunk: &Chunk,
+    entities: Vec<EntityData>,
+    block_entities: Vec<BlockEntityData>,
+) {
     let rpos = RegionPosition::from_chunk(chunk.position());
 
     let file = worker_region(&mut worker.open_regions, &worker.dir, rpos);
 
-    file.handle.save_chunk(chunk, entities).unwrap();
+    file.handle
+        .save_chunk(chunk, entities, block_entities)
+        .unwrap();
     worker
         .sender
         .send(Reply::SavedChunk(chunk.position()))
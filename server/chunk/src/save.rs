@@ -6,102 +6,199 @@ use feather_core::anvil::player::{InventorySlot, PlayerData};
 use feather_core::inventory::Inventory;
 use feather_core::util::{ChunkPosition, Gamemode, Position, Vec3d};
 use feather_server_types::{
-    ChunkLoadEvent, ChunkUnloadEvent, ComponentSerializer, Game, PlayerLeaveEvent, Uuid,
-    TICK_LENGTH, TPS,
+    add_statistic, AdvancementProgress, AutoSaveRequest, BedSpawn, ChunkUnloadEvent,
+    ComponentSerializer, CustomStatistic, Experience, Game, Player, PlayerLeaveEvent, RecipeBook,
+    SaveRequest, Statistic, Statistics, Uuid, TICK_LENGTH,
 };
-use fecs::{Entity, World};
+use fecs::{Entity, IntoQuery, Read, World};
 use std::collections::VecDeque;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
-/// A chunk to save + the tick count at which to do so.
-#[derive(Clone, Copy, Debug)]
-struct SaveTask {
-    /// Chunk position to save.
-    chunk: ChunkPosition,
-    /// Tick count at which to save this chunk.
-    at: u64,
+/// Number of chunks saved each tick during a save.
+const CHUNKS_PER_TICK: usize = 32;
+/// Number of players whose data is saved each tick during a save.
+const PLAYERS_PER_TICK: usize = 4;
+
+/// Resource which schedules saving of the world.
+///
+/// Every `save_interval`, a save is started which writes the
+/// modified chunks and the data of online players a few at a time
+/// over the following ticks, so that saving does not stall one tick.
+#[derive(Debug)]
+pub struct AutoSave {
+    /// Whether saves are started automatically.
+    enabled: bool,
+    /// Tick at which the next automatic save starts.
+    next_save: Option<u64>,
+    /// Chunks and players remaining in the current save.
+    chunks: VecDeque<ChunkPosition>,
+    players: VecDeque<Entity>,
+    /// Whether the current save was requested with `/save-all`,
+    /// in which case its completion is logged.
+    requested: bool,
+}
+
+impl Default for AutoSave {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            next_save: None,
+            chunks: VecDeque::new(),
+            players: VecDeque::new(),
+            requested: false,
+        }
+    }
 }
 
-/// Queue of chunks to save.
-#[derive(Debug, Default)]
-struct SaveQueue(VecDeque<SaveTask>);
+impl AutoSave {
+    /// Returns whether chunks are saved automatically. While they
+    /// are not, chunks are kept loaded rather than saved on unload.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
 
-/// On a chunk load, adds the chunk to the save queue.
-#[fecs::event_handler]
-pub fn on_chunk_load_queue_for_saving(
-    event: &ChunkLoadEvent,
+    fn is_saving(&self) -> bool {
+        !self.chunks.is_empty() || !self.players.is_empty()
+    }
+
+    /// Starts saving all loaded chunks and online players,
+    /// replacing any save in progress.
+    fn start(&mut self, game: &Game, world: &World) {
+        self.chunks.clear();
+        self.chunks.extend(
+            game.chunk_map
+                .iter_chunks()
+                .map(|chunk| chunk.read().position()),
+        );
+        self.players.clear();
+        self.players.extend(
+            <Read<Player>>::query()
+                .iter_entities(world.inner())
+                .map(|(player, _)| player),
+        );
+        self.next_save = Some(game.tick_count + ticks(game.config.world.save_interval));
+    }
+
+    /// Saves up to the given numbers of chunks and
+    /// players remaining in the current save.
+    fn save(
+        &mut self,
+        game: &Game,
+        world: &World,
+        chunk_worker_handle: &ChunkWorkerHandle,
+        chunks: usize,
+        players: usize,
+    ) {
+        let remaining = self.chunks.len().min(chunks);
+        for pos in self.chunks.drain(..remaining) {
+            // The chunk may have been unloaded, and so saved, since.
+            if game.chunk_map.chunk_at(pos).is_some() {
+                save_chunk_at(game, world, pos, chunk_worker_handle);
+            }
+        }
+
+        let remaining = self.players.len().min(players);
+        for player in self.players.drain(..remaining) {
+            if world.is_alive(player) {
+                save_player_data(game, world, player);
+            }
+        }
+
+        if !self.is_saving() && self.requested {
+            self.requested = false;
+            log::info!("Saved the game");
+        }
+    }
+}
+
+fn ticks(duration: Duration) -> u64 {
+    duration.as_millis() as u64 / TICK_LENGTH
+}
+
+/// System which starts automatic saves and
+/// runs the save in progress, if any.
+#[fecs::system]
+pub fn auto_save(
     game: &mut Game,
-    #[default] save_queue: &mut SaveQueue,
+    world: &mut World,
+    #[default] auto_save: &mut AutoSave,
+    chunk_worker_handle: &ChunkWorkerHandle,
 ) {
-    queue_for_saving(game, save_queue, event.chunk);
+    let next_save = *auto_save
+        .next_save
+        .get_or_insert(game.tick_count + ticks(game.config.world.save_interval));
+    if auto_save.enabled && !auto_save.is_saving() && game.tick_count >= next_save {
+        log::debug!("Starting automatic save");
+        auto_save.start(game, world);
+    }
+
+    auto_save.save(
+        game,
+        world,
+        chunk_worker_handle,
+        CHUNKS_PER_TICK,
+        PLAYERS_PER_TICK,
+    );
 }
 
-/// On a chunk unload, saves the chunk first.
+/// Handles a `SaveRequest`.
 #[fecs::event_handler]
-pub fn on_chunk_unload_save_chunk(
-    event: &ChunkUnloadEvent,
+pub fn save_request(
+    event: &SaveRequest,
     game: &mut Game,
     world: &mut World,
+    #[default] auto_save: &mut AutoSave,
     chunk_worker_handle: &ChunkWorkerHandle,
 ) {
-    save_chunk_at(game, world, event.chunk, chunk_worker_handle);
+    auto_save.start(game, world);
+    auto_save.requested = true;
+    if event.flush {
+        auto_save.save(
+            game,
+            world,
+            chunk_worker_handle,
+            usize::max_value(),
+            usize::max_value(),
+        );
+    }
 }
 
-fn queue_for_saving(game: &mut Game, save_queue: &mut SaveQueue, chunk: ChunkPosition) {
-    let tick_to_save_at =
-        game.tick_count + (game.config.world.save_interval.as_millis() as u64) / TICK_LENGTH;
-
-    let task = SaveTask {
-        chunk,
-        at: tick_to_save_at,
-    };
-
-    save_queue.0.push_back(task);
+/// Handles an `AutoSaveRequest`. Disabling automatic
+/// saving also stops an automatic save in progress.
+#[fecs::event_handler]
+pub fn auto_save_request(event: &AutoSaveRequest, #[default] auto_save: &mut AutoSave) {
+    *event.changed.lock() = auto_save.enabled != event.enabled;
+    auto_save.enabled = event.enabled;
+    if !event.enabled && !auto_save.requested {
+        auto_save.chunks.clear();
+        auto_save.players.clear();
+    }
 }
 
-/// System which checks for chunks which have been queued for saving
-/// and, if it is time, saves them.
-#[fecs::system]
-pub fn chunk_save(
+/// On a chunk unload, saves the chunk first, then
+/// despawns the entities which were saved with it.
+#[fecs::event_handler]
+pub fn on_chunk_unload_save_chunk(
+    event: &ChunkUnloadEvent,
     game: &mut Game,
     world: &mut World,
-    save_queue: &mut SaveQueue,
     chunk_worker_handle: &ChunkWorkerHandle,
 ) {
-    // no need to run this system every tick
-    if game.tick_count % TPS != 0 {
-        return;
-    }
-
-    loop {
-        let task = match save_queue.0.front().copied() {
-            Some(task) => task,
-            None => return, // no save tasks to run
-        };
-
-        if game.chunk_map.chunk_at(task.chunk).is_none() {
-            save_queue
-                .0
-                .pop_front()
-                .expect("we just verified the front task exists");
-            continue;
-        }
-
-        if task.at <= game.tick_count {
-            // Save the chunk, then pop the task from the queue.
-            save_chunk_at(game, world, task.chunk, chunk_worker_handle);
-
-            save_queue
-                .0
-                .pop_front()
-                .expect("we just verified the front task exists");
+    save_chunk_at(game, world, event.chunk, chunk_worker_handle);
 
-            // Requeue the chunk for saving again.
-            queue_for_saving(game, save_queue, task.chunk);
-        } else {
-            return;
-        }
+    // Entities are recreated from the saved data
+    // when the chunk is next loaded.
+    let entities: Vec<_> = game
+        .chunk_entities
+        .entities_in_chunk(event.chunk)
+        .iter()
+        .copied()
+        .filter(|&entity| !world.has::<Player>(entity))
+        .collect();
+    for entity in entities {
+        game.despawn(entity, world);
     }
 }
 
@@ -116,7 +213,11 @@ pub fn save_chunk_at(
         .chunk_handle_at(pos)
         .expect("chunk does not exist");
 
-    if !chunk.write().check_modified() && game.chunk_entities.entities_in_chunk(pos).is_empty() {
+    let block_entities = game.block_entities.in_chunk(pos);
+    if !chunk.write().check_modified()
+        && game.chunk_entities.entities_in_chunk(pos).is_empty()
+        && block_entities.is_empty()
+    {
         return;
     }
 
@@ -141,11 +242,18 @@ pub fn save_chunk_at(
         chunk_worker_handle,
         game.chunk_map.chunk_handle_at(pos).unwrap(),
         entities,
+        block_entities,
     );
 }
 
 #[fecs::event_handler]
 pub fn on_player_leave_save_data(event: &PlayerLeaveEvent, game: &Game, world: &mut World) {
+    add_statistic(
+        world,
+        event.player,
+        Statistic::Custom(CustomStatistic::LeaveGame),
+        1,
+    );
     save_player_data(game, world, event.player);
 }
 
@@ -155,19 +263,30 @@ pub fn save_player_data(game: &Game, world: &World, player: Entity) {
         .items()
         .iter()
         .enumerate()
-        .filter_map(|(i, item)| item.map(|item| (i, item)))
+        .filter_map(|(i, item)| item.as_ref().map(|item| (i, item)))
         .map(|(slot, item)| InventorySlot {
             count: item.amount as i8,
             slot: slot as i8,
             item: item.ty.identifier().to_owned(),
+            nbt: item.nbt().cloned(),
         })
         .collect();
 
-    let data = PlayerData {
+    let mut data = PlayerData {
         entity: BaseEntityData::new(*world.get::<Position>(player), Vec3d::broadcast(0.0)),
         gamemode: world.get::<Gamemode>(player).id() as i32,
         inventory,
+        ..Default::default()
     };
+    data.set_bed_spawn(world.try_get::<BedSpawn>(player).map(|spawn| spawn.0));
+    if let Some(recipe_book) = world.try_get::<RecipeBook>(player) {
+        data.recipe_book = recipe_book.to_data();
+    }
+    if let Some(experience) = world.try_get::<Experience>(player) {
+        data.xp_level = experience.level as i32;
+        data.xp_progress = experience.progress;
+        data.xp_total = experience.total as i32;
+    }
 
     let uuid = *world.get::<Uuid>(player);
     let config = Arc::clone(&game.config);
@@ -184,4 +303,20 @@ pub fn save_player_data(game: &Game, world: &World, player: Entity) {
             Err(e) => log::error!("Failed to save player data for UUID {}: {}", uuid, e),
         }
     });
+
+    if let Some(statistics) = world.try_get::<Statistics>(player) {
+        statistics.save(
+            Path::new(&game.config.world.name),
+            uuid,
+            &game.running_tasks,
+        );
+    }
+
+    if let Some(advancements) = world.try_get::<AdvancementProgress>(player) {
+        advancements.save(
+            Path::new(&game.config.world.name),
+            uuid,
+            &game.running_tasks,
+        );
+    }
 }
@@ -13,10 +13,17 @@ pub struct Config {
     pub io: IO,
     pub proxy: Proxy,
     pub server: Server,
+    pub auto_view_distance: AutoViewDistance,
     pub gameplay: Gameplay,
+    pub entity_limits: EntityLimits,
+    pub despawn: Despawn,
     pub log: Log,
+    pub chat: Chat,
     pub resource_pack: ResourcePack,
     pub world: World,
+    pub block_log: BlockLog,
+    pub query: Query,
+    pub rate_limits: RateLimits,
 }
 
 impl Config {
@@ -56,11 +63,13 @@ impl Default for Config {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct IO {
     pub compression_threshold: i32,
+    pub send_queue_limit: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Proxy {
     pub proxy_mode: ProxyMode,
+    pub proxy_protocol: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -68,10 +77,21 @@ pub struct Server {
     pub online_mode: bool,
     pub motd: String,
     pub max_players: i32,
+    pub hide_player_count: bool,
     pub view_distance: u8,
     pub address: String,
     pub port: u16,
     pub default_gamemode: Gamemode,
+    pub operators: Vec<String>,
+    pub permissions: PermissionsMode,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AutoViewDistance {
+    pub enabled: bool,
+    #[serde(with = "humantime_serde")]
+    pub max_tick_time: Duration,
+    pub min_view_distance: u8,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -80,6 +100,22 @@ pub struct Gameplay {
     pub animal_spawning: bool,
     pub pvp: bool,
     pub nerf_spawner_mobs: bool,
+    pub flying_speed: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EntityLimits {
+    pub mobs: usize,
+    pub items: usize,
+    pub item_frames: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Despawn {
+    #[serde(with = "humantime_serde")]
+    pub items: Duration,
+    #[serde(with = "humantime_serde")]
+    pub arrows: Duration,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -87,10 +123,17 @@ pub struct Log {
     pub level: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Chat {
+    pub format: String,
+    pub log: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ResourcePack {
     pub url: String,
     pub hash: String,
+    pub required: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -102,6 +145,48 @@ pub struct World {
     pub save_interval: Duration,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockLog {
+    pub enabled: bool,
+    pub file: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Query {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RateLimits {
+    pub enabled: bool,
+    pub kick_threshold: u32,
+    pub chat: RateLimit,
+    pub digging: RateLimit,
+    pub window_clicks: RateLimit,
+    pub movement: RateLimit,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Packets per second allowed on average.
+    pub rate: f64,
+    /// Packets which may be sent at once above the rate.
+    pub burst: u32,
+}
+
+/// How players' permissions are decided.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PermissionsMode {
+    /// By operator level alone.
+    #[serde(alias = "operators")]
+    Operators,
+    /// By the groups and nodes in `permissions.json`,
+    /// falling back to operator levels.
+    #[serde(alias = "file")]
+    File,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ProxyMode {
     #[serde(alias = "none")]
@@ -122,28 +207,52 @@ mod tests {
         let config = Config::load(input).expect("invalid default configuration");
         let io = &config.io;
         assert_eq!(io.compression_threshold, 256);
+        assert_eq!(io.send_queue_limit, 8_388_608);
 
         let server = &config.server;
         assert_eq!(server.online_mode, true);
         assert_eq!(server.motd, "A Feather server");
         assert_eq!(server.max_players, 16);
+        assert!(!server.hide_player_count);
         assert_eq!(server.default_gamemode, Gamemode::Creative);
         assert_eq!(server.view_distance, 6);
         assert_eq!(server.address, "0.0.0.0");
         assert_eq!(server.port, 25565);
+        assert!(server.operators.is_empty());
+        assert_eq!(server.permissions, PermissionsMode::Operators);
+
+        let auto_view_distance = &config.auto_view_distance;
+        assert!(!auto_view_distance.enabled);
+        assert_eq!(auto_view_distance.max_tick_time.as_millis(), 40);
+        assert_eq!(auto_view_distance.min_view_distance, 3);
 
         let gameplay = &config.gameplay;
         assert_eq!(gameplay.animal_spawning, true);
         assert_eq!(gameplay.monster_spawning, true);
         assert_eq!(gameplay.pvp, true);
         assert_eq!(gameplay.nerf_spawner_mobs, false);
+        assert!((gameplay.flying_speed - 0.05).abs() < f32::EPSILON);
+
+        let entity_limits = &config.entity_limits;
+        assert_eq!(entity_limits.mobs, 64);
+        assert_eq!(entity_limits.items, 256);
+        assert_eq!(entity_limits.item_frames, 64);
+
+        let despawn = &config.despawn;
+        assert_eq!(despawn.items.as_secs(), 5 * 60);
+        assert_eq!(despawn.arrows.as_secs(), 60);
 
         let log = &config.log;
         assert_eq!(log.level, "debug");
 
+        let chat = &config.chat;
+        assert_eq!(chat.format, "<{prefix}{player}> {message}");
+        assert!(chat.log);
+
         let resource_pack = &config.resource_pack;
         assert_eq!(resource_pack.url, "");
         assert_eq!(resource_pack.hash, "");
+        assert!(!resource_pack.required);
 
         let world = &config.world;
         assert_eq!(world.name, "world");
@@ -151,7 +260,28 @@ mod tests {
         assert_eq!(world.seed, "");
         assert_eq!(world.save_interval.as_millis(), 1000 * 60);
 
+        let block_log = &config.block_log;
+        assert!(!block_log.enabled);
+        assert_eq!(block_log.file, "block-log.dat");
+
         let proxy = &config.proxy;
         assert_eq!(proxy.proxy_mode, ProxyMode::None);
+        assert!(!proxy.proxy_protocol);
+
+        let query = &config.query;
+        assert!(!query.enabled);
+        assert_eq!(query.port, 25565);
+
+        let rate_limits = &config.rate_limits;
+        assert!(rate_limits.enabled);
+        assert_eq!(rate_limits.kick_threshold, 200);
+        assert_eq!(
+            rate_limits.chat,
+            RateLimit {
+                rate: 2.0,
+                burst: 10
+            }
+        );
+        assert_eq!(rate_limits.movement.burst, 100);
     }
 }
@@ -0,0 +1,53 @@
+#[macro_use]
+extern crate criterion;
+
+use criterion::Criterion;
+use feather_core::position;
+use feather_core::util::Position;
+use feather_server_entity::{broadcast_movement, on_entity_send_update_last_known_positions};
+use feather_server_types::EntitySendEvent;
+use feather_test_framework::Test;
+use fecs::Entity;
+
+/// Number of moving entities broadcast to each player.
+const ENTITIES: usize = 4096;
+const PLAYERS: usize = 4;
+
+fn movement(c: &mut Criterion) {
+    let mut test = Test::new();
+    let players: Vec<Entity> = (0..PLAYERS)
+        .map(|_| test.player("", position!(0.0, 64.0, 0.0)))
+        .collect();
+
+    let entities: Vec<Entity> = (0..ENTITIES)
+        .map(|i| {
+            let pos = position!((i % 64) as f64, 64.0, (i / 64) as f64);
+            let entity = test.entity(feather_server_entity::base().with(pos));
+            for &client in &players {
+                test.handle(
+                    EntitySendEvent { entity, client },
+                    on_entity_send_update_last_known_positions,
+                );
+            }
+            entity
+        })
+        .collect();
+
+    let mut offset = 0.5;
+    c.bench_function("broadcast_movement_4096_entities", |b| {
+        b.iter(|| {
+            offset = -offset;
+            for &entity in &entities {
+                test.world.get_mut::<Position>(entity).y += offset;
+            }
+
+            test.run(broadcast_movement);
+            for &player in &players {
+                test.clear_sent(player);
+            }
+        });
+    });
+}
+
+criterion_group!(benches, movement);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+//! Removal of block entities whose blocks are replaced.
+
+use crate::object::item::drop_block_item;
+use feather_server_types::{BlockUpdateEvent, Game};
+use fecs::World;
+
+/// Removes the block entity of a block which was replaced by another
+/// kind of block. The items of containers are dropped where they were.
+#[fecs::event_handler]
+pub fn on_block_update_remove_block_entity(
+    event: &BlockUpdateEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    if event.old.kind() == event.new.kind() {
+        return;
+    }
+
+    let items = match game.block_entities.remove(event.pos) {
+        Some(block_entity) => block_entity
+            .container()
+            .map(|container| container.items.clone())
+            .unwrap_or_default(),
+        None => return,
+    };
+    for item in items {
+        drop_block_item(game, world, event.pos, item.to_stack());
+    }
+}
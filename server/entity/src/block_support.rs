@@ -0,0 +1,51 @@
+//! Breaking of blocks which have lost their support,
+//! such as a torch whose wall was removed.
+
+use crate::item;
+use feather_core::blocks::BlockId;
+use feather_server_types::{BumpVec, Game, DO_TILE_DROPS};
+use feather_server_util::{
+    block_drops, is_supported, BlockNotifyCause, BlockNotifyPosition, BlockNotifySupport,
+};
+use fecs::{component, IntoQuery, Read, World};
+
+/// System which breaks blocks that are no longer supported
+/// by their neighbors, dropping their items.
+///
+/// Breaking a block notifies its neighbors in turn, so chains
+/// of unsupported blocks (e.g. a torch on top of a rail)
+/// are broken on successive ticks.
+#[fecs::system]
+pub fn break_unsupported_blocks(game: &mut Game, world: &mut World) {
+    let mut notifies = BumpVec::new_in(game.bump());
+    notifies.extend(
+        <(Read<BlockNotifyPosition>, Read<BlockNotifyCause>)>::query()
+            .filter(component::<BlockNotifySupport>())
+            .iter_entities(world.inner())
+            .map(|(entity, (pos, cause))| (entity, pos.0, cause.0)),
+    );
+
+    for (notify, pos, cause) in notifies {
+        world.despawn(notify);
+
+        // The block may have changed since the notify entity
+        // was created, so use its current state.
+        let block = match game.block_at(pos) {
+            Some(block) => block,
+            None => continue,
+        };
+        if is_supported(block, pos, |pos| game.block_at(pos)) {
+            continue;
+        }
+
+        game.set_block_at_with_cause(world, pos, BlockId::air(), cause.neighbor_of());
+
+        if !game.game_rules.bool(DO_TILE_DROPS) {
+            continue;
+        }
+        let drops = block_drops(block, None, &mut *game.rng());
+        for stack in drops {
+            item::drop_block_item(game, world, pos, stack);
+        }
+    }
+}
@@ -114,6 +114,7 @@ mod tests {
     use super::*;
     use feather_core::inventory::{
         SLOT_ARMOR_CHEST, SLOT_ARMOR_FEET, SLOT_ARMOR_HEAD, SLOT_ARMOR_LEGS, SLOT_INVENTORY_OFFSET,
+        SLOT_OFFHAND,
     };
     use feather_core::items::{Item, ItemStack};
     use feather_test_framework::Test;
@@ -132,7 +133,7 @@ mod tests {
         test.world.get_mut::<HeldItem>(player1).0 = 2;
         test.world
             .get_mut::<Inventory>(player1)
-            .set_item_at(slot, stack);
+            .set_item_at(slot, stack.clone());
 
         test.handle(
             InventoryUpdateEvent {
@@ -154,7 +155,7 @@ mod tests {
         test.world.get_mut::<HeldItem>(player3).0 = 2;
         test.world
             .get_mut::<Inventory>(player3)
-            .set_item_at(slot, stack);
+            .set_item_at(slot, stack.clone());
 
         test.handle(
             InventoryUpdateEvent {
@@ -179,7 +180,7 @@ mod tests {
             |test, player1, player2| {
                 test.world
                     .get_mut::<Inventory>(player1)
-                    .set_item_at(slot, stack);
+                    .set_item_at(slot, stack.clone());
                 EntitySendEvent {
                     entity: player1,
                     client: player2,
@@ -206,7 +207,7 @@ mod tests {
 
         test.world
             .get_mut::<Inventory>(player1)
-            .set_item_at(slot, stack);
+            .set_item_at(slot, stack.clone());
 
         test.handle(
             InventoryUpdateEvent {
@@ -233,6 +234,7 @@ mod tests {
             (0, SLOT_ARMOR_FEET, Ok(Equipment::Boots)),
             (0, SLOT_ARMOR_LEGS, Ok(Equipment::Leggings)),
             (0, SLOT_ARMOR_HEAD, Ok(Equipment::Helmet)),
+            (3, SLOT_OFFHAND, Ok(Equipment::OffHand)),
             (0, 10000, Err(())),
         ];
 
@@ -2,8 +2,8 @@
 
 use feather_core::entitymeta::EntityMetadata;
 use feather_core::network::packets::PacketEntityMetadata;
-use feather_server_types::{EntityId, EntitySendEvent, Network};
-use fecs::World;
+use feather_server_types::{BumpVec, EntityId, EntitySendEvent, Game, Network};
+use fecs::{IntoQuery, World, Write};
 
 /// System which sends entity metadata when an entity
 /// is sent to a player.
@@ -21,11 +21,39 @@ pub fn on_entity_send_send_metadata(event: &EntitySendEvent, world: &mut World)
     }
 }
 
+/// System which broadcasts changed metadata values
+/// to the clients able to see each entity.
+#[fecs::system]
+pub fn broadcast_metadata(game: &mut Game, world: &mut World) {
+    let mut changes = BumpVec::new_in(game.bump());
+
+    for (entity, mut metadata) in
+        <Write<EntityMetadata>>::query().iter_entities_mut(world.inner_mut())
+    {
+        if let Some(changed) = metadata.take_dirty() {
+            changes.push((entity, changed));
+        }
+    }
+
+    for (entity, metadata) in changes {
+        let entity_id = world.get::<EntityId>(entity).0;
+        game.broadcast_entity_update(
+            world,
+            PacketEntityMetadata {
+                entity_id,
+                metadata,
+            },
+            entity,
+            None,
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::item;
-    use feather_core::entitymeta::{MetaEntry, META_INDEX_ITEM_SLOT};
+    use feather_core::entitymeta::{MetaEntry, META_INDEX_IS_SILENT, META_INDEX_ITEM_SLOT};
     use feather_core::items::{Item, ItemStack};
     use feather_core::util::Position;
     use feather_test_framework::Test;
@@ -38,7 +66,8 @@ mod tests {
         let player2 = test.player("", position!(0.0, 100.0, 0.0));
 
         let stack = ItemStack::new(Item::String, 4);
-        let item = test.entity(item::create(stack, Default::default()).with(Position::default()));
+        let item =
+            test.entity(item::create(stack.clone(), Default::default()).with(Position::default()));
 
         test.handle(
             EntitySendEvent {
@@ -57,4 +86,28 @@ mod tests {
 
         assert!(test.sent::<PacketEntityMetadata>(player2).is_none());
     }
+
+    #[test]
+    fn broadcast_changed_metadata() {
+        let mut test = Test::new();
+
+        let player = test.player("", position!(0.0, 64.0, 0.0));
+        let stack = ItemStack::new(Item::String, 4);
+        let item = test.entity(item::create(stack, Default::default()).with(Position::default()));
+
+        test.run(broadcast_metadata);
+        assert!(test.sent::<PacketEntityMetadata>(player).is_none());
+
+        test.world.get_mut::<EntityMetadata>(item).set_silent(true);
+        test.run(broadcast_metadata);
+
+        let packet = test.sent::<PacketEntityMetadata>(player).unwrap();
+        assert_eq!(packet.entity_id, test.id(item));
+        let indices: Vec<_> = packet.metadata.iter().map(|(index, _)| index).collect();
+        assert_eq!(indices, vec![META_INDEX_IS_SILENT]);
+
+        // Values are only sent once.
+        test.run(broadcast_metadata);
+        assert!(test.sent::<PacketEntityMetadata>(player).is_none());
+    }
 }
@@ -0,0 +1,194 @@
+//! Despawning of items and arrows once they have existed
+//! for longer than the times set in the config, and of
+//! hostile mobs on peaceful difficulty.
+
+use crate::arrow::ArrowProperties;
+use crate::item::CollectableAt;
+use crate::Hostile;
+use feather_core::util::Difficulty;
+use feather_server_types::{BumpVec, Game, InGround, TICK_LENGTH, TPS};
+use fecs::{component, IntoQuery, Read, World, Write};
+use std::time::Duration;
+
+/// Interval in ticks at which entities are aged.
+const AGE_INTERVAL: u64 = TPS;
+
+/// Component storing how many ticks an item or arrow has aged.
+/// Arrows only age while they are stuck in the ground.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Age {
+    pub ticks: u64,
+    /// Whether the entity never despawns.
+    pub infinite: bool,
+}
+
+impl Age {
+    /// The `Age` or `life` tag value which marks an entity
+    /// as never despawning.
+    const NBT_INFINITE: i16 = i16::min_value();
+
+    /// Returns an age which never despawns.
+    pub fn infinite() -> Self {
+        Self {
+            ticks: 0,
+            infinite: true,
+        }
+    }
+
+    pub fn from_nbt(age: i16) -> Self {
+        if age == Self::NBT_INFINITE {
+            Self::infinite()
+        } else {
+            Self {
+                ticks: age.max(0) as u64,
+                infinite: false,
+            }
+        }
+    }
+
+    pub fn to_nbt(self) -> i16 {
+        if self.infinite {
+            Self::NBT_INFINITE
+        } else {
+            self.ticks.min(i16::max_value() as u64) as i16
+        }
+    }
+}
+
+/// System which ages items and arrows and
+/// despawns those which have become too old.
+#[fecs::system]
+pub fn despawn_old_entities(game: &mut Game, world: &mut World) {
+    if game.tick_count % AGE_INTERVAL != 0 {
+        return;
+    }
+
+    let item_ticks = despawn_ticks(game.config.despawn.items);
+    let arrow_ticks = despawn_ticks(game.config.despawn.arrows);
+
+    let mut despawned = BumpVec::new_in(game.bump());
+
+    for (item, mut age) in <Write<Age>>::query()
+        .filter(component::<CollectableAt>())
+        .iter_entities_mut(world.inner_mut())
+    {
+        if advance(&mut age, item_ticks) {
+            despawned.push(item);
+        }
+    }
+
+    for (arrow, mut age) in <Write<Age>>::query()
+        .filter(component::<ArrowProperties>() & component::<InGround>())
+        .iter_entities_mut(world.inner_mut())
+    {
+        if advance(&mut age, arrow_ticks) {
+            despawned.push(arrow);
+        }
+    }
+
+    // Arrows start aging again each time they land.
+    for mut age in <Write<Age>>::query()
+        .filter(component::<ArrowProperties>() & !component::<InGround>())
+        .iter_mut(world.inner_mut())
+    {
+        age.ticks = 0;
+    }
+
+    for entity in despawned {
+        game.despawn(entity, world);
+    }
+}
+
+/// System which despawns hostile mobs while the
+/// difficulty is peaceful.
+#[fecs::system]
+pub fn despawn_hostiles_on_peaceful(game: &mut Game, world: &mut World) {
+    if game.difficulty() != Difficulty::Peaceful {
+        return;
+    }
+
+    let mut despawned = BumpVec::new_in(game.bump());
+    for (mob, _) in <Read<Hostile>>::query().iter_entities(world.inner()) {
+        despawned.push(mob);
+    }
+
+    for entity in despawned {
+        game.despawn(entity, world);
+    }
+}
+
+/// Ages an entity by one interval. Returns whether it
+/// should despawn.
+fn advance(age: &mut Age, despawn_ticks: Option<u64>) -> bool {
+    if age.infinite {
+        return false;
+    }
+
+    age.ticks += AGE_INTERVAL;
+    despawn_ticks.map_or(false, |ticks| age.ticks >= ticks)
+}
+
+/// Converts a despawn time from the config to ticks,
+/// returning `None` if entities should never despawn.
+fn despawn_ticks(time: Duration) -> Option<u64> {
+    match time.as_millis() as u64 / TICK_LENGTH {
+        0 => None,
+        ticks => Some(ticks),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cow, item, zombie};
+    use feather_core::items::{Item, ItemStack};
+    use feather_test_framework::Test;
+
+    #[test]
+    fn age_nbt_round_trip() {
+        assert_eq!(Age::from_nbt(Age::infinite().to_nbt()), Age::infinite());
+        assert_eq!(Age::from_nbt(1200).ticks, 1200);
+        assert_eq!(Age::from_nbt(-5).ticks, 0);
+    }
+
+    #[test]
+    fn items_despawn_when_old() {
+        let mut test = Test::new();
+        let stack = ItemStack::new(Item::Stone, 1);
+        let old = test.entity(
+            item::create(stack.clone(), 0)
+                .with(position!(0.0, 64.0, 0.0))
+                .with(Age {
+                    ticks: 6000,
+                    infinite: false,
+                }),
+        );
+        let new = test.entity(item::create(stack.clone(), 0).with(position!(5.0, 64.0, 0.0)));
+        let infinite = test.entity(
+            item::create(stack, 0)
+                .with(position!(10.0, 64.0, 0.0))
+                .with(Age::infinite()),
+        );
+
+        test.run(despawn_old_entities);
+
+        test.assert_dead(old);
+        test.assert_alive(new).assert_alive(infinite);
+        assert_eq!(test.world.get::<Age>(new).ticks, AGE_INTERVAL);
+    }
+
+    #[test]
+    fn hostiles_despawn_on_peaceful() {
+        let mut test = Test::new();
+        let zombie = test.entity(zombie::create().with(position!(0.0, 64.0, 0.0)));
+        let cow = test.entity(cow::create().with(position!(5.0, 64.0, 0.0)));
+
+        test.game.level.difficulty = Difficulty::Easy.id() as i8;
+        test.run(despawn_hostiles_on_peaceful);
+        test.assert_alive(zombie);
+
+        test.game.level.difficulty = Difficulty::Peaceful.id() as i8;
+        test.run(despawn_hostiles_on_peaceful);
+        test.assert_dead(zombie).assert_alive(cow);
+    }
+}
@@ -6,17 +6,38 @@
 #[macro_use]
 extern crate feather_core;
 
+mod block_entity;
+mod block_support;
 mod broadcasters;
+mod despawn;
 mod inventory;
 mod mob;
 mod object;
+mod summon;
 
+pub use block_entity::on_block_update_remove_block_entity;
+pub use block_support::break_unsupported_blocks;
 pub use broadcasters::*;
+pub use despawn::*;
 pub use mob::*;
 pub use object::*;
 
+pub use object::arrow::{arrow_pickup, on_projectile_hit_handle_arrow};
+pub use object::egg::on_projectile_hit_handle_egg;
+pub use object::ender_pearl::{
+    on_player_leave_remove_ender_pearls, on_projectile_hit_handle_ender_pearl,
+};
 pub use object::falling_block::{on_entity_land_remove_falling_block, spawn_falling_blocks};
-pub use object::item::{item_collect, on_item_drop_spawn_item_entity};
+pub use object::firework_rocket::update_firework_rockets;
+pub use object::item::{
+    block_destroy_request, death_drops_request, item_collect, item_merge,
+    on_item_drop_spawn_item_entity,
+};
+pub use object::lightning_bolt::{despawn_lightning_bolts, lightning_strike_request};
+pub use object::primed_tnt::{tnt_prime_request, update_primed_tnt};
+pub use object::snowball::on_projectile_hit_handle_snowball;
+pub use object::trident::{on_projectile_hit_handle_trident, update_tridents};
+pub use summon::summon_request;
 
 extern crate nalgebra_glm as glm;
 
@@ -1,5 +1,48 @@
 //! Components and functionality shared across all mobs.
 
+/// Implements saving and loading for a mob type
+/// which has no type-specific data.
+///
+/// Defines `serialize` and `load` functions and registers
+/// the loader. The mob's `create` function should insert
+/// `ComponentSerializer(&serialize)`.
+macro_rules! persistent_mob {
+    ($marker:ident, $kind:ident, $data:ident) => {
+        inventory::submit! {
+            feather_server_types::EntityLoaderRegistration::new(
+                feather_core::anvil::entity::EntityDataKind::$data,
+                &load,
+            )
+        }
+
+        fn serialize(
+            _game: &feather_server_types::Game,
+            accessor: &fecs::EntityRef,
+        ) -> feather_core::anvil::entity::EntityData {
+            feather_core::anvil::entity::EntityData::$data(crate::mob::serialize(accessor))
+        }
+
+        fn load(
+            data: feather_core::anvil::entity::EntityData,
+        ) -> anyhow::Result<fecs::EntityBuilder> {
+            match data {
+                feather_core::anvil::entity::EntityData::$data(data) => Ok(crate::mob::load(
+                    crate::MobKind::$kind,
+                    &data,
+                    feather_core::entitymeta::EntityMetadata::entity_base(),
+                )?
+                .with($marker)
+                .with(feather_server_types::ComponentSerializer(&serialize))),
+                _ => panic!(concat!(
+                    "attempted to use ",
+                    stringify!($marker),
+                    " loader to load a different entity"
+                )),
+            }
+        }
+    };
+}
+
 mod boss;
 mod defensive;
 mod hostile;
@@ -8,17 +51,32 @@ mod passive;
 
 pub use boss::*;
 pub use defensive::*;
-use feather_core::entitymeta::EntityMetadata;
+use feather_core::anvil::entity::{BaseEntityData, MobData};
+use feather_core::entitymeta::{EntityMetadata, META_INDEX_MOB_FLAGS};
+use feather_core::items::{EntityTagNbt, Item};
 use feather_core::network::packets::SpawnMob;
 use feather_core::network::Packet;
-use feather_core::util::Position;
-use feather_server_types::{EntityId, SpawnPacketCreator, Uuid, Velocity};
+use feather_core::util::{Position, Vec3d};
+use feather_server_types::{
+    CreatureAttribute, CustomName, EntityCategory, EntityId, EntityType, ExperienceReward, Health,
+    Living, LootEntry, MobLoot, PhysicsBuilder, SpawnPacketCreator, Uuid, Velocity,
+};
 use feather_server_util::{degrees_to_stops, protocol_velocity};
 use fecs::{EntityBuilder, EntityRef};
 pub use hostile::*;
 pub use neutral::*;
 pub use passive::*;
 
+/// Marker component for mobs whose AI is disabled, as
+/// set by the `NoAI` tag. Such mobs do not act on their own.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NoAi;
+
+/// Marker component for hostile mobs, which
+/// despawn on peaceful difficulty.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Hostile;
+
 /// Enumeration of mob types. Note that this enum should not be
 /// used in queries to identify mobs of a given type.
 ///
@@ -88,10 +146,602 @@ pub enum MobKind {
     Phantom = 90,
 }
 
+impl MobKind {
+    /// Returns the namespaced identifier of this mob type.
+    pub fn identifier(self) -> &'static str {
+        match self {
+            MobKind::Bat => "minecraft:bat",
+            MobKind::Blaze => "minecraft:blaze",
+            MobKind::CaveSpider => "minecraft:cave_spider",
+            MobKind::Chicken => "minecraft:chicken",
+            MobKind::Cod => "minecraft:cod",
+            MobKind::Cow => "minecraft:cow",
+            MobKind::Creeper => "minecraft:creeper",
+            MobKind::Donkey => "minecraft:donkey",
+            MobKind::Dolphin => "minecraft:dolphin",
+            MobKind::Drowned => "minecraft:drowned",
+            MobKind::ElderGuardian => "minecraft:elder_guardian",
+            MobKind::EnderDragon => "minecraft:ender_dragon",
+            MobKind::Enderman => "minecraft:enderman",
+            MobKind::Endermite => "minecraft:endermite",
+            MobKind::EvocationIllager => "minecraft:evoker",
+            MobKind::Ghast => "minecraft:ghast",
+            MobKind::Giant => "minecraft:giant",
+            MobKind::Guardian => "minecraft:guardian",
+            MobKind::Horse => "minecraft:horse",
+            MobKind::Husk => "minecraft:husk",
+            MobKind::IllusionIllager => "minecraft:illusioner",
+            MobKind::Llama => "minecraft:llama",
+            MobKind::MagmaCube => "minecraft:magma_cube",
+            MobKind::Mule => "minecraft:mule",
+            MobKind::MushroomCow => "minecraft:mooshroom",
+            MobKind::Ocelot => "minecraft:ocelot",
+            MobKind::Parrot => "minecraft:parrot",
+            MobKind::Pig => "minecraft:pig",
+            MobKind::Pufferfish => "minecraft:pufferfish",
+            MobKind::PigZombie => "minecraft:zombie_pigman",
+            MobKind::PolarBear => "minecraft:polar_bear",
+            MobKind::Rabbit => "minecraft:rabbit",
+            MobKind::Salmon => "minecraft:salmon",
+            MobKind::Sheep => "minecraft:sheep",
+            MobKind::Shulker => "minecraft:shulker",
+            MobKind::Silverfish => "minecraft:silverfish",
+            MobKind::Skeleton => "minecraft:skeleton",
+            MobKind::SkeletonHorse => "minecraft:skeleton_horse",
+            MobKind::Slime => "minecraft:slime",
+            MobKind::SnowGolem => "minecraft:snow_golem",
+            MobKind::Spider => "minecraft:spider",
+            MobKind::Squid => "minecraft:squid",
+            MobKind::Stray => "minecraft:stray",
+            MobKind::TropicalFish => "minecraft:tropical_fish",
+            MobKind::Turtle => "minecraft:turtle",
+            MobKind::Vex => "minecraft:vex",
+            MobKind::Villager => "minecraft:villager",
+            MobKind::IronGolem => "minecraft:iron_golem",
+            MobKind::VindicationIllager => "minecraft:vindicator",
+            MobKind::Witch => "minecraft:witch",
+            MobKind::Wither => "minecraft:wither",
+            MobKind::WitherSkeleton => "minecraft:wither_skeleton",
+            MobKind::Wolf => "minecraft:wolf",
+            MobKind::Zombie => "minecraft:zombie",
+            MobKind::ZombieHorse => "minecraft:zombie_horse",
+            MobKind::ZombieVillager => "minecraft:zombie_villager",
+            MobKind::Phantom => "minecraft:phantom",
+        }
+    }
+
+    /// Returns the mob type with the given namespaced
+    /// identifier, or `None` if there is no such mob.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        Some(match identifier {
+            "minecraft:bat" => MobKind::Bat,
+            "minecraft:blaze" => MobKind::Blaze,
+            "minecraft:cave_spider" => MobKind::CaveSpider,
+            "minecraft:chicken" => MobKind::Chicken,
+            "minecraft:cod" => MobKind::Cod,
+            "minecraft:cow" => MobKind::Cow,
+            "minecraft:creeper" => MobKind::Creeper,
+            "minecraft:donkey" => MobKind::Donkey,
+            "minecraft:dolphin" => MobKind::Dolphin,
+            "minecraft:drowned" => MobKind::Drowned,
+            "minecraft:elder_guardian" => MobKind::ElderGuardian,
+            "minecraft:ender_dragon" => MobKind::EnderDragon,
+            "minecraft:enderman" => MobKind::Enderman,
+            "minecraft:endermite" => MobKind::Endermite,
+            "minecraft:evoker" => MobKind::EvocationIllager,
+            "minecraft:ghast" => MobKind::Ghast,
+            "minecraft:giant" => MobKind::Giant,
+            "minecraft:guardian" => MobKind::Guardian,
+            "minecraft:horse" => MobKind::Horse,
+            "minecraft:husk" => MobKind::Husk,
+            "minecraft:illusioner" => MobKind::IllusionIllager,
+            "minecraft:llama" => MobKind::Llama,
+            "minecraft:magma_cube" => MobKind::MagmaCube,
+            "minecraft:mule" => MobKind::Mule,
+            "minecraft:mooshroom" => MobKind::MushroomCow,
+            "minecraft:ocelot" => MobKind::Ocelot,
+            "minecraft:parrot" => MobKind::Parrot,
+            "minecraft:pig" => MobKind::Pig,
+            "minecraft:pufferfish" => MobKind::Pufferfish,
+            "minecraft:zombie_pigman" => MobKind::PigZombie,
+            "minecraft:polar_bear" => MobKind::PolarBear,
+            "minecraft:rabbit" => MobKind::Rabbit,
+            "minecraft:salmon" => MobKind::Salmon,
+            "minecraft:sheep" => MobKind::Sheep,
+            "minecraft:shulker" => MobKind::Shulker,
+            "minecraft:silverfish" => MobKind::Silverfish,
+            "minecraft:skeleton" => MobKind::Skeleton,
+            "minecraft:skeleton_horse" => MobKind::SkeletonHorse,
+            "minecraft:slime" => MobKind::Slime,
+            "minecraft:snow_golem" => MobKind::SnowGolem,
+            "minecraft:spider" => MobKind::Spider,
+            "minecraft:squid" => MobKind::Squid,
+            "minecraft:stray" => MobKind::Stray,
+            "minecraft:tropical_fish" => MobKind::TropicalFish,
+            "minecraft:turtle" => MobKind::Turtle,
+            "minecraft:vex" => MobKind::Vex,
+            "minecraft:villager" => MobKind::Villager,
+            "minecraft:iron_golem" => MobKind::IronGolem,
+            "minecraft:vindicator" => MobKind::VindicationIllager,
+            "minecraft:witch" => MobKind::Witch,
+            "minecraft:wither" => MobKind::Wither,
+            "minecraft:wither_skeleton" => MobKind::WitherSkeleton,
+            "minecraft:wolf" => MobKind::Wolf,
+            "minecraft:zombie" => MobKind::Zombie,
+            "minecraft:zombie_horse" => MobKind::ZombieHorse,
+            "minecraft:zombie_villager" => MobKind::ZombieVillager,
+            "minecraft:phantom" => MobKind::Phantom,
+            _ => return None,
+        })
+    }
+
+    /// Returns the width and height of this mob's bounding box.
+    ///
+    /// For slimes and magma cubes, this is the size of a small
+    /// (size 2) slime.
+    pub fn size(self) -> (f64, f64) {
+        match self {
+            MobKind::Bat => (0.5, 0.9),
+            MobKind::Blaze => (0.6, 1.8),
+            MobKind::CaveSpider => (0.7, 0.5),
+            MobKind::Chicken => (0.4, 0.7),
+            MobKind::Cod => (0.5, 0.3),
+            MobKind::Cow => (0.9, 1.4),
+            MobKind::Creeper => (0.6, 1.7),
+            MobKind::Donkey => (1.3965, 1.5),
+            MobKind::Dolphin => (0.9, 0.6),
+            MobKind::Drowned => (0.6, 1.95),
+            MobKind::ElderGuardian => (1.9975, 1.9975),
+            MobKind::EnderDragon => (16.0, 8.0),
+            MobKind::Enderman => (0.6, 2.9),
+            MobKind::Endermite => (0.4, 0.3),
+            MobKind::EvocationIllager => (0.6, 1.95),
+            MobKind::Ghast => (4.0, 4.0),
+            MobKind::Giant => (3.6, 12.0),
+            MobKind::Guardian => (0.85, 0.85),
+            MobKind::Horse => (1.3965, 1.6),
+            MobKind::Husk => (0.6, 1.95),
+            MobKind::IllusionIllager => (0.6, 1.95),
+            MobKind::Llama => (0.9, 1.87),
+            MobKind::MagmaCube => (1.02, 1.02),
+            MobKind::Mule => (1.3965, 1.6),
+            MobKind::MushroomCow => (0.9, 1.4),
+            MobKind::Ocelot => (0.6, 0.7),
+            MobKind::Parrot => (0.5, 0.9),
+            MobKind::Pig => (0.9, 0.9),
+            MobKind::Pufferfish => (0.7, 0.7),
+            MobKind::PigZombie => (0.6, 1.95),
+            MobKind::PolarBear => (1.4, 1.4),
+            MobKind::Rabbit => (0.4, 0.5),
+            MobKind::Salmon => (0.7, 0.4),
+            MobKind::Sheep => (0.9, 1.3),
+            MobKind::Shulker => (1.0, 1.0),
+            MobKind::Silverfish => (0.4, 0.3),
+            MobKind::Skeleton => (0.6, 1.99),
+            MobKind::SkeletonHorse => (1.3965, 1.6),
+            MobKind::Slime => (1.02, 1.02),
+            MobKind::SnowGolem => (0.7, 1.9),
+            MobKind::Spider => (1.4, 0.9),
+            MobKind::Squid => (0.8, 0.8),
+            MobKind::Stray => (0.6, 1.99),
+            MobKind::TropicalFish => (0.5, 0.4),
+            MobKind::Turtle => (1.2, 0.4),
+            MobKind::Vex => (0.4, 0.8),
+            MobKind::Villager => (0.6, 1.95),
+            MobKind::IronGolem => (1.4, 2.7),
+            MobKind::VindicationIllager => (0.6, 1.95),
+            MobKind::Witch => (0.6, 1.95),
+            MobKind::Wither => (0.9, 3.5),
+            MobKind::WitherSkeleton => (0.7, 2.4),
+            MobKind::Wolf => (0.6, 0.85),
+            MobKind::Zombie => (0.6, 1.95),
+            MobKind::ZombieHorse => (1.3965, 1.6),
+            MobKind::ZombieVillager => (0.6, 1.95),
+            MobKind::Phantom => (0.9, 0.5),
+        }
+    }
+
+    /// Returns the health with which this mob spawns.
+    ///
+    /// Horses, donkeys, mules, and llamas have random
+    /// maximum health in vanilla; this returns a typical value.
+    pub fn max_health(self) -> f32 {
+        match self {
+            MobKind::Bat => 6.0,
+            MobKind::Blaze => 20.0,
+            MobKind::CaveSpider => 12.0,
+            MobKind::Chicken => 4.0,
+            MobKind::Cod => 3.0,
+            MobKind::Cow => 10.0,
+            MobKind::Creeper => 20.0,
+            MobKind::Donkey => 20.0,
+            MobKind::Dolphin => 10.0,
+            MobKind::Drowned => 20.0,
+            MobKind::ElderGuardian => 80.0,
+            MobKind::EnderDragon => 200.0,
+            MobKind::Enderman => 40.0,
+            MobKind::Endermite => 8.0,
+            MobKind::EvocationIllager => 24.0,
+            MobKind::Ghast => 10.0,
+            MobKind::Giant => 100.0,
+            MobKind::Guardian => 30.0,
+            MobKind::Horse => 20.0,
+            MobKind::Husk => 20.0,
+            MobKind::IllusionIllager => 32.0,
+            MobKind::Llama => 20.0,
+            MobKind::MagmaCube => 4.0,
+            MobKind::Mule => 20.0,
+            MobKind::MushroomCow => 10.0,
+            MobKind::Ocelot => 10.0,
+            MobKind::Parrot => 6.0,
+            MobKind::Pig => 10.0,
+            MobKind::Pufferfish => 3.0,
+            MobKind::PigZombie => 20.0,
+            MobKind::PolarBear => 30.0,
+            MobKind::Rabbit => 3.0,
+            MobKind::Salmon => 3.0,
+            MobKind::Sheep => 8.0,
+            MobKind::Shulker => 30.0,
+            MobKind::Silverfish => 8.0,
+            MobKind::Skeleton => 20.0,
+            MobKind::SkeletonHorse => 15.0,
+            MobKind::Slime => 4.0,
+            MobKind::SnowGolem => 4.0,
+            MobKind::Spider => 16.0,
+            MobKind::Squid => 10.0,
+            MobKind::Stray => 20.0,
+            MobKind::TropicalFish => 3.0,
+            MobKind::Turtle => 30.0,
+            MobKind::Vex => 14.0,
+            MobKind::Villager => 20.0,
+            MobKind::IronGolem => 100.0,
+            MobKind::VindicationIllager => 24.0,
+            MobKind::Witch => 26.0,
+            MobKind::Wither => 300.0,
+            MobKind::WitherSkeleton => 20.0,
+            MobKind::Wolf => 8.0,
+            MobKind::Zombie => 20.0,
+            MobKind::ZombieHorse => 15.0,
+            MobKind::ZombieVillager => 20.0,
+            MobKind::Phantom => 20.0,
+        }
+    }
+
+    /// Returns the creature attribute of this mob, which
+    /// determines whether Smite or Bane of Arthropods
+    /// deal extra damage to it.
+    pub fn creature_attribute(self) -> Option<CreatureAttribute> {
+        match self {
+            MobKind::Drowned
+            | MobKind::Giant
+            | MobKind::Husk
+            | MobKind::Phantom
+            | MobKind::PigZombie
+            | MobKind::Skeleton
+            | MobKind::SkeletonHorse
+            | MobKind::Stray
+            | MobKind::Wither
+            | MobKind::WitherSkeleton
+            | MobKind::Zombie
+            | MobKind::ZombieHorse
+            | MobKind::ZombieVillager => Some(CreatureAttribute::Undead),
+            MobKind::CaveSpider | MobKind::Endermite | MobKind::Silverfish | MobKind::Spider => {
+                Some(CreatureAttribute::Arthropod)
+            }
+            MobKind::Cod
+            | MobKind::Dolphin
+            | MobKind::ElderGuardian
+            | MobKind::Guardian
+            | MobKind::Pufferfish
+            | MobKind::Salmon
+            | MobKind::Squid
+            | MobKind::TropicalFish
+            | MobKind::Turtle => Some(CreatureAttribute::Aquatic),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this mob is hostile, and so cannot
+    /// exist on peaceful difficulty. Neutral mobs which only
+    /// despawn on peaceful, like endermen, are included.
+    pub fn is_hostile(self) -> bool {
+        match self {
+            MobKind::Blaze
+            | MobKind::CaveSpider
+            | MobKind::Creeper
+            | MobKind::Drowned
+            | MobKind::ElderGuardian
+            | MobKind::Enderman
+            | MobKind::Endermite
+            | MobKind::EvocationIllager
+            | MobKind::Ghast
+            | MobKind::Giant
+            | MobKind::Guardian
+            | MobKind::Husk
+            | MobKind::IllusionIllager
+            | MobKind::MagmaCube
+            | MobKind::Phantom
+            | MobKind::PigZombie
+            | MobKind::Shulker
+            | MobKind::Silverfish
+            | MobKind::Skeleton
+            | MobKind::Slime
+            | MobKind::Spider
+            | MobKind::Stray
+            | MobKind::Vex
+            | MobKind::VindicationIllager
+            | MobKind::Witch
+            | MobKind::Wither
+            | MobKind::WitherSkeleton
+            | MobKind::Zombie
+            | MobKind::ZombieVillager => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the experience this mob drops when killed by a player.
+    ///
+    /// Vanilla gives hostile mobs bonus experience for the
+    /// equipment they wear; this is not included.
+    pub fn experience_reward(self) -> ExperienceReward {
+        match self {
+            MobKind::Chicken
+            | MobKind::Cod
+            | MobKind::Cow
+            | MobKind::Donkey
+            | MobKind::Dolphin
+            | MobKind::Horse
+            | MobKind::Llama
+            | MobKind::Mule
+            | MobKind::MushroomCow
+            | MobKind::Ocelot
+            | MobKind::Parrot
+            | MobKind::Pig
+            | MobKind::Pufferfish
+            | MobKind::PolarBear
+            | MobKind::Rabbit
+            | MobKind::Salmon
+            | MobKind::Sheep
+            | MobKind::SkeletonHorse
+            | MobKind::Squid
+            | MobKind::TropicalFish
+            | MobKind::Turtle
+            | MobKind::Wolf
+            | MobKind::ZombieHorse => ExperienceReward { min: 1, max: 3 },
+            MobKind::Bat | MobKind::IronGolem | MobKind::SnowGolem | MobKind::Villager => {
+                ExperienceReward::fixed(0)
+            }
+            // Slimes drop as much experience as their size.
+            MobKind::Slime | MobKind::MagmaCube => ExperienceReward::fixed(2),
+            MobKind::Endermite | MobKind::Vex => ExperienceReward::fixed(3),
+            MobKind::Blaze
+            | MobKind::ElderGuardian
+            | MobKind::EvocationIllager
+            | MobKind::Guardian => ExperienceReward::fixed(10),
+            MobKind::Wither => ExperienceReward::fixed(50),
+            // The dragon drops 12000 experience the first time it is killed.
+            MobKind::EnderDragon => ExperienceReward::fixed(500),
+            _ => ExperienceReward::fixed(5),
+        }
+    }
+
+    /// Returns the items this mob drops when killed.
+    ///
+    /// Vanilla changes some drops depending on how the mob died,
+    /// e.g. cooked meat for mobs killed by fire; this is not
+    /// included. Slimes, whose drops depend on their size,
+    /// drop nothing.
+    pub fn loot(self) -> &'static [LootEntry] {
+        use feather_core::items::Item::*;
+
+        match self {
+            MobKind::Chicken => &[LootEntry(Feather, 0, 2), LootEntry(Chicken, 1, 1)],
+            MobKind::Cow | MobKind::MushroomCow => {
+                &[LootEntry(Leather, 0, 2), LootEntry(Beef, 1, 3)]
+            }
+            MobKind::Pig => &[LootEntry(Porkchop, 1, 3)],
+            MobKind::Sheep => &[LootEntry(Mutton, 1, 2)],
+            MobKind::Rabbit => &[LootEntry(RabbitHide, 0, 1), LootEntry(Rabbit, 0, 1)],
+            MobKind::Horse | MobKind::Donkey | MobKind::Mule | MobKind::Llama => {
+                &[LootEntry(Leather, 0, 2)]
+            }
+            MobKind::Parrot => &[LootEntry(Feather, 1, 2)],
+            MobKind::Squid => &[LootEntry(InkSac, 1, 3)],
+            MobKind::Cod => &[LootEntry(Cod, 1, 1)],
+            MobKind::Salmon => &[LootEntry(Salmon, 1, 1)],
+            MobKind::Pufferfish => &[LootEntry(Pufferfish, 1, 1)],
+            MobKind::TropicalFish => &[LootEntry(TropicalFish, 1, 1)],
+            MobKind::PolarBear => &[LootEntry(Cod, 0, 2), LootEntry(Salmon, 0, 2)],
+            MobKind::Turtle => &[LootEntry(Seagrass, 0, 2)],
+            MobKind::IronGolem => &[LootEntry(IronIngot, 3, 5), LootEntry(Poppy, 0, 2)],
+            MobKind::SnowGolem => &[LootEntry(Snowball, 0, 15)],
+            MobKind::Zombie | MobKind::Husk | MobKind::Drowned | MobKind::ZombieVillager => {
+                &[LootEntry(RottenFlesh, 0, 2)]
+            }
+            MobKind::PigZombie => &[LootEntry(RottenFlesh, 0, 1), LootEntry(GoldNugget, 0, 1)],
+            MobKind::Skeleton | MobKind::Stray => &[LootEntry(Bone, 0, 2), LootEntry(Arrow, 0, 2)],
+            MobKind::WitherSkeleton => &[LootEntry(Bone, 0, 2), LootEntry(Coal, 0, 1)],
+            MobKind::Creeper => &[LootEntry(Gunpowder, 0, 2)],
+            MobKind::Spider | MobKind::CaveSpider => {
+                &[LootEntry(String, 0, 2), LootEntry(SpiderEye, 0, 1)]
+            }
+            MobKind::Enderman => &[LootEntry(EnderPearl, 0, 1)],
+            MobKind::Blaze => &[LootEntry(BlazeRod, 0, 1)],
+            MobKind::Ghast => &[LootEntry(GhastTear, 0, 1), LootEntry(Gunpowder, 0, 2)],
+            MobKind::Guardian | MobKind::ElderGuardian => &[LootEntry(PrismarineShard, 0, 2)],
+            MobKind::Shulker => &[LootEntry(ShulkerShell, 0, 1)],
+            MobKind::Phantom => &[LootEntry(PhantomMembrane, 0, 1)],
+            MobKind::EvocationIllager => &[LootEntry(TotemOfUndying, 1, 1)],
+            MobKind::VindicationIllager => &[LootEntry(Emerald, 0, 1)],
+            _ => &[],
+        }
+    }
+}
+
 /// Returns the base components for a mob with the given
 /// kind.
 pub fn base(kind: MobKind) -> EntityBuilder {
-    super::base().with(spawn_packet_creator(kind))
+    base_with_health(kind, kind.max_health())
+}
+
+fn base_with_health(kind: MobKind, health: f32) -> EntityBuilder {
+    let (width, height) = kind.size();
+    let mut builder = super::base()
+        .with(EntityType(kind.identifier()))
+        .with(spawn_packet_creator(kind))
+        .with(
+            PhysicsBuilder::for_living()
+                .bbox(width, height, width)
+                .build(),
+        )
+        .with(Living)
+        .with(Health(health))
+        .with(kind.experience_reward())
+        .with(MobLoot(kind.loot()))
+        .with(EntityCategory::Mob);
+    if let Some(attribute) = kind.creature_attribute() {
+        builder = builder.with(attribute);
+    }
+    if kind.is_hostile() {
+        builder = builder.with(Hostile);
+    }
+    builder
+}
+
+/// Serializes the components shared by all mobs.
+pub fn serialize(accessor: &EntityRef) -> MobData {
+    let vel = accessor.get::<Velocity>().0;
+
+    MobData {
+        base: BaseEntityData::new(*accessor.get::<Position>(), Vec3d::new(vel.x, vel.y, vel.z)),
+        health: accessor.try_get::<Health>().map(|health| health.0),
+        custom_name: accessor.try_get::<CustomName>().map(|name| name.0.clone()),
+    }
+}
+
+/// Returns the base components for a mob loaded from a `MobData`.
+/// `metadata` holds any type-specific metadata for the mob.
+///
+/// Like `base`, this does not include the components
+/// specific to the mob's type.
+pub fn load(
+    kind: MobKind,
+    data: &MobData,
+    mut metadata: EntityMetadata,
+) -> anyhow::Result<EntityBuilder> {
+    let pos = data.base.read_position()?;
+    let vel = data.base.read_velocity()?;
+
+    let mut builder = base_with_health(kind, data.health.unwrap_or_else(|| kind.max_health()))
+        .with(pos)
+        .with(Velocity(glm::vec3(vel.x, vel.y, vel.z)));
+
+    if let Some(custom_name) = &data.custom_name {
+        metadata.set_custom_name(Some(custom_name.clone()));
+        builder = builder.with(CustomName(custom_name.clone()));
+    }
+
+    Ok(builder.with(metadata))
+}
+
+/// Returns the components for a new mob with the given
+/// namespaced identifier, such as `minecraft:pig`, or `None`
+/// if there is no such mob.
+pub fn create_by_id(id: &str) -> Option<EntityBuilder> {
+    let create: fn() -> EntityBuilder = match id {
+        "minecraft:bat" => bat::create,
+        "minecraft:blaze" => blaze::create,
+        "minecraft:cave_spider" => cave_spider::create,
+        "minecraft:chicken" => chicken::create,
+        "minecraft:cod" => cod::create,
+        "minecraft:cow" => cow::create,
+        "minecraft:creeper" => creeper::create,
+        "minecraft:dolphin" => dolphin::create,
+        "minecraft:donkey" => donkey::create,
+        "minecraft:drowned" => drowned::create,
+        "minecraft:elder_guardian" => elder_guardian::create,
+        "minecraft:ender_dragon" => ender_dragon::create,
+        "minecraft:enderman" => enderman::create,
+        "minecraft:endermite" => endermite::create,
+        "minecraft:evoker" => evoker::create,
+        "minecraft:ghast" => ghast::create,
+        "minecraft:guardian" => guardian::create,
+        "minecraft:horse" => horse::create,
+        "minecraft:husk" => husk::create,
+        "minecraft:iron_golem" => iron_golem::create,
+        "minecraft:llama" => llama::create,
+        "minecraft:magma_cube" => magma_cube::create,
+        "minecraft:mooshroom" => mooshroom::create,
+        "minecraft:mule" => mule::create,
+        "minecraft:ocelot" => ocelot::create,
+        "minecraft:parrot" => parrot::create,
+        "minecraft:phantom" => phantom::create,
+        "minecraft:pig" => pig::create,
+        "minecraft:polar_bear" => polar_bear::create,
+        "minecraft:pufferfish" => pufferfish::create,
+        "minecraft:rabbit" => rabbit::create,
+        "minecraft:salmon" => salmon::create,
+        "minecraft:sheep" => sheep::create,
+        "minecraft:shulker" => shulker::create,
+        "minecraft:silverfish" => silverfish::create,
+        "minecraft:skeleton" => skeleton::create,
+        "minecraft:skeleton_horse" => skeleton_horse::create,
+        "minecraft:slime" => slime::create,
+        "minecraft:snow_golem" => snow_golem::create,
+        "minecraft:spider" => spider::create,
+        "minecraft:squid" => squid::create,
+        "minecraft:stray" => stray::create,
+        "minecraft:tropical_fish" => tropical_fish::create,
+        "minecraft:turtle" => turtle::create,
+        "minecraft:vex" => vex::create,
+        "minecraft:villager" => villager::create,
+        "minecraft:vindicator" => vindicator::create,
+        "minecraft:witch" => witch::create,
+        "minecraft:wither" => wither::create,
+        "minecraft:wither_skeleton" => wither_skeleton::create,
+        "minecraft:wolf" => wolf::create,
+        "minecraft:zombie" => zombie::create,
+        "minecraft:zombie_horse" => zombie_horse::create,
+        "minecraft:zombie_pigman" => zombie_pigman::create,
+        "minecraft:zombie_villager" => zombie_villager::create,
+        _ => return None,
+    };
+    Some(create())
+}
+
+/// Returns the identifier of the mob spawned by the given
+/// spawn egg, or `None` if the item is not a spawn egg.
+pub fn spawn_egg_mob(item: Item) -> Option<&'static str> {
+    // Spawn eggs are named after their mob's identifier.
+    let id = item.identifier();
+    if id.ends_with(SPAWN_EGG_SUFFIX) {
+        Some(id.trim_end_matches(SPAWN_EGG_SUFFIX))
+    } else {
+        None
+    }
+}
+
+const SPAWN_EGG_SUFFIX: &str = "_spawn_egg";
+
+/// Adds the components set by an `EntityTag`, as given by a spawn
+/// egg or `/summon`, to a mob. Status effects are not included,
+/// since they can only be added once the mob has spawned.
+pub fn with_entity_tag(mut builder: EntityBuilder, tag: &EntityTagNbt) -> EntityBuilder {
+    let mut metadata = EntityMetadata::entity_base();
+    metadata.set_custom_name_visible(tag.custom_name_visible);
+    metadata.set_silent(tag.silent);
+
+    if let Some(custom_name) = &tag.custom_name {
+        metadata.set_custom_name(Some(custom_name.clone()));
+        builder = builder.with(CustomName(custom_name.clone()));
+    }
+
+    if tag.no_ai {
+        metadata.set(META_INDEX_MOB_FLAGS, 0x01u8);
+        builder = builder.with(NoAi);
+    }
+
+    builder.with(metadata)
 }
 
 /// Returns a `SpawnPacketCreator` for a mob with the given kind.
@@ -132,3 +782,64 @@ pub fn spawn_packet_creator(kind: MobKind) -> SpawnPacketCreator {
 
     SpawnPacketCreator(Box::leak(f))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_test_framework::Test;
+
+    #[test]
+    fn mob_data_round_trip() {
+        let mut test = Test::new();
+        let pos = position!(1.0, 64.0, -3.0, 90.0, 0.0);
+        let pig = test.entity(
+            pig::create()
+                .with(pos)
+                .with(CustomName(String::from("{\"text\":\"Bacon\"}"))),
+        );
+        *test.world.get_mut::<Health>(pig) = Health(4.5);
+
+        let data = serialize(&test.world.entity(pig).unwrap());
+        assert_eq!(data.health, Some(4.5));
+
+        let loaded = test.entity(load(MobKind::Pig, &data, EntityMetadata::entity_base()).unwrap());
+        assert_eq!(*test.world.get::<Position>(loaded), pos);
+        assert_eq!(*test.world.get::<Health>(loaded), Health(4.5));
+        assert_eq!(
+            test.world.get::<CustomName>(loaded).0,
+            "{\"text\":\"Bacon\"}"
+        );
+    }
+
+    #[test]
+    fn spawn_eggs_spawn_their_mob() {
+        assert_eq!(spawn_egg_mob(Item::PigSpawnEgg), Some("minecraft:pig"));
+        assert_eq!(
+            spawn_egg_mob(Item::ZombiePigmanSpawnEgg),
+            Some("minecraft:zombie_pigman")
+        );
+        assert_eq!(spawn_egg_mob(Item::Egg), None);
+
+        for &egg in &[
+            Item::MooshroomSpawnEgg,
+            Item::EvokerSpawnEgg,
+            Item::ZombieHorseSpawnEgg,
+        ] {
+            assert!(create_by_id(spawn_egg_mob(egg).unwrap()).is_some());
+        }
+        assert!(create_by_id("minecraft:stone").is_none());
+    }
+
+    #[test]
+    fn loaded_mob_defaults_to_max_health() {
+        let mut test = Test::new();
+        let data = MobData::default();
+
+        let loaded =
+            test.entity(load(MobKind::Zombie, &data, EntityMetadata::entity_base()).unwrap());
+        assert_eq!(
+            *test.world.get::<Health>(loaded),
+            Health(MobKind::Zombie.max_health())
+        );
+    }
+}
@@ -1,8 +1,266 @@
-use crate::{mob, MobKind};
-use fecs::EntityBuilder;
+use crate::{mob, MobKind, NoAi};
+use feather_core::anvil::entity::{CreeperData, EntityData, EntityDataKind};
+use feather_core::entitymeta::{
+    EntityMetadata, ToMetaEntry, META_INDEX_CREEPER_CHARGED, META_INDEX_CREEPER_IGNITED,
+    META_INDEX_CREEPER_STATE,
+};
+use feather_core::util::{Gamemode, Position};
+use feather_server_types::{
+    BumpVec, ComponentSerializer, EntityLoaderRegistration, ExplosionRequest, ExplosionSource,
+    Game, Player, CREEPER_EXPLOSION_POWER,
+};
+use feather_server_util::{nearby_entities, play_sound, SoundCategory};
+use fecs::{Entity, EntityBuilder, EntityRef, IntoQuery, Read, World};
+
+/// Number of ticks a creeper's fuse burns before it explodes.
+const FUSE_TICKS: u32 = 30;
+/// Distance from a player within which a creeper starts its fuse.
+const IGNITE_DISTANCE: f64 = 3.0;
+/// Distance from the nearest player beyond which a creeper's
+/// fuse burns back down.
+const DEFUSE_DISTANCE: f64 = 7.0;
 
 pub struct Creeper;
 
+/// Component storing how far a creeper's fuse has burned.
+///
+/// The fuse burns while a player is close and burns back
+/// down once they get away, unless the creeper was ignited
+/// with flint and steel.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Fuse {
+    pub ticks: u32,
+    pub ignited: bool,
+}
+
+/// Marker component for creepers which have been struck
+/// by lightning. Charged creepers explode with twice the power.
+#[derive(Copy, Clone, Debug)]
+pub struct Charged;
+
+inventory::submit! {
+    EntityLoaderRegistration::new(EntityDataKind::Creeper, &load)
+}
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Creeper).with(Creeper)
+    mob::base(MobKind::Creeper)
+        .with(Creeper)
+        .with(Fuse::default())
+        .with(ComponentSerializer(&serialize))
+}
+
+fn serialize(_game: &Game, accessor: &EntityRef) -> EntityData {
+    EntityData::Creeper(CreeperData {
+        mob: mob::serialize(accessor),
+        powered: accessor.try_get::<Charged>().is_some() as u8,
+    })
+}
+
+fn load(data: EntityData) -> anyhow::Result<EntityBuilder> {
+    match data {
+        EntityData::Creeper(data) => {
+            let charged = data.powered != 0;
+            let metadata = EntityMetadata::entity_base().with(META_INDEX_CREEPER_CHARGED, charged);
+
+            let builder = mob::load(MobKind::Creeper, &data.mob, metadata)?
+                .with(Creeper)
+                .with(Fuse::default())
+                .with(ComponentSerializer(&serialize));
+            Ok(if charged {
+                builder.with(Charged)
+            } else {
+                builder
+            })
+        }
+        _ => panic!("attempted to use creeper::load to load a non-creeper"),
+    }
+}
+
+/// Makes a creeper explode, removing it from the world.
+pub fn explode(game: &mut Game, world: &mut World, creeper: Entity) {
+    let pos = *world.get::<Position>(creeper);
+    let power = if world.has::<Charged>(creeper) {
+        CREEPER_EXPLOSION_POWER * 2.0
+    } else {
+        CREEPER_EXPLOSION_POWER
+    };
+    game.handle(
+        world,
+        ExplosionRequest {
+            pos,
+            power,
+            source: ExplosionSource::Creeper(creeper),
+        },
+    );
+    game.despawn(creeper, world);
+}
+
+/// Ignites a creeper, as done with flint and steel. An
+/// ignited creeper explodes even if players get away.
+pub fn ignite(world: &mut World, creeper: Entity) {
+    world.get_mut::<Fuse>(creeper).ignited = true;
+    set_metadata(world, creeper, META_INDEX_CREEPER_IGNITED, true);
+}
+
+/// Returns whether a player within `distance` of `pos` can
+/// be attacked by a creeper.
+fn player_within(game: &Game, world: &World, pos: Position, distance: f64) -> bool {
+    nearby_entities(world, game, pos, glm::vec3(distance, distance, distance))
+        .into_iter()
+        .filter(|entity| world.has::<Player>(*entity))
+        .filter(
+            |entity| match world.try_get::<Gamemode>(*entity).map(|g| *g) {
+                Some(Gamemode::Survival) | Some(Gamemode::Adventure) => true,
+                _ => false,
+            },
+        )
+        .any(|player| world.get::<Position>(player).distance_to(pos) <= distance)
+}
+
+fn set_metadata(world: &mut World, creeper: Entity, index: u8, value: impl ToMetaEntry) {
+    if world.has::<EntityMetadata>(creeper) {
+        world.get_mut::<EntityMetadata>(creeper).set(index, value);
+    } else {
+        let mut metadata = EntityMetadata::entity_base();
+        metadata.set(index, value);
+        world.add(creeper, metadata).unwrap();
+    }
+}
+
+/// System which burns the fuses of creepers near players
+/// and makes creepers explode once their fuse runs out.
+#[fecs::system]
+pub fn update_creeper_fuses(game: &mut Game, world: &mut World) {
+    let mut updated = BumpVec::new_in(game.bump());
+
+    for (creeper, (pos, fuse)) in
+        <(Read<Position>, Read<Fuse>)>::query().iter_entities(world.inner())
+    {
+        let burning = if fuse.ignited {
+            true
+        } else if world.has::<NoAi>(creeper) {
+            // Creepers without AI do not notice players,
+            // but still explode when ignited.
+            false
+        } else if fuse.ticks == 0 {
+            player_within(game, world, *pos, IGNITE_DISTANCE)
+        } else {
+            player_within(game, world, *pos, DEFUSE_DISTANCE)
+        };
+        updated.push((creeper, *pos, *fuse, burning));
+    }
+
+    for (creeper, pos, mut fuse, burning) in updated {
+        // An earlier creeper's explosion may have killed this one.
+        if !world.is_alive(creeper) {
+            continue;
+        }
+
+        if burning {
+            if fuse.ticks == 0 {
+                play_sound(
+                    game,
+                    world,
+                    "entity.creeper.primed",
+                    SoundCategory::Hostile,
+                    pos,
+                    1.0,
+                    0.5,
+                    None,
+                );
+                set_metadata(world, creeper, META_INDEX_CREEPER_STATE, 1);
+            }
+            fuse.ticks += 1;
+        } else if fuse.ticks > 0 {
+            fuse.ticks -= 1;
+            if fuse.ticks == 0 {
+                set_metadata(world, creeper, META_INDEX_CREEPER_STATE, -1);
+            }
+        }
+
+        if fuse.ticks >= FUSE_TICKS {
+            explode(game, world, creeper);
+        } else {
+            *world.get_mut::<Fuse>(creeper) = fuse;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_server_types::EntitySpawnEvent;
+    use feather_server_util::on_entity_spawn_update_chunk_entities;
+    use feather_test_framework::Test;
+
+    fn fuse_ticks(test: &Test, creeper: Entity) -> u32 {
+        test.world.get::<Fuse>(creeper).ticks
+    }
+
+    #[test]
+    fn fuse_burns_near_survival_players() {
+        let mut test = Test::new();
+        let creeper = test.entity(create().with(position!(0.5, 64.0, 0.5)));
+        let player = test.player("", position!(2.5, 64.0, 0.5));
+        for &entity in &[creeper, player] {
+            test.handle(
+                EntitySpawnEvent { entity },
+                on_entity_spawn_update_chunk_entities,
+            );
+        }
+
+        // Creative players are left alone.
+        test.run(update_creeper_fuses);
+        assert_eq!(fuse_ticks(&test, creeper), 0);
+
+        *test.world.get_mut::<Gamemode>(player) = Gamemode::Survival;
+        for _ in 0..10 {
+            test.run(update_creeper_fuses);
+        }
+        assert_eq!(fuse_ticks(&test, creeper), 10);
+
+        // The fuse burns back down once the player gets away.
+        test.position(player, position!(10.5, 64.0, 0.5))
+            .run(update_creeper_fuses);
+        assert_eq!(fuse_ticks(&test, creeper), 9);
+
+        test.position(player, position!(2.5, 64.0, 0.5));
+        for _ in 0..FUSE_TICKS - 10 {
+            test.run(update_creeper_fuses);
+        }
+        assert_eq!(fuse_ticks(&test, creeper), FUSE_TICKS - 1);
+        test.run(update_creeper_fuses);
+        test.assert_dead(creeper);
+    }
+
+    #[test]
+    fn creepers_without_ai_ignore_players() {
+        let mut test = Test::new();
+        let creeper = test.entity(create().with(NoAi).with(position!(0.5, 64.0, 0.5)));
+        let player = test.player("", position!(2.5, 64.0, 0.5));
+        *test.world.get_mut::<Gamemode>(player) = Gamemode::Survival;
+        for &entity in &[creeper, player] {
+            test.handle(
+                EntitySpawnEvent { entity },
+                on_entity_spawn_update_chunk_entities,
+            );
+        }
+
+        test.run(update_creeper_fuses);
+        assert_eq!(fuse_ticks(&test, creeper), 0);
+    }
+
+    #[test]
+    fn ignited_creeper_explodes_without_players() {
+        let mut test = Test::new();
+        let creeper = test.entity(create().with(position!(0.5, 64.0, 0.5)));
+        ignite(&mut test.world, creeper);
+
+        for _ in 0..FUSE_TICKS - 1 {
+            test.run(update_creeper_fuses);
+        }
+        test.assert_alive(creeper);
+        test.run(update_creeper_fuses);
+        test.assert_dead(creeper);
+    }
 }
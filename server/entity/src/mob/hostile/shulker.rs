@@ -1,8 +1,9 @@
 use crate::{mob, MobKind};
+use feather_server_types::Stationary;
 use fecs::EntityBuilder;
 
 pub struct Shulker;
 
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Shulker).with(Shulker)
+    mob::base(MobKind::Shulker).with(Shulker).with(Stationary)
 }
@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Skeleton;
 
+persistent_mob!(Skeleton, Skeleton, Skeleton);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Skeleton).with(Skeleton)
+    mob::base(MobKind::Skeleton)
+        .with(Skeleton)
+        .with(ComponentSerializer(&serialize))
 }
@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Witch;
 
+persistent_mob!(Witch, Witch, Witch);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Witch).with(Witch)
+    mob::base(MobKind::Witch)
+        .with(Witch)
+        .with(ComponentSerializer(&serialize))
 }
@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Zombie;
 
+persistent_mob!(Zombie, Zombie, Zombie);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Zombie).with(Zombie)
+    mob::base(MobKind::Zombie)
+        .with(Zombie)
+        .with(ComponentSerializer(&serialize))
 }
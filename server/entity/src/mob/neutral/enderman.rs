@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Enderman;
 
+persistent_mob!(Enderman, Enderman, Enderman);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Enderman).with(Enderman)
+    mob::base(MobKind::Enderman)
+        .with(Enderman)
+        .with(ComponentSerializer(&serialize))
 }
@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Llama;
 
+persistent_mob!(Llama, Llama, Llama);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Llama).with(Llama)
+    mob::base(MobKind::Llama)
+        .with(Llama)
+        .with(ComponentSerializer(&serialize))
 }
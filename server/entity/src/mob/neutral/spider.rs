@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Spider;
 
+persistent_mob!(Spider, Spider, Spider);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Spider).with(Spider)
+    mob::base(MobKind::Spider)
+        .with(Spider)
+        .with(ComponentSerializer(&serialize))
 }
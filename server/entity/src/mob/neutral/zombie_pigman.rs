@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct ZombiePigman;
 
+persistent_mob!(ZombiePigman, PigZombie, ZombiePigman);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::PigZombie).with(ZombiePigman)
+    mob::base(MobKind::PigZombie)
+        .with(ZombiePigman)
+        .with(ComponentSerializer(&serialize))
 }
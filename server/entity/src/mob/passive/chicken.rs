@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Chicken;
 
+persistent_mob!(Chicken, Chicken, Chicken);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Chicken).with(Chicken)
+    mob::base(MobKind::Chicken)
+        .with(Chicken)
+        .with(ComponentSerializer(&serialize))
 }
@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Cow;
 
+persistent_mob!(Cow, Cow, Cow);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Cow).with(Cow)
+    mob::base(MobKind::Cow)
+        .with(Cow)
+        .with(ComponentSerializer(&serialize))
 }
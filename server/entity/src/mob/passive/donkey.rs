@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Donkey;
 
+persistent_mob!(Donkey, Donkey, Donkey);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Donkey).with(Donkey)
+    mob::base(MobKind::Donkey)
+        .with(Donkey)
+        .with(ComponentSerializer(&serialize))
 }
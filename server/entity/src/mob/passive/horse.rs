@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Horse;
 
+persistent_mob!(Horse, Horse, Horse);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Horse).with(Horse)
+    mob::base(MobKind::Horse)
+        .with(Horse)
+        .with(ComponentSerializer(&serialize))
 }
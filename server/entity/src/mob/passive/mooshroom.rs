@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Mooshroom;
 
+persistent_mob!(Mooshroom, MushroomCow, Mooshroom);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::MushroomCow).with(Mooshroom)
+    mob::base(MobKind::MushroomCow)
+        .with(Mooshroom)
+        .with(ComponentSerializer(&serialize))
 }
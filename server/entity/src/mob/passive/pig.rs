@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Pig;
 
+persistent_mob!(Pig, Pig, Pig);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Pig).with(Pig)
+    mob::base(MobKind::Pig)
+        .with(Pig)
+        .with(ComponentSerializer(&serialize))
 }
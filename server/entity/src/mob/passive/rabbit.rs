@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Rabbit;
 
+persistent_mob!(Rabbit, Rabbit, Rabbit);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Rabbit).with(Rabbit)
+    mob::base(MobKind::Rabbit)
+        .with(Rabbit)
+        .with(ComponentSerializer(&serialize))
 }
@@ -1,8 +1,222 @@
-use crate::{mob, MobKind};
-use fecs::EntityBuilder;
+//! Sheep, which players shear for wool of the sheep's color.
+//! Sheared sheep regrow their wool by eating grass.
+
+use crate::{item, mob, MobKind, NoAi};
+use feather_core::anvil::entity::{EntityData, EntityDataKind, SheepData};
+use feather_core::blocks::{BlockId, BlockKind};
+use feather_core::entitymeta::{EntityMetadata, META_INDEX_SHEEP_FLAGS};
+use feather_core::items::ItemStack;
+use feather_core::network::packets::EntityStatus;
+use feather_core::util::{BlockPosition, Position};
+use feather_server_types::{
+    BlockUpdateCause, BumpVec, ComponentSerializer, EntityId, EntityLoaderRegistration, Game,
+};
+use feather_server_util::DyeColor;
+use fecs::{Entity, EntityBuilder, EntityRef, IntoQuery, Read, World};
+use rand::Rng;
 
 pub struct Sheep;
 
+/// Component storing the state of a sheep's wool.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Wool {
+    pub color: DyeColor,
+    pub sheared: bool,
+}
+
+impl Wool {
+    /// Returns the value of the sheep's metadata flags.
+    fn flags(self) -> u8 {
+        self.color.id() | if self.sheared { 0x10 } else { 0 }
+    }
+}
+
+/// Component for sheep eating grass, storing
+/// the tick at which they finish.
+#[derive(Copy, Clone, Debug)]
+struct EatingGrass(u64);
+
+/// Chance per tick of a sheep starting to eat grass.
+const EAT_GRASS_CHANCE: u32 = 1000;
+
+/// Number of ticks a sheep spends eating grass.
+const EAT_GRASS_TICKS: u64 = 40;
+
+/// Entity status which plays the eating animation.
+const STATUS_EAT_GRASS: i8 = 10;
+
+inventory::submit! {
+    EntityLoaderRegistration::new(EntityDataKind::Sheep, &load)
+}
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Sheep).with(Sheep)
+    mob::base(MobKind::Sheep)
+        .with(Sheep)
+        .with(Wool {
+            color: DyeColor::White,
+            sheared: false,
+        })
+        .with(ComponentSerializer(&serialize))
+}
+
+fn serialize(_game: &Game, accessor: &EntityRef) -> EntityData {
+    let wool = *accessor.get::<Wool>();
+    EntityData::Sheep(SheepData {
+        mob: mob::serialize(accessor),
+        color: wool.color.id() as i8,
+        sheared: wool.sheared as u8,
+    })
+}
+
+fn load(data: EntityData) -> anyhow::Result<EntityBuilder> {
+    match data {
+        EntityData::Sheep(data) => {
+            let wool = Wool {
+                color: DyeColor::from_id(data.color as u8),
+                sheared: data.sheared != 0,
+            };
+            let metadata = EntityMetadata::entity_base().with(META_INDEX_SHEEP_FLAGS, wool.flags());
+
+            Ok(mob::load(MobKind::Sheep, &data.mob, metadata)?
+                .with(Sheep)
+                .with(wool)
+                .with(ComponentSerializer(&serialize)))
+        }
+        _ => panic!("attempted to use sheep::load to load a non-sheep"),
+    }
+}
+
+/// Shears a sheep, dropping one to three wool of its color.
+/// Returns `false` if the sheep has already been sheared.
+pub fn shear(game: &mut Game, world: &mut World, sheep: Entity) -> bool {
+    let mut wool = match world.try_get::<Wool>(sheep).map(|wool| *wool) {
+        Some(wool) if !wool.sheared => wool,
+        _ => return false,
+    };
+    wool.sheared = true;
+    set_wool(world, sheep, wool);
+
+    let pos = *world.get::<Position>(sheep) + glm::vec3(0.0, 1.0, 0.0);
+    let count = game.rng().gen_range(1, 4);
+    for _ in 0..count {
+        item::drop_item(game, world, pos, ItemStack::new(wool.color.wool(), 1));
+    }
+    true
+}
+
+/// Dyes a sheep's wool. Returns `false` if
+/// the wool already has the given color.
+pub fn dye(world: &mut World, sheep: Entity, color: DyeColor) -> bool {
+    let mut wool = match world.try_get::<Wool>(sheep).map(|wool| *wool) {
+        Some(wool) if wool.color != color => wool,
+        _ => return false,
+    };
+    wool.color = color;
+    set_wool(world, sheep, wool);
+    true
+}
+
+fn set_wool(world: &mut World, sheep: Entity, wool: Wool) {
+    *world.get_mut::<Wool>(sheep) = wool;
+
+    if world.has::<EntityMetadata>(sheep) {
+        world
+            .get_mut::<EntityMetadata>(sheep)
+            .set(META_INDEX_SHEEP_FLAGS, wool.flags());
+    } else {
+        let mut metadata = EntityMetadata::entity_base();
+        metadata.set(META_INDEX_SHEEP_FLAGS, wool.flags());
+        world.add(sheep, metadata).unwrap();
+    }
+}
+
+/// System which makes sheep eat grass around them
+/// now and then, which regrows their wool.
+#[fecs::system]
+pub fn sheep_eat_grass(game: &mut Game, world: &mut World) {
+    let mut started = BumpVec::new_in(game.bump());
+    let mut finished = BumpVec::new_in(game.bump());
+
+    for (sheep, pos) in <Read<Position>>::query()
+        .filter(fecs::component::<Wool>() & !fecs::component::<NoAi>())
+        .iter_entities(world.inner())
+    {
+        match world.try_get::<EatingGrass>(sheep).map(|eating| eating.0) {
+            Some(end) if end <= game.tick_count => finished.push((sheep, pos.block())),
+            Some(_) => (),
+            None => {
+                if game.rng().gen_range(0, EAT_GRASS_CHANCE) == 0
+                    && edible_grass(game, pos.block()).is_some()
+                {
+                    started.push(sheep);
+                }
+            }
+        }
+    }
+
+    for sheep in started {
+        world
+            .add(sheep, EatingGrass(game.tick_count + EAT_GRASS_TICKS))
+            .unwrap();
+        let entity_id = world.get::<EntityId>(sheep).0;
+        game.broadcast_entity_update(
+            world,
+            EntityStatus {
+                entity_id,
+                entity_status: STATUS_EAT_GRASS,
+            },
+            sheep,
+            None,
+        );
+    }
+
+    for (sheep, pos) in finished {
+        world.remove::<EatingGrass>(sheep).unwrap();
+
+        // The grass may have been removed while the sheep was eating.
+        let (grass, eaten) = match edible_grass(game, pos) {
+            Some(grass) => grass,
+            None => continue,
+        };
+        game.set_block_at_with_cause(world, grass, eaten, BlockUpdateCause::entity(sheep));
+
+        let mut wool = *world.get::<Wool>(sheep);
+        if wool.sheared {
+            wool.sheared = false;
+            set_wool(world, sheep, wool);
+        }
+    }
+}
+
+/// Returns the grass which a sheep standing at `pos` can eat,
+/// along with the block it leaves behind.
+fn edible_grass(game: &Game, pos: BlockPosition) -> Option<(BlockPosition, BlockId)> {
+    let below = pos - BlockPosition::new(0, 1, 0);
+    if game.block_at(pos)?.kind() == BlockKind::Grass {
+        Some((pos, BlockId::air()))
+    } else if game.block_at(below)?.kind() == BlockKind::GrassBlock {
+        Some((below, BlockId::dirt()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wool_flags() {
+        let wool = Wool {
+            color: DyeColor::Red,
+            sheared: false,
+        };
+        assert_eq!(wool.flags(), 14);
+
+        let sheared = Wool {
+            sheared: true,
+            ..wool
+        };
+        assert_eq!(sheared.flags(), 0x1E);
+    }
 }
@@ -4,5 +4,5 @@ use fecs::EntityBuilder;
 pub struct SkeletonHorse;
 
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Horse).with(SkeletonHorse)
+    mob::base(MobKind::SkeletonHorse).with(SkeletonHorse)
 }
@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Squid;
 
+persistent_mob!(Squid, Squid, Squid);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Squid).with(Squid)
+    mob::base(MobKind::Squid)
+        .with(Squid)
+        .with(ComponentSerializer(&serialize))
 }
@@ -1,8 +1,13 @@
 use crate::{mob, MobKind};
+use feather_server_types::ComponentSerializer;
 use fecs::EntityBuilder;
 
 pub struct Villager;
 
+persistent_mob!(Villager, Villager, Villager);
+
 pub fn create() -> EntityBuilder {
-    mob::base(MobKind::Villager).with(Villager)
+    mob::base(MobKind::Villager)
+        .with(Villager)
+        .with(ComponentSerializer(&serialize))
 }
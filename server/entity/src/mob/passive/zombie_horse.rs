@@ -0,0 +1,8 @@
+use crate::{mob, MobKind};
+use fecs::EntityBuilder;
+
+pub struct ZombieHorse;
+
+pub fn create() -> EntityBuilder {
+    mob::base(MobKind::ZombieHorse).with(ZombieHorse)
+}
@@ -1,3 +1,13 @@
 pub mod arrow;
+pub mod egg;
+pub mod ender_pearl;
+pub mod experience_orb;
 pub mod falling_block;
+pub mod firework_rocket;
 pub mod item;
+pub mod item_frame;
+pub mod lightning_bolt;
+pub mod primed_tnt;
+pub mod projectile;
+pub mod snowball;
+pub mod trident;
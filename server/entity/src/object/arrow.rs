@@ -1,57 +1,328 @@
-use feather_core::anvil::entity::{ArrowEntityData, BaseEntityData, EntityData};
-use feather_core::network::packets::SpawnObject;
+//! Arrows, which damage entities they hit and stick
+//! in blocks, where players may pick them up.
+
+use crate::trident::Trident;
+use crate::{projectile, Age};
+use feather_core::anvil::entity::{ArrowEntityData, BaseEntityData, EntityData, EntityDataKind};
+use feather_core::entitymeta::{EntityBitMask, EntityMetadata, META_INDEX_ARROW_FLAGS};
+use feather_core::inventory::Inventory;
+use feather_core::items::{Item, ItemStack};
 use feather_core::network::Packet;
-use feather_core::util::{Position, Vec3d};
+use feather_core::util::{Gamemode, Position, Vec3d};
 use feather_server_types::{
-    ComponentSerializer, EntityId, Game, PhysicsBuilder, SpawnPacketCreator, Uuid, Velocity,
+    BumpVec, ComponentSerializer, DamageCause, EntityDamageRequest, EntityId,
+    EntityLoaderRegistration, Game, InGround, InventoryUpdateEvent, ItemCollectEvent, Player,
+    ProjectileHitEvent, ProjectileKind, ProjectileTarget, SpawnPacketCreator, Velocity,
+    PLAYER_HEIGHT, PLAYER_WIDTH, TPS,
 };
-use feather_server_util::{degrees_to_stops, protocol_velocity};
-use fecs::{EntityBuilder, EntityRef};
+use feather_server_util::{nearby_entities, set_on_fire};
+use fecs::{component, Entity, EntityBuilder, EntityRef, IntoQuery, Read, World};
+use glm::DVec3;
+use rand::Rng;
+
+/// Damage dealt by an arrow per block/tick of speed.
+pub const BASE_DAMAGE: f64 = 2.0;
+
+/// Arrow metadata flag for critical arrows, which leave
+/// a trail of particles and deal extra damage.
+const FLAG_CRITICAL: u8 = 0x01;
+
+/// Number of ticks for which flaming arrows set their targets on fire.
+const FLAME_TICKS: u32 = 100;
+
+/// Component storing how much damage an arrow
+/// deals and what else it does to entities it hits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArrowProperties {
+    /// Damage dealt per block/tick of speed.
+    pub damage: f64,
+    /// Whether the arrow was shot from a fully drawn bow.
+    pub critical: bool,
+    /// Knockback level from the Punch enchantment.
+    pub knockback: u32,
+    /// Whether the arrow is on fire from the Flame enchantment.
+    pub on_fire: bool,
+}
+
+impl Default for ArrowProperties {
+    fn default() -> Self {
+        Self {
+            damage: BASE_DAMAGE,
+            critical: false,
+            knockback: 0,
+            on_fire: false,
+        }
+    }
+}
+
+/// Component indicating who may pick up an arrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrowPickup {
+    /// Any player may pick up the arrow.
+    Allowed,
+    /// Only players in creative mode may pick up the arrow.
+    /// They do not receive an item.
+    CreativeOnly,
+    /// The arrow cannot be picked up.
+    Disallowed,
+}
+
+impl ArrowPickup {
+    /// Returns the value of the `pickup` tag
+    /// with which arrows are saved.
+    fn to_nbt(self) -> u8 {
+        match self {
+            ArrowPickup::Disallowed => 0,
+            ArrowPickup::Allowed => 1,
+            ArrowPickup::CreativeOnly => 2,
+        }
+    }
+
+    fn from_nbt(pickup: u8) -> Self {
+        match pickup {
+            1 => ArrowPickup::Allowed,
+            2 => ArrowPickup::CreativeOnly,
+            _ => ArrowPickup::Disallowed,
+        }
+    }
+}
+
+inventory::submit! {
+    EntityLoaderRegistration::new(EntityDataKind::Arrow, &load)
+}
 
-pub fn create() -> EntityBuilder {
-    crate::base()
+pub fn create(
+    shooter: Option<Entity>,
+    pickup: ArrowPickup,
+    properties: ArrowProperties,
+) -> EntityBuilder {
+    let flags = if properties.critical {
+        FLAG_CRITICAL
+    } else {
+        0
+    };
+    let mut meta = EntityMetadata::entity_base().with(META_INDEX_ARROW_FLAGS, flags);
+    meta.set_flag(EntityBitMask::ON_FIRE, properties.on_fire);
+
+    projectile::base(ProjectileKind::Arrow, shooter)
+        .with(pickup)
+        .with(properties)
+        .with(Age::default())
+        .with(meta)
         .with(SpawnPacketCreator(&create_spawn_packet))
         .with(ComponentSerializer(&serialize))
-        .with(
-            PhysicsBuilder::new()
-                .bbox(0.5, 0.5, 0.5)
-                .gravity(-0.05)
-                .slip_multiplier(0.0)
-                .drag(0.99)
-                .build(),
-        )
 }
 
 fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
-    let position = *accessor.get::<Position>();
-    let velocity = *accessor.get::<Velocity>();
     let entity_id = accessor.get::<EntityId>().0;
-
-    let (velocity_x, velocity_y, velocity_z) = protocol_velocity(velocity.0);
-
-    let packet = SpawnObject {
-        entity_id,
-        object_uuid: Uuid::new_v4(),
-        ty: 60, // Type 60 for arrow projectile
-        x: position.x,
-        y: position.y,
-        z: position.z,
-        pitch: degrees_to_stops(position.pitch),
-        yaw: degrees_to_stops(position.yaw),
-        data: entity_id + 1,
-        velocity_x,
-        velocity_y,
-        velocity_z,
-    };
-
-    Box::new(packet)
+    projectile::create_spawn_packet(accessor, 60, entity_id + 1)
 }
 
 fn serialize(_game: &Game, accessor: &EntityRef) -> EntityData {
     let vel = accessor.get::<Velocity>().0;
+    let properties = *accessor.get::<ArrowProperties>();
 
     EntityData::Arrow(ArrowEntityData {
         entity: BaseEntityData::new(*accessor.get::<Position>(), Vec3d::new(vel.x, vel.y, vel.z)),
-        critical: 0, // TODO
+        critical: properties.critical as u8,
+        pickup: accessor.get::<ArrowPickup>().to_nbt(),
+        damage: properties.damage,
+        life: accessor.get::<Age>().to_nbt(),
     })
 }
+
+fn load(data: EntityData) -> anyhow::Result<EntityBuilder> {
+    match data {
+        EntityData::Arrow(data) => {
+            let pos = data.entity.read_position()?;
+            let vel = data.entity.read_velocity()?;
+
+            // The shooter and knockback are not persisted.
+            let properties = ArrowProperties {
+                damage: data.damage,
+                critical: data.critical != 0,
+                ..Default::default()
+            };
+            Ok(create(None, ArrowPickup::from_nbt(data.pickup), properties)
+                .with(Age::from_nbt(data.life))
+                .with(pos)
+                .with(Velocity(glm::vec3(vel.x, vel.y, vel.z))))
+        }
+        _ => panic!("attempted to use arrow::load to load a non-arrow"),
+    }
+}
+
+/// Returns the damage dealt by an arrow with the given
+/// damage per unit of speed traveling with the given velocity.
+fn damage(velocity: DVec3, damage: f64) -> f64 {
+    (velocity.norm() * damage).ceil()
+}
+
+/// Damages entities hit by arrows and sticks
+/// arrows which hit blocks into the ground.
+#[fecs::event_handler]
+pub fn on_projectile_hit_handle_arrow(
+    event: &ProjectileHitEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    if event.kind != ProjectileKind::Arrow {
+        return;
+    }
+
+    match event.target {
+        ProjectileTarget::Entity(target) => {
+            let properties = *world.get::<ArrowProperties>(event.projectile);
+
+            let mut amount = damage(event.velocity, properties.damage);
+            if properties.critical {
+                amount += f64::from(game.rng().gen_range(0, amount as u32 / 2 + 2));
+            }
+
+            game.handle(
+                world,
+                EntityDamageRequest {
+                    entity: target,
+                    damage: amount as f32,
+                    cause: DamageCause::Projectile {
+                        projectile: event.projectile,
+                        shooter: event.shooter,
+                    },
+                },
+            );
+
+            if properties.knockback > 0 && world.is_alive(target) {
+                punch(game, world, target, event.velocity, properties.knockback);
+            }
+            if properties.on_fire && world.is_alive(target) {
+                set_on_fire(world, target, FLAME_TICKS);
+            }
+
+            game.despawn(event.projectile, world);
+        }
+        ProjectileTarget::Block(block) => {
+            world.get_mut::<Velocity>(event.projectile).0 = glm::vec3(0.0, 0.0, 0.0);
+            world.add(event.projectile, InGround { block }).unwrap();
+        }
+    }
+}
+
+/// Knocks back an entity hit by an arrow with the Punch enchantment.
+fn punch(game: &Game, world: &mut World, target: Entity, velocity: DVec3, level: u32) {
+    let horizontal_speed = (velocity.x * velocity.x + velocity.z * velocity.z).sqrt();
+    if horizontal_speed <= 0.0 {
+        return;
+    }
+
+    let strength = f64::from(level) * 0.6 / horizontal_speed;
+    let current = world
+        .try_get::<Velocity>(target)
+        .map_or_else(|| glm::vec3(0.0, 0.0, 0.0), |velocity| velocity.0);
+    let knockback = glm::vec3(velocity.x * strength, 0.1, velocity.z * strength);
+    game.set_velocity(world, target, current + knockback);
+}
+
+/// System which lets players pick up arrows and
+/// tridents stuck in the ground.
+#[fecs::system]
+pub fn arrow_pickup(game: &mut Game, world: &mut World) {
+    // run every 1/10 second, like item collection
+    if game.tick_count % (TPS / 10) != 0 {
+        return;
+    }
+
+    let mut pickups: BumpVec<(Entity, Entity, bool)> = BumpVec::new_in(game.bump());
+
+    <Read<Position>>::query()
+        .filter(component::<Player>())
+        .iter_entities(world.inner())
+        .for_each(|(player, pos)| {
+            let gamemode = world
+                .try_get::<Gamemode>(player)
+                .map(|gamemode| *gamemode)
+                .unwrap_or(Gamemode::Survival);
+            if gamemode == Gamemode::Spectator {
+                return;
+            }
+
+            let center = *pos + glm::vec3(0.0, PLAYER_HEIGHT / 2.0, 0.0);
+            let radius = glm::vec3(
+                PLAYER_WIDTH / 2.0 + 1.0,
+                PLAYER_HEIGHT / 2.0 + 0.5,
+                PLAYER_WIDTH / 2.0 + 1.0,
+            );
+
+            for arrow in nearby_entities(world, game, center, radius) {
+                // Tridents flying back with Loyalty may be
+                // caught by their owner before they land.
+                let returning_to_player =
+                    world.try_get::<Trident>(arrow).map_or(false, |trident| {
+                        trident.returning && trident.owner == Some(player)
+                    });
+                if !(world.has::<InGround>(arrow) || returning_to_player)
+                    || pickups.iter().any(|(_, picked, _)| *picked == arrow)
+                {
+                    continue;
+                }
+
+                match world.try_get::<ArrowPickup>(arrow).map(|pickup| *pickup) {
+                    Some(ArrowPickup::Allowed) => pickups.push((player, arrow, true)),
+                    Some(ArrowPickup::CreativeOnly) if gamemode == Gamemode::Creative => {
+                        pickups.push((player, arrow, false))
+                    }
+                    _ => (),
+                }
+            }
+        });
+
+    for (player, arrow, give_item) in pickups {
+        if give_item {
+            let stack = world.try_get::<Trident>(arrow).map_or_else(
+                || ItemStack::new(Item::Arrow, 1),
+                |trident| trident.stack.clone(),
+            );
+            let (slots, remaining) = world.get_mut::<Inventory>(player).collect_item(stack);
+            if remaining != 0 {
+                // inventory is full
+                continue;
+            }
+            game.handle(world, InventoryUpdateEvent { slots, player });
+        }
+
+        game.handle(
+            world,
+            ItemCollectEvent {
+                item: arrow,
+                collector: player,
+                amount: 1,
+            },
+        );
+        game.despawn(arrow, world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn damage_scales_with_speed() {
+        assert_eq!(damage(glm::vec3(0.0, 0.0, 0.0), BASE_DAMAGE), 0.0);
+        assert_eq!(damage(glm::vec3(3.0, 0.0, 0.0), BASE_DAMAGE), 6.0);
+        assert_eq!(damage(glm::vec3(0.0, -1.2, 0.0), BASE_DAMAGE), 3.0);
+        // Power V
+        assert_eq!(damage(glm::vec3(3.0, 0.0, 0.0), 5.0), 15.0);
+    }
+
+    #[test]
+    fn pickup_nbt_round_trip() {
+        for &pickup in &[
+            ArrowPickup::Allowed,
+            ArrowPickup::CreativeOnly,
+            ArrowPickup::Disallowed,
+        ] {
+            assert_eq!(ArrowPickup::from_nbt(pickup.to_nbt()), pickup);
+        }
+    }
+}
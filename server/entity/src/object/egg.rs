@@ -0,0 +1,80 @@
+//! Thrown eggs, which have a chance of hatching chickens.
+
+use crate::projectile;
+use feather_core::network::Packet;
+use feather_server_types::{
+    EntityCategory, EntitySpawnEvent, Game, ProjectileHitEvent, ProjectileKind, SpawnPacketCreator,
+};
+use feather_server_util::can_spawn_in_chunk;
+use fecs::{Entity, EntityBuilder, EntityRef, World};
+use rand::Rng;
+
+pub fn create(shooter: Option<Entity>) -> EntityBuilder {
+    projectile::base(ProjectileKind::Egg, shooter).with(SpawnPacketCreator(&create_spawn_packet))
+}
+
+fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
+    projectile::create_spawn_packet(accessor, 62, 1)
+}
+
+/// Returns the number of chickens which hatch from an egg:
+/// one in eight eggs hatch a chicken, and one in 32 of those
+/// hatch four.
+fn chickens_to_hatch(rng: &mut impl Rng) -> usize {
+    if rng.gen_range(0, 8) != 0 {
+        0
+    } else if rng.gen_range(0, 32) == 0 {
+        4
+    } else {
+        1
+    }
+}
+
+/// Breaks eggs when they hit something, possibly hatching chickens.
+#[fecs::event_handler]
+pub fn on_projectile_hit_handle_egg(
+    event: &ProjectileHitEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    if event.kind != ProjectileKind::Egg {
+        return;
+    }
+
+    game.despawn(event.projectile, world);
+
+    let count = chickens_to_hatch(&mut *game.rng());
+    for _ in 0..count {
+        if !can_spawn_in_chunk(game, world, event.pos.chunk(), EntityCategory::Mob) {
+            break;
+        }
+
+        let mut pos = event.pos;
+        pos.pitch = 0.0;
+        pos.on_ground = false;
+
+        // TODO: chickens hatched from eggs should be babies
+        let entity = crate::chicken::create().with(pos).build().spawn_in(world);
+        game.handle(world, EntitySpawnEvent { entity });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn hatch_chance() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let trials = 80_000;
+        let hatched = (0..trials)
+            .filter(|_| chickens_to_hatch(&mut rng) > 0)
+            .count();
+
+        // expect about 1/8 of eggs to hatch
+        let expected = trials / 8;
+        assert!(hatched > expected * 9 / 10 && hatched < expected * 11 / 10);
+    }
+}
@@ -0,0 +1,170 @@
+//! Ender pearls, which teleport their thrower to where they land.
+
+use crate::projectile;
+use feather_core::network::packets::PlayerPositionAndLookClientbound;
+use feather_core::network::Packet;
+use feather_core::util::Position;
+use feather_server_types::{
+    BumpVec, DamageCause, EntityDamageRequest, EntitySpawnEvent, FallDistance, Game, Network,
+    Player, PlayerLeaveEvent, Projectile, ProjectileHitEvent, ProjectileKind, SpawnPacketCreator,
+};
+use fecs::{Entity, EntityBuilder, EntityRef, IntoQuery, Read, World};
+use rand::Rng;
+
+/// Damage dealt to players who teleport with an ender pearl.
+const TELEPORT_DAMAGE: f32 = 5.0;
+
+/// Chance for an endermite to spawn where a player teleported from.
+const ENDERMITE_CHANCE: f64 = 0.05;
+
+pub fn create(shooter: Option<Entity>) -> EntityBuilder {
+    projectile::base(ProjectileKind::EnderPearl, shooter)
+        .with(SpawnPacketCreator(&create_spawn_packet))
+}
+
+fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
+    projectile::create_spawn_packet(accessor, 65, 1)
+}
+
+/// Teleports the thrower of an ender pearl when it hits something.
+#[fecs::event_handler]
+pub fn on_projectile_hit_handle_ender_pearl(
+    event: &ProjectileHitEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    if event.kind != ProjectileKind::EnderPearl {
+        return;
+    }
+
+    game.despawn(event.projectile, world);
+
+    let shooter = match event.shooter {
+        Some(shooter) if world.is_alive(shooter) && world.has::<Position>(shooter) => shooter,
+        _ => return,
+    };
+
+    let mut pos = *world.get::<Position>(shooter);
+    let is_player = world.has::<Player>(shooter);
+
+    // Players who teleport leave an endermite behind now and then.
+    if is_player && game.rng().gen_bool(ENDERMITE_CHANCE) {
+        let endermite = crate::endermite::create().with(pos).build().spawn_in(world);
+        game.handle(world, EntitySpawnEvent { entity: endermite });
+    }
+
+    pos.x = event.pos.x;
+    pos.y = event.pos.y;
+    pos.z = event.pos.z;
+    pos.on_ground = false;
+    *world.get_mut::<Position>(shooter) = pos;
+
+    if let Some(network) = world.try_get::<Network>(shooter) {
+        network.send(PlayerPositionAndLookClientbound {
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+            yaw: pos.yaw,
+            pitch: pos.pitch,
+            flags: 0,
+            teleport_id: 0,
+        });
+    }
+
+    if world.has::<FallDistance>(shooter) {
+        world.get_mut::<FallDistance>(shooter).0 = 0.0;
+    }
+    if is_player {
+        game.handle(
+            world,
+            EntityDamageRequest {
+                entity: shooter,
+                damage: TELEPORT_DAMAGE,
+                cause: DamageCause::Fall,
+            },
+        );
+    }
+}
+
+/// Removes the ender pearls thrown by a player who leaves,
+/// since there is no longer anyone for them to teleport.
+#[fecs::event_handler]
+pub fn on_player_leave_remove_ender_pearls(
+    event: &PlayerLeaveEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    let mut pearls = BumpVec::new_in(game.bump());
+    for (entity, projectile) in <Read<Projectile>>::query().iter_entities(world.inner()) {
+        if projectile.kind == ProjectileKind::EnderPearl && projectile.shooter == Some(event.player)
+        {
+            pearls.push(entity);
+        }
+    }
+
+    for pearl in pearls {
+        game.despawn(pearl, world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arrow::{self, ArrowPickup, ArrowProperties};
+    use feather_core::position;
+    use feather_core::util::BlockPosition;
+    use feather_server_types::ProjectileTarget;
+    use feather_test_framework::Test;
+
+    #[test]
+    fn pearl_teleports_thrower() {
+        let mut test = Test::new();
+        let player = test.player("player", position!(0.0, 64.0, 0.0));
+        let pearl = test.entity(create(Some(player)).with(position!(0.0, 70.0, 0.0)));
+
+        let landing = position!(10.0, 65.0, -3.0);
+        test.handle(
+            ProjectileHitEvent {
+                projectile: pearl,
+                kind: ProjectileKind::EnderPearl,
+                shooter: Some(player),
+                target: ProjectileTarget::Block(BlockPosition::new(10, 64, -3)),
+                pos: landing,
+                velocity: glm::vec3(0.0, -1.0, 0.0),
+            },
+            on_projectile_hit_handle_ender_pearl,
+        );
+
+        test.assert_dead(pearl);
+        let pos = *test.world.get::<Position>(player);
+        assert_eq!((pos.x, pos.y, pos.z), (landing.x, landing.y, landing.z));
+    }
+
+    #[test]
+    fn pearls_removed_on_leave() {
+        let mut test = Test::new();
+        let player = test.player("player", position!(0.0, 64.0, 0.0));
+        let other = test.player("other", position!(0.0, 64.0, 0.0));
+
+        let pos = position!(0.0, 70.0, 0.0);
+        let pearl = test.entity(create(Some(player)).with(pos));
+        let other_pearl = test.entity(create(Some(other)).with(pos));
+        let arrow = test.entity(
+            arrow::create(
+                Some(player),
+                ArrowPickup::Allowed,
+                ArrowProperties::default(),
+            )
+            .with(pos),
+        );
+
+        test.handle(
+            PlayerLeaveEvent { player },
+            on_player_leave_remove_ender_pearls,
+        );
+
+        test.assert_dead(pearl);
+        test.assert_alive(other_pearl);
+        test.assert_alive(arrow);
+    }
+}
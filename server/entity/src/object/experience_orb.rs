@@ -0,0 +1,83 @@
+//! Experience orbs, which are dropped by entities when they die.
+
+use feather_core::network::packets::SpawnExperienceOrb;
+use feather_core::network::Packet;
+use feather_core::util::Position;
+use feather_server_types::{EntityId, EntityType, PhysicsBuilder, SpawnPacketCreator};
+use fecs::{EntityBuilder, EntityRef};
+
+/// Orb values, largest first. Experience is split into
+/// orbs of these values, as in vanilla.
+const ORB_VALUES: [u32; 11] = [2477, 1237, 617, 307, 149, 73, 37, 17, 7, 3, 1];
+
+/// Component for an experience orb, storing the
+/// number of experience points it is worth.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExperienceOrb(pub u32);
+
+/// Returns the value of the largest orb which fits
+/// into `experience` points.
+pub fn orb_value(experience: u32) -> u32 {
+    ORB_VALUES
+        .iter()
+        .copied()
+        .find(|&value| value <= experience)
+        .unwrap_or(1)
+}
+
+/// Splits experience into the values of the orbs it is dropped as.
+pub fn split_experience(mut experience: u32) -> impl Iterator<Item = u32> {
+    std::iter::from_fn(move || {
+        if experience == 0 {
+            return None;
+        }
+        let value = orb_value(experience);
+        experience -= value;
+        Some(value)
+    })
+}
+
+// TODO: collecting orbs and despawning them after five minutes
+pub fn create(value: u32) -> EntityBuilder {
+    crate::base()
+        .with(EntityType("minecraft:experience_orb"))
+        .with(ExperienceOrb(value))
+        .with(SpawnPacketCreator(&create_spawn_packet))
+        .with(
+            PhysicsBuilder::new()
+                .bbox(0.5, 0.5, 0.5)
+                .drag(0.98)
+                .gravity(-0.03)
+                .build(),
+        )
+}
+
+fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
+    let position = *accessor.get::<Position>();
+    let entity_id = accessor.get::<EntityId>().0;
+    let value = accessor.get::<ExperienceOrb>().0;
+
+    Box::new(SpawnExperienceOrb {
+        entity_id,
+        x: position.x,
+        y: position.y,
+        z: position.z,
+        count: value.min(i16::max_value() as u32) as i16,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experience_is_split_into_orbs() {
+        assert_eq!(split_experience(0).count(), 0);
+        assert_eq!(split_experience(5).collect::<Vec<_>>(), vec![3, 1, 1]);
+        assert_eq!(
+            split_experience(100).collect::<Vec<_>>(),
+            vec![73, 17, 7, 3]
+        );
+        assert_eq!(split_experience(3000).sum::<u32>(), 3000);
+    }
+}
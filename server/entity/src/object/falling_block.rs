@@ -6,12 +6,12 @@ use feather_core::network::packets::SpawnObject;
 use feather_core::network::Packet;
 use feather_core::util::{BlockPosition, Position};
 use feather_server_types::{
-    BumpVec, EntityId, EntityLandEvent, EntitySpawnEvent, Game, PhysicsBuilder, SpawnPacketCreator,
-    Uuid, Velocity,
+    BlockUpdateCause, BumpVec, EntityId, EntityLandEvent, EntitySpawnEvent, EntityType, Game,
+    PhysicsBuilder, SpawnPacketCreator, Uuid, Velocity,
 };
 use feather_server_util::{
-    degrees_to_stops, protocol_velocity, BlockNotifyBlock, BlockNotifyFallingBlock,
-    BlockNotifyPosition,
+    degrees_to_stops, protocol_velocity, BlockNotifyBlock, BlockNotifyCause,
+    BlockNotifyFallingBlock, BlockNotifyPosition,
 };
 use fecs::{component, EntityBuilder, EntityRef, IntoQuery, Read, World};
 
@@ -23,6 +23,12 @@ pub struct FallingBlock;
 #[derive(Copy, Clone, Debug)]
 pub struct FallingBlockType(pub BlockId);
 
+/// Component storing the cause of the block update which
+/// made a block fall. Used as the cause of the update
+/// when the block lands.
+#[derive(Copy, Clone, Debug)]
+struct FallingBlockCause(BlockUpdateCause);
+
 /// System to create a falling block when a block notify
 /// entity is spawned with `BlockNotifyFallingBlock`.
 #[fecs::system]
@@ -30,26 +36,32 @@ pub fn spawn_falling_blocks(game: &mut Game, world: &mut World) {
     let mut actions = BumpVec::new_in(game.bump());
 
     actions.extend(
-        <(Read<BlockNotifyBlock>, Read<BlockNotifyPosition>)>::query()
-            .filter(component::<BlockNotifyFallingBlock>())
-            .iter_entities(world.inner())
-            .map(|(entity, (block, position))| {
-                let builder = if game.block_at(position.0 - BlockPosition::new(0, 1, 0))
-                    == Some(BlockId::air())
-                {
-                    Some(
-                        create(block.0, position.0)
-                            .with(position.0.position() + position!(0.5, 0.0, 0.5)),
-                    )
-                } else {
-                    None
-                };
-
-                (entity, builder, position.0)
-            }),
+        <(
+            Read<BlockNotifyBlock>,
+            Read<BlockNotifyPosition>,
+            Read<BlockNotifyCause>,
+        )>::query()
+        .filter(component::<BlockNotifyFallingBlock>())
+        .iter_entities(world.inner())
+        .map(|(entity, (block, position, cause))| {
+            let cause = cause.0.neighbor_of();
+            let builder = if game.block_at(position.0 - BlockPosition::new(0, 1, 0))
+                == Some(BlockId::air())
+            {
+                Some(
+                    create(block.0, position.0)
+                        .with(position.0.position() + position!(0.5, 0.0, 0.5))
+                        .with(FallingBlockCause(cause)),
+                )
+            } else {
+                None
+            };
+
+            (entity, builder, position.0, cause)
+        }),
     );
 
-    for (entity_to_delete, entity_builder, block_to_clear) in actions {
+    for (entity_to_delete, entity_builder, block_to_clear, cause) in actions {
         world.despawn(entity_to_delete);
 
         if let Some(entity_builder) = entity_builder {
@@ -61,7 +73,7 @@ pub fn spawn_falling_blocks(game: &mut Game, world: &mut World) {
                 },
             );
 
-            game.set_block_at(world, block_to_clear, BlockId::air());
+            game.set_block_at_with_cause(world, block_to_clear, BlockId::air(), cause);
         }
     }
 }
@@ -79,7 +91,11 @@ pub fn on_entity_land_remove_falling_block(
         .map(|block| block.0)
     {
         let pos = event.pos.block();
-        game.set_block_at(world, pos, block);
+        let cause = world
+            .try_get::<FallingBlockCause>(event.entity)
+            .map(|cause| cause.0)
+            .unwrap_or_else(|| BlockUpdateCause::entity(event.entity));
+        game.set_block_at_with_cause(world, pos, block, cause);
 
         game.despawn(event.entity, world);
     }
@@ -91,6 +107,7 @@ pub fn create(ty: BlockId, spawn_pos: BlockPosition) -> EntityBuilder {
         EntityMetadata::entity_base().with(META_INDEX_FALLING_BLOCK_SPAWN_POSITION, spawn_pos);
 
     crate::base()
+        .with(EntityType("minecraft:falling_block"))
         .with(FallingBlock)
         .with(FallingBlockType(ty))
         .with(SpawnPacketCreator(&create_spawn_packet))
@@ -130,3 +147,50 @@ fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
 
     Box::new(packet)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::chunk::Chunk;
+    use feather_core::util::ChunkPosition;
+    use feather_server_types::BlockUpdateEvent;
+    use feather_server_util::on_block_update_notify_adjacent;
+    use feather_test_framework::Test;
+
+    #[test]
+    fn falling_blocks_remember_the_original_cause() {
+        let mut test = Test::new();
+        test.game
+            .chunk_map
+            .insert(Chunk::new(ChunkPosition::new(0, 0)));
+        let sand = BlockPosition::new(0, 65, 0);
+        test.game.chunk_map.set_block_at(sand, BlockId::sand());
+
+        let player = test.player("digger", position!(0.5, 66.0, 0.5));
+        let cause = BlockUpdateCause::entity(player);
+        test.handle(
+            BlockUpdateEvent {
+                pos: BlockPosition::new(0, 64, 0),
+                old: BlockId::stone(),
+                new: BlockId::air(),
+                cause,
+            },
+            on_block_update_notify_adjacent,
+        );
+        test.run(spawn_falling_blocks);
+
+        assert_eq!(test.game.block_at(sand), Some(BlockId::air()));
+        let causes: Vec<_> = <Read<FallingBlockCause>>::query()
+            .iter(test.world.inner())
+            .map(|cause| cause.0)
+            .collect();
+        assert_eq!(
+            causes,
+            vec![BlockUpdateCause {
+                entity: Some(player),
+                neighbor_update: true,
+                batch: cause.batch,
+            }]
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Firework rockets, which are either launched from the ground
+//! or used to boost players gliding with an elytra. Boosting
+//! rockets stay attached to the player. Both kinds explode at the
+//! end of their flight, damaging nearby entities if the rocket
+//! was crafted with firework stars.
+
+use feather_core::entitymeta::{
+    EntityMetadata, META_INDEX_FIREWORK_BOOSTED_ENTITY, META_INDEX_FIREWORK_ITEM,
+};
+use feather_core::items::ItemStack;
+use feather_core::network::packets::{EntityStatus, SpawnObject};
+use feather_core::network::Packet;
+use feather_core::util::Position;
+use feather_server_types::{
+    protocol_velocity, BumpVec, DamageCause, EntityDamageRequest, EntityId, EntityType, Game,
+    Health, PhysicsBuilder, SpawnPacketCreator, Uuid, Velocity,
+};
+use feather_server_util::nearby_entities;
+use fecs::{Entity, EntityBuilder, EntityRef, IntoQuery, World, Write};
+use rand::Rng;
+
+/// Object type of firework rockets in the `SpawnObject` packet.
+const OBJECT_TYPE: i8 = 76;
+
+/// Entity status which makes clients show a firework's explosion.
+const STATUS_EXPLODE: i8 = 17;
+
+/// Horizontal velocity of launched rockets is
+/// multiplied by this factor each tick.
+const HORIZONTAL_ACCELERATION: f64 = 1.15;
+/// Added to the vertical velocity of launched rockets each tick.
+const VERTICAL_ACCELERATION: f64 = 0.04;
+
+/// Distance within which an explosion damages entities.
+const EXPLOSION_RADIUS: f64 = 5.0;
+
+/// Component for a firework rocket.
+#[derive(Copy, Clone, Debug)]
+pub struct FireworkRocket {
+    /// The gliding entity boosted by the rocket.
+    pub boosted: Option<Entity>,
+    pub ticks_remaining: u32,
+    /// Number of explosions from the rocket's firework stars.
+    pub explosions: u32,
+}
+
+/// Returns the number of ticks a rocket with the given
+/// flight duration flies before exploding.
+pub fn firework_lifetime(flight_duration: u32, rng: &mut impl Rng) -> u32 {
+    10 * (flight_duration + 1) + rng.gen_range(0, 6) + rng.gen_range(0, 7)
+}
+
+/// Returns the damage dealt by an explosion with the given number
+/// of firework stars to an entity `distance` blocks away.
+pub fn explosion_damage(explosions: u32, distance: f64) -> f32 {
+    if explosions == 0 || distance > EXPLOSION_RADIUS {
+        return 0.0;
+    }
+    let damage = 5.0 + 2.0 * explosions as f64;
+    (damage * ((EXPLOSION_RADIUS - distance) / EXPLOSION_RADIUS).sqrt()) as f32
+}
+
+fn rocket(game: &Game, stack: &ItemStack, boosted: Option<Entity>) -> FireworkRocket {
+    FireworkRocket {
+        boosted,
+        ticks_remaining: firework_lifetime(stack.flight_duration(), &mut *game.rng()),
+        explosions: stack.firework_explosions().len() as u32,
+    }
+}
+
+/// Returns an `EntityBuilder` for a firework rocket boosting the given entity.
+pub fn create(game: &Game, world: &World, stack: ItemStack, boosted: Entity) -> EntityBuilder {
+    let rocket = rocket(game, &stack, Some(boosted));
+    let meta = EntityMetadata::entity_base()
+        .with(META_INDEX_FIREWORK_ITEM, Some(stack))
+        .with(
+            META_INDEX_FIREWORK_BOOSTED_ENTITY,
+            world.get::<EntityId>(boosted).0,
+        );
+
+    crate::base()
+        .with(EntityType("minecraft:firework_rocket"))
+        .with(rocket)
+        .with(*world.get::<Position>(boosted))
+        .with(SpawnPacketCreator(&create_spawn_packet))
+        .with(meta)
+}
+
+/// Returns an `EntityBuilder` for a firework rocket launched
+/// upwards from the given position.
+pub fn create_launched(game: &Game, stack: ItemStack, pos: Position) -> EntityBuilder {
+    let rocket = rocket(game, &stack, None);
+    // A slight horizontal velocity, which grows as the rocket
+    // accelerates, makes rockets drift off to the side.
+    let velocity = {
+        let mut rng = game.rng();
+        glm::vec3(
+            rng.gen_range(-0.002, 0.002),
+            0.05,
+            rng.gen_range(-0.002, 0.002),
+        )
+    };
+    let meta = EntityMetadata::entity_base().with(META_INDEX_FIREWORK_ITEM, Some(stack));
+
+    // Rockets accelerate by themselves in `update_firework_rockets`,
+    // so physics only has to stop them at blocks.
+    let physics = PhysicsBuilder::new()
+        .bbox(0.25, 0.25, 0.25)
+        .drag(1.0)
+        .gravity(0.0)
+        .slip_multiplier(1.0)
+        .build();
+
+    crate::base()
+        .with(EntityType("minecraft:firework_rocket"))
+        .with(rocket)
+        .with(pos)
+        .with(Velocity(velocity))
+        .with(physics)
+        .with(SpawnPacketCreator(&create_spawn_packet))
+        .with(meta)
+}
+
+fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
+    let position = *accessor.get::<Position>();
+    let entity_id = accessor.get::<EntityId>().0;
+    let (velocity_x, velocity_y, velocity_z) = protocol_velocity(accessor.get::<Velocity>().0);
+
+    Box::new(SpawnObject {
+        entity_id,
+        object_uuid: Uuid::new_v4(),
+        ty: OBJECT_TYPE,
+        x: position.x,
+        y: position.y,
+        z: position.z,
+        pitch: 0,
+        yaw: 0,
+        data: 0,
+        velocity_x,
+        velocity_y,
+        velocity_z,
+    })
+}
+
+/// System which accelerates launched rockets, keeps boosting
+/// rockets with the entity they boost and explodes rockets at
+/// the end of their flight.
+///
+/// Clients apply the boost to their own player, so the
+/// server only needs to track where the rocket is.
+#[fecs::system]
+pub fn update_firework_rockets(game: &mut Game, world: &mut World) {
+    let mut moved = BumpVec::new_in(game.bump());
+    let mut exploded = BumpVec::new_in(game.bump());
+
+    for (entity, (mut rocket, mut velocity)) in
+        <(Write<FireworkRocket>, Write<Velocity>)>::query().iter_entities_mut(world.inner_mut())
+    {
+        if rocket.ticks_remaining == 0 {
+            exploded.push((entity, *rocket));
+            continue;
+        }
+        rocket.ticks_remaining -= 1;
+
+        match rocket.boosted {
+            Some(boosted) => moved.push((entity, boosted)),
+            None => {
+                velocity.0.x *= HORIZONTAL_ACCELERATION;
+                velocity.0.z *= HORIZONTAL_ACCELERATION;
+                velocity.0.y += VERTICAL_ACCELERATION;
+            }
+        }
+    }
+
+    for (entity, boosted) in moved {
+        let pos = if world.is_alive(boosted) {
+            world.try_get::<Position>(boosted).map(|pos| *pos)
+        } else {
+            None
+        };
+        match pos {
+            Some(pos) => *world.get_mut::<Position>(entity) = pos,
+            None => world.get_mut::<FireworkRocket>(entity).boosted = None,
+        }
+    }
+
+    for (entity, rocket) in exploded {
+        explode(game, world, entity, rocket);
+        game.broadcast_entity_update(
+            world,
+            EntityStatus {
+                entity_id: world.get::<EntityId>(entity).0,
+                entity_status: STATUS_EXPLODE,
+            },
+            entity,
+            None,
+        );
+        game.despawn(entity, world);
+    }
+}
+
+/// Damages the entities around an exploding rocket.
+fn explode(game: &mut Game, world: &mut World, entity: Entity, rocket: FireworkRocket) {
+    if rocket.explosions == 0 {
+        return;
+    }
+
+    let pos = *world.get::<Position>(entity);
+    let radius = glm::vec3(EXPLOSION_RADIUS, EXPLOSION_RADIUS, EXPLOSION_RADIUS);
+    let mut damaged: BumpVec<(Entity, f32)> = BumpVec::new_in(game.bump());
+
+    // The boosted entity takes the full damage
+    // however far it has moved from the rocket.
+    if let Some(boosted) = rocket.boosted {
+        damaged.push((boosted, explosion_damage(rocket.explosions, 0.0)));
+    }
+
+    // TODO: only damage entities in line of sight of the rocket
+    for other in nearby_entities(world, game, pos, radius) {
+        if Some(other) == rocket.boosted || !world.has::<Health>(other) {
+            continue;
+        }
+        let distance = world.get::<Position>(other).distance_squared_to(pos).sqrt();
+        let damage = explosion_damage(rocket.explosions, distance);
+        if damage > 0.0 {
+            damaged.push((other, damage));
+        }
+    }
+
+    for (entity, damage) in damaged {
+        game.handle(
+            world,
+            EntityDamageRequest {
+                entity,
+                damage,
+                cause: DamageCause::Firework,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifetime_grows_with_flight_duration() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            assert!((10..=21).contains(&firework_lifetime(0, &mut rng)));
+            assert!((30..=41).contains(&firework_lifetime(2, &mut rng)));
+        }
+    }
+
+    #[test]
+    fn explosion_damage_falls_off() {
+        assert_eq!(explosion_damage(0, 0.0), 0.0);
+        assert_eq!(explosion_damage(2, 0.0), 9.0);
+        assert!(explosion_damage(2, 2.5) < 9.0);
+        assert_eq!(explosion_damage(2, 5.0), 0.0);
+        assert_eq!(explosion_damage(2, 6.0), 0.0);
+    }
+}
@@ -1,21 +1,28 @@
 //! Handling of item entities.
 
+use crate::experience_orb::{self, split_experience};
+use crate::Age;
 use feather_core::anvil::entity::{
     BaseEntityData, EntityData, EntityDataKind, ItemData, ItemEntityData,
 };
+use feather_core::blocks::BlockId;
 use feather_core::entitymeta::{EntityMetadata, META_INDEX_ITEM_SLOT};
-use feather_core::inventory::Inventory;
+use feather_core::inventory::{max_size, Inventory};
 use feather_core::items::{Item, ItemStack};
-use feather_core::network::packets::SpawnObject;
+use feather_core::network::packets::{Effect, SpawnObject};
 use feather_core::network::Packet;
-use feather_core::util::{Position, Vec3d};
+use feather_core::util::{BlockPosition, ChunkPosition, Gamemode, Position, Vec3d};
 use feather_server_types::{
-    ComponentSerializer, EntityId, EntityLoaderRegistration, EntitySpawnEvent, Game,
-    InventoryUpdateEvent, ItemCollectEvent, ItemDropEvent, PhysicsBuilder, Player,
-    SpawnPacketCreator, Uuid, Velocity, PLAYER_EYE_HEIGHT, TPS,
+    BlockDestroyRequest, ComponentSerializer, DeathDropsRequest, EntityCategory, EntityId,
+    EntityLoaderRegistration, EntitySpawnEvent, EntityType, Game, InventoryUpdateEvent,
+    ItemCollectEvent, ItemDropEvent, PhysicsBuilder, Player, SpawnPacketCreator, Uuid, Velocity,
+    DO_TILE_DROPS, PLAYER_EYE_HEIGHT, TPS,
 };
-use feather_server_util::{degrees_to_stops, nearby_entities, protocol_velocity};
-use fecs::{component, EntityBuilder, EntityRef, IntoQuery, Read, World, Write};
+use feather_server_util::{
+    block_drops, can_interact, degrees_to_stops, entities_of_category, entity_limit,
+    nearby_entities, protocol_velocity,
+};
+use fecs::{component, Entity, EntityBuilder, EntityRef, IntoQuery, Read, World, Write};
 use parking_lot::Mutex;
 use rand::Rng;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -26,11 +33,17 @@ use std::sync::atomic::{AtomicBool, Ordering};
 pub struct CollectableAt(u64);
 
 /// Component used to store whether an item has been collected/
-/// removed on a given tick. Used by `item_collect` and `item_merge`
-/// systems.
+/// removed on a given tick. Used by the `item_collect` system.
 #[derive(Debug)]
 struct IsRemoved(AtomicBool);
 
+/// Interval in ticks at which nearby items are merged.
+const MERGE_INTERVAL: u64 = TPS / 2;
+
+/// Maximum distance along each axis between two items
+/// which will be merged.
+const MERGE_RADIUS: (f64, f64, f64) = (0.5, 0.25, 0.5);
+
 inventory::submit! {
     EntityLoaderRegistration::new(EntityDataKind::Item, &load)
 }
@@ -70,7 +83,34 @@ pub fn on_item_drop_spawn_item_entity(event: &ItemDropEvent, game: &mut Game, wo
 
     drop(rng);
 
-    let entity = create(event.stack, game.tick_count + TPS)
+    make_room_for_item(game, world, pos.chunk());
+
+    let entity = create(event.stack.clone(), game.tick_count + TPS)
+        .with(pos)
+        .with(Velocity(velocity))
+        .build()
+        .spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity });
+}
+
+/// Spawns an item entity for a block which was broken at `pos`,
+/// e.g. a torch which lost its support.
+pub fn drop_block_item(game: &mut Game, world: &mut World, pos: BlockPosition, stack: ItemStack) {
+    let (offset, velocity) = {
+        let mut rng = game.rng();
+        let offset = glm::vec3(
+            rng.gen_range(0.25, 0.75),
+            rng.gen_range(0.25, 0.75),
+            rng.gen_range(0.25, 0.75),
+        );
+        let velocity = glm::vec3(rng.gen_range(-0.1, 0.1), 0.2, rng.gen_range(-0.1, 0.1));
+        (offset, velocity)
+    };
+
+    let pos = pos.position() + offset;
+    make_room_for_item(game, world, pos.chunk());
+
+    let entity = create(stack, game.tick_count + TPS / 2)
         .with(pos)
         .with(Velocity(velocity))
         .build()
@@ -78,6 +118,224 @@ pub fn on_item_drop_spawn_item_entity(event: &ItemDropEvent, game: &mut Game, wo
     game.handle(world, EntitySpawnEvent { entity });
 }
 
+/// Drops an item at the given position with a small random
+/// velocity, as when an item is knocked out of an item frame.
+pub fn drop_item(game: &mut Game, world: &mut World, pos: Position, stack: ItemStack) {
+    let velocity = {
+        let mut rng = game.rng();
+        glm::vec3(rng.gen_range(-0.1, 0.1), 0.2, rng.gen_range(-0.1, 0.1))
+    };
+
+    make_room_for_item(game, world, pos.chunk());
+
+    let entity = create(stack, game.tick_count + TPS / 2)
+        .with(pos)
+        .with(Velocity(velocity))
+        .build()
+        .spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity });
+}
+
+/// World event which displays a block's breaking particles and sound.
+const EFFECT_BLOCK_BREAK: i32 = 2001;
+
+/// Handles a `BlockDestroyRequest`.
+#[fecs::event_handler]
+pub fn block_destroy_request(event: &BlockDestroyRequest, game: &mut Game, world: &mut World) {
+    let block = match game.block_at(event.pos) {
+        Some(block) if !block.is_air() => block,
+        _ => return,
+    };
+    game.set_block_at_with_cause(world, event.pos, BlockId::air(), event.cause);
+
+    game.broadcast_chunk_update(
+        world,
+        Effect {
+            effect_id: EFFECT_BLOCK_BREAK,
+            location: event.pos,
+            data: block.vanilla_id() as i32,
+            disable_relative_volume: false,
+        },
+        event.pos.chunk(),
+        None,
+    );
+
+    if game.game_rules.bool(DO_TILE_DROPS) {
+        let drops = block_drops(block, None, &mut *game.rng());
+        for stack in drops {
+            drop_block_item(game, world, event.pos, stack);
+        }
+    }
+}
+
+/// Handles a `DeathDropsRequest`.
+#[fecs::event_handler]
+pub fn death_drops_request(event: &DeathDropsRequest, game: &mut Game, world: &mut World) {
+    let mut pos = event.position;
+    pos.on_ground = false;
+
+    for stack in &event.items {
+        let (pos, velocity, collectable_at) = {
+            let mut rng = game.rng();
+            if event.scatter {
+                // Players drop their inventory from their eyes,
+                // scattered in all directions.
+                let speed = rng.gen_range(0.0, 0.5);
+                let angle = rng.gen_range(0.0, 2.0 * std::f64::consts::PI);
+                (
+                    pos + glm::vec3(0.0, PLAYER_EYE_HEIGHT - 0.3, 0.0),
+                    glm::vec3(-angle.sin() * speed, 0.2, angle.cos() * speed),
+                    game.tick_count + TPS * 2,
+                )
+            } else {
+                (
+                    pos,
+                    glm::vec3(rng.gen_range(-0.1, 0.1), 0.2, rng.gen_range(-0.1, 0.1)),
+                    game.tick_count + TPS / 2,
+                )
+            }
+        };
+
+        make_room_for_item(game, world, pos.chunk());
+
+        let entity = create(stack.clone(), collectable_at)
+            .with(pos)
+            .with(Velocity(velocity))
+            .build()
+            .spawn_in(world);
+        game.handle(world, EntitySpawnEvent { entity });
+    }
+
+    for value in split_experience(event.experience) {
+        let velocity = {
+            let mut rng = game.rng();
+            glm::vec3(
+                rng.gen_range(-0.2, 0.2),
+                rng.gen_range(0.0, 0.4),
+                rng.gen_range(-0.2, 0.2),
+            )
+        };
+
+        let entity = experience_orb::create(value)
+            .with(pos)
+            .with(Velocity(velocity))
+            .build()
+            .spawn_in(world);
+        game.handle(world, EntitySpawnEvent { entity });
+    }
+}
+
+/// Despawns the oldest items in a chunk so that a new
+/// item can be spawned without exceeding the chunk's item limit.
+fn make_room_for_item(game: &mut Game, world: &mut World, chunk: ChunkPosition) {
+    let limit = match entity_limit(game, EntityCategory::Item) {
+        Some(limit) => limit,
+        None => return,
+    };
+
+    // Entity IDs are allocated sequentially, so lower IDs are older.
+    let mut items: Vec<_> = entities_of_category(game, world, chunk, EntityCategory::Item)
+        .map(|item| (world.get::<EntityId>(item).0, item))
+        .collect();
+    if items.len() < limit {
+        return;
+    }
+    items.sort_unstable();
+
+    let excess = items.len() + 1 - limit;
+    log::debug!(
+        "Item limit reached in chunk {}; removing {} items",
+        chunk,
+        excess
+    );
+    for (_, item) in items.into_iter().take(excess) {
+        game.despawn(item, world);
+    }
+}
+
+/// System which merges nearby items holding the same stack
+/// into a single entity, so that mining or dropping many items
+/// in one place does not spawn an entity for each.
+///
+/// The item with the larger stack absorbs the other, and
+/// items are only merged if the result fits in one stack.
+#[fecs::system]
+pub fn item_merge(game: &mut Game, world: &mut World) {
+    if game.tick_count % MERGE_INTERVAL != 0 {
+        return;
+    }
+
+    let items: Vec<Entity> = <Read<CollectableAt>>::query()
+        .iter_entities(world.inner())
+        .map(|(item, _)| item)
+        .collect();
+
+    let (x, y, z) = MERGE_RADIUS;
+    for item in items {
+        if !world.is_alive(item) {
+            continue;
+        }
+        let pos = *world.get::<Position>(item);
+
+        for other in nearby_entities(world, game, pos, glm::vec3(x, y, z)) {
+            if other == item || !world.is_alive(other) || !world.has::<CollectableAt>(other) {
+                continue;
+            }
+
+            if let Some(merged) = merge_items(world, item, other) {
+                game.despawn(merged, world);
+                if merged == item {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Merges two items if their stacks can be combined.
+/// Returns the item which was absorbed and should be despawned.
+fn merge_items(world: &mut World, first: Entity, second: Entity) -> Option<Entity> {
+    let (into, from) = {
+        let first_stack = world.get::<ItemStack>(first);
+        let second_stack = world.get::<ItemStack>(second);
+        if !first_stack.stacks_with(&second_stack)
+            || first_stack.amount as u32 + second_stack.amount as u32
+                > max_size(first_stack.ty) as u32
+        {
+            return None;
+        }
+
+        if first_stack.amount >= second_stack.amount {
+            (first, second)
+        } else {
+            (second, first)
+        }
+    };
+
+    let amount = world.get::<ItemStack>(from).amount;
+    let collectable_at = *world.get::<CollectableAt>(from);
+    let age = *world.get::<Age>(from);
+
+    let stack = {
+        let mut stack = world.get_mut::<ItemStack>(into);
+        stack.amount += amount;
+        stack.clone()
+    };
+    world
+        .get_mut::<EntityMetadata>(into)
+        .set(META_INDEX_ITEM_SLOT, Some(stack));
+
+    // The merged item may not be picked up before either item
+    // could, and it despawns when the newer item would have.
+    let into_collectable_at = world.get::<CollectableAt>(into).0.max(collectable_at.0);
+    world.get_mut::<CollectableAt>(into).0 = into_collectable_at;
+    let mut into_age = world.get_mut::<Age>(into);
+    into_age.ticks = into_age.ticks.min(age.ticks);
+    into_age.infinite |= age.infinite;
+
+    Some(from)
+}
+
 /// System to add items to player inventories when the player comes near.
 #[fecs::system]
 pub fn item_collect(game: &mut Game, world: &mut World) {
@@ -98,6 +356,11 @@ pub fn item_collect(game: &mut Game, world: &mut World) {
         <(Read<Position>, Write<Inventory>)>::query()
             .filter(component::<Player>())
             .par_entities_for_each_unchecked(world.inner(), |(player, (pos, mut inventory))| {
+                // Spectators pass through items without collecting them.
+                if !can_interact(*world.get::<Gamemode>(player)) {
+                    return;
+                }
+
                 let inventory: &mut Inventory = &mut *inventory;
 
                 let nearby_entities = nearby_entities(world, game, *pos, glm::vec3(1.0, 1.0, 1.0));
@@ -105,7 +368,7 @@ pub fn item_collect(game: &mut Game, world: &mut World) {
                     world
                         .try_get::<CollectableAt>(*entity)
                         .map(|collectable_at| {
-                            if collectable_at.0 <= game.time.world_age() {
+                            if collectable_at.0 <= game.tick_count {
                                 Some(*entity)
                             } else {
                                 None
@@ -126,10 +389,18 @@ pub fn item_collect(game: &mut Game, world: &mut World) {
                         // we now have unique access to this item and its components.
                         let mut stack = world.get_mut_unchecked::<ItemStack>(item);
 
-                        let (slots, stack_remaining) = inventory.collect_item(*stack);
+                        let (slots, stack_remaining) = inventory.collect_item(stack.clone());
 
                         let initial_remaining = stack.amount;
 
+                        if stack_remaining == initial_remaining {
+                            // The inventory is full, so leave
+                            // the item for another player.
+                            drop(stack);
+                            is_removed.0.store(false, Ordering::Release);
+                            continue;
+                        }
+
                         let event = InventoryUpdateEvent { slots, player };
                         inventory_update_events.lock().push(event);
 
@@ -140,7 +411,7 @@ pub fn item_collect(game: &mut Game, world: &mut World) {
                             stack.amount = stack_remaining;
                             world
                                 .get_mut_unchecked::<EntityMetadata>(item)
-                                .set(META_INDEX_ITEM_SLOT, Some(*stack));
+                                .set(META_INDEX_ITEM_SLOT, Some(stack.clone()));
                         }
 
                         item_collect_events.lock().push(ItemCollectEvent {
@@ -173,13 +444,16 @@ pub fn item_collect(game: &mut Game, world: &mut World) {
 /// Returns an entity builder to create an item entity
 /// with the given stack and collectable tick.
 pub fn create(stack: ItemStack, collectable_at: u64) -> EntityBuilder {
-    let meta = EntityMetadata::entity_base().with(META_INDEX_ITEM_SLOT, Some(stack));
+    let meta = EntityMetadata::entity_base().with(META_INDEX_ITEM_SLOT, Some(stack.clone()));
     let collectable_at = CollectableAt(collectable_at);
 
     crate::base()
+        .with(EntityType("minecraft:item"))
         .with(stack)
+        .with(EntityCategory::Item)
         .with(IsRemoved(AtomicBool::new(false)))
         .with(collectable_at)
+        .with(Age::default())
         .with(SpawnPacketCreator(&create_spawn_packet))
         .with(ComponentSerializer(&serialize))
         .with(meta)
@@ -222,12 +496,13 @@ fn serialize(game: &Game, accessor: &EntityRef) -> EntityData {
     let item = accessor.get::<ItemStack>();
     EntityData::Item(ItemEntityData {
         entity: BaseEntityData::new(*accessor.get::<Position>(), Vec3d::new(vel.x, vel.y, vel.z)),
-        age: 0, // todo
+        age: accessor.get::<Age>().to_nbt(),
         pickup_delay: (accessor.get::<CollectableAt>().0 as i64 - game.tick_count as i64).max(0)
             as u8,
         item: ItemData {
             count: item.amount,
             item: item.ty.identifier().to_owned(),
+            nbt: item.nbt().cloned(),
         },
     })
 }
@@ -238,18 +513,136 @@ fn load(data: EntityData) -> anyhow::Result<EntityBuilder> {
             let pos = data.entity.read_position()?;
             let vel = data.entity.read_velocity()?;
 
-            let stack = ItemStack::new(
+            let mut stack = ItemStack::new(
                 Item::from_identifier(&data.item.item)
                     .ok_or_else(|| anyhow::anyhow!("invalid item {}", data.item.item))?,
                 data.item.count,
             );
+            if let Some(nbt) = data.item.nbt {
+                stack.set_nbt(nbt);
+            }
 
             let collectable_at = data.pickup_delay;
 
             Ok(create(stack, collectable_at as u64)
+                .with(Age::from_nbt(data.age))
                 .with(pos)
                 .with(Velocity(glm::vec3(vel.x, vel.y, vel.z))))
         }
         _ => panic!("attempted to use item::load to load a non-item"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_server_util::on_entity_spawn_update_chunk_entities;
+    use feather_test_framework::Test;
+    use std::sync::Arc;
+
+    fn spawn_item(test: &mut Test, stack: ItemStack, collectable_at: u64, pos: Position) -> Entity {
+        let entity = test.entity(create(stack, collectable_at).with(pos));
+        test.handle(
+            EntitySpawnEvent { entity },
+            on_entity_spawn_update_chunk_entities,
+        );
+        entity
+    }
+
+    fn spawn_stone(test: &mut Test, x: f64) -> Entity {
+        spawn_item(
+            test,
+            ItemStack::new(Item::Stone, 1),
+            0,
+            position!(x, 64.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn nearby_items_merge() {
+        let mut test = Test::new();
+        let small = spawn_item(
+            &mut test,
+            ItemStack::new(Item::Stone, 3),
+            10,
+            position!(0.0, 64.0, 0.0),
+        );
+        let large = spawn_item(
+            &mut test,
+            ItemStack::new(Item::Stone, 5),
+            0,
+            position!(0.3, 64.0, 0.0),
+        );
+        let other = spawn_item(
+            &mut test,
+            ItemStack::new(Item::Dirt, 5),
+            0,
+            position!(0.0, 64.0, 0.3),
+        );
+
+        test.run(item_merge);
+
+        test.assert_dead(small);
+        test.assert_alive(large).assert_alive(other);
+        assert_eq!(test.world.get::<ItemStack>(large).amount, 8);
+        assert_eq!(*test.world.get::<CollectableAt>(large), CollectableAt(10));
+        assert_eq!(test.world.get::<ItemStack>(other).amount, 5);
+    }
+
+    #[test]
+    fn full_stacks_do_not_merge() {
+        let mut test = Test::new();
+        let first = spawn_item(
+            &mut test,
+            ItemStack::new(Item::Stone, 60),
+            0,
+            position!(0.0, 64.0, 0.0),
+        );
+        let second = spawn_item(
+            &mut test,
+            ItemStack::new(Item::Stone, 5),
+            0,
+            position!(0.3, 64.0, 0.0),
+        );
+
+        test.run(item_merge);
+
+        test.assert_alive(first).assert_alive(second);
+        assert_eq!(test.world.get::<ItemStack>(first).amount, 60);
+    }
+
+    #[test]
+    fn full_chunk_removes_oldest_items() {
+        let mut test = Test::new();
+        Arc::make_mut(&mut test.game.config).entity_limits.items = 2;
+        let oldest = spawn_stone(&mut test, 1.0);
+        let newest = spawn_stone(&mut test, 2.0);
+        let elsewhere = spawn_stone(&mut test, 20.0);
+
+        make_room_for_item(&mut test.game, &mut test.world, ChunkPosition::new(0, 0));
+
+        test.assert_dead(oldest)
+            .assert_alive(newest)
+            .assert_alive(elsewhere);
+    }
+
+    #[test]
+    fn items_within_limit_are_kept() {
+        let mut test = Test::new();
+        Arc::make_mut(&mut test.game.config).entity_limits.items = 2;
+        let item = spawn_stone(&mut test, 1.0);
+
+        make_room_for_item(&mut test.game, &mut test.world, ChunkPosition::new(0, 0));
+        test.assert_alive(item);
+
+        // A limit of 0 disables it.
+        Arc::make_mut(&mut test.game.config).entity_limits.items = 0;
+        let second = spawn_stone(&mut test, 2.0);
+        let third = spawn_stone(&mut test, 3.0);
+
+        make_room_for_item(&mut test.game, &mut test.world, ChunkPosition::new(0, 0));
+        test.assert_alive(item)
+            .assert_alive(second)
+            .assert_alive(third);
+    }
+}
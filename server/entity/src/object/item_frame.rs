@@ -0,0 +1,306 @@
+//! Item frames, which hang on the faces of blocks
+//! and display an item that players put in them.
+
+use crate::item;
+use feather_core::anvil::entity::{
+    BaseEntityData, EntityData, EntityDataKind, ItemData, ItemFrameData,
+};
+use feather_core::entitymeta::{
+    EntityMetadata, META_INDEX_ITEM_FRAME_ITEM, META_INDEX_ITEM_FRAME_ROTATION,
+};
+use feather_core::items::{Item, ItemStack};
+use feather_core::network::packets::{Face, SpawnObject};
+use feather_core::network::Packet;
+use feather_core::util::{BlockPosition, Position, Vec3d};
+use feather_server_types::{
+    ComponentSerializer, EntityCategory, EntityId, EntityLoaderRegistration, EntitySpawnEvent,
+    EntityType, Game, SpawnPacketCreator, Uuid,
+};
+use feather_server_util::can_spawn_in_chunk;
+use fecs::{Entity, EntityBuilder, EntityRef, IntoQuery, Read, World};
+use num_traits::FromPrimitive;
+
+/// Object type of item frames in the `SpawnObject` packet.
+const OBJECT_TYPE: i8 = 71;
+
+/// Number of rotations an item in a frame goes through
+/// before it is back where it started.
+const ROTATIONS: u8 = 8;
+
+/// Distance from the block it hangs on to the center of a frame.
+const FRAME_OFFSET: f64 = 0.5 - 1.0 / 32.0;
+
+inventory::submit! {
+    EntityLoaderRegistration::new(EntityDataKind::ItemFrame, &load)
+}
+
+/// Component for an item frame.
+#[derive(Clone, Debug)]
+pub struct ItemFrame {
+    /// The block the frame is in, next to
+    /// the block it hangs on.
+    pub pos: BlockPosition,
+    /// The face of that block on which the frame hangs.
+    pub facing: Face,
+    pub item: Option<ItemStack>,
+    /// Rotation of the item, in eighths of a full turn.
+    pub rotation: u8,
+}
+
+/// Returns an entity builder to create an item frame.
+pub fn create(frame: ItemFrame) -> EntityBuilder {
+    let offset = frame.facing.placement_offset();
+    let center = frame.pos.position()
+        + glm::vec3(
+            0.5 - f64::from(offset.x) * FRAME_OFFSET,
+            0.5 - f64::from(offset.y) * FRAME_OFFSET,
+            0.5 - f64::from(offset.z) * FRAME_OFFSET,
+        );
+    let meta = EntityMetadata::entity_base()
+        .with(META_INDEX_ITEM_FRAME_ITEM, frame.item.clone())
+        .with(META_INDEX_ITEM_FRAME_ROTATION, i32::from(frame.rotation));
+
+    crate::base()
+        .with(frame)
+        .with(EntityType("minecraft:item_frame"))
+        .with(EntityCategory::ItemFrame)
+        .with(center)
+        .with(SpawnPacketCreator(&create_spawn_packet))
+        .with(ComponentSerializer(&serialize))
+        .with(meta)
+}
+
+/// Hangs a new item frame on the face of the block at `location`.
+/// Returns `None` if the face already has a frame or the chunk
+/// has reached its item frame limit.
+pub fn place(
+    game: &mut Game,
+    world: &mut World,
+    location: BlockPosition,
+    facing: Face,
+) -> Option<Entity> {
+    let pos = location + facing.placement_offset();
+    let occupied = <Read<ItemFrame>>::query()
+        .iter(world.inner())
+        .any(|frame| frame.pos == pos && frame.facing as u8 == facing as u8);
+    if occupied || !can_spawn_in_chunk(game, world, pos.chunk(), EntityCategory::ItemFrame) {
+        return None;
+    }
+
+    let frame = create(ItemFrame {
+        pos,
+        facing,
+        item: None,
+        rotation: 0,
+    })
+    .build()
+    .spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity: frame });
+    Some(frame)
+}
+
+/// Puts an item in an empty frame. Returns `false`
+/// if the frame already holds an item.
+pub fn put_item(world: &mut World, frame: Entity, stack: &ItemStack) -> bool {
+    let mut item_frame = world.get_mut::<ItemFrame>(frame);
+    if item_frame.item.is_some() {
+        return false;
+    }
+
+    let mut stack = stack.clone();
+    stack.amount = 1;
+    item_frame.item = Some(stack.clone());
+    item_frame.rotation = 0;
+    drop(item_frame);
+
+    let mut meta = world.get_mut::<EntityMetadata>(frame);
+    meta.set(META_INDEX_ITEM_FRAME_ITEM, Some(stack));
+    meta.set(META_INDEX_ITEM_FRAME_ROTATION, 0i32);
+    true
+}
+
+/// Turns the item in a frame by an eighth. Returns
+/// `false` if the frame is empty.
+pub fn rotate_item(world: &mut World, frame: Entity) -> bool {
+    let mut item_frame = world.get_mut::<ItemFrame>(frame);
+    if item_frame.item.is_none() {
+        return false;
+    }
+
+    item_frame.rotation = (item_frame.rotation + 1) % ROTATIONS;
+    let rotation = i32::from(item_frame.rotation);
+    drop(item_frame);
+
+    world
+        .get_mut::<EntityMetadata>(frame)
+        .set(META_INDEX_ITEM_FRAME_ROTATION, rotation);
+    true
+}
+
+/// Handles a player hitting an item frame. The first hit
+/// knocks the item out of the frame, and the next breaks
+/// the frame itself. Nothing drops if `drops` is `false`.
+pub fn hit(game: &mut Game, world: &mut World, frame: Entity, drops: bool) {
+    let pos = *world.get::<Position>(frame);
+    let item = world.get_mut::<ItemFrame>(frame).item.take();
+
+    match item {
+        Some(stack) => {
+            world
+                .get_mut::<EntityMetadata>(frame)
+                .set(META_INDEX_ITEM_FRAME_ITEM, None::<ItemStack>);
+            if drops {
+                item::drop_item(game, world, pos, stack);
+            }
+        }
+        None => {
+            game.despawn(frame, world);
+            if drops {
+                item::drop_item(game, world, pos, ItemStack::new(Item::ItemFrame, 1));
+            }
+        }
+    }
+}
+
+fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
+    let frame = accessor.get::<ItemFrame>();
+    let entity_id = accessor.get::<EntityId>().0;
+
+    // Clients place the frame from the block it is
+    // in and the direction it faces.
+    Box::new(SpawnObject {
+        entity_id,
+        object_uuid: Uuid::new_v4(),
+        ty: OBJECT_TYPE,
+        x: f64::from(frame.pos.x),
+        y: f64::from(frame.pos.y),
+        z: f64::from(frame.pos.z),
+        pitch: 0,
+        yaw: 0,
+        data: frame.facing as i32,
+        velocity_x: 0,
+        velocity_y: 0,
+        velocity_z: 0,
+    })
+}
+
+fn serialize(_game: &Game, accessor: &EntityRef) -> EntityData {
+    let frame = accessor.get::<ItemFrame>();
+    EntityData::ItemFrame(ItemFrameData {
+        entity: BaseEntityData::new(*accessor.get::<Position>(), Vec3d::new(0.0, 0.0, 0.0)),
+        tile_x: frame.pos.x,
+        tile_y: frame.pos.y,
+        tile_z: frame.pos.z,
+        facing: frame.facing as u8,
+        item: frame.item.as_ref().map(|stack| ItemData {
+            count: stack.amount,
+            item: stack.ty.identifier().to_owned(),
+            nbt: stack.nbt().cloned(),
+        }),
+        item_rotation: frame.rotation,
+    })
+}
+
+fn load(data: EntityData) -> anyhow::Result<EntityBuilder> {
+    match data {
+        EntityData::ItemFrame(data) => {
+            let pos = BlockPosition::new(data.tile_x, data.tile_y, data.tile_z);
+            let facing = Face::from_u8(data.facing)
+                .ok_or_else(|| anyhow::anyhow!("invalid item frame facing {}", data.facing))?;
+
+            let item = match data.item {
+                Some(item) => {
+                    let mut stack = ItemStack::new(
+                        Item::from_identifier(&item.item)
+                            .ok_or_else(|| anyhow::anyhow!("invalid item {}", item.item))?,
+                        item.count,
+                    );
+                    if let Some(nbt) = item.nbt {
+                        stack.set_nbt(nbt);
+                    }
+                    Some(stack)
+                }
+                None => None,
+            };
+
+            Ok(create(ItemFrame {
+                pos,
+                facing,
+                item,
+                rotation: data.item_rotation % ROTATIONS,
+            }))
+        }
+        _ => panic!("attempted to use item_frame::load to load a non-item frame"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_server_util::on_entity_spawn_update_chunk_entities;
+    use feather_test_framework::Test;
+    use std::sync::Arc;
+
+    #[test]
+    fn placement_respects_limit() {
+        let mut test = Test::new();
+        Arc::make_mut(&mut test.game.config)
+            .entity_limits
+            .item_frames = 1;
+        let location = BlockPosition::new(8, 64, 8);
+
+        let frame = place(&mut test.game, &mut test.world, location, Face::North).unwrap();
+        test.handle(
+            EntitySpawnEvent { entity: frame },
+            on_entity_spawn_update_chunk_entities,
+        );
+        assert_eq!(
+            *test.world.get::<EntityCategory>(frame),
+            EntityCategory::ItemFrame
+        );
+        assert!(place(&mut test.game, &mut test.world, location, Face::South).is_none());
+
+        // Each face holds a single frame even without a limit.
+        Arc::make_mut(&mut test.game.config)
+            .entity_limits
+            .item_frames = 0;
+        assert!(place(&mut test.game, &mut test.world, location, Face::North).is_none());
+        assert!(place(&mut test.game, &mut test.world, location, Face::South).is_some());
+    }
+
+    #[test]
+    fn hits_remove_item_then_frame() {
+        let mut test = Test::new();
+        let frame = test.entity(create(ItemFrame {
+            pos: BlockPosition::new(0, 65, 0),
+            facing: Face::Top,
+            item: None,
+            rotation: 0,
+        }));
+        assert!(!rotate_item(&mut test.world, frame));
+
+        assert!(put_item(
+            &mut test.world,
+            frame,
+            &ItemStack::new(Item::Compass, 5)
+        ));
+        assert!(!put_item(
+            &mut test.world,
+            frame,
+            &ItemStack::new(Item::Stone, 1)
+        ));
+        assert!(rotate_item(&mut test.world, frame));
+        {
+            let item_frame = test.world.get::<ItemFrame>(frame);
+            assert_eq!(item_frame.item, Some(ItemStack::new(Item::Compass, 1)));
+            assert_eq!(item_frame.rotation, 1);
+        }
+
+        hit(&mut test.game, &mut test.world, frame, false);
+        test.assert_alive(frame);
+        assert_eq!(test.world.get::<ItemFrame>(frame).item, None);
+
+        hit(&mut test.game, &mut test.world, frame, false);
+        test.assert_dead(frame);
+    }
+}
@@ -0,0 +1,257 @@
+//! Lightning bolts, which strike during thunderstorms (or when
+//! requested by tridents and commands), start fires, and
+//! transform some of the mobs they hit.
+
+use crate::{creeper, pig, villager, witch, zombie_pigman};
+use feather_core::blocks::BlockId;
+use feather_core::entitymeta::{EntityMetadata, META_INDEX_CREEPER_CHARGED};
+use feather_core::network::packets::SpawnGlobalEntity;
+use feather_core::network::Packet;
+use feather_core::util::{BlockPosition, Position};
+use feather_server_types::{
+    BlockUpdateCause, BumpVec, DamageCause, EntityDamageRequest, EntityId, EntitySpawnEvent,
+    EntityType, Game, LightningStrikeEvent, LightningStrikeRequest, SpawnPacketCreator,
+    DO_FIRE_TICK,
+};
+use feather_server_util::{nearby_entities, set_on_fire};
+use fecs::{Entity, EntityBuilder, EntityRef, IntoQuery, World, Write};
+use rand::Rng;
+
+/// Entity type of lightning bolts in the `SpawnGlobalEntity` packet.
+const GLOBAL_ENTITY_THUNDERBOLT: u8 = 1;
+
+/// Number of ticks a lightning bolt exists before it is despawned.
+const LIFETIME: u32 = 2;
+
+/// Damage dealt to entities struck by lightning.
+const DAMAGE: f32 = 5.0;
+
+/// Number of ticks for which entities struck by lightning burn.
+const BURN_TICKS: u32 = 160;
+
+/// Number of fires started around the point of impact
+/// in addition to the one at the point itself.
+const EXTRA_FIRES: usize = 4;
+
+/// Component for a lightning bolt, storing the number
+/// of ticks until it is despawned.
+#[derive(Copy, Clone, Debug)]
+pub struct LightningBolt {
+    pub ticks_remaining: u32,
+}
+
+pub fn create() -> EntityBuilder {
+    crate::base()
+        .with(EntityType("minecraft:lightning_bolt"))
+        .with(LightningBolt {
+            ticks_remaining: LIFETIME,
+        })
+        .with(SpawnPacketCreator(&create_spawn_packet))
+}
+
+fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
+    let position = *accessor.get::<Position>();
+    let entity_id = accessor.get::<EntityId>().0;
+
+    Box::new(SpawnGlobalEntity {
+        entity_id,
+        ty: GLOBAL_ENTITY_THUNDERBOLT,
+        x: position.x,
+        y: position.y,
+        z: position.z,
+    })
+}
+
+/// Handles a `LightningStrikeRequest`.
+#[fecs::event_handler]
+pub fn lightning_strike_request(
+    event: &LightningStrikeRequest,
+    game: &mut Game,
+    world: &mut World,
+) {
+    strike(game, world, event.pos);
+}
+
+/// Strikes lightning at the given position, returning the bolt.
+pub fn strike(game: &mut Game, world: &mut World, pos: Position) -> Entity {
+    let bolt = create().with(pos).build().spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity: bolt });
+    game.handle(world, LightningStrikeEvent { bolt, pos });
+
+    start_fires(game, world, bolt, pos.block());
+    strike_entities(game, world, pos);
+    bolt
+}
+
+/// Starts fires at and around the block struck by lightning.
+///
+/// As in vanilla, fires are only started on normal
+/// and hard difficulties, and while `doFireTick` is on.
+fn start_fires(game: &mut Game, world: &mut World, bolt: Entity, center: BlockPosition) {
+    if game.level.difficulty < 2 || !game.game_rules.bool(DO_FIRE_TICK) {
+        return;
+    }
+
+    let cause = BlockUpdateCause::entity(bolt);
+    try_place_fire(game, world, center, cause);
+    for _ in 0..EXTRA_FIRES {
+        let offset = {
+            let mut rng = game.rng();
+            BlockPosition::new(
+                rng.gen_range(-1, 2),
+                rng.gen_range(-1, 2),
+                rng.gen_range(-1, 2),
+            )
+        };
+        try_place_fire(game, world, center + offset, cause);
+    }
+}
+
+fn try_place_fire(game: &mut Game, world: &mut World, pos: BlockPosition, cause: BlockUpdateCause) {
+    let replaceable = game.block_at(pos).map(BlockId::is_air).unwrap_or(false);
+    let on_solid = game
+        .block_at(pos - BlockPosition::new(0, 1, 0))
+        .map(BlockId::is_solid)
+        .unwrap_or(false);
+
+    if replaceable && on_solid {
+        game.set_block_at_with_cause(world, pos, BlockId::fire(), cause);
+    }
+}
+
+/// Applies the effects of a lightning strike to nearby entities.
+fn strike_entities(game: &mut Game, world: &mut World, pos: Position) {
+    // Vanilla affects entities within a box extending three
+    // blocks horizontally and nine blocks up from the strike.
+    let center = pos + glm::vec3(0.0, 3.0, 0.0);
+    let radius = glm::vec3(3.0, 6.0, 3.0);
+
+    for entity in nearby_entities(world, game, center, radius) {
+        if world.has::<LightningBolt>(entity) {
+            continue;
+        }
+
+        set_on_fire(world, entity, BURN_TICKS);
+        game.handle(
+            world,
+            EntityDamageRequest {
+                entity,
+                damage: DAMAGE,
+                cause: DamageCause::Lightning,
+            },
+        );
+        if !world.is_alive(entity) {
+            continue;
+        }
+
+        if world.has::<pig::Pig>(entity) {
+            convert(game, world, entity, zombie_pigman::create());
+        } else if world.has::<villager::Villager>(entity) {
+            convert(game, world, entity, witch::create());
+        } else if world.has::<creeper::Creeper>(entity) {
+            charge_creeper(world, entity);
+        }
+    }
+}
+
+/// Replaces a mob with a new one at the same position.
+fn convert(game: &mut Game, world: &mut World, entity: Entity, builder: EntityBuilder) {
+    let pos = *world.get::<Position>(entity);
+    game.despawn(entity, world);
+
+    let converted = builder.with(pos).build().spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity: converted });
+}
+
+/// Turns a creeper into a charged creeper.
+fn charge_creeper(world: &mut World, creeper: Entity) {
+    if world.has::<creeper::Charged>(creeper) {
+        return;
+    }
+    world.add(creeper, creeper::Charged).unwrap();
+
+    if !world.has::<EntityMetadata>(creeper) {
+        world.add(creeper, EntityMetadata::entity_base()).unwrap();
+    }
+    world
+        .get_mut::<EntityMetadata>(creeper)
+        .set(META_INDEX_CREEPER_CHARGED, true);
+}
+
+/// System which despawns lightning bolts at the end of their lifetime.
+#[fecs::system]
+pub fn despawn_lightning_bolts(game: &mut Game, world: &mut World) {
+    let mut to_despawn = BumpVec::new_in(game.bump());
+
+    for (entity, mut bolt) in <Write<LightningBolt>>::query().iter_entities_mut(world.inner_mut()) {
+        if bolt.ticks_remaining == 0 {
+            to_despawn.push(entity);
+        } else {
+            bolt.ticks_remaining -= 1;
+        }
+    }
+
+    for entity in to_despawn {
+        game.despawn(entity, world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::entitymeta::MetaEntry;
+    use feather_test_framework::Test;
+    use fecs::Read;
+
+    #[test]
+    fn bolt_despawns_after_lifetime() {
+        let mut test = Test::new();
+        let bolt = test.entity(create().with(position!(0.0, 64.0, 0.0)));
+
+        for _ in 0..=LIFETIME {
+            test.assert_alive(bolt);
+            test.run(despawn_lightning_bolts);
+        }
+        test.assert_dead(bolt);
+    }
+
+    #[test]
+    fn lightning_converts_pigs() {
+        let mut test = Test::new();
+        let pig = test.entity(pig::create().with(position!(1.0, 64.0, 0.0)));
+
+        test.handle(
+            LightningStrikeRequest {
+                pos: position!(0.0, 64.0, 0.0),
+            },
+            lightning_strike_request,
+        );
+
+        test.assert_dead(pig);
+        let pigmen = <Read<zombie_pigman::ZombiePigman>>::query()
+            .iter(test.world.inner())
+            .count();
+        assert_eq!(pigmen, 1);
+    }
+
+    #[test]
+    fn lightning_charges_creepers() {
+        let mut test = Test::new();
+        let creeper = test.entity(creeper::create().with(position!(0.0, 65.0, 2.0)));
+
+        test.handle(
+            LightningStrikeRequest {
+                pos: position!(0.0, 64.0, 0.0),
+            },
+            lightning_strike_request,
+        );
+
+        assert!(test.world.has::<creeper::Charged>(creeper));
+        assert_eq!(
+            test.world
+                .get::<EntityMetadata>(creeper)
+                .get(META_INDEX_CREEPER_CHARGED),
+            Some(MetaEntry::Boolean(true))
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Primed TNT, spawned when a TNT block is ignited. It falls
+//! like a falling block and explodes when its fuse runs out.
+
+use feather_core::blocks::{BlockId, BlockKind};
+use feather_core::entitymeta::{EntityMetadata, META_INDEX_PRIMED_TNT_FUSE};
+use feather_core::network::packets::SpawnObject;
+use feather_core::network::Packet;
+use feather_core::util::Position;
+use feather_server_types::{
+    protocol_velocity, BlockUpdateCause, BumpVec, EntityId, EntitySpawnEvent, EntityType,
+    ExplosionRequest, ExplosionSource, Game, PhysicsBuilder, SpawnPacketCreator, TntPrimeRequest,
+    Uuid, Velocity, TNT_EXPLOSION_POWER,
+};
+use feather_server_util::{play_sound, SoundCategory};
+use fecs::{EntityBuilder, EntityRef, IntoQuery, World, Write};
+use rand::Rng;
+use std::f64::consts::PI;
+
+/// Object type of primed TNT in the `SpawnObject` packet.
+const OBJECT_TYPE: i8 = 50;
+
+/// Component for primed TNT, storing the number
+/// of ticks until it explodes.
+#[derive(Copy, Clone, Debug)]
+pub struct PrimedTnt {
+    pub fuse: u32,
+}
+
+/// Returns an `EntityBuilder` for primed TNT which
+/// explodes after `fuse` ticks.
+pub fn create(fuse: u32) -> EntityBuilder {
+    let meta = EntityMetadata::entity_base().with(META_INDEX_PRIMED_TNT_FUSE, fuse as i32);
+
+    crate::base()
+        .with(EntityType("minecraft:tnt"))
+        .with(PrimedTnt { fuse })
+        .with(SpawnPacketCreator(&create_spawn_packet))
+        .with(
+            PhysicsBuilder::new()
+                .bbox(0.98, 0.98, 0.98)
+                .drag(0.98)
+                .gravity(-0.04)
+                .build(),
+        )
+        .with(meta)
+}
+
+fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
+    let position = *accessor.get::<Position>();
+    let entity_id = accessor.get::<EntityId>().0;
+    let (velocity_x, velocity_y, velocity_z) = protocol_velocity(accessor.get::<Velocity>().0);
+
+    Box::new(SpawnObject {
+        entity_id,
+        object_uuid: Uuid::new_v4(),
+        ty: OBJECT_TYPE,
+        x: position.x,
+        y: position.y,
+        z: position.z,
+        pitch: 0,
+        yaw: 0,
+        data: 0,
+        velocity_x,
+        velocity_y,
+        velocity_z,
+    })
+}
+
+/// Handles a `TntPrimeRequest`.
+#[fecs::event_handler]
+pub fn tnt_prime_request(event: &TntPrimeRequest, game: &mut Game, world: &mut World) {
+    // TNT destroyed by an explosion has already been removed.
+    if game.block_at(event.pos).map(BlockId::kind) == Some(BlockKind::Tnt) {
+        let cause = event
+            .igniter
+            .map_or_else(BlockUpdateCause::unknown, BlockUpdateCause::entity);
+        game.set_block_at_with_cause(world, event.pos, BlockId::air(), cause);
+        play_sound(
+            game,
+            world,
+            "entity.tnt.primed",
+            SoundCategory::Block,
+            event.pos.position(),
+            1.0,
+            1.0,
+            None,
+        );
+    }
+
+    // Primed TNT jumps up slightly in a random direction.
+    let velocity = {
+        let angle = game.rng().gen::<f64>() * 2.0 * PI;
+        glm::vec3(-angle.sin() * 0.02, 0.2, -angle.cos() * 0.02)
+    };
+
+    let entity = create(event.fuse)
+        .with(event.pos.position() + position!(0.5, 0.0, 0.5))
+        .with(Velocity(velocity))
+        .build()
+        .spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity });
+}
+
+/// System which counts down the fuses of primed TNT
+/// and explodes the TNT once they run out.
+///
+/// Clients animate the fuse themselves from the
+/// initial value in the entity's metadata.
+#[fecs::system]
+pub fn update_primed_tnt(game: &mut Game, world: &mut World) {
+    let mut exploded = BumpVec::new_in(game.bump());
+
+    for (entity, mut tnt) in <Write<PrimedTnt>>::query().iter_entities_mut(world.inner_mut()) {
+        if tnt.fuse == 0 {
+            exploded.push(entity);
+        } else {
+            tnt.fuse -= 1;
+        }
+    }
+
+    for entity in exploded {
+        let pos = *world.get::<Position>(entity) + glm::vec3(0.0, 0.0625, 0.0);
+        game.despawn(entity, world);
+        game.handle(
+            world,
+            ExplosionRequest {
+                pos,
+                power: TNT_EXPLOSION_POWER,
+                source: ExplosionSource::Tnt,
+            },
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Functionality shared by projectile entities. Movement and
+//! hit detection for projectiles live in the `physics` crate.
+
+use feather_core::network::packets::SpawnObject;
+use feather_core::network::Packet;
+use feather_core::util::Position;
+use feather_server_types::{EntityId, EntityType, Projectile, ProjectileKind, Uuid, Velocity};
+use feather_server_util::{degrees_to_stops, protocol_velocity};
+use fecs::{Entity, EntityBuilder, EntityRef};
+
+/// Inserts the base components for a projectile
+/// into an `EntityBuilder`.
+pub fn base(kind: ProjectileKind, shooter: Option<Entity>) -> EntityBuilder {
+    crate::base()
+        .with(EntityType(kind.identifier()))
+        .with(Projectile::new(kind, shooter))
+}
+
+/// Creates a `SpawnObject` packet for a projectile
+/// with the given object type and data.
+pub(crate) fn create_spawn_packet(accessor: &EntityRef, ty: i8, data: i32) -> Box<dyn Packet> {
+    let position = *accessor.get::<Position>();
+    let velocity = *accessor.get::<Velocity>();
+    let entity_id = accessor.get::<EntityId>().0;
+
+    let (velocity_x, velocity_y, velocity_z) = protocol_velocity(velocity.0);
+
+    let packet = SpawnObject {
+        entity_id,
+        object_uuid: Uuid::new_v4(),
+        ty,
+        x: position.x,
+        y: position.y,
+        z: position.z,
+        pitch: degrees_to_stops(position.pitch),
+        yaw: degrees_to_stops(position.yaw),
+        data,
+        velocity_x,
+        velocity_y,
+        velocity_z,
+    };
+
+    Box::new(packet)
+}
@@ -0,0 +1,29 @@
+//! Snowballs, which break on impact.
+
+use crate::projectile;
+use feather_core::network::Packet;
+use feather_server_types::{Game, ProjectileHitEvent, ProjectileKind, SpawnPacketCreator};
+use fecs::{Entity, EntityBuilder, EntityRef, World};
+
+pub fn create(shooter: Option<Entity>) -> EntityBuilder {
+    projectile::base(ProjectileKind::Snowball, shooter)
+        .with(SpawnPacketCreator(&create_spawn_packet))
+}
+
+fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
+    projectile::create_spawn_packet(accessor, 61, 1)
+}
+
+/// Removes snowballs when they hit something.
+#[fecs::event_handler]
+pub fn on_projectile_hit_handle_snowball(
+    event: &ProjectileHitEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    if event.kind != ProjectileKind::Snowball {
+        return;
+    }
+
+    game.despawn(event.projectile, world);
+}
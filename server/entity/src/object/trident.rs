@@ -0,0 +1,241 @@
+//! Thrown tridents, which damage the entity they hit and stick
+//! in blocks. Tridents enchanted with Loyalty fly back to
+//! their owner, and Channeling calls down lightning on the
+//! entity hit during thunderstorms.
+
+use crate::arrow::ArrowPickup;
+use crate::projectile;
+use feather_core::entitymeta::{
+    EntityMetadata, META_INDEX_ARROW_FLAGS, META_INDEX_TRIDENT_LOYALTY,
+};
+use feather_core::items::{Enchantment, ItemStack};
+use feather_core::network::Packet;
+use feather_core::util::{Gamemode, Position};
+use feather_server_types::{
+    BumpVec, CreatureAttribute, DamageCause, EntityDamageRequest, EntityId, EntitySpawnEvent, Game,
+    InGround, LightningStrikeRequest, Projectile, ProjectileHitEvent, ProjectileKind,
+    ProjectileTarget, SpawnPacketCreator, Velocity, Weather, PLAYER_EYE_HEIGHT,
+};
+use feather_server_util::enchantment_damage_bonus;
+use feather_server_weather::{get_weather, is_exposed_to_sky};
+use fecs::{Entity, EntityBuilder, EntityRef, IntoQuery, Read, World};
+
+/// Damage dealt by a thrown trident before enchantments.
+const BASE_DAMAGE: f32 = 8.0;
+
+/// Number of ticks a trident stays in the ground
+/// before Loyalty calls it back.
+const RETURN_DELAY_TICKS: u32 = 4;
+
+/// Component for thrown tridents.
+#[derive(Debug, Clone)]
+pub struct Trident {
+    /// The trident item, given to the player who picks it up.
+    pub stack: ItemStack,
+    /// The player who threw the trident.
+    pub owner: Option<Entity>,
+    /// Whether the trident has hit an entity or been in the
+    /// ground long enough to be called back by Loyalty.
+    pub dealt_damage: bool,
+    /// Whether the trident is flying back to its owner.
+    pub returning: bool,
+    ticks_in_ground: u32,
+}
+
+impl Trident {
+    fn loyalty(&self) -> u32 {
+        self.stack.enchantment_level(Enchantment::Loyalty)
+    }
+}
+
+pub fn create(owner: Option<Entity>, pickup: ArrowPickup, stack: ItemStack) -> EntityBuilder {
+    let loyalty = stack.enchantment_level(Enchantment::Loyalty);
+    let meta = EntityMetadata::entity_base()
+        .with(META_INDEX_ARROW_FLAGS, 0u8)
+        .with(META_INDEX_TRIDENT_LOYALTY, loyalty as i32);
+
+    projectile::base(ProjectileKind::Trident, owner)
+        .with(pickup)
+        .with(Trident {
+            stack,
+            owner,
+            dealt_damage: false,
+            returning: false,
+            ticks_in_ground: 0,
+        })
+        .with(meta)
+        .with(SpawnPacketCreator(&create_spawn_packet))
+}
+
+fn create_spawn_packet(accessor: &EntityRef) -> Box<dyn Packet> {
+    let entity_id = accessor.get::<EntityId>().0;
+    projectile::create_spawn_packet(accessor, 94, entity_id + 1)
+}
+
+/// Damages entities hit by tridents and sticks
+/// tridents which hit blocks into the ground.
+#[fecs::event_handler]
+pub fn on_projectile_hit_handle_trident(
+    event: &ProjectileHitEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    if event.kind != ProjectileKind::Trident {
+        return;
+    }
+
+    match event.target {
+        ProjectileTarget::Entity(target) => {
+            let stack = world.get::<Trident>(event.projectile).stack.clone();
+            let damage = BASE_DAMAGE
+                + enchantment_damage_bonus(
+                    stack.enchantments(),
+                    world.try_get::<CreatureAttribute>(target).map(|a| *a),
+                );
+            let target_pos = *world.get::<Position>(target);
+
+            game.handle(
+                world,
+                EntityDamageRequest {
+                    entity: target,
+                    damage,
+                    cause: DamageCause::Projectile {
+                        projectile: event.projectile,
+                        shooter: event.shooter,
+                    },
+                },
+            );
+
+            // Unlike arrows, tridents bounce off the entity
+            // they hit and pass through any others.
+            world.get_mut::<Trident>(event.projectile).dealt_damage = true;
+            world.get_mut::<Projectile>(event.projectile).hits_entities = false;
+            world.get_mut::<Velocity>(event.projectile).0 =
+                event.velocity.component_mul(&glm::vec3(-0.01, -0.1, -0.01));
+
+            let channeling = stack.enchantment_level(Enchantment::Channeling) > 0;
+            if channeling
+                && get_weather(game) == Weather::Thunder
+                && is_exposed_to_sky(game, target_pos.block())
+            {
+                game.handle(world, LightningStrikeRequest { pos: target_pos });
+            }
+        }
+        ProjectileTarget::Block(block) => {
+            world.get_mut::<Velocity>(event.projectile).0 = glm::vec3(0.0, 0.0, 0.0);
+            world.add(event.projectile, InGround { block }).unwrap();
+        }
+    }
+}
+
+/// System which calls tridents enchanted with Loyalty
+/// back to their owners once they have hit something.
+#[fecs::system]
+pub fn update_tridents(game: &mut Game, world: &mut World) {
+    let mut tridents = BumpVec::new_in(game.bump());
+    tridents.extend(
+        <Read<Trident>>::query()
+            .iter_entities(world.inner())
+            .map(|(entity, _)| entity),
+    );
+
+    let mut dropped = BumpVec::new_in(game.bump());
+
+    for entity in tridents {
+        let in_ground = world.has::<InGround>(entity);
+        let (loyalty, owner, returning, called_back) = {
+            let mut trident = world.get_mut::<Trident>(entity);
+            if in_ground {
+                trident.ticks_in_ground += 1;
+                if trident.ticks_in_ground > RETURN_DELAY_TICKS {
+                    trident.dealt_damage = true;
+                }
+            }
+            (
+                trident.loyalty(),
+                trident.owner,
+                trident.returning,
+                trident.dealt_damage || trident.returning,
+            )
+        };
+        if loyalty == 0 || !called_back {
+            continue;
+        }
+
+        let owner = match owner {
+            Some(owner) if owner_present(world, owner) => owner,
+            _ => {
+                // Tridents whose owner has left are dropped.
+                if *world.get::<ArrowPickup>(entity) == ArrowPickup::Allowed {
+                    let stack = world.get::<Trident>(entity).stack.clone();
+                    dropped.push((*world.get::<Position>(entity), stack));
+                }
+                game.despawn(entity, world);
+                continue;
+            }
+        };
+
+        if !returning {
+            world.get_mut::<Trident>(entity).returning = true;
+            let _ = world.remove::<Projectile>(entity);
+            let _ = world.remove::<InGround>(entity);
+        }
+
+        let target = *world.get::<Position>(owner) + glm::vec3(0.0, PLAYER_EYE_HEIGHT, 0.0);
+        let mut pos = *world.get::<Position>(entity);
+        let offset: glm::DVec3 = (target - pos).into();
+        let velocity = return_velocity(world.get::<Velocity>(entity).0, offset, loyalty);
+
+        pos.y += offset.y * 0.015 * f64::from(loyalty);
+        pos = pos + velocity;
+
+        *world.get_mut::<Position>(entity) = pos;
+        world.get_mut::<Velocity>(entity).0 = velocity;
+    }
+
+    for (pos, stack) in dropped {
+        let entity = crate::item::create(stack, game.tick_count)
+            .with(pos)
+            .build()
+            .spawn_in(world);
+        game.handle(world, EntitySpawnEvent { entity });
+    }
+}
+
+fn owner_present(world: &World, owner: Entity) -> bool {
+    world.is_alive(owner)
+        && world.has::<Position>(owner)
+        && world
+            .try_get::<Gamemode>(owner)
+            .map_or(true, |gamemode| *gamemode != Gamemode::Spectator)
+}
+
+/// Returns the velocity of a trident flying back towards its
+/// owner, where `offset` points from the trident to the owner.
+fn return_velocity(velocity: glm::DVec3, offset: glm::DVec3, loyalty: u32) -> glm::DVec3 {
+    if offset.norm_squared() < 1e-9 {
+        return velocity * 0.95;
+    }
+    velocity * 0.95 + offset.normalize() * 0.05 * f64::from(loyalty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loyalty_pulls_towards_owner() {
+        let offset = glm::vec3(10.0, 0.0, 0.0);
+        let mut velocity = glm::vec3(0.0, 0.0, -1.0);
+        for _ in 0..100 {
+            velocity = return_velocity(velocity, offset, 3);
+        }
+        assert!(velocity.z.abs() < 0.01);
+        assert!((velocity.x - 3.0).abs() < 0.05);
+
+        // Higher levels return faster.
+        let slow = return_velocity(glm::vec3(0.0, 0.0, 0.0), offset, 1);
+        let fast = return_velocity(glm::vec3(0.0, 0.0, 0.0), offset, 3);
+        assert!(fast.x > slow.x);
+    }
+}
@@ -0,0 +1,67 @@
+//! Summoning entities by their identifier, as done by `/summon`.
+
+use crate::{
+    create_by_id, egg, ender_pearl, lightning_bolt, primed_tnt, snowball, with_entity_tag,
+};
+use feather_server_types::{
+    ActiveEffect, EntityCategory, EntitySpawnEvent, Game, StatusEffect, SummonRequest,
+    TNT_FUSE_TICKS,
+};
+use feather_server_util::{add_effect, can_spawn_in_chunk};
+use fecs::{Entity, EntityBuilder, World};
+
+/// Handles a `SummonRequest`.
+#[fecs::event_handler]
+pub fn summon_request(event: &SummonRequest, game: &mut Game, world: &mut World) {
+    let summoned = summon(game, world, event);
+    *event.summoned.lock() = summoned;
+}
+
+fn summon(game: &mut Game, world: &mut World, event: &SummonRequest) -> Option<Entity> {
+    // Lightning has effects beyond spawning the bolt.
+    if event.id == "minecraft:lightning_bolt" {
+        return Some(lightning_bolt::strike(game, world, event.pos));
+    }
+
+    let (builder, is_mob) = match create_by_id(&event.id) {
+        Some(builder) => (with_entity_tag(builder, &event.tag), true),
+        None => (create_object(&event.id)?, false),
+    };
+    if is_mob && !can_spawn_in_chunk(game, world, event.pos.chunk(), EntityCategory::Mob) {
+        return None;
+    }
+
+    let entity = builder.with(event.pos).build().spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity });
+
+    if is_mob {
+        for effect in &event.tag.active_effects {
+            let status = match StatusEffect::from_id(effect.id) {
+                Some(status) => status,
+                None => continue,
+            };
+            let active = ActiveEffect {
+                amplifier: effect.amplifier as u8,
+                duration: effect.duration.max(0) as u32,
+                ambient: effect.ambient,
+                show_particles: effect.show_particles,
+            };
+            add_effect(game, world, entity, status, active);
+        }
+    }
+
+    Some(entity)
+}
+
+/// Returns the components for a summoned object, such as primed TNT.
+/// Objects which need more than a position to be useful, like items
+/// and falling blocks, cannot be summoned.
+fn create_object(id: &str) -> Option<EntityBuilder> {
+    Some(match id {
+        "minecraft:egg" => egg::create(None),
+        "minecraft:ender_pearl" => ender_pearl::create(None),
+        "minecraft:snowball" => snowball::create(None),
+        "minecraft:tnt" => primed_tnt::create(TNT_FUSE_TICKS),
+        _ => return None,
+    })
+}
@@ -0,0 +1,74 @@
+#[macro_use]
+extern crate criterion;
+
+use criterion::Criterion;
+use feather_core::blocks::BlockId;
+use feather_core::chunk::Chunk;
+use feather_core::util::{BlockPosition, ChunkPosition};
+use feather_server_lighting::Lighter;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// Creates a lighter with the 5x5 chunks around the origin loaded.
+fn lighter() -> (Lighter, Arc<RwLock<Chunk>>) {
+    feather_core::blocks::init();
+
+    let mut lighter = Lighter::new();
+    let mut center = None;
+    for x in -2..=2 {
+        for z in -2..=2 {
+            let pos = ChunkPosition::new(x, z);
+            let handle = Arc::new(RwLock::new(Chunk::new(pos)));
+            if x == 0 && z == 0 {
+                center = Some(Arc::clone(&handle));
+            }
+            lighter.load_chunk(pos, handle);
+        }
+    }
+
+    (lighter, center.unwrap())
+}
+
+fn set_block(chunk: &RwLock<Chunk>, pos: BlockPosition, block: BlockId) {
+    chunk
+        .write()
+        .set_block_at(pos.x as usize, pos.y as usize, pos.z as usize, block);
+}
+
+fn emitting_creation_and_removal(c: &mut Criterion) {
+    let (mut lighter, chunk) = lighter();
+    let pos = BlockPosition::new(8, 64, 8);
+
+    c.bench_function("light_emitting_creation_and_removal", |b| {
+        b.iter(|| {
+            set_block(&chunk, pos, BlockId::glowstone());
+            lighter.handle_block_update(pos, BlockId::air(), BlockId::glowstone());
+            set_block(&chunk, pos, BlockId::air());
+            lighter.handle_block_update(pos, BlockId::glowstone(), BlockId::air());
+        });
+    });
+}
+
+fn opaque_creation_and_removal(c: &mut Criterion) {
+    let (mut lighter, chunk) = lighter();
+    let light = BlockPosition::new(8, 64, 8);
+    set_block(&chunk, light, BlockId::glowstone());
+    lighter.handle_block_update(light, BlockId::air(), BlockId::glowstone());
+
+    let pos = BlockPosition::new(9, 64, 8);
+    c.bench_function("light_opaque_creation_and_removal", |b| {
+        b.iter(|| {
+            set_block(&chunk, pos, BlockId::stone());
+            lighter.handle_block_update(pos, BlockId::air(), BlockId::stone());
+            set_block(&chunk, pos, BlockId::air());
+            lighter.handle_block_update(pos, BlockId::stone(), BlockId::air());
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    emitting_creation_and_removal,
+    opaque_creation_and_removal
+);
+criterion_main!(benches);
@@ -179,20 +179,28 @@ impl ChunkLights {
 struct Worker {
     /// Receiver for new requests.
     rx: crossbeam::Receiver<Request>,
+    lighter: Lighter,
+    /// Whether the worker should shut down.
+    should_shut_down: bool,
+}
+
+/// The chunks and light sources known to the lighting worker.
+///
+/// The worker drives this from its own thread; it is public so that
+/// benchmarks can perform lighting updates synchronously.
+#[derive(Default)]
+pub struct Lighter {
     /// The worker's own copy of the chunk map, with `Arc`s
     /// being cloned from the server thread's "official" chunk map.
     chunk_map: ChunkMap,
     /// Caches the light sources in each chunk.
     lights: ChunkLights,
-    /// Whether the worker should shut down.
-    should_shut_down: bool,
 }
 
 fn run_worker(rx: crossbeam::Receiver<Request>, shutdown_tx: crossbeam::Sender<()>) {
     let mut worker = Worker {
         rx,
-        chunk_map: Default::default(),
-        lights: Default::default(),
+        lighter: Lighter::default(),
         should_shut_down: false,
     };
 
@@ -212,18 +220,65 @@ fn run_worker(rx: crossbeam::Receiver<Request>, shutdown_tx: crossbeam::Sender<(
 fn handle_request(worker: &mut Worker, request: Request) {
     match request {
         Request::ShutDown => worker.should_shut_down = true,
-        Request::LoadChunk { pos, handle } => load_chunk(worker, pos, handle),
-        Request::UnloadChunk { pos } => unload_chunk(worker, pos),
-        Request::HandleBlockUpdate { pos, old, new } => handle_block_update(worker, pos, old, new),
+        Request::LoadChunk { pos, handle } => worker.lighter.load_chunk(pos, handle),
+        Request::UnloadChunk { pos } => worker.lighter.unload_chunk(pos),
+        Request::HandleBlockUpdate { pos, old, new } => {
+            worker.lighter.handle_block_update(pos, old, new)
+        }
     }
 }
 
-fn load_chunk(worker: &mut Worker, pos: ChunkPosition, handle: Arc<RwLock<Chunk>>) {
-    worker
-        .lights
-        .0
-        .insert(pos, lights_in_chunk(&*handle.read()).collect());
-    worker.chunk_map.0.insert(pos, handle);
+impl Lighter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a chunk, caching the light sources in it.
+    pub fn load_chunk(&mut self, pos: ChunkPosition, handle: Arc<RwLock<Chunk>>) {
+        self.lights
+            .0
+            .insert(pos, lights_in_chunk(&*handle.read()).collect());
+        self.chunk_map.0.insert(pos, handle);
+    }
+
+    pub fn unload_chunk(&mut self, pos: ChunkPosition) {
+        self.lights.0.remove(&pos);
+        self.chunk_map.0.remove(&pos);
+    }
+
+    /// Updates light after the block at `pos` changed from `old` to `new`.
+    pub fn handle_block_update(&mut self, pos: BlockPosition, old: BlockId, new: BlockId) {
+        let mut ctx = match Context::new(&self.chunk_map, pos.chunk()) {
+            Some(ctx) => ctx,
+            None => return, // Unloaded chunk
+        };
+
+        // Determine which algorithm to use.
+        if old.light_emission() < new.light_emission() {
+            ctx.set_block_light_at(pos, new.light_emission());
+            emitting_creation(&mut ctx, pos);
+        } else if new.light_emission() == 0 && old.light_emission() > 0 {
+            ctx.set_block_light_at(pos, 0);
+            emitting_removal(&mut ctx, &self.lights, pos, old);
+        } else if old.is_opaque() && !new.is_opaque() {
+            opaque_non_emitting_removal(&mut ctx, pos);
+        } else {
+            opaque_non_emitting_creation(&mut ctx, &self.lights, pos, new);
+        }
+
+        // Update `ChunkLights`.
+        if old.light_emission() != new.light_emission() {
+            if new.light_emission() == 0 {
+                self.lights
+                    .0
+                    .entry(pos.chunk())
+                    .or_default()
+                    .retain(|p| *p != pos);
+            } else if old.light_emission() == 0 {
+                self.lights.0.entry(pos.chunk()).or_default().push(pos);
+            }
+        }
+    }
 }
 
 fn lights_in_chunk<'a>(chunk: &'a Chunk) -> impl Iterator<Item = BlockPosition> + 'a {
@@ -241,11 +296,6 @@ fn lights_in_chunk<'a>(chunk: &'a Chunk) -> impl Iterator<Item = BlockPosition>
         })
 }
 
-fn unload_chunk(worker: &mut Worker, pos: ChunkPosition) {
-    worker.lights.0.remove(&pos);
-    worker.chunk_map.0.remove(&pos);
-}
-
 /// Lighter context, used to cache things during
 /// a lighting iteration.
 struct Context<'a> {
@@ -319,40 +369,6 @@ impl<'a> Context<'a> {
 
 const MAX_TRAVEL_DISTANCE: u8 = 15;
 
-fn handle_block_update(worker: &mut Worker, pos: BlockPosition, old: BlockId, new: BlockId) {
-    let mut ctx = match Context::new(&worker.chunk_map, pos.chunk()) {
-        Some(ctx) => ctx,
-        None => return, // Unloaded chunk
-    };
-
-    // Determine which algorithm to use.
-    if old.light_emission() < new.light_emission() {
-        ctx.set_block_light_at(pos, new.light_emission());
-        emitting_creation(&mut ctx, pos);
-    } else if new.light_emission() == 0 && old.light_emission() > 0 {
-        ctx.set_block_light_at(pos, 0);
-        emitting_removal(&mut ctx, &worker.lights, pos, old);
-    } else if old.is_opaque() && !new.is_opaque() {
-        opaque_non_emitting_removal(&mut ctx, pos);
-    } else {
-        opaque_non_emitting_creation(&mut ctx, &worker.lights, pos, new);
-    }
-
-    // Update `ChunkLights`.
-    if old.light_emission() != new.light_emission() {
-        if new.light_emission() == 0 {
-            worker
-                .lights
-                .0
-                .entry(pos.chunk())
-                .or_default()
-                .retain(|p| *p != pos);
-        } else if old.light_emission() == 0 {
-            worker.lights.0.entry(pos.chunk()).or_default().push(pos);
-        }
-    }
-}
-
 /// Algorithm #1, as described in the module-level docs.
 fn emitting_creation(context: &mut Context, position: BlockPosition) {
     let emission = context.block_light_at(position);
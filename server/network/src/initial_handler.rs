@@ -14,7 +14,7 @@
 //! speeding up the login process and making the latency calculation in
 //! the server list ping as low as possible.
 
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use rand::rngs::OsRng;
@@ -23,14 +23,15 @@ use rsa_der as der;
 
 use thiserror::Error;
 
-use feather_core::network::{cast_packet, Packet, PacketStage, PacketType};
+use feather_core::network::{cast_packet, Packet, PacketStage, PacketType, ProtocolVersion};
+use feather_core::text::replace_formatting_codes;
 
-use crate::{PROTOCOL_VERSION, SERVER_VERSION};
+use crate::SERVER_VERSION;
 use feather_core::network::packets::{
     DisconnectLogin, EncryptionRequest, EncryptionResponse, Handshake, HandshakeState, LoginStart,
     LoginSuccess, Ping, Pong, Request, Response, SetCompression,
 };
-use feather_server_types::{Config, ProxyMode, Uuid};
+use feather_server_types::{Config, OnlinePlayers, ProxyMode, Uuid};
 use mojang_api::ProfileProperty;
 use once_cell::sync::Lazy;
 
@@ -44,6 +45,9 @@ type VerifyToken = [u8; 4];
 const RSA_KEY_BITS: usize = 1024;
 /// The number of bytes in the shared secret
 const SHARED_SECRET_LEN: usize = 128 / 8;
+/// The maximum number of online players listed
+/// in a server list ping response.
+const PLAYER_SAMPLE_SIZE: usize = 12;
 
 pub static RSA_KEY: Lazy<RSAPrivateKey> = Lazy::new(|| {
     let mut rng = OsRng;
@@ -58,6 +62,7 @@ pub enum Action {
     SendPacket(Box<dyn Packet>),
     Disconnect,
     SetStage(PacketStage),
+    SetVersion(ProtocolVersion),
     JoinGame(JoinResult),
 }
 
@@ -67,6 +72,9 @@ pub struct JoinResult {
     pub username: Option<String>,
     pub uuid: Uuid,
     pub props: Vec<mojang_api::ProfileProperty>,
+    /// The address of the client, if it was
+    /// forwarded by a proxy.
+    pub ip: Option<IpAddr>,
 }
 
 impl JoinResult {
@@ -83,6 +91,7 @@ impl Default for JoinResult {
             username: None,
             uuid: Uuid::new_v4(),
             props: vec![],
+            ip: None,
         }
     }
 }
@@ -112,13 +121,17 @@ pub struct InitialHandler {
     /// should be enabled with the given threshold.
     compression_threshold: Option<i32>,
 
+    /// The protocol version the client declared in
+    /// its handshake, if the server supports it.
+    version: Option<ProtocolVersion>,
+
     /// The verify token generated for this exchange.
     verify_token: VerifyToken,
 
     /// The server's configuration.
     config: Arc<Config>,
     /// The server's player count.
-    player_count: Arc<AtomicU32>,
+    online_players: Arc<OnlinePlayers>,
     /// The server's icon, if any was loaded.
     server_icon: Arc<Option<String>>,
 
@@ -134,7 +147,7 @@ pub struct InitialHandler {
 impl InitialHandler {
     pub fn new(
         config: Arc<Config>,
-        player_count: Arc<AtomicU32>,
+        online_players: Arc<OnlinePlayers>,
         server_icon: Arc<Option<String>>,
     ) -> Self {
         Self {
@@ -143,10 +156,12 @@ impl InitialHandler {
             key: None,
             compression_threshold: None,
 
+            version: None,
+
             verify_token: rand::random(),
 
             config,
-            player_count,
+            online_players,
             server_icon,
 
             info: None,
@@ -210,6 +225,14 @@ async fn _handle_packet(ih: &mut InitialHandler, packet: Box<dyn Packet>) -> Res
 fn handle_handshake(ih: &mut InitialHandler, packet: &Handshake) -> Result<(), Error> {
     check_stage(ih, Stage::AwaitHandshake, packet.ty())?;
 
+    ih.version = ProtocolVersion::from_id(packet.protocol_version);
+    // Codecs start out with the native version.
+    if let Some(version) = ih.version {
+        if version != ProtocolVersion::NATIVE {
+            ih.action_queue.push(Action::SetVersion(version));
+        }
+    }
+
     ih.stage = match packet.next_state {
         HandshakeState::Status => {
             ih.action_queue.push(Action::SetStage(PacketStage::Status));
@@ -218,9 +241,9 @@ fn handle_handshake(ih: &mut InitialHandler, packet: &Handshake) -> Result<(), E
         HandshakeState::Login => {
             // While status requests can use differing
             // protocol versions, a client
-            // needs to have a matching protocol version
+            // needs to have a supported protocol version
             // to log in.
-            if packet.protocol_version != PROTOCOL_VERSION {
+            if ih.version.is_none() {
                 return Err(Error::InvalidProtocol(packet.protocol_version));
             }
 
@@ -228,10 +251,17 @@ fn handle_handshake(ih: &mut InitialHandler, packet: &Handshake) -> Result<(), E
             // by BungeeCord if IP forwarding is enabled.
             if ih.config.proxy.proxy_mode == ProxyMode::BungeeCord {
                 let bungeecord_data = extract_bungeecord_data(packet)?;
+                let ip = bungeecord_data.client.parse().map_err(|_| {
+                    Error::BungeeSpecMismatch(format!(
+                        "invalid client address {}",
+                        bungeecord_data.client
+                    ))
+                })?;
                 ih.info = Some(JoinResult {
                     username: None,
                     uuid: bungeecord_data.uuid,
                     props: bungeecord_data.properties,
+                    ip: Some(ip),
                 });
             }
 
@@ -274,6 +304,10 @@ struct BungeeCordData {
 
 impl BungeeCordData {
     pub fn from_vec(data: &[&str]) -> Result<Self, Error> {
+        // Clients connecting directly send only the host.
+        if data.len() == 1 {
+            return Err(Error::BungeeForwardingMissing);
+        }
         if data.len() != 4 {
             return Err(Error::BungeeSpecMismatch("Incorrect length".to_string()));
         }
@@ -296,24 +330,46 @@ impl BungeeCordData {
 
 fn handle_request(ih: &mut InitialHandler, packet: &Request) -> Result<(), Error> {
     check_stage(ih, Stage::AwaitRequest, packet.ty())?;
-    let server_icon = (*ih.server_icon).clone().unwrap_or_default();
+
+    // Clients show the server as outdated unless
+    // the protocol matches their own.
+    let protocol = ih.version.unwrap_or(ProtocolVersion::NATIVE).id();
 
     // Send response packet
-    let json = serde_json::json!({
+    let mut json = serde_json::json!({
         "version": {
             "name": SERVER_VERSION,
-            "protocol": PROTOCOL_VERSION,
-        },
-        "players": {
-            "max": ih.config.server.max_players,
-            "online": ih.player_count.load(Ordering::SeqCst),
+            "protocol": protocol,
         },
         "description": {
-            "text": ih.config.server.motd,
+            "text": replace_formatting_codes(&ih.config.server.motd),
         },
-        "favicon": server_icon,
     });
 
+    // Without a player count, clients show "???" instead.
+    if !ih.config.server.hide_player_count {
+        let sample: Vec<_> = ih
+            .online_players
+            .sample(PLAYER_SAMPLE_SIZE)
+            .into_iter()
+            .map(|(uuid, name)| {
+                serde_json::json!({
+                    "name": name,
+                    "id": uuid.to_hyphenated_ref().to_string(),
+                })
+            })
+            .collect();
+        json["players"] = serde_json::json!({
+            "max": ih.config.server.max_players,
+            "online": ih.online_players.count(),
+            "sample": sample,
+        });
+    }
+
+    if let Some(server_icon) = ih.server_icon.as_ref() {
+        json["favicon"] = server_icon.as_str().into();
+    }
+
     let response = Response {
         json_response: json.to_string(),
     };
@@ -342,7 +398,7 @@ fn handle_ping(ih: &mut InitialHandler, packet: &Ping) -> Result<(), Error> {
 fn handle_login_start(ih: &mut InitialHandler, packet: &LoginStart) -> Result<(), Error> {
     check_stage(ih, Stage::AwaitLoginStart, packet.ty())?;
 
-    if ih.player_count.load(Ordering::Acquire) >= ih.config.server.max_players as u32 {
+    if ih.online_players.count() >= ih.config.server.max_players as u32 {
         disconnect_login(ih, "Server is full!");
         return Ok(());
     }
@@ -352,7 +408,12 @@ fn handle_login_start(ih: &mut InitialHandler, packet: &LoginStart) -> Result<()
     // If not in online mode, the login sequence is
     // already finished, so we can call `finish` after
     // setting the player's info.
-    if ih.config.server.online_mode {
+    //
+    // BungeeCord authenticates players itself and
+    // forwards their identity in the handshake.
+    let online_mode =
+        ih.config.server.online_mode && ih.config.proxy.proxy_mode != ProxyMode::BungeeCord;
+    if online_mode {
         use num_bigint_dig::{BigInt, Sign::Plus};
         // Start enabling encryption
         let der = der::public_key_to_der(
@@ -449,6 +510,7 @@ async fn handle_encryption_response(
                 username: Some(auth.name),
                 uuid: auth.id,
                 props: auth.properties,
+                ip: None,
             };
             ih.info = Some(info);
         }
@@ -552,6 +614,10 @@ enum Error {
     AuthenticationFailed(mojang_api::Error),
     #[error("received BungeeCord data does not match the specification: {0}")]
     BungeeSpecMismatch(String),
+    #[error(
+        "If you wish to use IP forwarding, please enable it in your BungeeCord config as well!"
+    )]
+    BungeeForwardingMissing,
     #[error("option that should not be None was None")]
     /// An Error type than can be used as the error type of using the Try operator on Option
     /// types. In rust-core, this is an unstable feature (issue #42327)
@@ -720,6 +786,29 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn status_can_hide_player_count() {
+        let mut config = Config::default();
+        config.server.hide_player_count = true;
+        let mut ih = ih_with_config(config);
+
+        let handshake = Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            server_address: String::default(),
+            server_port: 25565,
+            next_state: HandshakeState::Status,
+        };
+        ih.handle_packet(Box::new(handshake)).await;
+        ih.handle_packet(Box::new(Request {})).await;
+
+        let response = match ih.actions_to_execute().pop() {
+            Some(Action::SendPacket(response)) => cast_packet::<Response>(response),
+            _ => panic!(),
+        };
+        let json: serde_json::Value = serde_json::from_str(&response.json_response).unwrap();
+        assert!(json.get("players").is_none());
+    }
+
     #[test]
     fn test_initial_handler_new() {
         let mut ih = ih();
@@ -762,7 +851,13 @@ mod tests {
                 assert_eq!(response.ty(), PacketType::Response);
 
                 let response = cast_packet::<Response>(response);
-                let _: serde_json::Value = serde_json::from_str(&response.json_response).unwrap();
+                let json: serde_json::Value =
+                    serde_json::from_str(&response.json_response).unwrap();
+                assert_eq!(json["players"]["online"], player_count);
+                let sample = json["players"]["sample"].as_array().unwrap();
+                assert_eq!(sample.len(), PLAYER_SAMPLE_SIZE);
+                assert_eq!(sample[0]["name"], "player0");
+                assert_eq!(json["favicon"], "test");
             }
             _ => panic!(),
         }
@@ -792,6 +887,41 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn unsupported_versions_can_ping_but_not_log_in() {
+        let old_version = 340;
+        let mut ih = ih();
+        let handshake = Handshake {
+            protocol_version: old_version,
+            server_address: String::default(),
+            server_port: 25565,
+            next_state: HandshakeState::Status,
+        };
+        ih.handle_packet(Box::new(handshake)).await;
+        ih.actions_to_execute();
+
+        ih.handle_packet(Box::new(Request {})).await;
+        let response = match ih.actions_to_execute().remove(0) {
+            Action::SendPacket(response) => cast_packet::<Response>(response),
+            _ => panic!(),
+        };
+        let json: serde_json::Value = serde_json::from_str(&response.json_response).unwrap();
+        assert_eq!(json["version"]["protocol"], PROTOCOL_VERSION);
+
+        let mut ih = ih();
+        let handshake = Handshake {
+            protocol_version: old_version,
+            server_address: String::default(),
+            server_port: 25565,
+            next_state: HandshakeState::Login,
+        };
+        ih.handle_packet(Box::new(handshake)).await;
+        match ih.actions_to_execute().pop() {
+            Some(Action::Disconnect) => (),
+            _ => panic!(),
+        }
+    }
+
     #[tokio::test]
     async fn test_login_sequence() {
         let mut config = Config::default();
@@ -866,18 +996,128 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn older_supported_versions_can_log_in() {
+        let mut config = Config::default();
+        config.server.online_mode = false;
+        let mut ih = ih_with_config(config);
+
+        let handshake = Handshake {
+            protocol_version: ProtocolVersion::V1_13_1.id(),
+            server_address: String::default(),
+            server_port: 25565,
+            next_state: HandshakeState::Login,
+        };
+        ih.handle_packet(Box::new(handshake)).await;
+
+        // The codec is switched to the client's version
+        // before any login packets are exchanged.
+        let mut actions = ih.actions_to_execute();
+        assert_eq!(actions.len(), 2);
+        match actions.remove(0) {
+            Action::SetVersion(version) => assert_eq!(version, ProtocolVersion::V1_13_1),
+            _ => panic!(),
+        }
+        match actions.remove(0) {
+            Action::SetStage(stage) => assert_eq!(stage, PacketStage::Login),
+            _ => panic!(),
+        }
+
+        let login_start = LoginStart {
+            username: String::from("test"),
+        };
+        ih.handle_packet(Box::new(login_start)).await;
+
+        let actions = ih.actions_to_execute();
+        assert!(actions.iter().any(|action| match action {
+            Action::SendPacket(packet) => packet.ty() == PacketType::LoginSuccess,
+            _ => false,
+        }));
+        match actions.last() {
+            Some(Action::JoinGame(_)) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn bungeecord_forwards_identity() {
+        // Online mode is left on, since the proxy authenticates players.
+        let mut config = Config::default();
+        config.proxy.proxy_mode = ProxyMode::BungeeCord;
+        let mut ih = ih_with_config(config);
+
+        let handshake = Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            server_address: "192.168.1.87\0192.168.1.67\0905c7e4fb96b45139645d123225575e2\0[]"
+                .to_string(),
+            server_port: 25565,
+            next_state: HandshakeState::Login,
+        };
+        ih.handle_packet(Box::new(handshake)).await;
+        ih.actions_to_execute();
+
+        let login_start = LoginStart {
+            username: String::from("test"),
+        };
+        ih.handle_packet(Box::new(login_start)).await;
+
+        let info = ih
+            .actions_to_execute()
+            .into_iter()
+            .find_map(|action| match action {
+                Action::JoinGame(info) => Some(info),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(info.username.as_deref(), Some("test"));
+        assert_eq!(
+            info.uuid,
+            Uuid::parse_str("905c7e4fb96b45139645d123225575e2").unwrap()
+        );
+        assert_eq!(info.ip, Some("192.168.1.67".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn bungeecord_rejects_direct_connections() {
+        let mut config = Config::default();
+        config.proxy.proxy_mode = ProxyMode::BungeeCord;
+        let mut ih = ih_with_config(config);
+
+        let handshake = Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            server_address: String::from("localhost"),
+            server_port: 25565,
+            next_state: HandshakeState::Login,
+        };
+        ih.handle_packet(Box::new(handshake)).await;
+
+        let actions = ih.actions_to_execute();
+        assert!(actions.iter().any(|action| match action {
+            Action::SendPacket(packet) => packet.ty() == PacketType::DisconnectLogin,
+            _ => false,
+        }));
+        match actions.last() {
+            Some(Action::Disconnect) => (),
+            _ => panic!(),
+        }
+    }
+
     fn ih() -> InitialHandler {
         InitialHandler::new(
             Arc::new(Config::default()),
-            Arc::new(AtomicU32::new(0)),
+            Arc::new(OnlinePlayers::default()),
             Arc::new(Some(String::from("test"))),
         )
     }
 
     fn ih_with_player_count(count: u32) -> InitialHandler {
+        let online_players = OnlinePlayers::default();
+        for i in 0..count {
+            online_players.add(Uuid::new_v4(), format!("player{}", i));
+        }
         InitialHandler::new(
             Arc::new(Config::default()),
-            Arc::new(AtomicU32::new(count)),
+            Arc::new(online_players),
             Arc::new(Some(String::from("test"))),
         )
     }
@@ -885,7 +1125,7 @@ mod tests {
     fn ih_with_config(config: Config) -> InitialHandler {
         InitialHandler::new(
             Arc::new(config),
-            Arc::new(AtomicU32::new(0)),
+            Arc::new(OnlinePlayers::default()),
             Arc::new(Some(String::from("test"))),
         )
     }
@@ -0,0 +1,238 @@
+//! Answers server list pings from clients older than 1.7,
+//! which predate the handshake and instead send 0xFE
+//! as soon as they connect. Some server list crawlers
+//! still ping this way.
+
+use crate::SERVER_VERSION;
+use bytes::BytesMut;
+use feather_core::text::replace_formatting_codes;
+use feather_server_types::{Config, OnlinePlayers};
+use std::time::Duration;
+use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// Packet ID of the legacy ping.
+const LEGACY_PING: u8 = 0xFE;
+/// Packet ID of the kick packet carrying the response.
+const KICK: u8 = 0xFF;
+/// Protocol reported to 1.6 clients. None of them speak
+/// it, so they show the server as incompatible rather
+/// than trying to join.
+const LEGACY_PROTOCOL: u32 = 127;
+/// How long to wait for the rest of the first bytes of a
+/// connection before deciding what it starts with.
+const START_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How a new connection starts.
+pub enum ConnectionStart {
+    /// With a legacy ping, which has been answered.
+    /// The connection should be closed.
+    LegacyPing,
+    /// With a handshake. Holds the bytes read while
+    /// checking, which should be decoded first.
+    Handshake(BytesMut),
+}
+
+/// What the first bytes of a connection are.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    /// A legacy ping. Pings from 1.6 include the
+    /// server version in the response.
+    LegacyPing {
+        with_version: bool,
+    },
+    Handshake,
+}
+
+/// Checks whether a new connection starts with a legacy ping,
+/// and answers it if so.
+pub async fn handle_legacy_ping(
+    stream: &mut TcpStream,
+    config: &Config,
+    online_players: &OnlinePlayers,
+) -> io::Result<ConnectionStart> {
+    let mut start = BytesMut::new();
+    let kind = loop {
+        if let Some(kind) = classify(&start) {
+            break kind;
+        }
+
+        let mut buf = [0u8; 3];
+        let wanted = buf.len() - start.len();
+        let read = match timeout(START_TIMEOUT, stream.read(&mut buf[..wanted])).await {
+            Ok(read) => read?,
+            // Nothing more is coming for now.
+            Err(_) => 0,
+        };
+        if read == 0 {
+            break classify_incomplete(&start);
+        }
+        start.extend_from_slice(&buf[..read]);
+    };
+
+    let with_version = match kind {
+        Kind::LegacyPing { with_version } => with_version,
+        Kind::Handshake => return Ok(ConnectionStart::Handshake(start)),
+    };
+
+    let (online, max) = if config.server.hide_player_count {
+        // Legacy clients show counts they cannot parse as unknown.
+        (String::from("???"), String::from("???"))
+    } else {
+        (
+            online_players.count().to_string(),
+            config.server.max_players.to_string(),
+        )
+    };
+    let response = legacy_response(
+        &replace_formatting_codes(&config.server.motd),
+        &online,
+        &max,
+        with_version,
+    );
+
+    stream.write_all(&response).await?;
+    Ok(ConnectionStart::LegacyPing)
+}
+
+/// Classifies the first bytes of a connection, or returns
+/// `None` if more are needed to tell.
+///
+/// A handshake whose length is 254 bytes also starts with
+/// 0xFE 0x01, as a VarInt, so 1.6 pings are only recognized
+/// by the 0xFA which follows.
+fn classify(start: &[u8]) -> Option<Kind> {
+    match start {
+        [] | [LEGACY_PING] | [LEGACY_PING, 0x01] => None,
+        [LEGACY_PING, 0x01, 0xFA, ..] => Some(Kind::LegacyPing { with_version: true }),
+        _ => Some(Kind::Handshake),
+    }
+}
+
+/// Classifies the first bytes of a connection once no more
+/// arrive. Clients older than 1.4 send nothing but 0xFE.
+fn classify_incomplete(start: &[u8]) -> Kind {
+    match start {
+        [LEGACY_PING] => Kind::LegacyPing {
+            with_version: false,
+        },
+        _ => Kind::Handshake,
+    }
+}
+
+/// Encodes the kick packet which answers a legacy ping.
+fn legacy_response(motd: &str, online: &str, max: &str, with_version: bool) -> Vec<u8> {
+    // Legacy clients only show the first line of the MOTD.
+    let motd = motd.lines().next().unwrap_or_default();
+    let text = if with_version {
+        format!(
+            "§1\0{}\0{}\0{}\0{}\0{}",
+            LEGACY_PROTOCOL, SERVER_VERSION, motd, online, max
+        )
+    } else {
+        // Fields are separated by `§`, so the MOTD
+        // cannot contain formatting codes.
+        format!("{}§{}§{}", strip_formatting(motd), online, max)
+    };
+
+    let chars: Vec<u16> = text.encode_utf16().collect();
+    let mut response = Vec::with_capacity(3 + chars.len() * 2);
+    response.push(KICK);
+    response.extend_from_slice(&(chars.len() as u16).to_be_bytes());
+    for c in chars {
+        response.extend_from_slice(&c.to_be_bytes());
+    }
+    response
+}
+
+fn strip_formatting(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PROTOCOL_VERSION;
+    use feather_core::network::packets::{Handshake, HandshakeState};
+    use feather_core::network::{MinecraftCodec, Packet, PacketDirection};
+    use tokio_util::codec::Encoder;
+
+    fn decode(response: &[u8]) -> String {
+        assert_eq!(response[0], KICK);
+        let len = u16::from_be_bytes([response[1], response[2]]) as usize;
+        let chars: Vec<u16> = response[3..]
+            .chunks(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        assert_eq!(chars.len(), len);
+        String::from_utf16(&chars).unwrap()
+    }
+
+    /// Encodes a handshake as a client would send it.
+    fn handshake(server_address: &str) -> BytesMut {
+        let packet = Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            server_address: server_address.to_owned(),
+            server_port: 25565,
+            next_state: HandshakeState::Login,
+        };
+        let mut codec = MinecraftCodec::new(PacketDirection::Serverbound);
+        let mut buf = BytesMut::new();
+        codec
+            .encode(Box::new(packet) as Box<dyn Packet>, &mut buf)
+            .unwrap();
+        buf
+    }
+
+    #[test]
+    fn legacy_pings_are_recognized() {
+        assert_eq!(
+            classify(&[0xFE, 0x01, 0xFA]),
+            Some(Kind::LegacyPing { with_version: true })
+        );
+        assert_eq!(classify(&[0xFE]), None);
+        assert_eq!(
+            classify_incomplete(&[0xFE]),
+            Kind::LegacyPing {
+                with_version: false
+            }
+        );
+    }
+
+    #[test]
+    fn long_handshakes_are_not_legacy_pings() {
+        // The packet ID, protocol version, address length,
+        // port and next state take up 8 bytes.
+        let handshake = handshake(&"a".repeat(254 - 8));
+        assert_eq!(&handshake[..3], &[0xFE, 0x01, 0x00]);
+
+        assert_eq!(classify(&handshake[..2]), None);
+        assert_eq!(classify(&handshake[..3]), Some(Kind::Handshake));
+        assert_eq!(classify_incomplete(&handshake[..2]), Kind::Handshake);
+    }
+
+    #[test]
+    fn response_with_version() {
+        let response = legacy_response("§aA server\nSecond line", "3", "16", true);
+        assert_eq!(
+            decode(&response),
+            format!("§1\0127\0{}\0§aA server\03\016", SERVER_VERSION)
+        );
+    }
+
+    #[test]
+    fn response_without_version() {
+        let response = legacy_response("§aA server", "3", "16", false);
+        assert_eq!(decode(&response), "A server§3§16");
+    }
+}
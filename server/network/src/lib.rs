@@ -14,18 +14,25 @@ use derivative::Derivative;
 use feather_core::anvil::player::PlayerData;
 use feather_core::util::Position;
 use feather_server_types::{
-    Config, PacketBuffers, ServerToWorkerMessage, Uuid, WorkerToServerMessage,
+    AdvancementProgress, Config, ConnectionCounters, OnlinePlayers, PacketBuffers,
+    ServerToWorkerMessage, Statistics, Uuid, WorkerToServerMessage,
 };
 use fecs::Entity;
+use futures::future::{abortable, AbortHandle};
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
+use std::future::Future;
 use std::net::SocketAddr;
-use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, UdpSocket};
 
 mod initial_handler;
+mod legacy_ping;
 mod listener;
+mod proxy_protocol;
+pub mod query;
+mod rate_limit;
+mod send_queue;
 mod worker;
 
 #[derive(Debug)]
@@ -62,12 +69,19 @@ pub struct NewClientInfo {
     pub profile: Vec<mojang_api::ProfileProperty>,
     pub uuid: Uuid,
     pub data: PlayerData,
+    /// Whether the player has not joined before,
+    /// so `data` was created for them.
+    pub first_join: bool,
     pub position: Position,
+    pub statistics: Statistics,
+    pub advancements: AdvancementProgress,
 
     #[derivative(Debug = "ignore")]
     pub sender: flume::Sender<ServerToWorkerMessage>,
     #[derivative(Debug = "ignore")]
     pub receiver: flume::Receiver<WorkerToServerMessage>,
+    /// Traffic counters, updated by the worker.
+    pub counters: Arc<ConnectionCounters>,
 
     pub entity: Entity,
 }
@@ -77,43 +91,73 @@ pub struct NetworkIoManager {
     pub tx: flume::Sender<ServerToListenerMessage>,
     /// Used for testing
     pub listener_tx: flume::Sender<ListenerToServerMessage>,
+    /// Handles to the tasks which accept connections and queries.
+    listeners: Vec<AbortHandle>,
 }
 
 impl NetworkIoManager {
-    /// Starts a new IO listener.
+    /// Starts a new IO listener, and a query
+    /// listener if a query socket is given.
     pub fn start(
         listener: TcpListener,
+        query_socket: Option<UdpSocket>,
         config: Arc<Config>,
-        player_count: Arc<AtomicU32>,
+        online_players: Arc<OnlinePlayers>,
         server_icon: Arc<Option<String>>,
         packet_buffers: Arc<PacketBuffers>,
     ) -> Self {
         let (listener_tx, rx) = flume::bounded(16);
         let (tx, listener_rx) = flume::bounded(16);
 
-        let future = run_listener(
+        let mut listeners = vec![];
+        if let Some(socket) = query_socket {
+            listeners.push(spawn_listener(query::run_query(
+                socket,
+                Arc::clone(&config),
+                Arc::clone(&online_players),
+            )));
+        }
+        listeners.push(spawn_listener(run_listener(
             listener,
             listener_tx.clone(),
             listener_rx,
             config,
-            player_count,
+            online_players,
             server_icon,
             packet_buffers,
-        );
-
-        if cfg!(test) {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.spawn(future);
-        } else {
-            tokio::spawn(future);
-        }
+        )));
 
         Self {
             rx: Mutex::new(rx),
             tx,
             listener_tx,
+            listeners,
         }
     }
+
+    /// Stops accepting new connections and queries. Connections
+    /// which were already accepted are unaffected.
+    pub fn stop_listening(&self) {
+        for listener in &self.listeners {
+            listener.abort();
+        }
+    }
+}
+
+/// Spawns a listener task, returning the handle which stops it.
+fn spawn_listener(future: impl Future<Output = ()> + Send + 'static) -> AbortHandle {
+    let (future, handle) = abortable(future);
+    let future = async {
+        let _ = future.await;
+    };
+
+    if cfg!(test) {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.spawn(future);
+    } else {
+        tokio::spawn(future);
+    }
+    handle
 }
 
 /// Initializes certain static variables.
@@ -126,7 +170,7 @@ async fn run_listener(
     tx: flume::Sender<ListenerToServerMessage>,
     rx: flume::Receiver<ServerToListenerMessage>,
     config: Arc<Config>,
-    player_count: Arc<AtomicU32>,
+    online_players: Arc<OnlinePlayers>,
     server_icon: Arc<Option<String>>,
     packet_buffers: Arc<PacketBuffers>,
 ) {
@@ -135,7 +179,7 @@ async fn run_listener(
         tx,
         rx,
         config,
-        player_count,
+        online_players,
         server_icon,
         packet_buffers,
     )
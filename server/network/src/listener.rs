@@ -5,9 +5,8 @@
 
 use crate::worker::run_worker;
 use crate::{ListenerToServerMessage, ServerToListenerMessage};
-use feather_server_types::{Config, PacketBuffers};
+use feather_server_types::{Config, OnlinePlayers, PacketBuffers};
 
-use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
 use tokio::io;
 use tokio::net::TcpListener;
@@ -18,7 +17,7 @@ pub async fn run_listener(
     tx: flume::Sender<ListenerToServerMessage>,
     rx: flume::Receiver<ServerToListenerMessage>,
     config: Arc<Config>,
-    player_count: Arc<AtomicU32>,
+    online_players: Arc<OnlinePlayers>,
     server_icon: Arc<Option<String>>,
     packet_buffers: Arc<PacketBuffers>,
 ) -> Result<(), io::Error> {
@@ -33,15 +32,13 @@ pub async fn run_listener(
             }
         };
 
-        log::info!("Connection received from {}", ip);
-
         tokio::spawn(run_worker(
             stream,
             ip,
             tx.clone(),
             Arc::clone(&rx),
             Arc::clone(&config),
-            Arc::clone(&player_count),
+            Arc::clone(&online_players),
             Arc::clone(&server_icon),
             Arc::clone(&packet_buffers),
         ));
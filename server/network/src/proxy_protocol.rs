@@ -0,0 +1,181 @@
+//! Reads the PROXY protocol header which TCP load balancers
+//! such as HAProxy send at the start of each connection,
+//! carrying the address of the client they forward.
+//!
+//! Both the text format (v1) and the binary format (v2)
+//! are accepted. See https://www.haproxy.org/download/2.0/doc/proxy-protocol.txt.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use tokio::io::{self, AsyncReadExt};
+use tokio::net::TcpStream;
+
+/// Signature which starts a v2 header.
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+/// Prefix which starts a v1 header.
+const V1_PREFIX: &[u8] = b"PROXY ";
+/// Maximum length of a v1 header, including the trailing CRLF.
+const V1_MAX_LENGTH: usize = 107;
+
+/// Reads the PROXY protocol header from a new connection.
+///
+/// Returns the address of the client, or `None` if the header
+/// does not carry one, as is the case for health checks sent
+/// by the load balancer itself. Fails if the connection does
+/// not start with a valid header.
+pub async fn read_header(stream: &mut TcpStream) -> io::Result<Option<SocketAddr>> {
+    // Both headers are at least this long,
+    // so this never reads past the header.
+    let mut prefix = [0u8; 12];
+    stream.read_exact(&mut prefix).await?;
+
+    if prefix == V2_SIGNATURE {
+        let mut header = [0u8; 4];
+        stream.read_exact(&mut header).await?;
+        let len = u16::from_be_bytes([header[2], header[3]]) as usize;
+        let mut addresses = vec![0u8; len];
+        stream.read_exact(&mut addresses).await?;
+        parse_v2(header[0], header[1], &addresses)
+    } else if prefix.starts_with(V1_PREFIX) {
+        let mut line = prefix.to_vec();
+        while !line.ends_with(b"\r\n") {
+            if line.len() >= V1_MAX_LENGTH {
+                return Err(invalid("PROXY header too long"));
+            }
+            line.push(stream.read_u8().await?);
+        }
+        parse_v1(&line)
+    } else {
+        Err(invalid("missing PROXY header"))
+    }
+}
+
+fn invalid(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Parses a v1 header, such as
+/// `PROXY TCP4 192.168.0.1 192.168.0.11 56324 25565\r\n`.
+fn parse_v1(line: &[u8]) -> io::Result<Option<SocketAddr>> {
+    let line = std::str::from_utf8(line).map_err(|_| invalid("PROXY header is not ASCII"))?;
+    let fields: Vec<&str> = line.trim_end_matches("\r\n").split(' ').collect();
+
+    match fields.get(1).copied() {
+        Some("UNKNOWN") => Ok(None),
+        Some(protocol) if fields.len() == 6 => {
+            let ip: IpAddr = fields[2]
+                .parse()
+                .map_err(|_| invalid("invalid source address in PROXY header"))?;
+            let port: u16 = fields[4]
+                .parse()
+                .map_err(|_| invalid("invalid source port in PROXY header"))?;
+            match (protocol, ip) {
+                ("TCP4", IpAddr::V4(_)) | ("TCP6", IpAddr::V6(_)) => {
+                    Ok(Some(SocketAddr::new(ip, port)))
+                }
+                _ => Err(invalid("unsupported protocol in PROXY header")),
+            }
+        }
+        _ => Err(invalid("malformed PROXY header")),
+    }
+}
+
+/// Parses a v2 header from its version and command byte,
+/// its address family and protocol byte, and the bytes
+/// which follow them.
+fn parse_v2(version_command: u8, family: u8, addresses: &[u8]) -> io::Result<Option<SocketAddr>> {
+    if version_command >> 4 != 2 {
+        return Err(invalid("unsupported PROXY header version"));
+    }
+    match version_command & 0x0F {
+        // LOCAL: the connection was made by the
+        // load balancer rather than forwarded.
+        0x0 => return Ok(None),
+        // PROXY
+        0x1 => (),
+        _ => return Err(invalid("unsupported PROXY header command")),
+    }
+
+    // Addresses are followed by optional TLVs, which are ignored.
+    match family >> 4 {
+        // AF_INET
+        0x1 if addresses.len() >= 12 => {
+            let mut ip = [0u8; 4];
+            ip.copy_from_slice(&addresses[..4]);
+            let port = u16::from_be_bytes([addresses[8], addresses[9]]);
+            Ok(Some(SocketAddr::new(Ipv4Addr::from(ip).into(), port)))
+        }
+        // AF_INET6
+        0x2 if addresses.len() >= 36 => {
+            let mut ip = [0u8; 16];
+            ip.copy_from_slice(&addresses[..16]);
+            let port = u16::from_be_bytes([addresses[32], addresses[33]]);
+            Ok(Some(SocketAddr::new(Ipv6Addr::from(ip).into(), port)))
+        }
+        // AF_UNSPEC or AF_UNIX, which carry no usable address
+        0x0 | 0x3 => Ok(None),
+        _ => Err(invalid("malformed PROXY header")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_tcp4() {
+        let addr = parse_v1(b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 25565\r\n").unwrap();
+        assert_eq!(addr, Some("192.168.0.1:56324".parse().unwrap()));
+    }
+
+    #[test]
+    fn v1_tcp6() {
+        let addr = parse_v1(b"PROXY TCP6 ::1 ::2 56324 25565\r\n").unwrap();
+        assert_eq!(addr, Some("[::1]:56324".parse().unwrap()));
+    }
+
+    #[test]
+    fn v1_unknown() {
+        assert_eq!(parse_v1(b"PROXY UNKNOWN\r\n").unwrap(), None);
+        assert_eq!(
+            parse_v1(b"PROXY UNKNOWN ::1 ::2 56324 25565\r\n").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn v1_invalid() {
+        assert!(parse_v1(b"PROXY TCP4 ::1 ::2 56324 25565\r\n").is_err());
+        assert!(parse_v1(b"PROXY TCP4 192.168.0.1 192.168.0.11 56324\r\n").is_err());
+        assert!(parse_v1(b"PROXY UDP4 192.168.0.1 192.168.0.11 56324 25565\r\n").is_err());
+    }
+
+    #[test]
+    fn v2_inet() {
+        let addresses = [192, 168, 0, 1, 192, 168, 0, 11, 0xDC, 0x04, 0x63, 0xDD];
+        let addr = parse_v2(0x21, 0x11, &addresses).unwrap();
+        assert_eq!(addr, Some("192.168.0.1:56324".parse().unwrap()));
+    }
+
+    #[test]
+    fn v2_inet6() {
+        let mut addresses = [0u8; 36];
+        addresses[15] = 1;
+        addresses[31] = 2;
+        addresses[32..34].copy_from_slice(&56324u16.to_be_bytes());
+        let addr = parse_v2(0x21, 0x21, &addresses).unwrap();
+        assert_eq!(addr, Some("[::1]:56324".parse().unwrap()));
+    }
+
+    #[test]
+    fn v2_local() {
+        assert_eq!(parse_v2(0x20, 0x00, &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn v2_invalid() {
+        // Wrong version
+        assert!(parse_v2(0x11, 0x11, &[0; 12]).is_err());
+        // Truncated addresses
+        assert!(parse_v2(0x21, 0x11, &[0; 8]).is_err());
+    }
+}
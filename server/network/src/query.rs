@@ -0,0 +1,284 @@
+//! Listener for the GameSpy 4 query protocol, which hosting panels
+//! and server trackers use to query a server's status over UDP.
+//!
+//! Clients first send a handshake, to which we reply with a
+//! challenge token. A stat request carrying that token is then
+//! answered with either basic or full stats, depending on the
+//! length of the request.
+
+use crate::SERVER_VERSION;
+use feather_core::network::ProtocolVersion;
+use feather_server_types::{Config, OnlinePlayers};
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+
+/// Bytes at the start of every request.
+const MAGIC: [u8; 2] = [0xFE, 0xFD];
+const TYPE_HANDSHAKE: u8 = 0x09;
+const TYPE_STAT: u8 = 0x00;
+
+/// Only the lower four bits of each byte of a session ID are used.
+const SESSION_ID_MASK: i32 = 0x0F0F_0F0F;
+
+/// Length of a full stat request. Basic stat requests
+/// lack the four bytes of padding at the end.
+const FULL_STAT_LEN: usize = 15;
+
+/// How long a challenge token remains valid.
+const CHALLENGE_LIFETIME: Duration = Duration::from_secs(30);
+
+/// Padding preceding the key-value section of a full stat response.
+const FULL_STAT_KV_PADDING: &[u8] = b"splitnum\0\x80\0";
+/// Padding preceding the player list of a full stat response.
+const FULL_STAT_PLAYERS_PADDING: &[u8] = b"\x01player_\0\0";
+
+const GAME_TYPE: &str = "SMP";
+const GAME_ID: &str = "MINECRAFT";
+
+/// A parsed query request.
+#[derive(Debug, PartialEq, Eq)]
+enum Request {
+    Handshake {
+        session: i32,
+    },
+    Stat {
+        session: i32,
+        token: i32,
+        full: bool,
+    },
+}
+
+impl Request {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 7 || data[..2] != MAGIC {
+            return None;
+        }
+        let session = i32::from_be_bytes(data[3..7].try_into().ok()?) & SESSION_ID_MASK;
+
+        match data[2] {
+            TYPE_HANDSHAKE => Some(Request::Handshake { session }),
+            TYPE_STAT if data.len() >= 11 => Some(Request::Stat {
+                session,
+                token: i32::from_be_bytes(data[7..11].try_into().ok()?),
+                full: data.len() >= FULL_STAT_LEN,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The server's status as reported by query responses.
+struct Status<'a> {
+    motd: &'a str,
+    map: &'a str,
+    players: Vec<String>,
+    max_players: i32,
+    port: u16,
+    ip: &'a str,
+}
+
+/// Runs the query listener on the given socket.
+pub async fn run_query(
+    mut socket: UdpSocket,
+    config: Arc<Config>,
+    online_players: Arc<OnlinePlayers>,
+) {
+    let mut challenges: HashMap<SocketAddr, (i32, Instant)> = HashMap::new();
+    let mut buf = [0u8; 1460];
+
+    loop {
+        let (len, addr) = match socket.recv_from(&mut buf).await {
+            Ok(res) => res,
+            Err(e) => {
+                log::debug!("Failed to receive query request: {}", e);
+                continue;
+            }
+        };
+
+        let request = match Request::parse(&buf[..len]) {
+            Some(request) => request,
+            None => continue,
+        };
+
+        let now = Instant::now();
+        challenges.retain(|_, (_, issued)| now.duration_since(*issued) < CHALLENGE_LIFETIME);
+
+        let response = match request {
+            Request::Handshake { session } => {
+                let token = rand::random::<i32>() & 0x00FF_FFFF;
+                challenges.insert(addr, (token, now));
+                handshake_response(session, token)
+            }
+            Request::Stat {
+                session,
+                token,
+                full,
+            } => {
+                match challenges.get(&addr) {
+                    Some(&(expected, _)) if expected == token => (),
+                    _ => continue,
+                }
+
+                let status = Status {
+                    motd: &config.server.motd,
+                    map: &config.world.name,
+                    players: online_players
+                        .sample(usize::MAX)
+                        .into_iter()
+                        .map(|(_, name)| name)
+                        .collect(),
+                    max_players: config.server.max_players,
+                    port: config.server.port,
+                    ip: &config.server.address,
+                };
+                if full {
+                    full_stat_response(session, &status)
+                } else {
+                    basic_stat_response(session, &status)
+                }
+            }
+        };
+
+        if let Err(e) = socket.send_to(&response, addr).await {
+            log::debug!("Failed to send query response to {}: {}", addr, e);
+        }
+    }
+}
+
+fn response_header(ty: u8, session: i32) -> Vec<u8> {
+    let mut response = vec![ty];
+    response.extend_from_slice(&session.to_be_bytes());
+    response
+}
+
+fn push_str(buf: &mut Vec<u8>, s: &str) {
+    buf.extend_from_slice(s.as_bytes());
+    buf.push(0);
+}
+
+fn handshake_response(session: i32, token: i32) -> Vec<u8> {
+    let mut response = response_header(TYPE_HANDSHAKE, session);
+    push_str(&mut response, &token.to_string());
+    response
+}
+
+fn basic_stat_response(session: i32, status: &Status) -> Vec<u8> {
+    let mut response = response_header(TYPE_STAT, session);
+    push_str(&mut response, status.motd);
+    push_str(&mut response, GAME_TYPE);
+    push_str(&mut response, status.map);
+    push_str(&mut response, &status.players.len().to_string());
+    push_str(&mut response, &status.max_players.to_string());
+    // Unlike everything else, the port is little-endian.
+    response.extend_from_slice(&status.port.to_le_bytes());
+    push_str(&mut response, status.ip);
+    response
+}
+
+fn full_stat_response(session: i32, status: &Status) -> Vec<u8> {
+    let mut response = response_header(TYPE_STAT, session);
+    response.extend_from_slice(FULL_STAT_KV_PADDING);
+
+    let num_players = status.players.len().to_string();
+    let max_players = status.max_players.to_string();
+    let port = status.port.to_string();
+    let pairs = [
+        ("hostname", status.motd),
+        ("gametype", GAME_TYPE),
+        ("game_id", GAME_ID),
+        ("version", ProtocolVersion::NATIVE.name()),
+        ("plugins", SERVER_VERSION),
+        ("map", status.map),
+        ("numplayers", &num_players),
+        ("maxplayers", &max_players),
+        ("hostport", &port),
+        ("hostip", status.ip),
+    ];
+    for (key, value) in pairs.iter() {
+        push_str(&mut response, key);
+        push_str(&mut response, value);
+    }
+    response.push(0);
+
+    response.extend_from_slice(FULL_STAT_PLAYERS_PADDING);
+    for player in &status.players {
+        push_str(&mut response, player);
+    }
+    response.push(0);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> Status<'static> {
+        Status {
+            motd: "A Feather server",
+            map: "world",
+            players: vec![String::from("a"), String::from("b")],
+            max_players: 16,
+            port: 25565,
+            ip: "127.0.0.1",
+        }
+    }
+
+    #[test]
+    fn parse_requests() {
+        let handshake = [0xFE, 0xFD, 0x09, 0x00, 0x00, 0x00, 0x01];
+        assert_eq!(
+            Request::parse(&handshake),
+            Some(Request::Handshake { session: 1 })
+        );
+
+        let basic = [
+            0xFE, 0xFD, 0x00, 0xFF, 0x00, 0x00, 0x01, 0x00, 0x91, 0x29, 0x5B,
+        ];
+        assert_eq!(
+            Request::parse(&basic),
+            Some(Request::Stat {
+                session: 0x0F00_0001,
+                token: 9_513_307,
+                full: false,
+            })
+        );
+
+        let mut full = basic.to_vec();
+        full.extend_from_slice(&[0; 4]);
+        match Request::parse(&full) {
+            Some(Request::Stat { full: true, .. }) => (),
+            _ => panic!(),
+        }
+
+        assert_eq!(Request::parse(&[0xFE, 0xFD, 0x00]), None);
+        assert_eq!(Request::parse(&[0x00; 11]), None);
+    }
+
+    #[test]
+    fn handshake_token_is_a_string() {
+        assert_eq!(
+            handshake_response(1, 9_513_307),
+            b"\x09\x00\x00\x00\x019513307\0".to_vec()
+        );
+    }
+
+    #[test]
+    fn basic_stat() {
+        let response = basic_stat_response(1, &status());
+        let mut expected = b"\x00\x00\x00\x00\x01A Feather server\0SMP\0world\02\016\0".to_vec();
+        expected.extend_from_slice(&[0xDD, 0x63]);
+        expected.extend_from_slice(b"127.0.0.1\0");
+        assert_eq!(response, expected);
+    }
+
+    #[test]
+    fn full_stat_ends_with_players() {
+        let response = full_stat_response(1, &status());
+        assert!(response[5..].starts_with(FULL_STAT_KV_PADDING));
+        assert!(response.ends_with(b"\x01player_\0\0a\0b\0\0"));
+    }
+}
@@ -0,0 +1,203 @@
+//! Limits on how quickly clients may send packets which
+//! are expensive for the server to handle.
+//!
+//! Each kind of limited packet has a token bucket, refilled
+//! at the configured rate up to the burst size. Packets are
+//! dropped while their bucket is empty, and clients who keep
+//! sending them are kicked.
+
+use feather_core::network::PacketType;
+use feather_server_types::{RateLimit, RateLimits};
+use std::time::Instant;
+
+/// A kind of packet with its own limit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Category {
+    Chat,
+    Digging,
+    WindowClicks,
+    Movement,
+}
+
+impl Category {
+    const COUNT: usize = 4;
+
+    /// Returns the category of a packet type, or `None`
+    /// if packets of the type are not limited.
+    fn of(ty: PacketType) -> Option<Self> {
+        match ty {
+            PacketType::ChatMessageServerbound => Some(Category::Chat),
+            PacketType::PlayerDigging => Some(Category::Digging),
+            PacketType::ClickWindow | PacketType::CreativeInventoryAction => {
+                Some(Category::WindowClicks)
+            }
+            PacketType::Player
+            | PacketType::PlayerPosition
+            | PacketType::PlayerPositionAndLookServerbound
+            | PacketType::PlayerLook
+            | PacketType::VehicleMoveServerbound => Some(Category::Movement),
+            _ => None,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// What to do with a received packet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// The packet is over its limit and should be dropped.
+    Throttle,
+    /// The client is flooding the server and should be kicked.
+    Kick,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    last_refill: Instant,
+    /// Number of packets dropped since one was last allowed.
+    dropped: u32,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.burst),
+            last_refill: now,
+            dropped: 0,
+        }
+    }
+
+    fn take(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.limit.rate)
+            .min(f64::from(self.limit.burst));
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            self.dropped = 0;
+            true
+        } else {
+            self.dropped += 1;
+            false
+        }
+    }
+}
+
+/// Rate limiter for the packets received on one connection.
+#[derive(Debug)]
+pub struct RateLimiter {
+    buckets: [Bucket; Category::COUNT],
+    kick_threshold: u32,
+}
+
+impl RateLimiter {
+    pub fn new(limits: &RateLimits, now: Instant) -> Self {
+        Self {
+            buckets: [
+                Bucket::new(limits.chat, now),
+                Bucket::new(limits.digging, now),
+                Bucket::new(limits.window_clicks, now),
+                Bucket::new(limits.movement, now),
+            ],
+            kick_threshold: limits.kick_threshold,
+        }
+    }
+
+    /// Decides what to do with a packet of type `ty` received at `now`.
+    pub fn check(&mut self, ty: PacketType, now: Instant) -> Verdict {
+        let bucket = match Category::of(ty) {
+            Some(category) => &mut self.buckets[category.index()],
+            None => return Verdict::Allow,
+        };
+
+        if bucket.take(now) {
+            Verdict::Allow
+        } else if bucket.dropped > self.kick_threshold {
+            Verdict::Kick
+        } else {
+            Verdict::Throttle
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn limits() -> RateLimits {
+        let limit = RateLimit {
+            rate: 2.0,
+            burst: 3,
+        };
+        RateLimits {
+            enabled: true,
+            kick_threshold: 4,
+            chat: limit,
+            digging: limit,
+            window_clicks: limit,
+            movement: limit,
+        }
+    }
+
+    #[test]
+    fn burst_then_throttle() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(&limits(), now);
+        for _ in 0..3 {
+            assert_eq!(
+                limiter.check(PacketType::ChatMessageServerbound, now),
+                Verdict::Allow
+            );
+        }
+        assert_eq!(
+            limiter.check(PacketType::ChatMessageServerbound, now),
+            Verdict::Throttle
+        );
+
+        // Other categories have their own buckets.
+        assert_eq!(
+            limiter.check(PacketType::PlayerDigging, now),
+            Verdict::Allow
+        );
+        assert_eq!(
+            limiter.check(PacketType::KeepAliveServerbound, now),
+            Verdict::Allow
+        );
+
+        // Half a second refills one token at two per second.
+        let later = now + Duration::from_millis(500);
+        assert_eq!(
+            limiter.check(PacketType::ChatMessageServerbound, later),
+            Verdict::Allow
+        );
+        assert_eq!(
+            limiter.check(PacketType::ChatMessageServerbound, later),
+            Verdict::Throttle
+        );
+    }
+
+    #[test]
+    fn sustained_flooding_kicks() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(&limits(), now);
+        for _ in 0..3 {
+            limiter.check(PacketType::PlayerPosition, now);
+        }
+        for _ in 0..4 {
+            assert_eq!(
+                limiter.check(PacketType::PlayerPosition, now),
+                Verdict::Throttle
+            );
+        }
+        assert_eq!(limiter.check(PacketType::PlayerLook, now), Verdict::Kick);
+    }
+}
@@ -0,0 +1,223 @@
+//! Packets waiting to be written to a client's connection.
+//!
+//! Packets sent during a tick are queued and written at once
+//! when the server flushes at the end of the tick. Keep-alives
+//! and disconnects are written before anything else, so they are
+//! not held up behind a backlog of chunk data. Once the queue grows
+//! past its limit, as it does for clients who cannot keep up, queued
+//! packets which are only cosmetic are dropped, oldest first.
+
+use feather_core::network::{PacketType, RawPacket};
+use std::collections::VecDeque;
+
+/// How urgently a packet needs to be sent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Priority {
+    /// Sent ahead of any other queued packets.
+    Urgent,
+    Normal,
+    /// May be dropped if the queue is full.
+    Low,
+}
+
+impl Priority {
+    fn of(ty: PacketType) -> Self {
+        match ty {
+            PacketType::KeepAliveClientbound | PacketType::DisconnectPlay => Priority::Urgent,
+            // Packets which the client recovers from missing, because
+            // they are cosmetic or later packets overwrite their state.
+            PacketType::Particle
+            | PacketType::SoundEffect
+            | PacketType::NamedSoundEffect
+            | PacketType::AnimationClientbound
+            | PacketType::BlockBreakAnimation
+            | PacketType::EntityLook
+            | PacketType::EntityHeadLook
+            | PacketType::EntityVelocity
+            | PacketType::TimeUpdate => Priority::Low,
+            _ => Priority::Normal,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SendQueue {
+    urgent: VecDeque<RawPacket>,
+    queued: VecDeque<RawPacket>,
+    /// Total length of queued packets, before compression.
+    bytes: usize,
+    /// Length above which low priority packets are dropped.
+    limit: usize,
+}
+
+impl SendQueue {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            urgent: VecDeque::new(),
+            queued: VecDeque::new(),
+            bytes: 0,
+            limit,
+        }
+    }
+
+    /// Queues a packet. Returns the number of low priority packets
+    /// dropped because the queue went over its limit.
+    pub fn push(&mut self, packet: RawPacket) -> usize {
+        self.bytes += packet.len();
+        if Priority::of(packet.ty()) == Priority::Urgent {
+            self.urgent.push_back(packet);
+        } else {
+            self.queued.push_back(packet);
+        }
+
+        if self.bytes > self.limit {
+            self.drop_low_priority()
+        } else {
+            0
+        }
+    }
+
+    /// Drops low priority packets, oldest first,
+    /// until the queue is back within its limit.
+    fn drop_low_priority(&mut self) -> usize {
+        let mut bytes = self.bytes;
+        let limit = self.limit;
+        let before = self.queued.len();
+        self.queued.retain(|packet| {
+            if bytes > limit && Priority::of(packet.ty()) == Priority::Low {
+                bytes -= packet.len();
+                false
+            } else {
+                true
+            }
+        });
+        self.bytes = bytes;
+        before - self.queued.len()
+    }
+
+    /// Takes the next packet to write.
+    pub fn pop(&mut self) -> Option<RawPacket> {
+        let packet = self
+            .urgent
+            .pop_front()
+            .or_else(|| self.queued.pop_front())?;
+        self.bytes -= packet.len();
+        Some(packet)
+    }
+
+    /// Drops all queued packets except urgent ones, which
+    /// is done when disconnecting a client so that the
+    /// disconnect packet is not stuck behind a backlog.
+    pub fn discard_backlog(&mut self) {
+        self.queued.clear();
+        self.bytes = self.urgent.iter().map(RawPacket::len).sum();
+    }
+
+    /// Returns the number of queued packets.
+    pub fn len(&self) -> usize {
+        self.urgent.len() + self.queued.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the total length of queued packets, before compression.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::network::packets::{
+        ChatMessageClientbound, KeepAliveClientbound, TimeUpdate,
+    };
+    use feather_core::network::{MinecraftCodec, Packet, PacketDirection, PacketStage};
+
+    fn prepare(packet: &dyn Packet) -> RawPacket {
+        let mut codec = MinecraftCodec::new(PacketDirection::Clientbound);
+        codec.set_stage(PacketStage::Play);
+        codec.prepare(packet)
+    }
+
+    fn chat(message: &str) -> RawPacket {
+        prepare(&ChatMessageClientbound {
+            json_data: message.to_owned(),
+            position: 0,
+        })
+    }
+
+    fn time() -> RawPacket {
+        prepare(&TimeUpdate {
+            world_age: 0,
+            time_of_day: 0,
+        })
+    }
+
+    fn keep_alive() -> RawPacket {
+        prepare(&KeepAliveClientbound { keep_alive_id: 0 })
+    }
+
+    fn pop_all(queue: &mut SendQueue) -> Vec<PacketType> {
+        std::iter::from_fn(|| queue.pop()).map(|p| p.ty()).collect()
+    }
+
+    #[test]
+    fn urgent_packets_go_first() {
+        let mut queue = SendQueue::new(usize::max_value());
+        queue.push(chat("a"));
+        queue.push(time());
+        queue.push(keep_alive());
+
+        assert_eq!(
+            pop_all(&mut queue),
+            vec![
+                PacketType::KeepAliveClientbound,
+                PacketType::ChatMessageClientbound,
+                PacketType::TimeUpdate
+            ]
+        );
+        assert_eq!(queue.bytes(), 0);
+    }
+
+    #[test]
+    fn low_priority_packets_dropped_over_limit() {
+        let limit = time().len() * 2 + chat("a").len();
+        let mut queue = SendQueue::new(limit);
+        assert_eq!(queue.push(time()), 0);
+        assert_eq!(queue.push(chat("a")), 0);
+        assert_eq!(queue.push(time()), 0);
+        // Over the limit: the oldest time update is dropped.
+        assert_eq!(queue.push(chat("a")), 1);
+        assert!(queue.bytes() <= limit);
+
+        assert_eq!(
+            pop_all(&mut queue),
+            vec![
+                PacketType::ChatMessageClientbound,
+                PacketType::TimeUpdate,
+                PacketType::ChatMessageClientbound
+            ]
+        );
+    }
+
+    #[test]
+    fn normal_packets_are_kept_over_limit() {
+        let mut queue = SendQueue::new(0);
+        assert_eq!(queue.push(chat("a")), 0);
+        assert_eq!(queue.push(chat("b")), 0);
+        assert_eq!(pop_all(&mut queue).len(), 2);
+    }
+
+    #[test]
+    fn discard_backlog_keeps_urgent_packets() {
+        let mut queue = SendQueue::new(usize::max_value());
+        queue.push(chat("a"));
+        queue.push(keep_alive());
+        queue.discard_backlog();
+        assert_eq!(queue.bytes(), keep_alive().len());
+        assert_eq!(pop_all(&mut queue), vec![PacketType::KeepAliveClientbound]);
+    }
+}
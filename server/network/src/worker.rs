@@ -7,25 +7,32 @@
 //! to the worker for any given client.
 
 use crate::initial_handler::{Action, InitialHandler};
+use crate::legacy_ping::{handle_legacy_ping, ConnectionStart};
+use crate::proxy_protocol;
+use crate::rate_limit::{RateLimiter, Verdict};
+use crate::send_queue::SendQueue;
 use crate::{ListenerToServerMessage, NewClientInfo, ServerToListenerMessage};
 use feather_core::anvil::entity::BaseEntityData;
 use feather_core::anvil::player::PlayerData;
-use feather_core::network::{MinecraftCodec, Packet, PacketDirection};
+use feather_core::network::packets::DisconnectPlay;
+use feather_core::network::{MinecraftCodec, Packet, PacketDirection, RawPacket};
+use feather_core::text::{Text, TextRoot};
 use feather_core::util::{Position, Vec3d};
 use feather_server_types::{
-    Config, PacketBuffers, ServerToWorkerMessage, Uuid, WorkerToServerMessage,
+    AdvancementProgress, Config, ConnectionCounters, ConnectionStats, OnlinePlayers, PacketBuffers,
+    ServerToWorkerMessage, Statistics, Uuid, WorkerToServerMessage,
 };
 use fecs::Entity;
 use futures::future::Either;
-use futures::SinkExt;
-use futures::StreamExt;
+use futures::{Sink, SinkExt, StreamExt};
 use std::net::SocketAddr;
 use std::path::Path;
-use std::sync::atomic::AtomicU32;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::net::TcpStream;
 use tokio::sync::Mutex;
-use tokio_util::codec::Framed;
+use tokio_util::codec::{Framed, FramedParts};
 
 struct Worker {
     framed: Framed<TcpStream, MinecraftCodec>,
@@ -51,6 +58,14 @@ struct Worker {
     initial_handler: Option<InitialHandler>,
     /// The entity for the player on the server thread.
     entity: Entity,
+    /// Limits on packets received after login, or `None`
+    /// if rate limiting is disabled.
+    rate_limiter: Option<RateLimiter>,
+    /// Packets sent by the server which have
+    /// not yet been written to the connection.
+    send_queue: SendQueue,
+    /// Traffic counters shared with the server.
+    counters: Arc<ConnectionCounters>,
 }
 
 /// Runs a worker task for the given client.
@@ -61,24 +76,61 @@ pub async fn run_worker(
     listener_tx: flume::Sender<ListenerToServerMessage>,
     listener_rx: Arc<Mutex<flume::Receiver<ServerToListenerMessage>>>,
     config: Arc<Config>,
-    player_count: Arc<AtomicU32>,
+    online_players: Arc<OnlinePlayers>,
     server_icon: Arc<Option<String>>,
     packet_buffers: Arc<PacketBuffers>,
 ) {
+    let mut stream = stream;
+    let mut ip = ip;
+    if config.proxy.proxy_protocol {
+        match proxy_protocol::read_header(&mut stream).await {
+            Ok(Some(client)) => {
+                log::info!("Connection received from {} (through {})", client, ip);
+                ip = client;
+            }
+            Ok(None) => log::debug!("Connection received from {} without a client", ip),
+            Err(e) => {
+                log::debug!("Failed to read PROXY header from {}: {}", ip, e);
+                return;
+            }
+        }
+    } else {
+        log::info!("Connection received from {}", ip);
+    }
+
+    let read_buf = match handle_legacy_ping(&mut stream, &config, &online_players).await {
+        Ok(ConnectionStart::Handshake(read_buf)) => read_buf,
+        Ok(ConnectionStart::LegacyPing) => return,
+        Err(e) => {
+            log::debug!("Failed to check for a legacy ping from {}: {}", ip, e);
+            return;
+        }
+    };
+
     let (server_tx, rx) = flume::unbounded();
     let (tx, server_rx) = flume::unbounded();
 
     let initial_handler = Some(InitialHandler::new(
         Arc::clone(&config),
-        Arc::clone(&player_count),
+        Arc::clone(&online_players),
         Arc::clone(&server_icon),
     ));
 
     let codec = MinecraftCodec::new(PacketDirection::Serverbound);
-    let framed = Framed::new(stream, codec);
+    // Bytes read while checking for a legacy ping
+    // are the start of the handshake.
+    let mut parts = FramedParts::new::<Box<dyn Packet>>(stream, codec);
+    parts.read_buf = read_buf;
+    let framed = Framed::from_parts(parts);
 
     let entity = request_entity(&listener_tx, &mut *listener_rx.lock().await).await;
 
+    let rate_limiter = if config.rate_limits.enabled {
+        Some(RateLimiter::new(&config.rate_limits, Instant::now()))
+    } else {
+        None
+    };
+
     let mut worker = Worker {
         framed,
         ip,
@@ -90,7 +142,10 @@ pub async fn run_worker(
         tx,
         initial_handler,
         entity,
+        send_queue: SendQueue::new(config.io.send_queue_limit),
+        counters: Arc::new(ConnectionCounters::default()),
         config,
+        rate_limiter,
     };
 
     let msg = match run_worker_impl(&mut worker).await {
@@ -149,6 +204,7 @@ async fn run_worker_impl(worker: &mut Worker) -> anyhow::Result<()> {
             }
         }
 
+        publish_stats(worker);
         tokio::task::yield_now().await;
     }
 }
@@ -158,20 +214,110 @@ async fn handle_server_to_worker_message(
     msg: ServerToWorkerMessage,
 ) -> anyhow::Result<()> {
     match msg {
-        ServerToWorkerMessage::SendPacket(packet) => worker.framed.send(packet).await?,
-        ServerToWorkerMessage::Disconnect => anyhow::bail!("server requested disconnect"),
+        ServerToWorkerMessage::SendPacket(packet) => queue_packet(worker, &*packet),
+        ServerToWorkerMessage::Flush => flush(worker).await?,
+        ServerToWorkerMessage::Disconnect => {
+            worker.send_queue.discard_backlog();
+            flush(worker).await?;
+            anyhow::bail!("server requested disconnect");
+        }
     }
 
     Ok(())
 }
 
+fn queue_packet(worker: &mut Worker, packet: &dyn Packet) {
+    let packet = worker.framed.codec().prepare(packet);
+    let dropped = worker.send_queue.push(packet);
+    if dropped > 0 {
+        log::debug!(
+            "Send queue for {} is full; dropped {} packets",
+            worker.ip,
+            dropped
+        );
+    }
+}
+
+/// Updates the counters read by the server.
+fn publish_stats(worker: &Worker) {
+    let codec = worker.framed.codec().stats();
+    worker.counters.store(ConnectionStats {
+        packets_in: codec.packets_read,
+        bytes_in: codec.bytes_read,
+        packets_out: codec.packets_written,
+        bytes_out: codec.bytes_written,
+        uncompressed_bytes_out: codec.uncompressed_bytes_written,
+        queued_packets: worker.send_queue.len() as u64,
+        queued_bytes: worker.send_queue.bytes() as u64,
+    });
+}
+
+/// Writes queued packets to the connection.
+///
+/// Messages from the server are picked up between packets,
+/// so that keep-alives and disconnects sent while a large
+/// backlog is being written do not have to wait for it.
+async fn flush(worker: &mut Worker) -> anyhow::Result<()> {
+    let mut disconnect = false;
+    loop {
+        while let Ok(msg) = worker.rx.try_recv() {
+            match msg {
+                ServerToWorkerMessage::SendPacket(packet) => queue_packet(worker, &*packet),
+                ServerToWorkerMessage::Flush => (),
+                ServerToWorkerMessage::Disconnect => {
+                    worker.send_queue.discard_backlog();
+                    disconnect = true;
+                }
+            }
+        }
+
+        match worker.send_queue.pop() {
+            Some(packet) => feed(&mut worker.framed, packet).await?,
+            None => break,
+        }
+    }
+
+    SinkExt::<RawPacket>::flush(&mut worker.framed).await?;
+
+    if disconnect {
+        anyhow::bail!("server requested disconnect");
+    }
+    Ok(())
+}
+
+/// Encodes a packet into the connection's write buffer. The buffer
+/// is only written out once it grows large or is flushed.
+async fn feed(
+    framed: &mut Framed<TcpStream, MinecraftCodec>,
+    packet: RawPacket,
+) -> anyhow::Result<()> {
+    futures::future::poll_fn(|cx| Sink::<RawPacket>::poll_ready(Pin::new(&mut *framed), cx))
+        .await?;
+    Pin::new(framed).start_send(packet)
+}
+
 async fn handle_packet(worker: &mut Worker, packet: Box<dyn Packet>) -> anyhow::Result<()> {
     if let Some(ref mut ih) = worker.initial_handler {
         ih.handle_packet(packet).await;
 
         handle_ih_actions(worker).await?;
     } else {
-        worker.packet_buffers.push(worker.entity, packet);
+        let ty = packet.ty();
+        let verdict = match worker.rate_limiter {
+            Some(ref mut limiter) => limiter.check(ty, Instant::now()),
+            None => Verdict::Allow,
+        };
+        match verdict {
+            Verdict::Allow => worker.packet_buffers.push(worker.entity, packet),
+            Verdict::Throttle => (),
+            Verdict::Kick => {
+                let disconnect: Box<dyn Packet> = Box::new(DisconnectPlay {
+                    reason: TextRoot::from(Text::from("Sending packets too quickly")).into(),
+                });
+                worker.framed.send(disconnect).await?;
+                anyhow::bail!("sent {:?} packets too quickly", ty);
+            }
+        }
     }
 
     Ok(())
@@ -193,18 +339,29 @@ async fn handle_ih_actions(worker: &mut Worker) -> anyhow::Result<()> {
             Action::EnableEncryption(key) => worker.framed.codec_mut().enable_encryption(key),
             Action::Disconnect => anyhow::bail!("initial handler requested disconnect"),
             Action::SetStage(stage) => worker.framed.codec_mut().set_stage(stage),
+            Action::SetVersion(version) => worker.framed.codec_mut().set_version(version),
             Action::JoinGame(info) => {
-                let data = load_player_data(&worker.config, info.uuid).await?;
+                let (data, first_join) = load_player_data(&worker.config, info.uuid).await?;
                 let position = data.entity.read_position()?;
+                let statistics = load_statistics(&worker.config, info.uuid).await;
+                let advancements = load_advancements(&worker.config, info.uuid).await;
                 let info = NewClientInfo {
-                    ip: worker.ip,
+                    // Players connecting through a proxy
+                    // keep the port of the proxy's connection.
+                    ip: info
+                        .ip
+                        .map_or(worker.ip, |ip| SocketAddr::new(ip, worker.ip.port())),
                     username: info.username.unwrap_or_else(|| String::from("undefined")),
                     profile: info.props,
                     uuid: info.uuid,
                     data,
+                    first_join,
                     position,
+                    statistics,
+                    advancements,
                     sender: worker.server_tx.clone(),
                     receiver: worker.server_rx.take().unwrap(),
+                    counters: Arc::clone(&worker.counters),
                     entity: worker.entity,
                 };
 
@@ -223,10 +380,15 @@ async fn handle_ih_actions(worker: &mut Worker) -> anyhow::Result<()> {
 
 const DEFAULT_POSITION: Position = position!(0.0, 70.0, 0.0); // TODO: better calculation
 
-async fn load_player_data(config: &Config, uuid: Uuid) -> Result<PlayerData, anyhow::Error> {
+/// Loads a player's data, creating it if they have not joined before.
+/// Returns the data and whether it was created.
+async fn load_player_data(
+    config: &Config,
+    uuid: Uuid,
+) -> Result<(PlayerData, bool), anyhow::Error> {
     log::debug!("Loading player data for UUID {}", uuid);
     match feather_core::anvil::player::load_player_data(Path::new(&config.world.name), uuid).await {
-        Ok(data) => Ok(data),
+        Ok(data) => Ok((data, false)),
         Err(e) => {
             log::debug!(
                 "Failed to load player data for {} ({}); creating default data",
@@ -238,6 +400,7 @@ async fn load_player_data(config: &Config, uuid: Uuid) -> Result<PlayerData, any
                 entity: BaseEntityData::new(DEFAULT_POSITION, Vec3d::broadcast(0.0)),
                 gamemode: config.server.default_gamemode.id() as i32,
                 inventory: vec![],
+                ..Default::default()
             };
 
             feather_core::anvil::player::save_player_data(
@@ -247,7 +410,31 @@ async fn load_player_data(config: &Config, uuid: Uuid) -> Result<PlayerData, any
             )
             .await?;
 
-            Ok(data)
+            Ok((data, true))
+        }
+    }
+}
+
+/// Loads a player's statistics. Statistics which fail to load
+/// are reset rather than keeping the player from joining.
+async fn load_statistics(config: &Config, uuid: Uuid) -> Statistics {
+    match Statistics::load(Path::new(&config.world.name), uuid).await {
+        Ok(statistics) => statistics,
+        Err(e) => {
+            log::error!("Failed to load statistics for UUID {}: {}", uuid, e);
+            Statistics::default()
+        }
+    }
+}
+
+/// Loads a player's advancement progress, which
+/// is likewise reset if it fails to load.
+async fn load_advancements(config: &Config, uuid: Uuid) -> AdvancementProgress {
+    match AdvancementProgress::load(Path::new(&config.world.name), uuid).await {
+        Ok(progress) => progress,
+        Err(e) => {
+            log::error!("Failed to load advancements for UUID {}: {}", uuid, e);
+            AdvancementProgress::default()
         }
     }
 }
@@ -0,0 +1,183 @@
+//! Entity-to-entity collision: overlapping living entities
+//! are pushed apart each tick.
+//!
+//! Pushes are accumulated for all entities before being applied,
+//! so an entity's velocity changes at most once per tick and
+//! the velocity broadcast system sends a single update for it.
+
+use feather_core::util::{Gamemode, Position};
+use feather_server_types::{
+    player_height, AABBExt, BumpVec, Game, Living, NoPush, Physics, Player, Stationary, Velocity,
+    PLAYER_WIDTH,
+};
+use feather_server_util::nearby_entities;
+use fecs::{component, Entity, IntoQuery, Read, World};
+use glm::DVec3;
+
+/// Strength of the push applied to each of two overlapping entities.
+const PUSH_STRENGTH: f64 = 0.05;
+
+/// Distance (in blocks) beyond an entity's own half-width within
+/// which other entities are considered for collision.
+const SEARCH_MARGIN: f64 = 2.0;
+
+/// System which pushes apart overlapping living entities.
+#[fecs::system]
+pub fn entity_collision(game: &mut Game, world: &mut World) {
+    let mut pushes = BumpVec::new_in(game.bump());
+
+    <(Read<Position>, Read<Velocity>)>::query()
+        .filter(component::<Living>())
+        .iter_entities(world.inner())
+        .for_each(|(entity, (pos, _))| {
+            if !can_be_pushed(world, entity) {
+                return;
+            }
+            let size = match collision_size(world, entity) {
+                Some(size) => size,
+                None => return,
+            };
+
+            let radius = glm::vec3(
+                size.x / 2.0 + SEARCH_MARGIN,
+                size.y + SEARCH_MARGIN,
+                size.z / 2.0 + SEARCH_MARGIN,
+            );
+
+            let mut push = glm::vec3(0.0, 0.0, 0.0);
+            for other in nearby_entities(world, game, *pos, radius) {
+                if other == entity || !world.has::<Living>(other) || !can_push(world, other) {
+                    continue;
+                }
+                let other_size = match collision_size(world, other) {
+                    Some(size) => size,
+                    None => continue,
+                };
+                let other_pos = *world.get::<Position>(other);
+
+                if overlaps(*pos, size, other_pos, other_size) {
+                    push += push_velocity(*pos, other_pos);
+                }
+            }
+
+            if push != glm::vec3(0.0, 0.0, 0.0) {
+                pushes.push((entity, push));
+            }
+        });
+
+    for (entity, push) in pushes {
+        world.get_mut::<Velocity>(entity).0 += push;
+    }
+}
+
+/// Returns whether an entity may push other entities.
+fn can_push(world: &World, entity: Entity) -> bool {
+    !world.has::<NoPush>(entity) && !is_spectator(world, entity)
+}
+
+/// Returns whether an entity may be pushed by other entities.
+///
+/// Players are moved by their clients, which perform
+/// their own collision, so they are never pushed here.
+fn can_be_pushed(world: &World, entity: Entity) -> bool {
+    can_push(world, entity) && !world.has::<Stationary>(entity) && !world.has::<Player>(entity)
+}
+
+pub(crate) fn is_spectator(world: &World, entity: Entity) -> bool {
+    world
+        .try_get::<Gamemode>(entity)
+        .map(|gamemode| *gamemode == Gamemode::Spectator)
+        .unwrap_or(false)
+}
+
+/// Returns the size of an entity's collision box.
+pub(crate) fn collision_size(world: &World, entity: Entity) -> Option<DVec3> {
+    if let Some(physics) = world.try_get::<Physics>(entity) {
+        Some(physics.bbox.size())
+    } else if world.has::<Player>(entity) {
+        Some(glm::vec3(
+            PLAYER_WIDTH,
+            player_height(world, entity),
+            PLAYER_WIDTH,
+        ))
+    } else {
+        None
+    }
+}
+
+/// Returns whether two collision boxes overlap. Positions
+/// are at the bottom center of each box.
+fn overlaps(a: Position, a_size: DVec3, b: Position, b_size: DVec3) -> bool {
+    (a.x - b.x).abs() < (a_size.x + b_size.x) / 2.0
+        && (a.z - b.z).abs() < (a_size.z + b_size.z) / 2.0
+        && a.y < b.y + b_size.y
+        && b.y < a.y + a_size.y
+}
+
+/// Computes the velocity to add to an entity at `pos`
+/// which is being pushed by an entity at `other`.
+///
+/// The push is horizontal and points away from `other`.
+fn push_velocity(pos: Position, other: Position) -> DVec3 {
+    let mut dx = pos.x - other.x;
+    let mut dz = pos.z - other.z;
+
+    let max = dx.abs().max(dz.abs());
+    if max < 0.01 {
+        // entities are at the same position; there is
+        // no sensible direction to push in
+        return glm::vec3(0.0, 0.0, 0.0);
+    }
+
+    let distance = max.sqrt();
+    dx /= distance;
+    dz /= distance;
+
+    let factor = (1.0 / distance).min(1.0);
+    dx *= factor * PUSH_STRENGTH;
+    dz *= factor * PUSH_STRENGTH;
+
+    glm::vec3(dx, 0.0, dz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::position;
+
+    #[test]
+    fn overlapping_boxes() {
+        let size = glm::vec3(0.6, 1.8, 0.6);
+        let a = position!(0.0, 64.0, 0.0);
+
+        assert!(overlaps(a, size, position!(0.5, 64.0, 0.0), size));
+        assert!(!overlaps(a, size, position!(0.6, 64.0, 0.0), size));
+        assert!(overlaps(a, size, position!(0.0, 65.0, 0.3), size));
+        assert!(!overlaps(a, size, position!(0.0, 65.8, 0.0), size));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn push_points_away() {
+        let push = push_velocity(position!(0.5, 64.0, 0.0), position!(0.0, 64.0, 0.0));
+        assert!(push.x > 0.0);
+        assert_eq!(push.y, 0.0);
+        assert_eq!(push.z, 0.0);
+
+        let push = push_velocity(position!(0.0, 64.0, -0.2), position!(0.0, 64.0, 0.0));
+        assert!(push.z < 0.0);
+    }
+
+    #[test]
+    fn push_is_symmetric() {
+        let a = position!(0.1, 64.0, 0.3);
+        let b = position!(-0.2, 64.0, 0.1);
+        assert_eq!(push_velocity(a, b), -push_velocity(b, a));
+    }
+
+    #[test]
+    fn no_push_at_same_position() {
+        let a = position!(0.0, 64.0, 0.0);
+        assert_eq!(push_velocity(a, a), glm::vec3(0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,352 @@
+//! Explosions: computing which blocks are destroyed and which
+//! entities are affected, and applying the result.
+//!
+//! The algorithm matches vanilla: rays are cast outward from the
+//! center of the explosion, losing strength as they travel and pass
+//! through blocks. Any block reached by a ray with remaining strength
+//! is destroyed.
+
+use crate::block_impacted_by_ray;
+use crate::collision::collision_size;
+use feather_core::blocks::{BlockId, BlockKind};
+use feather_core::network::packets::Explosion;
+use feather_core::util::{BlockPosition, Position};
+use feather_server_types::{
+    BlockUpdateCause, DamageCause, EntityDamageRequest, ExplosionEffects, ExplosionEvent,
+    ExplosionRequest, ExplosionSource, Game, Network, Player, TntPrimeRequest, Velocity,
+    MOB_GRIEFING, TNT_FUSE_TICKS,
+};
+use feather_server_util::{knockback_resistance, nearby_entities};
+use fecs::{component, Entity, IntoQuery, Read, World};
+use glm::DVec3;
+use parking_lot::Mutex;
+use rand::Rng;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Number of ray origins along each edge of the cube
+/// from which explosion rays are cast.
+const RAYS_PER_EDGE: usize = 16;
+
+/// Distance traveled by a ray in each step.
+const RAY_STEP: f64 = 0.3;
+
+/// Strength lost by a ray in each step, regardless
+/// of the blocks it passes through.
+const RAY_ATTENUATION: f32 = 0.225;
+
+/// Players farther than this from an explosion
+/// are not sent the explosion packet.
+const BROADCAST_DISTANCE: f64 = 64.0;
+
+/// Handles an `ExplosionRequest`.
+#[fecs::event_handler]
+pub fn explosion_request(event: &ExplosionRequest, game: &mut Game, world: &mut World) {
+    // Creepers only destroy blocks if `mobGriefing` is on.
+    let blocks = match event.source {
+        ExplosionSource::Creeper(_) if !game.game_rules.bool(MOB_GRIEFING) => vec![],
+        _ => destroyed_blocks(game, event.pos, event.power),
+    };
+    let effects = ExplosionEffects {
+        blocks,
+        entities: affected_entities(game, world, event.pos, event.power),
+        cancelled: false,
+    };
+    let effects = Arc::new(Mutex::new(effects));
+
+    game.handle(
+        world,
+        ExplosionEvent {
+            pos: event.pos,
+            power: event.power,
+            source: event.source,
+            effects: Arc::clone(&effects),
+        },
+    );
+
+    let effects = std::mem::take(&mut *effects.lock());
+    if effects.cancelled {
+        return;
+    }
+
+    // Compute impacts before destroying blocks, since
+    // exposure depends on the blocks around the explosion.
+    let impacts: Vec<_> = effects
+        .entities
+        .iter()
+        .filter_map(|&entity| {
+            impact(game, world, event.pos, event.power, entity).map(|impact| (entity, impact))
+        })
+        .collect();
+    let knockback: Vec<_> = impacts
+        .iter()
+        .map(|&(entity, impact)| (entity, knockback(world, event.pos, entity, impact)))
+        .collect();
+
+    // TNT caught in the explosion is primed with a
+    // shorter fuse, setting off a chain reaction.
+    let tnt: Vec<_> = effects
+        .blocks
+        .iter()
+        .copied()
+        .filter(|&block| game.block_at(block).map(BlockId::kind) == Some(BlockKind::Tnt))
+        .collect();
+
+    // All blocks destroyed by the explosion share a batch.
+    let cause = match event.source {
+        ExplosionSource::Creeper(creeper) => BlockUpdateCause::entity(creeper),
+        _ => BlockUpdateCause::unknown(),
+    };
+    for &block in &effects.blocks {
+        game.set_block_at_with_cause(world, block, BlockId::air(), cause);
+    }
+
+    for pos in tnt {
+        let fuse = chain_reaction_fuse(&mut *game.rng());
+        game.handle(
+            world,
+            TntPrimeRequest {
+                pos,
+                fuse,
+                igniter: None,
+            },
+        );
+    }
+
+    for &(entity, knockback) in &knockback {
+        // Players are knocked back by their clients
+        // using the motion in the explosion packet.
+        if !world.has::<Player>(entity) && world.has::<Velocity>(entity) {
+            let velocity = world.get::<Velocity>(entity).0 + knockback;
+            game.set_velocity(world, entity, velocity);
+        }
+    }
+
+    broadcast_explosion(world, event, &effects.blocks, &knockback);
+
+    let cause = DamageCause::Explosion {
+        pos: event.pos,
+        source: event.source,
+    };
+    for &(entity, impact) in &impacts {
+        game.handle(
+            world,
+            EntityDamageRequest {
+                entity,
+                damage: damage(impact, event.power),
+                cause,
+            },
+        );
+    }
+}
+
+/// Returns the unit vectors along which explosion rays are cast:
+/// one through each point on the surface of a cube.
+fn ray_directions() -> impl Iterator<Item = DVec3> {
+    let max = RAYS_PER_EDGE - 1;
+    (0..RAYS_PER_EDGE)
+        .flat_map(|x| (0..RAYS_PER_EDGE).map(move |y| (x, y)))
+        .flat_map(|(x, y)| (0..RAYS_PER_EDGE).map(move |z| (x, y, z)))
+        .filter(move |&(x, y, z)| x == 0 || x == max || y == 0 || y == max || z == 0 || z == max)
+        .map(move |(x, y, z)| {
+            let to_unit = |c: usize| c as f64 / max as f64 * 2.0 - 1.0;
+            glm::vec3(to_unit(x), to_unit(y), to_unit(z)).normalize()
+        })
+}
+
+/// Computes the blocks destroyed by an explosion.
+fn destroyed_blocks(game: &Game, center: Position, power: f32) -> Vec<BlockPosition> {
+    let mut blocks = BTreeSet::new();
+    let mut rng = game.rng();
+
+    for direction in ray_directions() {
+        let mut strength = power * (0.7 + rng.gen::<f32>() * 0.6);
+        let mut pos: DVec3 = center.into();
+
+        while strength > 0.0 {
+            let block_pos = Position::from(pos).block();
+            let block = match game.block_at(block_pos) {
+                Some(block) => block,
+                None => break,
+            };
+
+            if !block.is_air() {
+                strength -= (block.blast_resistance() + 0.3) * 0.3;
+                if strength > 0.0 {
+                    blocks.insert(block_pos);
+                }
+            }
+
+            pos += direction * RAY_STEP;
+            strength -= RAY_ATTENUATION;
+        }
+    }
+
+    blocks.into_iter().collect()
+}
+
+/// Returns the entities within range of an explosion.
+fn affected_entities(game: &Game, world: &World, center: Position, power: f32) -> Vec<Entity> {
+    let radius = f64::from(power) * 2.0;
+    nearby_entities(world, game, center, glm::vec3(radius, radius, radius))
+        .into_iter()
+        .filter(|&entity| {
+            world.get::<Position>(entity).distance_squared_to(center) <= radius * radius
+        })
+        .collect()
+}
+
+/// Computes the impact of an explosion on an entity, which
+/// decreases with distance and with the blocks in between.
+fn impact(game: &Game, world: &World, center: Position, power: f32, entity: Entity) -> Option<f64> {
+    let radius = f64::from(power) * 2.0;
+    let pos = *world.get::<Position>(entity);
+
+    let distance = (pos.distance_squared_to(center)).sqrt() / radius;
+    if distance > 1.0 {
+        return None;
+    }
+
+    let exposure = exposure(game, world, center, entity);
+    Some((1.0 - distance) * exposure)
+}
+
+/// Computes the knockback applied to an entity by an explosion,
+/// reduced by the entity's knockback resistance.
+fn knockback(world: &World, center: Position, entity: Entity, impact: f64) -> DVec3 {
+    let offset: DVec3 = (*world.get::<Position>(entity) - center).into();
+    if offset.norm_squared() == 0.0 {
+        return glm::vec3(0.0, 0.0, 0.0);
+    }
+    let resistance = knockback_resistance(world, entity).max(0.0).min(1.0);
+    offset.normalize() * impact * (1.0 - resistance)
+}
+
+/// Returns the fuse of TNT primed by another explosion.
+fn chain_reaction_fuse(rng: &mut impl Rng) -> u32 {
+    let quarter = TNT_FUSE_TICKS / 4;
+    rng.gen_range(0, quarter) + quarter / 2
+}
+
+/// Computes the damage dealt to an entity by an explosion.
+fn damage(impact: f64, power: f32) -> f32 {
+    ((impact * impact + impact) / 2.0 * 7.0 * f64::from(power) * 2.0 + 1.0).floor() as f32
+}
+
+/// Returns the fraction of an entity's bounding box which
+/// is exposed to an explosion.
+fn exposure(game: &Game, world: &World, center: Position, entity: Entity) -> f64 {
+    let size = match collision_size(world, entity) {
+        Some(size) => size,
+        None => return 1.0,
+    };
+    let pos: DVec3 = (*world.get::<Position>(entity)).into();
+    let min = pos - glm::vec3(size.x / 2.0, 0.0, size.z / 2.0);
+    let center: DVec3 = center.into();
+
+    let step = |extent: f64| 1.0 / (extent * 2.0 + 1.0);
+    let (step_x, step_y, step_z) = (step(size.x), step(size.y), step(size.z));
+
+    let mut exposed = 0;
+    let mut total = 0;
+
+    let mut fx = 0.0;
+    while fx <= 1.0 {
+        let mut fy = 0.0;
+        while fy <= 1.0 {
+            let mut fz = 0.0;
+            while fz <= 1.0 {
+                let sample = min + glm::vec3(size.x * fx, size.y * fy, size.z * fz);
+                let ray = center - sample;
+                if block_impacted_by_ray(game, sample, ray, ray.norm_squared()).is_none() {
+                    exposed += 1;
+                }
+                total += 1;
+                fz += step_z;
+            }
+            fy += step_y;
+        }
+        fx += step_x;
+    }
+
+    f64::from(exposed) / f64::from(total)
+}
+
+/// Sends the explosion packet to nearby players.
+fn broadcast_explosion(
+    world: &World,
+    event: &ExplosionRequest,
+    blocks: &[BlockPosition],
+    knockback: &[(Entity, DVec3)],
+) {
+    let origin = event.pos.block();
+    let records: Vec<_> = blocks
+        .iter()
+        .map(|block| {
+            (
+                (block.x - origin.x) as i8,
+                (block.y - origin.y) as i8,
+                (block.z - origin.z) as i8,
+            )
+        })
+        .collect();
+
+    for (player, (network, pos)) in <(Read<Network>, Read<Position>)>::query()
+        .filter(component::<Player>())
+        .iter_entities(world.inner())
+    {
+        if pos.distance_squared_to(event.pos) > BROADCAST_DISTANCE * BROADCAST_DISTANCE {
+            continue;
+        }
+
+        let motion = knockback
+            .iter()
+            .find(|(entity, _)| *entity == player)
+            .map(|(_, motion)| *motion)
+            .unwrap_or_else(|| glm::vec3(0.0, 0.0, 0.0));
+
+        network.send(Explosion {
+            x: event.pos.x as f32,
+            y: event.pos.y as f32,
+            z: event.pos.z as f32,
+            radius: event.power,
+            records: records.clone(),
+            player_motion_x: motion.x as f32,
+            player_motion_y: motion.y as f32,
+            player_motion_z: motion.z as f32,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rays_cover_cube_surface() {
+        let directions: Vec<_> = ray_directions().collect();
+        // 16^3 points minus the 14^3 interior points
+        assert_eq!(directions.len(), 16 * 16 * 16 - 14 * 14 * 14);
+        assert!(directions
+            .iter()
+            .all(|direction| (direction.norm() - 1.0).abs() < 1e-9));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn damage_scales_with_impact() {
+        // a TNT explosion at point-blank range
+        assert_eq!(damage(1.0, 4.0), 57.0);
+        assert_eq!(damage(0.0, 4.0), 1.0);
+        assert_eq!(damage(0.5, 4.0), 22.0);
+    }
+
+    #[test]
+    fn chain_reactions_shorten_fuse() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let fuse = chain_reaction_fuse(&mut rng);
+            assert!(fuse >= 10 && fuse < 30);
+        }
+    }
+}
@@ -3,8 +3,14 @@
 extern crate nalgebra_glm as glm;
 
 mod block_bboxes;
+mod collision;
 mod entity;
+mod explosion;
 mod math;
+mod projectile;
 
+pub use collision::entity_collision;
 pub use entity::entity_physics;
+pub use explosion::explosion_request;
 pub use math::*;
+pub use projectile::projectile_physics;
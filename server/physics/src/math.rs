@@ -91,6 +91,23 @@ pub fn block_impacted_by_ray(
     origin: DVec3,
     ray: DVec3,
     max_distance_squared: f64,
+) -> Option<RayImpact> {
+    block_impacted_by_ray_where(game, origin, ray, max_distance_squared, BlockId::is_solid)
+}
+
+/// Finds the first block impacted by the given ray
+/// for which `hits` returns `true`, e.g. to find the
+/// fluid a player is looking at. Other blocks are
+/// passed through.
+///
+/// Traces up to `max_distance` before returning `None`
+/// if no block was found.
+pub fn block_impacted_by_ray_where(
+    game: &Game,
+    origin: DVec3,
+    ray: DVec3,
+    max_distance_squared: f64,
+    hits: impl Fn(BlockId) -> bool,
 ) -> Option<RayImpact> {
     if ray == vec3(0.0, 0.0, 0.0) {
         return None;
@@ -166,7 +183,7 @@ pub fn block_impacted_by_ray(
 
     while dist_traveled.magnitude_squared() < max_distance_squared {
         if let Some(block) = game.block_at(current_pos) {
-            if block.is_solid() {
+            if hits(block) {
                 // Calculate world-space position of
                 // impact using `ncollide`.
                 let ray = Ray::new(Point3::from(origin), direction);
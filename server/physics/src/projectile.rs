@@ -0,0 +1,267 @@
+//! Projectile physics: arrows, snowballs, eggs, ender pearls and tridents.
+//!
+//! Each tick, a projectile's path is ray-cast against blocks
+//! and entities. If something is hit, a `ProjectileHitEvent` is
+//! triggered; handlers for each projectile kind (in the `entity`
+//! crate) decide what happens next.
+
+use crate::block_impacted_by_ray;
+use crate::collision::{collision_size, is_spectator};
+use feather_core::blocks::BlockKind;
+use feather_core::util::Position;
+use feather_server_types::{
+    BumpVec, Game, InGround, Living, Projectile, ProjectileHitEvent, ProjectileTarget, Velocity,
+};
+use feather_server_util::nearby_entities;
+use fecs::{Entity, IntoQuery, Read, World};
+use glm::DVec3;
+
+/// Amount by which entity bounding boxes are expanded
+/// when testing for projectile hits.
+const HIT_MARGIN: f64 = 0.3;
+
+/// Number of ticks during which a projectile cannot
+/// hit the entity which shot it.
+const SHOOTER_IMMUNITY_TICKS: u32 = 5;
+
+/// System which moves projectiles and detects hits.
+#[fecs::system]
+pub fn projectile_physics(game: &mut Game, world: &mut World) {
+    let mut projectiles = BumpVec::new_in(game.bump());
+    projectiles.extend(
+        <(Read<Position>, Read<Velocity>, Read<Projectile>)>::query()
+            .iter_entities(world.inner())
+            .map(|(entity, (pos, vel, projectile))| (entity, *pos, vel.0, *projectile)),
+    );
+
+    let mut hits = BumpVec::new_in(game.bump());
+    let mut dislodged = BumpVec::new_in(game.bump());
+    let mut to_despawn = BumpVec::new_in(game.bump());
+
+    for (entity, pos, velocity, mut projectile) in projectiles {
+        if let Some(in_ground) = world.try_get::<InGround>(entity).map(|g| *g) {
+            let still_solid = game
+                .block_at(in_ground.block)
+                .map(|block| block.is_solid())
+                .unwrap_or(false);
+            if still_solid {
+                continue;
+            }
+            dislodged.push(entity);
+        }
+
+        let origin: DVec3 = pos.into();
+        let mut end = origin + velocity;
+        let mut target = None;
+
+        if let Some(impact) = block_impacted_by_ray(game, origin, velocity, velocity.norm_squared())
+        {
+            end = impact.pos.into();
+            target = Some(ProjectileTarget::Block(impact.block));
+        }
+
+        let entity_hit = if projectile.hits_entities {
+            entity_impacted(game, world, entity, &projectile, origin, end)
+        } else {
+            None
+        };
+        if let Some((hit, t)) = entity_hit {
+            end = origin + (end - origin) * t;
+            target = Some(ProjectileTarget::Entity(hit));
+        }
+
+        let mut new_pos = Position::from(end);
+        if let Some(ProjectileTarget::Block(_)) = target {
+            // Back off slightly so the projectile rests
+            // outside the block it hit.
+            new_pos = new_pos - velocity.normalize() * 0.05;
+        }
+        let (yaw, pitch) = rotation_from_velocity(velocity);
+        new_pos.yaw = yaw;
+        new_pos.pitch = pitch;
+
+        let block = match game.block_at(new_pos.block()) {
+            Some(block) => block,
+            None => {
+                // Projectile has left loaded chunks.
+                to_despawn.push(entity);
+                continue;
+            }
+        };
+
+        let drag = if block.kind() == BlockKind::Water {
+            projectile.kind.water_drag()
+        } else {
+            projectile.kind.drag()
+        };
+        let mut new_velocity = velocity * drag;
+        new_velocity.y -= projectile.kind.gravity();
+
+        projectile.ticks_in_air += 1;
+
+        *world.get_mut::<Position>(entity) = new_pos;
+        world.get_mut::<Velocity>(entity).0 = new_velocity;
+        *world.get_mut::<Projectile>(entity) = projectile;
+
+        if let Some(target) = target {
+            hits.push(ProjectileHitEvent {
+                projectile: entity,
+                kind: projectile.kind,
+                shooter: projectile.shooter,
+                target,
+                pos: new_pos,
+                velocity,
+            });
+        }
+    }
+
+    for entity in dislodged {
+        let _ = world.remove::<InGround>(entity);
+    }
+
+    for entity in to_despawn {
+        game.despawn(entity, world);
+    }
+
+    for event in hits {
+        game.handle(world, event);
+    }
+}
+
+/// Finds the first entity intersecting the segment from `origin`
+/// to `end`, returning the entity and the fraction of the
+/// segment traveled before it was hit.
+fn entity_impacted(
+    game: &Game,
+    world: &World,
+    projectile_entity: Entity,
+    projectile: &Projectile,
+    origin: DVec3,
+    end: DVec3,
+) -> Option<(Entity, f64)> {
+    let center = Position::from((origin + end) / 2.0);
+    let half = glm::abs(&(end - origin)) / 2.0;
+    let margin = glm::vec3(2.0, 2.0, 2.0);
+
+    nearby_entities(world, game, center, half + margin)
+        .into_iter()
+        .filter(|&other| other != projectile_entity)
+        .filter(|&other| world.has::<Living>(other))
+        .filter(|&other| !is_spectator(world, other))
+        .filter(|&other| {
+            projectile.shooter != Some(other) || projectile.ticks_in_air >= SHOOTER_IMMUNITY_TICKS
+        })
+        .filter_map(|other| {
+            let size = collision_size(world, other)?;
+            let pos: DVec3 = (*world.get::<Position>(other)).into();
+
+            let margin = glm::vec3(HIT_MARGIN, HIT_MARGIN, HIT_MARGIN);
+            let half_width = glm::vec3(size.x / 2.0, 0.0, size.z / 2.0);
+            let min = pos - half_width - margin;
+            let max = pos + half_width + glm::vec3(0.0, size.y, 0.0) + margin;
+
+            segment_intersects_box(origin, end, min, max).map(|t| (other, t))
+        })
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+}
+
+/// Tests whether the segment from `start` to `end` intersects
+/// the axis-aligned box with corners `min` and `max`. If so,
+/// returns the fraction of the segment at which it enters the box.
+fn segment_intersects_box(start: DVec3, end: DVec3, min: DVec3, max: DVec3) -> Option<f64> {
+    let direction = end - start;
+    let mut t_min = 0.0f64;
+    let mut t_max = 1.0f64;
+
+    for axis in 0..3 {
+        if direction[axis].abs() < 1e-9 {
+            if start[axis] < min[axis] || start[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let inverse = 1.0 / direction[axis];
+        let mut t1 = (min[axis] - start[axis]) * inverse;
+        let mut t2 = (max[axis] - start[axis]) * inverse;
+        if t1 > t2 {
+            std::mem::swap(&mut t1, &mut t2);
+        }
+
+        t_min = t_min.max(t1);
+        t_max = t_max.min(t2);
+        if t_min > t_max {
+            return None;
+        }
+    }
+
+    Some(t_min)
+}
+
+/// Computes the yaw and pitch of a projectile
+/// facing along its velocity.
+fn rotation_from_velocity(velocity: DVec3) -> (f32, f32) {
+    let horizontal = (velocity.x * velocity.x + velocity.z * velocity.z).sqrt();
+    let yaw = (-velocity.x).atan2(velocity.z).to_degrees();
+    let pitch = (-velocity.y).atan2(horizontal).to_degrees();
+    (yaw as f32, pitch as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_hits_box() {
+        let min = glm::vec3(-0.5, 0.0, -0.5);
+        let max = glm::vec3(0.5, 2.0, 0.5);
+
+        let t = segment_intersects_box(
+            glm::vec3(-2.0, 1.0, 0.0),
+            glm::vec3(2.0, 1.0, 0.0),
+            min,
+            max,
+        )
+        .unwrap();
+        assert!((t - 0.375).abs() < 1e-9);
+
+        // starting inside the box
+        assert_eq!(
+            segment_intersects_box(glm::vec3(0.0, 1.0, 0.0), glm::vec3(3.0, 1.0, 0.0), min, max),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn segment_misses_box() {
+        let min = glm::vec3(-0.5, 0.0, -0.5);
+        let max = glm::vec3(0.5, 2.0, 0.5);
+
+        // passes above
+        assert!(segment_intersects_box(
+            glm::vec3(-2.0, 3.0, 0.0),
+            glm::vec3(2.0, 3.0, 0.0),
+            min,
+            max
+        )
+        .is_none());
+        // stops short
+        assert!(segment_intersects_box(
+            glm::vec3(-2.0, 1.0, 0.0),
+            glm::vec3(-1.0, 1.0, 0.0),
+            min,
+            max
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn rotation_faces_velocity() {
+        let (yaw, pitch) = rotation_from_velocity(glm::vec3(0.0, 0.0, 1.0));
+        assert!(yaw.abs() < 1e-4);
+        assert!(pitch.abs() < 1e-4);
+
+        let (_, pitch) = rotation_from_velocity(glm::vec3(0.0, -1.0, 0.0));
+        assert!((pitch - 90.0).abs() < 1e-4);
+    }
+}
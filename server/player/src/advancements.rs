@@ -0,0 +1,249 @@
+//! Granting advancements to players, and keeping their
+//! advancements screens up to date.
+//!
+//! A player is only sent the advancements they can see. Others,
+//! such as hidden advancements and the children of those, are sent
+//! once they become visible. Clients show a toast for advancements
+//! which are made in any update after the first.
+
+use crate::statistics::entity_type;
+use feather_core::inventory::Inventory;
+use feather_core::network::packets::Advancements;
+use feather_core::text::{Text, TextComponentBuilder, Translate};
+use feather_core::util::Position;
+use feather_server_types::{
+    advancement_time_now, Advancement, AdvancementProgress, AdvancementRegistry, ChatEvent,
+    ChatPosition, EntityDeathEvent, Game, InventoryUpdateEvent, Name, Network, Player,
+    PlayerJoinEvent, Trigger, ANNOUNCE_ADVANCEMENTS, TPS,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Interval, in ticks, at which players' locations
+/// trigger advancements.
+const LOCATION_INTERVAL: u64 = TPS;
+
+/// Component storing the advancements a player has been sent.
+#[derive(Debug, Default)]
+pub struct SentAdvancements(BTreeSet<String>);
+
+/// Sends joining players the advancements they can see.
+#[fecs::event_handler]
+pub fn on_player_join_send_advancements(
+    event: &PlayerJoinEvent,
+    world: &mut World,
+    advancements: &AdvancementRegistry,
+) {
+    if world.has::<AdvancementProgress>(event.player) {
+        send_advancements(world, event.player, advancements, true, &[]);
+    }
+}
+
+/// Sends a player the advancements which became visible to them,
+/// along with their progress towards those and the `changed` ones.
+/// If `reset` is set, the client discards its advancements first,
+/// and is sent all those it can see.
+fn send_advancements(
+    world: &World,
+    player: Entity,
+    advancements: &AdvancementRegistry,
+    reset: bool,
+    changed: &[&str],
+) {
+    let progress = world.get::<AdvancementProgress>(player);
+    let mut sent = world.get_mut::<SentAdvancements>(player);
+    if reset {
+        sent.0.clear();
+    }
+
+    let updated: Vec<&Advancement> = changed
+        .iter()
+        .filter(|id| sent.0.contains(**id))
+        .filter_map(|id| advancements.get(id))
+        .collect();
+    let added: Vec<&Advancement> = advancements
+        .iter()
+        .filter(|advancement| !sent.0.contains(&advancement.id))
+        .filter(|advancement| advancements.is_visible(advancement, &progress))
+        .collect();
+    sent.0
+        .extend(added.iter().map(|advancement| advancement.id.clone()));
+
+    let criteria_progress = added
+        .iter()
+        .chain(&updated)
+        .map(|advancement| {
+            let achieved = progress.achieved(&advancement.id);
+            let criteria = advancement
+                .criteria
+                .keys()
+                .map(|criterion| {
+                    let time = achieved.and_then(|achieved| achieved.get(criterion));
+                    (criterion.clone(), time.map(|&time| time as i64))
+                })
+                .collect();
+            (advancement.id.clone(), criteria)
+        })
+        .collect();
+
+    world.get::<Network>(player).send(Advancements {
+        reset,
+        added: added
+            .iter()
+            .map(|advancement| (advancement.id.clone(), advancement.to_data()))
+            .collect(),
+        removed: vec![],
+        progress: criteria_progress,
+    });
+}
+
+/// Achieves the criteria which a trigger meets for a player,
+/// making the advancements whose requirements are then met.
+pub fn trigger_advancements(game: &mut Game, world: &mut World, player: Entity, trigger: Trigger) {
+    if !world.has::<AdvancementProgress>(player) {
+        return;
+    }
+
+    let resources = Arc::clone(&game.resources);
+    let advancements = resources.get::<AdvancementRegistry>();
+    let time = advancement_time_now();
+
+    let mut changed = vec![];
+    let mut made = vec![];
+    {
+        let mut progress = world.get_mut::<AdvancementProgress>(player);
+        for advancement in advancements.iter() {
+            if progress.is_done(&advancement.id) {
+                continue;
+            }
+
+            let mut achieved_any = false;
+            for (name, criterion) in &advancement.criteria {
+                if criterion.is_met(&trigger) && progress.achieve(&advancement.id, name, time) {
+                    achieved_any = true;
+                }
+            }
+            if !achieved_any {
+                continue;
+            }
+
+            changed.push(advancement.id.as_str());
+            let done = progress
+                .achieved(&advancement.id)
+                .map_or(false, |achieved| advancement.is_done_with(achieved));
+            if done {
+                progress.set_done(&advancement.id);
+                made.push(advancement);
+            }
+        }
+    }
+
+    if changed.is_empty() {
+        return;
+    }
+    send_advancements(world, player, &advancements, false, &changed);
+
+    for advancement in made {
+        announce_advancement(game, world, player, advancement);
+    }
+}
+
+/// Announces in chat that a player made an advancement.
+fn announce_advancement(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    advancement: &Advancement,
+) {
+    let display = match &advancement.display {
+        Some(display) if display.announce_to_chat => display,
+        _ => return,
+    };
+    if !game.game_rules.bool(ANNOUNCE_ADVANCEMENTS) {
+        return;
+    }
+
+    let color = display.frame.color();
+    let hover =
+        display.title.clone().color(color.clone()) + Text::from("\n") + display.description.clone();
+    let title = (Text::from("[") + display.title.clone() + Text::from("]"))
+        .color(color)
+        .on_hover_show_text(hover);
+    let name = Text::from(world.get::<Name>(player).0.clone());
+
+    game.handle(
+        world,
+        ChatEvent {
+            message: Translate::from(display.frame.announcement_key()) * vec![name, title],
+            position: ChatPosition::SystemMessage,
+            sender: None,
+        },
+    );
+}
+
+/// Triggers advancements for the items a player holds.
+#[fecs::event_handler]
+pub fn on_inventory_update_trigger_advancements(
+    event: &InventoryUpdateEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    if !world.has::<AdvancementProgress>(event.player) {
+        return;
+    }
+
+    let items: Vec<_> = world
+        .get::<Inventory>(event.player)
+        .items()
+        .iter()
+        .flatten()
+        .map(|stack| stack.ty)
+        .collect();
+    trigger_advancements(game, world, event.player, Trigger::InventoryChanged(&items));
+}
+
+/// Triggers advancements for kills by and of players.
+#[fecs::event_handler]
+pub fn on_entity_death_trigger_advancements(
+    event: &EntityDeathEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    let victim = event.entity;
+    let killer = match event.cause.attacker() {
+        Some(killer) if world.is_alive(killer) => killer,
+        _ => return,
+    };
+
+    if let Some(victim_type) = entity_type(world, victim) {
+        trigger_advancements(game, world, killer, Trigger::KilledEntity(victim_type));
+    }
+    if let Some(killer_type) = entity_type(world, killer) {
+        trigger_advancements(game, world, victim, Trigger::KilledByEntity(killer_type));
+    }
+}
+
+/// System which triggers advancements for
+/// the biome each player is in.
+#[fecs::system]
+pub fn trigger_location_advancements(game: &mut Game, world: &mut World) {
+    if game.tick_count % LOCATION_INTERVAL != 0 {
+        return;
+    }
+
+    let locations: Vec<_> = <Read<Position>>::query()
+        .filter(component::<Player>() & component::<AdvancementProgress>())
+        .iter_entities(world.inner())
+        .filter_map(|(player, pos)| {
+            let block = pos.block();
+            let chunk = game.chunk_map.chunk_at(pos.chunk())?;
+            let biome = chunk.biome_at((block.x & 15) as usize, (block.z & 15) as usize);
+            Some((player, biome))
+        })
+        .collect();
+
+    for (player, biome) in locations {
+        trigger_advancements(game, world, player, Trigger::Location(biome));
+    }
+}
@@ -0,0 +1,61 @@
+//! Bone meal, which players use on plants to make them grow.
+
+use crate::remove_one_item;
+use feather_core::inventory::SlotIndex;
+use feather_core::network::packets::Effect;
+use feather_core::util::{BlockPosition, Gamemode};
+use feather_server_types::{BlockUpdateCause, Game};
+use feather_server_util::{consumes_items, fertilize, Growth};
+use fecs::{Entity, World};
+
+/// World event which displays the bone meal particles.
+const EFFECT_BONE_MEAL_PARTICLES: i32 = 2005;
+
+/// Applies the bone meal held in `slot` to the block at `pos`.
+/// The bone meal is used up if the block can be fertilized.
+pub fn use_bone_meal(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    pos: BlockPosition,
+) {
+    let block = match game.block_at(pos) {
+        Some(block) => block,
+        None => return,
+    };
+    let growth = {
+        let mut rng = game.rng();
+        fertilize(block, pos, |pos| game.block_at(pos), &mut *rng)
+    };
+    let growth = match growth {
+        Some(growth) => growth,
+        None => return,
+    };
+
+    match growth {
+        Growth::Blocks(blocks) => {
+            for (pos, block) in blocks {
+                game.set_block_at_with_cause(world, pos, block, BlockUpdateCause::entity(player));
+            }
+        }
+        Growth::Drop(stack) => entity::item::drop_block_item(game, world, pos, stack),
+    }
+
+    game.broadcast_chunk_update(
+        world,
+        Effect {
+            effect_id: EFFECT_BONE_MEAL_PARTICLES,
+            location: pos,
+            // zero uses the default number of particles
+            data: 0,
+            disable_relative_volume: false,
+        },
+        pos.chunk(),
+        None,
+    );
+
+    if consumes_items(*world.get::<Gamemode>(player)) {
+        remove_one_item(game, world, player, slot);
+    }
+}
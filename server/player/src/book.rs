@@ -0,0 +1,134 @@
+//! Books: editing a book and quill and signing it into
+//! a written book, and opening written books.
+
+use crate::{hand_slot, IteratorExt};
+use feather_core::inventory::{Inventory, SlotIndex};
+use feather_core::items::{Item, ItemNbt, ItemStack};
+use feather_core::network::packets::EditBook;
+use feather_core::text::{Text, TextRoot};
+use feather_core::util::Hand;
+use feather_server_types::{Game, InventoryUpdateEvent, Name, PacketBuffers};
+use feather_server_util::send_plugin_message;
+use fecs::{Entity, World};
+use smallvec::smallvec;
+use std::sync::Arc;
+
+/// The maximum number of pages in a book.
+const MAX_PAGES: usize = 50;
+/// The maximum number of characters on a page.
+const MAX_PAGE_LENGTH: usize = 256;
+/// The maximum number of characters in a book's title.
+const MAX_TITLE_LENGTH: usize = 16;
+
+/// Plugin channel on which the client is told to open
+/// the written book in a hand.
+const BOOK_OPEN_CHANNEL: &str = "minecraft:book_open";
+
+/// System which handles Edit Book packets, sent when a player
+/// saves or signs the book and quill they are holding.
+#[fecs::system]
+pub fn handle_edit_book(game: &mut Game, world: &mut World, packet_buffers: &Arc<PacketBuffers>) {
+    packet_buffers
+        .received::<EditBook>()
+        .for_each_valid(world, |world, (player, packet)| {
+            let hand = match packet.hand {
+                0 => Hand::Main,
+                _ => Hand::Off,
+            };
+            let slot = hand_slot(world, player, hand);
+
+            let held = world.get::<Inventory>(player).item_at(slot).cloned();
+            let (held, edited) = match (held, packet.new_book) {
+                (Some(held), Some(edited))
+                    if held.ty == Item::WritableBook && edited.ty == Item::WritableBook =>
+                {
+                    (held, edited)
+                }
+                _ => return,
+            };
+
+            let pages = edited.pages();
+            let valid = pages.len() <= MAX_PAGES
+                && pages
+                    .iter()
+                    .all(|page| page.chars().count() <= MAX_PAGE_LENGTH);
+            if !valid {
+                // Undo the edit on the client.
+                update_slot(game, world, player, slot);
+                return;
+            }
+
+            let book = if packet.is_signing {
+                let title = edited
+                    .nbt()
+                    .and_then(|nbt| nbt.title.clone())
+                    .unwrap_or_default();
+                if title.trim().is_empty() || title.chars().count() > MAX_TITLE_LENGTH {
+                    update_slot(game, world, player, slot);
+                    return;
+                }
+                let author = world.get::<Name>(player).0.clone();
+                sign(&held, pages, title, author)
+            } else {
+                let mut book = held;
+                book.set_pages(pages.to_vec());
+                book
+            };
+
+            world.get_mut::<Inventory>(player).set_item_at(slot, book);
+            update_slot(game, world, player, slot);
+        });
+}
+
+/// Returns the written book made by signing `book`
+/// with the given pages, title and author.
+fn sign(book: &ItemStack, pages: &[String], title: String, author: String) -> ItemStack {
+    let pages = pages
+        .iter()
+        .map(|page| TextRoot::from(Text::from(page.as_str())).into())
+        .collect();
+
+    // Other tags of the book and quill, such as
+    // its custom name, carry over to the written book.
+    let nbt = ItemNbt {
+        pages,
+        title: Some(title),
+        author: Some(author),
+        ..book.nbt().cloned().unwrap_or_default()
+    };
+    ItemStack::new(Item::WrittenBook, 1).with_nbt(nbt)
+}
+
+fn update_slot(game: &mut Game, world: &mut World, player: Entity, slot: SlotIndex) {
+    game.handle(
+        world,
+        InventoryUpdateEvent {
+            slots: smallvec![slot],
+            player,
+        },
+    );
+}
+
+/// Opens the written book in a player's hand.
+pub fn open_book(game: &mut Game, world: &mut World, player: Entity, hand: Hand) {
+    let slot = hand_slot(world, player, hand);
+
+    // Books are marked as resolved the first time they are
+    // opened, at which point vanilla resolves selectors and
+    // scores in their pages. Those are left as they are here.
+    let resolved = world
+        .get_mut::<Inventory>(player)
+        .item_at_mut(slot)
+        .map_or(false, |book| {
+            book.modify_nbt(|nbt| std::mem::replace(&mut nbt.resolved, true))
+        });
+    if !resolved {
+        update_slot(game, world, player, slot);
+    }
+
+    let hand_id = match hand {
+        Hand::Main => 0,
+        Hand::Off => 1,
+    };
+    send_plugin_message(world, player, BOOK_OPEN_CHANNEL, vec![hand_id]);
+}
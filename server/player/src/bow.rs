@@ -0,0 +1,166 @@
+//! Bows, which players draw by holding the use button and shoot
+//! by releasing it. The longer a bow is drawn, the faster and
+//! more damaging the arrow it shoots.
+
+use crate::{hand_slot, remove_one_item, start_timed_use, ItemTimedUse};
+use entity::arrow::{ArrowPickup, ArrowProperties, BASE_DAMAGE};
+use feather_core::inventory::{Inventory, SlotIndex, SLOT_HOTBAR_OFFSET, SLOT_OFFHAND};
+use feather_core::items::{Enchantment, Item, ItemStack};
+use feather_core::util::{Gamemode, Hand, Position};
+use feather_server_types::{
+    add_statistic, EntitySpawnEvent, Game, Statistic, Velocity, PLAYER_EYE_HEIGHT,
+};
+use feather_server_util::{charge_from_ticks_held, compute_projectile_velocity};
+use fecs::{Entity, World};
+
+/// Number of ticks after which a bow is fully drawn.
+const FULL_DRAW_TICKS: u64 = 20;
+
+/// Bows released before being drawn this far do not shoot.
+const MIN_DRAW: f32 = 0.1;
+
+/// The enchantments on a bow which affect the arrows it shoots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BowEnchantments {
+    pub power: u32,
+    pub punch: u32,
+    pub flame: bool,
+    pub infinity: bool,
+}
+
+/// Returns the enchantments on a bow.
+pub fn bow_enchantments(bow: &ItemStack) -> BowEnchantments {
+    BowEnchantments {
+        power: bow.enchantment_level(Enchantment::Power),
+        punch: bow.enchantment_level(Enchantment::Punch),
+        flame: bow.enchantment_level(Enchantment::Flame) > 0,
+        infinity: bow.enchantment_level(Enchantment::Infinity) > 0,
+    }
+}
+
+/// Starts drawing the bow in the given hand. Players who are
+/// not in creative mode need an arrow to draw a bow.
+pub fn draw_bow(game: &Game, world: &mut World, player: Entity, hand: Hand) {
+    let creative = *world.get::<Gamemode>(player) == Gamemode::Creative;
+    if !creative && find_arrow(&world.get::<Inventory>(player)).is_none() {
+        return;
+    }
+
+    start_timed_use(game, world, player, hand);
+}
+
+/// Shoots an arrow from a bow which was drawn during `timed_use`.
+pub fn shoot_bow(game: &mut Game, world: &mut World, player: Entity, timed_use: ItemTimedUse) {
+    let bow = match world
+        .get::<Inventory>(player)
+        .item_at(hand_slot(world, player, timed_use.hand))
+    {
+        Some(stack) if stack.ty == Item::Bow => stack.clone(),
+        _ => return,
+    };
+    let enchantments = bow_enchantments(&bow);
+
+    let draw = draw_strength(game.tick_count - timed_use.tick_start);
+    log::trace!("Bow drawn to {}.", draw);
+    if draw < MIN_DRAW {
+        return;
+    }
+
+    let creative = *world.get::<Gamemode>(player) == Gamemode::Creative;
+    let arrow = find_arrow(&world.get::<Inventory>(player));
+    let arrow_type = match &arrow {
+        Some((_, stack)) => stack.ty,
+        // Creative players may shoot without arrows.
+        None if creative => Item::Arrow,
+        None => return,
+    };
+
+    // Infinity only spares regular arrows.
+    let consumed = !creative && !(enchantments.infinity && arrow_type == Item::Arrow);
+    if consumed {
+        let (slot, _) = arrow.unwrap();
+        remove_one_item(game, world, player, slot);
+    }
+
+    let init_position = *world.get::<Position>(player) + glm::vec3(0.0, PLAYER_EYE_HEIGHT, 0.0);
+    let direction = init_position.direction();
+
+    let arrow_velocity = compute_projectile_velocity(
+        glm::vec3(direction.x, direction.y, direction.z),
+        f64::from(draw * 3.0),
+        0.0,
+        &mut *game.rng(),
+    );
+    log::trace!(
+        "Computed exit velocity: {}. Velocity is norm {}",
+        arrow_velocity,
+        arrow_velocity.norm()
+    );
+
+    let mut damage = BASE_DAMAGE;
+    if enchantments.power > 0 {
+        damage += f64::from(enchantments.power) * 0.5 + 0.5;
+    }
+    let properties = ArrowProperties {
+        damage,
+        critical: draw >= 1.0,
+        knockback: enchantments.punch,
+        on_fire: enchantments.flame,
+    };
+
+    // Arrows which were not consumed cannot be picked up for an item.
+    let pickup = if consumed {
+        ArrowPickup::Allowed
+    } else {
+        ArrowPickup::CreativeOnly
+    };
+
+    log::trace!("Spawning arrow entity.");
+    let entity = entity::arrow::create(Some(player), pickup, properties)
+        .with(init_position)
+        .with(Velocity(arrow_velocity))
+        .build()
+        .spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity });
+    add_statistic(world, player, Statistic::Used(Item::Bow), 1);
+}
+
+/// Returns how far a bow has been drawn after the given
+/// number of ticks, from 0 to 1.
+fn draw_strength(ticks: u64) -> f32 {
+    charge_from_ticks_held(ticks.min(FULL_DRAW_TICKS) as u32) / 3.0
+}
+
+fn find_arrow(inventory: &Inventory) -> Option<(SlotIndex, ItemStack)> {
+    // Order of priority is: off-hand, hotbar (0 to 8), rest of inventory
+
+    if let Some(offhand) = inventory.item_at(SLOT_OFFHAND) {
+        if is_arrow_item(offhand.ty) {
+            return Some((SLOT_OFFHAND, offhand.clone()));
+        }
+    }
+
+    for hotbar_slot in 0..9 {
+        if let Some(hotbar_stack) = inventory.item_at(SLOT_HOTBAR_OFFSET + hotbar_slot) {
+            if is_arrow_item(hotbar_stack.ty) {
+                return Some((SLOT_HOTBAR_OFFSET + hotbar_slot, hotbar_stack.clone()));
+            }
+        }
+    }
+
+    for inv_slot in 9..=35 {
+        if let Some(inv_stack) = inventory.item_at(inv_slot) {
+            if is_arrow_item(inv_stack.ty) {
+                return Some((inv_slot, inv_stack.clone()));
+            }
+        }
+    }
+    None
+}
+
+fn is_arrow_item(item: Item) -> bool {
+    match item {
+        Item::Arrow | Item::SpectralArrow | Item::TippedArrow => true,
+        _ => false,
+    }
+}
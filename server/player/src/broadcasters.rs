@@ -6,4 +6,6 @@ mod keepalive;
 pub use animation::on_player_animation_broadcast_animation;
 pub use block::on_block_update_broadcast;
 pub use chat::on_chat_broadcast;
-pub use keepalive::broadcast_keepalive;
+pub use keepalive::{
+    broadcast_keepalive, broadcast_latency, handle_keep_alive, KeepAlive, KEEP_ALIVE_TIMEOUT,
+};
@@ -8,7 +8,11 @@ use fecs::World;
 /// events to all clients.
 #[fecs::event_handler]
 pub fn on_block_update_broadcast(event: &BlockUpdateEvent, game: &mut Game, world: &mut World) {
-    // Broadcast Block Change packet.
+    // Changes made by large edits are sent together.
+    if game.block_changes.buffer(event.pos, event.new) {
+        return;
+    }
+
     let packet = BlockChange {
         location: event.pos,
         block_id: event.new.vanilla_id() as i32,
@@ -1,19 +1,33 @@
 //! Broadcasting of chat messages
 
 use feather_core::network::packets::ChatMessageClientbound;
-use feather_server_types::{ChatEvent, ChatPosition, Game};
-use fecs::World;
+use feather_core::text::TextRoot;
+use feather_server_types::{is_ignoring, ChatEvent, ChatPosition, Game, Network};
+use fecs::{IntoQuery, Read, World};
 
 /// System that broadcasts chat messages to all players
 #[fecs::event_handler]
 pub fn on_chat_broadcast(event: &ChatEvent, game: &Game, world: &mut World) {
+    if game.config.chat.log && event.position != ChatPosition::GameInfo {
+        log::info!("[Chat] {}", event.message.to_plain());
+    }
+
     let packet = ChatMessageClientbound {
-        json_data: event.message.clone(),
+        json_data: TextRoot::from(event.message.clone()).into(),
         position: match event.position {
             ChatPosition::Chat => 0,
             ChatPosition::SystemMessage => 1,
             ChatPosition::GameInfo => 2,
         },
     };
-    game.broadcast_global(world, packet, None);
+    match event.sender {
+        Some(sender) => {
+            for (player, network) in <Read<Network>>::query().iter_entities(world.inner()) {
+                if !is_ignoring(world, player, sender) {
+                    network.send(packet.clone());
+                }
+            }
+        }
+        None => game.broadcast_global(world, packet, None),
+    }
 }
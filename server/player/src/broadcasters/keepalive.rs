@@ -1,14 +1,117 @@
-use feather_core::network::packets::KeepAliveClientbound;
-use feather_server_types::{Game, TPS};
-use fecs::World;
+//! Keep-alives, which detect dead connections and measure
+//! each player's latency for the tab list.
 
-/// Broadcasts keepalives every second.
+use crate::IteratorExt;
+use feather_core::network::packets::{
+    KeepAliveClientbound, KeepAliveServerbound, PlayerInfo, PlayerInfoAction,
+};
+use feather_server_types::{BumpVec, Game, Network, PacketBuffers, Ping, Uuid, TPS};
+use fecs::{IntoQuery, Read, World, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Time after which a player who has not answered a
+/// keep-alive is disconnected.
+pub const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval in ticks at which players' latencies are sent
+/// to the tab list.
+const LATENCY_BROADCAST_INTERVAL: u64 = 5 * TPS;
+
+/// Player component tracking the keep-alive awaiting a response.
+#[derive(Debug, Clone, Default)]
+pub struct KeepAlive {
+    /// ID of the keep-alive which was sent and the time at which it was sent.
+    pending: Option<(u64, Instant)>,
+}
+
+/// Sends keep-alives every second to players who have answered the
+/// last one, and disconnects those who have not answered in time.
+#[fecs::system]
+pub fn broadcast_keepalive(game: &mut Game, world: &mut World) {
+    if game.tick_count % TPS != 0 {
+        return;
+    }
+
+    let now = Instant::now();
+    let mut timed_out = BumpVec::new_in(game.bump());
+    <(Write<KeepAlive>, Read<Network>)>::query()
+        .iter_entities_mut(world.inner_mut())
+        .for_each(
+            |(player, (mut keep_alive, network))| match keep_alive.pending {
+                Some((_, sent)) if now.duration_since(sent) >= KEEP_ALIVE_TIMEOUT => {
+                    timed_out.push(player);
+                }
+                Some(_) => (),
+                None => {
+                    network.send(KeepAliveClientbound {
+                        keep_alive_id: game.tick_count,
+                    });
+                    keep_alive.pending = Some((game.tick_count, now));
+                }
+            },
+        );
+
+    timed_out.into_iter().for_each(|player| {
+        game.kick(player, world, "Timed out");
+    });
+}
+
+/// Handles keep-alive responses, updating the player's latency.
+#[fecs::system]
+pub fn handle_keep_alive(world: &mut World, packet_buffers: &Arc<PacketBuffers>) {
+    let now = Instant::now();
+    packet_buffers
+        .received::<KeepAliveServerbound>()
+        .for_each_valid(world, |world, (player, packet)| {
+            let sent = match world.get::<KeepAlive>(player).pending {
+                Some((id, sent)) if id as i64 == packet.id => sent,
+                // Responses to keep-alives we did not send are ignored.
+                _ => return,
+            };
+            world.get_mut::<KeepAlive>(player).pending = None;
+
+            let round_trip = now.duration_since(sent).as_millis() as u32;
+            let mut ping = world.get_mut::<Ping>(player);
+            ping.0 = smooth_ping(ping.0, round_trip);
+        });
+}
+
+/// Blends a new round trip time into the previous latency, so
+/// that a single slow response does not make the ping bars jump.
+fn smooth_ping(previous: u32, round_trip: u32) -> u32 {
+    (previous * 3 + round_trip) / 4
+}
+
+/// Periodically sends every player's latency to the tab list.
 #[fecs::system]
-pub fn broadcast_keepalive(game: &Game, world: &mut World) {
-    if game.tick_count % TPS == 0 {
-        let packet = KeepAliveClientbound {
-            keep_alive_id: game.tick_count,
+pub fn broadcast_latency(game: &Game, world: &mut World) {
+    if game.tick_count % LATENCY_BROADCAST_INTERVAL != 0 {
+        return;
+    }
+
+    let mut latencies = BumpVec::new_in(game.bump());
+    <(Read<Uuid>, Read<Ping>)>::query()
+        .iter(world.inner())
+        .for_each(|(uuid, ping)| latencies.push((*uuid, ping.0)));
+
+    for (uuid, ping) in latencies {
+        let packet = PlayerInfo {
+            action: PlayerInfoAction::UpdateLatency(ping as i32),
+            uuid,
         };
         game.broadcast_global(world, packet, None);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ping_is_smoothed() {
+        assert_eq!(smooth_ping(0, 100), 25);
+        assert_eq!(smooth_ping(100, 100), 100);
+        assert_eq!(smooth_ping(100, 20), 80);
+    }
+}
@@ -0,0 +1,200 @@
+//! Buckets, which pick up and place water and lava, carry fish,
+//! and are filled with milk by using them on cows.
+
+use crate::exchange_item;
+use feather_core::blocks::BlockId;
+use feather_core::inventory::SlotIndex;
+use feather_core::items::{Item, ItemStack};
+use feather_core::util::{BlockPosition, Gamemode, Position};
+use feather_server_physics::{block_impacted_by_ray, block_impacted_by_ray_where};
+use feather_server_types::{BlockUpdateCause, EntitySpawnEvent, Game, PLAYER_EYE_HEIGHT};
+use feather_server_util::{
+    can_place_in_gamemode, consumes_items, is_fluid_source, play_sound, Fluid, SoundCategory,
+};
+use fecs::{Entity, World};
+
+/// Maximum distance at which players can use buckets on blocks.
+const BUCKET_REACH: f64 = 5.0;
+
+/// Returns whether the given item is a bucket which
+/// can be used on blocks.
+pub fn is_placeable_bucket(item: Item) -> bool {
+    match item {
+        Item::Bucket
+        | Item::WaterBucket
+        | Item::LavaBucket
+        | Item::CodBucket
+        | Item::SalmonBucket
+        | Item::PufferfishBucket
+        | Item::TropicalFishBucket => true,
+        _ => false,
+    }
+}
+
+/// Uses the bucket `stack` held in `slot` on the block the player
+/// is looking at: empty buckets pick up fluid sources and full
+/// buckets place their contents.
+pub fn use_bucket(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    stack: &ItemStack,
+) {
+    let eyes = *world.get::<Position>(player) + glm::vec3(0.0, PLAYER_EYE_HEIGHT, 0.0);
+    let direction = eyes.direction();
+    let direction = glm::vec3(direction.x, direction.y, direction.z);
+    let origin = glm::vec3(eyes.x, eyes.y, eyes.z);
+
+    if stack.ty == Item::Bucket {
+        fill_from_block(game, world, player, slot, stack, origin, direction);
+    } else {
+        empty_into_block(game, world, player, slot, stack, origin, direction);
+    }
+}
+
+fn fill_from_block(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    stack: &ItemStack,
+    origin: glm::DVec3,
+    direction: glm::DVec3,
+) {
+    let impact = match block_impacted_by_ray_where(
+        game,
+        origin,
+        direction,
+        BUCKET_REACH * BUCKET_REACH,
+        |block| block.is_solid() || is_fluid_source(block),
+    ) {
+        Some(impact) => impact,
+        None => return,
+    };
+    let block = game.block_at(impact.block).unwrap();
+    let fluid = match Fluid::of(block) {
+        Some(fluid) if is_fluid_source(block) => fluid,
+        _ => return,
+    };
+    if !can_place_in_gamemode(*world.get::<Gamemode>(player), stack, block) {
+        return;
+    }
+
+    let (remaining, filled, sound) = match fluid {
+        Fluid::Water if !block.is_fluid() => (
+            block.with_waterlogged(false),
+            Item::WaterBucket,
+            "item.bucket.fill",
+        ),
+        Fluid::Water => (BlockId::air(), Item::WaterBucket, "item.bucket.fill"),
+        Fluid::Lava => (BlockId::air(), Item::LavaBucket, "item.bucket.fill_lava"),
+    };
+    game.set_block_at_with_cause(
+        world,
+        impact.block,
+        remaining,
+        BlockUpdateCause::entity(player),
+    );
+
+    let pos = *world.get::<Position>(player);
+    play_sound(
+        game,
+        world,
+        sound,
+        SoundCategory::Neutral,
+        pos,
+        1.0,
+        1.0,
+        Some(player),
+    );
+
+    fill_bucket(game, world, player, slot, filled);
+}
+
+fn empty_into_block(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    stack: &ItemStack,
+    origin: glm::DVec3,
+    direction: glm::DVec3,
+) {
+    let impact = match block_impacted_by_ray(game, origin, direction, BUCKET_REACH * BUCKET_REACH) {
+        Some(impact) => impact,
+        None => return,
+    };
+    let against = game.block_at(impact.block).unwrap();
+    if !can_place_in_gamemode(*world.get::<Gamemode>(player), stack, against) {
+        return;
+    }
+
+    let fluid = match stack.ty {
+        Item::LavaBucket => Fluid::Lava,
+        _ => Fluid::Water,
+    };
+
+    // Water fills waterloggable blocks. Otherwise, the fluid
+    // is placed next to the face the player looked at.
+    let (pos, block) = if fluid == Fluid::Water && against.waterlogged() == Some(false) {
+        (impact.block, against.with_waterlogged(true))
+    } else {
+        let offset = impact.face.as_vector();
+        let pos =
+            impact.block + BlockPosition::new(offset.x as i32, offset.y as i32, offset.z as i32);
+        match game.block_at(pos) {
+            Some(block) if block.is_air() || block.is_fluid() => (pos, fluid.source()),
+            _ => return,
+        }
+    };
+    game.set_block_at_with_cause(world, pos, block, BlockUpdateCause::entity(player));
+
+    let center = pos.position() + glm::vec3(0.5, 0.5, 0.5);
+    let sound = match stack.ty {
+        Item::WaterBucket => "item.bucket.empty",
+        Item::LavaBucket => "item.bucket.empty_lava",
+        _ => "item.bucket.empty_fish",
+    };
+    play_sound(
+        game,
+        world,
+        sound,
+        SoundCategory::Block,
+        center,
+        1.0,
+        1.0,
+        Some(player),
+    );
+
+    let fish = match stack.ty {
+        Item::CodBucket => Some(entity::cod::create()),
+        Item::SalmonBucket => Some(entity::salmon::create()),
+        Item::PufferfishBucket => Some(entity::pufferfish::create()),
+        Item::TropicalFishBucket => Some(entity::tropical_fish::create()),
+        _ => None,
+    };
+    if let Some(fish) = fish {
+        let entity = fish
+            .with(pos.position() + glm::vec3(0.5, 0.0, 0.5))
+            .build()
+            .spawn_in(world);
+        game.handle(world, EntitySpawnEvent { entity });
+    }
+
+    fill_bucket(game, world, player, slot, Item::Bucket);
+}
+
+/// Replaces one bucket in `slot` with a bucket of `filled`,
+/// unless the player is in creative mode.
+pub fn fill_bucket(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    filled: Item,
+) {
+    if consumes_items(*world.get::<Gamemode>(player)) {
+        exchange_item(game, world, player, slot, ItemStack::new(filled, 1));
+    }
+}
@@ -1,6 +1,9 @@
-use feather_core::text::{Color, TextRoot, Translate};
-use feather_server_types::{ChatEvent, ChatPosition, Game, Name, PlayerJoinEvent};
-use fecs::World;
+//! Join messages and the formatting of players' chat messages.
+
+use feather_core::text::{replace_formatting_codes, Color, Text, Translate};
+use feather_core::util::Gamemode;
+use feather_server_types::{chat_prefix, ChatEvent, ChatPosition, Game, Name, PlayerJoinEvent};
+use fecs::{Entity, World};
 
 #[fecs::event_handler]
 pub fn on_player_join_broadcast_join_message(
@@ -8,12 +11,9 @@ pub fn on_player_join_broadcast_join_message(
     game: &mut Game,
     world: &mut World,
 ) {
-    let message: String = {
+    let message = {
         let name = world.get::<Name>(event.player);
-        TextRoot::from(
-            Translate::MultiplayerPlayerJoined * vec![name.0.to_string()] * Color::Yellow,
-        )
-        .into()
+        Translate::MultiplayerPlayerJoined * vec![name.0.to_string()] * Color::Yellow
     };
 
     game.handle(
@@ -21,6 +21,102 @@ pub fn on_player_join_broadcast_join_message(
         ChatEvent {
             message,
             position: ChatPosition::Chat,
+            sender: None,
         },
     );
 }
+
+/// A part of the chat format.
+#[derive(Debug, PartialEq, Eq)]
+enum FormatPart<'a> {
+    Literal(&'a str),
+    /// The name of a placeholder written as `{name}`.
+    Placeholder(&'a str),
+}
+
+fn parse_format(format: &str) -> Vec<FormatPart> {
+    let mut parts = vec![];
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(len) => start + len,
+            None => break,
+        };
+        if start > 0 {
+            parts.push(FormatPart::Literal(&rest[..start]));
+        }
+        parts.push(FormatPart::Placeholder(&rest[start + 1..end]));
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(FormatPart::Literal(rest));
+    }
+    parts
+}
+
+/// Formats a player's chat message with the format in the
+/// configuration. Unknown placeholders are left as they are.
+pub fn format_chat_message(game: &Game, world: &World, player: Entity, message: &str) -> Text {
+    parse_format(&game.config.chat.format)
+        .into_iter()
+        .map(|part| match part {
+            FormatPart::Literal(literal) => Text::from(replace_formatting_codes(literal)),
+            FormatPart::Placeholder("player") => Text::from(world.get::<Name>(player).0.clone()),
+            FormatPart::Placeholder("message") => Text::from(message.to_owned()),
+            FormatPart::Placeholder("world") => Text::from(game.config.world.name.clone()),
+            FormatPart::Placeholder("gamemode") => Text::translate_with(
+                gamemode_key(*world.get::<Gamemode>(player)),
+                Vec::<Text>::new(),
+            ),
+            FormatPart::Placeholder("prefix") => {
+                let prefix = chat_prefix(game, world, player).unwrap_or_default();
+                Text::from(replace_formatting_codes(&prefix))
+            }
+            FormatPart::Placeholder(other) => Text::from(format!("{{{}}}", other)),
+        })
+        .fold(Text::empty(), |text, part| text + part)
+}
+
+/// Returns the translation key of a gamemode's name.
+fn gamemode_key(gamemode: Gamemode) -> &'static str {
+    match gamemode {
+        Gamemode::Survival => "gameMode.survival",
+        Gamemode::Creative => "gameMode.creative",
+        Gamemode::Adventure => "gameMode.adventure",
+        Gamemode::Spectator => "gameMode.spectator",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_parts() {
+        use FormatPart::*;
+        assert_eq!(
+            parse_format("<{prefix}{player}> {message}"),
+            vec![
+                Literal("<"),
+                Placeholder("prefix"),
+                Placeholder("player"),
+                Literal("> "),
+                Placeholder("message"),
+            ]
+        );
+        assert_eq!(
+            parse_format("[{world}] {player: {message}"),
+            vec![
+                Literal("["),
+                Placeholder("world"),
+                Literal("] "),
+                Placeholder("player: {message"),
+            ]
+        );
+        assert_eq!(
+            parse_format("no {placeholders"),
+            vec![Literal("no {placeholders")]
+        );
+        assert_eq!(parse_format(""), vec![]);
+    }
+}
@@ -0,0 +1,233 @@
+//! Opening containers, such as chests, whose items are kept in
+//! block entities. Two chests next to each other open as one.
+//!
+//! Furnaces and brewing stands, which process their items,
+//! cannot be opened yet.
+
+use crate::window::{close_window, container_size, resend_window, Window};
+use feather_core::anvil::block_entity::{BlockEntityData, ContainerData};
+use feather_core::blocks::{BlockId, BlockKind, ChestKind, FacingCardinal};
+use feather_core::network::packets::{BlockAction, CloseWindowClientbound, OpenWindow};
+use feather_core::text::{Text, TextRoot};
+use feather_core::util::{BlockPosition, Position};
+use feather_server_types::{Game, Network};
+use feather_server_util::{facing_offset, play_sound, SoundCategory};
+use fecs::{Entity, IntoQuery, Read, World};
+use smallvec::{smallvec, SmallVec};
+
+/// Greatest distance from the center of a container
+/// at which a player may keep it open.
+const MAX_DISTANCE: f64 = 8.0;
+
+/// Block action which sets the number of
+/// players viewing a chest, opening its lid.
+const ACTION_VIEWERS: u8 = 1;
+
+/// Returns an empty block entity for a block which is a
+/// container, or `None` if the block cannot be opened.
+pub fn empty_container(kind: BlockKind, pos: BlockPosition) -> Option<BlockEntityData> {
+    let data = ContainerData::new(pos);
+    let block_entity = match kind {
+        BlockKind::Chest => BlockEntityData::Chest(data),
+        BlockKind::TrappedChest => BlockEntityData::TrappedChest(data),
+        BlockKind::Dispenser => BlockEntityData::Dispenser(data),
+        BlockKind::Dropper => BlockEntityData::Dropper(data),
+        BlockKind::Hopper => BlockEntityData::Hopper(data),
+        _ => return None,
+    };
+    Some(block_entity)
+}
+
+/// Returns the window type and title of a container block.
+fn window_type(kind: BlockKind, double: bool) -> (&'static str, &'static str) {
+    match kind {
+        BlockKind::Dispenser => ("minecraft:dispenser", "container.dispenser"),
+        BlockKind::Dropper => ("minecraft:dropper", "container.dropper"),
+        BlockKind::Hopper => ("minecraft:hopper", "container.hopper"),
+        _ if double => ("minecraft:chest", "container.chestDouble"),
+        _ => ("minecraft:chest", "container.chest"),
+    }
+}
+
+/// Returns the positions of the containers opened by clicking
+/// the container at `pos`: both halves of a double chest,
+/// with the right half first, or `pos` alone.
+fn container_positions(
+    game: &Game,
+    pos: BlockPosition,
+    block: BlockId,
+) -> SmallVec<[BlockPosition; 2]> {
+    let (kind, facing) = match (block.chest_kind(), block.facing_cardinal()) {
+        (Some(kind), Some(facing)) if kind != ChestKind::Single => (kind, facing),
+        _ => return smallvec![pos],
+    };
+
+    // The other half lies to the right of a left half,
+    // as seen from the front of the chest.
+    let (direction, other_kind) = match kind {
+        ChestKind::Left => (clockwise(facing), ChestKind::Right),
+        _ => (counterclockwise(facing), ChestKind::Left),
+    };
+    let other = pos + facing_offset(direction);
+    match game.block_at(other) {
+        Some(other_block)
+            if other_block.kind() == block.kind()
+                && other_block.chest_kind() == Some(other_kind) =>
+        {
+            if kind == ChestKind::Right {
+                smallvec![pos, other]
+            } else {
+                smallvec![other, pos]
+            }
+        }
+        _ => smallvec![pos],
+    }
+}
+
+fn clockwise(facing: FacingCardinal) -> FacingCardinal {
+    match facing {
+        FacingCardinal::North => FacingCardinal::East,
+        FacingCardinal::East => FacingCardinal::South,
+        FacingCardinal::South => FacingCardinal::West,
+        FacingCardinal::West => FacingCardinal::North,
+    }
+}
+
+fn counterclockwise(facing: FacingCardinal) -> FacingCardinal {
+    clockwise(clockwise(clockwise(facing)))
+}
+
+/// Handles a player right-clicking the block at `pos`,
+/// opening it if it is a container. Returns whether
+/// the block was a container.
+pub fn open_container(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    pos: BlockPosition,
+) -> bool {
+    let block = match game.block_at(pos) {
+        Some(block) if empty_container(block.kind(), pos).is_some() => block,
+        _ => return false,
+    };
+
+    let containers = container_positions(game, pos, block);
+    for pos in &containers {
+        if game.block_entities.get(*pos).is_none() {
+            let container = empty_container(block.kind(), *pos).unwrap();
+            game.block_entities.insert(container);
+        }
+    }
+
+    if world.get::<Window>(player).id != 0 {
+        close_window(game, world, player);
+    }
+
+    let (window_type, title) = window_type(block.kind(), containers.len() > 1);
+    let number_of_slots = containers
+        .iter()
+        .filter_map(|pos| game.block_entities.get(*pos))
+        .map(container_size)
+        .sum::<usize>() as u8;
+    let window_id = world.get_mut::<Window>(player).open(containers.clone());
+
+    world.get::<Network>(player).send(OpenWindow {
+        window_id,
+        window_type: window_type.to_owned(),
+        window_title: TextRoot::from(Text::translate_with(title, Vec::<Text>::new())).into(),
+        number_of_slots,
+        entity_id: 0,
+    });
+    resend_window(game, world, player);
+
+    update_viewers(game, world, &containers, true);
+    true
+}
+
+/// Returns the number of players viewing the container at `pos`.
+fn viewers(world: &World, pos: BlockPosition) -> usize {
+    <Read<Window>>::query()
+        .iter(world.inner())
+        .filter(|window| window.containers.contains(&pos))
+        .count()
+}
+
+/// Shows the number of players viewing each of the given chests
+/// to nearby players, which opens their lids while there are any.
+/// Plays the sound of the lid opening or shutting if the player
+/// who `opened` or closed the chests was the first or last viewer.
+pub fn update_viewers(game: &Game, world: &World, containers: &[BlockPosition], opened: bool) {
+    let mut first_viewers = None;
+    for pos in containers {
+        let block = match game.block_at(*pos) {
+            Some(block) => block,
+            None => continue,
+        };
+        if block.kind() != BlockKind::Chest && block.kind() != BlockKind::TrappedChest {
+            continue;
+        }
+
+        let viewers = viewers(world, *pos);
+        first_viewers.get_or_insert(viewers);
+        game.broadcast_chunk_update(
+            world,
+            BlockAction {
+                location: *pos,
+                action_id: ACTION_VIEWERS,
+                action_param: viewers.min(u8::max_value() as usize) as u8,
+                block_type: block.kind() as i32,
+            },
+            pos.chunk(),
+            None,
+        );
+    }
+
+    let sound = match (first_viewers, opened) {
+        (Some(1), true) => "block.chest.open",
+        (Some(0), false) => "block.chest.close",
+        _ => return,
+    };
+    // Double chests sound from between their halves.
+    let (first, last) = (containers[0], containers[containers.len() - 1]);
+    let center = first.position()
+        + glm::vec3(
+            f64::from(last.x - first.x) / 2.0 + 0.5,
+            0.5,
+            f64::from(last.z - first.z) / 2.0 + 0.5,
+        );
+    play_sound(
+        game,
+        world,
+        sound,
+        SoundCategory::Block,
+        center,
+        0.5,
+        0.9,
+        None,
+    );
+}
+
+/// System which closes the containers of players who moved
+/// too far away from them or whose containers were removed.
+#[fecs::system]
+pub fn close_unreachable_containers(game: &mut Game, world: &mut World) {
+    let unreachable: SmallVec<[Entity; 4]> = <(Read<Window>, Read<Position>)>::query()
+        .iter_entities(world.inner())
+        .filter(|(_, (window, pos))| {
+            window.containers.iter().any(|container| {
+                let center: Position = container.position() + glm::vec3(0.5, 0.5, 0.5);
+                game.block_entities.get(*container).is_none()
+                    || center.distance_squared_to(**pos) > MAX_DISTANCE * MAX_DISTANCE
+            })
+        })
+        .map(|(player, _)| player)
+        .collect();
+
+    for player in unreachable {
+        let window_id = world.get::<Window>(player).id;
+        world
+            .get::<Network>(player)
+            .send(CloseWindowClientbound { window_id });
+        close_window(game, world, player);
+    }
+}
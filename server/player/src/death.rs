@@ -0,0 +1,236 @@
+//! Player death and respawning.
+//!
+//! Dead players keep their entity, with zero health, until their
+//! client requests to respawn. Respawning sends the client a Respawn
+//! packet, which makes it discard its view of the world, so the view
+//! is then sent again from the respawn position.
+
+use crate::{forget_view, ItemTimedUse};
+use feather_core::blocks::BlockKind;
+use feather_core::entitymeta::EntityMetadata;
+use feather_core::inventory::Inventory;
+use feather_core::network::packets::{
+    ChangeGameState, CombatEvent, CombatEventType, PlayerPositionAndLookClientbound, Respawn,
+};
+use feather_core::text::{Text, TextRoot, Translate};
+use feather_core::util::{BlockPosition, Dimension, Gamemode, Position};
+use feather_server_types::{
+    attribute_value, ActiveEffects, Air, AttackCooldown, Attribute, BedSpawn, BlockingWithShield,
+    Burning, ChatEvent, ChatPosition, ChunkCrossEvent, DamageCause, EntityDeathEvent, EntityId,
+    FallDistance, Game, Gliding, Health, Hunger, InventoryUpdateEvent, LastDamage, Name, Network,
+    Player, PreviousPosition, Sneaking, Swimming, SHOW_DEATH_MESSAGES,
+};
+use feather_server_util::{send_health, set_sprinting};
+use fecs::{Entity, World};
+
+/// Reason sent in Change Game State when a player's bed is missing.
+const GAME_STATE_INVALID_BED: u8 = 0;
+
+/// Builds the message broadcast when a player is killed.
+pub fn death_message(world: &World, player: Entity, cause: &DamageCause) -> Text {
+    let victim = world.get::<Name>(player).0.clone();
+
+    // TODO: name mobs once they have display names
+    let attacker = cause
+        .attacker()
+        .filter(|&attacker| world.is_alive(attacker));
+    let attacker_name =
+        attacker.and_then(|attacker| world.try_get::<Name>(attacker).map(|name| name.0.clone()));
+    let by_player = attacker.map_or(false, |attacker| world.has::<Player>(attacker));
+
+    let (key, with) = match attacker_name {
+        Some(attacker) => (cause.death_message_key(by_player), vec![victim, attacker]),
+        None => {
+            let key = match cause {
+                // These messages name the attacker.
+                DamageCause::EntityAttack(_)
+                | DamageCause::Projectile { .. }
+                | DamageCause::Thorns(_) => "death.attack.generic",
+                cause => cause.death_message_key(false),
+            };
+            (key, vec![victim])
+        }
+    };
+
+    Translate::from(key) * with
+}
+
+/// Shows the death screen to players who die and
+/// broadcasts their death message.
+#[fecs::event_handler]
+pub fn on_player_death_send_death_message(
+    event: &EntityDeathEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    if !world.has::<Player>(event.entity) {
+        return;
+    }
+
+    let message = death_message(world, event.entity, &event.cause);
+    let killer_id = event
+        .cause
+        .attacker()
+        .and_then(|attacker| world.try_get::<EntityId>(attacker).map(|id| id.0))
+        .unwrap_or(-1);
+
+    world.get::<Network>(event.entity).send(CombatEvent {
+        event: CombatEventType::EntityDead(
+            world.get::<EntityId>(event.entity).0,
+            killer_id,
+            TextRoot::from(message.clone()).into(),
+        ),
+    });
+
+    if game.game_rules.bool(SHOW_DEATH_MESSAGES) {
+        game.handle(
+            world,
+            ChatEvent {
+                message,
+                position: ChatPosition::SystemMessage,
+                sender: None,
+            },
+        );
+    }
+}
+
+/// Respawns a dead player at their bed, or at the world
+/// spawn if they have no bed.
+pub fn respawn(game: &mut Game, world: &mut World, player: Entity) {
+    if world.get::<Health>(player).0 > 0.0 {
+        return;
+    }
+
+    forget_view(game, world, player);
+
+    let old = *world.get::<Position>(player);
+    let spawn = respawn_position(game, world, player);
+    let pos = Position {
+        x: spawn.0,
+        y: spawn.1,
+        z: spawn.2,
+        pitch: 0.0,
+        yaw: old.yaw,
+        on_ground: false,
+    };
+    *world.get_mut::<Position>(player) = pos;
+    // The view is sent below, so no chunk cross should be detected.
+    world.get_mut::<PreviousPosition>(player).0 = pos;
+    if old.chunk() != pos.chunk() {
+        game.chunk_entities
+            .move_entity(player, old.chunk(), pos.chunk());
+    }
+
+    reset_state(game, world, player);
+
+    {
+        let network = world.get::<Network>(player);
+        // TODO: use the world's dimension
+        network.send(Respawn {
+            dimension: Dimension::Overwold.id(),
+            difficulty: game.difficulty().id(),
+            gamemode: world.get::<Gamemode>(player).id(),
+            level_type: game.level.generator_name.clone(),
+        });
+        network.send(PlayerPositionAndLookClientbound {
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+            yaw: pos.yaw,
+            pitch: pos.pitch,
+            flags: 0,
+            teleport_id: 0,
+        });
+    }
+    send_health(world, player);
+
+    game.handle(
+        world,
+        ChunkCrossEvent {
+            entity: player,
+            old: None,
+            new: pos.chunk(),
+        },
+    );
+
+    // The client's new player entity starts with an empty inventory.
+    let slots = (0..world.get::<Inventory>(player).slot_count() as usize).collect();
+    game.handle(world, InventoryUpdateEvent { slots, player });
+}
+
+/// Returns the position at which a player respawns, removing
+/// their bed spawn if the bed no longer exists.
+fn respawn_position(game: &Game, world: &mut World, player: Entity) -> (f64, f64, f64) {
+    if let Some(bed) = world.try_get::<BedSpawn>(player).map(|spawn| spawn.0) {
+        if game
+            .block_at(bed)
+            .map_or(false, |block| is_bed(block.kind()))
+        {
+            // Players respawn standing on their bed.
+            return (
+                f64::from(bed.x) + 0.5,
+                f64::from(bed.y) + 0.5625,
+                f64::from(bed.z) + 0.5,
+            );
+        }
+
+        world.remove::<BedSpawn>(player).unwrap();
+        world.get::<Network>(player).send(ChangeGameState {
+            reason: GAME_STATE_INVALID_BED,
+            value: 0.0,
+        });
+    }
+
+    let spawn = BlockPosition::new(game.level.spawn_x, game.level.spawn_y, game.level.spawn_z);
+    (
+        f64::from(spawn.x) + 0.5,
+        f64::from(spawn.y),
+        f64::from(spawn.z) + 0.5,
+    )
+}
+
+/// Resets the state a player accumulated before dying.
+fn reset_state(game: &Game, world: &mut World, player: Entity) {
+    let max_health = attribute_value(world, player, Attribute::MaxHealth) as f32;
+    *world.get_mut::<Health>(player) = Health(max_health);
+    *world.get_mut::<Hunger>(player) = Hunger::default();
+    *world.get_mut::<EntityMetadata>(player) = EntityMetadata::new();
+    *world.get_mut::<AttackCooldown>(player) = AttackCooldown::new(game.tick_count);
+
+    if world.has::<ActiveEffects>(player) {
+        *world.get_mut::<ActiveEffects>(player) = ActiveEffects::default();
+    }
+
+    let _ = world.remove::<Air>(player);
+    let _ = world.remove::<Burning>(player);
+    let _ = world.remove::<FallDistance>(player);
+    let _ = world.remove::<LastDamage>(player);
+    let _ = world.remove::<BlockingWithShield>(player);
+    let _ = world.remove::<ItemTimedUse>(player);
+    let _ = world.remove::<Sneaking>(player);
+    let _ = world.remove::<Swimming>(player);
+    let _ = world.remove::<Gliding>(player);
+    set_sprinting(game, world, player, false);
+}
+
+fn is_bed(kind: BlockKind) -> bool {
+    match kind {
+        BlockKind::WhiteBed
+        | BlockKind::OrangeBed
+        | BlockKind::MagentaBed
+        | BlockKind::LightBlueBed
+        | BlockKind::YellowBed
+        | BlockKind::LimeBed
+        | BlockKind::PinkBed
+        | BlockKind::GrayBed
+        | BlockKind::LightGrayBed
+        | BlockKind::CyanBed
+        | BlockKind::PurpleBed
+        | BlockKind::BlueBed
+        | BlockKind::BrownBed
+        | BlockKind::GreenBed
+        | BlockKind::RedBed
+        | BlockKind::BlackBed => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,106 @@
+//! Eating, which players do by holding the use button
+//! with food in hand until the food is consumed.
+
+use crate::{hand_slot, start_timed_use, trigger_advancements, ItemTimedUse};
+use feather_core::inventory::Inventory;
+use feather_core::items::{Item, ItemStack};
+use feather_core::network::packets::EntityStatus;
+use feather_core::util::{Gamemode, Hand};
+use feather_server_types::{
+    add_statistic, BumpVec, EntityId, Game, Hunger, InventoryUpdateEvent, Network, Player,
+    Statistic, Trigger, CHORUS_FRUIT_COOLDOWN_TICKS, MAX_FOOD,
+};
+use feather_server_util::{
+    always_edible, apply_food_effects, consumes_items, eating_ticks, food_leftover,
+    has_item_cooldown, is_food, restore_hunger, send_health, start_item_cooldown,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+use smallvec::smallvec;
+
+/// Entity status sent to a player when they finish eating.
+const STATUS_FINISHED_EATING: i8 = 9;
+
+/// Starts eating the food in the given hand.
+pub fn start_eating(game: &Game, world: &mut World, player: Entity, hand: Hand, food: &ItemStack) {
+    // Players may only eat most foods when they are hungry.
+    let hungry = world.get::<Hunger>(player).food < MAX_FOOD;
+    let creative = !consumes_items(*world.get::<Gamemode>(player));
+    if !(hungry || creative || always_edible(food.ty))
+        || has_item_cooldown(game, world, player, food.ty)
+    {
+        return;
+    }
+
+    start_timed_use(game, world, player, hand);
+}
+
+/// System which consumes food once players have eaten for long enough.
+#[fecs::system]
+pub fn finish_eating(game: &mut Game, world: &mut World) {
+    let mut eaten = BumpVec::new_in(game.bump());
+
+    for (player, timed_use) in <Read<ItemTimedUse>>::query()
+        .filter(component::<Player>())
+        .iter_entities(world.inner())
+    {
+        let slot = hand_slot(world, player, timed_use.hand);
+        let food = match world.get::<Inventory>(player).item_at(slot) {
+            Some(stack) if is_food(stack.ty) => stack.clone(),
+            _ => continue,
+        };
+
+        if game.tick_count - timed_use.tick_start >= eating_ticks(food.ty) {
+            eaten.push((player, slot, food));
+        }
+    }
+
+    for (player, slot, food) in eaten {
+        world.remove::<ItemTimedUse>(player).unwrap();
+
+        world.get::<Network>(player).send(EntityStatus {
+            entity_id: world.get::<EntityId>(player).0,
+            entity_status: STATUS_FINISHED_EATING,
+        });
+
+        restore_hunger(&mut world.get_mut::<Hunger>(player), food.ty);
+        send_health(world, player);
+        apply_food_effects(game, world, player, &food);
+        add_statistic(world, player, Statistic::Used(food.ty), 1);
+        trigger_advancements(game, world, player, Trigger::ConsumedItem(food.ty));
+
+        // TODO: teleport players who eat chorus fruit
+        if food.ty == Item::ChorusFruit {
+            start_item_cooldown(
+                game,
+                world,
+                player,
+                Item::ChorusFruit,
+                CHORUS_FRUIT_COOLDOWN_TICKS,
+            );
+        }
+
+        if consumes_items(*world.get::<Gamemode>(player)) {
+            {
+                let mut inventory = world.get_mut::<Inventory>(player);
+                if food.amount > 1 {
+                    let amount = food.amount - 1;
+                    inventory.set_item_at(slot, ItemStack { amount, ..food });
+                } else {
+                    match food_leftover(food.ty) {
+                        Some(leftover) => inventory.set_item_at(slot, ItemStack::new(leftover, 1)),
+                        None => {
+                            inventory.clear_item_at(slot);
+                        }
+                    }
+                }
+            }
+            game.handle(
+                world,
+                InventoryUpdateEvent {
+                    slots: smallvec![slot],
+                    player,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Elytra flight, which players start by jumping while falling
+//! with an elytra in their chest slot.
+//!
+//! Gliding itself is simulated by the client. The server decides
+//! when players may glide, rejects glide movement which is too fast
+//! to be legitimate, and damages players who fly into walls.
+
+use crate::{hand_slot, remove_one_item};
+use feather_core::entitymeta::{EntityBitMask, EntityMetadata};
+use feather_core::inventory::{Inventory, SLOT_ARMOR_CHEST};
+use feather_core::items::Item;
+use feather_core::network::packets::PlayerPositionAndLookClientbound;
+use feather_core::util::{Gamemode, Hand, Position};
+use feather_server_types::{
+    ActiveEffects, BumpVec, DamageCause, EntityDamageRequest, EntitySpawnEvent, Flying, Game,
+    Gliding, Network, Player, PreviousPosition, StatusEffect, PLAYER_WIDTH, TPS,
+};
+use feather_server_util::{consumes_items, wear_item};
+use fecs::{component, Entity, IntoQuery, Read, World};
+
+/// Squared distance a gliding player may move in one tick
+/// before their movement is rejected, as in vanilla.
+const MAX_GLIDE_MOVE_SQUARED: f64 = 300.0;
+
+/// Horizontal speed which may be lost in a collision without
+/// taking damage, in tenths of a block per tick.
+const SAFE_SPEED_LOSS: f64 = 3.0;
+
+/// Returns whether a player wears an elytra which can be used.
+/// Elytras never break; they stop working one point of
+/// damage before they would.
+fn wears_elytra(world: &World, player: Entity) -> bool {
+    world
+        .get::<Inventory>(player)
+        .item_at(SLOT_ARMOR_CHEST)
+        .map_or(false, |stack| {
+            stack.ty == Item::Elytra
+                && stack.damage() + 1 < stack.ty.max_durability().unwrap_or(u32::max_value())
+        })
+}
+
+fn levitating(world: &World, player: Entity) -> bool {
+    world
+        .try_get::<ActiveEffects>(player)
+        .map_or(false, |effects| effects.has(StatusEffect::Levitation))
+}
+
+fn in_water(game: &Game, pos: Position) -> bool {
+    game.block_at(pos.block())
+        .map_or(false, |block| block.is_water())
+}
+
+/// Starts a player gliding in response to an entity action,
+/// as long as they wear an elytra and are falling through the air.
+pub fn start_gliding(game: &Game, world: &mut World, player: Entity) {
+    if world.has::<Gliding>(player) || world.has::<Flying>(player) {
+        return;
+    }
+
+    let pos = *world.get::<Position>(player);
+    let falling = !pos.on_ground && pos.y < world.get::<PreviousPosition>(player).0.y;
+
+    let can_glide = falling
+        && !in_water(game, pos)
+        && !levitating(world, player)
+        && wears_elytra(world, player);
+    if can_glide {
+        world
+            .add(
+                player,
+                Gliding {
+                    since: game.tick_count,
+                    last_speed: 0.0,
+                },
+            )
+            .unwrap();
+    }
+
+    // Clients which were refused expect to be told they are not gliding.
+    world
+        .get_mut::<EntityMetadata>(player)
+        .set_flag(EntityBitMask::FLYING_WITH_ELYTRA, can_glide);
+}
+
+/// Stops a player gliding.
+pub fn stop_gliding(world: &mut World, player: Entity) {
+    if world.remove::<Gliding>(player).is_err() {
+        return;
+    }
+
+    world
+        .get_mut::<EntityMetadata>(player)
+        .set_flag(EntityBitMask::FLYING_WITH_ELYTRA, false);
+}
+
+/// Returns whether a player's bounding box touches a solid
+/// block on any of its sides.
+fn touches_wall(game: &Game, pos: Position) -> bool {
+    // Slightly more than half the width, so blocks directly
+    // next to the bounding box are found.
+    let reach = PLAYER_WIDTH / 2.0 + 0.01;
+
+    [(reach, 0.0), (-reach, 0.0), (0.0, reach), (0.0, -reach)]
+        .iter()
+        .any(|&(dx, dz)| {
+            let side = (pos + glm::vec3(dx, 0.0, dz)).block();
+            game.block_at(side).map_or(false, |block| block.is_solid())
+        })
+}
+
+/// Computes the damage taken by a gliding player who flies into
+/// a wall, going from `old_speed` to `new_speed` horizontally.
+pub fn wall_collision_damage(old_speed: f64, new_speed: f64) -> f32 {
+    ((old_speed - new_speed) * 10.0 - SAFE_SPEED_LOSS).max(0.0) as f32
+}
+
+/// System which stops players gliding when they can no longer
+/// glide and handles the movement of those who keep gliding.
+#[fecs::system]
+pub fn update_gliding(game: &mut Game, world: &mut World) {
+    let mut stopped = BumpVec::new_in(game.bump());
+    let mut rejected = BumpVec::new_in(game.bump());
+    let mut moved = BumpVec::new_in(game.bump());
+    let mut worn = BumpVec::new_in(game.bump());
+
+    for (player, (pos, previous, gliding)) in
+        <(Read<Position>, Read<PreviousPosition>, Read<Gliding>)>::query()
+            .filter(component::<Player>())
+            .iter_entities(world.inner())
+    {
+        if pos.on_ground
+            || in_water(game, *pos)
+            || levitating(world, player)
+            || !wears_elytra(world, player)
+        {
+            stopped.push(player);
+            continue;
+        }
+
+        // Elytras wear down once per second of flight.
+        if (game.tick_count - gliding.since + 1) % TPS == 0 {
+            worn.push(player);
+        }
+
+        let offset = glm::vec3(
+            pos.x - previous.0.x,
+            pos.y - previous.0.y,
+            pos.z - previous.0.z,
+        );
+        if offset.norm_squared() > MAX_GLIDE_MOVE_SQUARED {
+            rejected.push((player, previous.0));
+            continue;
+        }
+
+        // Ticks without a movement packet carry no information
+        // about the player's speed.
+        if offset.norm_squared() == 0.0 {
+            continue;
+        }
+
+        let speed = offset.x.hypot(offset.z);
+        let damage = if touches_wall(game, *pos) {
+            wall_collision_damage(gliding.last_speed, speed)
+        } else {
+            0.0
+        };
+        moved.push((player, speed, damage));
+    }
+
+    for player in stopped {
+        stop_gliding(world, player);
+    }
+
+    for player in worn {
+        wear_item(game, world, player, SLOT_ARMOR_CHEST, 1);
+    }
+
+    for (player, previous) in rejected {
+        log::debug!("Rejected glide movement of {:?} which was too fast", player);
+        *world.get_mut::<Position>(player) = previous;
+        world
+            .get::<Network>(player)
+            .send(PlayerPositionAndLookClientbound {
+                x: previous.x,
+                y: previous.y,
+                z: previous.z,
+                yaw: previous.yaw,
+                pitch: previous.pitch,
+                flags: 0,
+                teleport_id: 0,
+            });
+    }
+
+    for (player, speed, damage) in moved {
+        world.get_mut::<Gliding>(player).last_speed = speed;
+
+        if damage > 0.0 {
+            game.handle(
+                world,
+                EntityDamageRequest {
+                    entity: player,
+                    damage,
+                    cause: DamageCause::FlyIntoWall,
+                },
+            );
+        }
+    }
+}
+
+/// Uses the firework rocket in the given hand to boost a gliding
+/// player, consuming it unless they are in creative mode.
+pub fn boost_with_firework(game: &mut Game, world: &mut World, player: Entity, hand: Hand) {
+    let slot = hand_slot(world, player, hand);
+    let stack = match world.get::<Inventory>(player).item_at(slot) {
+        Some(stack) if stack.ty == Item::FireworkRocket => stack.clone(),
+        _ => return,
+    };
+
+    let rocket = entity::firework_rocket::create(game, world, stack, player)
+        .build()
+        .spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity: rocket });
+
+    if consumes_items(*world.get::<Gamemode>(player)) {
+        remove_one_item(game, world, player, slot);
+    }
+}
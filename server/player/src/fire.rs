@@ -0,0 +1,81 @@
+//! Flint and steel and fire charges, which players use
+//! to start fires and ignite TNT.
+
+use crate::remove_one_item;
+use feather_core::blocks::{BlockId, BlockKind};
+use feather_core::inventory::SlotIndex;
+use feather_core::items::Item;
+use feather_core::network::packets::Face;
+use feather_core::util::{BlockPosition, Gamemode};
+use feather_server_types::{BlockUpdateCause, Game, TntPrimeRequest, TNT_FUSE_TICKS};
+use feather_server_util::{consumes_items, play_sound, wear_item, SoundCategory};
+use fecs::{Entity, World};
+use rand::Rng;
+
+/// Uses the flint and steel or fire charge in `slot` on the face
+/// of a block. TNT is ignited; otherwise fire is placed against
+/// the face if there is room.
+///
+/// Flint and steel loses durability while fire charges are used up.
+pub fn ignite(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    item: Item,
+    location: BlockPosition,
+    face: Face,
+) {
+    let tnt = game.block_at(location).map(BlockId::kind) == Some(BlockKind::Tnt);
+    if tnt {
+        game.handle(
+            world,
+            TntPrimeRequest {
+                pos: location,
+                fuse: TNT_FUSE_TICKS,
+                igniter: Some(player),
+            },
+        );
+    } else {
+        let pos = location + face.placement_offset();
+        if game.block_at(pos) != Some(BlockId::air()) {
+            return;
+        }
+        game.set_block_at_with_cause(
+            world,
+            pos,
+            BlockId::fire(),
+            BlockUpdateCause::entity(player),
+        );
+
+        let (sound, pitch) = {
+            let mut rng = game.rng();
+            match item {
+                Item::FireCharge => (
+                    "item.firecharge.use",
+                    (rng.gen::<f32>() - rng.gen::<f32>()) * 0.2 + 1.0,
+                ),
+                _ => ("item.flintandsteel.use", rng.gen::<f32>() * 0.4 + 0.8),
+            }
+        };
+        play_sound(
+            game,
+            world,
+            sound,
+            SoundCategory::Block,
+            pos.position(),
+            1.0,
+            pitch,
+            None,
+        );
+    }
+
+    match item {
+        Item::FireCharge => {
+            if consumes_items(*world.get::<Gamemode>(player)) {
+                remove_one_item(game, world, player, slot);
+            }
+        }
+        _ => wear_item(game, world, player, slot, 1),
+    }
+}
@@ -1,11 +1,17 @@
 //! Join logic for players.
 
-use feather_core::network::packets::{JoinGame, PlayerPositionAndLookClientbound, SpawnPosition};
-use feather_core::util::{BlockPosition, Difficulty, Dimension, Gamemode, Position};
+use feather_core::network::packets::{
+    DisconnectPlay, JoinGame, PlayerPositionAndLookClientbound, SpawnPosition,
+};
+use feather_core::text::TextRoot;
+use feather_core::util::{BlockPosition, Dimension, Gamemode, Position};
+use feather_server_chat::{ban_message, ip_ban_message};
 use feather_server_network::{ListenerToServerMessage, NetworkIoManager, ServerToListenerMessage};
 use feather_server_types::{
-    BumpVec, ChunkSendEvent, EntityId, Game, Network, PlayerJoinEvent, WorkerToServerMessage,
+    moderation_now, BumpVec, ChunkSendEvent, EntityId, Game, Moderation, Network, PlayerJoinEvent,
+    ServerToWorkerMessage, WorkerToServerMessage, REDUCED_DEBUG_INFO,
 };
+use feather_server_util::send_abilities;
 use fecs::{IntoQuery, Read, World};
 use std::iter;
 
@@ -34,10 +40,37 @@ pub fn poll_player_disconnect(game: &mut Game, world: &mut World) {
 
 /// System which polls for new clients from the listener task.
 #[fecs::system]
-pub fn poll_new_clients(game: &mut Game, world: &mut World, io_handle: &mut NetworkIoManager) {
+pub fn poll_new_clients(
+    game: &mut Game,
+    world: &mut World,
+    io_handle: &mut NetworkIoManager,
+    moderation: &Moderation,
+) {
     while let Ok(msg) = io_handle.rx.lock().try_recv() {
         match msg {
             ListenerToServerMessage::NewClient(info) => {
+                let now = moderation_now();
+                let ban = match moderation.bans.find(info.uuid, &info.username, now) {
+                    Some(ban) => Some(ban_message(ban, now)),
+                    None => moderation
+                        .ip_bans
+                        .find(info.ip.ip(), now)
+                        .map(|ban| ip_ban_message(ban, now)),
+                };
+                if let Some(message) = ban {
+                    log::info!("{} tried to join but is banned", info.username);
+                    let packet = DisconnectPlay {
+                        reason: TextRoot::from(message).into(),
+                    };
+                    let _ = info
+                        .sender
+                        .send(ServerToWorkerMessage::SendPacket(Box::new(packet)));
+                    let _ = info.sender.send(ServerToWorkerMessage::Disconnect);
+                    // the entity has no components yet
+                    world.despawn(info.entity);
+                    continue;
+                }
+
                 crate::create(game, world, info);
             }
             ListenerToServerMessage::RequestEntity => {
@@ -111,12 +144,13 @@ pub fn on_player_join_send_join_game(event: &PlayerJoinEvent, game: &Game, world
     // TODO
     let packet = JoinGame {
         entity_id: id.0,
-        gamemode: Gamemode::Creative.id(),
+        gamemode: world.get::<Gamemode>(event.player).id(),
         dimension: Dimension::Overwold.id(),
-        difficulty: Difficulty::Medium.id(),
+        difficulty: game.difficulty().id(),
         max_players: game.config.server.max_players as u8,
         level_type: game.level.generator_name.clone(),
-        reduced_debug_info: false,
+        reduced_debug_info: game.game_rules.bool(REDUCED_DEBUG_INFO),
     };
     network.send(packet);
+    send_abilities(game, world, event.player);
 }
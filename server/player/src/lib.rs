@@ -2,39 +2,172 @@
 
 extern crate nalgebra_glm as glm;
 
+mod advancements;
+mod bone_meal;
+mod book;
+mod bow;
 mod broadcasters;
+mod bucket;
 mod chat;
+mod container;
+mod death;
+mod eating;
+mod elytra;
+mod fire;
 mod join;
+mod map;
 mod packet_handlers;
+mod plugin_message;
+mod recipe_book;
+mod resource_pack;
+mod spawn_egg;
+mod spectator;
+mod statistics;
+mod swimming;
+mod trident;
 mod view;
+mod view_distance;
+mod window;
 
-use feather_core::inventory::{Inventory, InventoryType};
-use feather_core::items::{Item, ItemStack};
+use feather_core::entitymeta::EntityMetadata;
+use feather_core::inventory::{
+    Inventory, InventoryType, SlotIndex, SLOT_HOTBAR_OFFSET, SLOT_OFFHAND,
+};
+use feather_core::items::ItemStack;
 use feather_core::network::packets::{PlayerInfo, PlayerInfoAction, SpawnPlayer};
 use feather_core::network::Packet;
 use feather_core::text::Text;
-use feather_core::util::{Gamemode, Position};
+use feather_core::util::{Gamemode, Hand, Position};
 use feather_server_network::NewClientInfo;
 use feather_server_types::{
-    ChunkHolder, CreationPacketCreator, EntityId, EntitySpawnEvent, Game, HeldItem,
-    InventoryUpdateEvent, LastKnownPositions, Name, Network, Player, PlayerJoinEvent,
-    PreviousPosition, ProfileProperties, SpawnPacketCreator, Uuid,
+    AttackCooldown, Attribute, Attributes, BedSpawn, ChunkHolder, CreationPacketCreator, EntityId,
+    EntitySpawnEvent, Experience, Flying, Game, Health, HeldItem, Hunger, InventoryUpdateEvent,
+    ItemDropEvent, LastKnownPositions, Living, Name, Network, Ping, Player, PlayerJoinEvent,
+    PluginChannels, PreviousPosition, ProfileProperties, RecipeBook, SpawnPacketCreator, Uuid,
+    ViewDistance, PLAYER_ATTACK_DAMAGE, PLAYER_MOVEMENT_SPEED,
 };
-use feather_server_util::degrees_to_stops;
+use feather_server_util::{default_gamemode, degrees_to_stops, update_armor_attributes};
 use fecs::{Entity, EntityRef, World};
+use smallvec::smallvec;
 
+pub use advancements::*;
+pub use book::*;
 pub use broadcasters::*;
 pub use chat::*;
+pub use container::*;
+pub use death::*;
+pub use eating::*;
+pub use elytra::*;
 pub use join::*;
+pub use map::*;
 pub use packet_handlers::*;
-use std::sync::atomic::Ordering;
+pub use plugin_message::*;
+pub use recipe_book::*;
+pub use resource_pack::*;
+pub use spawn_egg::*;
+pub use spectator::*;
+pub use statistics::*;
+pub use swimming::*;
+pub use trident::*;
 pub use view::*;
+pub use view_distance::*;
+pub use window::*;
 
 pub const PLAYER_INVENTORY_SIZE: u32 = 46;
 
+/// Component for players who are using an item
+/// over time, such as drawing a bow or eating.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ItemTimedUse {
     pub tick_start: u64,
+    /// The hand holding the item.
+    pub hand: Hand,
+}
+
+/// Returns the inventory slot of the item in a player's hand.
+pub(crate) fn hand_slot(world: &World, player: Entity, hand: Hand) -> SlotIndex {
+    match hand {
+        Hand::Main => SLOT_HOTBAR_OFFSET + world.get::<HeldItem>(player).0,
+        Hand::Off => SLOT_OFFHAND,
+    }
+}
+
+/// Starts using the item in the given hand,
+/// replacing any use in progress.
+pub(crate) fn start_timed_use(game: &Game, world: &mut World, player: Entity, hand: Hand) {
+    let timed_use = ItemTimedUse {
+        tick_start: game.tick_count,
+        hand,
+    };
+    if world.has::<ItemTimedUse>(player) {
+        *world.get_mut::<ItemTimedUse>(player) = timed_use;
+    } else {
+        world.add(player, timed_use).unwrap();
+    }
+}
+
+/// Removes one item from the stack in `slot`.
+pub(crate) fn remove_one_item(game: &mut Game, world: &mut World, player: Entity, slot: SlotIndex) {
+    {
+        let mut inventory = world.get_mut::<Inventory>(player);
+        match inventory.item_at_mut(slot) {
+            Some(stack) if stack.amount > 1 => stack.amount -= 1,
+            _ => {
+                inventory.clear_item_at(slot);
+            }
+        }
+    }
+    game.handle(
+        world,
+        InventoryUpdateEvent {
+            slots: smallvec![slot],
+            player,
+        },
+    );
+}
+
+/// Replaces one item of the stack in `slot` with `new`. If
+/// the slot holds more than one item, `new` goes elsewhere in
+/// the inventory, or is dropped if the inventory is full.
+pub(crate) fn exchange_item(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    new: ItemStack,
+) {
+    let mut slots = smallvec![slot];
+    let mut dropped = None;
+    {
+        let mut inventory = world.get_mut::<Inventory>(player);
+        let mut old = inventory.item_at(slot).unwrap().clone();
+        if old.amount > 1 {
+            old.amount -= 1;
+            inventory.set_item_at(slot, old);
+            let (collected, remaining) = inventory.collect_item(new.clone());
+            slots.extend(collected);
+            if remaining > 0 {
+                dropped = Some(ItemStack {
+                    amount: remaining,
+                    ..new
+                });
+            }
+        } else {
+            inventory.set_item_at(slot, new);
+        }
+    }
+
+    game.handle(world, InventoryUpdateEvent { slots, player });
+    if let Some(stack) = dropped {
+        game.handle(
+            world,
+            ItemDropEvent {
+                slot: None,
+                stack,
+                player,
+            },
+        );
+    }
 }
 
 /// Creates a new player from the given `NewClientInfo`.
@@ -53,6 +186,7 @@ pub fn create(game: &mut Game, world: &mut World, info: NewClientInfo) -> Entity
             Network {
                 tx: info.sender,
                 rx: info.receiver.into(),
+                counters: info.counters,
             },
         )
         .unwrap();
@@ -60,6 +194,9 @@ pub fn create(game: &mut Game, world: &mut World, info: NewClientInfo) -> Entity
     world.add(entity, ProfileProperties(info.profile)).unwrap();
     world.add(entity, Name(info.username)).unwrap();
     world.add(entity, ChunkHolder::default()).unwrap();
+    world
+        .add(entity, ViewDistance::new(game.config.server.view_distance))
+        .unwrap();
     world.add(entity, LastKnownPositions::default()).unwrap();
     world
         .add(entity, SpawnPacketCreator(&create_spawn_packet))
@@ -67,19 +204,22 @@ pub fn create(game: &mut Game, world: &mut World, info: NewClientInfo) -> Entity
     world
         .add(entity, CreationPacketCreator(&create_initialization_packet))
         .unwrap();
-    world
-        .add(entity, Gamemode::from_id(info.data.gamemode as u8))
-        .unwrap();
+    // Players joining for the first time use the world's default gamemode.
+    let gamemode = if info.first_join {
+        default_gamemode(game)
+    } else {
+        Gamemode::from_id(info.data.gamemode as u8)
+    };
+    world.add(entity, gamemode).unwrap();
+    if gamemode == Gamemode::Spectator {
+        world.add(entity, Flying).unwrap();
+    }
 
-    let items = info.data.inventory.iter().map(|slot| {
-        (
-            slot.slot as usize,
-            ItemStack::new(
-                Item::from_identifier(&slot.item).unwrap_or(Item::Air),
-                slot.count as u8,
-            ),
-        )
-    });
+    let items = info
+        .data
+        .inventory
+        .iter()
+        .map(|slot| (slot.slot as usize, slot.to_stack()));
     let slots = info.data.inventory.iter().map(|slot| slot.slot as usize);
 
     let mut inventory = Inventory::new(InventoryType::Player, PLAYER_INVENTORY_SIZE);
@@ -87,10 +227,52 @@ pub fn create(game: &mut Game, world: &mut World, info: NewClientInfo) -> Entity
 
     world.add(entity, inventory).unwrap();
     world.add(entity, HeldItem(0)).unwrap(); // todo: load from player data
+    world.add(entity, info.statistics).unwrap();
+    world.add(entity, info.advancements).unwrap();
+    world.add(entity, SentAdvancements::default()).unwrap();
+    world.add(entity, Window::default()).unwrap();
+    world
+        .add(entity, RecipeBook::from_data(&info.data.recipe_book))
+        .unwrap();
+
+    if let Some(spawn) = info.data.bed_spawn() {
+        world.add(entity, BedSpawn(spawn)).unwrap();
+    }
+
+    world.add(entity, Health(20.0)).unwrap(); // todo: load from player data
+    world.add(entity, Hunger::default()).unwrap(); // todo: load from player data
+    world
+        .add(
+            entity,
+            Experience {
+                level: info.data.xp_level.max(0) as u32,
+                progress: info.data.xp_progress,
+                total: info.data.xp_total.max(0) as u32,
+            },
+        )
+        .unwrap();
+    world
+        .add(
+            entity,
+            Attributes::new()
+                .with_base(Attribute::AttackDamage, PLAYER_ATTACK_DAMAGE)
+                .with_base(Attribute::MovementSpeed, PLAYER_MOVEMENT_SPEED),
+        )
+        .unwrap();
+    update_armor_attributes(world, entity);
+    world
+        .add(entity, AttackCooldown::new(game.tick_count))
+        .unwrap();
+    world.add(entity, EntityMetadata::new()).unwrap();
+    world.add(entity, KeepAlive::default()).unwrap();
+    world.add(entity, Ping::default()).unwrap();
+    world.add(entity, PluginChannels::default()).unwrap();
 
+    world.add(entity, Living).unwrap();
     world.add(entity, Player).unwrap();
 
-    game.player_count.fetch_add(1, Ordering::SeqCst);
+    let name = world.get::<Name>(entity).0.clone();
+    game.online_players.add(*world.get::<Uuid>(entity), name);
     game.handle(world, EntitySpawnEvent { entity });
     game.handle(world, PlayerJoinEvent { player: entity });
     game.handle(
@@ -128,6 +310,8 @@ fn create_initialization_packet(accessor: &EntityRef) -> Box<dyn Packet> {
     let name = accessor.get::<Name>();
     let props = accessor.get::<ProfileProperties>();
     let uuid = *accessor.get::<Uuid>();
+    let gamemode = *accessor.get::<Gamemode>();
+    let ping = accessor.get::<Ping>().0 as i32;
 
     let props = props
         .0
@@ -143,8 +327,7 @@ fn create_initialization_packet(accessor: &EntityRef) -> Box<dyn Packet> {
 
     let display_name = Text::of(name.0.clone()).into();
 
-    let action =
-        PlayerInfoAction::AddPlayer(name.0.clone(), props, Gamemode::Creative, 50, display_name);
+    let action = PlayerInfoAction::AddPlayer(name.0.clone(), props, gamemode, ping, display_name);
 
     let packet = PlayerInfo { action, uuid };
     Box::new(packet)
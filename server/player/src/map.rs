@@ -0,0 +1,367 @@
+//! Map items. Empty maps become filled maps when used, and
+//! filled maps draw the terrain around players holding them.
+
+use crate::exchange_item;
+use ahash::AHashMap;
+use feather_core::anvil::map::{MapBanner, MapBannerPos};
+use feather_core::blocks::BlockId;
+use feather_core::inventory::{Inventory, Slot, SlotIndex, SLOT_HOTBAR_OFFSET, SLOT_OFFHAND};
+use feather_core::items::{Item, ItemStack};
+use feather_core::network::packets::{MapData, MapIcon};
+use feather_core::util::{BlockPosition, ChunkPosition, Gamemode, Position};
+use feather_server_types::{Game, HeldItem, InventoryUpdateEvent, MapState, Maps, Network, Player};
+use feather_server_util::{
+    banner_color, banner_icon, consumes_items, map_center, map_icon_direction, map_icon_position,
+    map_surface, shade_pixel, shade_water, MapColor, MapSurface, MAP_ICON_PLAYER,
+    MAP_ICON_PLAYER_OFF_MAP, MAP_SIZE, MAX_MAP_SCALE,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+use smallvec::smallvec;
+use std::sync::Arc;
+
+/// Distance in blocks around a player within which
+/// the map they are holding is drawn.
+const RENDER_DISTANCE: i32 = 128;
+/// Each tick, one in this many columns of pixels
+/// is drawn, spreading the cost of drawing a map.
+const RENDER_STRIPES: i32 = 16;
+/// Number of ticks between updates of map icons.
+const ICON_INTERVAL: u64 = 5;
+/// Number of ticks between saves of changed maps.
+const SAVE_INTERVAL: u64 = 6000;
+
+/// Uses the empty map held in `slot`, creating a new map
+/// centered near the player.
+pub fn use_empty_map(game: &mut Game, world: &mut World, player: Entity, slot: SlotIndex) {
+    let pos = *world.get::<Position>(player);
+    let center = map_center(pos.x.floor() as i32, pos.z.floor() as i32, 0);
+
+    let id = {
+        let resources = Arc::clone(&game.resources);
+        let mut maps = resources.get_mut::<Maps>();
+        maps.create(MapState::new(0, center, 0), &game.running_tasks)
+    };
+    let mut filled = ItemStack::new(Item::FilledMap, 1);
+    filled.set_map_id(Some(id));
+
+    if consumes_items(*world.get::<Gamemode>(player)) {
+        exchange_item(game, world, player, slot, filled);
+        return;
+    }
+
+    // In creative mode, the empty map is kept.
+    let (slots, _) = world.get_mut::<Inventory>(player).collect_item(filled);
+    game.handle(world, InventoryUpdateEvent { slots, player });
+}
+
+/// Marks the banner at `pos` on the map `stack`, or removes
+/// its marker if it is already marked. Returns `false` if
+/// the block is not a banner or lies outside the map.
+pub fn mark_banner(game: &mut Game, stack: &ItemStack, pos: BlockPosition) -> bool {
+    let color = match game.block_at(pos).and_then(banner_color) {
+        Some(color) => color,
+        None => return false,
+    };
+    let id = match stack.map_id() {
+        Some(id) => id,
+        None => return false,
+    };
+
+    let resources = Arc::clone(&game.resources);
+    let mut maps = resources.get_mut::<Maps>();
+    let map = match maps.get_mut(id) {
+        Some(map) => map,
+        None => return false,
+    };
+    let (_, _, on_map) = map_icon_position(
+        map.center,
+        map.scale,
+        f64::from(pos.x) + 0.5,
+        f64::from(pos.z) + 0.5,
+    );
+    if !on_map {
+        return false;
+    }
+
+    // TODO: use the banner's custom name once
+    // block entity data is available.
+    map.toggle_banner(MapBanner {
+        pos: MapBannerPos {
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+        },
+        color: color.to_owned(),
+        name: None,
+    });
+    true
+}
+
+/// Returns the result of the special map crafting recipes for
+/// the items in a crafting grid:
+/// * a filled map with empty maps makes copies of the filled map, and
+/// * a filled map surrounded by eight paper makes a copy which zooms
+///   out once held.
+pub fn map_crafting_result(grid: &[Slot]) -> Option<ItemStack> {
+    let mut filled = None;
+    let mut empty_maps = 0;
+    let mut paper = 0;
+    for stack in grid.iter().flatten() {
+        match stack.ty {
+            Item::FilledMap if filled.is_none() => filled = Some(stack),
+            Item::Map => empty_maps += 1,
+            Item::Paper => paper += 1,
+            _ => return None,
+        }
+    }
+    let filled = filled.filter(|stack| stack.map_id().is_some())?;
+    let mut result = ItemStack {
+        amount: 1,
+        ..filled.clone()
+    };
+
+    match (empty_maps, paper) {
+        (0, 0) => None,
+        (copies, 0) => {
+            result.amount += copies;
+            Some(result)
+        }
+        (0, 8) => {
+            result.modify_nbt(|nbt| nbt.map_scale_direction = 1);
+            Some(result)
+        }
+        _ => None,
+    }
+}
+
+/// A player holding a filled map.
+struct MapHolder {
+    player: Entity,
+    slot: SlotIndex,
+    stack: ItemStack,
+}
+
+/// System which draws maps around the players holding them
+/// and sends the changes and icons to those players.
+#[fecs::system]
+pub fn update_maps(game: &mut Game, world: &mut World, maps: &mut Maps) {
+    if game.tick_count % SAVE_INTERVAL == 0 {
+        maps.save(&game.running_tasks);
+    }
+
+    let mut holders = Vec::new();
+    for (player, (inventory, held_item)) in <(Read<Inventory>, Read<HeldItem>)>::query()
+        .filter(component::<Player>())
+        .iter_entities(world.inner())
+    {
+        for &slot in &[SLOT_HOTBAR_OFFSET + held_item.0, SLOT_OFFHAND] {
+            match inventory.item_at(slot) {
+                Some(stack) if stack.ty == Item::FilledMap && stack.map_id().is_some() => holders
+                    .push(MapHolder {
+                        player,
+                        slot,
+                        stack: stack.clone(),
+                    }),
+                _ => (),
+            }
+        }
+    }
+
+    for holder in &mut holders {
+        zoom_out(game, world, maps, holder);
+    }
+
+    let mut holders_by_map: AHashMap<i32, Vec<Entity>> = AHashMap::new();
+    for holder in &holders {
+        let id = holder.stack.map_id().unwrap();
+        let map = match maps.get_mut(id) {
+            Some(map) => map,
+            None => continue,
+        };
+        let pos = *world.get::<Position>(holder.player);
+        render_around(game, map, pos);
+        holders_by_map.entry(id).or_default().push(holder.player);
+    }
+
+    for (id, players) in holders_by_map {
+        let map = maps.get_mut(id).unwrap();
+        map.retain_viewers(|viewer| players.contains(viewer));
+
+        let update = map.take_update();
+        let send_icons = game.tick_count % ICON_INTERVAL == 0;
+        let icons = icons(world, map, &players);
+
+        for player in players {
+            let update = if map.add_viewer(player) {
+                Some(map.full_update())
+            } else if update.is_some() || send_icons {
+                update.clone()
+            } else {
+                continue;
+            };
+
+            world.get::<Network>(player).send(MapData {
+                map_id: id,
+                scale: map.scale,
+                tracking_position: map.tracking_position,
+                icons: icons.clone(),
+                update,
+            });
+        }
+    }
+}
+
+/// Replaces a map which was crafted with paper to zoom it
+/// out by a new map with a larger scale.
+fn zoom_out(game: &mut Game, world: &mut World, maps: &mut Maps, holder: &mut MapHolder) {
+    let scale_direction = holder.stack.nbt().map_or(0, |nbt| nbt.map_scale_direction);
+    if scale_direction <= 0 {
+        return;
+    }
+
+    let id = holder.stack.map_id().unwrap();
+    let zoomed = maps.get(id).map(|map| {
+        let scale = (map.scale + scale_direction as i8).min(MAX_MAP_SCALE);
+        let mut zoomed = map.zoomed_out(scale);
+        zoomed.center = map_center(map.center.0, map.center.1, scale);
+        zoomed
+    });
+
+    holder.stack.modify_nbt(|nbt| nbt.map_scale_direction = 0);
+    if let Some(zoomed) = zoomed {
+        let id = maps.create(zoomed, &game.running_tasks);
+        holder.stack.set_map_id(Some(id));
+    }
+
+    world
+        .get_mut::<Inventory>(holder.player)
+        .set_item_at(holder.slot, holder.stack.clone());
+    game.handle(
+        world,
+        InventoryUpdateEvent {
+            slots: smallvec![holder.slot],
+            player: holder.player,
+        },
+    );
+}
+
+/// Draws one stripe of the pixels within `RENDER_DISTANCE` of `pos`.
+fn render_around(game: &Game, map: &mut MapState, pos: Position) {
+    let blocks_per_pixel = 1 << map.scale;
+    let half = MAP_SIZE / 2;
+    let origin = (
+        map.center.0 / blocks_per_pixel - half,
+        map.center.1 / blocks_per_pixel - half,
+    );
+    let player_pixel = (
+        pos.x.floor() as i32 / blocks_per_pixel - origin.0,
+        pos.z.floor() as i32 / blocks_per_pixel - origin.1,
+    );
+    let radius = RENDER_DISTANCE / blocks_per_pixel;
+    let stripe = (game.tick_count % RENDER_STRIPES as u64) as i32;
+
+    let min_x = (player_pixel.0 - radius).max(0);
+    let max_x = (player_pixel.0 + radius).min(MAP_SIZE - 1);
+    for x in (min_x..=max_x).filter(|x| x % RENDER_STRIPES == stripe) {
+        // The height of the previous pixel to the north,
+        // used to shade slopes.
+        let mut north_height = None;
+
+        let min_z = (player_pixel.1 - radius - 1).max(0);
+        let max_z = (player_pixel.1 + radius).min(MAP_SIZE - 1);
+        for z in min_z..=max_z {
+            let dx = x - player_pixel.0;
+            let dz = z - player_pixel.1;
+            let in_range = dx * dx + dz * dz <= radius * radius;
+
+            let block_x = (origin.0 + x) * blocks_per_pixel;
+            let block_z = (origin.1 + z) * blocks_per_pixel;
+            let surface = match column_surface(game, block_x, block_z) {
+                Some(surface) => surface,
+                None => {
+                    north_height = None;
+                    continue;
+                }
+            };
+
+            // The row just outside the range is only
+            // needed to shade the first row inside it.
+            if in_range {
+                let color = if surface.color == MapColor::Water as u8 {
+                    shade_water(surface.water_depth, x, z)
+                } else {
+                    let north_height = north_height.unwrap_or(surface.height);
+                    shade_pixel(surface.color, surface.height, north_height)
+                };
+                map.set_pixel(x as usize, z as usize, color);
+            }
+            north_height = Some(surface.height);
+        }
+    }
+}
+
+/// Finds the surface of the column at `x`, `z`, or `None`
+/// if its chunk is not loaded.
+fn column_surface(game: &Game, x: i32, z: i32) -> Option<MapSurface> {
+    let chunk = game
+        .chunk_map
+        .chunk_at(ChunkPosition::new(x.div_euclid(16), z.div_euclid(16)))?;
+    let (local_x, local_z) = (x.rem_euclid(16) as usize, z.rem_euclid(16) as usize);
+
+    // The heightmap may lie above the surface after blocks
+    // are removed, but never below it.
+    let top = match chunk.heightmap(local_x, local_z).world_surface() {
+        0 => 255,
+        top => i32::from(top),
+    };
+    map_surface(x, z, top, |pos: BlockPosition| -> Option<BlockId> {
+        Some(chunk.block_at(local_x, pos.y as usize, local_z))
+    })
+}
+
+/// Returns the icons displayed on a map: its banners and,
+/// if it tracks positions, the players holding it.
+fn icons(world: &World, map: &MapState, players: &[Entity]) -> Vec<MapIcon> {
+    let mut icons = Vec::new();
+
+    for banner in &map.banners {
+        let (x, z, on_map) = map_icon_position(
+            map.center,
+            map.scale,
+            f64::from(banner.pos.x) + 0.5,
+            f64::from(banner.pos.z) + 0.5,
+        );
+        let ty = match banner_icon(&banner.color) {
+            Some(ty) if on_map => ty,
+            _ => continue,
+        };
+        icons.push(MapIcon {
+            ty,
+            x,
+            z,
+            direction: 8,
+            display_name: banner.name.clone(),
+        });
+    }
+
+    if map.tracking_position {
+        for &player in players {
+            let pos = *world.get::<Position>(player);
+            let (x, z, on_map) = map_icon_position(map.center, map.scale, pos.x, pos.z);
+            let (ty, direction) = if on_map {
+                (MAP_ICON_PLAYER, map_icon_direction(pos.yaw))
+            } else {
+                (MAP_ICON_PLAYER_OFF_MAP, 0)
+            };
+            icons.push(MapIcon {
+                ty,
+                x,
+                z,
+                direction,
+                display_name: None,
+            });
+        }
+    }
+
+    icons
+}
@@ -1,22 +1,46 @@
 //! Systems which handle packets.
 
+mod abilities;
 mod animation;
 mod chat;
+mod client_status;
 mod digging;
+mod entity_action;
 mod inventory;
 mod movement;
 mod placement;
+mod tab_complete;
+mod use_entity;
 mod use_item;
 
+pub use abilities::handle_player_abilities;
 pub use animation::handle_animation;
 pub use chat::handle_chat;
+pub use client_status::handle_client_status;
 pub use digging::handle_player_digging;
+pub use entity_action::handle_entity_action;
+use feather_core::network::packets::BlockChange;
+use feather_core::util::BlockPosition;
+use feather_server_types::{Game, Network};
 use fecs::{Entity, World};
 pub use inventory::{handle_creative_inventory_action, handle_held_item_change};
 pub use movement::handle_movement_packets;
 pub use placement::handle_player_block_placement;
+pub use tab_complete::handle_tab_complete;
+pub use use_entity::handle_use_entity;
 pub use use_item::handle_player_use_item;
 
+/// Sends a player the block at `pos`, undoing any change
+/// their client predicted for an action which was denied.
+pub(crate) fn resend_block(game: &Game, world: &World, player: Entity, pos: BlockPosition) {
+    if let Some(block) = game.block_at(pos) {
+        world.get::<Network>(player).send(BlockChange {
+            location: pos,
+            block_id: block.vanilla_id() as i32,
+        });
+    }
+}
+
 /// Iterator filter to ensure players have not been removed from the world.
 pub trait IteratorExt: Iterator {
     fn for_each_valid(self, world: &mut World, f: impl FnMut(&mut World, Self::Item));
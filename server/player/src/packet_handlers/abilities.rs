@@ -0,0 +1,54 @@
+//! Handling of the Player Abilities packet, which clients
+//! send when players start or stop flying.
+
+use crate::IteratorExt;
+use feather_core::network::packets::PlayerAbilitiesServerbound;
+use feather_core::util::Gamemode;
+use feather_server_types::{Game, PacketBuffers};
+use feather_server_util::{can_fly, send_abilities, set_flying};
+use fecs::{Entity, World};
+use std::sync::Arc;
+
+/// Flag set in the packet while the player is flying.
+const FLAG_FLYING: u8 = 0x02;
+
+/// System which starts and stops players flying when
+/// their client asks to.
+#[fecs::system]
+pub fn handle_player_abilities(
+    game: &mut Game,
+    world: &mut World,
+    packet_buffers: &Arc<PacketBuffers>,
+) {
+    packet_buffers
+        .received::<PlayerAbilitiesServerbound>()
+        .for_each_valid(world, |world, (player, packet)| {
+            let flying = packet.flags & FLAG_FLYING != 0;
+            handle_flying(game, world, player, flying);
+        });
+}
+
+fn handle_flying(game: &Game, world: &mut World, player: Entity, flying: bool) {
+    let gamemode = *world.get::<Gamemode>(player);
+
+    // Only the flying flag is taken from the client; the others,
+    // along with the flying speed, are decided by the server.
+    let allowed = if flying {
+        can_fly(gamemode)
+    } else {
+        gamemode != Gamemode::Spectator
+    };
+
+    if allowed {
+        set_flying(world, player, flying);
+    } else {
+        log::debug!(
+            "Player {:?} in {:?} mode attempted to {} flying",
+            player,
+            gamemode,
+            if flying { "start" } else { "stop" }
+        );
+        // Correct the client, which has already changed its state.
+        send_abilities(game, world, player);
+    }
+}
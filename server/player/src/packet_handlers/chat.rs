@@ -1,7 +1,8 @@
-use crate::IteratorExt;
+use crate::{format_chat_message, IteratorExt};
 use feather_core::network::packets::ChatMessageServerbound;
-use feather_core::text::{TextRoot, Translate};
-use feather_server_types::{ChatEvent, ChatPosition, Game, Name, PacketBuffers};
+use feather_server_types::{
+    filter_chat_message, ChatEvent, ChatPosition, CommandEvent, Game, PacketBuffers,
+};
 use fecs::World;
 use std::sync::Arc;
 
@@ -12,25 +13,28 @@ pub fn handle_chat(game: &mut Game, world: &mut World, packet_buffers: &Arc<Pack
         .received::<ChatMessageServerbound>()
         .for_each_valid(world, |world, (player, packet)| {
             if packet.message.starts_with('/') {
-                log::debug!("Skipping command {}", packet.message);
+                game.handle(
+                    world,
+                    CommandEvent {
+                        sender: player,
+                        command: packet.message[1..].to_owned(),
+                    },
+                );
                 return;
             }
 
-            let player_name = world.get::<Name>(player);
-            let message: String = TextRoot::from(
-                Translate::ChatTypeText
-                    * vec![player_name.0.to_string(), packet.message.to_string()],
-            )
-            .into();
-
-            log::info!("<{}> {}", player_name.0, packet.message);
-            drop(player_name);
+            let message = match filter_chat_message(game, world, player, packet.message.clone()) {
+                Some(message) => message,
+                None => return,
+            };
 
+            let message = format_chat_message(game, world, player, &message);
             game.handle(
                 world,
                 ChatEvent {
                     message,
                     position: ChatPosition::Chat,
+                    sender: Some(player),
                 },
             );
         });
@@ -0,0 +1,26 @@
+use crate::{respawn, send_statistics, IteratorExt};
+use feather_core::network::packets::ClientStatus;
+use feather_server_types::{Game, PacketBuffers};
+use fecs::World;
+use std::sync::Arc;
+
+/// Client Status action sent when a dead player clicks "Respawn."
+const ACTION_PERFORM_RESPAWN: i32 = 0;
+/// Client Status action sent when a player opens the statistics menu.
+const ACTION_REQUEST_STATS: i32 = 1;
+
+/// Handles client status packets.
+#[fecs::system]
+pub fn handle_client_status(
+    game: &mut Game,
+    world: &mut World,
+    packet_buffers: &Arc<PacketBuffers>,
+) {
+    packet_buffers
+        .received::<ClientStatus>()
+        .for_each_valid(world, |world, (player, packet)| match packet.action_id {
+            ACTION_PERFORM_RESPAWN => respawn(game, world, player),
+            ACTION_REQUEST_STATS => send_statistics(world, player),
+            action => log::trace!("Unhandled client status action {}", action),
+        });
+}
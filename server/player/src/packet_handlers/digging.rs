@@ -4,17 +4,23 @@
 //! for actions mostly unrelated to digging including eating, shooting bows,
 //! swapping items out to the offhand, and dropping items.
 
+use crate::bow::shoot_bow;
+use crate::packet_handlers::resend_block;
+use crate::trident::release_trident;
 use crate::{ItemTimedUse, IteratorExt};
 use feather_core::blocks::BlockId;
-use feather_core::inventory::{Inventory, SlotIndex, SLOT_HOTBAR_OFFSET, SLOT_OFFHAND};
+use feather_core::inventory::{Inventory, SLOT_HOTBAR_OFFSET, SLOT_OFFHAND};
 use feather_core::items::{Item, ItemStack};
 use feather_core::network::packets::{PlayerDigging, PlayerDiggingStatus};
-use feather_core::util::{Gamemode, Position};
+use feather_core::util::Gamemode;
 use feather_server_types::{
-    EntitySpawnEvent, Game, HeldItem, InventoryUpdateEvent, ItemDropEvent, PacketBuffers,
-    PLAYER_EYE_HEIGHT,
+    add_statistic, BlockUpdateCause, Game, HeldItem, InventoryUpdateEvent, ItemDropEvent,
+    PacketBuffers, Statistic, DO_TILE_DROPS,
+};
+use feather_server_util::{
+    block_drops, breaks_instantly, can_break_in_gamemode, can_interact, consumes_items, dig_speed,
+    digs_instantly, held_stack, lower_shield, reset_attack_cooldown,
 };
-use feather_server_util::{charge_from_ticks_held, compute_projectile_velocity};
 use fecs::{Entity, World};
 use smallvec::smallvec;
 use std::sync::Arc;
@@ -31,22 +37,38 @@ pub fn handle_player_digging(
 
     packet_buffers
         .received::<PlayerDigging>()
-        .for_each_valid(world, |world, (player, packet)| match packet.status {
-            StartedDigging | FinishedDigging | CancelledDigging => {
-                handle_digging(game, world, player, packet)
+        .for_each_valid(world, |world, (player, packet)| {
+            if !can_interact(*world.get::<Gamemode>(player)) {
+                return;
+            }
+
+            match packet.status {
+                StartedDigging | FinishedDigging | CancelledDigging => {
+                    handle_digging(game, world, player, packet)
+                }
+                DropItem | DropItemStack => handle_drop_item_stack(game, world, player, packet),
+                ConsumeItem => handle_consume_item(game, world, player, packet),
+                SwapItemInHand => handle_swap_hands(game, world, player),
             }
-            DropItem | DropItemStack => handle_drop_item_stack(game, world, player, packet),
-            ConsumeItem => handle_consume_item(game, world, player, packet),
-            status => log::warn!("Unhandled Player Digging status {:?}", status),
         });
 }
 
 fn handle_digging(game: &mut Game, world: &mut World, player: Entity, packet: PlayerDigging) {
     let gamemode = *world.get::<Gamemode>(player);
+    let tool = held_stack(world, player);
+    let block = game.block_at(packet.location);
+
     // Return early if needed
     match packet.status {
         PlayerDiggingStatus::StartedDigging => {
-            if gamemode != Gamemode::Creative {
+            // Clients break blocks which they dig in a
+            // single tick without finishing digging them.
+            let instant = breaks_instantly(gamemode)
+                || block.map_or(false, |block| {
+                    let speed = dig_speed(game, world, player, tool.as_ref(), block);
+                    digs_instantly(speed, block, tool.as_ref())
+                });
+            if !instant {
                 return;
             }
         }
@@ -54,15 +76,20 @@ fn handle_digging(game: &mut Game, world: &mut World, player: Entity, packet: Pl
         _ => (),
     }
 
-    let item_in_main_hand = world
-        .get::<Inventory>(player)
-        .item_at(world.get::<HeldItem>(player).0)
-        .copied();
+    let allowed = block.map_or(true, |block| {
+        can_break_in_gamemode(gamemode, tool.as_ref(), block)
+    });
+    if !allowed {
+        resend_block(game, world, player, packet.location);
+        return;
+    }
+
+    let item_in_main_hand = tool.as_ref().map(|stack| stack.ty);
 
     // Don't break block if player is holding a sword in creative mode.
     if gamemode == Gamemode::Creative {
         if let Some(item_in_main_hand) = item_in_main_hand {
-            match item_in_main_hand.ty {
+            match item_in_main_hand {
                 Item::WoodenSword
                 | Item::StoneSword
                 | Item::GoldenSword
@@ -73,10 +100,28 @@ fn handle_digging(game: &mut Game, world: &mut World, player: Entity, packet: Pl
         }
     }
 
-    if !game.set_block_at(world, packet.location, BlockId::air()) {
+    if !game.set_block_at_with_cause(
+        world,
+        packet.location,
+        BlockId::air(),
+        BlockUpdateCause::entity(player),
+    ) {
         game.disconnect(player, world, "attempted to break block in unloaded chunk");
         return;
     }
+
+    // Blocks broken in creative mode drop nothing.
+    if let Some(block) = block {
+        if consumes_items(gamemode) {
+            add_statistic(world, player, Statistic::Mined(block.kind()), 1);
+            if game.game_rules.bool(DO_TILE_DROPS) {
+                let drops = block_drops(block, tool.as_ref(), &mut *game.rng());
+                for stack in drops {
+                    entity::item::drop_block_item(game, world, packet.location, stack);
+                }
+            }
+        }
+    }
 }
 
 fn handle_drop_item_stack(
@@ -97,7 +142,7 @@ fn handle_drop_item_stack(
 
     let stack = {
         if let Some(item) = inventory.item_at(slot) {
-            *item
+            item.clone()
         } else {
             // Silently fail - no item stack to drop
             return;
@@ -113,7 +158,14 @@ fn handle_drop_item_stack(
                 inventory.clear_item_at(slot);
                 1
             } else {
-                inventory.set_item_at(slot, ItemStack::new(stack.ty, stack.amount - 1));
+                let amount = stack.amount - 1;
+                inventory.set_item_at(
+                    slot,
+                    ItemStack {
+                        amount,
+                        ..stack.clone()
+                    },
+                );
                 1
             }
         }
@@ -135,141 +187,59 @@ fn handle_drop_item_stack(
     if amnt != 0 {
         let item_drop = ItemDropEvent {
             slot: Some(slot),
-            stack: ItemStack::new(stack.ty, amnt),
+            stack: ItemStack {
+                amount: amnt,
+                ..stack
+            },
             player,
         };
         game.handle(world, item_drop);
     }
 }
 
-/// Handles food consumption and shooting arrows.
-fn handle_consume_item(game: &mut Game, world: &mut World, player: Entity, packet: PlayerDigging) {
-    assert_eq!(packet.status, PlayerDiggingStatus::ConsumeItem);
+/// Swaps the items in a player's main hand and offhand.
+fn handle_swap_hands(game: &mut Game, world: &mut World, player: Entity) {
+    let main_hand = SLOT_HOTBAR_OFFSET + world.get::<HeldItem>(player).0;
 
-    // TODO: Fallback to off-hand if main-hand is not a consumable
-    let inventory = world.get::<Inventory>(player);
-    let used_item = inventory.item_at(world.get::<HeldItem>(player).0);
-
-    if let Some(item) = used_item {
-        if item.ty == Item::Bow {
-            drop(inventory);
-            handle_shoot_bow(game, world, player);
-        }
-        // TODO: Food, potions
-    }
-}
-
-fn handle_shoot_bow(game: &mut Game, world: &mut World, player: Entity) {
-    let inventory = world.get::<Inventory>(player);
-    let arrow_to_consume: Option<(SlotIndex, ItemStack)> = find_arrow(&inventory);
-    // Unnecessary until more gamemodes are supported
-    /*
-    if player.gamemode == Gamemode::Survival || player.gamemode == Gamemode::Adventure {
-        // If no arrow was found, don't shoot
-        let arrow_to_consume = arrow_to_consume.clone();
-        if arrow_to_consume.is_none() {
-            debug!("Tried to shoot bow with no arrows.");
+    {
+        let mut inventory = world.get_mut::<Inventory>(player);
+        if inventory.item_at(main_hand).is_none() && inventory.item_at(SLOT_OFFHAND).is_none() {
             return;
         }
-
-        // Consume arrow
-        let (arrow_slot, arrow_stack) = arrow_to_consume.unwrap();
-        let mut arrow_stack: ItemStack = arrow_stack;
-        arrow_stack.amount -= 1;
-
-        inventory.set_item_at(arrow_slot, arrow_stack);
-        inventory_updates.single_write(InventoryUpdateEvent {
-            slots: smallvec![arrow_slot],
-            player: entity,
-        });
-    }
-    */
-
-    drop(inventory); // Inventory no longer used.
-
-    let _arrow_type: Item = match arrow_to_consume {
-        None => Item::Arrow, // Default to generic arrow in creative mode with none in inventory
-        Some((_, arrow_stack)) => arrow_stack.ty,
-    };
-
-    let timed_use = world.try_get::<ItemTimedUse>(player);
-
-    // Spam clicking can lead to a scenario where this system is called before the UseItem system adds the component
-    // In that case just return.
-    if timed_use.is_none() {
-        return;
-    }
-
-    let timed_use = timed_use.unwrap();
-
-    let mut time_held = game.tick_count - timed_use.tick_start;
-
-    if time_held > 20 {
-        time_held = 20;
+        inventory.swap_items(main_hand, SLOT_OFFHAND);
     }
 
-    let charge_force = charge_from_ticks_held(time_held as u32);
-    log::trace!("Held for {} ticks. Force of {}", time_held, charge_force);
-
-    let init_position = *world.get::<Position>(player) + glm::vec3(0.0, PLAYER_EYE_HEIGHT, 0.0);
+    // Neither hand holds the item in use anymore.
+    lower_shield(world, player);
+    let _ = world.remove::<ItemTimedUse>(player);
+    reset_attack_cooldown(game, world, player);
 
-    let direction = init_position.direction();
-
-    let arrow_velocity = compute_projectile_velocity(
-        glm::vec3(direction.x, direction.y, direction.z),
-        charge_force as f64,
-        0.0,
-        &mut *game.rng(),
-    );
-    log::trace!(
-        "Computed exit velocity: {}. Velocity is norm {}",
-        arrow_velocity,
-        arrow_velocity.norm()
+    game.handle(
+        world,
+        InventoryUpdateEvent {
+            slots: smallvec![main_hand, SLOT_OFFHAND],
+            player,
+        },
     );
-
-    drop(timed_use);
-
-    world.remove::<ItemTimedUse>(player).unwrap();
-
-    log::trace!("Spawning arrow entity.");
-    let entity = entity::arrow::create()
-        .with(init_position)
-        .with(arrow_velocity)
-        .build()
-        .spawn_in(world);
-    game.handle(world, EntitySpawnEvent { entity });
 }
 
-fn find_arrow(inventory: &Inventory) -> Option<(SlotIndex, ItemStack)> {
-    // Order of priority is: off-hand, hotbar (0 to 8), rest of inventory
-
-    if let Some(offhand) = inventory.item_at(SLOT_OFFHAND) {
-        if is_arrow_item(offhand.ty) {
-            return Some((SLOT_OFFHAND, *offhand));
-        }
-    }
+/// Handles food consumption and shooting arrows.
+fn handle_consume_item(game: &mut Game, world: &mut World, player: Entity, packet: PlayerDigging) {
+    assert_eq!(packet.status, PlayerDiggingStatus::ConsumeItem);
 
-    for hotbar_slot in 0..9 {
-        if let Some(hotbar_stack) = inventory.item_at(SLOT_HOTBAR_OFFSET + hotbar_slot) {
-            if is_arrow_item(hotbar_stack.ty) {
-                return Some((SLOT_HOTBAR_OFFSET + hotbar_slot, *hotbar_stack));
-            }
-        }
-    }
+    // This status is also sent when a player lowers their shield.
+    lower_shield(world, player);
 
-    for inv_slot in 9..=35 {
-        if let Some(inv_stack) = inventory.item_at(inv_slot) {
-            if is_arrow_item(inv_stack.ty) {
-                return Some((inv_slot, *inv_stack));
-            }
-        }
-    }
-    None
-}
+    // Spam clicking can lead to a scenario where this is called
+    // before the item use started. In that case just return.
+    let timed_use = match world.try_get::<ItemTimedUse>(player) {
+        Some(timed_use) => *timed_use,
+        None => return,
+    };
+    // Releasing the item stops eating, so the use ends regardless of the item.
+    world.remove::<ItemTimedUse>(player).unwrap();
 
-fn is_arrow_item(item: Item) -> bool {
-    match item {
-        Item::Arrow | Item::SpectralArrow | Item::TippedArrow => true,
-        _ => false,
-    }
+    shoot_bow(game, world, player, timed_use);
+    release_trident(game, world, player, timed_use);
+    // TODO: potions
 }
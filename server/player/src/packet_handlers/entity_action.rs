@@ -0,0 +1,43 @@
+use crate::{start_gliding, stop_spectating, IteratorExt};
+use feather_core::entitymeta::{EntityBitMask, EntityMetadata};
+use feather_core::network::packets::{EntityAction, EntityActionType};
+use feather_server_types::{Game, PacketBuffers, Sneaking};
+use feather_server_util::set_sprinting;
+use fecs::{Entity, World};
+use std::sync::Arc;
+
+/// Handles entity action packets.
+#[fecs::system]
+pub fn handle_entity_action(
+    game: &mut Game,
+    world: &mut World,
+    packet_buffers: &Arc<PacketBuffers>,
+) {
+    packet_buffers
+        .received::<EntityAction>()
+        .for_each_valid(world, |world, (player, packet)| match packet.action_id {
+            EntityActionType::StartSneaking => {
+                // Sneaking is how spectators leave the entity they watch.
+                stop_spectating(world, player);
+                set_sneaking(world, player, true)
+            }
+            EntityActionType::StopSneaking => set_sneaking(world, player, false),
+            EntityActionType::StartSprinting => set_sprinting(game, world, player, true),
+            EntityActionType::StopSprinting => set_sprinting(game, world, player, false),
+            EntityActionType::StartFlyingWithElytra => start_gliding(game, world, player),
+            action => log::trace!("Unhandled entity action {:?}", action),
+        });
+}
+
+/// Starts or stops a player sneaking.
+fn set_sneaking(world: &mut World, player: Entity, sneaking: bool) {
+    if sneaking && !world.has::<Sneaking>(player) {
+        world.add(player, Sneaking).unwrap();
+    } else if !sneaking && world.has::<Sneaking>(player) {
+        world.remove::<Sneaking>(player).unwrap();
+    }
+
+    world
+        .get_mut::<EntityMetadata>(player)
+        .set_flag(EntityBitMask::CROUCHED, sneaking);
+}
@@ -1,12 +1,19 @@
 //! Handling of inventory update packets.
 //! This currently includes Creative Inventory Action and Held Item Change.
 
-use crate::IteratorExt;
-use feather_core::inventory::{Inventory, HOTBAR_SIZE, SLOT_HOTBAR_OFFSET};
+use crate::{update_crafting_output, IteratorExt};
+use feather_core::inventory::{
+    max_size, Inventory, HOTBAR_SIZE, SLOT_CRAFTING_OUTPUT, SLOT_HOTBAR_OFFSET,
+};
+use feather_core::items::{Enchantment, Item, ItemStack};
 use feather_core::network::packets::{CreativeInventoryAction, HeldItemChangeServerbound};
 use feather_core::util::Gamemode;
-use feather_server_types::{Game, HeldItem, InventoryUpdateEvent, ItemDropEvent, PacketBuffers};
+use feather_server_types::{
+    Game, HeldItem, InventoryUpdateEvent, ItemDropEvent, PacketBuffers, RecipeRegistry,
+};
+use feather_server_util::{lower_shield, reset_attack_cooldown};
 use fecs::World;
+use smallvec::SmallVec;
 use std::sync::Arc;
 
 /// System for handling Creative Inventory Action packets.
@@ -15,6 +22,7 @@ pub fn handle_creative_inventory_action(
     game: &mut Game,
     world: &mut World,
     packet_buffers: &Arc<PacketBuffers>,
+    recipes: &RecipeRegistry,
 ) {
     packet_buffers
         .received::<CreativeInventoryAction>()
@@ -31,24 +39,22 @@ pub fn handle_creative_inventory_action(
                 return;
             }
 
+            let stack = packet.clicked_item.and_then(sanitize_creative_stack);
+
             // Slot -1 means that the user clicked outside the window,
             // dropping the item.
             if packet.slot == -1 {
-                match &packet.clicked_item {
-                    Some(stack) => {
-                        // Cause item to be dropped
-                        let event = ItemDropEvent {
-                            slot: None,
-                            stack: *stack,
-                            player,
-                        };
-                        game.handle(world, event);
-
-                        // No need to update inventory
-                        return;
-                    }
-                    None => (),
+                if let Some(stack) = stack {
+                    let event = ItemDropEvent {
+                        slot: None,
+                        stack,
+                        player,
+                    };
+                    game.handle(world, event);
                 }
+
+                // No need to update inventory
+                return;
             }
 
             let inventory = world.get::<Inventory>(player);
@@ -60,27 +66,77 @@ pub fn handle_creative_inventory_action(
                 return;
             }
 
+            // The crafting output cannot be set directly.
+            if packet.slot == SLOT_CRAFTING_OUTPUT as i16 {
+                return;
+            }
+
             let mut inventory = world.get_mut::<Inventory>(player);
 
-            match packet.clicked_item.as_ref() {
+            match stack {
                 Some(item) => {
-                    inventory.set_item_at(packet.slot as usize, item.clone());
+                    inventory.set_item_at(packet.slot as usize, item);
                 }
                 None => {
                     inventory.clear_item_at(packet.slot as usize);
                 }
             }
 
+            let mut slots: SmallVec<[usize; 2]> = std::iter::once(packet.slot as usize).collect();
+            if update_crafting_output(&mut inventory, recipes) {
+                slots.push(SLOT_CRAFTING_OUTPUT);
+            }
+
             // Trigger inventory update event
-            let event = InventoryUpdateEvent {
-                slots: std::iter::once(packet.slot as usize).collect(),
-                player,
-            };
+            let event = InventoryUpdateEvent { slots, player };
             drop(inventory);
             game.handle(world, event);
         });
 }
 
+/// Tags without a typed field which creative players may set,
+/// such as those of potions, enchanted books and player heads.
+const CREATIVE_TAGS: &[&str] = &[
+    "AttributeModifiers",
+    "BlockEntityTag",
+    "BlockStateTag",
+    "BucketVariantTag",
+    "CustomPotionColor",
+    "CustomPotionEffects",
+    "Decorations",
+    "Explosion",
+    "HideFlags",
+    "Potion",
+    "Recipes",
+    "RepairCost",
+    "SkullOwner",
+    "StoredEnchantments",
+];
+
+/// Cleans up a stack which a creative player took from the
+/// creative inventory, whose contents the client decides.
+///
+/// Returns `None` if the stack should be discarded.
+fn sanitize_creative_stack(mut stack: ItemStack) -> Option<ItemStack> {
+    if stack.ty == Item::Air || stack.amount == 0 {
+        return None;
+    }
+
+    // Clients may send stacks of any size.
+    stack.amount = stack.amount.min(max_size(stack.ty));
+
+    if stack.nbt.is_some() {
+        stack.modify_nbt(|nbt| {
+            nbt.damage = nbt.damage.max(0);
+            nbt.enchantments
+                .retain(|entry| Enchantment::from_identifier(&entry.id).is_some());
+            nbt.other
+                .retain(|name, _| CREATIVE_TAGS.contains(&name.as_str()));
+        });
+    }
+    Some(stack)
+}
+
 /// System for handling Held Item Change packets.
 #[fecs::system]
 pub fn handle_held_item_change(
@@ -97,6 +153,7 @@ pub fn handle_held_item_change(
         }
 
         let mut held_item = world.get_mut::<HeldItem>(player);
+        let switched = held_item.0 != packet.slot as usize;
         held_item.0 = packet.slot as usize;
 
         // Trigger event
@@ -105,6 +162,14 @@ pub fn handle_held_item_change(
             player,
         };
         drop(held_item);
+
+        // Switching items restarts the attack cooldown
+        // and lowers a raised shield.
+        if switched {
+            reset_attack_cooldown(game, world, player);
+            lower_shield(world, player);
+        }
+
         game.handle(world, event);
     }
 }
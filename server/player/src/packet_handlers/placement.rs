@@ -1,14 +1,26 @@
 //! Handling of player block placement packets.
 
-use crate::IteratorExt;
-use feather_core::blocks::BlockKind;
-use feather_core::inventory::{Inventory, SLOT_HOTBAR_OFFSET};
+use crate::bone_meal::use_bone_meal;
+use crate::container::open_container;
+use crate::fire::ignite;
+use crate::map::mark_banner;
+use crate::packet_handlers::resend_block;
+use crate::spawn_egg::use_spawn_egg;
+use crate::{hand_slot, remove_one_item, IteratorExt};
+use feather_core::blocks::{BlockId, BlockKind, FacingCardinal};
+use feather_core::inventory::{Inventory, SlotIndex};
 use feather_core::item_block::ItemToBlock;
-use feather_core::items::ItemStack;
-use feather_core::network::packets::PlayerBlockPlacement;
-use feather_core::util::Gamemode;
-use feather_server_types::{Game, HeldItem, InventoryUpdateEvent, PacketBuffers};
-use fecs::World;
+use feather_core::items::{Item, ItemStack};
+use feather_core::network::packets::{Face, PlayerBlockPlacement};
+use feather_core::util::{BlockPosition, Gamemode, Hand, Position};
+use feather_server_types::{
+    add_statistic, BlockInteractEvent, BlockUpdateCause, EntitySpawnEvent, Game,
+    InventoryUpdateEvent, PacketBuffers, Sneaking, Statistic,
+};
+use feather_server_util::{
+    can_interact, can_place_in_gamemode, consumes_items, play_sound, wear_item, SoundCategory,
+};
+use fecs::{Entity, World};
 use std::sync::Arc;
 
 /// System for handling Player Block Placement packets
@@ -24,14 +36,86 @@ pub fn handle_player_block_placement(
         .for_each_valid(world, |world, (player, packet)| {
             // TODO: handle slabs, blocks with directions, etc.
             let gamemode = *world.get::<Gamemode>(player);
-            let inventory = world.get::<Inventory>(player);
+            if !can_interact(gamemode) {
+                return;
+            }
+            let hand = match packet.hand {
+                0 => Hand::Main,
+                _ => Hand::Off,
+            };
+            if hand == Hand::Main && !world.has::<Sneaking>(player) {
+                if let Some(block) = game.block_at(packet.location) {
+                    game.handle(
+                        world,
+                        BlockInteractEvent {
+                            player,
+                            pos: packet.location,
+                            block,
+                        },
+                    );
+                }
+                if open_container(game, world, player, packet.location) {
+                    return;
+                }
+            }
 
-            let item = match inventory.item_at(world.get::<HeldItem>(player).0) {
-                Some(item) => *item,
+            let slot = hand_slot(world, player, hand);
+            let item = match world.get::<Inventory>(player).item_at(slot) {
+                Some(item) => item.clone(),
                 None => return, // No block to place
             };
 
-            drop(inventory);
+            if item.ty == Item::Shears
+                && carve_pumpkin(game, world, player, slot, packet.location, packet.face)
+            {
+                return;
+            }
+            if item.ty == Item::FlintAndSteel || item.ty == Item::FireCharge {
+                ignite(
+                    game,
+                    world,
+                    player,
+                    slot,
+                    item.ty,
+                    packet.location,
+                    packet.face,
+                );
+                return;
+            }
+            if item.ty == Item::BoneMeal {
+                use_bone_meal(game, world, player, slot, packet.location);
+                return;
+            }
+            if item.ty == Item::FilledMap && mark_banner(game, &item, packet.location) {
+                return;
+            }
+            if entity::spawn_egg_mob(item.ty).is_some() {
+                use_spawn_egg(
+                    game,
+                    world,
+                    player,
+                    slot,
+                    &item,
+                    packet.location,
+                    packet.face,
+                );
+                return;
+            }
+            if item.ty == Item::FireworkRocket {
+                let pos = packet.location.position()
+                    + glm::vec3(
+                        packet.cursor_position_x as f64,
+                        packet.cursor_position_y as f64,
+                        packet.cursor_position_z as f64,
+                    );
+                launch_firework(game, world, player, slot, item, pos);
+                return;
+            }
+
+            if item.ty == Item::ItemFrame {
+                hang_item_frame(game, world, player, slot, packet.location, packet.face);
+                return;
+            }
 
             let block = match item.ty.to_block() {
                 Some(block) => block,
@@ -54,9 +138,23 @@ pub fn handle_player_block_placement(
                 _ => packet.location + packet.face.placement_offset(),
             };
 
-            game.set_block_at(world, pos, block);
+            if !can_place_in_gamemode(gamemode, &item, placed_on) {
+                // Undo the placement the client predicted.
+                resend_block(game, world, player, pos);
+                resend_block(game, world, player, packet.location);
+                game.handle(
+                    world,
+                    InventoryUpdateEvent {
+                        slots: std::iter::once(slot).collect(),
+                        player,
+                    },
+                );
+                return;
+            }
+
+            game.set_block_at_with_cause(world, pos, block, BlockUpdateCause::entity(player));
+            add_statistic(world, player, Statistic::Used(item.ty), 1);
 
-            let held_item = world.get::<HeldItem>(player).0;
             let mut inventory = world.get_mut::<Inventory>(player);
 
             // Update player's inventory if in survival
@@ -71,11 +169,11 @@ pub fn handle_player_block_placement(
                     return;
                 }
 
-                let item = ItemStack::new(item.ty, item.amount - 1);
-                inventory.set_item_at(held_item, item);
+                let amount = item.amount - 1;
+                inventory.set_item_at(slot, ItemStack { amount, ..item });
 
                 let event = InventoryUpdateEvent {
-                    slots: std::iter::once(SLOT_HOTBAR_OFFSET + held_item).collect(),
+                    slots: std::iter::once(slot).collect(),
                     player,
                 };
                 drop(inventory);
@@ -83,3 +181,127 @@ pub fn handle_player_block_placement(
             }
         });
 }
+
+/// Carves a pumpkin clicked with shears, which drops its seeds.
+/// Returns `false` if the block is not a pumpkin.
+fn carve_pumpkin(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    location: BlockPosition,
+    face: Face,
+) -> bool {
+    match game.block_at(location) {
+        Some(block) if block.kind() == BlockKind::Pumpkin => (),
+        _ => return false,
+    }
+
+    let facing = match face {
+        Face::North => FacingCardinal::North,
+        Face::South => FacingCardinal::South,
+        Face::West => FacingCardinal::West,
+        Face::East => FacingCardinal::East,
+        // Pumpkins carved from above or below face the player.
+        Face::Top | Face::Bottom => facing_towards(world.get::<Position>(player).yaw),
+    };
+    game.set_block_at_with_cause(
+        world,
+        location,
+        BlockId::carved_pumpkin().with_facing_cardinal(facing),
+        BlockUpdateCause::entity(player),
+    );
+    play_sound(
+        game,
+        world,
+        "block.pumpkin.carve",
+        SoundCategory::Block,
+        location.position(),
+        1.0,
+        1.0,
+        None,
+    );
+
+    wear_item(game, world, player, slot, 1);
+
+    let offset = match facing {
+        FacingCardinal::North => glm::vec3(0.0, 0.0, -0.65),
+        FacingCardinal::South => glm::vec3(0.0, 0.0, 0.65),
+        FacingCardinal::West => glm::vec3(-0.65, 0.0, 0.0),
+        FacingCardinal::East => glm::vec3(0.65, 0.0, 0.0),
+    };
+    let seeds_pos = location.position() + glm::vec3(0.5, 0.1, 0.5) + offset;
+    entity::item::drop_item(
+        game,
+        world,
+        seeds_pos,
+        ItemStack::new(Item::PumpkinSeeds, 4),
+    );
+    true
+}
+
+/// Returns the horizontal direction pointing back
+/// towards an entity whose yaw is `yaw`.
+fn facing_towards(yaw: f32) -> FacingCardinal {
+    match ((yaw / 90.0).round() as i32).rem_euclid(4) {
+        0 => FacingCardinal::North,
+        1 => FacingCardinal::East,
+        2 => FacingCardinal::South,
+        _ => FacingCardinal::West,
+    }
+}
+
+/// Launches the firework rocket in `slot` from the
+/// point on a block a player clicked.
+fn launch_firework(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    stack: ItemStack,
+    pos: Position,
+) {
+    let rocket = entity::firework_rocket::create_launched(game, stack, pos)
+        .build()
+        .spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity: rocket });
+
+    if consumes_items(*world.get::<Gamemode>(player)) {
+        remove_one_item(game, world, player, slot);
+    }
+}
+
+/// Hangs an item frame on the clicked face of a solid block,
+/// using up the item frame in the given inventory slot.
+fn hang_item_frame(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    location: BlockPosition,
+    face: Face,
+) {
+    match game.block_at(location) {
+        Some(block) if block.is_solid() => (),
+        _ => return,
+    }
+    let frame = match entity::item_frame::place(game, world, location, face) {
+        Some(frame) => frame,
+        None => return,
+    };
+
+    let pos = *world.get::<Position>(frame);
+    play_sound(
+        game,
+        world,
+        "entity.item_frame.place",
+        SoundCategory::Neutral,
+        pos,
+        1.0,
+        1.0,
+        None,
+    );
+    if consumes_items(*world.get::<Gamemode>(player)) {
+        remove_one_item(game, world, player, slot);
+    }
+}
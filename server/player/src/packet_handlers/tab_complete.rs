@@ -0,0 +1,26 @@
+use crate::IteratorExt;
+use feather_core::network::packets::{TabCompleteClientbound, TabCompleteServerbound};
+use feather_server_chat::suggest;
+use feather_server_types::{Game, Network, PacketBuffers};
+use fecs::World;
+use std::sync::Arc;
+
+/// Handles tab completion requests for commands.
+#[fecs::system]
+pub fn handle_tab_complete(game: &Game, world: &mut World, packet_buffers: &Arc<PacketBuffers>) {
+    packet_buffers
+        .received::<TabCompleteServerbound>()
+        .for_each_valid(world, |world, (player, packet)| {
+            let suggestions = suggest(game, world, player, &packet.text);
+            world.get::<Network>(player).send(TabCompleteClientbound {
+                transaction_id: packet.transaction_id,
+                start: suggestions.start as i32,
+                length: suggestions.length as i32,
+                matches: suggestions
+                    .matches
+                    .into_iter()
+                    .map(|suggestion| (suggestion, None))
+                    .collect(),
+            });
+        });
+}
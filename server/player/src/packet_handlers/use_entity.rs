@@ -0,0 +1,413 @@
+use crate::bucket::fill_bucket;
+use crate::{is_spectator, remove_one_item, start_spectating, IteratorExt};
+use entity::cow::Cow;
+use entity::creeper::Creeper;
+use entity::item_frame::{self, ItemFrame};
+use entity::mooshroom::Mooshroom;
+use entity::sheep::Wool;
+use feather_core::inventory::{Inventory, SLOT_HOTBAR_OFFSET};
+use feather_core::items::{Enchantment, Item};
+use feather_core::network::packets::{
+    AnimationClientbound, SimpleParticle, UseEntity, UseEntityType,
+};
+use feather_core::position;
+use feather_core::util::{ClientboundAnimation, Gamemode, Position};
+use feather_server_types::{
+    ActiveEffects, CreatureAttribute, DamageCause, EntityDamageRequest, EntityId, Game, Health,
+    HeldItem, LastDamage, PacketBuffers, PreviousPosition, Sprinting, StatusEffect, PLAYER_HEIGHT,
+};
+use feather_server_util::{
+    apply_fire_aspect, attack_charge, attack_damage, charged_attack_damage, consumes_items,
+    enchantment_damage_bonus, held_enchantment, held_stack, knock_back, nearby_entities,
+    play_sound, reset_attack_cooldown, set_sprinting, sweeping_damage_ratio, wear_item, DyeColor,
+    SoundCategory, EXTRA_KNOCKBACK,
+};
+use fecs::{Entity, World};
+use smallvec::SmallVec;
+use std::sync::Arc;
+
+/// Maximum distance from which players may attack entities.
+const MAX_REACH: f64 = 6.0;
+
+/// Damage multiplier of critical hits.
+const CRITICAL_MULTIPLIER: f32 = 1.5;
+
+/// Horizontal and vertical distance around the target of
+/// a sweep attack within which other entities are hit.
+const SWEEP_EXTENT: (f64, f64) = (1.0, 0.25);
+/// Maximum distance from the attacker of entities hit by a sweep.
+const SWEEP_REACH: f64 = 3.0;
+const SWEEP_DAMAGE: f32 = 1.0;
+const SWEEP_KNOCKBACK: f64 = 0.4;
+
+/// Handles use entity packets.
+#[fecs::system]
+pub fn handle_use_entity(game: &mut Game, world: &mut World, packet_buffers: &Arc<PacketBuffers>) {
+    packet_buffers
+        .received::<UseEntity>()
+        .for_each_valid(world, |world, (player, packet)| {
+            if is_spectator(world, player) {
+                if let UseEntityType::Attack = packet.ty {
+                    spectate(game, world, player, packet.target);
+                }
+                return;
+            }
+
+            match packet.ty {
+                UseEntityType::Attack => handle_attack(game, world, player, packet.target),
+                UseEntityType::Interact => handle_interact(game, world, player, packet.target),
+                // TODO: interacting at a position, e.g. with armor stands
+                UseEntityType::InteractAt(..) => (),
+            }
+        });
+}
+
+/// Finds the entity with the given ID if it is within
+/// reach of the player.
+fn find_target(game: &Game, world: &World, player: Entity, target_id: i32) -> Option<Entity> {
+    let pos = *world.get::<Position>(player);
+    nearby_entities(world, game, pos, glm::vec3(MAX_REACH, MAX_REACH, MAX_REACH))
+        .into_iter()
+        .find(|&entity| {
+            entity != player
+                && world
+                    .try_get::<EntityId>(entity)
+                    .map_or(false, |id| id.0 == target_id)
+        })
+        .filter(|&entity| {
+            world.get::<Position>(entity).distance_squared_to(pos) <= MAX_REACH * MAX_REACH
+        })
+}
+
+/// Attaches a spectator's camera to the entity they attacked.
+fn spectate(game: &Game, world: &mut World, player: Entity, target_id: i32) {
+    if let Some(target) = find_target(game, world, player, target_id) {
+        start_spectating(world, player, target);
+    }
+}
+
+/// Handles a player right-clicking an entity with their main hand.
+fn handle_interact(game: &mut Game, world: &mut World, player: Entity, target_id: i32) {
+    let target = match find_target(game, world, player, target_id) {
+        Some(target) => target,
+        None => return,
+    };
+    if world.has::<ItemFrame>(target) {
+        interact_with_item_frame(game, world, player, target);
+        return;
+    }
+
+    let held = held_stack(world, player).map(|stack| stack.ty);
+
+    // Milk cows and mooshrooms.
+    if held == Some(Item::Bucket) && (world.has::<Cow>(target) || world.has::<Mooshroom>(target)) {
+        let pos = *world.get::<Position>(player);
+        play_sound(
+            game,
+            world,
+            "entity.cow.milk",
+            SoundCategory::Player,
+            pos,
+            1.0,
+            1.0,
+            Some(player),
+        );
+        let slot = SLOT_HOTBAR_OFFSET + world.get::<HeldItem>(player).0;
+        fill_bucket(game, world, player, slot, Item::MilkBucket);
+        return;
+    }
+
+    // Ignite creepers with flint and steel.
+    if held == Some(Item::FlintAndSteel) && world.has::<Creeper>(target) {
+        let pos = *world.get::<Position>(target);
+        play_sound(
+            game,
+            world,
+            "item.flintandsteel.use",
+            SoundCategory::Hostile,
+            pos,
+            1.0,
+            1.0,
+            None,
+        );
+        entity::creeper::ignite(world, target);
+        let slot = SLOT_HOTBAR_OFFSET + world.get::<HeldItem>(player).0;
+        wear_item(game, world, player, slot, 1);
+        return;
+    }
+
+    if world.has::<Wool>(target) {
+        interact_with_sheep(game, world, player, target, held);
+    }
+}
+
+/// Shears or dyes a sheep right-clicked by a player.
+fn interact_with_sheep(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    sheep: Entity,
+    held: Option<Item>,
+) {
+    let held = match held {
+        Some(held) => held,
+        None => return,
+    };
+
+    if held == Item::Shears {
+        if entity::sheep::shear(game, world, sheep) {
+            let slot = SLOT_HOTBAR_OFFSET + world.get::<HeldItem>(player).0;
+            wear_item(game, world, player, slot, 1);
+            let pos = *world.get::<Position>(sheep);
+            play_sound(
+                game,
+                world,
+                "entity.sheep.shear",
+                SoundCategory::Player,
+                pos,
+                1.0,
+                1.0,
+                None,
+            );
+        }
+    } else if let Some(color) = DyeColor::from_dye(held) {
+        if entity::sheep::dye(world, sheep, color) && consumes_items(*world.get::<Gamemode>(player))
+        {
+            let slot = SLOT_HOTBAR_OFFSET + world.get::<HeldItem>(player).0;
+            remove_one_item(game, world, player, slot);
+        }
+    }
+}
+
+/// Puts the item held by a player in an empty item frame,
+/// or turns the item already in the frame.
+fn interact_with_item_frame(game: &mut Game, world: &mut World, player: Entity, frame: Entity) {
+    let sound = match held_stack(world, player) {
+        Some(stack) if item_frame::put_item(world, frame, &stack) => {
+            if consumes_items(*world.get::<Gamemode>(player)) {
+                let slot = SLOT_HOTBAR_OFFSET + world.get::<HeldItem>(player).0;
+                remove_one_item(game, world, player, slot);
+            }
+            "entity.item_frame.add_item"
+        }
+        _ if item_frame::rotate_item(world, frame) => "entity.item_frame.rotate_item",
+        _ => return,
+    };
+
+    let pos = *world.get::<Position>(frame);
+    play_sound(
+        game,
+        world,
+        sound,
+        SoundCategory::Neutral,
+        pos,
+        1.0,
+        1.0,
+        None,
+    );
+}
+
+fn handle_attack(game: &mut Game, world: &mut World, player: Entity, target_id: i32) {
+    let pos = *world.get::<Position>(player);
+    let target = match find_target(game, world, player, target_id) {
+        Some(target) => target,
+        None => return,
+    };
+
+    if world.has::<ItemFrame>(target) {
+        hit_item_frame(game, world, player, target);
+        return;
+    }
+
+    let charge = attack_charge(game, world, player);
+    reset_attack_cooldown(game, world, player);
+
+    let mut damage = charged_attack_damage(attack_damage(world, player), charge);
+    let fully_charged = charge > 0.9;
+    let critical = fully_charged && is_critical_hit(game, world, player);
+    if critical {
+        damage *= CRITICAL_MULTIPLIER;
+    }
+    // Enchantment damage scales linearly with charge
+    // and is not multiplied by critical hits.
+    let bonus = held_stack(world, player).map_or(0.0, |weapon| {
+        enchantment_damage_bonus(
+            weapon.enchantments(),
+            world.try_get::<CreatureAttribute>(target).map(|a| *a),
+        )
+    });
+    damage += bonus * charge;
+
+    // The target may be despawned by the damage, so remember
+    // where it was for the sweep.
+    let target_pos = *world.get::<Position>(target);
+    let sprint_hit = fully_charged && world.has::<Sprinting>(player);
+    let extra_knockback =
+        held_enchantment(world, player, Enchantment::Knockback) + sprint_hit as u32;
+
+    game.handle(
+        world,
+        EntityDamageRequest {
+            entity: target,
+            damage,
+            cause: DamageCause::EntityAttack(player),
+        },
+    );
+
+    // Extra knockback and Fire Aspect only apply if the attack hurt the target.
+    let hurt = world
+        .try_get::<LastDamage>(target)
+        .map_or(false, |last| last.tick == game.tick_count);
+    if hurt && world.is_alive(target) {
+        apply_fire_aspect(world, player, target);
+    }
+    if hurt && extra_knockback > 0 {
+        let yaw = f64::from(pos.yaw).to_radians();
+        knock_back(
+            game,
+            world,
+            target,
+            f64::from(extra_knockback) * EXTRA_KNOCKBACK,
+            yaw.sin(),
+            -yaw.cos(),
+        );
+
+        if sprint_hit {
+            set_sprinting(game, world, player, false);
+        }
+    }
+
+    if critical && world.is_alive(target) {
+        let packet = AnimationClientbound {
+            entity_id: world.get::<EntityId>(target).0,
+            animation: ClientboundAnimation::CriticalEffect,
+        };
+        game.broadcast_entity_update(world, packet, target, None);
+    }
+
+    if fully_charged && !critical && can_sweep(world, player) {
+        sweep(game, world, player, target, target_pos, damage);
+    }
+}
+
+/// Knocks the item out of an item frame hit by a player,
+/// or breaks the frame if it is empty. Creative players
+/// do not get the item or the frame back.
+fn hit_item_frame(game: &mut Game, world: &mut World, player: Entity, frame: Entity) {
+    let sound = if world.get::<ItemFrame>(frame).item.is_some() {
+        "entity.item_frame.remove_item"
+    } else {
+        "entity.item_frame.break"
+    };
+    let pos = *world.get::<Position>(frame);
+    let drops = consumes_items(*world.get::<Gamemode>(player));
+    item_frame::hit(game, world, frame, drops);
+
+    play_sound(
+        game,
+        world,
+        sound,
+        SoundCategory::Neutral,
+        pos,
+        1.0,
+        1.0,
+        None,
+    );
+}
+
+/// Returns whether an attack by the given player, if fully
+/// charged, is a critical hit. Players must be falling and
+/// not be sprinting, in water, or blinded.
+fn is_critical_hit(game: &Game, world: &World, player: Entity) -> bool {
+    let pos = *world.get::<Position>(player);
+    let falling = !pos.on_ground && pos.y < world.get::<PreviousPosition>(player).0.y;
+    let in_water = game
+        .block_at(pos.block())
+        .map_or(false, |block| block.is_water());
+    let blind = world
+        .try_get::<ActiveEffects>(player)
+        .map_or(false, |effects| effects.has(StatusEffect::Blindness));
+
+    falling && !in_water && !blind && !world.has::<Sprinting>(player)
+}
+
+/// Returns whether a player's fully charged, non-critical
+/// attack sweeps the entities around the target.
+fn can_sweep(world: &World, player: Entity) -> bool {
+    let pos = *world.get::<Position>(player);
+    let held = world
+        .get::<Inventory>(player)
+        .item_at(SLOT_HOTBAR_OFFSET + world.get::<HeldItem>(player).0)
+        .map(|stack| stack.ty);
+
+    pos.on_ground && !world.has::<Sprinting>(player) && held.map_or(false, is_sword)
+}
+
+fn is_sword(item: Item) -> bool {
+    match item {
+        Item::WoodenSword
+        | Item::StoneSword
+        | Item::IronSword
+        | Item::GoldenSword
+        | Item::DiamondSword => true,
+        _ => false,
+    }
+}
+
+/// Damages and knocks back living entities near the target
+/// of a sweep attack, then displays the sweep particle.
+///
+/// `damage` is the damage dealt to the target, of which
+/// Sweeping Edge adds a fraction to the sweep damage.
+fn sweep(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    target: Entity,
+    target_pos: Position,
+    damage: f32,
+) {
+    let sweep_damage = SWEEP_DAMAGE
+        + sweeping_damage_ratio(held_enchantment(world, player, Enchantment::SweepingEdge))
+            * damage;
+    let pos = *world.get::<Position>(player);
+    let yaw = f64::from(pos.yaw).to_radians();
+    let (dx, dz) = (yaw.sin(), -yaw.cos());
+
+    let swept: SmallVec<[Entity; 4]> = nearby_entities(
+        world,
+        game,
+        target_pos,
+        glm::vec3(SWEEP_EXTENT.0, SWEEP_EXTENT.1, SWEEP_EXTENT.0),
+    )
+    .into_iter()
+    .filter(|&entity| {
+        entity != player
+            && entity != target
+            && world.has::<Health>(entity)
+            && world.get::<Position>(entity).distance_squared_to(pos) < SWEEP_REACH * SWEEP_REACH
+    })
+    .collect();
+
+    for entity in swept {
+        knock_back(game, world, entity, SWEEP_KNOCKBACK, dx, dz);
+        game.handle(
+            world,
+            EntityDamageRequest {
+                entity,
+                damage: sweep_damage,
+                cause: DamageCause::EntityAttack(player),
+            },
+        );
+    }
+
+    let particle_pos = position!(pos.x - dx, pos.y + PLAYER_HEIGHT / 2.0, pos.z - dz);
+    game.spawn_particle(
+        world,
+        particle_pos,
+        SimpleParticle::SweepAttack,
+        1,
+        (0.0, 0.0, 0.0),
+        0.0,
+    );
+}
@@ -1,12 +1,27 @@
-use crate::{ItemTimedUse, IteratorExt};
-use feather_core::inventory::Inventory;
+use crate::book::open_book;
+use crate::bow::draw_bow;
+use crate::bucket::{is_placeable_bucket, use_bucket};
+use crate::map::use_empty_map;
+use crate::trident::draw_trident;
+use crate::{boost_with_firework, hand_slot, remove_one_item, start_eating, IteratorExt};
+use feather_core::inventory::{Inventory, SlotIndex};
 use feather_core::items::Item;
 use feather_core::network::packets::UseItem;
-use feather_core::util::Hand;
-use feather_server_types::{Game, HeldItem, Name, PacketBuffers};
+use feather_core::util::{Gamemode, Hand, Position};
+use feather_server_types::{
+    add_statistic, EntitySpawnEvent, Game, Gliding, PacketBuffers, Statistic, Velocity,
+    ENDER_PEARL_COOLDOWN_TICKS, PLAYER_EYE_HEIGHT,
+};
+use feather_server_util::{
+    armor_slot, can_interact, compute_projectile_velocity, consumes_items, equip_armor,
+    has_item_cooldown, is_food, raise_shield, start_item_cooldown,
+};
 use fecs::{Entity, World};
 use std::sync::Arc;
 
+/// Speed at which snowballs, eggs, and ender pearls are thrown.
+const THROW_FORCE: f64 = 1.5;
+
 #[fecs::system]
 pub fn handle_player_use_item(
     game: &mut Game,
@@ -21,34 +36,110 @@ pub fn handle_player_use_item(
 }
 
 fn handle_use_item(game: &mut Game, world: &mut World, player: Entity, packet: UseItem) {
+    if !can_interact(*world.get::<Gamemode>(player)) {
+        return;
+    }
+
     let hand = match packet.hand {
         0 => Hand::Main,
         _ => Hand::Off,
     };
 
-    if hand != Hand::Main {
+    let slot = hand_slot(world, player, hand);
+    let item_in_hand = world.get::<Inventory>(player).item_at(slot).cloned();
+
+    match item_in_hand.as_ref().map(|stack| stack.ty) {
+        Some(Item::Shield) => {
+            raise_shield(game, world, player, hand);
+            return;
+        }
+        Some(Item::Bow) => {
+            draw_bow(game, world, player, hand);
+            return;
+        }
+        Some(Item::Trident) => {
+            draw_trident(game, world, player, hand);
+            return;
+        }
+        Some(item) if is_food(item) => {
+            start_eating(game, world, player, hand, &item_in_hand.unwrap());
+            return;
+        }
+        Some(Item::FireworkRocket) if world.has::<Gliding>(player) => {
+            boost_with_firework(game, world, player, hand);
+            return;
+        }
+        Some(item) if is_placeable_bucket(item) => {
+            use_bucket(game, world, player, slot, &item_in_hand.unwrap());
+            return;
+        }
+        Some(Item::WrittenBook) => {
+            open_book(game, world, player, hand);
+            return;
+        }
+        Some(Item::Map) => {
+            use_empty_map(game, world, player, slot);
+            return;
+        }
+        Some(item) if armor_slot(item).is_some() => {
+            equip_armor(game, world, player, hand);
+            return;
+        }
+        _ => (),
+    }
+
+    if let Some(item_in_hand) = item_in_hand {
+        match item_in_hand.ty {
+            Item::Snowball | Item::Egg | Item::EnderPearl => {
+                throw_projectile(game, world, player, slot, item_in_hand.ty)
+            }
+            _ => (), //TODO: Handle other used items
+        }
+    }
+}
+
+/// Throws a snowball, egg, or ender pearl from the player's
+/// eyes, consuming the item unless they are in creative mode.
+fn throw_projectile(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    item: Item,
+) {
+    if has_item_cooldown(game, world, player, item) {
         return;
     }
 
-    let item_in_main_hand = world
-        .get::<Inventory>(player)
-        .item_at(world.get::<HeldItem>(player).0)
-        .copied();
-
-    if let Some(item_in_main_hand) = item_in_main_hand {
-        if item_in_main_hand.ty != Item::Bow {
-            //TODO: Handle other used items
-            return;
-        }
-        world
-            .add(
-                player,
-                ItemTimedUse {
-                    tick_start: game.tick_count,
-                },
-            )
-            .unwrap();
-        let player_name = world.get::<Name>(player);
-        log::trace!("Added ItemTimedUse to player {}.", player_name.0);
+    let pos = *world.get::<Position>(player) + glm::vec3(0.0, PLAYER_EYE_HEIGHT - 0.1, 0.0);
+    let direction = pos.direction();
+
+    let velocity = compute_projectile_velocity(
+        glm::vec3(direction.x, direction.y, direction.z),
+        THROW_FORCE,
+        1.0,
+        &mut *game.rng(),
+    );
+
+    let builder = match item {
+        Item::Snowball => entity::snowball::create(Some(player)),
+        Item::Egg => entity::egg::create(Some(player)),
+        Item::EnderPearl => entity::ender_pearl::create(Some(player)),
+        _ => unreachable!(),
+    };
+    let entity = builder
+        .with(pos)
+        .with(Velocity(velocity))
+        .build()
+        .spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity });
+    add_statistic(world, player, Statistic::Used(item), 1);
+
+    if item == Item::EnderPearl {
+        start_item_cooldown(game, world, player, item, ENDER_PEARL_COOLDOWN_TICKS);
+    }
+
+    if consumes_items(*world.get::<Gamemode>(player)) {
+        remove_one_item(game, world, player, slot);
     }
 }
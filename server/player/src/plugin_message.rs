@@ -0,0 +1,73 @@
+//! Handling of plugin channel messages from clients.
+
+use crate::IteratorExt;
+use feather_core::network::packets::PluginMessageServerbound;
+use feather_server_types::{
+    ClientBrand, Game, PacketBuffers, PlayerJoinEvent, PluginChannels, PluginMessageEvent,
+};
+use feather_server_util::{
+    decode_plugin_string, encode_plugin_string, parse_channel_list, send_plugin_message,
+    CHANNEL_BRAND, CHANNEL_REGISTER, CHANNEL_UNREGISTER,
+};
+use fecs::World;
+use std::sync::Arc;
+
+/// Brand shown in the client's debug screen.
+const SERVER_BRAND: &str = "Feather";
+
+/// Sends the server brand to joining players.
+#[fecs::event_handler]
+pub fn on_player_join_send_brand(event: &PlayerJoinEvent, world: &mut World) {
+    send_plugin_message(
+        world,
+        event.player,
+        CHANNEL_BRAND,
+        encode_plugin_string(SERVER_BRAND),
+    );
+}
+
+/// Handles plugin messages. Channel registrations and
+/// brands are tracked on the player; other messages
+/// trigger a `PluginMessageEvent`.
+#[fecs::system]
+pub fn handle_plugin_message(
+    game: &mut Game,
+    world: &mut World,
+    packet_buffers: &Arc<PacketBuffers>,
+) {
+    packet_buffers
+        .received::<PluginMessageServerbound>()
+        .for_each_valid(world, |world, (player, packet)| {
+            let PluginMessageServerbound { channel, data } = packet;
+            match channel.as_str() {
+                CHANNEL_REGISTER => {
+                    let mut channels = world.get_mut::<PluginChannels>(player);
+                    channels.0.extend(parse_channel_list(&data));
+                }
+                CHANNEL_UNREGISTER => {
+                    let mut channels = world.get_mut::<PluginChannels>(player);
+                    for channel in parse_channel_list(&data) {
+                        channels.0.remove(&channel);
+                    }
+                }
+                CHANNEL_BRAND => {
+                    if let Some(brand) = decode_plugin_string(&data) {
+                        log::debug!("Player {:?} has client brand {}", player, brand);
+                        if world.has::<ClientBrand>(player) {
+                            world.get_mut::<ClientBrand>(player).0 = brand;
+                        } else {
+                            world.add(player, ClientBrand(brand)).unwrap();
+                        }
+                    }
+                }
+                _ => game.handle(
+                    world,
+                    PluginMessageEvent {
+                        player,
+                        channel: channel.clone(),
+                        data: data.to_vec(),
+                    },
+                ),
+            }
+        });
+}
@@ -0,0 +1,304 @@
+//! The recipe book, which lists the recipes a player has unlocked
+//! and fills their crafting grid with the ingredients of a recipe
+//! they click.
+//!
+//! Recipes unlock once the player first obtains one of their
+//! ingredients. The unlocked recipes are saved in player data and
+//! sent to the client when the player joins.
+
+use crate::{update_crafting_output, IteratorExt, Window, CRAFTING_GRID_WIDTH};
+use feather_core::inventory::{
+    max_size, Inventory, SlotIndex, SLOT_CRAFTING_INPUT_X0_Y0, SLOT_CRAFTING_OUTPUT,
+    SLOT_INVENTORY_OFFSET, SLOT_OFFHAND,
+};
+use feather_core::network::packets::{
+    CraftRecipeRequest, CraftRecipeResponse, RecipeBookData, RecipeBookDataType, UnlockRecipes,
+    UnlockRecipesAction,
+};
+use feather_server_types::{
+    Game, Ingredient, InventoryUpdateEvent, Network, PacketBuffers, PlayerJoinEvent, RecipeBook,
+    RecipeRegistry,
+};
+use fecs::{Entity, World};
+use smallvec::SmallVec;
+use std::sync::Arc;
+
+/// Number of slots in the crafting grid.
+const GRID_SLOTS: usize = CRAFTING_GRID_WIDTH * CRAFTING_GRID_WIDTH;
+
+fn send_unlock_recipes(
+    world: &World,
+    player: Entity,
+    action: UnlockRecipesAction,
+    recipe_ids: Vec<String>,
+) {
+    let book = world.get::<RecipeBook>(player);
+    let displayed_recipe_ids = match action {
+        UnlockRecipesAction::Init => book.to_be_displayed.iter().cloned().collect(),
+        _ => vec![],
+    };
+
+    world.get::<Network>(player).send(UnlockRecipes {
+        action,
+        crafting_book_open: book.crafting_book_open,
+        crafting_filter_active: book.crafting_filter_active,
+        smelting_book_open: book.smelting_book_open,
+        smelting_filter_active: book.smelting_filter_active,
+        recipe_ids,
+        displayed_recipe_ids,
+    });
+}
+
+/// Sends joining players the server's recipes and those they have unlocked.
+#[fecs::event_handler]
+pub fn on_player_join_send_recipe_book(
+    event: &PlayerJoinEvent,
+    world: &mut World,
+    recipes: &RecipeRegistry,
+) {
+    world
+        .get::<Network>(event.player)
+        .send(recipes.declare_packet());
+
+    let unlocked = world
+        .get::<RecipeBook>(event.player)
+        .unlocked
+        .iter()
+        .cloned()
+        .collect();
+    send_unlock_recipes(world, event.player, UnlockRecipesAction::Init, unlocked);
+}
+
+/// Unlocks the recipes using the items a player obtains.
+#[fecs::event_handler]
+pub fn on_inventory_update_unlock_recipes(
+    event: &InventoryUpdateEvent,
+    world: &mut World,
+    recipes: &RecipeRegistry,
+) {
+    if !world.has::<RecipeBook>(event.player) {
+        return;
+    }
+
+    let unlocked: Vec<String> = {
+        let inventory = world.get::<Inventory>(event.player);
+        let mut book = world.get_mut::<RecipeBook>(event.player);
+
+        event
+            .slots
+            .iter()
+            .filter_map(|slot| inventory.item_at(*slot))
+            .flat_map(|stack| recipes.using(stack.ty))
+            .filter(|recipe| recipe.in_recipe_book())
+            .filter(|recipe| book.unlock(&recipe.id))
+            .map(|recipe| recipe.id.clone())
+            .collect()
+    };
+
+    if !unlocked.is_empty() {
+        send_unlock_recipes(world, event.player, UnlockRecipesAction::Add, unlocked);
+    }
+}
+
+/// System which updates players' recipe books when they
+/// view a recipe or open, close or filter the book.
+#[fecs::system]
+pub fn handle_recipe_book_data(world: &mut World, packet_buffers: &Arc<PacketBuffers>) {
+    packet_buffers
+        .received::<RecipeBookData>()
+        .for_each_valid(world, |world, (player, packet)| {
+            let mut book = world.get_mut::<RecipeBook>(player);
+            match packet.ty {
+                RecipeBookDataType::DisplayedRecipe(id) => {
+                    book.to_be_displayed.remove(&id);
+                }
+                RecipeBookDataType::States {
+                    crafting_book_open,
+                    crafting_filter_active,
+                    smelting_book_open,
+                    smelting_filter_active,
+                } => {
+                    book.crafting_book_open = crafting_book_open;
+                    book.crafting_filter_active = crafting_filter_active;
+                    book.smelting_book_open = smelting_book_open;
+                    book.smelting_filter_active = smelting_filter_active;
+                }
+            }
+        });
+}
+
+/// System which fills players' crafting grids with the
+/// ingredients of recipes they click in the recipe book.
+#[fecs::system]
+pub fn handle_craft_recipe_request(
+    game: &mut Game,
+    world: &mut World,
+    packet_buffers: &Arc<PacketBuffers>,
+    recipes: &RecipeRegistry,
+) {
+    packet_buffers
+        .received::<CraftRecipeRequest>()
+        .for_each_valid(world, |world, (player, packet)| {
+            handle_request(game, world, recipes, player, packet)
+        });
+}
+
+fn handle_request(
+    game: &mut Game,
+    world: &mut World,
+    recipes: &RecipeRegistry,
+    player: Entity,
+    packet: CraftRecipeRequest,
+) {
+    // Only the player's own inventory has a crafting grid.
+    if packet.window_id != 0 || world.get::<Window>(player).id != 0 {
+        return;
+    }
+    if !world.get::<RecipeBook>(player).is_unlocked(&packet.recipe) {
+        log::debug!(
+            "Player {:?} requested locked recipe {}",
+            player,
+            packet.recipe
+        );
+        return;
+    }
+    let layout = match recipes
+        .get(&packet.recipe)
+        .and_then(|recipe| recipe.grid_layout(CRAFTING_GRID_WIDTH, CRAFTING_GRID_WIDTH))
+    {
+        Some(layout) => layout,
+        None => return,
+    };
+
+    let mut changed: SmallVec<[SlotIndex; 2]> = SmallVec::new();
+    let crafts = {
+        let mut inventory = world.get_mut::<Inventory>(player);
+        let crafts = if clear_grid(&mut inventory, &mut changed) {
+            let max_crafts = if packet.make_all { u8::max_value() } else { 1 };
+            (0..max_crafts)
+                .take_while(|_| place_ingredients(&mut inventory, &layout, &mut changed))
+                .count()
+        } else {
+            0
+        };
+        if update_crafting_output(&mut inventory, recipes) {
+            changed.push(SLOT_CRAFTING_OUTPUT);
+        }
+        crafts
+    };
+
+    if crafts == 0 {
+        // Show the player which items are missing.
+        world.get::<Network>(player).send(CraftRecipeResponse {
+            window_id: packet.window_id,
+            recipe: packet.recipe,
+        });
+    }
+
+    if !changed.is_empty() {
+        game.handle(
+            world,
+            InventoryUpdateEvent {
+                slots: changed,
+                player,
+            },
+        );
+    }
+}
+
+/// Returns the items in the crafting grid to the rest of the inventory.
+/// Returns whether the grid is empty afterwards.
+fn clear_grid(inventory: &mut Inventory, changed: &mut SmallVec<[SlotIndex; 2]>) -> bool {
+    let mut cleared = true;
+    for slot in grid_slots() {
+        if let Some(mut stack) = inventory.clear_item_at(slot) {
+            changed.push(slot);
+            let (slots, remaining) = inventory.collect_item(stack.clone());
+            changed.extend(slots);
+            if remaining > 0 {
+                stack.amount = remaining;
+                inventory.set_item_at(slot, stack);
+                cleared = false;
+            }
+        }
+    }
+    cleared
+}
+
+/// Moves one of each ingredient from the inventory into the crafting
+/// grid. Returns whether every ingredient was placed; if not, the
+/// inventory is left unchanged.
+fn place_ingredients(
+    inventory: &mut Inventory,
+    layout: &[Option<&Ingredient>],
+    changed: &mut SmallVec<[SlotIndex; 2]>,
+) -> bool {
+    let mut moved: SmallVec<[(SlotIndex, SlotIndex); GRID_SLOTS]> = SmallVec::new();
+
+    for (grid_slot, ingredient) in grid_slots().zip(layout) {
+        let ingredient = match ingredient {
+            Some(ingredient) => ingredient,
+            None => continue,
+        };
+
+        let in_grid = inventory.item_at(grid_slot).cloned();
+        if in_grid
+            .as_ref()
+            .map_or(false, |stack| stack.amount >= max_size(stack.ty))
+        {
+            undo_moves(inventory, &moved);
+            return false;
+        }
+
+        let source = (SLOT_INVENTORY_OFFSET..SLOT_OFFHAND).find(|&slot| {
+            inventory.item_at(slot).map_or(false, |stack| {
+                ingredient.matches(stack.ty)
+                    && in_grid
+                        .as_ref()
+                        .map_or(true, |grid| grid.stacks_with(stack))
+            })
+        });
+        let source = match source {
+            Some(source) => source,
+            None => {
+                undo_moves(inventory, &moved);
+                return false;
+            }
+        };
+
+        move_one(inventory, source, grid_slot);
+        moved.push((source, grid_slot));
+    }
+
+    for (source, grid_slot) in moved {
+        for slot in [source, grid_slot].iter() {
+            if !changed.contains(slot) {
+                changed.push(*slot);
+            }
+        }
+    }
+    true
+}
+
+fn undo_moves(inventory: &mut Inventory, moved: &[(SlotIndex, SlotIndex)]) {
+    for &(source, grid_slot) in moved.iter().rev() {
+        move_one(inventory, grid_slot, source);
+    }
+}
+
+/// Moves a single item from one slot to another, which must
+/// be empty or hold a stack it can merge with.
+fn move_one(inventory: &mut Inventory, from: SlotIndex, to: SlotIndex) {
+    let mut stack = inventory.item_at(from).unwrap().clone();
+    let amount = inventory.item_at(to).map_or(0, |stack| stack.amount);
+
+    stack.amount -= 1;
+    inventory.set_item_at(from, stack.clone());
+
+    stack.amount = amount + 1;
+    inventory.set_item_at(to, stack);
+}
+
+fn grid_slots() -> impl Iterator<Item = SlotIndex> {
+    SLOT_CRAFTING_INPUT_X0_Y0..SLOT_CRAFTING_INPUT_X0_Y0 + GRID_SLOTS
+}
@@ -0,0 +1,112 @@
+//! Prompting players to use the server resource pack.
+
+use crate::IteratorExt;
+use feather_core::network::packets::{ResourcePackSend, ResourcePackStatus};
+use feather_server_types::{
+    Game, Network, PacketBuffers, PlayerJoinEvent, ResourcePackResponse, ResourcePackResponseEvent,
+};
+use fecs::World;
+use std::sync::Arc;
+
+/// Sends the server resource pack, if there is one, to joining players.
+#[fecs::event_handler]
+pub fn on_player_join_send_resource_pack(event: &PlayerJoinEvent, game: &Game, world: &mut World) {
+    let resource_pack = &game.config.resource_pack;
+    if resource_pack.url.is_empty() {
+        return;
+    }
+
+    world.get::<Network>(event.player).send(ResourcePackSend {
+        url: resource_pack.url.clone(),
+        // The client only accepts lowercase hashes.
+        hash: resource_pack.hash.to_lowercase(),
+    });
+}
+
+/// Handles players' responses to the resource pack.
+#[fecs::system]
+pub fn handle_resource_pack_status(
+    game: &mut Game,
+    world: &mut World,
+    packet_buffers: &Arc<PacketBuffers>,
+) {
+    packet_buffers
+        .received::<ResourcePackStatus>()
+        .for_each_valid(world, |world, (player, packet)| {
+            let response = match packet.result {
+                0 => ResourcePackResponse::Loaded,
+                1 => ResourcePackResponse::Declined,
+                2 => ResourcePackResponse::FailedDownload,
+                3 => ResourcePackResponse::Accepted,
+                result => {
+                    log::trace!("Unknown resource pack status {}", result);
+                    return;
+                }
+            };
+
+            if world.has::<ResourcePackResponse>(player) {
+                *world.get_mut::<ResourcePackResponse>(player) = response;
+            } else {
+                world.add(player, response).unwrap();
+            }
+            game.handle(world, ResourcePackResponseEvent { player, response });
+        });
+}
+
+/// Kicks players who will not use the resource pack
+/// when it is required.
+#[fecs::event_handler]
+pub fn on_resource_pack_response_kick_if_required(
+    event: &ResourcePackResponseEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    let refused = match event.response {
+        ResourcePackResponse::Declined | ResourcePackResponse::FailedDownload => true,
+        ResourcePackResponse::Loaded | ResourcePackResponse::Accepted => false,
+    };
+    if !refused || !game.config.resource_pack.required {
+        return;
+    }
+
+    game.kick(
+        event.player,
+        world,
+        "This server requires its resource pack.",
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::position;
+    use feather_test_framework::Test;
+    use fecs::Entity;
+
+    fn decline(test: &mut Test, required: bool) -> Entity {
+        Arc::make_mut(&mut test.game.config).resource_pack.required = required;
+        let player = test.player("player", position!(0.0, 64.0, 0.0));
+        test.handle(
+            ResourcePackResponseEvent {
+                player,
+                response: ResourcePackResponse::Declined,
+            },
+            on_resource_pack_response_kick_if_required,
+        );
+        player
+    }
+
+    #[test]
+    fn declining_required_pack_kicks() {
+        let mut test = Test::new();
+        let player = decline(&mut test, true);
+        test.assert_disconnected(player).assert_dead(player);
+    }
+
+    #[test]
+    fn declining_optional_pack_is_allowed() {
+        let mut test = Test::new();
+        let player = decline(&mut test, false);
+        test.assert_alive(player);
+    }
+}
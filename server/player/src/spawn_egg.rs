@@ -0,0 +1,117 @@
+//! Spawn eggs, which players use on blocks to spawn a mob
+//! or to change the mob spawned by a spawner.
+
+use crate::remove_one_item;
+use entity::{create_by_id, spawn_egg_mob, with_entity_tag};
+use feather_core::anvil::block_entity::{BlockEntityData, SpawnerData};
+use feather_core::blocks::BlockKind;
+use feather_core::inventory::SlotIndex;
+use feather_core::items::ItemStack;
+use feather_core::network::packets::{Face, UpdateBlockEntity};
+use feather_core::position;
+use feather_core::util::{BlockPosition, Gamemode};
+use feather_server_types::{ChunkSendEvent, EntityCategory, EntitySpawnEvent, Game, Network};
+use feather_server_util::{can_spawn_in_chunk, consumes_items};
+use fecs::{Entity, World};
+use rand::Rng;
+
+/// Update Block Entity action which sets the mob of a spawner.
+const ACTION_SET_SPAWNER_MOB: u8 = 1;
+
+/// Uses the spawn egg in `slot` on the face of a block.
+pub fn use_spawn_egg(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    slot: SlotIndex,
+    stack: &ItemStack,
+    location: BlockPosition,
+    face: Face,
+) {
+    let block = match game.block_at(location) {
+        Some(block) => block,
+        None => return,
+    };
+
+    // An `id` in the entity tag replaces the egg's own mob.
+    let mob = match stack.entity_tag().and_then(|tag| tag.id.as_deref()) {
+        Some(id) => id,
+        None => match spawn_egg_mob(stack.ty) {
+            Some(id) => id,
+            None => return,
+        },
+    };
+    let builder = match create_by_id(mob) {
+        Some(builder) => builder,
+        None => return,
+    };
+
+    if block.kind() == BlockKind::Spawner {
+        // Spawners keep their other settings when their mob changes.
+        let mut spawner = match game.block_entities.get(location) {
+            Some(BlockEntityData::MobSpawner(spawner)) => spawner.clone(),
+            _ => SpawnerData::new(location, mob),
+        };
+        spawner.spawn_data.id = mob.to_owned();
+        let spawner = BlockEntityData::MobSpawner(spawner);
+
+        game.broadcast_chunk_update(
+            world,
+            UpdateBlockEntity::new(location, ACTION_SET_SPAWNER_MOB, &spawner),
+            location.chunk(),
+            None,
+        );
+        game.block_entities.insert(spawner);
+    } else {
+        // Mobs spawn in blocks they can stand in, such as grass,
+        // and otherwise next to the clicked face.
+        let pos = if block.is_solid() {
+            location + face.placement_offset()
+        } else {
+            location
+        };
+        let yaw = game.rng().gen_range(0.0, 360.0);
+        let pos = position!(
+            pos.x as f64 + 0.5,
+            pos.y as f64,
+            pos.z as f64 + 0.5,
+            0.0,
+            yaw
+        );
+        if !can_spawn_in_chunk(game, world, pos.chunk(), EntityCategory::Mob) {
+            return;
+        }
+
+        let mut tag = stack.entity_tag().cloned().unwrap_or_default();
+        if let Some(name) = stack.display_name() {
+            tag.custom_name = Some(name.to_owned());
+        }
+
+        let entity = with_entity_tag(builder, &tag)
+            .with(pos)
+            .build()
+            .spawn_in(world);
+        game.handle(world, EntitySpawnEvent { entity });
+    }
+
+    if consumes_items(*world.get::<Gamemode>(player)) {
+        remove_one_item(game, world, player, slot);
+    }
+}
+
+/// Sends the mobs of the spawners in a chunk to a player who
+/// was sent the chunk, since chunk data leaves them out.
+#[fecs::event_handler]
+pub fn on_chunk_send_send_spawners(event: &ChunkSendEvent, game: &Game, world: &mut World) {
+    for spawner in game.block_entities.in_chunk(event.chunk) {
+        if let BlockEntityData::MobSpawner(data) = &spawner {
+            world
+                .get::<Network>(event.player)
+                .send(UpdateBlockEntity::new(
+                    data.position(),
+                    ACTION_SET_SPAWNER_MOB,
+                    &spawner,
+                ));
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Spectator mode, in which players fly through blocks and may
+//! attach their camera to other entities by attacking them.
+//!
+//! Clients hide spectators from players in other gamemodes once
+//! the player list reports their gamemode, and spectators adjust
+//! their flying speed by scrolling without telling the server.
+//! The server stops spectators from changing the world and keeps
+//! the position of those watching an entity in sync with it.
+
+use feather_core::network::packets::Camera;
+use feather_core::util::{Gamemode, Position};
+use feather_server_types::{
+    BumpVec, EntityDespawnEvent, EntityId, Game, GamemodeUpdateEvent, Network, Player,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+
+/// Component for spectators whose camera is attached to another entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spectating(pub Entity);
+
+/// Returns whether a player is in spectator mode.
+pub fn is_spectator(world: &World, player: Entity) -> bool {
+    world
+        .try_get::<Gamemode>(player)
+        .map_or(false, |gamemode| *gamemode == Gamemode::Spectator)
+}
+
+fn send_camera(world: &World, player: Entity, camera: Entity) {
+    world.get::<Network>(player).send(Camera {
+        camera_id: world.get::<EntityId>(camera).0,
+    });
+}
+
+/// Attaches a spectator's camera to the given entity.
+pub fn start_spectating(world: &mut World, player: Entity, target: Entity) {
+    if !is_spectator(world, player) || player == target {
+        return;
+    }
+
+    if world.has::<Spectating>(player) {
+        world.remove::<Spectating>(player).unwrap();
+    }
+    world.add(player, Spectating(target)).unwrap();
+    send_camera(world, player, target);
+}
+
+/// Returns a spectator's camera to their own position.
+pub fn stop_spectating(world: &mut World, player: Entity) {
+    if world.remove::<Spectating>(player).is_err() {
+        return;
+    }
+
+    send_camera(world, player, player);
+}
+
+/// System which moves spectators along with the entity they
+/// spectate, so that the chunks and entities around it are sent.
+#[fecs::system]
+pub fn update_spectating(game: &mut Game, world: &mut World) {
+    let mut lost = BumpVec::new_in(game.bump());
+    let mut moved = BumpVec::new_in(game.bump());
+
+    for (player, spectating) in <Read<Spectating>>::query()
+        .filter(component::<Player>())
+        .iter_entities(world.inner())
+    {
+        match world.try_get::<Position>(spectating.0) {
+            Some(pos) if world.is_alive(spectating.0) => moved.push((player, *pos)),
+            _ => lost.push(player),
+        }
+    }
+
+    for player in lost {
+        stop_spectating(world, player);
+    }
+
+    for (player, pos) in moved {
+        *world.get_mut::<Position>(player) = pos;
+    }
+}
+
+/// Returns players' cameras to themselves when they leave spectator mode.
+#[fecs::event_handler]
+pub fn on_gamemode_update_stop_spectating(event: &GamemodeUpdateEvent, world: &mut World) {
+    if event.old == Gamemode::Spectator {
+        stop_spectating(world, event.player);
+    }
+}
+
+/// Returns the cameras of players spectating a despawned entity.
+#[fecs::event_handler]
+pub fn on_entity_despawn_stop_spectating(
+    event: &EntityDespawnEvent,
+    game: &mut Game,
+    world: &mut World,
+) {
+    let mut spectators = BumpVec::new_in(game.bump());
+    for (player, spectating) in <Read<Spectating>>::query().iter_entities(world.inner()) {
+        if spectating.0 == event.entity {
+            spectators.push(player);
+        }
+    }
+
+    for player in spectators {
+        stop_spectating(world, player);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::position;
+    use feather_test_framework::Test;
+    use fecs::EntityBuilder;
+
+    fn spectator(test: &mut Test) -> Entity {
+        let player = test.player("spectator", position!(0.0, 64.0, 0.0));
+        *test.world.get_mut::<Gamemode>(player) = Gamemode::Spectator;
+        player
+    }
+
+    fn camera(test: &mut Test, player: Entity) -> Option<i32> {
+        test.sent::<Camera>(player).map(|camera| camera.camera_id)
+    }
+
+    #[test]
+    fn only_spectators_attach_camera() {
+        let mut test = Test::new();
+        let player = test.player("player", position!(0.0, 64.0, 0.0));
+        let target = test.entity(
+            EntityBuilder::new()
+                .with(EntityId(100))
+                .with(position!(5.0, 64.0, 0.0)),
+        );
+
+        start_spectating(&mut test.world, player, target);
+        assert!(!test.world.has::<Spectating>(player));
+        assert_eq!(camera(&mut test, player), None);
+
+        let spectator = spectator(&mut test);
+        start_spectating(&mut test.world, spectator, spectator);
+        assert!(!test.world.has::<Spectating>(spectator));
+
+        start_spectating(&mut test.world, spectator, target);
+        assert_eq!(*test.world.get::<Spectating>(spectator), Spectating(target));
+        assert_eq!(camera(&mut test, spectator), Some(100));
+    }
+
+    #[test]
+    fn spectators_follow_target_until_it_despawns() {
+        let mut test = Test::new();
+        let spectator = spectator(&mut test);
+        let target = test.entity(
+            EntityBuilder::new()
+                .with(EntityId(100))
+                .with(position!(5.0, 70.0, 3.0)),
+        );
+        start_spectating(&mut test.world, spectator, target);
+        test.clear_sent(spectator);
+
+        test.run(update_spectating);
+        assert_eq!(
+            *test.world.get::<Position>(spectator),
+            position!(5.0, 70.0, 3.0)
+        );
+
+        test.handle(
+            EntityDespawnEvent { entity: target },
+            on_entity_despawn_stop_spectating,
+        );
+        assert!(!test.world.has::<Spectating>(spectator));
+        let own_id = test.id(spectator);
+        assert_eq!(camera(&mut test, spectator), Some(own_id));
+    }
+
+    #[test]
+    fn leaving_spectator_mode_resets_camera() {
+        let mut test = Test::new();
+        let spectator = spectator(&mut test);
+        let target = test.player("target", position!(5.0, 64.0, 0.0));
+        start_spectating(&mut test.world, spectator, target);
+        test.clear_sent(spectator);
+
+        *test.world.get_mut::<Gamemode>(spectator) = Gamemode::Survival;
+        test.handle(
+            GamemodeUpdateEvent {
+                player: spectator,
+                old: Gamemode::Spectator,
+                new: Gamemode::Survival,
+            },
+            on_gamemode_update_stop_spectating,
+        );
+        assert!(!test.world.has::<Spectating>(spectator));
+        let own_id = test.id(spectator);
+        assert_eq!(camera(&mut test, spectator), Some(own_id));
+    }
+}
@@ -0,0 +1,219 @@
+//! Tracking of player statistics during gameplay, and sending
+//! them to clients which open the statistics menu.
+//!
+//! Statistics of blocks mined and items used are added where
+//! the blocks are broken and the items used.
+
+use entity::MobKind;
+use feather_core::blocks::BlockId;
+use feather_core::items::Item;
+use feather_core::network::packets;
+use feather_core::util::Position;
+use feather_server_types::{
+    add_statistic, BumpVec, CustomStatistic, EntityDeathEvent, EntityType, Flying, Game, Gliding,
+    Health, Network, Player, PreviousPosition, Sneaking, Sprinting, Statistic, StatisticCategory,
+    Statistics, Swimming,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+
+/// Entity type ID of players, which have no `MobKind`.
+const PLAYER_ENTITY_TYPE_ID: i32 = 92;
+
+/// Movements further than this in one tick, in blocks,
+/// are teleports and do not count towards distances.
+const MAX_TRACKED_MOVEMENT: f64 = 10.0;
+
+/// Sends a player their statistics.
+pub fn send_statistics(world: &World, player: Entity) {
+    let statistics = world
+        .get::<Statistics>(player)
+        .iter()
+        .filter_map(|(category, stat, value)| {
+            let category = StatisticCategory::from_identifier(category)?;
+            let id = statistic_id(category, stat)?;
+            Some((category.id(), id, value))
+        })
+        .collect();
+    world
+        .get::<Network>(player)
+        .send(packets::Statistics { statistics });
+}
+
+/// Returns the protocol ID of the block, item, entity type
+/// or custom statistic counted by a statistic, or `None`
+/// if it is unknown, in which case it is not sent.
+fn statistic_id(category: StatisticCategory, identifier: &str) -> Option<i32> {
+    match category {
+        StatisticCategory::Mined => {
+            BlockId::from_identifier(identifier).map(|block| block.kind() as i32)
+        }
+        StatisticCategory::Crafted
+        | StatisticCategory::Used
+        | StatisticCategory::Broken
+        | StatisticCategory::PickedUp
+        | StatisticCategory::Dropped => {
+            Item::from_identifier(identifier).map(Item::native_protocol_id)
+        }
+        StatisticCategory::Killed | StatisticCategory::KilledBy => {
+            if identifier == "minecraft:player" {
+                Some(PLAYER_ENTITY_TYPE_ID)
+            } else {
+                MobKind::from_identifier(identifier).map(|kind| kind as i32)
+            }
+        }
+        StatisticCategory::Custom => {
+            CustomStatistic::from_identifier(identifier).map(CustomStatistic::id)
+        }
+    }
+}
+
+/// System which counts living players' play time, time
+/// since death, time spent sneaking, and distances moved.
+///
+/// Must run before positions are copied into `PreviousPosition`.
+#[fecs::system]
+pub fn update_statistics(game: &mut Game, world: &mut World) {
+    let mut updates = BumpVec::new_in(game.bump());
+
+    for (player, (pos, previous_pos, health)) in
+        <(Read<Position>, Read<PreviousPosition>, Read<Health>)>::query()
+            .filter(component::<Statistics>())
+            .iter_entities(world.inner())
+    {
+        if health.0 <= 0.0 {
+            continue;
+        }
+        let movement = movement_statistic(world, player, previous_pos.0, *pos);
+        updates.push((player, world.has::<Sneaking>(player), movement));
+    }
+
+    for (player, sneaking, movement) in updates {
+        let mut statistics = world.get_mut::<Statistics>(player);
+        statistics.add(Statistic::Custom(CustomStatistic::PlayOneMinute), 1);
+        statistics.add(Statistic::Custom(CustomStatistic::TimeSinceDeath), 1);
+        if sneaking {
+            statistics.add(Statistic::Custom(CustomStatistic::SneakTime), 1);
+        }
+        if let Some((stat, cm)) = movement {
+            statistics.add(Statistic::Custom(stat), cm);
+        }
+    }
+}
+
+/// Returns the statistic counting a player's movement
+/// during the last tick and the distance in centimeters.
+fn movement_statistic(
+    world: &World,
+    player: Entity,
+    from: Position,
+    to: Position,
+) -> Option<(CustomStatistic, i32)> {
+    let dx = to.x - from.x;
+    let dy = to.y - from.y;
+    let dz = to.z - from.z;
+    let horizontal = (dx * dx + dz * dz).sqrt();
+    let distance = (horizontal * horizontal + dy * dy).sqrt();
+    if distance > MAX_TRACKED_MOVEMENT {
+        return None;
+    }
+
+    let (stat, distance) = if world.has::<Swimming>(player) {
+        (CustomStatistic::SwimOneCm, distance)
+    } else if world.has::<Gliding>(player) {
+        (CustomStatistic::AviateOneCm, distance)
+    } else if to.on_ground {
+        let stat = if world.has::<Sprinting>(player) {
+            CustomStatistic::SprintOneCm
+        } else if world.has::<Sneaking>(player) {
+            CustomStatistic::CrouchOneCm
+        } else {
+            CustomStatistic::WalkOneCm
+        };
+        (stat, horizontal)
+    } else if world.has::<Flying>(player) {
+        (CustomStatistic::FlyOneCm, horizontal)
+    } else if dy < 0.0 {
+        (CustomStatistic::FallOneCm, -dy)
+    } else {
+        return None;
+    };
+
+    let cm = (distance * 100.0).round() as i32;
+    if cm > 0 {
+        Some((stat, cm))
+    } else {
+        None
+    }
+}
+
+/// Counts deaths, and kills by players.
+#[fecs::event_handler]
+pub fn on_entity_death_update_statistics(event: &EntityDeathEvent, world: &mut World) {
+    let victim = event.entity;
+    if world.has::<Statistics>(victim) {
+        let mut statistics = world.get_mut::<Statistics>(victim);
+        statistics.add(Statistic::Custom(CustomStatistic::Deaths), 1);
+        statistics.set(Statistic::Custom(CustomStatistic::TimeSinceDeath), 0);
+    }
+
+    let killer = match event.cause.attacker() {
+        Some(killer) if world.is_alive(killer) => killer,
+        _ => return,
+    };
+    if let Some(killer_type) = entity_type(world, killer) {
+        add_statistic(world, victim, Statistic::KilledBy(killer_type), 1);
+    }
+
+    let kills = if world.has::<Player>(victim) {
+        CustomStatistic::PlayerKills
+    } else {
+        CustomStatistic::MobKills
+    };
+    add_statistic(world, killer, Statistic::Custom(kills), 1);
+    if let Some(victim_type) = entity_type(world, victim) {
+        add_statistic(world, killer, Statistic::Killed(victim_type), 1);
+    }
+}
+
+/// Returns the identifier of an entity's type, if it has one.
+pub(crate) fn entity_type(world: &World, entity: Entity) -> Option<&'static str> {
+    if world.has::<Player>(entity) {
+        Some("minecraft:player")
+    } else {
+        world.try_get::<EntityType>(entity).map(|ty| ty.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::blocks::BlockKind;
+
+    #[test]
+    fn statistic_ids() {
+        assert_eq!(
+            statistic_id(StatisticCategory::Mined, "minecraft:stone"),
+            Some(BlockKind::Stone as i32)
+        );
+        assert_eq!(
+            statistic_id(StatisticCategory::Used, "minecraft:bow"),
+            Some(Item::Bow.native_protocol_id())
+        );
+        assert_eq!(
+            statistic_id(StatisticCategory::Killed, "minecraft:zombie"),
+            Some(87)
+        );
+        assert_eq!(
+            statistic_id(StatisticCategory::KilledBy, "minecraft:player"),
+            Some(PLAYER_ENTITY_TYPE_ID)
+        );
+        assert_eq!(
+            statistic_id(StatisticCategory::Custom, "minecraft:mob_kills"),
+            Some(29)
+        );
+        assert_eq!(
+            statistic_id(StatisticCategory::Custom, "minecraft:time_since_rest"),
+            None
+        );
+    }
+}
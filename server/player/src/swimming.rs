@@ -0,0 +1,129 @@
+//! Swimming, which players start by sprinting underwater.
+//!
+//! The swimming pose is decided by the server from the player's
+//! sprint state and the blocks around them, so players cannot keep
+//! the small bounding box of the pose after leaving water.
+
+use feather_core::entitymeta::{EntityBitMask, EntityMetadata};
+use feather_core::util::Position;
+use feather_server_types::{player_eye_height, BumpVec, Game, Player, Sprinting, Swimming};
+use fecs::{component, IntoQuery, Read, World};
+
+/// System which starts and stops players swimming.
+#[fecs::system]
+pub fn update_swimming(game: &mut Game, world: &mut World) {
+    let mut changes = BumpVec::new_in(game.bump());
+
+    for (player, pos) in <Read<Position>>::query()
+        .filter(component::<Player>())
+        .iter_entities(world.inner())
+    {
+        let is_water = |y: f64| {
+            let pos = *pos + glm::vec3(0.0, y, 0.0);
+            game.block_at(pos.block())
+                .map_or(false, |block| block.is_water())
+        };
+
+        let swimming = world.has::<Swimming>(player);
+        let sprinting = world.has::<Sprinting>(player);
+        let in_water = is_water(0.0);
+
+        let should_swim = if swimming {
+            sprinting && in_water
+        } else {
+            // Players must be fully submerged to start swimming.
+            sprinting && in_water && is_water(player_eye_height(world, player))
+        };
+
+        if should_swim != swimming {
+            changes.push((player, should_swim));
+        }
+    }
+
+    for (player, swimming) in changes {
+        if swimming {
+            world.add(player, Swimming).unwrap();
+        } else {
+            world.remove::<Swimming>(player).unwrap();
+        }
+
+        world
+            .get_mut::<EntityMetadata>(player)
+            .set_flag(EntityBitMask::SWIMMING, swimming);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::blocks::BlockId;
+    use feather_core::chunk::Chunk;
+    use feather_core::position;
+    use feather_core::util::{BlockPosition, ChunkPosition};
+    use feather_test_framework::Test;
+    use fecs::Entity;
+
+    /// Returns a test with water at the given heights
+    /// of the column in which players are created.
+    fn water(heights: &[i32]) -> Test {
+        let mut test = Test::new();
+        test.game
+            .chunk_map
+            .insert(Chunk::new(ChunkPosition::new(0, 0)));
+        for &y in heights {
+            test.game
+                .chunk_map
+                .set_block_at(BlockPosition::new(0, y, 0), BlockId::water());
+        }
+        test
+    }
+
+    fn swimming(test: &Test, player: Entity) -> bool {
+        let swimming = test.world.has::<Swimming>(player);
+        assert_eq!(
+            test.world
+                .get::<EntityMetadata>(player)
+                .flags()
+                .contains(EntityBitMask::SWIMMING),
+            swimming
+        );
+        swimming
+    }
+
+    #[test]
+    fn sprinting_underwater_starts_swimming() {
+        let mut test = water(&[64, 65]);
+        let player = test.player("swimmer", position!(0.5, 64.0, 0.5));
+
+        test.run(update_swimming);
+        assert!(!swimming(&test, player));
+
+        test.world.add(player, Sprinting).unwrap();
+        test.run(update_swimming);
+        assert!(swimming(&test, player));
+
+        test.world.remove::<Sprinting>(player).unwrap();
+        test.run(update_swimming);
+        assert!(!swimming(&test, player));
+    }
+
+    #[test]
+    fn only_submerged_players_start_swimming() {
+        let mut test = water(&[64]);
+        let player = test.player("swimmer", position!(0.5, 64.0, 0.5));
+        test.world.add(player, Sprinting).unwrap();
+
+        test.run(update_swimming);
+        assert!(!swimming(&test, player));
+
+        // Swimmers with their head above water keep swimming
+        // until they leave the water altogether.
+        test.world.add(player, Swimming).unwrap();
+        test.run(update_swimming);
+        assert!(test.world.has::<Swimming>(player));
+
+        test.position(player, position!(0.5, 66.0, 0.5));
+        test.run(update_swimming);
+        assert!(!swimming(&test, player));
+    }
+}
@@ -0,0 +1,175 @@
+//! Tridents, which players throw by holding the use button
+//! and releasing it. Tridents enchanted with Riptide launch
+//! the player instead while they are in water or rain.
+
+use crate::{hand_slot, remove_one_item, start_timed_use, ItemTimedUse};
+use entity::arrow::ArrowPickup;
+use feather_core::entitymeta::EntityMetadata;
+use feather_core::inventory::{Inventory, SlotIndex};
+use feather_core::items::{Enchantment, Item, ItemStack};
+use feather_core::util::{Gamemode, Hand, Position};
+use feather_server_types::{BumpVec, EntitySpawnEvent, Game, Velocity, PLAYER_EYE_HEIGHT};
+use feather_server_util::{compute_projectile_velocity, wear_item};
+use feather_server_weather::is_rained_on;
+use fecs::{Entity, IntoQuery, Read, World};
+
+/// Number of ticks a trident must be held back before it can be thrown.
+const MIN_DRAW_TICKS: u64 = 10;
+
+/// Speed at which tridents are thrown.
+const THROW_SPEED: f64 = 2.5;
+
+/// Number of ticks for which a player spins after using Riptide.
+const SPIN_TICKS: u64 = 20;
+
+/// Component for players spinning after launching
+/// themselves with a Riptide trident.
+#[derive(Debug, Clone, Copy)]
+pub struct SpinAttack {
+    /// The tick at which the spin ends.
+    pub until: u64,
+}
+
+/// Starts holding back the trident in the given hand. Riptide
+/// tridents can only be used in water or rain.
+pub fn draw_trident(game: &Game, world: &mut World, player: Entity, hand: Hand) {
+    let riptide = world
+        .get::<Inventory>(player)
+        .item_at(hand_slot(world, player, hand))
+        .map_or(0, |stack| stack.enchantment_level(Enchantment::Riptide));
+    if riptide > 0 && !is_wet(game, *world.get::<Position>(player)) {
+        return;
+    }
+
+    start_timed_use(game, world, player, hand);
+}
+
+/// Throws a trident which was held back during `timed_use`,
+/// or launches the player if it is enchanted with Riptide.
+pub fn release_trident(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    timed_use: ItemTimedUse,
+) {
+    let slot = hand_slot(world, player, timed_use.hand);
+    let trident = match world.get::<Inventory>(player).item_at(slot) {
+        Some(stack) if stack.ty == Item::Trident => stack.clone(),
+        _ => return,
+    };
+
+    if game.tick_count - timed_use.tick_start < MIN_DRAW_TICKS {
+        return;
+    }
+
+    // Tridents wear down when thrown or used for Riptide,
+    // and one which breaks is not thrown.
+    wear_item(game, world, player, slot, 1);
+    let trident = match world.get::<Inventory>(player).item_at(slot) {
+        Some(stack) if stack.ty == Item::Trident => stack.clone(),
+        _ => return,
+    };
+
+    let riptide = trident.enchantment_level(Enchantment::Riptide);
+    if riptide > 0 {
+        riptide_launch(game, world, player, riptide);
+    } else {
+        throw(game, world, player, slot, trident);
+    }
+}
+
+fn throw(game: &mut Game, world: &mut World, player: Entity, slot: SlotIndex, trident: ItemStack) {
+    let creative = *world.get::<Gamemode>(player) == Gamemode::Creative;
+    // Creative players keep their trident, so the thrown one
+    // cannot be picked up for another.
+    let pickup = if creative {
+        ArrowPickup::CreativeOnly
+    } else {
+        remove_one_item(game, world, player, slot);
+        ArrowPickup::Allowed
+    };
+
+    let init_position = *world.get::<Position>(player) + glm::vec3(0.0, PLAYER_EYE_HEIGHT, 0.0);
+    let direction = init_position.direction();
+    let velocity = compute_projectile_velocity(
+        glm::vec3(direction.x, direction.y, direction.z),
+        THROW_SPEED,
+        1.0,
+        &mut *game.rng(),
+    );
+
+    let entity = entity::trident::create(Some(player), pickup, trident)
+        .with(init_position)
+        .with(Velocity(velocity))
+        .build()
+        .spawn_in(world);
+    game.handle(world, EntitySpawnEvent { entity });
+}
+
+/// Launches a player in the direction they are looking.
+fn riptide_launch(game: &mut Game, world: &mut World, player: Entity, level: u32) {
+    let pos = *world.get::<Position>(player);
+    let direction = pos.direction();
+    let direction = glm::vec3(direction.x, direction.y, direction.z).normalize();
+
+    // The client predicts the launch, but the server
+    // has the final say on the player's velocity.
+    let velocity = world.get::<Velocity>(player).0 + direction * riptide_speed(level);
+    game.set_velocity(world, player, velocity);
+
+    let spin = SpinAttack {
+        until: game.tick_count + SPIN_TICKS,
+    };
+    if world.has::<SpinAttack>(player) {
+        *world.get_mut::<SpinAttack>(player) = spin;
+    } else {
+        world.add(player, spin).unwrap();
+    }
+    world
+        .get_mut::<EntityMetadata>(player)
+        .set_spin_attack(true);
+}
+
+/// Returns the speed at which Riptide of the given level launches a player.
+fn riptide_speed(level: u32) -> f64 {
+    3.0 * (1.0 + f64::from(level)) / 4.0
+}
+
+fn is_wet(game: &Game, pos: Position) -> bool {
+    let in_water = game
+        .block_at(pos.block())
+        .map_or(false, |block| block.is_water());
+    in_water || is_rained_on(game, pos.block())
+}
+
+/// System which ends the spin of players
+/// who launched themselves with Riptide.
+#[fecs::system]
+pub fn end_spin_attacks(game: &mut Game, world: &mut World) {
+    let mut ended = BumpVec::new_in(game.bump());
+    ended.extend(
+        <Read<SpinAttack>>::query()
+            .iter_entities(world.inner())
+            .filter(|(_, spin)| spin.until <= game.tick_count)
+            .map(|(player, _)| player),
+    );
+
+    for player in ended {
+        world.remove::<SpinAttack>(player).unwrap();
+        world
+            .get_mut::<EntityMetadata>(player)
+            .set_spin_attack(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn riptide_speed_scales_with_level() {
+        assert_eq!(riptide_speed(1), 1.5);
+        assert_eq!(riptide_speed(3), 3.0);
+    }
+}
@@ -23,9 +23,9 @@ use feather_core::chunk::Chunk;
 use feather_core::network::packets::{ChunkData, DestroyEntities, UnloadChunk};
 use feather_core::util::{ChunkPosition, Position};
 use feather_server_types::{
-    BumpVec, ChunkCrossEvent, ChunkLoadEvent, ChunkSendEvent, EntityClientRemoveEvent, EntityId,
-    EntitySendEvent, Game, HoldChunkRequest, LoadChunkRequest, Network, PlayerJoinEvent,
-    PreviousPosition, ReleaseChunkRequest, SpawnPacketCreator,
+    BumpVec, ChunkCrossEvent, ChunkHolder, ChunkLoadEvent, ChunkSendEvent, EntityClientRemoveEvent,
+    EntityId, EntitySendEvent, Game, HoldChunkRequest, LoadChunkRequest, Network, PlayerJoinEvent,
+    PreviousPosition, ReleaseChunkRequest, SpawnPacketCreator, ViewDistance,
 };
 use fecs::{Entity, IntoQuery, Read, World};
 use itertools::Either;
@@ -78,6 +78,48 @@ pub fn on_player_join_trigger_chunk_cross(
     );
 }
 
+/// Returns the view distance of a player, in chunks. Players
+/// without a `ViewDistance` use the configured default.
+pub fn view_distance(game: &Game, world: &World, player: Entity) -> u8 {
+    world
+        .try_get::<ViewDistance>(player)
+        .map_or(game.config.server.view_distance, |distance| {
+            distance.current
+        })
+}
+
+/// The chunks visible to a player: those within
+/// `distance` chunks of `center` on both axes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct View {
+    pub center: ChunkPosition,
+    pub distance: u8,
+}
+
+impl View {
+    pub fn new(center: ChunkPosition, distance: u8) -> Self {
+        Self { center, distance }
+    }
+
+    /// Returns the view of a player in the given chunk.
+    pub fn of(game: &Game, world: &World, player: Entity, center: ChunkPosition) -> Self {
+        Self::new(center, view_distance(game, world, player))
+    }
+
+    pub fn contains(self, chunk: ChunkPosition) -> bool {
+        let distance = i32::from(self.distance);
+        (chunk.x - self.center.x).abs() <= distance && (chunk.z - self.center.z).abs() <= distance
+    }
+
+    pub fn chunks(self) -> impl Iterator<Item = ChunkPosition> {
+        let distance = i32::from(self.distance);
+        let center = self.center;
+        (-distance..=distance).flat_map(move |x| {
+            (-distance..=distance).map(move |z| center.add(ChunkPosition::new(x, z)))
+        })
+    }
+}
+
 /// System which sends new chunks and unloads old chunks on the client
 /// when the view is updated.
 #[fecs::event_handler]
@@ -91,22 +133,35 @@ pub fn on_chunk_cross_update_chunks(
         return;
     }
 
+    let old = event
+        .old
+        .map(|old| View::of(game, world, event.entity, old));
+    let new = View::of(game, world, event.entity, event.new);
+    update_chunks(game, world, chunks_to_send, event.entity, old, new);
+}
+
+/// Sends a player the chunks which are in their new view
+/// but not their old one, and unloads those which left it.
+pub(crate) fn update_chunks(
+    game: &mut Game,
+    world: &mut World,
+    chunks_to_send: &mut ChunksToSend,
+    player: Entity,
+    old: Option<View>,
+    new: View,
+) {
     // The client likes it if we send closer chunks first,
     // so we'll sort by the Manhattan distance to the player.
     let mut pending_send = BumpVec::new_in(game.bump());
-    pending_send.extend(find_new_chunks(
-        event.old,
-        event.new,
-        game.config.server.view_distance,
-    ));
-    pending_send.sort_unstable_by_key(|chunk| chunk.manhattan_distance_to(event.new));
+    pending_send.extend(find_new_chunks(old, new));
+    pending_send.sort_unstable_by_key(|chunk| chunk.manhattan_distance_to(new.center));
 
     for chunk in pending_send {
-        send_chunk_to_player(game, world, chunks_to_send, event.entity, chunk);
+        send_chunk_to_player(game, world, chunks_to_send, player, chunk);
     }
 
-    for chunk in find_old_chunks(event.old, event.new, game.config.server.view_distance) {
-        unload_chunk_for_player(game, world, chunk, event.entity);
+    for chunk in find_old_chunks(old, new) {
+        unload_chunk_for_player(game, world, chunk, player);
     }
 }
 
@@ -114,16 +169,34 @@ pub fn on_chunk_cross_update_chunks(
 /// when a player crosses into a new view.
 #[fecs::event_handler]
 pub fn on_chunk_cross_update_entities(event: &ChunkCrossEvent, game: &mut Game, world: &mut World) {
-    let network = match world.try_get::<Network>(event.entity) {
-        Some(net) => net,
-        None => return, // not a player
-    };
+    if !world.has::<Network>(event.entity) {
+        return; // not a player
+    }
+
+    let old = event
+        .old
+        .map(|old| View::of(game, world, event.entity, old));
+    let new = View::of(game, world, event.entity, event.new);
+    update_visible_entities(game, world, event.entity, old, new);
+    update_viewers(game, world, event.entity, event.old, event.new);
+}
+
+/// Sends a player the entities in chunks which are in their new
+/// view but not their old one, and removes those which left it.
+pub(crate) fn update_visible_entities(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    old: Option<View>,
+    new: View,
+) {
+    let network = world.get::<Network>(player);
 
     // Send newly visible entities.
     let mut sends_to_trigger = vec![];
-    for other in find_new_chunks(event.old, event.new, game.config.server.view_distance)
+    for other in find_new_chunks(old, new)
         .flat_map(|chunk| game.chunk_entities.entities_in_chunk(chunk))
-        .filter(|other| **other != event.entity)
+        .filter(|other| **other != player)
     // don't send player to themselves!
     {
         if let Some(creator) = world.try_get::<SpawnPacketCreator>(*other) {
@@ -133,41 +206,19 @@ pub fn on_chunk_cross_update_entities(event: &ChunkCrossEvent, game: &mut Game,
             let packet = creator.get(&accessor);
 
             network.send_boxed(packet);
-            sends_to_trigger.push((*other, event.entity));
-        }
-
-        // if this `other` is a player, also send `entity` to other
-        if let Some(network) = world.try_get::<Network>(*other) {
-            if let Some(creator) = world.try_get::<SpawnPacketCreator>(event.entity) {
-                let accessor = world.entity(event.entity).expect("entity does not exist");
-                let packet = creator.get(&accessor);
-
-                network.send_boxed(packet);
-                sends_to_trigger.push((event.entity, *other));
-            }
+            sends_to_trigger.push((*other, player));
         }
     }
 
     // Tell the client to despawn entities which are no longer visible.
     let mut to_client_remove_trigger = vec![];
     to_client_remove_trigger.extend(
-        find_old_chunks(event.old, event.new, game.config.server.view_distance)
+        find_old_chunks(old, new)
             .flat_map(|chunk| game.chunk_entities.entities_in_chunk(chunk))
-            .map(|other| (*other, event.entity)),
+            .filter(|other| **other != player)
+            .map(|other| (*other, player)),
     );
 
-    // Despawn this entity on other visible clients.
-    find_old_chunks(event.old, event.new, game.config.server.view_distance)
-        .flat_map(|chunk| game.chunk_entities.entities_in_chunk(chunk))
-        .filter_map(|entity| world.try_get::<Network>(*entity).map(|net| (*entity, net)))
-        .for_each(|(other, network)| {
-            let packet = DestroyEntities {
-                entity_ids: vec![world.get::<EntityId>(event.entity).0],
-            };
-            network.send(packet);
-            to_client_remove_trigger.push((event.entity, other));
-        });
-
     let to_destroy = to_client_remove_trigger
         .iter()
         .filter_map(|(other, _)| world.try_get::<EntityId>(*other).map(|id| id.0))
@@ -182,68 +233,132 @@ pub fn on_chunk_cross_update_entities(event: &ChunkCrossEvent, game: &mut Game,
 
     drop(network);
 
-    // Trigger on_entity_send
-    for (entity, client) in sends_to_trigger {
-        game.handle(world, EntitySendEvent { entity, client });
-    }
-
-    // Trigger on_entity_client_remove
-    for (other, to) in to_client_remove_trigger {
-        game.handle(
-            world,
-            EntityClientRemoveEvent {
-                entity: other,
-                client: to,
-            },
-        );
-    }
+    trigger_view_events(game, world, sends_to_trigger, to_client_remove_trigger);
 }
 
-/// Returns new chunks visible from a new chunk position.
-fn find_new_chunks(
+/// Sends a player who moved from chunk `old` to chunk `new` to the
+/// players who can now see them, and removes them from the clients
+/// of players who no longer can.
+///
+/// Players see entities in the chunks they hold, which
+/// depend on their own view distance.
+fn update_viewers(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
     old: Option<ChunkPosition>,
     new: ChunkPosition,
-    view_distance: u8,
-) -> impl Iterator<Item = ChunkPosition> {
-    let within_view_distance = chunks_within_view_distance(new, view_distance);
+) {
+    let held_before = |other: &Entity| old.map_or(false, |old| holds(game, *other, old));
+
+    let mut sends_to_trigger = vec![];
+    if let Some(creator) = world.try_get::<SpawnPacketCreator>(player) {
+        let accessor = world.entity(player).expect("entity does not exist");
+        for &other in game.chunk_holders.holders_for(new) {
+            if other == player || held_before(&other) {
+                continue;
+            }
+            if let Some(network) = world.try_get::<Network>(other) {
+                network.send_boxed(creator.get(&accessor));
+                sends_to_trigger.push((player, other));
+            }
+        }
+    }
+
+    // Despawn this entity on clients which can no longer see it.
+    let mut to_client_remove_trigger = vec![];
     if let Some(old) = old {
-        Either::Left(within_view_distance.filter(move |chunk| {
-            (chunk.x - old.x).abs() >= view_distance as i32
-                || (chunk.z - old.z).abs() >= view_distance as i32
-        }))
-    } else {
-        Either::Right(within_view_distance)
+        let entity_id = world.get::<EntityId>(player).0;
+        for &other in game.chunk_holders.holders_for(old) {
+            if other == player || holds(game, other, new) {
+                continue;
+            }
+            if let Some(network) = world.try_get::<Network>(other) {
+                network.send(DestroyEntities {
+                    entity_ids: vec![entity_id],
+                });
+                to_client_remove_trigger.push((player, other));
+            }
+        }
     }
+
+    trigger_view_events(game, world, sends_to_trigger, to_client_remove_trigger);
 }
 
-/// Returns chunks which are no longer visible from a new chunk position.
-fn find_old_chunks(
-    old: Option<ChunkPosition>,
-    new: ChunkPosition,
-    view_distance: u8,
-) -> impl Iterator<Item = ChunkPosition> {
-    if let Some(old) = old {
-        Either::Left(
-            chunks_within_view_distance(old, view_distance).filter(move |chunk| {
-                (chunk.x - new.x).abs() >= view_distance as i32
-                    || (chunk.z - new.z).abs() >= view_distance as i32
-            }),
-        )
-    } else {
-        Either::Right(iter::empty())
+/// Returns whether an entity has a hold on a chunk.
+fn holds(game: &Game, entity: Entity, chunk: ChunkPosition) -> bool {
+    game.chunk_holders.holders_for(chunk).contains(&entity)
+}
+
+/// Triggers the events for entities which were sent to
+/// or removed from clients, as `(entity, client)` pairs.
+fn trigger_view_events(
+    game: &mut Game,
+    world: &mut World,
+    sent: Vec<(Entity, Entity)>,
+    removed: Vec<(Entity, Entity)>,
+) {
+    for (entity, client) in sent {
+        game.handle(world, EntitySendEvent { entity, client });
+    }
+
+    for (entity, client) in removed {
+        game.handle(world, EntityClientRemoveEvent { entity, client });
     }
 }
 
-/// Finds all chunks within the view distance of a given chunk.
-fn chunks_within_view_distance(
-    chunk: ChunkPosition,
-    view_distance: u8,
-) -> impl Iterator<Item = ChunkPosition> {
-    let view_distance = i32::from(view_distance);
+/// Forgets the chunks and entities a player's client was sent, for
+/// when the client discards them, as it does when respawning.
+///
+/// The player's chunk holds are released, and the player is removed
+/// from the clients of other players, so that triggering a chunk cross
+/// with no old chunk sends the player's view anew.
+pub(crate) fn forget_view(game: &mut Game, world: &mut World, player: Entity) {
+    let chunk = world.get::<Position>(player).chunk();
+    let entity_id = world.get::<EntityId>(player).0;
+    let mut removed = BumpVec::new_in(game.bump());
+    for &other in View::of(game, world, player, chunk)
+        .chunks()
+        .flat_map(|chunk| game.chunk_entities.entities_in_chunk(chunk))
+        .filter(|other| **other != player)
+    {
+        removed.push((other, player));
+    }
+    for &other in game.chunk_holders.holders_for(chunk) {
+        if other == player {
+            continue;
+        }
+        if let Some(network) = world.try_get::<Network>(other) {
+            network.send(DestroyEntities {
+                entity_ids: vec![entity_id],
+            });
+            removed.push((player, other));
+        }
+    }
 
-    (-view_distance..=view_distance).flat_map(move |x| {
-        (-view_distance..=view_distance).map(move |z| chunk.add(ChunkPosition::new(x, z)))
-    })
+    let mut holds = BumpVec::new_in(game.bump());
+    holds.extend(world.get::<ChunkHolder>(player).holds.iter().copied());
+    for chunk in holds {
+        game.handle(world, ReleaseChunkRequest { player, chunk });
+    }
+
+    for (entity, client) in removed {
+        game.handle(world, EntityClientRemoveEvent { entity, client });
+    }
+}
+
+/// Returns new chunks visible from a new view.
+fn find_new_chunks(old: Option<View>, new: View) -> impl Iterator<Item = ChunkPosition> {
+    new.chunks()
+        .filter(move |&chunk| old.map_or(true, |old| !old.contains(chunk)))
+}
+
+/// Returns chunks which are no longer visible from a new view.
+fn find_old_chunks(old: Option<View>, new: View) -> impl Iterator<Item = ChunkPosition> {
+    match old {
+        Some(old) => Either::Left(old.chunks().filter(move |&chunk| !new.contains(chunk))),
+        None => Either::Right(iter::empty()),
+    }
 }
 
 /// Resource containing a mapping from chunks -> sets of players indicating
@@ -0,0 +1,181 @@
+//! Changing players' view distances, whether set with
+//! `/viewdistance` or lowered automatically while the
+//! server is overloaded.
+
+use crate::view::{update_chunks, update_visible_entities, View};
+use crate::ChunksToSend;
+use feather_server_types::{
+    AutoViewDistance, Game, PreviousPosition, TickTimes, ViewDistance, ViewDistanceRequest, TPS,
+};
+use fecs::{Entity, IntoQuery, Read, World};
+use std::time::Duration;
+
+/// Interval, in ticks, at which the automatic
+/// view distance is adjusted.
+const ADJUST_INTERVAL: u64 = TPS * 5;
+
+/// Resource holding the limit which the automatic view distance
+/// places on players' view distances, if it is lowering them.
+#[derive(Debug, Default)]
+pub struct ViewDistanceLimit(Option<u8>);
+
+impl ViewDistanceLimit {
+    /// Returns the view distance to use for a player
+    /// with the given target view distance.
+    fn apply(&self, target: u8) -> u8 {
+        self.0.map_or(target, |limit| limit.min(target))
+    }
+}
+
+/// Handles a `ViewDistanceRequest`.
+#[fecs::event_handler]
+pub fn view_distance_request(
+    event: &ViewDistanceRequest,
+    game: &mut Game,
+    world: &mut World,
+    #[default] chunks_to_send: &mut ChunksToSend,
+    #[default] limit: &ViewDistanceLimit,
+) {
+    if !world.has::<ViewDistance>(event.player) {
+        return;
+    }
+    world.get_mut::<ViewDistance>(event.player).target = event.distance;
+    let distance = limit.apply(event.distance);
+    set_view_distance(game, world, chunks_to_send, event.player, distance);
+}
+
+/// System which lowers the view distance limit while ticks take
+/// too long on average, and raises it again once they no longer do.
+///
+/// The limit is also applied to players who joined since
+/// the last adjustment.
+#[fecs::system]
+pub fn adjust_view_distances(
+    game: &mut Game,
+    world: &mut World,
+    #[default] chunks_to_send: &mut ChunksToSend,
+    #[default] limit: &mut ViewDistanceLimit,
+) {
+    let config = game.config.auto_view_distance.clone();
+    if !config.enabled || game.tick_count % ADJUST_INTERVAL != 0 {
+        return;
+    }
+
+    let max_target = <Read<ViewDistance>>::query()
+        .iter(world.inner())
+        .map(|distance| distance.target)
+        .max()
+        .unwrap_or(game.config.server.view_distance);
+    let average = game.resources.get::<TickTimes>().average();
+    let new_limit = next_limit(limit.0, max_target, average, &config);
+    if new_limit != limit.0 {
+        match new_limit {
+            Some(new_limit) => log::info!(
+                "Ticks are taking {}ms on average; limiting view distance to {} chunks",
+                average.as_millis(),
+                new_limit
+            ),
+            None => log::info!("No longer limiting view distance"),
+        }
+        limit.0 = new_limit;
+    }
+
+    let changed: Vec<(Entity, u8)> = <Read<ViewDistance>>::query()
+        .iter_entities(world.inner())
+        .filter_map(|(player, distance)| {
+            let current = limit.apply(distance.target);
+            if current == distance.current {
+                None
+            } else {
+                Some((player, current))
+            }
+        })
+        .collect();
+    for (player, distance) in changed {
+        set_view_distance(game, world, chunks_to_send, player, distance);
+    }
+}
+
+/// Returns the view distance limit following `limit`, one chunk
+/// lower if the average tick time is over the maximum, or one
+/// higher if it is well under it. The limit is removed once it
+/// no longer lowers any player's view distance.
+fn next_limit(
+    limit: Option<u8>,
+    max_target: u8,
+    average: Duration,
+    config: &AutoViewDistance,
+) -> Option<u8> {
+    let current = limit.unwrap_or(max_target);
+    if average > config.max_tick_time {
+        if current <= config.min_view_distance {
+            limit
+        } else {
+            Some(current - 1)
+        }
+    } else if average < config.max_tick_time * 3 / 4 {
+        limit.and_then(|limit| {
+            if limit + 1 >= max_target {
+                None
+            } else {
+                Some(limit + 1)
+            }
+        })
+    } else {
+        limit
+    }
+}
+
+/// Sets the view distance a player uses, sending the chunks and
+/// entities which enter their view and unloading those which leave it.
+fn set_view_distance(
+    game: &mut Game,
+    world: &mut World,
+    chunks_to_send: &mut ChunksToSend,
+    player: Entity,
+    distance: u8,
+) {
+    // The view is centered on the chunk of the last chunk
+    // cross, which is that of the previous position.
+    let center = world.get::<PreviousPosition>(player).0.chunk();
+    let old = View::of(game, world, player, center);
+    world.get_mut::<ViewDistance>(player).current = distance;
+    let new = View::new(center, distance);
+    if old == new {
+        return;
+    }
+
+    update_chunks(game, world, chunks_to_send, player, Some(old), new);
+    update_visible_entities(game, world, player, Some(old), new);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AutoViewDistance {
+        AutoViewDistance {
+            enabled: true,
+            max_tick_time: Duration::from_millis(40),
+            min_view_distance: 3,
+        }
+    }
+
+    #[test]
+    fn limit_follows_tick_time() {
+        let config = config();
+        let slow = Duration::from_millis(45);
+        let fast = Duration::from_millis(10);
+        let steady = Duration::from_millis(35);
+
+        assert_eq!(next_limit(None, 6, fast, &config), None);
+        assert_eq!(next_limit(None, 6, slow, &config), Some(5));
+        assert_eq!(next_limit(Some(5), 6, steady, &config), Some(5));
+        assert_eq!(next_limit(Some(3), 6, slow, &config), Some(3));
+        assert_eq!(next_limit(Some(3), 6, fast, &config), Some(4));
+        assert_eq!(next_limit(Some(5), 6, fast, &config), None);
+
+        // Players with a view distance under the minimum are not lowered.
+        assert_eq!(next_limit(None, 2, slow, &config), None);
+    }
+}
@@ -0,0 +1,735 @@
+//! Windows, which players click to move items between slots.
+//!
+//! The server keeps the authoritative contents of every slot and
+//! applies each click itself. When a client's view of a window turns
+//! out to differ from the server's, the click is rejected and the
+//! whole window is sent to the client again.
+//!
+//! Window 0 is the player's own inventory, whose crafting grid is
+//! matched against the server's recipes whenever its contents change.
+//! Other windows show the slots of containers, such as chests, above
+//! the player's main inventory and hotbar.
+
+use crate::container::update_viewers;
+use crate::{map_crafting_result, IteratorExt};
+use feather_core::anvil::block_entity::BlockEntityData;
+use feather_core::inventory::{
+    max_size, Click, ClickOutcome, ClickState, Inventory, Slot, SlotIndex, WindowContents,
+    HOTBAR_SIZE, INVENTORY_SIZE, SLOT_ARMOR_MAX, SLOT_ARMOR_MIN, SLOT_CRAFTING_INPUT_X0_Y0,
+    SLOT_CRAFTING_INPUT_X1_Y1, SLOT_CRAFTING_OUTPUT, SLOT_HOTBAR_OFFSET, SLOT_INVENTORY_OFFSET,
+    SLOT_OFFHAND,
+};
+use feather_core::items::{Item, ItemStack};
+use feather_core::network::packets::{
+    ClickWindow, CloseWindowServerbound, ConfirmTransactionClientbound,
+    ConfirmTransactionServerbound, SetSlot, WindowItems,
+};
+use feather_core::util::{BlockPosition, Gamemode};
+use feather_server_types::{
+    crafting_remainder, BlockEntities, Game, InventoryUpdateEvent, ItemDropEvent, Network,
+    PacketBuffers, PlayerLeaveEvent, RecipeRegistry,
+};
+use feather_server_util::armor_slot;
+use fecs::{Entity, IntoQuery, Read, World};
+use smallvec::SmallVec;
+use std::sync::Arc;
+
+/// Window and slot IDs of Set Slot packets which update the cursor.
+const CURSOR_WINDOW_ID: i8 = -1;
+const CURSOR_SLOT: i16 = -1;
+
+/// Component holding the state of the window a player has open.
+#[derive(Debug, Clone, Default)]
+pub struct Window {
+    /// ID of the open window. Window 0 is the player's
+    /// inventory, which is open whenever no other window is.
+    pub id: u8,
+    /// Positions of the containers whose slots come first in the
+    /// open window, in order. Empty while window 0 is open.
+    pub containers: SmallVec<[BlockPosition; 2]>,
+    /// The cursor and drag state of the player's clicks.
+    pub clicks: ClickState,
+    /// Whether the player's clicks are ignored until their client
+    /// acknowledges a rejected click, as in vanilla.
+    pub awaiting_confirmation: bool,
+    /// ID of the last window opened, from which
+    /// the ID of the next one is chosen.
+    last_id: u8,
+}
+
+impl Window {
+    /// Opens a window showing the given containers, returning its ID.
+    /// As in vanilla, IDs count from 1 to 100 and then wrap around.
+    pub fn open(&mut self, containers: SmallVec<[BlockPosition; 2]>) -> u8 {
+        self.last_id = self.last_id % 100 + 1;
+        *self = Window {
+            id: self.last_id,
+            containers,
+            last_id: self.last_id,
+            ..Window::default()
+        };
+        self.id
+    }
+}
+
+/// Width of the crafting grid in the player's inventory.
+pub const CRAFTING_GRID_WIDTH: usize = 2;
+
+/// Sets the crafting output of a player's inventory to the result
+/// of the recipe matching their crafting grid. Returns whether
+/// the output changed.
+pub fn update_crafting_output(inventory: &mut Inventory, recipes: &RecipeRegistry) -> bool {
+    let grid: SmallVec<[Slot; 4]> = (SLOT_CRAFTING_INPUT_X0_Y0..=SLOT_CRAFTING_INPUT_X1_Y1)
+        .map(|slot| inventory.item_at(slot).cloned())
+        .collect();
+    let result = map_crafting_result(&grid).or_else(|| {
+        recipes
+            .match_crafting(&grid, CRAFTING_GRID_WIDTH)
+            .map(|recipe| recipe.result.clone())
+    });
+
+    if inventory.item_at(SLOT_CRAFTING_OUTPUT) == result.as_ref() {
+        return false;
+    }
+    match result {
+        Some(result) => inventory.set_item_at(SLOT_CRAFTING_OUTPUT, result),
+        None => {
+            inventory.clear_item_at(SLOT_CRAFTING_OUTPUT);
+        }
+    }
+    true
+}
+
+/// A player's inventory seen as window 0.
+struct PlayerWindow<'a> {
+    inventory: &'a mut Inventory,
+    recipes: &'a RecipeRegistry,
+    /// Slots changed as a side effect of setting other slots:
+    /// the crafting grid and output.
+    crafting_changed: SmallVec<[SlotIndex; 8]>,
+}
+
+impl<'a> PlayerWindow<'a> {
+    fn new(inventory: &'a mut Inventory, recipes: &'a RecipeRegistry) -> Self {
+        Self {
+            inventory,
+            recipes,
+            crafting_changed: SmallVec::new(),
+        }
+    }
+
+    fn mark_changed(&mut self, slot: SlotIndex) {
+        if !self.crafting_changed.contains(&slot) {
+            self.crafting_changed.push(slot);
+        }
+    }
+
+    /// Uses up one of each ingredient in the crafting
+    /// grid after the result has been taken.
+    fn consume_ingredients(&mut self) {
+        for slot in SLOT_CRAFTING_INPUT_X0_Y0..=SLOT_CRAFTING_INPUT_X1_Y1 {
+            let mut stack = match self.inventory.item_at(slot).cloned() {
+                Some(stack) => stack,
+                None => continue,
+            };
+            self.mark_changed(slot);
+
+            stack.amount -= 1;
+            let remainder = crafting_remainder(stack.ty).map(|item| ItemStack::new(item, 1));
+            match remainder {
+                Some(remainder) if stack.amount == 0 => {
+                    self.inventory.set_item_at(slot, remainder);
+                    continue;
+                }
+                Some(remainder) => {
+                    let (slots, _) = self.inventory.collect_item(remainder);
+                    for changed in slots {
+                        self.mark_changed(changed);
+                    }
+                }
+                None => (),
+            }
+            self.inventory.set_item_at(slot, stack);
+        }
+    }
+}
+
+impl WindowContents for PlayerWindow<'_> {
+    fn slot_count(&self) -> SlotIndex {
+        self.inventory.slot_count() as SlotIndex
+    }
+
+    fn item(&self, slot: SlotIndex) -> Slot {
+        self.inventory.item_at(slot).cloned()
+    }
+
+    fn set_item(&mut self, slot: SlotIndex, item: Slot) {
+        let taken_result = slot == SLOT_CRAFTING_OUTPUT
+            && item.is_none()
+            && self.inventory.item_at(slot).is_some();
+
+        match item {
+            Some(item) => self.inventory.set_item_at(slot, item),
+            None => {
+                self.inventory.clear_item_at(slot);
+            }
+        }
+
+        if taken_result {
+            self.consume_ingredients();
+        }
+        let grid = SLOT_CRAFTING_INPUT_X0_Y0..=SLOT_CRAFTING_INPUT_X1_Y1;
+        if (taken_result || grid.contains(&slot))
+            && update_crafting_output(self.inventory, self.recipes)
+        {
+            self.mark_changed(SLOT_CRAFTING_OUTPUT);
+        }
+    }
+
+    fn accepts(&self, slot: SlotIndex, stack: &ItemStack) -> bool {
+        match slot {
+            SLOT_CRAFTING_OUTPUT => false,
+            SLOT_ARMOR_MIN..=SLOT_ARMOR_MAX => armor_slot(stack.ty) == Some(slot),
+            _ => true,
+        }
+    }
+
+    fn max_stack(&self, slot: SlotIndex, stack: &ItemStack) -> u8 {
+        match slot {
+            SLOT_ARMOR_MIN..=SLOT_ARMOR_MAX => 1,
+            _ => max_size(stack.ty),
+        }
+    }
+
+    fn hotbar_slot(&self, hotbar: SlotIndex) -> SlotIndex {
+        SLOT_HOTBAR_OFFSET + hotbar
+    }
+
+    fn quick_move_targets(&self, slot: SlotIndex, stack: &ItemStack) -> SmallVec<[SlotIndex; 36]> {
+        let main = SLOT_INVENTORY_OFFSET..SLOT_HOTBAR_OFFSET;
+        let hotbar = SLOT_HOTBAR_OFFSET..SLOT_OFFHAND;
+
+        // Armor and shields are equipped before anything else.
+        let mut equipment = SmallVec::<[SlotIndex; 36]>::new();
+        equipment.extend(armor_slot(stack.ty));
+        if stack.ty == Item::Shield {
+            equipment.push(SLOT_OFFHAND);
+        }
+
+        match slot {
+            // Crafting results go to the hotbar first.
+            SLOT_CRAFTING_OUTPUT => (SLOT_INVENTORY_OFFSET..SLOT_OFFHAND).rev().collect(),
+            SLOT_CRAFTING_INPUT_X0_Y0..=SLOT_ARMOR_MAX | SLOT_OFFHAND => {
+                (SLOT_INVENTORY_OFFSET..SLOT_OFFHAND).collect()
+            }
+            slot if main.contains(&slot) => {
+                equipment.extend(hotbar);
+                equipment
+            }
+            _ => {
+                equipment.extend(main);
+                equipment
+            }
+        }
+    }
+}
+
+/// Number of slots of a player's inventory shown below
+/// the slots of a container: the main inventory and hotbar.
+const PLAYER_SLOTS: SlotIndex = INVENTORY_SIZE + HOTBAR_SIZE;
+
+/// Returns the number of slots of a container block entity.
+pub fn container_size(block_entity: &BlockEntityData) -> SlotIndex {
+    match block_entity {
+        BlockEntityData::Chest(_) | BlockEntityData::TrappedChest(_) => 27,
+        BlockEntityData::Dispenser(_) | BlockEntityData::Dropper(_) => 9,
+        BlockEntityData::Hopper(_) => 5,
+        BlockEntityData::MobSpawner(_) | BlockEntityData::Unknown => 0,
+    }
+}
+
+/// The slots of one or more containers, followed by
+/// the main inventory and hotbar of the player.
+struct ContainerWindow<'a> {
+    containers: &'a [BlockPosition],
+    /// Number of slots of each container.
+    sizes: SmallVec<[SlotIndex; 2]>,
+    block_entities: &'a mut BlockEntities,
+    inventory: &'a mut Inventory,
+}
+
+impl<'a> ContainerWindow<'a> {
+    fn new(
+        containers: &'a [BlockPosition],
+        block_entities: &'a mut BlockEntities,
+        inventory: &'a mut Inventory,
+    ) -> Self {
+        let sizes = containers
+            .iter()
+            .map(|pos| block_entities.get(*pos).map(container_size).unwrap_or(0))
+            .collect();
+        Self {
+            containers,
+            sizes,
+            block_entities,
+            inventory,
+        }
+    }
+
+    /// Returns the number of container slots.
+    fn container_slots(&self) -> SlotIndex {
+        self.sizes.iter().sum()
+    }
+
+    /// Returns the container of a window slot and the
+    /// slot within it, or `None` for a player's slot.
+    fn container_slot(&self, mut slot: SlotIndex) -> Option<(BlockPosition, SlotIndex)> {
+        for (pos, size) in self.containers.iter().zip(&self.sizes) {
+            if slot < *size {
+                return Some((*pos, slot));
+            }
+            slot -= size;
+        }
+        None
+    }
+
+    /// Returns the slot of the player's inventory
+    /// shown in a window slot below the containers.
+    fn inventory_slot(&self, slot: SlotIndex) -> SlotIndex {
+        slot - self.container_slots() + SLOT_INVENTORY_OFFSET
+    }
+}
+
+impl WindowContents for ContainerWindow<'_> {
+    fn slot_count(&self) -> SlotIndex {
+        self.container_slots() + PLAYER_SLOTS
+    }
+
+    fn item(&self, slot: SlotIndex) -> Slot {
+        match self.container_slot(slot) {
+            Some((pos, slot)) => self
+                .block_entities
+                .get(pos)
+                .and_then(BlockEntityData::container)
+                .and_then(|container| container.item(slot)),
+            None => self.inventory.item_at(self.inventory_slot(slot)).cloned(),
+        }
+    }
+
+    fn set_item(&mut self, slot: SlotIndex, item: Slot) {
+        match self.container_slot(slot) {
+            Some((pos, slot)) => {
+                if let Some(container) = self
+                    .block_entities
+                    .get_mut(pos)
+                    .and_then(BlockEntityData::container_mut)
+                {
+                    container.set_item(slot, item);
+                }
+            }
+            None => {
+                let slot = self.inventory_slot(slot);
+                match item {
+                    Some(item) => self.inventory.set_item_at(slot, item),
+                    None => {
+                        self.inventory.clear_item_at(slot);
+                    }
+                }
+            }
+        }
+    }
+
+    fn accepts(&self, _slot: SlotIndex, _stack: &ItemStack) -> bool {
+        true
+    }
+
+    fn hotbar_slot(&self, hotbar: SlotIndex) -> SlotIndex {
+        self.container_slots() + INVENTORY_SIZE + hotbar
+    }
+
+    fn quick_move_targets(&self, slot: SlotIndex, _stack: &ItemStack) -> SmallVec<[SlotIndex; 36]> {
+        // Items move from containers to the end of the hotbar
+        // first, and from the player to the start of the containers.
+        let containers = self.container_slots();
+        if slot < containers {
+            (containers..self.slot_count()).rev().collect()
+        } else {
+            (0..containers).collect()
+        }
+    }
+}
+
+/// Returns the contents of each slot of a player's window.
+fn window_items(game: &Game, world: &World, player: Entity) -> Vec<Slot> {
+    let window = world.get::<Window>(player);
+    let inventory = world.get::<Inventory>(player);
+    if window.containers.is_empty() {
+        return inventory.items().to_vec();
+    }
+
+    let mut items = Vec::new();
+    for pos in &window.containers {
+        if let Some(block_entity) = game.block_entities.get(*pos) {
+            let container = block_entity.container();
+            items.extend(
+                (0..container_size(block_entity))
+                    .map(|slot| container.and_then(|container| container.item(slot))),
+            );
+        }
+    }
+    items.extend(
+        (SLOT_INVENTORY_OFFSET..SLOT_INVENTORY_OFFSET + PLAYER_SLOTS)
+            .map(|slot| inventory.item_at(slot).cloned()),
+    );
+    items
+}
+
+/// Sends a player the full contents of their window and cursor,
+/// undoing any changes their client predicted.
+pub fn resend_window(game: &Game, world: &World, player: Entity) {
+    let network = world.get::<Network>(player);
+    let window = world.get::<Window>(player);
+
+    network.send(WindowItems {
+        window_id: window.id,
+        slots: window_items(game, world, player),
+    });
+    network.send(SetSlot {
+        window_id: CURSOR_WINDOW_ID,
+        slot: CURSOR_SLOT,
+        slot_data: window.clicks.cursor.clone(),
+    });
+}
+
+/// System which applies Click Window packets.
+#[fecs::system]
+pub fn handle_click_window(
+    game: &mut Game,
+    world: &mut World,
+    packet_buffers: &Arc<PacketBuffers>,
+    recipes: &RecipeRegistry,
+) {
+    packet_buffers
+        .received::<ClickWindow>()
+        .for_each_valid(world, |world, (player, packet)| {
+            handle_click(game, world, recipes, player, packet)
+        });
+}
+
+fn handle_click(
+    game: &mut Game,
+    world: &mut World,
+    recipes: &RecipeRegistry,
+    player: Entity,
+    packet: ClickWindow,
+) {
+    let creative = *world.get::<Gamemode>(player) == Gamemode::Creative;
+    let slot = packet.slot as i16;
+    let click = Click::from_packet(packet.mode, packet.button, slot);
+
+    let (outcome, accepted, containers, inventory_offset) = {
+        let mut window = world.get_mut::<Window>(player);
+        if window.awaiting_confirmation || window.id != packet.window_id {
+            return;
+        }
+        let window = &mut *window;
+        let mut inventory = world.get_mut::<Inventory>(player);
+
+        let (outcome, in_sync, inventory_offset) = if window.containers.is_empty() {
+            let mut contents = PlayerWindow::new(&mut *inventory, recipes);
+            let (outcome, in_sync) =
+                click_player_window(&mut window.clicks, &mut contents, click, &packet, creative);
+            (outcome, in_sync, None)
+        } else {
+            let mut contents = ContainerWindow::new(
+                &window.containers,
+                &mut game.block_entities,
+                &mut *inventory,
+            );
+            let (outcome, in_sync) =
+                apply_click(&mut window.clicks, &mut contents, click, &packet, creative);
+            (outcome, in_sync, Some(contents.container_slots()))
+        };
+
+        let accepted = in_sync && outcome.is_some();
+        if !accepted {
+            window.awaiting_confirmation = true;
+        }
+        (
+            outcome,
+            accepted,
+            window.containers.clone(),
+            inventory_offset,
+        )
+    };
+
+    world
+        .get::<Network>(player)
+        .send(ConfirmTransactionClientbound {
+            window_id: packet.window_id as i8,
+            action_number: packet.action_number,
+            accepted,
+        });
+    if !accepted {
+        log::debug!(
+            "Rejected click by {:?} (mode {}, button {}, slot {})",
+            player,
+            packet.mode,
+            packet.button,
+            slot
+        );
+        resend_window(game, world, player);
+    }
+
+    if let Some(outcome) = outcome {
+        for stack in outcome.dropped {
+            game.handle(
+                world,
+                ItemDropEvent {
+                    slot: None,
+                    stack,
+                    player,
+                },
+            );
+        }
+
+        // Slots below the containers are those of the player's
+        // inventory; changes to the others are shown to the
+        // other players viewing the containers.
+        let mut changed: SmallVec<[SlotIndex; 4]> = SmallVec::new();
+        for slot in outcome.changed {
+            match inventory_offset {
+                None => changed.push(slot),
+                Some(offset) if slot >= offset => {
+                    changed.push(slot - offset + SLOT_INVENTORY_OFFSET)
+                }
+                Some(_) => send_container_slot(game, world, player, &containers, slot),
+            }
+        }
+
+        if !changed.is_empty() {
+            game.handle(
+                world,
+                InventoryUpdateEvent {
+                    slots: changed.into_iter().collect(),
+                    player,
+                },
+            );
+        }
+    }
+}
+
+/// Applies a click to a window. Returns the outcome of the click, if
+/// it was valid, and whether the client's view of the clicked slot
+/// matched the server's.
+fn apply_click(
+    clicks: &mut ClickState,
+    contents: &mut impl WindowContents,
+    click: Option<Click>,
+    packet: &ClickWindow,
+    creative: bool,
+) -> (Option<ClickOutcome>, bool) {
+    // The client sends the contents it saw in the clicked slot,
+    // which tells whether its view of the window is correct.
+    let in_sync = match click {
+        Some(Click::Pickup { slot, .. }) | Some(Click::QuickMove { slot })
+            if slot < contents.slot_count() =>
+        {
+            contents.item(slot) == packet.clicked_item
+        }
+        _ => true,
+    };
+
+    let outcome = click.and_then(|click| clicks.click(contents, click, creative));
+    (outcome, in_sync)
+}
+
+/// Applies a click to a player's inventory, crafting
+/// with the items in their crafting grid as needed.
+fn click_player_window(
+    clicks: &mut ClickState,
+    contents: &mut PlayerWindow,
+    click: Option<Click>,
+    packet: &ClickWindow,
+    creative: bool,
+) -> (Option<ClickOutcome>, bool) {
+    let result = contents.item(SLOT_CRAFTING_OUTPUT);
+    let (mut outcome, in_sync) = apply_click(clicks, contents, click, packet, creative);
+
+    // Shift clicking a crafting result crafts as many times as possible.
+    if click
+        == Some(Click::QuickMove {
+            slot: SLOT_CRAFTING_OUTPUT,
+        })
+    {
+        while let Some(outcome) = outcome.as_mut() {
+            let next = contents.item(SLOT_CRAFTING_OUTPUT);
+            let same_result =
+                next.as_ref().map(|stack| stack.ty) == result.as_ref().map(|stack| stack.ty);
+            if next.is_none() || !same_result {
+                break;
+            }
+            match clicks.click(contents, click.unwrap(), creative) {
+                Some(more) if !more.changed.is_empty() => outcome.changed.extend(more.changed),
+                _ => break,
+            }
+        }
+    }
+
+    if let Some(outcome) = outcome.as_mut() {
+        for slot in contents.crafting_changed.drain(..) {
+            if !outcome.changed.contains(&slot) {
+                outcome.changed.push(slot);
+            }
+        }
+    }
+    (outcome, in_sync)
+}
+
+/// Sends the contents of a changed container slot in a player's
+/// window to the other players viewing the same container.
+fn send_container_slot(
+    game: &Game,
+    world: &World,
+    player: Entity,
+    containers: &[BlockPosition],
+    mut slot: SlotIndex,
+) {
+    let mut changed = None;
+    for pos in containers {
+        let block_entity = match game.block_entities.get(*pos) {
+            Some(block_entity) => block_entity,
+            None => continue,
+        };
+        let size = container_size(block_entity);
+        if slot < size {
+            let item = block_entity
+                .container()
+                .and_then(|container| container.item(slot));
+            changed = Some((*pos, slot, item));
+            break;
+        }
+        slot -= size;
+    }
+    let (pos, slot, item) = match changed {
+        Some(changed) => changed,
+        None => return,
+    };
+
+    for (viewer, window) in <Read<Window>>::query().iter_entities(world.inner()) {
+        if viewer == player {
+            continue;
+        }
+        // Find where the container's slots start in the viewer's window.
+        let mut offset = 0;
+        for container in &window.containers {
+            if *container == pos {
+                world.get::<Network>(viewer).send(SetSlot {
+                    window_id: window.id as i8,
+                    slot: (offset + slot) as i16,
+                    slot_data: item.clone(),
+                });
+                break;
+            }
+            offset += game
+                .block_entities
+                .get(*container)
+                .map(container_size)
+                .unwrap_or(0);
+        }
+    }
+}
+
+/// System which resumes handling a player's clicks once
+/// their client acknowledges a rejected click.
+#[fecs::system]
+pub fn handle_confirm_transaction(world: &mut World, packet_buffers: &Arc<PacketBuffers>) {
+    packet_buffers
+        .received::<ConfirmTransactionServerbound>()
+        .for_each_valid(world, |world, (player, packet)| {
+            let mut window = world.get_mut::<Window>(player);
+            if packet.window_id == window.id && packet.accepted {
+                window.awaiting_confirmation = false;
+            }
+        });
+}
+
+/// System which handles players closing their window.
+#[fecs::system]
+pub fn handle_close_window(
+    game: &mut Game,
+    world: &mut World,
+    packet_buffers: &Arc<PacketBuffers>,
+) {
+    packet_buffers
+        .received::<CloseWindowServerbound>()
+        .for_each_valid(world, |world, (player, _)| {
+            close_window(game, world, player)
+        });
+}
+
+/// Closes the window of a player who leaves, so
+/// that the items on their cursor are not lost.
+#[fecs::event_handler]
+pub fn on_player_leave_close_window(event: &PlayerLeaveEvent, game: &mut Game, world: &mut World) {
+    close_window(game, world, event.player);
+}
+
+/// Closes a player's window, dropping the items on their cursor and
+/// returning those in their crafting grid to their inventory.
+pub fn close_window(game: &mut Game, world: &mut World, player: Entity) {
+    let mut dropped: SmallVec<[ItemStack; 5]> = SmallVec::new();
+    let mut changed: SmallVec<[SlotIndex; 8]> = SmallVec::new();
+
+    let containers = {
+        let mut window = world.get_mut::<Window>(player);
+        dropped.extend(window.clicks.cursor.take());
+        let containers = std::mem::take(&mut window.containers);
+        *window = Window {
+            last_id: window.last_id,
+            ..Window::default()
+        };
+
+        let mut inventory = world.get_mut::<Inventory>(player);
+        if inventory.clear_item_at(SLOT_CRAFTING_OUTPUT).is_some() {
+            changed.push(SLOT_CRAFTING_OUTPUT);
+        }
+        for slot in SLOT_CRAFTING_INPUT_X0_Y0..=SLOT_CRAFTING_INPUT_X1_Y1 {
+            if let Some(mut stack) = inventory.clear_item_at(slot) {
+                changed.push(slot);
+                let (slots, remaining) = inventory.collect_item(stack.clone());
+                changed.extend(slots);
+                if remaining > 0 {
+                    stack.amount = remaining;
+                    dropped.push(stack);
+                }
+            }
+        }
+        containers
+    };
+
+    if !containers.is_empty() {
+        update_viewers(game, world, &containers, false);
+    }
+
+    for stack in dropped {
+        game.handle(
+            world,
+            ItemDropEvent {
+                slot: None,
+                stack,
+                player,
+            },
+        );
+    }
+
+    if !changed.is_empty() {
+        game.handle(
+            world,
+            InventoryUpdateEvent {
+                slots: changed.into_iter().collect(),
+                player,
+            },
+        );
+    }
+}
@@ -1,7 +1,10 @@
 //! Defines the event handlers.
+use crate::shutdown::shutdown_request;
+use feather_server_chat::*;
 use feather_server_chunk::*;
 use feather_server_entity::*;
 use feather_server_lighting::*;
+use feather_server_physics::*;
 use feather_server_player::*;
 use feather_server_util::*;
 use feather_server_weather::*;
@@ -22,10 +25,14 @@ pub fn build_event_handlers() -> EventHandlers {
         on_block_update_notify_adjacent,
         on_block_update_broadcast,
         on_block_update_notify_lighting_worker,
+        on_block_update_log_change,
+        on_block_update_remove_block_entity,
+        on_block_interact_log_container_access,
 
         on_entity_despawn_remove_chunk_holder,
         on_entity_despawn_update_chunk_entities,
         on_entity_despawn_broadcast_despawn,
+        on_entity_despawn_stop_spectating,
 
         on_entity_spawn_update_chunk_entities,
         on_entity_spawn_send_to_clients,
@@ -37,17 +44,29 @@ pub fn build_event_handlers() -> EventHandlers {
         on_entity_client_remove_update_last_known_positions,
 
         on_player_join_send_join_game,
+        on_player_join_send_recipe_book,
+        on_player_join_send_advancements,
         on_player_join_send_existing_entities,
         on_player_join_send_time,
+        on_player_join_send_difficulty,
         on_player_join_trigger_chunk_cross,
         on_player_join_send_weather,
         on_player_join_broadcast_join_message,
+        on_player_join_send_resource_pack,
+        on_player_join_send_brand,
+        on_player_join_send_commands,
+        on_player_join_send_permission_level,
+        on_player_join_send_scoreboard,
 
+        on_resource_pack_response_kick_if_required,
+
+        on_player_leave_close_window,
         on_player_leave_save_data,
+        on_player_leave_cancel_confirmations,
+        on_player_leave_remove_ender_pearls,
 
         on_chunk_load_notify_lighting_worker,
         on_chunk_load_send_to_clients,
-        on_chunk_load_queue_for_saving,
 
         on_chunk_holder_release_unload_chunk,
 
@@ -56,22 +75,60 @@ pub fn build_event_handlers() -> EventHandlers {
         on_chunk_cross_update_entities,
 
         on_chunk_send_join_player,
+        on_chunk_send_send_spawners,
 
         on_inventory_update_send_set_slot,
         on_inventory_update_broadcast_equipment_update,
+        on_inventory_update_update_armor_attributes,
+        on_inventory_update_unlock_recipes,
+        on_inventory_update_trigger_advancements,
 
         on_player_animation_broadcast_animation,
 
+        on_player_death_send_death_message,
+        on_entity_death_update_scores,
+        on_entity_death_update_statistics,
+        on_entity_death_trigger_advancements,
+
+        on_gamemode_update_stop_spectating,
+
         on_item_drop_spawn_item_entity,
 
         on_item_collect_broadcast,
 
         on_weather_change_broadcast_weather,
 
+        on_game_rule_change_send_time,
+
+        on_difficulty_change_broadcast,
+
+        on_player_chat_enforce_mute,
+
         on_chat_broadcast,
 
+        on_command_dispatch,
+
         on_entity_land_remove_falling_block,
 
+        on_projectile_hit_handle_arrow,
+        on_projectile_hit_handle_snowball,
+        on_projectile_hit_handle_egg,
+        on_projectile_hit_handle_ender_pearl,
+        on_projectile_hit_handle_trident,
+
+        entity_damage_request,
+        death_drops_request,
+        block_destroy_request,
+        explosion_request,
+        lightning_strike_request,
+        summon_request,
+        tnt_prime_request,
+        weather_request,
+        shutdown_request,
+        save_request,
+        auto_save_request,
+        view_distance_request,
+
         load_chunk_request,
 
         release_chunk_request,
@@ -1,14 +1,20 @@
 //! Startup logic.
 
+use crate::shutdown::Shutdown;
 use crate::{event_handlers, systems};
 use anyhow::Context;
 use feather_core::anvil::level::{LevelData, LevelGeneratorType};
-use feather_core::util::ChunkPosition;
+use feather_core::util::{ChunkPosition, Difficulty};
+use feather_server_chat::ConsoleInput;
 use feather_server_chunk::{chunk_worker, ChunkWorkerHandle};
 use feather_server_config::DEFAULT_CONFIG_STR;
 use feather_server_network::NetworkIoManager;
 use feather_server_packet_buffer::PacketBuffers;
-use feather_server_types::{Config, Game, RunningTasks};
+use feather_server_types::{
+    AdvancementRegistry, BlockLog, Config, FilePermissions, Game, GameRules, Maps, Moderation,
+    NetworkStats, OperatorPermissions, Operators, Permissions, PermissionsMode, RecipeRegistry,
+    RunningTasks, Scoreboard, TickTimes, Time, ADVANCEMENTS_DIR, RECIPES_DIR,
+};
 use feather_server_worldgen::{
     ComposableGenerator, EmptyWorldGenerator, SuperflatWorldGenerator, WorldGenerator,
 };
@@ -20,12 +26,16 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpListener;
+use tokio::net::{TcpListener, UdpSocket};
 use tokio::{io, runtime};
 
+/// Directory holding the vanilla data pack, from which recipes are loaded.
+const DATA_DIR: &str = "data";
+
 /// Intializes the server.
 pub async fn init(
     runtime: runtime::Handle,
+    shutdown: Shutdown,
 ) -> anyhow::Result<(Executor, Arc<OwnedResources>, World)> {
     let mut executor = systems::build_executor();
     let mut event_handlers = event_handlers::build_event_handlers();
@@ -46,8 +56,62 @@ pub async fn init(
         .await
         .context("Failed to load level file (is your world directory corrupted?)")?;
 
+    log::info!("Loading ban and mute lists");
+    let moderation =
+        Moderation::load(Path::new(".")).context("Failed to load ban and mute lists")?;
+
+    log::info!("Loading operator list");
+    let operators = Operators::load(Path::new(".")).context("Failed to load operator list")?;
+    let permissions = match config.server.permissions {
+        PermissionsMode::Operators => Permissions::new(OperatorPermissions),
+        PermissionsMode::File => {
+            log::info!("Loading permissions file");
+            Permissions::new(
+                FilePermissions::load(Path::new(".")).context("Failed to load permissions file")?,
+            )
+        }
+    };
+
+    if config.block_log.enabled {
+        log::info!("Loading block log");
+    }
+    let block_log = BlockLog::load(Path::new(&config.block_log.file), config.block_log.enabled)
+        .context("Failed to load block log")?;
+
+    log::info!("Loading recipes");
+    let recipes = RecipeRegistry::load(Path::new(DATA_DIR)).context("Failed to load recipes")?;
+    if recipes.is_empty() {
+        log::warn!(
+            "No recipes found in {}/{}; crafting will be unavailable",
+            DATA_DIR,
+            RECIPES_DIR
+        );
+    } else {
+        log::info!("Loaded {} recipes", recipes.len());
+    }
+
+    log::info!("Loading advancements");
+    let advancements =
+        AdvancementRegistry::load(Path::new(DATA_DIR)).context("Failed to load advancements")?;
+    if advancements.is_empty() {
+        log::warn!(
+            "No advancements found in {}/{}; advancements will be unavailable",
+            DATA_DIR,
+            ADVANCEMENTS_DIR
+        );
+    } else {
+        log::info!("Loaded {} advancements", advancements.len());
+    }
+
+    log::info!("Loading maps");
+    let maps = Maps::load(Path::new(&config.world.name))
+        .await
+        .context("Failed to load maps")?;
+
     let cworker_handle = create_cworker_handle(&config, &level);
 
+    let time = Time::new(level.time.max(0) as u64, level.day_time.max(0) as u64);
+    let game_rules = GameRules::load(&level.game_rules);
     let mut game = Game {
         chunk_map: Default::default(),
         tick_count: 0,
@@ -55,19 +119,25 @@ pub async fn init(
         config: Arc::clone(&config),
         level,
         chunk_entities: Default::default(),
-        time: Default::default(),
+        block_entities: Default::default(),
+        time,
+        game_rules,
+        block_changes: Default::default(),
         running_tasks: RunningTasks::new(runtime),
         event_handlers: Arc::new(event_handlers),
         resources: Arc::new(Default::default()), // we override this momentarily
         rng: Default::default(),
         bump: Default::default(),
-        player_count: Arc::new(Default::default()),
+        online_players: Arc::new(Default::default()),
     };
     let packet_buffers = Arc::new(PacketBuffers::new());
 
     log::info!("Queueing spawn chunks for loading");
     load_spawn_chunks(&mut game, &mut world, &cworker_handle);
 
+    let console =
+        ConsoleInput::start(&game, &mut world).context("Failed to start the console thread")?;
+
     log::info!("Creating RSA keypair");
     feather_server_network::init();
 
@@ -86,6 +156,15 @@ pub async fn init(
         cworker_handle,
         networking_handle,
         packet_buffers,
+        moderation,
+        operators,
+        permissions,
+        block_log,
+        recipes,
+        advancements,
+        maps,
+        console,
+        shutdown,
     );
 
     Ok((executor, resources, world))
@@ -168,9 +247,11 @@ fn generate_level(config: &Config) -> LevelData {
         clear_weather_time: 0,
         data_version: 0,
         day_time: 0,
-        difficulty: 0,
+        // New worlds start on easy, as in vanilla.
+        difficulty: Difficulty::Easy.id() as i8,
         difficulty_locked: 0,
-        game_type: 0,
+        game_type: i32::from(config.server.default_gamemode.id()),
+        game_rules: Default::default(),
         hardcore: false,
         initialized: false,
         last_played: 0,
@@ -246,10 +327,22 @@ async fn create_networking_handle(
 
     log::info!("Listening on {}", addr);
 
+    let query_socket = if config.query.enabled {
+        let addr = format!("{}:{}", config.server.address, config.query.port);
+        let socket = UdpSocket::bind(&addr)
+            .await
+            .context("failed to bind to the query port")?;
+        log::info!("Answering queries on {}", addr);
+        Some(socket)
+    } else {
+        None
+    };
+
     Ok(NetworkIoManager::start(
         socket,
+        query_socket,
         config,
-        Arc::clone(&game.player_count),
+        Arc::clone(&game.online_players),
         Arc::new(server_icon),
         packet_buffers,
     ))
@@ -294,19 +387,41 @@ fn load_spawn_chunks(game: &mut Game, world: &mut World, cworker_handle: &ChunkW
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_resources(
     resources: OwnedResources,
     game: Game,
     cworker_handle: ChunkWorkerHandle,
     networking_handle: NetworkIoManager,
     packet_buffers: Arc<PacketBuffers>,
+    moderation: Moderation,
+    operators: Operators,
+    permissions: Permissions,
+    block_log: BlockLog,
+    recipes: RecipeRegistry,
+    advancements: AdvancementRegistry,
+    maps: Maps,
+    console: ConsoleInput,
+    shutdown: Shutdown,
 ) -> Arc<OwnedResources> {
     let resources = {
         let resources = resources
             .with(game)
             .with(cworker_handle)
             .with(networking_handle)
-            .with(packet_buffers);
+            .with(packet_buffers)
+            .with(moderation)
+            .with(operators)
+            .with(permissions)
+            .with(block_log)
+            .with(recipes)
+            .with(advancements)
+            .with(maps)
+            .with(console)
+            .with(shutdown)
+            .with(Scoreboard::default())
+            .with(NetworkStats::default())
+            .with(TickTimes::default());
         Arc::new(resources)
     };
 
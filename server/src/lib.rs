@@ -2,15 +2,18 @@
 //!
 //! For extensive developer documentation, please see [the book](https://feather-rs.github.io/book).
 
+use crate::shutdown::Shutdown;
 use feather_server_chunk::ChunkWorkerHandle;
 use feather_server_lighting::LightingWorkerHandle;
-use feather_server_types::{Game, TPS};
+use feather_server_network::NetworkIoManager;
+use feather_server_types::{Game, Maps, TickTimes, TPS};
 use fecs::{Executor, OwnedResources, ResourcesProvider, World};
 use spin_sleep::LoopHelper;
 use std::ops::Deref;
 use std::panic::AssertUnwindSafe;
 use std::process::exit;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::runtime;
 
 mod event_handlers;
@@ -27,7 +30,13 @@ struct FullState {
 
 pub async fn main(runtime: runtime::Handle) {
     log::info!("Starting Feather; please wait");
-    let (executor, resources, world) = match init::init(runtime).await {
+
+    // Channels used by the shutdown handler thread
+    // and `/stop` to notify server thread of shutdown
+    let (shutdown_tx, shutdown_rx) = crossbeam::bounded(1);
+    let shutdown = Shutdown::new(shutdown_tx);
+
+    let (executor, resources, world) = match init::init(runtime, shutdown.clone()).await {
         Ok(res) => res,
         Err(e) => {
             // Logging might not have been initialized yet - init it and ignore errors
@@ -38,10 +47,7 @@ pub async fn main(runtime: runtime::Handle) {
         }
     };
 
-    // Channels used by the shutdown handler thread
-    // to notify server thread of shutdown
-    let (shutdown_tx, shutdown_rx) = crossbeam::bounded(1);
-    shutdown::init(shutdown_tx);
+    shutdown::init(shutdown);
 
     let state = FullState {
         resources,
@@ -106,9 +112,14 @@ fn run_loop(state: &mut FullState) {
         loop_helper.loop_start();
 
         // Execute all systems
+        let start = Instant::now();
         state
             .executor
             .execute(state.resources.deref(), &mut state.world);
+        state
+            .resources
+            .get_mut::<TickTimes>()
+            .record(start.elapsed());
         // Clean up world
         state.world.defrag(Some(256)); // should this be done at an interval rate?
 
@@ -116,9 +127,10 @@ fn run_loop(state: &mut FullState) {
     }
 }
 
+/// Runs the shutdown sequence once the game loop has stopped.
 async fn shut_down(resources: &OwnedResources, world: &mut World) -> anyhow::Result<()> {
-    log::info!("Disconnecting players");
-    shutdown::disconnect_players(&world)?;
+    log::info!("Saving player data and disconnecting players");
+    shutdown::disconnect_players(&*resources.get::<Game>(), &world)?;
     log::info!("Shutting down workers");
     shutdown::shut_down_workers(
         &*resources.get::<Game>(),
@@ -132,8 +144,10 @@ async fn shut_down(resources: &OwnedResources, world: &mut World) -> anyhow::Res
     )?;
     log::info!("Saving level.dat");
     shutdown::save_level(&mut *resources.get_mut::<Game>()).await?;
-    log::info!("Saving player data");
-    shutdown::save_player_data(&*resources.get::<Game>(), &world)?;
+    log::info!("Saving maps");
+    shutdown::save_maps(&*resources.get::<Game>(), &mut *resources.get_mut::<Maps>())?;
+    log::info!("Stopping network listeners");
+    shutdown::stop_listening(&*resources.get::<NetworkIoManager>())?;
     log::info!("Waiting for tasks to finish");
     shutdown::wait_for_task_completion(&*resources.get::<Game>()).await?;
 
@@ -5,24 +5,55 @@ use feather_core::text::{Text, TextRoot};
 use feather_server_chunk::chunk_worker::Request;
 use feather_server_chunk::{save_chunk_at, ChunkWorkerHandle};
 use feather_server_lighting::LightingWorkerHandle;
-use feather_server_types::{Game, Network, Player};
+use feather_server_network::NetworkIoManager;
+use feather_server_types::{Game, Maps, Network, ShutdownRequest};
 use fecs::{IntoQuery, Read, World};
 use tokio::fs::File;
 
-pub fn init(tx: crossbeam::Sender<()>) {
+/// Resource used to stop the game loop, which
+/// is followed by the shutdown sequence.
+#[derive(Clone)]
+pub struct Shutdown(crossbeam::Sender<()>);
+
+impl Shutdown {
+    pub fn new(tx: crossbeam::Sender<()>) -> Self {
+        Self(tx)
+    }
+
+    /// Stops the game loop at the end of the current tick.
+    /// Does nothing if a shutdown was already requested.
+    pub fn request(&self) {
+        let _ = self.0.try_send(());
+    }
+}
+
+/// Shuts the server down on Ctrl-C, as well as
+/// on `SIGTERM` and `SIGHUP` on Unix.
+pub fn init(shutdown: Shutdown) {
     ctrlc::set_handler(move || {
-        tx.send(()).unwrap();
+        log::info!("Received a shutdown signal");
+        shutdown.request();
     })
     .unwrap();
 }
 
-pub fn disconnect_players(world: &World) -> anyhow::Result<()> {
-    <Read<Network>>::query().for_each(world.inner(), |network| {
+/// Handles a `ShutdownRequest`.
+#[fecs::event_handler]
+pub fn shutdown_request(_event: &ShutdownRequest, shutdown: &Shutdown) {
+    shutdown.request();
+}
+
+/// Kicks all players, saving the data of each as they are disconnected.
+pub fn disconnect_players(game: &Game, world: &World) -> anyhow::Result<()> {
+    <Read<Network>>::query().for_each_entities(world.inner(), |(player, network)| {
+        feather_server_chunk::save_player_data(game, world, player);
+
         let packet = DisconnectPlay {
             reason: TextRoot::from(Text::from("Server closed")).into(),
         };
 
         network.send(packet);
+        network.flush();
     });
 
     Ok(())
@@ -47,9 +78,10 @@ pub fn save_chunks(
 }
 
 pub async fn save_level(game: &mut Game) -> anyhow::Result<()> {
-    // Sync world time + level time
-    let time = game.time.world_age() as i64;
-    game.level.time = time;
+    // Sync world time + level time, and gamerules
+    game.level.time = game.time.world_age() as i64;
+    game.level.day_time = game.time.day_time() as i64;
+    game.game_rules.save(&mut game.level.game_rules);
 
     let level_path = format!("{}/{}", game.config.world.name, "level.dat");
 
@@ -61,11 +93,13 @@ pub async fn save_level(game: &mut Game) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn save_player_data(game: &Game, world: &World) -> anyhow::Result<()> {
-    <Read<Player>>::query().for_each_entities(&world.inner(), |(player, _)| {
-        feather_server_chunk::save_player_data(game, world, player);
-    });
+pub fn save_maps(game: &Game, maps: &mut Maps) -> anyhow::Result<()> {
+    maps.save(&game.running_tasks);
+    Ok(())
+}
 
+pub fn stop_listening(networking_handle: &NetworkIoManager) -> anyhow::Result<()> {
+    networking_handle.stop_listening();
     Ok(())
 }
 
@@ -2,6 +2,7 @@
 
 use fecs::Executor;
 
+use feather_server_chat as chat;
 use feather_server_chunk as chunk_logic;
 use feather_server_entity as entity;
 use feather_server_physics as physics;
@@ -14,28 +15,84 @@ pub fn build_executor() -> Executor {
     Executor::new()
         .with(player::poll_player_disconnect)
         .with(player::poll_new_clients)
+        .with(physics::entity_collision)
         .with(physics::entity_physics)
+        .with(physics::projectile_physics)
         .with(player::handle_movement_packets)
+        .with(player::handle_entity_action)
+        .with(player::handle_player_abilities)
+        .with(player::handle_client_status)
+        .with(player::update_swimming)
+        .with(player::update_gliding)
+        .with(player::update_spectating)
         .with(player::handle_creative_inventory_action)
+        .with(player::handle_confirm_transaction)
+        .with(player::handle_click_window)
+        .with(player::handle_close_window)
+        .with(player::close_unreachable_containers)
+        .with(player::handle_recipe_book_data)
+        .with(player::handle_craft_recipe_request)
         .with(player::handle_held_item_change)
         .with(player::handle_animation)
+        .with(player::handle_use_entity)
         .with(player::handle_player_block_placement)
         .with(player::handle_player_use_item)
+        .with(player::handle_edit_book)
         .with(player::handle_player_digging)
+        .with(player::finish_eating)
+        .with(player::end_spin_attacks)
         .with(player::handle_chat)
+        .with(player::handle_tab_complete)
+        .with(player::handle_keep_alive)
+        .with(player::handle_resource_pack_status)
+        .with(player::handle_plugin_message)
+        .with(chat::dispatch_console_commands)
+        .with(chat::expire_punishments)
+        .with(chat::expire_confirmations)
+        .with(chat::flush_block_log)
         .with(weather::update_weather)
+        .with(weather::strike_thunderstorm_lightning)
+        .with(entity::item::item_merge)
         .with(entity::item::item_collect)
+        .with(entity::arrow::arrow_pickup)
+        .with(entity::update_firework_rockets)
+        .with(entity::update_tridents)
+        .with(entity::update_primed_tnt)
+        .with(entity::sheep::sheep_eat_grass)
         .with(chunk_logic::chunk_load)
         .with(chunk_logic::chunk_unload)
         .with(chunk_logic::chunk_optimize)
         .with(player::check_crossed_chunks)
+        .with(player::adjust_view_distances)
+        .with(player::update_maps)
         .with(player::broadcast_keepalive)
+        .with(player::broadcast_latency)
         .with(entity::broadcast_movement)
         .with(entity::broadcast_velocity)
+        .with(entity::broadcast_metadata)
         .with(entity::falling_block::spawn_falling_blocks)
-        .with(chunk_logic::chunk_save)
+        .with(entity::creeper::update_creeper_fuses)
+        .with(entity::break_unsupported_blocks)
+        .with(util::flow_fluids)
+        .with(entity::despawn_lightning_bolts)
+        .with(entity::despawn_old_entities)
+        .with(entity::despawn_hostiles_on_peaceful)
+        .with(util::tick_effects)
+        .with(util::apply_worn_effects)
+        .with(util::update_fall_distance)
+        .with(util::update_air)
+        .with(util::update_burning)
+        .with(util::apply_environmental_damage)
+        .with(util::update_sprinting)
+        .with(util::update_hunger)
+        .with(util::update_criteria_scores)
+        .with(player::update_statistics)
+        .with(player::trigger_location_advancements)
+        .with(chunk_logic::auto_save)
         .with(game::reset_bump_allocators)
         .with(game::increment_tick_count)
         .with(util::increment_time)
+        .with(game::flush_network)
+        .with(game::update_network_stats)
         .with(entity::previous_position_velocity_reset) // should be at end
 }
@@ -77,7 +77,10 @@ impl Test {
             config: Arc::new(Default::default()),
             level: Default::default(),
             chunk_entities: Default::default(),
+            block_entities: Default::default(),
             time: Default::default(),
+            game_rules: Default::default(),
+            block_changes: Default::default(),
             running_tasks: RunningTasks::new(
                 tokio::runtime::Builder::new()
                     .basic_scheduler()
@@ -90,7 +93,7 @@ impl Test {
             resources: Arc::new(Default::default()),
             rng: Default::default(),
             bump: Default::default(),
-            player_count: Arc::new(Default::default()),
+            online_players: Arc::new(Default::default()),
         };
         resources.insert(cworker_handle);
 
@@ -197,10 +200,15 @@ impl Test {
                 entity: BaseEntityData::new(position, vec3(0.0, 0.0, 0.0)),
                 gamemode: 1,
                 inventory: vec![],
+                ..Default::default()
             },
+            first_join: false,
             position,
+            statistics: Default::default(),
+            advancements: Default::default(),
             sender: server_tx,
             receiver: server_rx,
+            counters: Default::default(),
             entity,
         };
         feather_server_player::create(&mut self.game, &mut self.world, info);
@@ -261,6 +269,14 @@ impl Test {
         Self::remove_player_buffered_packet(tracked)
     }
 
+    /// Discards all packets sent to `player` so far.
+    pub fn clear_sent(&mut self, player: Entity) -> &mut Self {
+        let tracked = self.tracked_player(player);
+        Self::update_player(tracked);
+        tracked.buffered_sent_packets.clear();
+        self
+    }
+
     fn remove_player_buffered_packet<P>(player: &mut TrackedPlayer) -> Option<P>
     where
         P: Packet,
@@ -299,6 +315,7 @@ impl Test {
                 ServerToWorkerMessage::SendPacket(packet) => {
                     player.buffered_sent_packets.push(packet)
                 }
+                ServerToWorkerMessage::Flush => (),
                 ServerToWorkerMessage::Disconnect => player.disconnected = true,
             }
         }
@@ -0,0 +1,518 @@
+//! Advancements, loaded from the vanilla data pack, and
+//! each player's progress towards them.
+//!
+//! A player's progress is stored in `advancements/<uuid>.json`
+//! in the world directory, in the vanilla format.
+
+use crate::recipes::Ingredient;
+use crate::RunningTasks;
+use feather_core::biomes::Biome;
+use feather_core::items::{Item, ItemStack};
+use feather_core::network::packets::{AdvancementData, AdvancementDisplayData};
+use feather_core::text::{Color, Text, TextRoot};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+mod loading;
+
+pub use loading::*;
+
+/// Data version written to advancement files, that of 1.13.2.
+const DATA_VERSION: i32 = 1631;
+
+/// The frame drawn around an advancement's icon, which
+/// also determines how its completion is announced.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AdvancementFrame {
+    Task,
+    Challenge,
+    Goal,
+}
+
+impl AdvancementFrame {
+    pub fn id(self) -> i32 {
+        match self {
+            AdvancementFrame::Task => 0,
+            AdvancementFrame::Challenge => 1,
+            AdvancementFrame::Goal => 2,
+        }
+    }
+
+    /// Returns the translation key of the chat
+    /// message announcing that a player made it.
+    pub fn announcement_key(self) -> &'static str {
+        match self {
+            AdvancementFrame::Task => "chat.type.advancement.task",
+            AdvancementFrame::Challenge => "chat.type.advancement.challenge",
+            AdvancementFrame::Goal => "chat.type.advancement.goal",
+        }
+    }
+
+    /// Returns the color of the advancement's title in chat.
+    pub fn color(self) -> Color {
+        match self {
+            AdvancementFrame::Challenge => Color::DarkPurple,
+            AdvancementFrame::Task | AdvancementFrame::Goal => Color::Green,
+        }
+    }
+}
+
+/// How an advancement is shown in the advancements screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdvancementDisplay {
+    pub title: Text,
+    pub description: Text,
+    pub icon: ItemStack,
+    pub frame: AdvancementFrame,
+    /// Background texture of the tab, for root advancements.
+    pub background: Option<String>,
+    pub show_toast: bool,
+    pub announce_to_chat: bool,
+    /// Hidden advancements are only shown once made.
+    pub hidden: bool,
+    /// Position in the tab, computed once all advancements are loaded.
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Something which happened to a player, which may
+/// achieve a criterion of an advancement.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Trigger<'a> {
+    /// The player's inventory changed, and now holds these items.
+    InventoryChanged(&'a [Item]),
+    /// The player killed an entity of the given type.
+    KilledEntity(&'a str),
+    /// The player was killed by an entity of the given type.
+    KilledByEntity(&'a str),
+    /// The player is in the given biome. Triggered every second.
+    Location(Biome),
+    /// The player finished eating or drinking an item.
+    ConsumedItem(Item),
+}
+
+/// A criterion of an advancement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Criterion {
+    /// Achieved by holding an item matching each ingredient.
+    InventoryChanged(Vec<Ingredient>),
+    /// Achieved by killing an entity of the given
+    /// type, or any entity if there is none.
+    PlayerKilledEntity(Option<String>),
+    /// Achieved by being killed by an entity of the
+    /// given type, or any entity if there is none.
+    EntityKilledPlayer(Option<String>),
+    /// Achieved by being in the given biome, or anywhere.
+    Location(Option<Biome>),
+    /// Achieved by consuming an item matching the ingredient, or any item.
+    ConsumeItem(Option<Ingredient>),
+    /// Never achieved through gameplay. These are the `minecraft:impossible`
+    /// criteria as well as those with unsupported triggers or conditions.
+    Other,
+}
+
+impl Criterion {
+    /// Returns whether the trigger achieves this criterion.
+    pub fn is_met(&self, trigger: &Trigger) -> bool {
+        match (self, trigger) {
+            (Criterion::InventoryChanged(ingredients), Trigger::InventoryChanged(items)) => {
+                ingredients
+                    .iter()
+                    .all(|ingredient| items.iter().any(|item| ingredient.matches(*item)))
+            }
+            (Criterion::PlayerKilledEntity(ty), Trigger::KilledEntity(victim)) => {
+                ty.as_ref().map_or(true, |ty| ty == victim)
+            }
+            (Criterion::EntityKilledPlayer(ty), Trigger::KilledByEntity(killer)) => {
+                ty.as_ref().map_or(true, |ty| ty == killer)
+            }
+            (Criterion::Location(biome), Trigger::Location(at)) => {
+                biome.map_or(true, |biome| biome == *at)
+            }
+            (Criterion::ConsumeItem(ingredient), Trigger::ConsumedItem(item)) => ingredient
+                .as_ref()
+                .map_or(true, |ingredient| ingredient.matches(*item)),
+            _ => false,
+        }
+    }
+}
+
+/// An advancement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advancement {
+    /// Identifier of the advancement, such as `minecraft:story/mine_stone`.
+    pub id: String,
+    pub parent: Option<String>,
+    /// How the advancement is shown, if it is shown at all.
+    pub display: Option<AdvancementDisplay>,
+    pub criteria: BTreeMap<String, Criterion>,
+    /// The advancement is made once at least one
+    /// criterion of each requirement is achieved.
+    pub requirements: Vec<Vec<String>>,
+}
+
+impl Advancement {
+    /// Returns whether the achieved criteria make this advancement.
+    pub fn is_done_with(&self, achieved: &BTreeMap<String, u64>) -> bool {
+        !self.requirements.is_empty()
+            && self.requirements.iter().all(|requirement| {
+                requirement
+                    .iter()
+                    .any(|criterion| achieved.contains_key(criterion))
+            })
+    }
+
+    /// Returns the advancement as sent in the Advancements packet.
+    pub fn to_data(&self) -> AdvancementData {
+        AdvancementData {
+            parent: self.parent.clone(),
+            display: self.display.as_ref().map(|display| AdvancementDisplayData {
+                title: TextRoot::from(display.title.clone()).into(),
+                description: TextRoot::from(display.description.clone()).into(),
+                icon: Some(display.icon.clone()),
+                frame: display.frame.id(),
+                background: display.background.clone(),
+                show_toast: display.show_toast,
+                hidden: display.hidden,
+                x: display.x,
+                y: display.y,
+            }),
+            criteria: self.criteria.keys().cloned().collect(),
+            requirements: self.requirements.clone(),
+        }
+    }
+}
+
+/// Resource storing all advancements.
+#[derive(Debug, Default)]
+pub struct AdvancementRegistry {
+    advancements: BTreeMap<String, Advancement>,
+}
+
+impl AdvancementRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, advancement: Advancement) {
+        self.advancements
+            .insert(advancement.id.clone(), advancement);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Advancement> {
+        self.advancements.get(id)
+    }
+
+    /// Returns all advancements, ordered by identifier.
+    pub fn iter(&self) -> impl Iterator<Item = &Advancement> {
+        self.advancements.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.advancements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.advancements.is_empty()
+    }
+
+    /// Returns whether a player with the given progress is
+    /// shown an advancement. Advancements without a display
+    /// are never shown, and hidden ones only once made.
+    /// An advancement is only shown along with its parent.
+    pub fn is_visible(&self, advancement: &Advancement, progress: &AdvancementProgress) -> bool {
+        let display = match &advancement.display {
+            Some(display) => display,
+            None => return false,
+        };
+        if display.hidden && !progress.is_done(&advancement.id) {
+            return false;
+        }
+        match advancement
+            .parent
+            .as_ref()
+            .and_then(|parent| self.get(parent))
+        {
+            Some(parent) => self.is_visible(parent, progress),
+            None => true,
+        }
+    }
+}
+
+/// Component storing a player's progress towards advancements.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdvancementProgress {
+    /// The criteria achieved for each advancement, along with the time
+    /// they were achieved, in milliseconds since the UNIX epoch.
+    criteria: BTreeMap<String, BTreeMap<String, u64>>,
+    done: BTreeSet<String>,
+}
+
+impl AdvancementProgress {
+    /// Returns the criteria achieved for an advancement.
+    pub fn achieved(&self, advancement: &str) -> Option<&BTreeMap<String, u64>> {
+        self.criteria.get(advancement)
+    }
+
+    pub fn is_achieved(&self, advancement: &str, criterion: &str) -> bool {
+        self.achieved(advancement)
+            .map_or(false, |criteria| criteria.contains_key(criterion))
+    }
+
+    /// Marks a criterion as achieved at the given time, returning
+    /// `false` if it had already been.
+    pub fn achieve(&mut self, advancement: &str, criterion: &str, time: u64) -> bool {
+        let criteria = self.criteria.entry(advancement.to_owned()).or_default();
+        if criteria.contains_key(criterion) {
+            return false;
+        }
+        criteria.insert(criterion.to_owned(), time);
+        true
+    }
+
+    pub fn is_done(&self, advancement: &str) -> bool {
+        self.done.contains(advancement)
+    }
+
+    pub fn set_done(&mut self, advancement: &str) {
+        self.done.insert(advancement.to_owned());
+    }
+
+    /// Loads a player's progress, returning no progress
+    /// if the player has no advancements file.
+    pub async fn load(world_dir: &Path, uuid: Uuid) -> anyhow::Result<Self> {
+        match tokio::fs::read(file_path(world_dir, uuid)).await {
+            Ok(bytes) => Self::from_json(&bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes a player's progress asynchronously
+    /// on the given task manager.
+    pub fn save(&self, world_dir: &Path, uuid: Uuid, tasks: &RunningTasks) {
+        let json = self.to_json();
+        let world_dir = world_dir.to_owned();
+        tasks.schedule(async move {
+            if let Err(e) = save_file(&world_dir, uuid, json).await {
+                log::error!("Failed to save advancements for UUID {}: {}", uuid, e);
+            }
+        });
+    }
+
+    fn from_json(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut file: BTreeMap<String, serde_json::Value> = serde_json::from_slice(bytes)?;
+        file.remove("DataVersion");
+
+        let mut progress = Self::default();
+        for (advancement, entry) in file {
+            let entry: ProgressEntry = serde_json::from_value(entry)?;
+            let criteria = entry
+                .criteria
+                .into_iter()
+                .map(|(criterion, date)| (criterion, parse_date(&date).unwrap_or(0)))
+                .collect();
+            progress.criteria.insert(advancement.clone(), criteria);
+            if entry.done {
+                progress.done.insert(advancement);
+            }
+        }
+        Ok(progress)
+    }
+
+    fn to_json(&self) -> String {
+        let mut file = serde_json::Map::new();
+        for (advancement, criteria) in &self.criteria {
+            let entry = ProgressEntry {
+                criteria: criteria
+                    .iter()
+                    .map(|(criterion, &time)| (criterion.clone(), format_date(time)))
+                    .collect(),
+                done: self.is_done(advancement),
+            };
+            file.insert(
+                advancement.clone(),
+                serde_json::to_value(entry).expect("progress is valid JSON"),
+            );
+        }
+        file.insert("DataVersion".to_owned(), DATA_VERSION.into());
+        serde_json::to_string_pretty(&file).expect("progress is valid JSON")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ProgressEntry {
+    /// The date each criterion was achieved, as `yyyy-MM-dd HH:mm:ss Z`.
+    #[serde(default)]
+    criteria: BTreeMap<String, String>,
+    #[serde(default)]
+    done: bool,
+}
+
+async fn save_file(world_dir: &Path, uuid: Uuid, json: String) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(world_dir.join("advancements")).await?;
+    tokio::fs::write(file_path(world_dir, uuid), json).await?;
+    Ok(())
+}
+
+fn file_path(world_dir: &Path, uuid: Uuid) -> PathBuf {
+    world_dir
+        .join("advancements")
+        .join(format!("{}.json", uuid))
+}
+
+/// Returns the current time in milliseconds since the UNIX epoch,
+/// as stored for achieved criteria.
+pub fn advancement_time_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Formats a time in milliseconds since the UNIX epoch
+/// as a UTC date, as written in advancement files.
+fn format_date(millis: u64) -> String {
+    let secs = millis / 1000;
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let secs_of_day = secs % 86400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} +0000",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Parses a date written as `yyyy-MM-dd HH:mm:ss Z`, returning
+/// the time in milliseconds since the UNIX epoch.
+fn parse_date(date: &str) -> Option<u64> {
+    let mut parts = date.split_whitespace();
+    let mut ymd = parts.next()?.split('-').map(|x| x.parse::<i64>());
+    let mut hms = parts.next()?.split(':').map(|x| x.parse::<i64>());
+    let zone = parts.next()?;
+
+    let (year, month, day) = (ymd.next()?.ok()?, ymd.next()?.ok()?, ymd.next()?.ok()?);
+    let (hour, minute, second) = (hms.next()?.ok()?, hms.next()?.ok()?, hms.next()?.ok()?);
+    if zone.len() != 5 {
+        return None;
+    }
+    let offset_sign = match &zone[..1] {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let offset = offset_sign
+        * (zone[1..3].parse::<i64>().ok()? * 3600 + zone[3..5].parse::<i64>().ok()? * 60);
+
+    let secs =
+        days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second - offset;
+    if secs < 0 {
+        None
+    } else {
+        Some(secs as u64 * 1000)
+    }
+}
+
+/// Returns the year, month and day of the given
+/// number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = (if z >= 0 { z } else { z - 146_096 }) / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Returns the number of days since 1970-01-01 of the given date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates() {
+        assert_eq!(format_date(0), "1970-01-01 00:00:00 +0000");
+        assert_eq!(
+            format_date(1_551_398_400_000 + 3_723_000),
+            "2019-03-01 01:02:03 +0000"
+        );
+        assert_eq!(
+            parse_date("2019-03-01 01:02:03 +0000"),
+            Some(1_551_398_400_000 + 3_723_000)
+        );
+        assert_eq!(
+            parse_date("2019-03-01 03:02:03 +0200"),
+            Some(1_551_398_400_000 + 3_723_000)
+        );
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn progress_file() {
+        let json = br#"{
+            "minecraft:story/root": {
+                "criteria": { "crafting_table": "2019-03-01 01:02:03 +0000" },
+                "done": true
+            },
+            "minecraft:adventure/kill_all_mobs": {
+                "criteria": { "minecraft:zombie": "2019-03-01 01:02:03 +0000" },
+                "done": false
+            },
+            "DataVersion": 1631
+        }"#;
+        let mut progress = AdvancementProgress::from_json(json).unwrap();
+        assert!(progress.is_done("minecraft:story/root"));
+        assert!(!progress.is_done("minecraft:adventure/kill_all_mobs"));
+        assert!(progress.is_achieved("minecraft:adventure/kill_all_mobs", "minecraft:zombie"));
+
+        assert!(progress.achieve("minecraft:adventure/kill_all_mobs", "minecraft:pig", 0));
+        assert!(!progress.achieve("minecraft:adventure/kill_all_mobs", "minecraft:pig", 0));
+
+        let saved = AdvancementProgress::from_json(progress.to_json().as_bytes()).unwrap();
+        assert_eq!(saved, progress);
+    }
+
+    #[test]
+    fn criteria() {
+        let stone = Criterion::InventoryChanged(vec![Ingredient(vec![Item::Cobblestone])]);
+        assert!(stone.is_met(&Trigger::InventoryChanged(&[Item::Dirt, Item::Cobblestone])));
+        assert!(!stone.is_met(&Trigger::InventoryChanged(&[Item::Dirt])));
+        assert!(!stone.is_met(&Trigger::ConsumedItem(Item::Cobblestone)));
+
+        let kill = Criterion::PlayerKilledEntity(Some("minecraft:zombie".to_owned()));
+        assert!(kill.is_met(&Trigger::KilledEntity("minecraft:zombie")));
+        assert!(!kill.is_met(&Trigger::KilledEntity("minecraft:pig")));
+        assert!(Criterion::PlayerKilledEntity(None).is_met(&Trigger::KilledEntity("minecraft:pig")));
+
+        let biome = Criterion::Location(Some(Biome::Desert));
+        assert!(biome.is_met(&Trigger::Location(Biome::Desert)));
+        assert!(!biome.is_met(&Trigger::Location(Biome::Plains)));
+        assert!(!Criterion::Other.is_met(&Trigger::Location(Biome::Plains)));
+    }
+}
@@ -0,0 +1,454 @@
+//! Loading of advancements from the JSON files of the vanilla data pack.
+//!
+//! Advancements are read from each tab's directory in
+//! `minecraft/advancements`, relative to the data directory,
+//! so that `story/mine_stone.json` is `minecraft:story/mine_stone`.
+//! The `recipes` tab is skipped: its advancements only unlock
+//! recipes, which the recipe book already does as ingredients
+//! are obtained. Advancements whose parent is missing are skipped,
+//! as are criteria whose triggers or conditions are not supported,
+//! which then cannot be achieved.
+
+use super::{Advancement, AdvancementDisplay, AdvancementFrame, AdvancementRegistry, Criterion};
+use crate::recipes::{load_item_tags, read_json_files, Ingredient, ItemTags, ITEM_TAGS_DIR};
+use anyhow::{anyhow, Context};
+use feather_core::biomes::Biome;
+use feather_core::items::{Item, ItemStack};
+use feather_core::text::Text;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Directory of advancement tabs, relative to the data directory.
+pub const ADVANCEMENTS_DIR: &str = "minecraft/advancements";
+
+/// Tabs which are not loaded.
+const SKIPPED_TABS: &[&str] = &["recipes"];
+
+#[derive(Deserialize)]
+struct AdvancementFile {
+    parent: Option<String>,
+    display: Option<DisplayFile>,
+    criteria: BTreeMap<String, Value>,
+    requirements: Option<Vec<Vec<String>>>,
+}
+
+#[derive(Deserialize)]
+struct DisplayFile {
+    icon: IconFile,
+    title: Text,
+    description: Text,
+    #[serde(default)]
+    frame: Option<String>,
+    background: Option<String>,
+    #[serde(default = "yes")]
+    show_toast: bool,
+    #[serde(default = "yes")]
+    announce_to_chat: bool,
+    #[serde(default)]
+    hidden: bool,
+}
+
+#[derive(Deserialize)]
+struct IconFile {
+    item: String,
+}
+
+fn yes() -> bool {
+    true
+}
+
+impl AdvancementRegistry {
+    /// Loads the advancements in the given data directory.
+    ///
+    /// Returns an empty registry if the directory has no advancements.
+    pub fn load(data_dir: &Path) -> anyhow::Result<Self> {
+        let tags = load_item_tags(&data_dir.join(ITEM_TAGS_DIR))?;
+        let dir = data_dir.join(ADVANCEMENTS_DIR);
+
+        let mut registry = AdvancementRegistry::new();
+        for tab in read_tabs(&dir)? {
+            for (name, json) in read_json_files(&dir.join(&tab))? {
+                let id = format!("minecraft:{}/{}", tab, name);
+                match parse_advancement(&id, &json, &tags) {
+                    Ok(advancement) => registry.register(advancement),
+                    Err(e) => log::debug!("Skipping advancement {}: {}", id, e),
+                }
+            }
+        }
+
+        registry.remove_orphans();
+        registry.lay_out();
+        Ok(registry)
+    }
+
+    /// Removes advancements whose parent is missing,
+    /// along with their descendants.
+    fn remove_orphans(&mut self) {
+        loop {
+            let orphans: Vec<String> = self
+                .iter()
+                .filter(|advancement| {
+                    advancement
+                        .parent
+                        .as_ref()
+                        .map_or(false, |parent| self.get(parent).is_none())
+                })
+                .map(|advancement| advancement.id.clone())
+                .collect();
+            if orphans.is_empty() {
+                return;
+            }
+            for orphan in orphans {
+                log::debug!("Skipping advancement {}: parent is missing", orphan);
+                self.advancements.remove(&orphan);
+            }
+        }
+    }
+
+    /// Positions the displayed advancements of each tab: each
+    /// advancement is one column right of its parent, level with
+    /// its first child, and above the rows of its later children.
+    fn lay_out(&mut self) {
+        let mut children: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        let mut roots = vec![];
+        for advancement in self.iter().filter(|a| a.display.is_some()) {
+            match &advancement.parent {
+                Some(parent) => children
+                    .entry(parent.as_str())
+                    .or_default()
+                    .push(advancement.id.as_str()),
+                None => roots.push(advancement.id.as_str()),
+            }
+        }
+
+        let mut positions = vec![];
+        for root in roots {
+            place(root, 0, &mut 0, &children, &mut positions);
+        }
+
+        for (id, x, y) in positions {
+            let display = self
+                .advancements
+                .get_mut(&id)
+                .and_then(|advancement| advancement.display.as_mut());
+            if let Some(display) = display {
+                display.x = x;
+                display.y = y;
+            }
+        }
+    }
+}
+
+fn place(
+    id: &str,
+    column: usize,
+    row: &mut usize,
+    children: &BTreeMap<&str, Vec<&str>>,
+    positions: &mut Vec<(String, f32, f32)>,
+) {
+    positions.push((id.to_owned(), column as f32, *row as f32));
+    for (i, child) in children.get(id).into_iter().flatten().enumerate() {
+        if i > 0 {
+            *row += 1;
+        }
+        place(child, column + 1, row, children, positions);
+    }
+}
+
+/// Returns the names of the advancement tabs to load.
+fn read_tabs(dir: &Path) -> anyhow::Result<Vec<String>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut tabs = vec![];
+    for entry in entries {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(name) = entry.file_name().to_str() {
+            if !SKIPPED_TABS.contains(&name) {
+                tabs.push(name.to_owned());
+            }
+        }
+    }
+    tabs.sort();
+    Ok(tabs)
+}
+
+fn parse_advancement(id: &str, json: &str, tags: &ItemTags) -> anyhow::Result<Advancement> {
+    let file: AdvancementFile = serde_json::from_str(json).context("invalid advancement")?;
+    if file.criteria.is_empty() {
+        return Err(anyhow!("advancement has no criteria"));
+    }
+
+    let display = match file.display {
+        Some(display) => Some(parse_display(display)?),
+        None => None,
+    };
+    let requirements = file.requirements.unwrap_or_else(|| {
+        file.criteria
+            .keys()
+            .map(|criterion| vec![criterion.clone()])
+            .collect()
+    });
+    let criteria = file
+        .criteria
+        .iter()
+        .map(|(name, json)| (name.clone(), parse_criterion(json, tags)))
+        .collect();
+
+    Ok(Advancement {
+        id: id.to_owned(),
+        parent: file.parent,
+        display,
+        criteria,
+        requirements,
+    })
+}
+
+fn parse_display(file: DisplayFile) -> anyhow::Result<AdvancementDisplay> {
+    let icon = Item::from_identifier(&file.icon.item)
+        .ok_or_else(|| anyhow!("unknown item {}", file.icon.item))?;
+    let frame = match file.frame.as_ref().map(String::as_str) {
+        None | Some("task") => AdvancementFrame::Task,
+        Some("challenge") => AdvancementFrame::Challenge,
+        Some("goal") => AdvancementFrame::Goal,
+        Some(frame) => return Err(anyhow!("unknown frame {}", frame)),
+    };
+
+    Ok(AdvancementDisplay {
+        title: file.title,
+        description: file.description,
+        icon: ItemStack::new(icon, 1),
+        frame,
+        background: file.background,
+        show_toast: file.show_toast,
+        announce_to_chat: file.announce_to_chat,
+        hidden: file.hidden,
+        x: 0.0,
+        y: 0.0,
+    })
+}
+
+fn parse_criterion(json: &Value, tags: &ItemTags) -> Criterion {
+    let trigger = json.get("trigger").and_then(Value::as_str).unwrap_or("");
+    let empty = Map::new();
+    let conditions = match json.get("conditions") {
+        Some(Value::Object(conditions)) => conditions,
+        Some(_) => return Criterion::Other,
+        None => &empty,
+    };
+    parse_conditions(trigger, conditions, tags).unwrap_or(Criterion::Other)
+}
+
+/// Parses the conditions of a trigger, returning `None` if the
+/// trigger is not supported or a condition is not understood.
+fn parse_conditions(
+    trigger: &str,
+    conditions: &Map<String, Value>,
+    tags: &ItemTags,
+) -> Option<Criterion> {
+    Some(match trigger {
+        "minecraft:inventory_changed" => {
+            let conditions = fields(conditions, &["items"])?;
+            let items = match conditions.get("items") {
+                Some(items) => items
+                    .as_array()?
+                    .iter()
+                    .map(|item| parse_item_predicate(item, tags))
+                    .collect::<Option<_>>()?,
+                None => vec![],
+            };
+            Criterion::InventoryChanged(items)
+        }
+        "minecraft:player_killed_entity" => {
+            Criterion::PlayerKilledEntity(parse_entity_condition(conditions)?)
+        }
+        "minecraft:entity_killed_player" => {
+            Criterion::EntityKilledPlayer(parse_entity_condition(conditions)?)
+        }
+        "minecraft:location" => {
+            let biome = match fields(conditions, &["location"])?.get("location") {
+                Some(location) => {
+                    let location = fields(location.as_object()?, &["biome"])?;
+                    match location.get("biome") {
+                        Some(biome) => Some(Biome::from_identifier(biome.as_str()?)?),
+                        None => None,
+                    }
+                }
+                None => None,
+            };
+            Criterion::Location(biome)
+        }
+        "minecraft:consume_item" => {
+            let item = match fields(conditions, &["item"])?.get("item") {
+                Some(item) => Some(parse_item_predicate(item, tags)?),
+                None => None,
+            };
+            Criterion::ConsumeItem(item)
+        }
+        _ => return None,
+    })
+}
+
+/// Parses the `entity` condition of a kill trigger into
+/// the entity type it requires.
+fn parse_entity_condition(conditions: &Map<String, Value>) -> Option<Option<String>> {
+    match fields(conditions, &["entity"])?.get("entity") {
+        Some(entity) => {
+            let entity = fields(entity.as_object()?, &["type"])?;
+            match entity.get("type") {
+                Some(ty) => Some(Some(ty.as_str()?.to_owned())),
+                None => Some(None),
+            }
+        }
+        None => Some(None),
+    }
+}
+
+/// Parses an item predicate naming an item or an item tag.
+fn parse_item_predicate(json: &Value, tags: &ItemTags) -> Option<Ingredient> {
+    let predicate = fields(json.as_object()?, &["item", "tag"])?;
+    if let Some(item) = predicate.get("item") {
+        Some(Ingredient(vec![Item::from_identifier(item.as_str()?)?]))
+    } else if let Some(tag) = predicate.get("tag") {
+        Some(Ingredient(tags.get(tag.as_str()?)?.clone()))
+    } else {
+        None
+    }
+}
+
+/// Returns the object if it has no keys other than `allowed`.
+fn fields<'a>(object: &'a Map<String, Value>, allowed: &[&str]) -> Option<&'a Map<String, Value>> {
+    if object.keys().all(|key| allowed.contains(&key.as_str())) {
+        Some(object)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn tags() -> ItemTags {
+        let mut tags = HashMap::new();
+        tags.insert(
+            "minecraft:logs".to_owned(),
+            vec![Item::OakLog, Item::BirchLog],
+        );
+        tags
+    }
+
+    #[test]
+    fn criteria() {
+        let json = r#"{
+            "parent": "minecraft:story/root",
+            "display": {
+                "icon": { "item": "minecraft:wooden_pickaxe" },
+                "title": { "translate": "advancements.story.mine_stone.title" },
+                "description": { "translate": "advancements.story.mine_stone.description" }
+            },
+            "criteria": {
+                "get_stone": {
+                    "trigger": "minecraft:inventory_changed",
+                    "conditions": { "items": [{ "item": "minecraft:cobblestone" }, { "tag": "minecraft:logs" }] }
+                },
+                "kill_zombie": {
+                    "trigger": "minecraft:player_killed_entity",
+                    "conditions": { "entity": { "type": "minecraft:zombie" } }
+                },
+                "snipe": {
+                    "trigger": "minecraft:player_killed_entity",
+                    "conditions": { "entity": { "type": "minecraft:skeleton", "distance": { "horizontal": { "min": 50 } } } }
+                },
+                "desert": {
+                    "trigger": "minecraft:location",
+                    "conditions": { "location": { "biome": "minecraft:desert" } }
+                },
+                "brew": { "trigger": "minecraft:brewed_potion" }
+            }
+        }"#;
+        let advancement = parse_advancement("minecraft:story/mine_stone", json, &tags()).unwrap();
+
+        assert_eq!(advancement.parent.as_deref(), Some("minecraft:story/root"));
+        let display = advancement.display.as_ref().unwrap();
+        assert_eq!(display.icon.ty, Item::WoodenPickaxe);
+        assert_eq!(display.frame, AdvancementFrame::Task);
+        assert!(display.show_toast && display.announce_to_chat && !display.hidden);
+
+        assert_eq!(
+            advancement.criteria["get_stone"],
+            Criterion::InventoryChanged(vec![
+                Ingredient(vec![Item::Cobblestone]),
+                Ingredient(vec![Item::OakLog, Item::BirchLog]),
+            ])
+        );
+        assert_eq!(
+            advancement.criteria["kill_zombie"],
+            Criterion::PlayerKilledEntity(Some("minecraft:zombie".to_owned()))
+        );
+        assert_eq!(advancement.criteria["snipe"], Criterion::Other);
+        assert_eq!(
+            advancement.criteria["desert"],
+            Criterion::Location(Some(Biome::Desert))
+        );
+        assert_eq!(advancement.criteria["brew"], Criterion::Other);
+
+        // Without requirements, every criterion is required.
+        assert_eq!(advancement.requirements.len(), 5);
+    }
+
+    #[test]
+    fn layout_and_orphans() {
+        let advancement = |id: &str, parent: Option<&str>| {
+            let json = format!(
+                r#"{{
+                    {}
+                    "display": {{
+                        "icon": {{ "item": "minecraft:stone" }},
+                        "title": "{}",
+                        "description": ""
+                    }},
+                    "criteria": {{ "never": {{ "trigger": "minecraft:impossible" }} }}
+                }}"#,
+                parent.map_or(String::new(), |parent| format!(
+                    r#""parent": "{}","#,
+                    parent
+                )),
+                id
+            );
+            parse_advancement(id, &json, &tags()).unwrap()
+        };
+
+        let mut registry = AdvancementRegistry::new();
+        registry.register(advancement("a:root", None));
+        registry.register(advancement("a:root/1", Some("a:root")));
+        registry.register(advancement("a:root/2", Some("a:root")));
+        registry.register(advancement("a:root/1/1", Some("a:root/1")));
+        registry.register(advancement("a:root/2/1", Some("a:root/2")));
+        registry.register(advancement("a:lost", Some("a:missing")));
+        registry.register(advancement("a:lost/1", Some("a:lost")));
+        registry.remove_orphans();
+        registry.lay_out();
+
+        assert_eq!(registry.len(), 5);
+        let position = |id: &str| {
+            let display = registry.get(id).unwrap().display.as_ref().unwrap();
+            (display.x, display.y)
+        };
+        assert_eq!(position("a:root"), (0.0, 0.0));
+        assert_eq!(position("a:root/1"), (1.0, 0.0));
+        assert_eq!(position("a:root/1/1"), (2.0, 0.0));
+        assert_eq!(position("a:root/2"), (1.0, 1.0));
+        assert_eq!(position("a:root/2/1"), (2.0, 1.0));
+    }
+}
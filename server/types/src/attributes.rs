@@ -0,0 +1,350 @@
+//! Entity attributes, such as `generic.attackDamage`, and
+//! the modifiers which items and effects apply to them.
+//!
+//! See https://minecraft.gamepedia.com/Attribute for the
+//! vanilla values.
+
+use ahash::AHashMap;
+use feather_core::items::Item;
+use fecs::{Entity, World};
+use smallvec::SmallVec;
+use uuid::Uuid;
+
+/// An attribute of an entity.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Attribute {
+    MaxHealth,
+    KnockbackResistance,
+    MovementSpeed,
+    AttackDamage,
+    AttackSpeed,
+    Armor,
+    ArmorToughness,
+}
+
+impl Attribute {
+    /// Returns the identifier of this attribute,
+    /// as used in the protocol and in NBT.
+    pub fn identifier(self) -> &'static str {
+        match self {
+            Attribute::MaxHealth => "generic.maxHealth",
+            Attribute::KnockbackResistance => "generic.knockbackResistance",
+            Attribute::MovementSpeed => "generic.movementSpeed",
+            Attribute::AttackDamage => "generic.attackDamage",
+            Attribute::AttackSpeed => "generic.attackSpeed",
+            Attribute::Armor => "generic.armor",
+            Attribute::ArmorToughness => "generic.armorToughness",
+        }
+    }
+
+    /// Returns the base value of this attribute for
+    /// entities which do not override it.
+    pub fn default_value(self) -> f64 {
+        match self {
+            Attribute::MaxHealth => 20.0,
+            Attribute::KnockbackResistance => 0.0,
+            Attribute::MovementSpeed => 0.7,
+            Attribute::AttackDamage => 2.0,
+            Attribute::AttackSpeed => 4.0,
+            Attribute::Armor => 0.0,
+            Attribute::ArmorToughness => 0.0,
+        }
+    }
+
+    /// Returns the range to which values of this attribute are clamped.
+    pub fn range(self) -> (f64, f64) {
+        match self {
+            Attribute::MaxHealth => (0.0, 1024.0),
+            Attribute::KnockbackResistance => (0.0, 1.0),
+            Attribute::MovementSpeed => (0.0, 1024.0),
+            Attribute::AttackDamage => (0.0, 2048.0),
+            Attribute::AttackSpeed => (0.0, 1024.0),
+            Attribute::Armor => (0.0, 30.0),
+            Attribute::ArmorToughness => (0.0, 20.0),
+        }
+    }
+}
+
+/// How an `AttributeModifier` is applied.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ModifierOperation {
+    /// The amount is added to the base value.
+    Add,
+    /// The base value, multiplied by the amount,
+    /// is added to the value.
+    MultiplyBase,
+    /// The value is multiplied by one plus the amount.
+    Multiply,
+}
+
+impl ModifierOperation {
+    /// Returns the ID of this operation in the protocol.
+    pub fn id(self) -> i8 {
+        match self {
+            ModifierOperation::Add => 0,
+            ModifierOperation::MultiplyBase => 1,
+            ModifierOperation::Multiply => 2,
+        }
+    }
+}
+
+/// A modifier to an attribute. Modifiers are identified
+/// by their UUID; adding a modifier replaces any existing
+/// modifier with the same UUID.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AttributeModifier {
+    pub uuid: Uuid,
+    pub amount: f64,
+    pub operation: ModifierOperation,
+}
+
+/// UUID of the attack damage modifier applied by held items.
+pub const ITEM_ATTACK_DAMAGE_MODIFIER: Uuid =
+    Uuid::from_u128(0xCB3F55D3_645C_4F38_A497_9C13A33DB5CF);
+/// UUID of the attack speed modifier applied by held items.
+pub const ITEM_ATTACK_SPEED_MODIFIER: Uuid =
+    Uuid::from_u128(0xFA233E1C_4180_4865_B01B_BCCE9785ACA3);
+/// UUID of the movement speed modifier applied while sprinting.
+pub const SPRINTING_SPEED_MODIFIER: Uuid = Uuid::from_u128(0x662A6B8D_DA3E_4C1C_8813_96EA6097278D);
+/// UUIDs of the armor modifiers applied by worn items,
+/// from the head slot to the feet slot.
+pub const ARMOR_MODIFIERS: [Uuid; 4] = [
+    Uuid::from_u128(0x2AD3F246_FEE1_4E67_B886_69FD380BB150),
+    Uuid::from_u128(0x9F3D476D_C118_4544_8365_64846904B48E),
+    Uuid::from_u128(0xD8499B04_0E66_4726_AB29_64469D734E0D),
+    Uuid::from_u128(0x845DB27C_C624_495F_8C9F_6020A9A58B6B),
+];
+
+/// The base value and modifiers of a single attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeInstance {
+    pub base: f64,
+    pub modifiers: SmallVec<[AttributeModifier; 2]>,
+}
+
+impl AttributeInstance {
+    pub fn new(base: f64) -> Self {
+        Self {
+            base,
+            modifiers: SmallVec::new(),
+        }
+    }
+
+    /// Computes the value of this attribute, applying
+    /// the given modifiers in addition to its own.
+    fn value_with(&self, attribute: Attribute, extra: &[AttributeModifier]) -> f64 {
+        let modifiers: SmallVec<[AttributeModifier; 4]> =
+            self.modifiers.iter().chain(extra).copied().collect();
+        let amounts = |operation| {
+            modifiers
+                .iter()
+                .filter(move |modifier| modifier.operation == operation)
+                .map(|modifier| modifier.amount)
+        };
+
+        let base = self.base + amounts(ModifierOperation::Add).sum::<f64>();
+        let mut value = base;
+        for amount in amounts(ModifierOperation::MultiplyBase) {
+            value += base * amount;
+        }
+        for amount in amounts(ModifierOperation::Multiply) {
+            value *= 1.0 + amount;
+        }
+
+        let (min, max) = attribute.range();
+        value.max(min).min(max)
+    }
+}
+
+/// Component storing the attributes of an entity which differ
+/// from the defaults, or which have modifiers applied.
+#[derive(Clone, Debug, Default)]
+pub struct Attributes(AHashMap<Attribute, AttributeInstance>);
+
+impl Attributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the base value of an attribute.
+    pub fn with_base(mut self, attribute: Attribute, base: f64) -> Self {
+        self.instance_mut(attribute).base = base;
+        self
+    }
+
+    fn instance_mut(&mut self, attribute: Attribute) -> &mut AttributeInstance {
+        self.0
+            .entry(attribute)
+            .or_insert_with(|| AttributeInstance::new(attribute.default_value()))
+    }
+
+    /// Returns the base value and modifiers of an attribute,
+    /// if they differ from the defaults.
+    pub fn instance(&self, attribute: Attribute) -> Option<&AttributeInstance> {
+        self.0.get(&attribute)
+    }
+
+    /// Adds a modifier to an attribute, replacing any
+    /// existing modifier with the same UUID.
+    pub fn add_modifier(&mut self, attribute: Attribute, modifier: AttributeModifier) {
+        let modifiers = &mut self.instance_mut(attribute).modifiers;
+        modifiers.retain(|existing| existing.uuid != modifier.uuid);
+        modifiers.push(modifier);
+    }
+
+    /// Removes the modifier with the given UUID from an attribute.
+    pub fn remove_modifier(&mut self, attribute: Attribute, uuid: Uuid) {
+        if let Some(instance) = self.0.get_mut(&attribute) {
+            instance.modifiers.retain(|modifier| modifier.uuid != uuid);
+        }
+    }
+
+    /// Returns the value of an attribute.
+    pub fn value(&self, attribute: Attribute) -> f64 {
+        self.value_with(attribute, &[])
+    }
+
+    /// Returns the value of an attribute with
+    /// the given modifiers applied temporarily.
+    pub fn value_with(&self, attribute: Attribute, extra: &[AttributeModifier]) -> f64 {
+        match self.0.get(&attribute) {
+            Some(instance) => instance.value_with(attribute, extra),
+            None => AttributeInstance::new(attribute.default_value()).value_with(attribute, extra),
+        }
+    }
+}
+
+/// Returns the value of an entity's attribute, using the
+/// default value if the entity has no `Attributes`.
+pub fn attribute_value(world: &World, entity: Entity, attribute: Attribute) -> f64 {
+    match world.try_get::<Attributes>(entity) {
+        Some(attributes) => attributes.value(attribute),
+        None => Attributes::new().value(attribute),
+    }
+}
+
+/// Base attack damage of players, which is lower
+/// than the default for other entities.
+pub const PLAYER_ATTACK_DAMAGE: f64 = 1.0;
+
+/// Base movement speed of players.
+pub const PLAYER_MOVEMENT_SPEED: f64 = 0.1;
+
+/// Returns the attribute modifiers applied by an
+/// item while it is held in the main hand.
+pub fn held_item_modifiers(item: Item) -> SmallVec<[(Attribute, AttributeModifier); 2]> {
+    let (damage, speed) = match item {
+        Item::WoodenSword | Item::GoldenSword => (4.0, 1.6),
+        Item::StoneSword => (5.0, 1.6),
+        Item::IronSword => (6.0, 1.6),
+        Item::DiamondSword => (7.0, 1.6),
+        Item::WoodenAxe => (7.0, 0.8),
+        Item::GoldenAxe => (7.0, 1.0),
+        Item::StoneAxe => (9.0, 0.8),
+        Item::IronAxe => (9.0, 0.9),
+        Item::DiamondAxe => (9.0, 1.0),
+        Item::WoodenPickaxe | Item::GoldenPickaxe => (2.0, 1.2),
+        Item::StonePickaxe => (3.0, 1.2),
+        Item::IronPickaxe => (4.0, 1.2),
+        Item::DiamondPickaxe => (5.0, 1.2),
+        Item::WoodenShovel | Item::GoldenShovel => (2.5, 1.0),
+        Item::StoneShovel => (3.5, 1.0),
+        Item::IronShovel => (4.5, 1.0),
+        Item::DiamondShovel => (5.5, 1.0),
+        Item::WoodenHoe | Item::GoldenHoe => (1.0, 1.0),
+        Item::StoneHoe => (1.0, 2.0),
+        Item::IronHoe => (1.0, 3.0),
+        Item::DiamondHoe => (1.0, 4.0),
+        Item::Trident => (9.0, 1.1),
+        _ => return SmallVec::new(),
+    };
+
+    // The table lists the resulting values for players, whose
+    // base attack damage is 1 and base attack speed is 4.
+    let mut modifiers = SmallVec::new();
+    modifiers.push((
+        Attribute::AttackDamage,
+        AttributeModifier {
+            uuid: ITEM_ATTACK_DAMAGE_MODIFIER,
+            amount: damage - 1.0,
+            operation: ModifierOperation::Add,
+        },
+    ));
+    modifiers.push((
+        Attribute::AttackSpeed,
+        AttributeModifier {
+            uuid: ITEM_ATTACK_SPEED_MODIFIER,
+            amount: speed - 4.0,
+            operation: ModifierOperation::Add,
+        },
+    ));
+    modifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modifier(n: u128, amount: f64, operation: ModifierOperation) -> AttributeModifier {
+        AttributeModifier {
+            uuid: Uuid::from_u128(n),
+            amount,
+            operation,
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn modifier_operations() {
+        let mut attributes = Attributes::new().with_base(Attribute::MovementSpeed, 0.1);
+        attributes.add_modifier(
+            Attribute::MovementSpeed,
+            modifier(1, 0.1, ModifierOperation::Add),
+        );
+        attributes.add_modifier(
+            Attribute::MovementSpeed,
+            modifier(2, 0.5, ModifierOperation::MultiplyBase),
+        );
+        attributes.add_modifier(
+            Attribute::MovementSpeed,
+            modifier(3, 1.0, ModifierOperation::Multiply),
+        );
+
+        // ((0.1 + 0.1) + 0.2 * 0.5) * 2
+        assert!((attributes.value(Attribute::MovementSpeed) - 0.6).abs() < 1e-9);
+
+        attributes.remove_modifier(Attribute::MovementSpeed, Uuid::from_u128(3));
+        assert!((attributes.value(Attribute::MovementSpeed) - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn modifiers_are_replaced_by_uuid() {
+        let mut attributes = Attributes::new();
+        attributes.add_modifier(Attribute::Armor, modifier(1, 5.0, ModifierOperation::Add));
+        attributes.add_modifier(Attribute::Armor, modifier(1, 3.0, ModifierOperation::Add));
+        assert_eq!(attributes.value(Attribute::Armor), 3.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn values_are_clamped() {
+        let attributes = Attributes::new().with_base(Attribute::Armor, 100.0);
+        assert_eq!(attributes.value(Attribute::Armor), 30.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn held_sword_damage() {
+        let attributes = Attributes::new().with_base(Attribute::AttackDamage, PLAYER_ATTACK_DAMAGE);
+        let modifiers: Vec<_> = held_item_modifiers(Item::DiamondSword)
+            .into_iter()
+            .filter(|(attribute, _)| *attribute == Attribute::AttackDamage)
+            .map(|(_, modifier)| modifier)
+            .collect();
+        assert_eq!(
+            attributes.value_with(Attribute::AttackDamage, &modifiers),
+            7.0
+        );
+    }
+}
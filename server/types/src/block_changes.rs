@@ -0,0 +1,112 @@
+//! Buffering of block changes, so that edits to many blocks
+//! at once, such as those made by `/fill`, are sent to clients
+//! with one packet per chunk rather than one per block.
+//!
+//! Changes are buffered while running `Game::buffer_block_changes`.
+
+use ahash::AHashMap;
+use feather_core::blocks::BlockId;
+use feather_core::network::packets::{BlockChange, BlockChangeRecord, MultiBlockChange};
+use feather_core::network::Packet;
+use feather_core::util::{BlockPosition, ChunkPosition};
+
+/// Block changes waiting to be sent, grouped by chunk. Only
+/// the latest change to each block is kept.
+#[derive(Debug, Default)]
+pub struct BlockChangeBuffer {
+    /// Number of nested `Game::buffer_block_changes` calls.
+    depth: usize,
+    chunks: AHashMap<ChunkPosition, AHashMap<BlockPosition, BlockId>>,
+}
+
+impl BlockChangeBuffer {
+    pub fn is_buffering(&self) -> bool {
+        self.depth > 0
+    }
+
+    /// Buffers a change to the block at `pos`. Returns `false`
+    /// if changes are not being buffered, in which case the
+    /// caller should send the change itself.
+    pub fn buffer(&mut self, pos: BlockPosition, block: BlockId) -> bool {
+        if !self.is_buffering() {
+            return false;
+        }
+        self.chunks
+            .entry(pos.chunk())
+            .or_default()
+            .insert(pos, block);
+        true
+    }
+
+    pub(crate) fn begin(&mut self) {
+        self.depth += 1;
+    }
+
+    /// Stops buffering, returning the buffered changes
+    /// if this ends the outermost buffering call.
+    pub(crate) fn end(
+        &mut self,
+    ) -> Option<AHashMap<ChunkPosition, AHashMap<BlockPosition, BlockId>>> {
+        self.depth -= 1;
+        if self.depth == 0 {
+            Some(std::mem::take(&mut self.chunks))
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the packet which sends changes to blocks in one chunk:
+/// a `BlockChange` for a single block, or a `MultiBlockChange`.
+pub fn block_changes_packet(
+    chunk: ChunkPosition,
+    changes: &AHashMap<BlockPosition, BlockId>,
+) -> Box<dyn Packet> {
+    if changes.len() == 1 {
+        let (&location, block) = changes.iter().next().unwrap();
+        return Box::new(BlockChange {
+            location,
+            block_id: block.vanilla_id() as i32,
+        });
+    }
+
+    Box::new(MultiBlockChange {
+        chunk_x: chunk.x,
+        chunk_z: chunk.z,
+        records: changes
+            .iter()
+            .map(|(&pos, block)| BlockChangeRecord::new(pos, block.vanilla_id() as i32))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_buffers_when_enabled() {
+        let mut buffer = BlockChangeBuffer::default();
+        let pos = BlockPosition::new(1, 64, 1);
+        assert!(!buffer.buffer(pos, BlockId::stone()));
+
+        buffer.begin();
+        buffer.begin();
+        assert!(buffer.buffer(pos, BlockId::stone()));
+        assert!(buffer.buffer(pos, BlockId::dirt()));
+        assert!(buffer.buffer(BlockPosition::new(17, 64, 1), BlockId::dirt()));
+        assert!(buffer.end().is_none());
+
+        let chunks = buffer.end().unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[&pos.chunk()][&pos], BlockId::dirt());
+        assert!(!buffer.is_buffering());
+    }
+
+    #[test]
+    fn record_positions() {
+        let record = BlockChangeRecord::new(BlockPosition::new(-1, 70, 18), 1);
+        assert_eq!(record.horizontal_position, 0xF2);
+        assert_eq!(record.y, 70);
+    }
+}
@@ -0,0 +1,84 @@
+//! Storage of the block entities in loaded chunks.
+//!
+//! Block entities are loaded and saved along with their chunks.
+//! Those which the server does not use are not kept.
+
+use ahash::AHashMap;
+use feather_core::anvil::block_entity::BlockEntityData;
+use feather_core::util::{BlockPosition, ChunkPosition};
+
+/// The block entities in loaded chunks, grouped by chunk.
+#[derive(Debug, Default)]
+pub struct BlockEntities {
+    chunks: AHashMap<ChunkPosition, AHashMap<BlockPosition, BlockEntityData>>,
+}
+
+impl BlockEntities {
+    /// Returns the block entity at `pos`, if any.
+    pub fn get(&self, pos: BlockPosition) -> Option<&BlockEntityData> {
+        self.chunks.get(&pos.chunk())?.get(&pos)
+    }
+
+    /// Returns the block entity at `pos`, if any.
+    pub fn get_mut(&mut self, pos: BlockPosition) -> Option<&mut BlockEntityData> {
+        self.chunks.get_mut(&pos.chunk())?.get_mut(&pos)
+    }
+
+    /// Adds a block entity, replacing any at the same position.
+    /// Does nothing for block entities without a position.
+    pub fn insert(&mut self, block_entity: BlockEntityData) {
+        if let Some(pos) = block_entity.position() {
+            self.chunks
+                .entry(pos.chunk())
+                .or_default()
+                .insert(pos, block_entity);
+        }
+    }
+
+    /// Removes the block entity at `pos`, returning it.
+    pub fn remove(&mut self, pos: BlockPosition) -> Option<BlockEntityData> {
+        let chunk = self.chunks.get_mut(&pos.chunk())?;
+        let block_entity = chunk.remove(&pos);
+        if chunk.is_empty() {
+            self.chunks.remove(&pos.chunk());
+        }
+        block_entity
+    }
+
+    /// Returns copies of the block entities in a chunk, to be saved.
+    pub fn in_chunk(&self, chunk: ChunkPosition) -> Vec<BlockEntityData> {
+        self.chunks
+            .get(&chunk)
+            .map(|block_entities| block_entities.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Removes the block entities of a chunk which is unloaded.
+    pub fn remove_chunk(&mut self, chunk: ChunkPosition) {
+        self.chunks.remove(&chunk);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::anvil::block_entity::ContainerData;
+
+    #[test]
+    fn insert_and_remove() {
+        let pos = BlockPosition::new(17, 64, -3);
+        let chest = BlockEntityData::Chest(ContainerData::new(pos));
+
+        let mut block_entities = BlockEntities::default();
+        block_entities.insert(chest.clone());
+        block_entities.insert(BlockEntityData::Unknown);
+
+        assert_eq!(block_entities.get(pos), Some(&chest));
+        assert_eq!(block_entities.in_chunk(pos.chunk()), vec![chest.clone()]);
+        assert!(block_entities.in_chunk(ChunkPosition::new(0, 0)).is_empty());
+
+        assert_eq!(block_entities.remove(pos), Some(chest));
+        assert_eq!(block_entities.get(pos), None);
+        assert!(block_entities.chunks.is_empty());
+    }
+}
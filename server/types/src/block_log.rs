@@ -0,0 +1,414 @@
+//! Storage of the block log: a record of who placed and
+//! broke which blocks, and who accessed which containers.
+//!
+//! The log is kept in memory and persisted to an append-only
+//! file. The file is a sequence of fixed-size records, interleaved
+//! with actor definitions which map the actor indices stored in
+//! records to names. New entries are buffered and appended to the
+//! file periodically.
+
+use crate::RunningTasks;
+use ahash::AHashMap;
+use feather_core::blocks::BlockId;
+use feather_core::util::BlockPosition;
+use std::convert::TryInto;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+
+/// Tag of an actor definition in the log file.
+const TAG_ACTOR: u8 = 0;
+/// Tag of a block change record.
+const TAG_BLOCK_CHANGE: u8 = 1;
+/// Tag of a block change record made by a rollback.
+const TAG_ROLLBACK: u8 = 2;
+/// Tag of a container access record.
+const TAG_CONTAINER_ACCESS: u8 = 3;
+
+/// Size of a record in the log file, excluding its tag.
+const RECORD_SIZE: usize = 8 + 4 * 3 + 2 * 2 + 4;
+
+/// Index of an actor in the block log's actor table.
+pub type ActorIndex = u32;
+
+/// Name of the actor for changes with no known cause.
+pub const ACTOR_WORLD: &str = "#world";
+/// Name of the actor for changes caused by non-player entities.
+pub const ACTOR_ENTITY: &str = "#entity";
+
+/// The kind of a block log record.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlockLogKind {
+    /// A block was changed from `old` to `new`.
+    BlockChange { old: BlockId, new: BlockId },
+    /// A block was changed from `old` to `new`
+    /// while rolling back other changes.
+    Rollback { old: BlockId, new: BlockId },
+    /// A container block was opened.
+    ContainerAccess,
+}
+
+/// A single entry in the block log.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BlockLogRecord {
+    /// UNIX timestamp, in seconds, at which the change occurred.
+    pub time: u64,
+    pub pos: BlockPosition,
+    pub actor: ActorIndex,
+    pub kind: BlockLogKind,
+}
+
+impl BlockLogRecord {
+    fn write_to(&self, buf: &mut Vec<u8>) {
+        let (tag, old, new) = match self.kind {
+            BlockLogKind::BlockChange { old, new } => (TAG_BLOCK_CHANGE, old, new),
+            BlockLogKind::Rollback { old, new } => (TAG_ROLLBACK, old, new),
+            BlockLogKind::ContainerAccess => (TAG_CONTAINER_ACCESS, BlockId::air(), BlockId::air()),
+        };
+
+        buf.push(tag);
+        buf.extend_from_slice(&self.time.to_le_bytes());
+        buf.extend_from_slice(&self.pos.x.to_le_bytes());
+        buf.extend_from_slice(&self.pos.y.to_le_bytes());
+        buf.extend_from_slice(&self.pos.z.to_le_bytes());
+        buf.extend_from_slice(&old.vanilla_id().to_le_bytes());
+        buf.extend_from_slice(&new.vanilla_id().to_le_bytes());
+        buf.extend_from_slice(&self.actor.to_le_bytes());
+    }
+
+    fn read_from(tag: u8, bytes: &[u8; RECORD_SIZE]) -> Self {
+        let u16_at = |i: usize| u16::from_le_bytes(bytes[i..i + 2].try_into().unwrap());
+        let i32_at = |i: usize| i32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+
+        let time = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        let pos = BlockPosition::new(i32_at(8), i32_at(12), i32_at(16));
+        let old = BlockId::from_vanilla_id(u16_at(20));
+        let new = BlockId::from_vanilla_id(u16_at(22));
+        let actor = u32::from_le_bytes(bytes[24..28].try_into().unwrap());
+
+        let kind = match tag {
+            TAG_ROLLBACK => BlockLogKind::Rollback { old, new },
+            TAG_CONTAINER_ACCESS => BlockLogKind::ContainerAccess,
+            _ => BlockLogKind::BlockChange { old, new },
+        };
+
+        Self {
+            time,
+            pos,
+            actor,
+            kind,
+        }
+    }
+}
+
+/// A query over the block log. Records must
+/// match all filters which are set.
+#[derive(Clone, Debug, Default)]
+pub struct BlockLogQuery {
+    /// Only match records within this (cubic) radius of a position.
+    pub area: Option<(BlockPosition, i32)>,
+    /// Only match records made by this actor.
+    pub actor: Option<String>,
+    /// Only match records made at or after this UNIX timestamp.
+    pub since: Option<u64>,
+}
+
+/// Resource storing the block log.
+#[derive(Debug)]
+pub struct BlockLog {
+    /// Whether changes are being logged.
+    pub enabled: bool,
+    /// Whether changes currently being made are part of a rollback.
+    pub rolling_back: bool,
+    path: PathBuf,
+    records: Vec<BlockLogRecord>,
+    actors: Vec<String>,
+    actor_indices: AHashMap<String, ActorIndex>,
+    /// Data not yet appended to the file.
+    pending: Vec<u8>,
+}
+
+impl BlockLog {
+    /// Creates an empty log which will be saved to the given path.
+    pub fn new(path: impl Into<PathBuf>, enabled: bool) -> Self {
+        Self {
+            enabled,
+            rolling_back: false,
+            path: path.into(),
+            records: vec![],
+            actors: vec![],
+            actor_indices: AHashMap::new(),
+            pending: vec![],
+        }
+    }
+
+    /// Loads the log from the given path. A missing
+    /// file results in an empty log. If logging is
+    /// disabled, the file is not read.
+    pub fn load(path: &Path, enabled: bool) -> anyhow::Result<Self> {
+        let mut log = Self::new(path, enabled);
+        if !enabled {
+            return Ok(log);
+        }
+
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(log),
+            Err(e) => return Err(e.into()),
+        };
+        log.read_entries(&bytes)?;
+
+        Ok(log)
+    }
+
+    fn read_entries(&mut self, mut bytes: &[u8]) -> anyhow::Result<()> {
+        while let Some((&tag, rest)) = bytes.split_first() {
+            bytes = rest;
+            if tag == TAG_ACTOR {
+                let (&len, rest) = bytes
+                    .split_first()
+                    .ok_or_else(|| anyhow::anyhow!("truncated actor definition"))?;
+                let len = len as usize;
+                if rest.len() < len {
+                    anyhow::bail!("truncated actor definition");
+                }
+                let name = std::str::from_utf8(&rest[..len])?.to_owned();
+                self.define_actor(name);
+                bytes = &rest[len..];
+            } else {
+                if bytes.len() < RECORD_SIZE {
+                    // A partially written record; ignore it.
+                    log::warn!("Ignoring truncated record at the end of the block log");
+                    break;
+                }
+                let record = BlockLogRecord::read_from(tag, bytes[..RECORD_SIZE].try_into()?);
+                self.records.push(record);
+                bytes = &bytes[RECORD_SIZE..];
+            }
+        }
+
+        Ok(())
+    }
+
+    fn define_actor(&mut self, name: String) -> ActorIndex {
+        let index = self.actors.len() as ActorIndex;
+        self.actor_indices.insert(name.clone(), index);
+        self.actors.push(name);
+        index
+    }
+
+    /// Returns the index of the actor with the given
+    /// name, adding it to the actor table if needed.
+    fn actor_index(&mut self, name: &str) -> ActorIndex {
+        if let Some(index) = self.actor_indices.get(name) {
+            return *index;
+        }
+
+        // Names longer than 255 bytes cannot be stored;
+        // player names are at most 16 characters.
+        let mut name = name.to_owned();
+        while name.len() > u8::MAX as usize {
+            name.pop();
+        }
+
+        self.pending.push(TAG_ACTOR);
+        self.pending.push(name.len() as u8);
+        self.pending.extend_from_slice(name.as_bytes());
+        self.define_actor(name)
+    }
+
+    /// Returns the name of the given actor.
+    pub fn actor_name(&self, actor: ActorIndex) -> &str {
+        self.actors
+            .get(actor as usize)
+            .map(String::as_str)
+            .unwrap_or(ACTOR_WORLD)
+    }
+
+    /// Logs an entry made by the actor with the given name.
+    pub fn record(&mut self, actor: &str, pos: BlockPosition, kind: BlockLogKind) {
+        if !self.enabled {
+            return;
+        }
+
+        let record = BlockLogRecord {
+            time: block_log_now(),
+            pos,
+            actor: self.actor_index(actor),
+            kind,
+        };
+        record.write_to(&mut self.pending);
+        self.records.push(record);
+    }
+
+    /// Returns the records matching the given query, newest first.
+    pub fn query<'a>(
+        &'a self,
+        query: &'a BlockLogQuery,
+    ) -> impl Iterator<Item = &'a BlockLogRecord> + 'a {
+        let actor = match &query.actor {
+            Some(name) => match self
+                .actors
+                .iter()
+                .position(|actor| actor.eq_ignore_ascii_case(name))
+            {
+                Some(index) => Some(index as ActorIndex),
+                // No records can match an unknown actor.
+                None => Some(ActorIndex::MAX),
+            },
+            None => None,
+        };
+
+        self.records.iter().rev().filter(move |record| {
+            if let Some(since) = query.since {
+                if record.time < since {
+                    return false;
+                }
+            }
+            if let Some(actor) = actor {
+                if record.actor != actor {
+                    return false;
+                }
+            }
+            if let Some((center, radius)) = query.area {
+                let offset = record.pos - center;
+                if offset.x.abs() > radius || offset.y.abs() > radius || offset.z.abs() > radius {
+                    return false;
+                }
+            }
+            true
+        })
+    }
+
+    /// Appends the entries logged since the last call
+    /// to the log file. The write is performed
+    /// asynchronously on the given task manager.
+    pub fn flush(&mut self, tasks: &RunningTasks) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let bytes = std::mem::take(&mut self.pending);
+        let path = self.path.clone();
+
+        tasks.schedule(async move {
+            let result = async {
+                let mut file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .await?;
+                file.write_all(&bytes).await
+            }
+            .await;
+
+            if let Err(e) = result {
+                log::error!("Failed to write block log {}: {}", path.display(), e);
+            }
+        });
+    }
+}
+
+/// Returns the current UNIX timestamp in seconds,
+/// as used for block log timestamps.
+pub fn block_log_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(old: BlockId, new: BlockId) -> BlockLogKind {
+        BlockLogKind::BlockChange { old, new }
+    }
+
+    #[test]
+    fn encoding_round_trip() {
+        let mut log = BlockLog::new("test.dat", true);
+        log.record(
+            "caelunshun",
+            BlockPosition::new(-10, 64, 3000),
+            change(BlockId::stone(), BlockId::air()),
+        );
+        log.record(
+            "Notch",
+            BlockPosition::new(0, 0, 0),
+            BlockLogKind::ContainerAccess,
+        );
+        log.record(
+            "caelunshun",
+            BlockPosition::new(1, 2, 3),
+            BlockLogKind::Rollback {
+                old: BlockId::air(),
+                new: BlockId::stone(),
+            },
+        );
+
+        let mut loaded = BlockLog::new("test.dat", true);
+        loaded.read_entries(&log.pending).unwrap();
+
+        assert_eq!(loaded.records, log.records);
+        assert_eq!(loaded.actors, vec!["caelunshun", "Notch"]);
+    }
+
+    #[test]
+    fn truncated_record_is_ignored() {
+        let mut log = BlockLog::new("test.dat", true);
+        log.record(
+            "Notch",
+            BlockPosition::new(0, 0, 0),
+            change(BlockId::stone(), BlockId::air()),
+        );
+        let mut bytes = log.pending.clone();
+        bytes.extend_from_slice(&[TAG_BLOCK_CHANGE, 1, 2, 3]);
+
+        let mut loaded = BlockLog::new("test.dat", true);
+        loaded.read_entries(&bytes).unwrap();
+        assert_eq!(loaded.records.len(), 1);
+    }
+
+    #[test]
+    fn query_filters() {
+        let mut log = BlockLog::new("test.dat", true);
+        let edit = change(BlockId::stone(), BlockId::air());
+        log.record("a", BlockPosition::new(0, 64, 0), edit);
+        log.record("b", BlockPosition::new(5, 64, 0), edit);
+        log.record("a", BlockPosition::new(50, 64, 0), edit);
+
+        let query = BlockLogQuery {
+            actor: Some(String::from("A")),
+            ..Default::default()
+        };
+        let positions: Vec<_> = log.query(&query).map(|r| r.pos.x).collect();
+        // newest first
+        assert_eq!(positions, vec![50, 0]);
+
+        let query = BlockLogQuery {
+            area: Some((BlockPosition::new(0, 64, 0), 10)),
+            ..Default::default()
+        };
+        assert_eq!(log.query(&query).count(), 2);
+
+        let query = BlockLogQuery {
+            actor: Some(String::from("nobody")),
+            ..Default::default()
+        };
+        assert_eq!(log.query(&query).count(), 0);
+    }
+
+    #[test]
+    fn disabled_log_records_nothing() {
+        let mut log = BlockLog::new("test.dat", false);
+        log.record(
+            "a",
+            BlockPosition::new(0, 0, 0),
+            change(BlockId::stone(), BlockId::air()),
+        );
+        assert_eq!(log.query(&BlockLogQuery::default()).count(), 0);
+    }
+}
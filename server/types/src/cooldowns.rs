@@ -0,0 +1,68 @@
+//! Item cooldowns, during which an entity may not use
+//! any item of a type, e.g. after throwing an ender pearl.
+
+use feather_core::items::Item;
+use std::collections::HashMap;
+
+/// Number of ticks after throwing an ender pearl
+/// before another one may be thrown.
+pub const ENDER_PEARL_COOLDOWN_TICKS: u64 = 20;
+
+/// Number of ticks after eating chorus fruit
+/// before more may be eaten.
+pub const CHORUS_FRUIT_COOLDOWN_TICKS: u64 = 20;
+
+/// Component storing the items an entity is waiting to use again.
+#[derive(Clone, Debug, Default)]
+pub struct ItemCooldowns {
+    /// Tick at which each item may be used again.
+    until: HashMap<Item, u64>,
+}
+
+impl ItemCooldowns {
+    /// Starts a cooldown on an item which ends at the given tick,
+    /// replacing any cooldown the item already has.
+    ///
+    /// Cooldowns which ended before `tick` are forgotten.
+    pub fn start(&mut self, item: Item, tick: u64, until: u64) {
+        self.until.retain(|_, end| *end > tick);
+        self.until.insert(item, until);
+    }
+
+    /// Ends the cooldown on an item, if it has one.
+    pub fn clear(&mut self, item: Item) {
+        self.until.remove(&item);
+    }
+
+    /// Returns the number of ticks left in an item's
+    /// cooldown, or 0 if it may be used.
+    pub fn remaining(&self, item: Item, tick: u64) -> u64 {
+        self.until
+            .get(&item)
+            .map_or(0, |until| until.saturating_sub(tick))
+    }
+
+    /// Returns whether an item is on cooldown at the given tick.
+    pub fn is_cooling_down(&self, item: Item, tick: u64) -> bool {
+        self.remaining(item, tick) > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cooldowns_expire() {
+        let mut cooldowns = ItemCooldowns::default();
+        cooldowns.start(Item::EnderPearl, 10, 30);
+
+        assert!(cooldowns.is_cooling_down(Item::EnderPearl, 10));
+        assert_eq!(cooldowns.remaining(Item::EnderPearl, 25), 5);
+        assert!(!cooldowns.is_cooling_down(Item::EnderPearl, 30));
+        assert!(!cooldowns.is_cooling_down(Item::ChorusFruit, 10));
+
+        cooldowns.start(Item::ChorusFruit, 40, 60);
+        assert_eq!(cooldowns.until.len(), 1);
+    }
+}
@@ -0,0 +1,327 @@
+//! Types for damaging entities. The damage calculation
+//! itself lives in the `util` crate.
+
+use crate::ExplosionSource;
+use feather_core::items::{Item, ItemStack};
+use feather_core::util::{Hand, Position};
+use fecs::Entity;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Number of ticks after being damaged during which an entity
+/// only takes damage exceeding the damage it last took.
+pub const INVULNERABILITY_TICKS: u64 = 10;
+
+/// What damaged an entity.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DamageCause {
+    /// A melee attack by an entity.
+    EntityAttack(Entity),
+    /// A hit by a projectile.
+    Projectile {
+        projectile: Entity,
+        shooter: Option<Entity>,
+    },
+    Explosion {
+        pos: Position,
+        source: ExplosionSource,
+    },
+    /// The explosion of a firework rocket with firework stars.
+    Firework,
+    Lightning,
+    /// Standing in fire.
+    Fire,
+    /// Being on fire.
+    Burning,
+    Lava,
+    Fall,
+    Drowning,
+    Suffocation,
+    /// Having an empty food bar.
+    Starvation,
+    /// Falling out of the world.
+    Void,
+    Magic,
+    /// Flying into a wall while gliding with an elytra.
+    FlyIntoWall,
+    /// The Thorns enchantment on the armor of the given entity,
+    /// which damages those who attack it.
+    Thorns(Entity),
+    Generic,
+}
+
+impl DamageCause {
+    /// Returns the entity responsible for the damage, if any.
+    pub fn attacker(&self) -> Option<Entity> {
+        match self {
+            DamageCause::EntityAttack(attacker) | DamageCause::Thorns(attacker) => Some(*attacker),
+            DamageCause::Projectile { shooter, .. } => *shooter,
+            DamageCause::Explosion {
+                source: ExplosionSource::Creeper(creeper),
+                ..
+            } => Some(*creeper),
+            _ => None,
+        }
+    }
+
+    /// Returns whether armor reduces this damage.
+    pub fn reduced_by_armor(&self) -> bool {
+        match self {
+            DamageCause::Burning
+            | DamageCause::Fall
+            | DamageCause::Drowning
+            | DamageCause::Suffocation
+            | DamageCause::Starvation
+            | DamageCause::Void
+            | DamageCause::Magic
+            | DamageCause::Generic => false,
+            _ => true,
+        }
+    }
+
+    /// Returns whether protection enchantments and
+    /// the Resistance effect reduce this damage.
+    pub fn reduced_by_protection(&self) -> bool {
+        match self {
+            DamageCause::Void | DamageCause::Starvation => false,
+            _ => true,
+        }
+    }
+
+    /// Returns the translation key of the message broadcast
+    /// when an entity is killed by this damage.
+    ///
+    /// `by_player` is whether the attacker, if any, was a player.
+    pub fn death_message_key(&self, by_player: bool) -> &'static str {
+        match self {
+            DamageCause::EntityAttack(_) if by_player => "death.attack.player",
+            DamageCause::EntityAttack(_) => "death.attack.mob",
+            DamageCause::Projectile { .. } => "death.attack.arrow",
+            DamageCause::Explosion { .. } if by_player => "death.attack.explosion.player",
+            DamageCause::Explosion { .. } => "death.attack.explosion",
+            DamageCause::Firework => "death.attack.fireworks",
+            DamageCause::Lightning => "death.attack.lightningBolt",
+            DamageCause::Fire => "death.attack.inFire",
+            DamageCause::Burning => "death.attack.onFire",
+            DamageCause::Lava => "death.attack.lava",
+            DamageCause::Fall => "death.attack.fall",
+            DamageCause::Drowning => "death.attack.drown",
+            DamageCause::Suffocation => "death.attack.inWall",
+            DamageCause::Starvation => "death.attack.starve",
+            DamageCause::Void => "death.attack.outOfWorld",
+            DamageCause::Magic => "death.attack.magic",
+            DamageCause::FlyIntoWall => "death.attack.flyIntoWall",
+            DamageCause::Thorns(_) => "death.attack.thorns",
+            DamageCause::Generic => "death.attack.generic",
+        }
+    }
+
+    pub fn is_fire(&self) -> bool {
+        match self {
+            DamageCause::Fire | DamageCause::Burning | DamageCause::Lava => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_explosion(&self) -> bool {
+        match self {
+            DamageCause::Explosion { .. } | DamageCause::Firework => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_projectile(&self) -> bool {
+        match self {
+            DamageCause::Projectile { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Returns whether a shield can block this damage.
+    pub fn blockable(&self) -> bool {
+        match self {
+            DamageCause::EntityAttack(_)
+            | DamageCause::Projectile { .. }
+            | DamageCause::Explosion { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+/// Component for mobs which take extra damage
+/// from some enchantments.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CreatureAttribute {
+    /// Zombies, skeletons, and the like, which take
+    /// extra damage from Smite.
+    Undead,
+    /// Spiders, silverfish, and endermites, which take
+    /// extra damage from Bane of Arthropods.
+    Arthropod,
+    /// Fish, squid, guardians and other water mobs,
+    /// which take extra damage from Impaling.
+    Aquatic,
+}
+
+/// Component storing the number of ticks for which
+/// an entity is on fire.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Burning(pub u32);
+
+/// Number of ticks after raising a shield before it blocks damage.
+pub const SHIELD_WARMUP_TICKS: u64 = 5;
+
+/// Number of ticks for which a shield is disabled after
+/// being hit by an axe.
+pub const SHIELD_DISABLE_TICKS: u64 = 100;
+
+/// Component for entities which have raised a shield.
+#[derive(Copy, Clone, Debug)]
+pub struct BlockingWithShield {
+    /// The hand holding the shield.
+    pub hand: Hand,
+    /// Tick at which the shield was raised.
+    pub raised_at: u64,
+}
+
+impl BlockingWithShield {
+    /// Returns whether the shield has been raised
+    /// long enough to block damage.
+    pub fn is_active(&self, tick: u64) -> bool {
+        tick >= self.raised_at + SHIELD_WARMUP_TICKS
+    }
+}
+
+/// Component storing the last damage taken by an entity,
+/// used to make it briefly invulnerable after being damaged.
+#[derive(Copy, Clone, Debug)]
+pub struct LastDamage {
+    /// Tick at which the invulnerability period started.
+    pub tick: u64,
+    /// Damage taken, before reductions.
+    pub amount: f32,
+}
+
+/// Component storing the distance an entity has fallen since
+/// it was last on the ground, used to compute fall damage.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FallDistance(pub f64);
+
+/// Number of ticks of air an entity has when its head is out of water.
+pub const MAX_AIR: i32 = 300;
+
+/// Component storing the ticks of air an entity has left before
+/// it starts drowning. Entities without this component have
+/// `MAX_AIR`.
+///
+/// Air drops below zero while an entity drowns, and resets
+/// to zero whenever drowning damage is dealt.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Air(pub i32);
+
+impl Default for Air {
+    fn default() -> Self {
+        Air(MAX_AIR)
+    }
+}
+
+/// Component tracking the recharge of an entity's melee attacks.
+/// Entities without this component always attack at full strength.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AttackCooldown {
+    /// Tick at which the entity last attacked or switched items.
+    pub last_reset: u64,
+}
+
+impl AttackCooldown {
+    pub fn new(tick: u64) -> Self {
+        Self { last_reset: tick }
+    }
+
+    pub fn reset(&mut self, tick: u64) {
+        self.last_reset = tick;
+    }
+}
+
+/// The result of damaging an entity.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DamageOutcome {
+    /// Damage dealt after all reductions.
+    pub damage: f32,
+    /// Whether the damage was blocked by a shield.
+    pub blocked: bool,
+    /// If set, the entity is not damaged.
+    pub cancelled: bool,
+}
+
+/// Triggered when an entity is damaged, after the final damage
+/// has been computed but before it is applied.
+///
+/// Handlers may modify the damage in `outcome` or cancel it.
+#[derive(Clone, Debug)]
+pub struct EntityDamageEvent {
+    pub entity: Entity,
+    pub cause: DamageCause,
+    pub outcome: Arc<Mutex<DamageOutcome>>,
+}
+
+/// Requests that an entity be damaged. All damage, regardless
+/// of its cause, should use this request.
+///
+/// This is a "request"-type event: it has one handler defined
+/// in the `util` crate which applies shields, invulnerability,
+/// armor, and effects, triggers an `EntityDamageEvent`, and
+/// then applies the damage.
+#[derive(Copy, Clone, Debug)]
+pub struct EntityDamageRequest {
+    pub entity: Entity,
+    /// Damage before any reductions.
+    pub damage: f32,
+    pub cause: DamageCause,
+}
+
+/// An item which a mob drops when killed, with the least
+/// and greatest amount dropped. Each level of Looting on
+/// the killer's weapon may add one more.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LootEntry(pub Item, pub u8, pub u8);
+
+/// Component for mobs which drop items when killed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MobLoot(pub &'static [LootEntry]);
+
+/// The items and experience dropped by an entity which died.
+#[derive(Clone, Debug, Default)]
+pub struct DeathDrops {
+    pub items: Vec<ItemStack>,
+    /// Experience points, which are dropped as orbs.
+    pub experience: u32,
+    /// If set, nothing is dropped, and players keep
+    /// their inventory and experience.
+    pub cancelled: bool,
+}
+
+/// Triggered when a living entity is killed, before it is despawned.
+/// Dead players remain until they respawn.
+///
+/// Handlers may modify the entity's drops in `drops` or cancel them.
+#[derive(Clone, Debug)]
+pub struct EntityDeathEvent {
+    pub entity: Entity,
+    /// The cause of the damage which killed the entity.
+    pub cause: DamageCause,
+    pub drops: Arc<Mutex<DeathDrops>>,
+}
+
+/// Requests that the drops of an entity which died be spawned
+/// around `position`. Handled in the `entity` crate.
+#[derive(Clone, Debug)]
+pub struct DeathDropsRequest {
+    pub position: Position,
+    pub items: Vec<ItemStack>,
+    pub experience: u32,
+    /// Whether items are scattered around the position the
+    /// way players drop their inventory, rather than popping
+    /// up the way mobs drop their loot.
+    pub scatter: bool,
+}
@@ -0,0 +1,195 @@
+//! Status effects, such as Resistance or Regeneration,
+//! which are active on an entity for a limited time.
+
+use ahash::AHashMap;
+use serde::{Deserialize, Serialize};
+
+/// A status effect. The discriminants are the effect IDs used in the protocol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StatusEffect {
+    Speed = 1,
+    Slowness = 2,
+    Haste = 3,
+    MiningFatigue = 4,
+    Strength = 5,
+    InstantHealth = 6,
+    InstantDamage = 7,
+    JumpBoost = 8,
+    Nausea = 9,
+    Regeneration = 10,
+    Resistance = 11,
+    FireResistance = 12,
+    WaterBreathing = 13,
+    Invisibility = 14,
+    Blindness = 15,
+    NightVision = 16,
+    Hunger = 17,
+    Weakness = 18,
+    Poison = 19,
+    Wither = 20,
+    HealthBoost = 21,
+    Absorption = 22,
+    Saturation = 23,
+    Glowing = 24,
+    Levitation = 25,
+    Luck = 26,
+    BadLuck = 27,
+    SlowFalling = 28,
+    ConduitPower = 29,
+    DolphinsGrace = 30,
+}
+
+impl StatusEffect {
+    /// Returns the ID of this effect in the protocol.
+    pub fn id(self) -> i8 {
+        self as i8
+    }
+
+    /// Returns the effect with the given protocol ID.
+    pub fn from_id(id: i8) -> Option<Self> {
+        Some(match id {
+            1 => StatusEffect::Speed,
+            2 => StatusEffect::Slowness,
+            3 => StatusEffect::Haste,
+            4 => StatusEffect::MiningFatigue,
+            5 => StatusEffect::Strength,
+            6 => StatusEffect::InstantHealth,
+            7 => StatusEffect::InstantDamage,
+            8 => StatusEffect::JumpBoost,
+            9 => StatusEffect::Nausea,
+            10 => StatusEffect::Regeneration,
+            11 => StatusEffect::Resistance,
+            12 => StatusEffect::FireResistance,
+            13 => StatusEffect::WaterBreathing,
+            14 => StatusEffect::Invisibility,
+            15 => StatusEffect::Blindness,
+            16 => StatusEffect::NightVision,
+            17 => StatusEffect::Hunger,
+            18 => StatusEffect::Weakness,
+            19 => StatusEffect::Poison,
+            20 => StatusEffect::Wither,
+            21 => StatusEffect::HealthBoost,
+            22 => StatusEffect::Absorption,
+            23 => StatusEffect::Saturation,
+            24 => StatusEffect::Glowing,
+            25 => StatusEffect::Levitation,
+            26 => StatusEffect::Luck,
+            27 => StatusEffect::BadLuck,
+            28 => StatusEffect::SlowFalling,
+            29 => StatusEffect::ConduitPower,
+            30 => StatusEffect::DolphinsGrace,
+            _ => return None,
+        })
+    }
+
+    /// Returns all status effects, in order of ID.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (1..=30).filter_map(Self::from_id)
+    }
+
+    /// Returns the identifier of this effect, such as `minecraft:speed`.
+    pub fn identifier(self) -> &'static str {
+        match self {
+            StatusEffect::Speed => "minecraft:speed",
+            StatusEffect::Slowness => "minecraft:slowness",
+            StatusEffect::Haste => "minecraft:haste",
+            StatusEffect::MiningFatigue => "minecraft:mining_fatigue",
+            StatusEffect::Strength => "minecraft:strength",
+            StatusEffect::InstantHealth => "minecraft:instant_health",
+            StatusEffect::InstantDamage => "minecraft:instant_damage",
+            StatusEffect::JumpBoost => "minecraft:jump_boost",
+            StatusEffect::Nausea => "minecraft:nausea",
+            StatusEffect::Regeneration => "minecraft:regeneration",
+            StatusEffect::Resistance => "minecraft:resistance",
+            StatusEffect::FireResistance => "minecraft:fire_resistance",
+            StatusEffect::WaterBreathing => "minecraft:water_breathing",
+            StatusEffect::Invisibility => "minecraft:invisibility",
+            StatusEffect::Blindness => "minecraft:blindness",
+            StatusEffect::NightVision => "minecraft:night_vision",
+            StatusEffect::Hunger => "minecraft:hunger",
+            StatusEffect::Weakness => "minecraft:weakness",
+            StatusEffect::Poison => "minecraft:poison",
+            StatusEffect::Wither => "minecraft:wither",
+            StatusEffect::HealthBoost => "minecraft:health_boost",
+            StatusEffect::Absorption => "minecraft:absorption",
+            StatusEffect::Saturation => "minecraft:saturation",
+            StatusEffect::Glowing => "minecraft:glowing",
+            StatusEffect::Levitation => "minecraft:levitation",
+            StatusEffect::Luck => "minecraft:luck",
+            StatusEffect::BadLuck => "minecraft:unluck",
+            StatusEffect::SlowFalling => "minecraft:slow_falling",
+            StatusEffect::ConduitPower => "minecraft:conduit_power",
+            StatusEffect::DolphinsGrace => "minecraft:dolphins_grace",
+        }
+    }
+
+    /// Returns the effect with the given identifier. The
+    /// `minecraft:` namespace may be omitted.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        let name = identifier.trim_start_matches("minecraft:");
+        Self::all().find(|effect| &effect.identifier()["minecraft:".len()..] == name)
+    }
+}
+
+/// An active instance of a status effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ActiveEffect {
+    /// The level of the effect minus one.
+    pub amplifier: u8,
+    /// Number of ticks until the effect expires.
+    pub duration: u32,
+    /// Whether the effect was applied by a beacon or conduit.
+    pub ambient: bool,
+    pub show_particles: bool,
+}
+
+impl ActiveEffect {
+    pub fn new(amplifier: u8, duration: u32) -> Self {
+        Self {
+            amplifier,
+            duration,
+            ambient: false,
+            show_particles: true,
+        }
+    }
+}
+
+/// Component storing the status effects active on an entity.
+///
+/// Effects should be added and removed with the functions in the
+/// `util` crate, which send the changes to clients.
+#[derive(Clone, Debug, Default)]
+pub struct ActiveEffects(pub AHashMap<StatusEffect, ActiveEffect>);
+
+impl ActiveEffects {
+    pub fn get(&self, effect: StatusEffect) -> Option<&ActiveEffect> {
+        self.0.get(&effect)
+    }
+
+    /// Returns the amplifier of an effect, if it is active.
+    pub fn amplifier(&self, effect: StatusEffect) -> Option<u8> {
+        self.get(effect).map(|active| active.amplifier)
+    }
+
+    pub fn has(&self, effect: StatusEffect) -> bool {
+        self.0.contains_key(&effect)
+    }
+}
+
+/// An effect applied when a food item is eaten, as stored in the
+/// `Effects` tag of suspicious stews. Any food may carry this tag.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoodEffect {
+    #[serde(rename = "EffectId")]
+    pub id: i8,
+    /// Duration in ticks.
+    #[serde(rename = "EffectDuration", default = "default_food_effect_duration")]
+    pub duration: i32,
+    /// Not used by vanilla, which always applies level I.
+    #[serde(rename = "Amplifier", default)]
+    pub amplifier: u8,
+}
+
+fn default_food_effect_duration() -> i32 {
+    160
+}
@@ -0,0 +1,30 @@
+//! Experience, which players collect from orbs dropped
+//! by mobs they kill.
+
+/// Component storing a player's experience.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Experience {
+    pub level: u32,
+    /// Progress towards the next level, from 0 to 1.
+    pub progress: f32,
+    /// Experience points collected since the player last died.
+    pub total: u32,
+}
+
+/// Component for mobs which drop experience when
+/// killed by a player, storing the range of points dropped.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExperienceReward {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl ExperienceReward {
+    /// A reward of exactly `points`.
+    pub fn fixed(points: u32) -> Self {
+        Self {
+            min: points,
+            max: points,
+        }
+    }
+}
@@ -1,13 +1,21 @@
 use crate::network::{Network, ServerToWorkerMessage};
 use crate::task::RunningTasks;
-use crate::{BlockUpdateEvent, EntityDespawnEvent, Name, PlayerLeaveEvent};
+use crate::{
+    block_changes_packet, protocol_velocity, BlockChangeBuffer, BlockEntities, BlockUpdateCause,
+    BlockUpdateEvent, EntityDespawnEvent, EntityId, Name, OnlinePlayers, PlayerLeaveEvent, Title,
+    Uuid, Velocity,
+};
 use ahash::AHashMap;
 use bumpalo::Bump;
 use feather_core::anvil::level::LevelData;
 use feather_core::blocks::BlockId;
 use feather_core::chunk_map::ChunkMap;
+use feather_core::network::packets::{
+    DisconnectPlay, EntityVelocity, Particle, ParticleKind, Title as TitlePacket, TitleAction,
+};
 use feather_core::network::Packet;
-use feather_core::util::{BlockPosition, ChunkPosition, Position};
+use feather_core::text::{Text, TextRoot};
+use feather_core::util::{BlockPosition, ChunkPosition, Difficulty, Position};
 use feather_server_config::Config;
 use fecs::{Entity, Event, EventHandlers, IntoQuery, OwnedResources, Read, RefResources, World};
 use rand::rngs::SmallRng;
@@ -15,8 +23,6 @@ use rand::{Rng, SeedableRng};
 use smallvec::SmallVec;
 use std::cell::{RefCell, RefMut};
 use std::fmt::Display;
-use std::ops::{Deref, DerefMut};
-use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use thread_local::CachedThreadLocal;
 
@@ -38,8 +44,14 @@ pub struct Game {
     /// Associates chunks with the entities that reside in them. Used
     /// as an acceleration structure for spacial lookups.
     pub chunk_entities: ChunkEntities,
+    /// The block entities in loaded chunks.
+    pub block_entities: BlockEntities,
     /// World time, in the Minecraft way.
     pub time: Time,
+    /// Gamerules, which are synced to the level data on save.
+    pub game_rules: GameRules,
+    /// Block changes which are buffered to be sent in bulk.
+    pub block_changes: BlockChangeBuffer,
     /// Server task manager, which allows executing futures
     /// which will not be interrupted on shutdown.
     pub running_tasks: RunningTasks,
@@ -51,8 +63,8 @@ pub struct Game {
     pub rng: CachedThreadLocal<RefCell<SmallRng>>,
     /// Shared bump allocator, reset each tick.
     pub bump: CachedThreadLocal<Bump>,
-    /// The server player count.
-    pub player_count: Arc<AtomicU32>,
+    /// The players online, shared with the network tasks.
+    pub online_players: Arc<OnlinePlayers>,
 }
 
 impl Game {
@@ -72,11 +84,54 @@ impl Game {
         self.chunk_map.block_at(pos)
     }
 
+    /// Runs `f`, buffering the block changes it makes. Once it returns,
+    /// the changes are sent to clients with one packet per chunk.
+    ///
+    /// Use this when changing many blocks at once, as the client
+    /// would otherwise receive one packet per changed block.
+    pub fn buffer_block_changes<T>(
+        &mut self,
+        world: &mut World,
+        f: impl FnOnce(&mut Game, &mut World) -> T,
+    ) -> T {
+        self.block_changes.begin();
+        let result = f(self, world);
+        if let Some(chunks) = self.block_changes.end() {
+            for (chunk, changes) in chunks {
+                self.broadcast_chunk_update_boxed(
+                    world,
+                    block_changes_packet(chunk, &changes),
+                    chunk,
+                    None,
+                );
+            }
+        }
+        result
+    }
+
     /// Sets the block at the given position.
     ///
     /// If the block's chunk's is not loaded, returns `false`;
     /// otherwise, returns `true`.
+    ///
+    /// The update has no known cause; use `set_block_at_with_cause`
+    /// for updates made by entities or in response to other updates.
     pub fn set_block_at(&mut self, world: &mut World, pos: BlockPosition, block: BlockId) -> bool {
+        self.set_block_at_with_cause(world, pos, block, BlockUpdateCause::unknown())
+    }
+
+    /// Sets the block at the given position, triggering
+    /// a `BlockUpdateEvent` with the given cause.
+    ///
+    /// If the block's chunk's is not loaded, returns `false`;
+    /// otherwise, returns `true`.
+    pub fn set_block_at_with_cause(
+        &mut self,
+        world: &mut World,
+        pos: BlockPosition,
+        block: BlockId,
+        cause: BlockUpdateCause,
+    ) -> bool {
         let old = match self.block_at(pos) {
             Some(block) => block,
             None => return false,
@@ -90,6 +145,7 @@ impl Game {
                 pos,
                 old,
                 new: block,
+                cause,
             },
         );
 
@@ -108,6 +164,11 @@ impl Game {
             .borrow_mut()
     }
 
+    /// Returns the difficulty of the world, as stored in the level data.
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::from_id(self.level.difficulty as u8)
+    }
+
     /// Despawns an entity. This should be used instead of `World::despawn`
     /// as it properly handles events.
     pub fn despawn(&mut self, entity: Entity, world: &mut World) {
@@ -127,12 +188,101 @@ impl Game {
         drop(name);
         drop(network);
 
-        self.player_count.fetch_sub(1, Ordering::AcqRel);
+        if let Some(uuid) = world.try_get::<Uuid>(player).map(|uuid| *uuid) {
+            self.online_players.remove(uuid);
+        }
 
         self.handle(world, PlayerLeaveEvent { player });
         self.despawn(player, world);
     }
 
+    /// Kicks a player, showing them the given reason.
+    pub fn kick(&mut self, player: Entity, world: &mut World, reason: impl Into<Text>) {
+        let reason = reason.into();
+        let plain = reason.to_plain();
+        world.get::<Network>(player).send(DisconnectPlay {
+            reason: TextRoot::from(reason).into(),
+        });
+        self.disconnect(player, world, format!("kicked: {}", plain));
+    }
+
+    /// Shows a title to a player.
+    pub fn send_title(&self, player: Entity, world: &World, title: Title) {
+        let network = world.get::<Network>(player);
+        for packet in title.packets() {
+            network.send(packet);
+        }
+    }
+
+    /// Shows a message above a player's hotbar.
+    pub fn send_action_bar(&self, player: Entity, world: &World, text: impl Into<Text>) {
+        world.get::<Network>(player).send(TitlePacket {
+            action: TitleAction::SetActionBar(TextRoot::from(text.into()).into()),
+        });
+    }
+
+    /// Hides the title shown to a player. If `reset` is set, its
+    /// subtitle and timings are also restored to the defaults.
+    pub fn clear_title(&self, player: Entity, world: &World, reset: bool) {
+        let action = if reset {
+            TitleAction::Reset
+        } else {
+            TitleAction::Hide
+        };
+        world.get::<Network>(player).send(TitlePacket { action });
+    }
+
+    /// Sets the velocity of an entity and sends it to all players
+    /// who can see the entity, including the entity itself if it
+    /// is a player.
+    ///
+    /// Players have no server-side velocity; their clients
+    /// apply the velocity they are sent.
+    pub fn set_velocity(&self, world: &mut World, entity: Entity, velocity: glm::DVec3) {
+        if world.has::<Velocity>(entity) {
+            world.get_mut::<Velocity>(entity).0 = velocity;
+        }
+
+        let (velocity_x, velocity_y, velocity_z) = protocol_velocity(velocity);
+        let packet = EntityVelocity {
+            entity_id: world.get::<EntityId>(entity).0,
+            velocity_x,
+            velocity_y,
+            velocity_z,
+        };
+        self.broadcast_entity_update(world, packet, entity, None);
+    }
+
+    /// Spawns `count` particles around `pos`, shown to players who
+    /// can see its chunk. Each particle's position is offset by a
+    /// random amount with the standard deviations in `offset`.
+    ///
+    /// A `count` of zero spawns a single particle, with `offset`
+    /// used as its velocity or, for some particles, its color.
+    pub fn spawn_particle(
+        &self,
+        world: &World,
+        pos: Position,
+        particle: impl Into<ParticleKind>,
+        count: u32,
+        offset: (f32, f32, f32),
+        speed: f32,
+    ) {
+        let packet = Particle {
+            kind: particle.into(),
+            long_distance: false,
+            x: pos.x as f32,
+            y: pos.y as f32,
+            z: pos.z as f32,
+            offset_x: offset.0,
+            offset_y: offset.1,
+            offset_z: offset.2,
+            speed,
+            count: count as i32,
+        };
+        self.broadcast_chunk_update(world, packet, pos.chunk(), None);
+    }
+
     /* BROADCAST FUNCTIONS */
     /// Broadcasts a packet to all online players.
     pub fn broadcast_global(&self, world: &World, packet: impl Packet, neq: Option<Entity>) {
@@ -268,36 +418,69 @@ impl ChunkEntities {
     pub fn entities_in_chunk(&self, chunk: ChunkPosition) -> &[Entity] {
         self.0.get(&chunk).map(|vec| vec.as_slice()).unwrap_or(&[])
     }
+
+    /// Moves an entity from the `old` chunk to the `new` chunk.
+    pub fn move_entity(&mut self, entity: Entity, old: ChunkPosition, new: ChunkPosition) {
+        if let Some(vec) = self.0.get_mut(&old) {
+            if let Some(index) = vec.iter().position(|e| *e == entity) {
+                vec.swap_remove(index);
+            }
+        }
+
+        self.0.entry(new).or_default().push(entity);
+    }
 }
 
 /// The current time of the world.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub struct Time(pub u64);
+pub struct Time {
+    /// Ticks since the world was created.
+    world_age: u64,
+    /// Ticks since the first day of the world began,
+    /// which may be changed with `/time`.
+    day_time: u64,
+}
 
-impl Deref for Time {
-    type Target = u64;
+impl Time {
+    pub fn new(world_age: u64, day_time: u64) -> Self {
+        Self {
+            world_age,
+            day_time,
+        }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    /// Returns the time of day, from 0 at
+    /// sunrise to 24000 at the next sunrise.
+    pub fn time_of_day(self) -> u64 {
+        self.day_time % 24_000
     }
-}
 
-impl DerefMut for Time {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+    /// Returns the time since the first day began.
+    pub fn day_time(self) -> u64 {
+        self.day_time
     }
-}
 
-impl Time {
-    /// Returns the time of day. This is calculated
-    /// as `time.0 % 24_000`.
-    pub fn time_of_day(self) -> u64 {
-        self.0 % 24_000
+    /// Returns the number of days which have passed.
+    pub fn day(self) -> u64 {
+        self.day_time / 24_000
     }
 
-    /// Returns the age of the world in ticks. Equivalent to `time.0`.
+    /// Returns the age of the world in ticks.
     pub fn world_age(self) -> u64 {
-        self.0
+        self.world_age
+    }
+
+    pub fn set_day_time(&mut self, day_time: u64) {
+        self.day_time = day_time;
+    }
+
+    /// Advances the time by one tick. The time of day
+    /// only advances if the daylight cycle is enabled.
+    pub fn tick(&mut self, daylight_cycle: bool) {
+        self.world_age += 1;
+        if daylight_cycle {
+            self.day_time += 1;
+        }
     }
 }
 
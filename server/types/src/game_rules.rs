@@ -0,0 +1,251 @@
+//! Gamerules: named boolean and integer settings which
+//! alter gameplay, changed at runtime with `/gamerule`.
+//!
+//! Rules are persisted as strings in the level file. They are
+//! parsed into `GameRules` on startup and written back on save.
+
+use ahash::AHashMap;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+/// A boolean gamerule.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BoolGameRule {
+    pub name: &'static str,
+    pub default: bool,
+}
+
+/// An integer gamerule.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct IntGameRule {
+    pub name: &'static str,
+    pub default: i32,
+}
+
+const fn bool_rule(name: &'static str, default: bool) -> BoolGameRule {
+    BoolGameRule { name, default }
+}
+
+const fn int_rule(name: &'static str, default: i32) -> IntGameRule {
+    IntGameRule { name, default }
+}
+
+pub const ANNOUNCE_ADVANCEMENTS: BoolGameRule = bool_rule("announceAdvancements", true);
+pub const COMMAND_BLOCK_OUTPUT: BoolGameRule = bool_rule("commandBlockOutput", true);
+pub const DISABLE_ELYTRA_MOVEMENT_CHECK: BoolGameRule =
+    bool_rule("disableElytraMovementCheck", false);
+pub const DO_DAYLIGHT_CYCLE: BoolGameRule = bool_rule("doDaylightCycle", true);
+pub const DO_ENTITY_DROPS: BoolGameRule = bool_rule("doEntityDrops", true);
+pub const DO_FIRE_TICK: BoolGameRule = bool_rule("doFireTick", true);
+pub const DO_LIMITED_CRAFTING: BoolGameRule = bool_rule("doLimitedCrafting", false);
+pub const DO_MOB_LOOT: BoolGameRule = bool_rule("doMobLoot", true);
+pub const DO_MOB_SPAWNING: BoolGameRule = bool_rule("doMobSpawning", true);
+pub const DO_TILE_DROPS: BoolGameRule = bool_rule("doTileDrops", true);
+pub const DO_WEATHER_CYCLE: BoolGameRule = bool_rule("doWeatherCycle", true);
+pub const KEEP_INVENTORY: BoolGameRule = bool_rule("keepInventory", false);
+pub const LOG_ADMIN_COMMANDS: BoolGameRule = bool_rule("logAdminCommands", true);
+pub const MAX_COMMAND_CHAIN_LENGTH: IntGameRule = int_rule("maxCommandChainLength", 65_536);
+pub const MAX_ENTITY_CRAMMING: IntGameRule = int_rule("maxEntityCramming", 24);
+pub const MOB_GRIEFING: BoolGameRule = bool_rule("mobGriefing", true);
+pub const NATURAL_REGENERATION: BoolGameRule = bool_rule("naturalRegeneration", true);
+pub const RANDOM_TICK_SPEED: IntGameRule = int_rule("randomTickSpeed", 3);
+pub const REDUCED_DEBUG_INFO: BoolGameRule = bool_rule("reducedDebugInfo", false);
+pub const SEND_COMMAND_FEEDBACK: BoolGameRule = bool_rule("sendCommandFeedback", true);
+pub const SHOW_DEATH_MESSAGES: BoolGameRule = bool_rule("showDeathMessages", true);
+pub const SPAWN_RADIUS: IntGameRule = int_rule("spawnRadius", 10);
+pub const SPECTATORS_GENERATE_CHUNKS: BoolGameRule = bool_rule("spectatorsGenerateChunks", true);
+
+/// Every gamerule, sorted by name.
+pub const GAME_RULES: &[GameRule] = &[
+    GameRule::Bool(ANNOUNCE_ADVANCEMENTS),
+    GameRule::Bool(COMMAND_BLOCK_OUTPUT),
+    GameRule::Bool(DISABLE_ELYTRA_MOVEMENT_CHECK),
+    GameRule::Bool(DO_DAYLIGHT_CYCLE),
+    GameRule::Bool(DO_ENTITY_DROPS),
+    GameRule::Bool(DO_FIRE_TICK),
+    GameRule::Bool(DO_LIMITED_CRAFTING),
+    GameRule::Bool(DO_MOB_LOOT),
+    GameRule::Bool(DO_MOB_SPAWNING),
+    GameRule::Bool(DO_TILE_DROPS),
+    GameRule::Bool(DO_WEATHER_CYCLE),
+    GameRule::Bool(KEEP_INVENTORY),
+    GameRule::Bool(LOG_ADMIN_COMMANDS),
+    GameRule::Int(MAX_COMMAND_CHAIN_LENGTH),
+    GameRule::Int(MAX_ENTITY_CRAMMING),
+    GameRule::Bool(MOB_GRIEFING),
+    GameRule::Bool(NATURAL_REGENERATION),
+    GameRule::Int(RANDOM_TICK_SPEED),
+    GameRule::Bool(REDUCED_DEBUG_INFO),
+    GameRule::Bool(SEND_COMMAND_FEEDBACK),
+    GameRule::Bool(SHOW_DEATH_MESSAGES),
+    GameRule::Int(SPAWN_RADIUS),
+    GameRule::Bool(SPECTATORS_GENERATE_CHUNKS),
+];
+
+/// A gamerule of either type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameRule {
+    Bool(BoolGameRule),
+    Int(IntGameRule),
+}
+
+impl GameRule {
+    /// Looks up a gamerule by its name, which is case-sensitive.
+    pub fn from_name(name: &str) -> Option<Self> {
+        GAME_RULES.iter().copied().find(|rule| rule.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameRule::Bool(rule) => rule.name,
+            GameRule::Int(rule) => rule.name,
+        }
+    }
+
+    pub fn default(self) -> GameRuleValue {
+        match self {
+            GameRule::Bool(rule) => GameRuleValue::Bool(rule.default),
+            GameRule::Int(rule) => GameRuleValue::Int(rule.default),
+        }
+    }
+
+    /// Parses a value of this rule's type, as
+    /// stored in the level file.
+    pub fn parse(self, value: &str) -> Option<GameRuleValue> {
+        match self {
+            GameRule::Bool(_) => match value {
+                "true" => Some(GameRuleValue::Bool(true)),
+                "false" => Some(GameRuleValue::Bool(false)),
+                _ => None,
+            },
+            GameRule::Int(_) => value.parse().ok().map(GameRuleValue::Int),
+        }
+    }
+}
+
+/// The value of a gamerule.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameRuleValue {
+    Bool(bool),
+    Int(i32),
+}
+
+impl Display for GameRuleValue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GameRuleValue::Bool(value) => value.fmt(f),
+            GameRuleValue::Int(value) => value.fmt(f),
+        }
+    }
+}
+
+/// The values of all gamerules. Rules which
+/// were never set have their default value.
+#[derive(Debug, Clone, Default)]
+pub struct GameRules {
+    values: AHashMap<&'static str, GameRuleValue>,
+}
+
+impl GameRules {
+    /// Parses gamerules from the strings stored in the level file.
+    /// Unknown rules and malformed values are ignored.
+    pub fn load(rules: &HashMap<String, String>) -> Self {
+        let mut values = AHashMap::new();
+        for (name, value) in rules {
+            let rule = match GameRule::from_name(name) {
+                Some(rule) => rule,
+                None => continue,
+            };
+            match rule.parse(value) {
+                Some(value) => {
+                    values.insert(rule.name(), value);
+                }
+                None => log::warn!("Invalid value `{}` for gamerule {}", value, name),
+            }
+        }
+        Self { values }
+    }
+
+    /// Writes every gamerule into the strings stored in the level file.
+    /// Unknown rules already present are kept.
+    pub fn save(&self, rules: &mut HashMap<String, String>) {
+        for &rule in GAME_RULES {
+            rules.insert(rule.name().to_owned(), self.get(rule).to_string());
+        }
+    }
+
+    pub fn get(&self, rule: GameRule) -> GameRuleValue {
+        self.values
+            .get(rule.name())
+            .copied()
+            .unwrap_or_else(|| rule.default())
+    }
+
+    pub fn bool(&self, rule: BoolGameRule) -> bool {
+        match self.values.get(rule.name) {
+            Some(&GameRuleValue::Bool(value)) => value,
+            _ => rule.default,
+        }
+    }
+
+    pub fn int(&self, rule: IntGameRule) -> i32 {
+        match self.values.get(rule.name) {
+            Some(&GameRuleValue::Int(value)) => value,
+            _ => rule.default,
+        }
+    }
+
+    /// Sets a gamerule.
+    ///
+    /// # Panics
+    /// Panics if `value` is not of the rule's type.
+    pub fn set(&mut self, rule: GameRule, value: GameRuleValue) {
+        match (rule, value) {
+            (GameRule::Bool(_), GameRuleValue::Bool(_))
+            | (GameRule::Int(_), GameRuleValue::Int(_)) => {
+                self.values.insert(rule.name(), value);
+            }
+            _ => panic!("value {:?} has the wrong type for {:?}", value, rule),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_are_sorted() {
+        let names: Vec<_> = GAME_RULES.iter().map(|rule| rule.name()).collect();
+        let mut sorted = names.clone();
+        sorted.sort_by_key(|name| name.to_lowercase());
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn load_and_save() {
+        let mut stored = HashMap::new();
+        stored.insert("keepInventory".to_owned(), "true".to_owned());
+        stored.insert("randomTickSpeed".to_owned(), "abc".to_owned());
+        stored.insert("customRule".to_owned(), "1".to_owned());
+
+        let mut rules = GameRules::load(&stored);
+        assert!(rules.bool(KEEP_INVENTORY));
+        assert!(rules.bool(DO_DAYLIGHT_CYCLE));
+        assert_eq!(rules.int(RANDOM_TICK_SPEED), 3);
+
+        rules.set(GameRule::Int(RANDOM_TICK_SPEED), GameRuleValue::Int(10));
+        rules.save(&mut stored);
+        assert_eq!(stored["randomTickSpeed"], "10");
+        assert_eq!(stored["keepInventory"], "true");
+        assert_eq!(stored["mobGriefing"], "true");
+        assert_eq!(stored["customRule"], "1");
+        assert_eq!(stored.len(), GAME_RULES.len() + 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_wrong_type() {
+        GameRules::default().set(GameRule::Bool(MOB_GRIEFING), GameRuleValue::Int(1));
+    }
+}
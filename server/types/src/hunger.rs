@@ -0,0 +1,43 @@
+//! Hunger: the food level and saturation of players,
+//! which are used up as they exert themselves.
+
+/// Food level of a player who is not hungry.
+pub const MAX_FOOD: i32 = 20;
+
+/// Exhaustion at which a point of saturation, or of food
+/// if there is no saturation left, is used up.
+pub const EXHAUSTION_PER_FOOD: f32 = 4.0;
+
+/// Maximum exhaustion a player may accumulate.
+pub const MAX_EXHAUSTION: f32 = 40.0;
+
+/// Component storing a player's hunger.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hunger {
+    /// Food level, from 0 to `MAX_FOOD`.
+    pub food: i32,
+    /// Saturation, which is used up before the food level.
+    /// It never exceeds the food level.
+    pub saturation: f32,
+    pub exhaustion: f32,
+    /// Ticks since health was last regenerated from food.
+    pub regeneration_timer: u32,
+}
+
+impl Default for Hunger {
+    fn default() -> Self {
+        Self {
+            food: MAX_FOOD,
+            saturation: 5.0,
+            exhaustion: 0.0,
+            regeneration_timer: 0,
+        }
+    }
+}
+
+impl Hunger {
+    /// Adds exhaustion, e.g. from healing.
+    pub fn add_exhaustion(&mut self, exhaustion: f32) {
+        self.exhaustion = (self.exhaustion + exhaustion).min(MAX_EXHAUSTION);
+    }
+}
@@ -54,20 +54,80 @@ pub const TICK_LENGTH: u64 = 1000 / TPS;
 
 /// Height from a player's position where the camera lies.
 pub const PLAYER_EYE_HEIGHT: f64 = 1.62;
+/// Width of a player's bounding box.
+pub const PLAYER_WIDTH: f64 = 0.6;
+/// Height of a player's bounding box.
+pub const PLAYER_HEIGHT: f64 = 1.8;
+/// Height from a sneaking player's position where the camera lies.
+pub const PLAYER_SNEAKING_EYE_HEIGHT: f64 = 1.54;
+/// Height of a swimming player's bounding box.
+pub const PLAYER_SWIMMING_HEIGHT: f64 = 0.6;
+/// Height from a swimming player's position where the camera lies.
+pub const PLAYER_SWIMMING_EYE_HEIGHT: f64 = 0.4;
 
 // COMPONENTS
 
+mod advancements;
+mod attributes;
+mod block_changes;
+mod block_entities;
+mod block_log;
+mod cooldowns;
+mod damage;
+mod effects;
+mod experience;
+mod game_rules;
+mod hunger;
+mod maps;
+mod moderation;
 mod network;
+mod network_stats;
+mod online_players;
+mod operators;
+mod permissions;
 mod physics;
+mod recipes;
+mod scoreboard;
+mod statistics;
 mod task;
-
+mod tick_times;
+mod title;
+
+pub use advancements::*;
+pub use attributes::*;
+pub use block_changes::*;
+pub use block_entities::*;
+pub use block_log::*;
+pub use cooldowns::*;
+pub use damage::*;
+pub use effects::*;
+pub use experience::*;
 pub use feather_core::inventory::Inventory;
-pub use network::{Network, ServerToWorkerMessage, WorkerToServerMessage};
-pub use physics::{AABBExt, Physics, PhysicsBuilder};
+pub use game_rules::*;
+pub use hunger::*;
+pub use maps::*;
+pub use moderation::*;
+pub use network::{
+    flush_network, ConnectionCounters, ConnectionStats, Network, ServerToWorkerMessage,
+    WorkerToServerMessage,
+};
+pub use network_stats::*;
+pub use online_players::OnlinePlayers;
+pub use operators::*;
+pub use permissions::*;
+pub use physics::{AABBExt, InGround, Physics, PhysicsBuilder, Projectile, ProjectileKind};
+pub use recipes::*;
+pub use scoreboard::*;
+pub use statistics::*;
+pub use tick_times::*;
+pub use title::*;
 pub use uuid::Uuid;
 
 use feather_core::inventory::SlotIndex;
-use feather_core::util::{BlockPosition, ChunkPosition, ClientboundAnimation, Position};
+use feather_core::util::{
+    BlockPosition, ChunkPosition, ClientboundAnimation, Difficulty, Gamemode, Position,
+};
+use std::collections::BTreeSet;
 
 /// The item an entity is currently holding.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
@@ -77,6 +137,30 @@ pub struct HeldItem(pub SlotIndex);
 #[derive(Debug, Clone, Default)]
 pub struct Name(pub String);
 
+/// A custom name given to a non-player entity, e.g.
+/// with a name tag, as a JSON text component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomName(pub String);
+
+/// The namespaced identifier of a non-player entity's
+/// type, such as `minecraft:zombie`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EntityType(pub &'static str);
+
+/// Tags added to an entity with `/tag`, which
+/// commands can use to select it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tags(pub BTreeSet<String>);
+
+/// The health of a living entity, in half-hearts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Health(pub f32);
+
+/// The bed at which a player respawns after dying,
+/// rather than at the world spawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BedSpawn(pub BlockPosition);
+
 /// Position of an entity on the previous tick.
 #[derive(Copy, Clone, Debug)]
 pub struct PreviousPosition(pub Position);
@@ -91,6 +175,17 @@ impl Default for Velocity {
     }
 }
 
+/// Converts float-based velocity in blocks per tick
+/// to the format used by the protocol.
+pub fn protocol_velocity(vel: glm::DVec3) -> (i16, i16, i16) {
+    // These are in units of 1/8000 block per tick.
+    (
+        (vel.x * 8000.0) as i16,
+        (vel.y * 8000.0) as i16,
+        (vel.z * 8000.0) as i16,
+    )
+}
+
 /// Velocity of an entity on the previous tick.
 #[derive(Copy, Clone, Debug)]
 pub struct PreviousVelocity(pub glm::DVec3);
@@ -199,23 +294,145 @@ pub struct LastKnownPositions(pub DashMap<Entity, Position>);
 #[derive(Debug, Clone)]
 pub struct ProfileProperties(pub Vec<mojang_api::ProfileProperty>);
 
+/// A player's latency in milliseconds, measured from
+/// keep-alive round trips.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct Ping(pub u32);
+
+/// Plugin channels a player's client has registered
+/// to receive messages on.
+#[derive(Debug, Clone, Default)]
+pub struct PluginChannels(pub AHashSet<String>);
+
+/// The client brand a player reported, such as
+/// "vanilla" or the name of a mod loader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientBrand(pub String);
+
 /// Zero-sized marker component used to mark players.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Player;
 
+/// Marker component for the server console, the entity which
+/// runs commands typed into the server's terminal. It has a
+/// `Name` and a `Position` at the world spawn, but no `Network`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Console;
+
+/// The view distance of a player: the radius, in chunks, of
+/// the area around them which is sent to their client and
+/// within which they see entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewDistance {
+    /// The view distance chosen for the player: the configured
+    /// default, unless it was changed with `/viewdistance`.
+    pub target: u8,
+    /// The view distance in use. This is below `target` while the
+    /// automatic view distance is lowering it to reduce load.
+    pub current: u8,
+}
+
+impl ViewDistance {
+    pub fn new(distance: u8) -> Self {
+        Self {
+            target: distance,
+            current: distance,
+        }
+    }
+}
+
+/// Marker component for players who are sprinting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sprinting;
+
+/// Marker component for players who are sneaking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sneaking;
+
+/// Marker component for players who are flying
+/// in creative or spectator mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flying;
+
+/// Marker component for players who are swimming,
+/// which reduces the height of their bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Swimming;
+
+/// Component for players who are gliding with an elytra,
+/// which gives them the same bounding box as swimming.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gliding {
+    /// Tick at which the player started gliding.
+    pub since: u64,
+    /// Horizontal distance the player moved during
+    /// their last movement, used to detect collisions.
+    pub last_speed: f64,
+}
+
+/// Returns the height of a player's bounding box.
+pub fn player_height(world: &World, player: Entity) -> f64 {
+    if world.has::<Swimming>(player) || world.has::<Gliding>(player) {
+        PLAYER_SWIMMING_HEIGHT
+    } else {
+        PLAYER_HEIGHT
+    }
+}
+
+/// Returns the height from a player's position where the camera lies.
+pub fn player_eye_height(world: &World, player: Entity) -> f64 {
+    if world.has::<Swimming>(player) || world.has::<Gliding>(player) {
+        PLAYER_SWIMMING_EYE_HEIGHT
+    } else if world.has::<Sneaking>(player) {
+        PLAYER_SNEAKING_EYE_HEIGHT
+    } else {
+        PLAYER_EYE_HEIGHT
+    }
+}
+
+/// Marker component for living entities: players and mobs.
+///
+/// Living entities push each other apart when they overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Living;
+
+/// Marker component for entities which neither push nor
+/// are pushed by other entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoPush;
+
+/// Marker component for entities which push other entities
+/// but are never pushed themselves, e.g. shulkers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stationary;
+
+/// Category of an entity, used to enforce per-chunk entity limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityCategory {
+    Mob,
+    Item,
+    ItemFrame,
+}
+
 // RESOURCES
 
 use ahash::AHashSet;
 use dashmap::DashMap;
 use feather_core::anvil::entity::{EntityData, EntityDataKind};
 use feather_core::blocks::BlockId;
-use feather_core::items::ItemStack;
+use feather_core::items::{EntityTagNbt, ItemStack};
 use feather_core::network::Packet;
-use fecs::{Entity, EntityBuilder, EntityRef};
+use feather_core::text::Text;
+use fecs::{Entity, EntityBuilder, EntityRef, World};
+use parking_lot::Mutex;
 use smallvec::SmallVec;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 mod game;
-pub use feather_server_config::{Config, ProxyMode};
+pub use feather_server_config::{
+    AutoViewDistance, Config, PermissionsMode, ProxyMode, RateLimit, RateLimits,
+};
 pub use feather_server_packet_buffer::{PacketBuffer, PacketBuffers};
 pub use game::*;
 pub use task::*;
@@ -230,6 +447,94 @@ pub struct BlockUpdateEvent {
     pub old: BlockId,
     /// New block
     pub new: BlockId,
+    /// What caused the update
+    pub cause: BlockUpdateCause,
+}
+
+/// Event triggered when a player right-clicks a block
+/// without sneaking, before any held item is used.
+#[derive(Copy, Clone, Debug)]
+pub struct BlockInteractEvent {
+    pub player: Entity,
+    pub pos: BlockPosition,
+    pub block: BlockId,
+}
+
+/// Requests that a block be destroyed as if broken without a tool:
+/// it is replaced with air, shows its breaking particles, and drops
+/// its items if `doTileDrops` is enabled.
+///
+/// This is a "request"-type event: it has one handler defined
+/// in the `entity` crate which breaks the block and spawns the drops.
+#[derive(Copy, Clone, Debug)]
+pub struct BlockDestroyRequest {
+    pub pos: BlockPosition,
+    pub cause: BlockUpdateCause,
+}
+
+/// Identifier shared by all block updates resulting from
+/// a single action, such as an explosion. Updates made in
+/// response to a neighbor update inherit its batch.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockUpdateBatch(pub u64);
+
+static NEXT_BLOCK_UPDATE_BATCH: AtomicU64 = AtomicU64::new(0);
+
+impl BlockUpdateBatch {
+    /// Allocates a new, unique batch.
+    pub fn new() -> Self {
+        Self(NEXT_BLOCK_UPDATE_BATCH.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for BlockUpdateBatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The cause of a block update.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BlockUpdateCause {
+    /// The entity which caused the update, if any. For
+    /// neighbor updates, this is the entity which caused
+    /// the original update; e.g. for a torch which broke
+    /// because its wall was mined, it is the player who
+    /// mined the wall.
+    pub entity: Option<Entity>,
+    /// Whether the update was made in response to an update
+    /// of an adjacent block, rather than being made directly.
+    pub neighbor_update: bool,
+    /// The batch to which the update belongs.
+    pub batch: BlockUpdateBatch,
+}
+
+impl BlockUpdateCause {
+    /// An update with no causing entity, e.g. one made by a command.
+    pub fn unknown() -> Self {
+        Self {
+            entity: None,
+            neighbor_update: false,
+            batch: BlockUpdateBatch::new(),
+        }
+    }
+
+    /// An update made directly by an entity, e.g.
+    /// a player placing or breaking a block.
+    pub fn entity(entity: Entity) -> Self {
+        Self {
+            entity: Some(entity),
+            ..Self::unknown()
+        }
+    }
+
+    /// An update made in response to an update with this cause.
+    pub fn neighbor_of(self) -> Self {
+        Self {
+            neighbor_update: true,
+            ..self
+        }
+    }
 }
 
 /// Triggered directly _before_ an entity is removed from the world.
@@ -253,12 +558,38 @@ pub struct PlayerJoinEvent {
     pub player: Entity,
 }
 
+/// A player's response to the server resource pack.
+///
+/// Players have this as a component once they have responded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResourcePackResponse {
+    Loaded,
+    Declined,
+    FailedDownload,
+    Accepted,
+}
+
+/// Triggered when a player responds to the server resource pack.
+#[derive(Copy, Clone, Debug)]
+pub struct ResourcePackResponseEvent {
+    pub player: Entity,
+    pub response: ResourcePackResponse,
+}
+
 /// Triggered when a player leaves.
 #[derive(Copy, Clone, Debug)]
 pub struct PlayerLeaveEvent {
     pub player: Entity,
 }
 
+/// Triggered when a player's gamemode is changed.
+#[derive(Copy, Clone, Debug)]
+pub struct GamemodeUpdateEvent {
+    pub player: Entity,
+    pub old: Gamemode,
+    pub new: Gamemode,
+}
+
 /// Triggered when an entity lands on the ground.
 #[derive(Copy, Clone, Debug)]
 pub struct EntityLandEvent {
@@ -267,6 +598,29 @@ pub struct EntityLandEvent {
     pub pos: Position,
 }
 
+/// What a projectile hit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProjectileTarget {
+    Block(BlockPosition),
+    Entity(Entity),
+}
+
+/// Triggered when a projectile hits a block or an entity.
+///
+/// Handlers for each projectile kind decide what happens
+/// to the projectile; it is not despawned automatically.
+#[derive(Copy, Clone, Debug)]
+pub struct ProjectileHitEvent {
+    pub projectile: Entity,
+    pub kind: ProjectileKind,
+    pub shooter: Option<Entity>,
+    pub target: ProjectileTarget,
+    /// Position at which the projectile hit.
+    pub pos: Position,
+    /// Velocity of the projectile upon impact.
+    pub velocity: glm::DVec3,
+}
+
 /// Event triggered when an item is dropped.
 ///
 /// Before this event is triggered, the item
@@ -325,13 +679,103 @@ pub struct PlayerAnimationEvent {
     pub animation: ClientboundAnimation,
 }
 
+/// Triggered when a player sends a message on a plugin channel
+/// which the server does not handle itself.
+#[derive(Debug, Clone)]
+pub struct PluginMessageEvent {
+    pub player: Entity,
+    pub channel: String,
+    pub data: Vec<u8>,
+}
+
 /// Event triggered when a chat message is sent out
 #[derive(Debug, Clone)]
 pub struct ChatEvent {
-    /// The JSON-formatted message
-    pub message: String,
+    /// The message
+    pub message: Text,
     /// The position of the message
     pub position: ChatPosition,
+    /// The entity which sent the message, if any. Players
+    /// who ignore the sender are not sent the message.
+    pub sender: Option<Entity>,
+}
+
+/// Component storing the UUIDs of the players whose
+/// messages a player ignores, as set with `/ignore`.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreList(pub BTreeSet<Uuid>);
+
+/// Returns whether `player` ignores messages from `sender`.
+pub fn is_ignoring(world: &World, player: Entity, sender: Entity) -> bool {
+    match (
+        world.try_get::<IgnoreList>(player),
+        world.try_get::<Uuid>(sender),
+    ) {
+        (Some(ignored), Some(sender)) => ignored.0.contains(&sender),
+        _ => false,
+    }
+}
+
+/// A chat message sent by a player, as handlers
+/// of `PlayerChatEvent` leave it.
+#[derive(Clone, Debug, Default)]
+pub struct PlayerChatMessage {
+    pub message: String,
+    /// If set, the message is not sent.
+    pub cancelled: bool,
+}
+
+/// Triggered when a player sends a chat message, private
+/// message or emote, before it is formatted and sent.
+///
+/// Handlers may rewrite the message in `outcome` or cancel
+/// it, e.g. to filter words or enforce a mute.
+#[derive(Clone, Debug)]
+pub struct PlayerChatEvent {
+    pub player: Entity,
+    pub outcome: Arc<Mutex<PlayerChatMessage>>,
+}
+
+/// Runs a message sent by a player through the `PlayerChatEvent`
+/// handlers, returning the message they leave, or `None` if it
+/// was cancelled or left empty. Messages sent by the console
+/// are returned as they are.
+pub fn filter_chat_message(
+    game: &mut Game,
+    world: &mut World,
+    player: Entity,
+    message: String,
+) -> Option<String> {
+    if world.has::<Console>(player) {
+        return Some(message);
+    }
+
+    let outcome = Arc::new(Mutex::new(PlayerChatMessage {
+        message,
+        cancelled: false,
+    }));
+    game.handle(
+        world,
+        PlayerChatEvent {
+            player,
+            outcome: Arc::clone(&outcome),
+        },
+    );
+    let outcome = std::mem::take(&mut *outcome.lock());
+    if outcome.cancelled || outcome.message.is_empty() {
+        None
+    } else {
+        Some(outcome.message)
+    }
+}
+
+/// Event triggered when a player runs a command.
+#[derive(Debug, Clone)]
+pub struct CommandEvent {
+    /// The player who ran the command, or the console.
+    pub sender: Entity,
+    /// The command line, without the leading slash.
+    pub command: String,
 }
 
 /// Different positions a chat message can be displayed
@@ -415,6 +859,188 @@ pub struct WeatherChangeEvent {
     pub duration: i32,
 }
 
+/// Triggered when a gamerule is changed with `/gamerule`.
+#[derive(Copy, Clone, Debug)]
+pub struct GameRuleChangeEvent {
+    pub rule: GameRule,
+    pub value: GameRuleValue,
+}
+
+/// Triggered when the difficulty is changed with `/difficulty`.
+#[derive(Copy, Clone, Debug)]
+pub struct DifficultyChangeEvent {
+    pub from: Difficulty,
+    pub to: Difficulty,
+}
+
+/// Requests that the weather be set for `duration` ticks,
+/// as by `/weather`. Handled in the `weather` crate.
+#[derive(Copy, Clone, Debug)]
+pub struct WeatherRequest {
+    pub weather: Weather,
+    pub duration: i32,
+}
+
+/// The cause of an explosion.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExplosionSource {
+    /// A creeper exploded. The creeper is despawned
+    /// after the explosion is handled.
+    Creeper(Entity),
+    /// Primed TNT exploded.
+    Tnt,
+    /// A bed was used in a dimension where beds explode.
+    Bed,
+    Other,
+}
+
+/// Explosion power of a creeper.
+pub const CREEPER_EXPLOSION_POWER: f32 = 3.0;
+/// Explosion power of TNT.
+pub const TNT_EXPLOSION_POWER: f32 = 4.0;
+/// Explosion power of a bed.
+pub const BED_EXPLOSION_POWER: f32 = 5.0;
+
+/// The blocks and entities affected by an explosion.
+#[derive(Clone, Debug, Default)]
+pub struct ExplosionEffects {
+    /// Blocks which will be destroyed.
+    pub blocks: Vec<BlockPosition>,
+    /// Entities which will be damaged and knocked back.
+    pub entities: Vec<Entity>,
+    /// If set, the explosion has no effect on the world.
+    pub cancelled: bool,
+}
+
+/// Triggered when an explosion has been computed but
+/// before it is applied to the world.
+///
+/// Handlers may remove blocks or entities from `effects`,
+/// e.g. to protect a region, or cancel the explosion entirely.
+#[derive(Clone, Debug)]
+pub struct ExplosionEvent {
+    pub pos: Position,
+    pub power: f32,
+    pub source: ExplosionSource,
+    pub effects: Arc<Mutex<ExplosionEffects>>,
+}
+
+/// Requests that an explosion be created. All explosions,
+/// regardless of their source, should use this request.
+///
+/// This is a "request"-type event: it has one handler defined
+/// in the `physics` crate which computes the explosion, triggers
+/// an `ExplosionEvent`, and then applies the result.
+#[derive(Copy, Clone, Debug)]
+pub struct ExplosionRequest {
+    pub pos: Position,
+    pub power: f32,
+    pub source: ExplosionSource,
+}
+
+/// Number of ticks before TNT ignited by a player explodes.
+pub const TNT_FUSE_TICKS: u32 = 80;
+
+/// Requests that the TNT block at `pos` be primed, replacing
+/// it with a primed TNT entity which explodes after `fuse` ticks.
+///
+/// This is a "request"-type event: it has one handler defined
+/// in the `entity` crate which removes the block and spawns the entity.
+#[derive(Copy, Clone, Debug)]
+pub struct TntPrimeRequest {
+    pub pos: BlockPosition,
+    pub fuse: u32,
+    /// The entity which ignited the TNT, if any.
+    pub igniter: Option<Entity>,
+}
+
+/// Triggered when a lightning bolt strikes, after the bolt
+/// entity has been spawned and before its effects are applied.
+#[derive(Copy, Clone, Debug)]
+pub struct LightningStrikeEvent {
+    pub bolt: Entity,
+    pub pos: Position,
+}
+
+/// Requests that a lightning bolt strike at the given position.
+/// Used by thunderstorms as well as channeling tridents and commands.
+///
+/// This is a "request"-type event: it has one handler defined
+/// in the `entity` crate which spawns the bolt and applies its effects.
+#[derive(Copy, Clone, Debug)]
+pub struct LightningStrikeRequest {
+    pub pos: Position,
+}
+
+/// Requests that an entity be summoned, as by `/summon`.
+///
+/// This is a "request"-type event: it has one handler defined
+/// in the `entity` crate which creates and spawns the entity,
+/// storing it in `summoned`. `summoned` is left empty if no
+/// entity with the identifier can be summoned.
+#[derive(Clone, Debug)]
+pub struct SummonRequest {
+    /// The namespaced identifier of the entity type.
+    pub id: String,
+    pub pos: Position,
+    /// Tags applied to the entity if it is a mob.
+    pub tag: EntityTagNbt,
+    pub summoned: Arc<Mutex<Option<Entity>>>,
+}
+
+impl SummonRequest {
+    pub fn new(id: String, pos: Position, tag: EntityTagNbt) -> Self {
+        Self {
+            id,
+            pos,
+            tag,
+            summoned: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// Requests that the server shut down, as by `/stop`.
+///
+/// This is a "request"-type event: it has one handler defined in
+/// the `feather-server` crate which stops the game loop at the end
+/// of the tick, after which players are kicked and the world saved.
+#[derive(Copy, Clone, Debug)]
+pub struct ShutdownRequest;
+
+/// Requests that the world be saved, as by `/save-all`. This
+/// saves the world even if automatic saving is disabled.
+///
+/// This is a "request"-type event: it has one handler defined in
+/// the `chunk` crate which starts saving modified chunks and player
+/// data over the following ticks, or saves everything at once if
+/// `flush` is set.
+#[derive(Copy, Clone, Debug)]
+pub struct SaveRequest {
+    pub flush: bool,
+}
+
+/// Requests that automatic saving be enabled or disabled, as
+/// by `/save-on` and `/save-off`. While it is disabled, chunks
+/// are kept loaded, since unloading a chunk saves it.
+///
+/// This is a "request"-type event: it has one handler defined in the
+/// `chunk` crate which stores in `changed` whether automatic saving
+/// was not already in the requested state.
+#[derive(Clone, Debug)]
+pub struct AutoSaveRequest {
+    pub enabled: bool,
+    pub changed: Arc<Mutex<bool>>,
+}
+
+impl AutoSaveRequest {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            changed: Arc::new(Mutex::new(false)),
+        }
+    }
+}
+
 /// Requests that a chunk be held for the given client.
 ///
 /// This is a "request"-type event: it has one handler defined
@@ -432,6 +1058,16 @@ pub struct ReleaseChunkRequest {
     pub chunk: ChunkPosition,
 }
 
+/// Requests that a player's view distance be set, as by `/viewdistance`.
+///
+/// This is a "request"-type event: it has one handler defined in the
+/// `player` crate which sends or unloads chunks and entities to match.
+#[derive(Copy, Clone, Debug)]
+pub struct ViewDistanceRequest {
+    pub player: Entity,
+    pub distance: u8,
+}
+
 /// Requests that a chunk be queued for loading.
 #[derive(Copy, Clone, Debug)]
 pub struct LoadChunkRequest {
@@ -0,0 +1,289 @@
+//! The state of map items, whose pixels are rendered
+//! by the server from the terrain around their holders.
+
+use crate::RunningTasks;
+use ahash::{AHashMap, AHashSet};
+use feather_core::anvil::map::{self, MapBanner, MapData};
+use feather_core::network::packets::MapUpdate;
+use fecs::Entity;
+use std::path::{Path, PathBuf};
+
+/// Number of pixels along each side of a map.
+const SIZE: usize = map::MAP_SIZE;
+
+/// The state of a single map.
+#[derive(Debug, Clone)]
+pub struct MapState {
+    /// Zoom level, where each pixel covers `2^scale` blocks.
+    pub scale: i8,
+    pub dimension: i32,
+    /// The block at the center of the map.
+    pub center: (i32, i32),
+    /// Whether player markers are displayed.
+    pub tracking_position: bool,
+    pub banners: Vec<MapBanner>,
+    colors: Vec<u8>,
+    /// Region of pixels changed since updates were last
+    /// sent, as minimum and maximum `(x, z)`.
+    dirty: Option<((u8, u8), (u8, u8))>,
+    /// Players who have been sent the whole map.
+    viewers: AHashSet<Entity>,
+    /// Whether the map has changed since it was last saved.
+    unsaved: bool,
+}
+
+impl MapState {
+    /// Creates a blank map.
+    pub fn new(scale: i8, center: (i32, i32), dimension: i32) -> Self {
+        Self {
+            scale,
+            dimension,
+            center,
+            tracking_position: true,
+            banners: vec![],
+            colors: vec![0; SIZE * SIZE],
+            dirty: None,
+            viewers: AHashSet::new(),
+            unsaved: true,
+        }
+    }
+
+    fn from_data(data: MapData) -> Self {
+        let mut colors: Vec<u8> = data.colors.into_iter().map(|color| color as u8).collect();
+        colors.resize(SIZE * SIZE, 0);
+
+        Self {
+            scale: data.scale,
+            dimension: data.dimension,
+            center: (data.x_center, data.z_center),
+            tracking_position: data.tracking_position,
+            banners: data.banners,
+            colors,
+            dirty: None,
+            viewers: AHashSet::new(),
+            unsaved: false,
+        }
+    }
+
+    fn to_data(&self) -> MapData {
+        MapData {
+            scale: self.scale,
+            dimension: self.dimension,
+            x_center: self.center.0,
+            z_center: self.center.1,
+            tracking_position: self.tracking_position,
+            unlimited_tracking: false,
+            banners: self.banners.clone(),
+            colors: self.colors.iter().map(|&color| color as i8).collect(),
+        }
+    }
+
+    /// Returns the color of the pixel at `x`, `z`.
+    pub fn pixel(&self, x: usize, z: usize) -> u8 {
+        self.colors[z * SIZE + x]
+    }
+
+    /// Sets the color of the pixel at `x`, `z`,
+    /// marking it to be sent to viewers if it changed.
+    pub fn set_pixel(&mut self, x: usize, z: usize, color: u8) {
+        let index = z * SIZE + x;
+        if self.colors[index] == color {
+            return;
+        }
+        self.colors[index] = color;
+        self.unsaved = true;
+
+        let (x, z) = (x as u8, z as u8);
+        self.dirty = Some(match self.dirty {
+            Some((min, max)) => ((min.0.min(x), min.1.min(z)), (max.0.max(x), max.1.max(z))),
+            None => ((x, z), (x, z)),
+        });
+    }
+
+    /// Returns an update holding every pixel of the map.
+    pub fn full_update(&self) -> MapUpdate {
+        MapUpdate {
+            columns: SIZE as u8,
+            rows: SIZE as u8,
+            x: 0,
+            z: 0,
+            data: self.colors.clone(),
+        }
+    }
+
+    /// Returns an update holding the pixels changed since the
+    /// last call, or `None` if nothing has changed.
+    pub fn take_update(&mut self) -> Option<MapUpdate> {
+        let (min, max) = self.dirty.take()?;
+        let (columns, rows) = (max.0 - min.0 + 1, max.1 - min.1 + 1);
+
+        let mut data = Vec::with_capacity(columns as usize * rows as usize);
+        for z in min.1..=max.1 {
+            let row = z as usize * SIZE;
+            data.extend_from_slice(&self.colors[row + min.0 as usize..=row + max.0 as usize]);
+        }
+
+        Some(MapUpdate {
+            columns,
+            rows,
+            x: min.0,
+            z: min.1,
+            data,
+        })
+    }
+
+    /// Adds a player who is holding the map. Returns `false` if
+    /// they were already viewing it.
+    pub fn add_viewer(&mut self, player: Entity) -> bool {
+        self.viewers.insert(player)
+    }
+
+    /// Removes all viewers for which `f` returns `false`.
+    pub fn retain_viewers(&mut self, f: impl FnMut(&Entity) -> bool) {
+        self.viewers.retain(f);
+    }
+
+    /// Returns the players who have been sent the map.
+    pub fn viewers(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.viewers.iter().copied()
+    }
+
+    /// Marks the given banner on the map, or removes its
+    /// marker if it is already marked.
+    pub fn toggle_banner(&mut self, banner: MapBanner) {
+        let existing = self.banners.iter().position(|b| b.pos == banner.pos);
+        match existing {
+            Some(index) => {
+                self.banners.remove(index);
+            }
+            None => self.banners.push(banner),
+        }
+        self.unsaved = true;
+    }
+
+    /// Returns a copy of this map with the given scale and no
+    /// pixels drawn, as created when a map is zoomed out.
+    pub fn zoomed_out(&self, scale: i8) -> Self {
+        let mut map = Self::new(scale, self.center, self.dimension);
+        map.tracking_position = self.tracking_position;
+        map
+    }
+}
+
+/// Resource storing all maps in the world.
+#[derive(Debug, Default)]
+pub struct Maps {
+    world_dir: PathBuf,
+    maps: AHashMap<i32, MapState>,
+    /// The last ID handed out to a map.
+    last_id: Option<i32>,
+}
+
+impl Maps {
+    /// Loads all maps from the given world directory.
+    pub async fn load(world_dir: &Path) -> anyhow::Result<Self> {
+        let mut maps = Self {
+            world_dir: world_dir.to_path_buf(),
+            maps: AHashMap::new(),
+            last_id: map::load_last_map_id(world_dir).await?,
+        };
+
+        for id in 0..=maps.last_id.unwrap_or(-1) {
+            if let Some(data) = map::load_map_data(world_dir, id).await? {
+                maps.maps.insert(id, MapState::from_data(data));
+            }
+        }
+
+        Ok(maps)
+    }
+
+    /// Returns the number of maps.
+    pub fn len(&self) -> usize {
+        self.maps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+
+    /// Adds a map, returning its new ID.
+    pub fn create(&mut self, map: MapState, tasks: &RunningTasks) -> i32 {
+        let id = self.last_id.map_or(0, |id| id + 1);
+        self.last_id = Some(id);
+        self.maps.insert(id, map);
+
+        // Save the ID counter right away so that the
+        // ID can't be handed out again after a crash.
+        let world_dir = self.world_dir.clone();
+        tasks.schedule(async move {
+            if let Err(e) = map::save_last_map_id(&world_dir, id).await {
+                log::error!("Failed to save map ID counter: {}", e);
+            }
+        });
+
+        id
+    }
+
+    pub fn get(&self, id: i32) -> Option<&MapState> {
+        self.maps.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: i32) -> Option<&mut MapState> {
+        self.maps.get_mut(&id)
+    }
+
+    /// Writes maps which have changed since they were last
+    /// saved. The writes are performed asynchronously on the
+    /// given task manager.
+    pub fn save(&mut self, tasks: &RunningTasks) {
+        for (&id, map) in self.maps.iter_mut().filter(|(_, map)| map.unsaved) {
+            map.unsaved = false;
+
+            let data = map.to_data();
+            let world_dir = self.world_dir.clone();
+            tasks.schedule(async move {
+                if let Err(e) = map::save_map_data(&world_dir, id, &data).await {
+                    log::error!("Failed to save map {}: {}", id, e);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_cover_changed_pixels() {
+        let mut map = MapState::new(0, (0, 0), 0);
+        assert!(map.take_update().is_none());
+
+        map.set_pixel(3, 10, 5);
+        map.set_pixel(5, 8, 6);
+        // unchanged pixels are not sent
+        map.set_pixel(0, 0, 0);
+
+        let update = map.take_update().unwrap();
+        assert_eq!((update.x, update.z), (3, 8));
+        assert_eq!((update.columns, update.rows), (3, 3));
+        assert_eq!(update.data[2], 6);
+        assert_eq!(update.data[6], 5);
+        assert!(map.take_update().is_none());
+    }
+
+    #[test]
+    fn banners_toggle() {
+        let mut map = MapState::new(0, (0, 0), 0);
+        let banner = MapBanner {
+            pos: map::MapBannerPos { x: 1, y: 64, z: 2 },
+            color: String::from("blue"),
+            name: None,
+        };
+
+        map.toggle_banner(banner.clone());
+        assert_eq!(map.banners, vec![banner.clone()]);
+        map.toggle_banner(banner);
+        assert!(map.banners.is_empty());
+    }
+}
@@ -0,0 +1,474 @@
+//! Storage of player punishments—bans, IP bans, and mutes.
+//!
+//! Punishments are persisted to JSON files in the server
+//! directory. Bans use the format of vanilla's ban list, so
+//! that the file can be moved between servers, and mutes,
+//! which vanilla lacks, are stored the same way. Each punishment
+//! may have an expiry date, which is absolute so that temporary
+//! punishments keep counting down across server restarts.
+
+use crate::RunningTasks;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::io::ErrorKind;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// File name of the ban list.
+pub const BANS_FILE: &str = "banned-players.json";
+/// File name of the IP ban list.
+pub const IP_BANS_FILE: &str = "banned-ips.json";
+/// File name of the mute list.
+pub const MUTES_FILE: &str = "muted-players.json";
+
+/// Format of the dates in ban lists.
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S %z";
+/// Expiry date of permanent punishments.
+const FOREVER: &str = "forever";
+
+/// A ban or a mute applied to a player.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Punishment {
+    /// UUID of the punished player, if known.
+    ///
+    /// This is `None` when the player was punished
+    /// by name while offline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<Uuid>,
+    /// Name of the punished player.
+    pub name: String,
+    /// UNIX timestamp, in seconds, at which the punishment was issued.
+    #[serde(with = "date")]
+    pub created: u64,
+    /// Name of whoever issued the punishment.
+    pub source: String,
+    /// UNIX timestamp, in seconds, at which the punishment expires.
+    /// `None` indicates a permanent punishment.
+    #[serde(with = "expiry")]
+    pub expires: Option<u64>,
+    /// Reason given for the punishment.
+    pub reason: String,
+}
+
+impl Punishment {
+    /// Returns whether this punishment applies to the player
+    /// with the given UUID and name.
+    pub fn applies_to(&self, uuid: Uuid, name: &str) -> bool {
+        match self.uuid {
+            Some(punished) => punished == uuid,
+            None => self.name.eq_ignore_ascii_case(name),
+        }
+    }
+
+    /// Returns whether this punishment has expired at
+    /// the given UNIX timestamp.
+    pub fn is_expired(&self, now: u64) -> bool {
+        match self.expires {
+            Some(expires) => expires <= now,
+            None => false,
+        }
+    }
+
+    /// Returns the number of seconds remaining until
+    /// this punishment expires, or `None` if it is permanent.
+    pub fn remaining(&self, now: u64) -> Option<u64> {
+        self.expires.map(|expires| expires.saturating_sub(now))
+    }
+}
+
+/// A list of punishments of the same kind, backed
+/// by a JSON file.
+#[derive(Debug, Clone)]
+pub struct PunishmentList {
+    path: PathBuf,
+    entries: Vec<Punishment>,
+}
+
+impl PunishmentList {
+    /// Creates an empty list which will be saved
+    /// to the given path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            entries: vec![],
+        }
+    }
+
+    /// Loads a list from the given path. A missing file
+    /// results in an empty list.
+    pub fn load(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let entries = load_entries(&path)?;
+        Ok(Self { path, entries })
+    }
+
+    /// Writes this list to its file. The write is performed
+    /// asynchronously on the given task manager.
+    pub fn save(&self, tasks: &RunningTasks) {
+        save_entries(&self.path, &self.entries, tasks);
+    }
+
+    /// Returns the active punishment for the given player, if any.
+    pub fn find(&self, uuid: Uuid, name: &str, now: u64) -> Option<&Punishment> {
+        self.entries
+            .iter()
+            .find(|p| p.applies_to(uuid, name) && !p.is_expired(now))
+    }
+
+    /// Returns the punishment for the player with the given name, if any.
+    pub fn find_by_name(&self, name: &str) -> Option<&Punishment> {
+        self.entries
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Adds a punishment, replacing any existing punishment
+    /// for the same player.
+    pub fn insert(&mut self, punishment: Punishment) {
+        self.entries.retain(|p| {
+            !(p.name.eq_ignore_ascii_case(&punishment.name)
+                || (p.uuid.is_some() && p.uuid == punishment.uuid))
+        });
+        self.entries.push(punishment);
+    }
+
+    /// Removes the punishment for the player with the given name,
+    /// returning it if it existed.
+    pub fn remove(&mut self, name: &str) -> Option<Punishment> {
+        let index = self
+            .entries
+            .iter()
+            .position(|p| p.name.eq_ignore_ascii_case(name))?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Removes all punishments which have expired at the
+    /// given timestamp, returning them.
+    pub fn remove_expired(&mut self, now: u64) -> Vec<Punishment> {
+        let (expired, active) = self.entries.drain(..).partition(|p| p.is_expired(now));
+        self.entries = active;
+        expired
+    }
+
+    /// Returns an iterator over the punishments in this list.
+    pub fn iter(&self) -> impl Iterator<Item = &Punishment> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A ban of an IP address, which applies to
+/// every player connecting from it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpBan {
+    pub ip: IpAddr,
+    /// UNIX timestamp, in seconds, at which the ban was issued.
+    #[serde(with = "date")]
+    pub created: u64,
+    /// Name of whoever issued the ban.
+    pub source: String,
+    /// UNIX timestamp, in seconds, at which the ban expires.
+    /// `None` indicates a permanent ban.
+    #[serde(with = "expiry")]
+    pub expires: Option<u64>,
+    /// Reason given for the ban.
+    pub reason: String,
+}
+
+impl IpBan {
+    pub fn is_expired(&self, now: u64) -> bool {
+        match self.expires {
+            Some(expires) => expires <= now,
+            None => false,
+        }
+    }
+
+    /// Returns the number of seconds remaining until
+    /// this ban expires, or `None` if it is permanent.
+    pub fn remaining(&self, now: u64) -> Option<u64> {
+        self.expires.map(|expires| expires.saturating_sub(now))
+    }
+}
+
+/// The list of IP bans, backed by a JSON file.
+#[derive(Debug, Clone)]
+pub struct IpBanList {
+    path: PathBuf,
+    entries: Vec<IpBan>,
+}
+
+impl IpBanList {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            entries: vec![],
+        }
+    }
+
+    /// Loads a list from the given path. A missing file
+    /// results in an empty list.
+    pub fn load(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let entries = load_entries(&path)?;
+        Ok(Self { path, entries })
+    }
+
+    /// Writes this list to its file. The write is performed
+    /// asynchronously on the given task manager.
+    pub fn save(&self, tasks: &RunningTasks) {
+        save_entries(&self.path, &self.entries, tasks);
+    }
+
+    /// Returns the active ban of the given address, if any.
+    pub fn find(&self, ip: IpAddr, now: u64) -> Option<&IpBan> {
+        self.entries
+            .iter()
+            .find(|ban| ban.ip == ip && !ban.is_expired(now))
+    }
+
+    /// Adds a ban, replacing any existing ban of the same address.
+    pub fn insert(&mut self, ban: IpBan) {
+        self.entries.retain(|existing| existing.ip != ban.ip);
+        self.entries.push(ban);
+    }
+
+    /// Removes the ban of the given address,
+    /// returning it if it existed.
+    pub fn remove(&mut self, ip: IpAddr) -> Option<IpBan> {
+        let index = self.entries.iter().position(|ban| ban.ip == ip)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Removes all bans which have expired at the
+    /// given timestamp, returning them.
+    pub fn remove_expired(&mut self, now: u64) -> Vec<IpBan> {
+        let (expired, active) = self.entries.drain(..).partition(|ban| ban.is_expired(now));
+        self.entries = active;
+        expired
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &IpBan> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+pub(crate) fn load_entries<T: DeserializeOwned>(path: &Path) -> anyhow::Result<Vec<T>> {
+    match std::fs::read_to_string(path) {
+        Ok(s) => Ok(serde_json::from_str(&s)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub(crate) fn save_entries<T: Serialize>(path: &Path, entries: &[T], tasks: &RunningTasks) {
+    let json = serde_json::to_string_pretty(entries).expect("failed to serialize list");
+    let path = path.to_owned();
+
+    tasks.schedule(async move {
+        if let Err(e) = tokio::fs::write(&path, json).await {
+            log::error!("Failed to save {}: {}", path.display(), e);
+        }
+    });
+}
+
+/// Resource storing the server's bans and mutes.
+#[derive(Debug, Clone)]
+pub struct Moderation {
+    pub bans: PunishmentList,
+    pub ip_bans: IpBanList,
+    pub mutes: PunishmentList,
+}
+
+impl Default for Moderation {
+    fn default() -> Self {
+        Self {
+            bans: PunishmentList::new(BANS_FILE),
+            ip_bans: IpBanList::new(IP_BANS_FILE),
+            mutes: PunishmentList::new(MUTES_FILE),
+        }
+    }
+}
+
+impl Moderation {
+    /// Loads the ban and mute lists from the given directory.
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            bans: PunishmentList::load(dir.join(BANS_FILE))?,
+            ip_bans: IpBanList::load(dir.join(IP_BANS_FILE))?,
+            mutes: PunishmentList::load(dir.join(MUTES_FILE))?,
+        })
+    }
+}
+
+/// Serializes UNIX timestamps as dates in the
+/// format of vanilla's ban lists.
+mod date {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(timestamp: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        let date = Utc.timestamp(*timestamp as i64, 0);
+        serializer.collect_str(&date.format(DATE_FORMAT))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let date = String::deserialize(deserializer)?;
+        parse(&date).map_err(de::Error::custom)
+    }
+
+    pub fn parse(date: &str) -> Result<u64, chrono::ParseError> {
+        DateTime::parse_from_str(date, DATE_FORMAT).map(|date| date.timestamp().max(0) as u64)
+    }
+}
+
+/// Serializes expiry dates, which are `forever`
+/// for permanent punishments.
+mod expiry {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        expires: &Option<u64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match expires {
+            Some(expires) => date::serialize(expires, serializer),
+            None => serializer.serialize_str(FOREVER),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        let expires = String::deserialize(deserializer)?;
+        if expires == FOREVER {
+            Ok(None)
+        } else {
+            date::parse(&expires).map(Some).map_err(de::Error::custom)
+        }
+    }
+}
+
+/// Returns the current UNIX timestamp in seconds,
+/// as used for punishment timestamps.
+pub fn moderation_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn punishment(name: &str, uuid: Option<Uuid>, expires: Option<u64>) -> Punishment {
+        Punishment {
+            uuid,
+            name: name.to_owned(),
+            source: String::from("Server"),
+            reason: String::new(),
+            created: 0,
+            expires,
+        }
+    }
+
+    #[test]
+    fn find_by_uuid_or_name() {
+        let mut list = PunishmentList::new("test.json");
+        let uuid = Uuid::new_v4();
+        list.insert(punishment("caelunshun", Some(uuid), None));
+        list.insert(punishment("Notch", None, None));
+
+        assert!(list.find(uuid, "renamed", 0).is_some());
+        assert!(list.find(Uuid::new_v4(), "caelunshun", 0).is_none());
+        assert!(list.find(Uuid::new_v4(), "notch", 0).is_some());
+    }
+
+    #[test]
+    fn insert_replaces_existing() {
+        let mut list = PunishmentList::new("test.json");
+        list.insert(punishment("Notch", None, Some(10)));
+        list.insert(punishment("notch", None, Some(20)));
+
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.find_by_name("Notch").unwrap().expires, Some(20));
+    }
+
+    #[test]
+    fn expiry() {
+        let mut list = PunishmentList::new("test.json");
+        list.insert(punishment("a", None, Some(100)));
+        list.insert(punishment("b", None, None));
+
+        assert!(list.find(Uuid::new_v4(), "a", 99).is_some());
+        assert!(list.find(Uuid::new_v4(), "a", 100).is_none());
+
+        let expired = list.remove_expired(100);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].name, "a");
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn vanilla_format() {
+        let json = r#"[{"uuid":"069a79f4-44e9-4726-a5be-fca90e38aaf5","name":"Notch",
+            "created":"2020-05-01 12:30:00 +0200","source":"Server",
+            "expires":"forever","reason":"Banned by an operator."}]"#;
+        let bans: Vec<Punishment> = serde_json::from_str(json).unwrap();
+        assert_eq!(bans[0].name, "Notch");
+        assert_eq!(bans[0].created, 1_588_329_000);
+        assert_eq!(bans[0].expires, None);
+
+        let mut ban = punishment("Notch", None, Some(1_588_329_000));
+        ban.created = 1_588_329_000;
+        let json = serde_json::to_value(&ban).unwrap();
+        assert_eq!(json["created"], "2020-05-01 10:30:00 +0000");
+        assert_eq!(json["expires"], "2020-05-01 10:30:00 +0000");
+        assert!(json.get("uuid").is_none());
+        assert_eq!(serde_json::from_value::<Punishment>(json).unwrap(), ban);
+    }
+
+    #[test]
+    fn ip_bans() {
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let ban = |expires| IpBan {
+            ip,
+            created: 0,
+            source: String::from("Server"),
+            expires,
+            reason: String::new(),
+        };
+        // Fields are written in the order of vanilla's ban list.
+        assert_eq!(
+            serde_json::to_string(&ban(None)).unwrap(),
+            r#"{"ip":"127.0.0.1","created":"1970-01-01 00:00:00 +0000","source":"Server","expires":"forever","reason":""}"#
+        );
+
+        let mut list = IpBanList::new("test.json");
+        list.insert(ban(Some(100)));
+        list.insert(ban(None));
+
+        assert_eq!(list.len(), 1);
+        assert!(list.find(ip, 1000).is_some());
+        assert!(list.find("127.0.0.2".parse().unwrap(), 0).is_none());
+        assert!(list.remove(ip).is_some());
+        assert!(list.is_empty());
+    }
+}
@@ -1,5 +1,8 @@
 use feather_core::network::Packet;
+use fecs::{IntoQuery, Read, World};
 use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 /// Network component containing channels to send and receive packets.
 ///
@@ -7,6 +10,8 @@ use parking_lot::Mutex;
 pub struct Network {
     pub tx: flume::Sender<ServerToWorkerMessage>,
     pub rx: Mutex<flume::Receiver<WorkerToServerMessage>>,
+    /// Counters updated by the player's IO task.
+    pub counters: Arc<ConnectionCounters>,
 }
 
 impl Network {
@@ -23,13 +28,32 @@ impl Network {
         // by the server)
         let _ = self.tx.try_send(ServerToWorkerMessage::SendPacket(packet));
     }
+
+    /// Writes out the packets queued for this player.
+    ///
+    /// This happens at the end of each tick, so
+    /// there is usually no need to call it.
+    pub fn flush(&self) {
+        let _ = self.tx.try_send(ServerToWorkerMessage::Flush);
+    }
+}
+
+/// Flushes the packets sent to each player this tick,
+/// so that they are written to the connection at once.
+#[fecs::system]
+pub fn flush_network(world: &mut World) {
+    <Read<Network>>::query().for_each(world.inner(), |network| network.flush());
 }
 
 /// Message sent from the server threads to a player's
 /// IO task.
 pub enum ServerToWorkerMessage {
     /// Requests that a packet be sent to the client.
+    ///
+    /// The packet is queued until the next `Flush`.
     SendPacket(Box<dyn Packet>),
+    /// Requests that queued packets be written to the connection.
+    Flush,
     /// Requests that the client be disconnected.
     Disconnect,
 }
@@ -40,3 +64,87 @@ pub enum WorkerToServerMessage {
     /// Notifies the server thread that the player disconnected.
     NotifyDisconnected { reason: String },
 }
+
+/// Traffic counters for a connection, shared between
+/// its IO task, which updates them, and the server.
+#[derive(Debug, Default)]
+pub struct ConnectionCounters {
+    packets_in: AtomicU64,
+    bytes_in: AtomicU64,
+    packets_out: AtomicU64,
+    bytes_out: AtomicU64,
+    uncompressed_bytes_out: AtomicU64,
+    queued_packets: AtomicU64,
+    queued_bytes: AtomicU64,
+}
+
+impl ConnectionCounters {
+    /// Stores new values of the counters.
+    pub fn store(&self, stats: ConnectionStats) {
+        self.packets_in.store(stats.packets_in, Ordering::Relaxed);
+        self.bytes_in.store(stats.bytes_in, Ordering::Relaxed);
+        self.packets_out.store(stats.packets_out, Ordering::Relaxed);
+        self.bytes_out.store(stats.bytes_out, Ordering::Relaxed);
+        self.uncompressed_bytes_out
+            .store(stats.uncompressed_bytes_out, Ordering::Relaxed);
+        self.queued_packets
+            .store(stats.queued_packets, Ordering::Relaxed);
+        self.queued_bytes
+            .store(stats.queued_bytes, Ordering::Relaxed);
+    }
+
+    pub fn load(&self) -> ConnectionStats {
+        ConnectionStats {
+            packets_in: self.packets_in.load(Ordering::Relaxed),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            packets_out: self.packets_out.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            uncompressed_bytes_out: self.uncompressed_bytes_out.load(Ordering::Relaxed),
+            queued_packets: self.queued_packets.load(Ordering::Relaxed),
+            queued_bytes: self.queued_bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Traffic on a connection since it was opened.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionStats {
+    pub packets_in: u64,
+    pub bytes_in: u64,
+    pub packets_out: u64,
+    pub bytes_out: u64,
+    /// Bytes sent, counting packets at their size before compression.
+    pub uncompressed_bytes_out: u64,
+    /// Packets waiting to be sent.
+    pub queued_packets: u64,
+    /// Bytes of packets waiting to be sent, before compression.
+    pub queued_bytes: u64,
+}
+
+impl ConnectionStats {
+    /// Returns the size of sent data relative to its
+    /// size before compression, from 0 to 1.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.uncompressed_bytes_out == 0 {
+            1.0
+        } else {
+            self.bytes_out as f64 / self.uncompressed_bytes_out as f64
+        }
+    }
+}
+
+impl std::ops::Add for ConnectionStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            packets_in: self.packets_in + other.packets_in,
+            bytes_in: self.bytes_in + other.bytes_in,
+            packets_out: self.packets_out + other.packets_out,
+            bytes_out: self.bytes_out + other.bytes_out,
+            uncompressed_bytes_out: self.uncompressed_bytes_out + other.uncompressed_bytes_out,
+            queued_packets: self.queued_packets + other.queued_packets,
+            queued_bytes: self.queued_bytes + other.queued_bytes,
+        }
+    }
+}
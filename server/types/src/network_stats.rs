@@ -0,0 +1,118 @@
+//! Traffic statistics for every player's connection,
+//! used to find players who use a lot of bandwidth.
+
+use crate::{ConnectionStats, Game, Network, TPS};
+use ahash::AHashMap;
+use fecs::{Entity, IntoQuery, Read, World};
+
+/// Traffic on one player's connection.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PlayerNetworkStats {
+    pub total: ConnectionStats,
+    /// Bytes received over the last second.
+    pub bytes_in_per_second: u64,
+    /// Bytes sent over the last second.
+    pub bytes_out_per_second: u64,
+}
+
+/// Resource holding network statistics, updated every second.
+#[derive(Debug, Default)]
+pub struct NetworkStats {
+    players: AHashMap<Entity, PlayerNetworkStats>,
+}
+
+impl NetworkStats {
+    /// Replaces the statistics with the latest totals of all connected
+    /// players, which should be taken a second after the last update.
+    pub fn update(&mut self, latest: impl IntoIterator<Item = (Entity, ConnectionStats)>) {
+        let previous = std::mem::take(&mut self.players);
+        self.players = latest
+            .into_iter()
+            .map(|(player, total)| {
+                let last = previous
+                    .get(&player)
+                    .map(|stats| stats.total)
+                    .unwrap_or_default();
+                let stats = PlayerNetworkStats {
+                    total,
+                    bytes_in_per_second: total.bytes_in.saturating_sub(last.bytes_in),
+                    bytes_out_per_second: total.bytes_out.saturating_sub(last.bytes_out),
+                };
+                (player, stats)
+            })
+            .collect();
+    }
+
+    pub fn player(&self, player: Entity) -> Option<&PlayerNetworkStats> {
+        self.players.get(&player)
+    }
+
+    pub fn players(&self) -> impl Iterator<Item = (Entity, &PlayerNetworkStats)> {
+        self.players.iter().map(|(player, stats)| (*player, stats))
+    }
+
+    /// Returns the statistics of all connections added together.
+    pub fn total(&self) -> PlayerNetworkStats {
+        self.players
+            .values()
+            .fold(PlayerNetworkStats::default(), |total, stats| {
+                PlayerNetworkStats {
+                    total: total.total + stats.total,
+                    bytes_in_per_second: total.bytes_in_per_second + stats.bytes_in_per_second,
+                    bytes_out_per_second: total.bytes_out_per_second + stats.bytes_out_per_second,
+                }
+            })
+    }
+}
+
+/// System to update `NetworkStats` once a second.
+#[fecs::system]
+pub fn update_network_stats(game: &mut Game, world: &mut World) {
+    if game.tick_count % TPS != 0 {
+        return;
+    }
+
+    let latest: Vec<_> = <Read<Network>>::query()
+        .iter_entities(world.inner())
+        .map(|(player, network)| (player, network.counters.load()))
+        .collect();
+    game.resources.get_mut::<NetworkStats>().update(latest);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fecs::World;
+
+    fn stats(bytes_in: u64, bytes_out: u64) -> ConnectionStats {
+        ConnectionStats {
+            bytes_in,
+            bytes_out,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rates_and_totals() {
+        let mut world = World::new();
+        let a = world.spawn(std::iter::once(()))[0];
+        let b = world.spawn(std::iter::once(()))[0];
+
+        let mut network_stats = NetworkStats::default();
+        network_stats.update(vec![(a, stats(100, 1000))]);
+        assert_eq!(network_stats.player(a).unwrap().bytes_out_per_second, 1000);
+
+        network_stats.update(vec![(a, stats(150, 1500)), (b, stats(10, 20))]);
+        let a_stats = network_stats.player(a).unwrap();
+        assert_eq!(a_stats.bytes_in_per_second, 50);
+        assert_eq!(a_stats.bytes_out_per_second, 500);
+
+        let total = network_stats.total();
+        assert_eq!(total.total.bytes_out, 1520);
+        assert_eq!(total.bytes_out_per_second, 520);
+
+        // Disconnected players are removed.
+        network_stats.update(vec![(b, stats(10, 20))]);
+        assert!(network_stats.player(a).is_none());
+    }
+}
@@ -0,0 +1,57 @@
+//! The list of online players, shared between the server
+//! thread and the network tasks.
+
+use parking_lot::RwLock;
+use uuid::Uuid;
+
+/// The players currently online, in the order they joined.
+///
+/// The server thread adds and removes players as they join
+/// and leave; the initial handler reads it to enforce the
+/// player limit and answer server list pings.
+#[derive(Debug, Default)]
+pub struct OnlinePlayers {
+    players: RwLock<Vec<(Uuid, String)>>,
+}
+
+impl OnlinePlayers {
+    /// Adds a player who has joined.
+    pub fn add(&self, uuid: Uuid, name: String) {
+        self.players.write().push((uuid, name));
+    }
+
+    /// Removes a player who has left.
+    pub fn remove(&self, uuid: Uuid) {
+        self.players.write().retain(|(player, _)| *player != uuid);
+    }
+
+    /// Returns the number of players online.
+    pub fn count(&self) -> u32 {
+        self.players.read().len() as u32
+    }
+
+    /// Returns up to `max` online players, starting
+    /// with those who have been online the longest.
+    pub fn sample(&self, max: usize) -> Vec<(Uuid, String)> {
+        self.players.read().iter().take(max).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_and_remove() {
+        let players = OnlinePlayers::default();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        players.add(a, String::from("a"));
+        players.add(b, String::from("b"));
+        assert_eq!(players.count(), 2);
+        assert_eq!(players.sample(1), vec![(a, String::from("a"))]);
+
+        players.remove(a);
+        assert_eq!(players.count(), 1);
+        assert_eq!(players.sample(8), vec![(b, String::from("b"))]);
+    }
+}
@@ -0,0 +1,129 @@
+//! Server operators and their permission levels,
+//! persisted to `ops.json` in the server directory.
+//!
+//! As in vanilla, levels range from 1 to 4:
+//! * 1 — may bypass spawn protection.
+//! * 2 — may use most cheat commands, such as `/gamemode` and `/tp`.
+//! * 3 — may use moderation commands, such as `/ban` and `/op`.
+//! * 4 — may use all commands, including `/stop`.
+
+use crate::moderation::{load_entries, save_entries};
+use crate::RunningTasks;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// File name of the operator list.
+pub const OPS_FILE: &str = "ops.json";
+
+/// The highest permission level, given to
+/// players made operators with `/op`.
+pub const MAX_PERMISSION_LEVEL: u8 = 4;
+
+/// An entry in the operator list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operator {
+    pub uuid: Uuid,
+    pub name: String,
+    pub level: u8,
+    /// Whether the operator may join when the server is full.
+    /// Kept for compatibility with vanilla, but not yet honored.
+    #[serde(default)]
+    pub bypasses_player_limit: bool,
+}
+
+/// Resource storing the operator list.
+#[derive(Debug, Clone)]
+pub struct Operators {
+    path: PathBuf,
+    entries: Vec<Operator>,
+}
+
+impl Default for Operators {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from(OPS_FILE),
+            entries: vec![],
+        }
+    }
+}
+
+impl Operators {
+    /// Loads the operator list from the given directory.
+    /// A missing file results in an empty list.
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(OPS_FILE);
+        let entries = load_entries(&path)?;
+        Ok(Self { path, entries })
+    }
+
+    /// Writes this list to its file. The write is performed
+    /// asynchronously on the given task manager.
+    pub fn save(&self, tasks: &RunningTasks) {
+        save_entries(&self.path, &self.entries, tasks);
+    }
+
+    pub fn find(&self, uuid: Uuid) -> Option<&Operator> {
+        self.entries.iter().find(|op| op.uuid == uuid)
+    }
+
+    /// Returns the permission level of the player with the
+    /// given UUID, which is 0 if they are not an operator.
+    pub fn level(&self, uuid: Uuid) -> u8 {
+        self.find(uuid).map_or(0, |op| op.level)
+    }
+
+    /// Adds an operator, replacing any existing
+    /// entry for the same player.
+    pub fn insert(&mut self, op: Operator) {
+        self.entries.retain(|existing| existing.uuid != op.uuid);
+        self.entries.push(op);
+    }
+
+    /// Removes the operator with the given UUID,
+    /// returning their entry if it existed.
+    pub fn remove(&mut self, uuid: Uuid) -> Option<Operator> {
+        let index = self.entries.iter().position(|op| op.uuid == uuid)?;
+        Some(self.entries.remove(index))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Operator> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels() {
+        let uuid = Uuid::new_v4();
+        let mut ops = Operators::default();
+        assert_eq!(ops.level(uuid), 0);
+
+        let op = |level| Operator {
+            uuid,
+            name: String::from("caelunshun"),
+            level,
+            bypasses_player_limit: false,
+        };
+        ops.insert(op(4));
+        ops.insert(op(2));
+        assert_eq!(ops.level(uuid), 2);
+        assert_eq!(ops.iter().count(), 1);
+
+        assert!(ops.remove(uuid).is_some());
+        assert_eq!(ops.level(uuid), 0);
+    }
+
+    #[test]
+    fn vanilla_format() {
+        let json = r#"[{"uuid":"069a79f4-44e9-4726-a5be-fca90e38aaf5","name":"Notch","level":3,"bypassesPlayerLimit":true}]"#;
+        let ops: Vec<Operator> = serde_json::from_str(json).unwrap();
+        assert_eq!(ops[0].name, "Notch");
+        assert_eq!(ops[0].level, 3);
+        assert!(ops[0].bypasses_player_limit);
+    }
+}
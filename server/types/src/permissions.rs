@@ -0,0 +1,451 @@
+//! Permissions: whether a player may run a command or use a
+//! feature, as decided by a pluggable `PermissionsProvider`.
+//!
+//! Each permission is a dot-separated node, such as
+//! `minecraft.command.ban`, along with the operator level which
+//! grants it when permissions are taken from operator levels.
+//! Two providers are included: `OperatorPermissions`, which only
+//! considers operator levels, and `FilePermissions`, which assigns
+//! nodes to groups and players in `permissions.json`.
+
+use crate::{Console, Game, Name, Operators, Uuid, MAX_PERMISSION_LEVEL};
+use fecs::{Entity, ResourcesProvider, World};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// File name of the permissions file used by `FilePermissions`.
+pub const PERMISSIONS_FILE: &str = "permissions.json";
+
+/// Name of the group which applies to every player.
+pub const DEFAULT_GROUP: &str = "default";
+
+/// Bounds group inheritance, in case groups inherit from each other.
+const MAX_INHERITANCE_DEPTH: usize = 16;
+
+/// A permission checked by the server.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Permission<'a> {
+    pub node: &'a str,
+    /// The operator level which grants this permission
+    /// unless a provider decides otherwise.
+    pub level: u8,
+}
+
+impl<'a> Permission<'a> {
+    pub const fn new(node: &'a str, level: u8) -> Self {
+        Self { node, level }
+    }
+}
+
+/// Permission to use entity selectors, such as `@a`,
+/// in commands which take entities.
+pub const SELECTOR_PERMISSION: Permission<'static> =
+    Permission::new("minecraft.command.selector", 2);
+
+/// Decides which permissions players have.
+pub trait PermissionsProvider: Send + Sync + 'static {
+    fn has_permission(
+        &self,
+        game: &Game,
+        world: &World,
+        player: Entity,
+        permission: Permission,
+    ) -> bool;
+
+    /// Returns the prefix shown before a player's
+    /// name in chat, if they have one.
+    fn prefix(&self, _game: &Game, _world: &World, _player: Entity) -> Option<String> {
+        None
+    }
+}
+
+/// Resource holding the permissions provider in use.
+pub struct Permissions(Box<dyn PermissionsProvider>);
+
+impl Default for Permissions {
+    fn default() -> Self {
+        Self::new(OperatorPermissions)
+    }
+}
+
+impl Permissions {
+    pub fn new(provider: impl PermissionsProvider) -> Self {
+        Self(Box::new(provider))
+    }
+
+    pub fn has_permission(
+        &self,
+        game: &Game,
+        world: &World,
+        player: Entity,
+        permission: Permission,
+    ) -> bool {
+        self.0.has_permission(game, world, player, permission)
+    }
+
+    pub fn prefix(&self, game: &Game, world: &World, player: Entity) -> Option<String> {
+        self.0.prefix(game, world, player)
+    }
+}
+
+/// Returns whether a player has a permission,
+/// according to the provider in use. The console
+/// has every permission.
+pub fn has_permission(game: &Game, world: &World, player: Entity, permission: Permission) -> bool {
+    if world.has::<Console>(player) {
+        return true;
+    }
+    game.resources
+        .get::<Permissions>()
+        .has_permission(game, world, player, permission)
+}
+
+/// Returns the prefix shown before a player's name in chat,
+/// according to the provider in use. The console has none.
+pub fn chat_prefix(game: &Game, world: &World, player: Entity) -> Option<String> {
+    if world.has::<Console>(player) {
+        return None;
+    }
+    game.resources
+        .get::<Permissions>()
+        .prefix(game, world, player)
+}
+
+/// Returns the operator level of a player. The console and operators
+/// listed in the configuration file have the highest level; others
+/// have their level from the operator list, or 0 if they are not listed.
+pub fn permission_level(game: &Game, world: &World, player: Entity) -> u8 {
+    if world.has::<Console>(player) {
+        return MAX_PERMISSION_LEVEL;
+    }
+    let name = world.get::<Name>(player);
+    let configured = game
+        .config
+        .server
+        .operators
+        .iter()
+        .any(|op| op.eq_ignore_ascii_case(&name.0));
+    if configured {
+        return MAX_PERMISSION_LEVEL;
+    }
+
+    match world.try_get::<Uuid>(player) {
+        Some(uuid) => game.resources.get::<Operators>().level(*uuid),
+        None => 0,
+    }
+}
+
+/// Provider which grants permissions by operator level.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct OperatorPermissions;
+
+impl PermissionsProvider for OperatorPermissions {
+    fn has_permission(
+        &self,
+        game: &Game,
+        world: &World,
+        player: Entity,
+        permission: Permission,
+    ) -> bool {
+        permission_level(game, world, player) >= permission.level
+    }
+}
+
+/// A group of permissions in `permissions.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Group {
+    /// Permission nodes granted to members. A node may end in
+    /// `*` to match all nodes beneath it, and may be prefixed
+    /// with `-` to deny rather than grant it.
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// Groups whose permissions members also have.
+    #[serde(default)]
+    pub inherits: Vec<String>,
+    /// Shown before members' names in chat, with
+    /// formatting codes written with `&`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+}
+
+/// The groups and permissions of a player in `permissions.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerPermissions {
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Nodes granted or denied to this player, which take
+    /// precedence over those of their groups.
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// The contents of `permissions.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionsFile {
+    #[serde(default)]
+    pub groups: HashMap<String, Group>,
+    /// Players by name.
+    #[serde(default)]
+    pub players: HashMap<String, PlayerPermissions>,
+}
+
+/// Provider which grants permissions by the groups and nodes in
+/// `permissions.json`. Permissions matched by no node in the file
+/// fall back to operator levels.
+#[derive(Debug, Clone, Default)]
+pub struct FilePermissions {
+    file: PermissionsFile,
+}
+
+impl FilePermissions {
+    pub fn new(file: PermissionsFile) -> Self {
+        Self { file }
+    }
+
+    /// Loads the permissions file from the given directory.
+    /// A missing file results in no permissions being assigned.
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(PERMISSIONS_FILE);
+        let file = match std::fs::read_to_string(&path) {
+            Ok(s) => serde_json::from_str(&s)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => PermissionsFile::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self::new(file))
+    }
+
+    /// Looks up a node for the player with the given name, returning
+    /// whether it is granted, or `None` if no node in the file matches.
+    pub fn lookup(&self, name: &str, node: &str) -> Option<bool> {
+        if let Some(player) = self.player(name) {
+            if let Some(granted) = lookup_nodes(&player.permissions, node) {
+                return Some(granted);
+            }
+            for group in &player.groups {
+                if let Some(granted) = self.lookup_group(group, node, 0) {
+                    return Some(granted);
+                }
+            }
+        }
+        self.lookup_group(DEFAULT_GROUP, node, 0)
+    }
+
+    fn lookup_group(&self, name: &str, node: &str, depth: usize) -> Option<bool> {
+        let group = self.file.groups.get(name)?;
+        if let Some(granted) = lookup_nodes(&group.permissions, node) {
+            return Some(granted);
+        }
+        if depth == MAX_INHERITANCE_DEPTH {
+            return None;
+        }
+        group
+            .inherits
+            .iter()
+            .find_map(|parent| self.lookup_group(parent, node, depth + 1))
+    }
+
+    /// Returns the chat prefix of the player with the given name: that
+    /// of the first of their groups, or the groups those inherit from,
+    /// which has one, or else that of the default group.
+    pub fn prefix_of(&self, name: &str) -> Option<&str> {
+        let groups = self
+            .player(name)
+            .map_or(&[][..], |player| player.groups.as_slice());
+        groups
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(DEFAULT_GROUP))
+            .find_map(|group| self.group_prefix(group, 0))
+    }
+
+    fn group_prefix(&self, name: &str, depth: usize) -> Option<&str> {
+        let group = self.file.groups.get(name)?;
+        if let Some(prefix) = &group.prefix {
+            return Some(prefix);
+        }
+        if depth == MAX_INHERITANCE_DEPTH {
+            return None;
+        }
+        group
+            .inherits
+            .iter()
+            .find_map(|parent| self.group_prefix(parent, depth + 1))
+    }
+
+    fn player(&self, name: &str) -> Option<&PlayerPermissions> {
+        self.file
+            .players
+            .iter()
+            .find(|(player, _)| player.eq_ignore_ascii_case(name))
+            .map(|(_, player)| player)
+    }
+}
+
+impl PermissionsProvider for FilePermissions {
+    fn has_permission(
+        &self,
+        game: &Game,
+        world: &World,
+        player: Entity,
+        permission: Permission,
+    ) -> bool {
+        let granted = self.lookup(&world.get::<Name>(player).0, permission.node);
+        match granted {
+            Some(granted) => granted,
+            None => OperatorPermissions.has_permission(game, world, player, permission),
+        }
+    }
+
+    fn prefix(&self, _game: &Game, world: &World, player: Entity) -> Option<String> {
+        self.prefix_of(&world.get::<Name>(player).0)
+            .map(str::to_owned)
+    }
+}
+
+/// Looks up a node in a list of permissions. Denials take
+/// precedence over grants, and more specific patterns take
+/// precedence over wildcards.
+fn lookup_nodes(patterns: &[String], node: &str) -> Option<bool> {
+    patterns
+        .iter()
+        .filter_map(|pattern| {
+            let (granted, pattern) = if pattern.starts_with('-') {
+                (false, &pattern[1..])
+            } else {
+                (true, pattern.as_str())
+            };
+            node_specificity(pattern, node).map(|specificity| (specificity, !granted))
+        })
+        .max()
+        .map(|(_, denied)| !denied)
+}
+
+/// Returns how specifically `pattern` matches `node`, as the length
+/// of the matched prefix, or `None` if it does not match.
+fn node_specificity(pattern: &str, node: &str) -> Option<usize> {
+    if pattern == node {
+        return Some(usize::max_value());
+    }
+    if pattern == "*" {
+        return Some(0);
+    }
+    if pattern.ends_with(".*") {
+        let prefix = &pattern[..pattern.len() - 1];
+        if node.starts_with(prefix) {
+            return Some(prefix.len());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permissions(json: &str) -> FilePermissions {
+        FilePermissions::new(serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn wildcards_and_denials() {
+        let patterns = vec![
+            String::from("minecraft.command.*"),
+            String::from("-minecraft.command.ban"),
+        ];
+        assert_eq!(
+            lookup_nodes(&patterns, "minecraft.command.kick"),
+            Some(true)
+        );
+        assert_eq!(
+            lookup_nodes(&patterns, "minecraft.command.ban"),
+            Some(false)
+        );
+        assert_eq!(lookup_nodes(&patterns, "minecraft.gamemode"), None);
+
+        let patterns = vec![String::from("*"), String::from("-minecraft.command.*")];
+        assert_eq!(lookup_nodes(&patterns, "minecraft.command.op"), Some(false));
+        assert_eq!(lookup_nodes(&patterns, "feather.other"), Some(true));
+    }
+
+    #[test]
+    fn groups_and_players() {
+        let permissions = permissions(
+            r#"{
+                "groups": {
+                    "default": { "permissions": ["minecraft.command.msg"] },
+                    "moderator": {
+                        "permissions": ["minecraft.command.kick", "-minecraft.command.msg"],
+                        "inherits": ["default"]
+                    },
+                    "admin": { "permissions": ["*"], "inherits": ["moderator"] }
+                },
+                "players": {
+                    "Notch": { "groups": ["moderator"] },
+                    "jeb_": { "groups": ["admin"], "permissions": ["-minecraft.command.stop"] }
+                }
+            }"#,
+        );
+
+        assert_eq!(
+            permissions.lookup("anyone", "minecraft.command.msg"),
+            Some(true)
+        );
+        assert_eq!(permissions.lookup("anyone", "minecraft.command.kick"), None);
+        assert_eq!(
+            permissions.lookup("notch", "minecraft.command.kick"),
+            Some(true)
+        );
+        assert_eq!(
+            permissions.lookup("Notch", "minecraft.command.msg"),
+            Some(false)
+        );
+        assert_eq!(permissions.lookup("Notch", "minecraft.command.ban"), None);
+        assert_eq!(
+            permissions.lookup("jeb_", "minecraft.command.ban"),
+            Some(true)
+        );
+        assert_eq!(
+            permissions.lookup("jeb_", "minecraft.command.stop"),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn cyclic_inheritance() {
+        let permissions = permissions(
+            r#"{
+                "groups": {
+                    "a": { "inherits": ["b"] },
+                    "b": { "inherits": ["a"] }
+                },
+                "players": { "Notch": { "groups": ["a"] } }
+            }"#,
+        );
+        assert_eq!(permissions.lookup("Notch", "minecraft.command.kick"), None);
+        assert_eq!(permissions.prefix_of("Notch"), None);
+    }
+
+    #[test]
+    fn prefixes() {
+        let permissions = permissions(
+            r#"{
+                "groups": {
+                    "default": { "prefix": "" },
+                    "helper": { "inherits": ["default"] },
+                    "moderator": { "prefix": "&2[Mod] " },
+                    "senior": { "inherits": ["moderator"] }
+                },
+                "players": {
+                    "Notch": { "groups": ["helper", "senior"] },
+                    "jeb_": { "groups": ["senior"] }
+                }
+            }"#,
+        );
+        assert_eq!(permissions.prefix_of("anyone"), Some(""));
+        assert_eq!(permissions.prefix_of("jeb_"), Some("&2[Mod] "));
+        // Groups are searched in order, along with those they inherit from.
+        assert_eq!(permissions.prefix_of("Notch"), Some(""));
+    }
+}
@@ -1,6 +1,8 @@
 //! Assorted components relating to physics
 //! and systems to initialize them.
 
+use feather_core::util::BlockPosition;
+use fecs::Entity;
 use glm::DVec3;
 use ncollide3d::bounding_volume::AABB;
 
@@ -85,6 +87,90 @@ impl PhysicsBuilder {
     }
 }
 
+/// The kind of a projectile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectileKind {
+    Arrow,
+    Snowball,
+    Egg,
+    EnderPearl,
+    Trident,
+}
+
+impl ProjectileKind {
+    /// Returns the namespaced identifier of this projectile's entity type.
+    pub fn identifier(self) -> &'static str {
+        match self {
+            ProjectileKind::Arrow => "minecraft:arrow",
+            ProjectileKind::Snowball => "minecraft:snowball",
+            ProjectileKind::Egg => "minecraft:egg",
+            ProjectileKind::EnderPearl => "minecraft:ender_pearl",
+            ProjectileKind::Trident => "minecraft:trident",
+        }
+    }
+
+    /// Returns the downward acceleration applied each tick.
+    pub fn gravity(self) -> f64 {
+        match self {
+            ProjectileKind::Arrow | ProjectileKind::Trident => 0.05,
+            _ => 0.03,
+        }
+    }
+
+    /// Returns the factor by which velocity is multiplied
+    /// each tick while in air.
+    pub fn drag(self) -> f64 {
+        0.99
+    }
+
+    /// Returns the factor by which velocity is multiplied
+    /// each tick while in water.
+    pub fn water_drag(self) -> f64 {
+        match self {
+            ProjectileKind::Arrow => 0.6,
+            // Tridents are made to be thrown underwater.
+            ProjectileKind::Trident => 0.99,
+            _ => 0.8,
+        }
+    }
+}
+
+/// Component for projectile entities.
+///
+/// Projectiles are moved by the projectile physics system
+/// instead of the regular entity physics, and should not
+/// have a `Physics` component.
+#[derive(Debug, Clone, Copy)]
+pub struct Projectile {
+    pub kind: ProjectileKind,
+    /// The entity which shot or threw this projectile.
+    pub shooter: Option<Entity>,
+    /// Number of ticks this projectile has been in flight.
+    pub ticks_in_air: u32,
+    /// Whether the projectile can hit entities. Tridents
+    /// pass through entities after they have dealt damage.
+    pub hits_entities: bool,
+}
+
+impl Projectile {
+    pub fn new(kind: ProjectileKind, shooter: Option<Entity>) -> Self {
+        Self {
+            kind,
+            shooter,
+            ticks_in_air: 0,
+            hits_entities: true,
+        }
+    }
+}
+
+/// Component for projectiles which are stuck in a block,
+/// e.g. arrows. If the block is removed, the component is
+/// removed and the projectile falls again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InGround {
+    pub block: BlockPosition,
+}
+
 pub trait AABBExt {
     /// Returns the difference between the two
     /// corners of this bounding box.
@@ -0,0 +1,571 @@
+//! Recipes and the recipe book of each player.
+
+use ahash::AHashMap;
+use feather_core::anvil::player::PlayerRecipeBook;
+use feather_core::inventory::Slot;
+use feather_core::items::{Item, ItemStack};
+use feather_core::network::packets::{DeclareRecipes, DeclaredRecipe, DeclaredRecipeKind};
+use std::collections::BTreeSet;
+
+mod loading;
+
+pub use loading::*;
+
+/// The set of items which may fill a single slot of a recipe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ingredient(pub Vec<Item>);
+
+impl Ingredient {
+    /// Returns whether the given item may be used for this ingredient.
+    pub fn matches(&self, item: Item) -> bool {
+        self.0.contains(&item)
+    }
+}
+
+/// The kind of a recipe and the arrangement of its ingredients.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecipeKind {
+    /// A crafting recipe whose ingredients must be placed
+    /// in the given pattern, anywhere in the grid. The pattern
+    /// is stored row by row, with `None` for slots which must
+    /// be empty, and may also be used mirrored.
+    Shaped {
+        width: usize,
+        height: usize,
+        pattern: Vec<Option<Ingredient>>,
+    },
+    /// A crafting recipe whose ingredients may be placed in any slot.
+    Shapeless { ingredients: Vec<Ingredient> },
+    /// A furnace recipe.
+    Smelting {
+        ingredient: Ingredient,
+        experience: f32,
+        /// Time to smelt the ingredient, in ticks.
+        cooking_time: u32,
+    },
+    /// A stonecutter recipe.
+    Stonecutting { ingredient: Ingredient },
+}
+
+/// A recipe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipe {
+    /// Identifier of the recipe, such as `minecraft:crafting_table`.
+    pub id: String,
+    /// Recipes with the same group are shown
+    /// together in the recipe book.
+    pub group: String,
+    pub kind: RecipeKind,
+    pub result: ItemStack,
+}
+
+impl Recipe {
+    /// Returns the ingredients of this recipe.
+    pub fn ingredients(&self) -> impl Iterator<Item = &Ingredient> {
+        let (pattern, list, single) = match &self.kind {
+            RecipeKind::Shaped { pattern, .. } => (pattern.as_slice(), &[][..], None),
+            RecipeKind::Shapeless { ingredients } => (&[][..], ingredients.as_slice(), None),
+            RecipeKind::Smelting { ingredient, .. } | RecipeKind::Stonecutting { ingredient } => {
+                (&[][..], &[][..], Some(ingredient))
+            }
+        };
+        pattern.iter().flatten().chain(list).chain(single)
+    }
+
+    /// Returns whether this recipe is shown in recipe books.
+    ///
+    /// Stonecutter recipes are not, as clients of this
+    /// protocol version do not know of them.
+    pub fn in_recipe_book(&self) -> bool {
+        match self.kind {
+            RecipeKind::Stonecutting { .. } => false,
+            _ => true,
+        }
+    }
+
+    /// Returns the ingredient to place in each slot of a crafting
+    /// grid of the given size, row by row, or `None` if the recipe
+    /// does not fit in the grid.
+    ///
+    /// Shaped recipes are placed in the top left corner of the grid.
+    pub fn grid_layout(&self, width: usize, height: usize) -> Option<Vec<Option<&Ingredient>>> {
+        let mut layout = vec![None; width * height];
+        match &self.kind {
+            RecipeKind::Shaped {
+                width: recipe_width,
+                height: recipe_height,
+                pattern,
+            } => {
+                if *recipe_width > width || *recipe_height > height {
+                    return None;
+                }
+                for (i, ingredient) in pattern.iter().enumerate() {
+                    let (x, y) = (i % recipe_width, i / recipe_width);
+                    layout[y * width + x] = ingredient.as_ref();
+                }
+            }
+            RecipeKind::Shapeless { ingredients } => {
+                if ingredients.len() > layout.len() {
+                    return None;
+                }
+                for (slot, ingredient) in layout.iter_mut().zip(ingredients) {
+                    *slot = Some(ingredient);
+                }
+            }
+            _ => return None,
+        }
+        Some(layout)
+    }
+
+    /// Returns whether the items in a crafting grid of the given
+    /// width, stored row by row, match this recipe.
+    pub fn matches_grid(&self, grid: &[Option<Item>], width: usize) -> bool {
+        match &self.kind {
+            RecipeKind::Shaped {
+                width: recipe_width,
+                height: recipe_height,
+                pattern,
+            } => {
+                let (x0, y0, x1, y1) = match bounding_box(grid, width) {
+                    Some(bounds) => bounds,
+                    None => return false,
+                };
+                if x1 - x0 != *recipe_width || y1 - y0 != *recipe_height {
+                    return false;
+                }
+
+                [false, true].iter().any(|&mirrored| {
+                    (0..*recipe_height).all(|y| {
+                        (0..*recipe_width).all(|x| {
+                            let pattern_x = if mirrored { recipe_width - 1 - x } else { x };
+                            let ingredient = &pattern[y * recipe_width + pattern_x];
+                            match (ingredient, grid[(y0 + y) * width + x0 + x]) {
+                                (None, None) => true,
+                                (Some(ingredient), Some(item)) => ingredient.matches(item),
+                                _ => false,
+                            }
+                        })
+                    })
+                })
+            }
+            RecipeKind::Shapeless { ingredients } => {
+                let items: Vec<Item> = grid.iter().flatten().copied().collect();
+                items.len() == ingredients.len()
+                    && assign_ingredients(&items, ingredients, &mut vec![false; items.len()])
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Returns the smallest rectangle, as `(x0, y0, x1, y1)` with exclusive
+/// upper bounds, holding all the items in a grid.
+fn bounding_box(grid: &[Option<Item>], width: usize) -> Option<(usize, usize, usize, usize)> {
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for (i, _) in grid.iter().enumerate().filter(|(_, item)| item.is_some()) {
+        let (x, y) = (i % width, i / width);
+        bounds = Some(match bounds {
+            Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x + 1), y1.max(y + 1)),
+            None => (x, y, x + 1, y + 1),
+        });
+    }
+    bounds
+}
+
+/// Returns whether each ingredient can be matched
+/// with a different one of the unused items.
+fn assign_ingredients(items: &[Item], ingredients: &[Ingredient], used: &mut [bool]) -> bool {
+    let (ingredient, rest) = match ingredients.split_first() {
+        Some(split) => split,
+        None => return true,
+    };
+
+    for i in 0..items.len() {
+        if used[i] || !ingredient.matches(items[i]) {
+            continue;
+        }
+        used[i] = true;
+        if assign_ingredients(items, rest, used) {
+            return true;
+        }
+        used[i] = false;
+    }
+    false
+}
+
+/// Returns the item left in the crafting grid after
+/// crafting with the given ingredient, if any.
+pub fn crafting_remainder(item: Item) -> Option<Item> {
+    match item {
+        Item::WaterBucket | Item::LavaBucket | Item::MilkBucket => Some(Item::Bucket),
+        Item::DragonBreath => Some(Item::GlassBottle),
+        _ => None,
+    }
+}
+
+/// Resource holding the recipes known to the server.
+#[derive(Debug, Default)]
+pub struct RecipeRegistry {
+    recipes: Vec<Recipe>,
+    by_id: AHashMap<String, usize>,
+}
+
+impl RecipeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a recipe, replacing any existing recipe with the same ID.
+    pub fn register(&mut self, recipe: Recipe) {
+        match self.by_id.get(&recipe.id) {
+            Some(&index) => self.recipes[index] = recipe,
+            None => {
+                self.by_id.insert(recipe.id.clone(), self.recipes.len());
+                self.recipes.push(recipe);
+            }
+        }
+    }
+
+    /// Returns the recipe with the given ID.
+    pub fn get(&self, id: &str) -> Option<&Recipe> {
+        self.by_id.get(id).map(|&index| &self.recipes[index])
+    }
+
+    /// Returns all registered recipes.
+    pub fn iter(&self) -> impl Iterator<Item = &Recipe> {
+        self.recipes.iter()
+    }
+
+    /// Returns the number of registered recipes.
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    /// Returns the recipes which take the given item as an ingredient.
+    pub fn using(&self, item: Item) -> impl Iterator<Item = &Recipe> {
+        self.recipes.iter().filter(move |recipe| {
+            recipe
+                .ingredients()
+                .any(|ingredient| ingredient.matches(item))
+        })
+    }
+
+    /// Returns the crafting recipe matching the contents of a
+    /// crafting grid of the given width, stored row by row.
+    pub fn match_crafting(&self, grid: &[Slot], width: usize) -> Option<&Recipe> {
+        let items: Vec<Option<Item>> = grid
+            .iter()
+            .map(|slot| slot.as_ref().map(|stack| stack.ty))
+            .collect();
+        self.recipes
+            .iter()
+            .find(|recipe| recipe.matches_grid(&items, width))
+    }
+
+    /// Returns the furnace recipe smelting the given item.
+    pub fn match_smelting(&self, item: Item) -> Option<&Recipe> {
+        self.recipes.iter().find(|recipe| match &recipe.kind {
+            RecipeKind::Smelting { ingredient, .. } => ingredient.matches(item),
+            _ => false,
+        })
+    }
+
+    /// Returns the stonecutter recipes which take the given item.
+    pub fn match_stonecutting(&self, item: Item) -> impl Iterator<Item = &Recipe> {
+        self.recipes
+            .iter()
+            .filter(move |recipe| match &recipe.kind {
+                RecipeKind::Stonecutting { ingredient } => ingredient.matches(item),
+                _ => false,
+            })
+    }
+
+    /// Creates the Declare Recipes packet sent to clients
+    /// so that their recipe books can display the recipes.
+    pub fn declare_packet(&self) -> DeclareRecipes {
+        let stacks = |ingredient: &Ingredient| {
+            ingredient
+                .0
+                .iter()
+                .map(|item| ItemStack::new(*item, 1))
+                .collect::<Vec<_>>()
+        };
+
+        let recipes = self
+            .recipes
+            .iter()
+            .filter_map(|recipe| {
+                let kind = match &recipe.kind {
+                    RecipeKind::Shaped {
+                        width,
+                        height,
+                        pattern,
+                    } => DeclaredRecipeKind::Shaped {
+                        width: *width,
+                        height: *height,
+                        ingredients: pattern
+                            .iter()
+                            .map(|ingredient| ingredient.as_ref().map(stacks).unwrap_or_default())
+                            .collect(),
+                        result: recipe.result.clone(),
+                    },
+                    RecipeKind::Shapeless { ingredients } => DeclaredRecipeKind::Shapeless {
+                        ingredients: ingredients.iter().map(stacks).collect(),
+                        result: recipe.result.clone(),
+                    },
+                    RecipeKind::Smelting {
+                        ingredient,
+                        experience,
+                        cooking_time,
+                    } => DeclaredRecipeKind::Smelting {
+                        ingredient: stacks(ingredient),
+                        result: recipe.result.clone(),
+                        experience: *experience,
+                        cooking_time: *cooking_time as i32,
+                    },
+                    RecipeKind::Stonecutting { .. } => return None,
+                };
+                Some(DeclaredRecipe {
+                    id: recipe.id.clone(),
+                    group: recipe.group.clone(),
+                    kind,
+                })
+            })
+            .collect();
+
+        DeclareRecipes { recipes }
+    }
+}
+
+/// Component holding the recipes a player has unlocked
+/// and the state of their recipe book interface.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecipeBook {
+    /// IDs of the unlocked recipes.
+    pub unlocked: BTreeSet<String>,
+    /// IDs of unlocked recipes which the player has not yet
+    /// looked at, which their client highlights.
+    pub to_be_displayed: BTreeSet<String>,
+    pub crafting_book_open: bool,
+    pub crafting_filter_active: bool,
+    pub smelting_book_open: bool,
+    pub smelting_filter_active: bool,
+}
+
+impl RecipeBook {
+    /// Unlocks a recipe, returning whether it was locked before.
+    pub fn unlock(&mut self, id: &str) -> bool {
+        if self.unlocked.contains(id) {
+            return false;
+        }
+        self.unlocked.insert(id.to_owned());
+        self.to_be_displayed.insert(id.to_owned());
+        true
+    }
+
+    /// Locks a recipe, returning whether it was unlocked before.
+    pub fn lock(&mut self, id: &str) -> bool {
+        self.to_be_displayed.remove(id);
+        self.unlocked.remove(id)
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains(id)
+    }
+
+    /// Creates a recipe book from its representation in player data.
+    pub fn from_data(data: &PlayerRecipeBook) -> Self {
+        Self {
+            unlocked: data.recipes.iter().cloned().collect(),
+            to_be_displayed: data.to_be_displayed.iter().cloned().collect(),
+            crafting_book_open: data.is_gui_open,
+            crafting_filter_active: data.is_filtering_craftable,
+            smelting_book_open: data.is_furnace_gui_open,
+            smelting_filter_active: data.is_furnace_filtering_craftable,
+        }
+    }
+
+    /// Converts this recipe book to its representation in player data.
+    pub fn to_data(&self) -> PlayerRecipeBook {
+        PlayerRecipeBook {
+            recipes: self.unlocked.iter().cloned().collect(),
+            to_be_displayed: self.to_be_displayed.iter().cloned().collect(),
+            is_gui_open: self.crafting_book_open,
+            is_filtering_craftable: self.crafting_filter_active,
+            is_furnace_gui_open: self.smelting_book_open,
+            is_furnace_filtering_craftable: self.smelting_filter_active,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planks() -> Ingredient {
+        Ingredient(vec![Item::OakPlanks, Item::SprucePlanks])
+    }
+
+    #[test]
+    fn grid_layout() {
+        let sticks = Recipe {
+            id: "minecraft:stick".to_owned(),
+            group: String::new(),
+            kind: RecipeKind::Shaped {
+                width: 1,
+                height: 2,
+                pattern: vec![Some(planks()), Some(planks())],
+            },
+            result: ItemStack::new(Item::Stick, 4),
+        };
+        let planks = planks();
+        assert_eq!(
+            sticks.grid_layout(2, 2),
+            Some(vec![Some(&planks), None, Some(&planks), None])
+        );
+        assert_eq!(sticks.grid_layout(2, 1), None);
+
+        let dye = Recipe {
+            id: "minecraft:pink_dye".to_owned(),
+            group: "pink_dye".to_owned(),
+            kind: RecipeKind::Shapeless {
+                ingredients: vec![
+                    Ingredient(vec![Item::RoseRed]),
+                    Ingredient(vec![Item::BoneMeal]),
+                ],
+            },
+            result: ItemStack::new(Item::PinkDye, 2),
+        };
+        assert_eq!(dye.grid_layout(2, 2).unwrap().iter().flatten().count(), 2);
+        assert_eq!(dye.grid_layout(1, 1), None);
+    }
+
+    #[test]
+    fn registry() {
+        let mut registry = RecipeRegistry::new();
+        registry.register(Recipe {
+            id: "minecraft:crafting_table".to_owned(),
+            group: String::new(),
+            kind: RecipeKind::Shaped {
+                width: 2,
+                height: 2,
+                pattern: vec![Some(planks()); 4],
+            },
+            result: ItemStack::new(Item::CraftingTable, 1),
+        });
+
+        assert!(registry.get("minecraft:crafting_table").is_some());
+        assert!(registry.get("minecraft:furnace").is_none());
+        assert_eq!(registry.using(Item::SprucePlanks).count(), 1);
+        assert_eq!(registry.using(Item::Cobblestone).count(), 0);
+    }
+
+    #[test]
+    fn match_crafting() {
+        let mut registry = RecipeRegistry::new();
+        registry.register(Recipe {
+            id: "minecraft:wooden_hoe".to_owned(),
+            group: String::new(),
+            kind: RecipeKind::Shaped {
+                width: 2,
+                height: 3,
+                pattern: vec![
+                    Some(planks()),
+                    Some(planks()),
+                    None,
+                    Some(Ingredient(vec![Item::Stick])),
+                    None,
+                    Some(Ingredient(vec![Item::Stick])),
+                ],
+            },
+            result: ItemStack::new(Item::WoodenHoe, 1),
+        });
+        registry.register(Recipe {
+            id: "minecraft:pink_dye".to_owned(),
+            group: String::new(),
+            kind: RecipeKind::Shapeless {
+                ingredients: vec![
+                    Ingredient(vec![Item::RoseRed]),
+                    Ingredient(vec![Item::BoneMeal]),
+                ],
+            },
+            result: ItemStack::new(Item::PinkDye, 2),
+        });
+
+        let plank = || Some(ItemStack::new(Item::OakPlanks, 1));
+        let stick = || Some(ItemStack::new(Item::Stick, 1));
+        let hoe = [
+            plank(),
+            plank(),
+            None,
+            None,
+            stick(),
+            None,
+            None,
+            stick(),
+            None,
+        ];
+        let mirrored = [
+            None,
+            plank(),
+            plank(),
+            None,
+            stick(),
+            None,
+            None,
+            stick(),
+            None,
+        ];
+        let incomplete = [
+            None,
+            None,
+            None,
+            plank(),
+            plank(),
+            None,
+            None,
+            stick(),
+            None,
+        ];
+        assert_eq!(
+            registry.match_crafting(&hoe, 3).map(|r| r.result.ty),
+            Some(Item::WoodenHoe)
+        );
+        assert!(registry.match_crafting(&mirrored, 3).is_some());
+        assert!(registry.match_crafting(&incomplete, 3).is_none());
+
+        let dye = [
+            Some(ItemStack::new(Item::BoneMeal, 1)),
+            None,
+            None,
+            Some(ItemStack::new(Item::RoseRed, 3)),
+        ];
+        assert_eq!(
+            registry.match_crafting(&dye, 2).map(|r| r.result.ty),
+            Some(Item::PinkDye)
+        );
+        assert!(registry
+            .match_crafting(&[None, None, None, None], 2)
+            .is_none());
+        assert!(registry
+            .match_crafting(&[dye[0], None, None, None], 2)
+            .is_none());
+    }
+
+    #[test]
+    fn recipe_book() {
+        let mut book = RecipeBook::default();
+        assert!(book.unlock("minecraft:stick"));
+        assert!(!book.unlock("minecraft:stick"));
+        assert!(book.is_unlocked("minecraft:stick"));
+        assert!(book.to_be_displayed.contains("minecraft:stick"));
+
+        assert!(book.lock("minecraft:stick"));
+        assert!(!book.is_unlocked("minecraft:stick"));
+        assert!(book.to_be_displayed.is_empty());
+    }
+}
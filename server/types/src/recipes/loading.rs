@@ -0,0 +1,463 @@
+//! Loading of recipes from the JSON files of the vanilla data pack.
+//!
+//! Recipes are read from `minecraft/recipes` and the item tags their
+//! ingredients refer to from `minecraft/tags/items`, both relative to
+//! the data directory. Recipes naming items which this version
+//! does not have are skipped, as are the special recipes, such as
+//! firework crafting, which are not described by their files.
+
+use super::{Ingredient, Recipe, RecipeKind, RecipeRegistry};
+use anyhow::{anyhow, Context};
+use feather_core::items::{Item, ItemStack};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Directory of recipe files, relative to the data directory.
+pub const RECIPES_DIR: &str = "minecraft/recipes";
+/// Directory of item tag files, relative to the data directory.
+pub const ITEM_TAGS_DIR: &str = "minecraft/tags/items";
+
+/// Default time to smelt an item in a furnace, in ticks.
+const DEFAULT_COOKING_TIME: u32 = 200;
+
+/// Items in each item tag, by identifier.
+pub(crate) type ItemTags = HashMap<String, Vec<Item>>;
+
+#[derive(Deserialize)]
+struct TagFile {
+    values: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IngredientEntry {
+    Item { item: String },
+    Tag { tag: String },
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IngredientFile {
+    Single(IngredientEntry),
+    Any(Vec<IngredientEntry>),
+}
+
+#[derive(Deserialize)]
+struct ResultFile {
+    item: String,
+    #[serde(default = "one")]
+    count: u8,
+}
+
+#[derive(Deserialize)]
+struct ShapedFile {
+    #[serde(default)]
+    group: String,
+    pattern: Vec<String>,
+    key: HashMap<String, IngredientFile>,
+    result: ResultFile,
+}
+
+#[derive(Deserialize)]
+struct ShapelessFile {
+    #[serde(default)]
+    group: String,
+    ingredients: Vec<IngredientFile>,
+    result: ResultFile,
+}
+
+#[derive(Deserialize)]
+struct SmeltingFile {
+    #[serde(default)]
+    group: String,
+    ingredient: IngredientFile,
+    result: String,
+    #[serde(default)]
+    experience: f32,
+    #[serde(rename = "cookingtime")]
+    #[serde(default = "default_cooking_time")]
+    cooking_time: u32,
+}
+
+#[derive(Deserialize)]
+struct StonecuttingFile {
+    #[serde(default)]
+    group: String,
+    ingredient: IngredientFile,
+    result: String,
+    #[serde(default = "one")]
+    count: u8,
+}
+
+fn one() -> u8 {
+    1
+}
+
+fn default_cooking_time() -> u32 {
+    DEFAULT_COOKING_TIME
+}
+
+impl RecipeRegistry {
+    /// Loads the recipes in the given data directory.
+    ///
+    /// Returns an empty registry if the directory has no recipes.
+    pub fn load(data_dir: &Path) -> anyhow::Result<Self> {
+        let tags = load_item_tags(&data_dir.join(ITEM_TAGS_DIR))?;
+
+        let mut registry = RecipeRegistry::new();
+        for (name, json) in read_json_files(&data_dir.join(RECIPES_DIR))? {
+            let id = format!("minecraft:{}", name);
+            match parse_recipe(&id, &json, &tags) {
+                Ok(Some(recipe)) => registry.register(recipe),
+                Ok(None) => (),
+                Err(e) => log::debug!("Skipping recipe {}: {}", id, e),
+            }
+        }
+
+        Ok(registry)
+    }
+}
+
+/// Reads the JSON files in a directory, returning their contents
+/// along with their names without the extension.
+pub(crate) fn read_json_files(dir: &Path) -> anyhow::Result<Vec<(String, String)>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut files = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if path
+            .extension()
+            .map_or(true, |extension| extension != "json")
+        {
+            continue;
+        }
+        let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(name) => name.to_owned(),
+            None => continue,
+        };
+        let json = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        files.push((name, json));
+    }
+
+    // Sort so that recipes are always declared in the same order.
+    files.sort();
+    Ok(files)
+}
+
+pub(crate) fn load_item_tags(dir: &Path) -> anyhow::Result<ItemTags> {
+    let mut files = HashMap::new();
+    for (name, json) in read_json_files(dir)? {
+        let file: TagFile =
+            serde_json::from_str(&json).with_context(|| format!("invalid item tag {}", name))?;
+        files.insert(format!("minecraft:{}", name), file.values);
+    }
+    Ok(resolve_tags(&files))
+}
+
+/// Resolves item tags, which may include other tags, to the items they contain.
+/// Unknown items and tags are ignored.
+fn resolve_tags(files: &HashMap<String, Vec<String>>) -> ItemTags {
+    fn resolve(
+        name: &str,
+        files: &HashMap<String, Vec<String>>,
+        visiting: &mut Vec<String>,
+        items: &mut Vec<Item>,
+    ) {
+        // Guard against tags which include themselves.
+        if visiting.iter().any(|visited| visited == name) {
+            return;
+        }
+        visiting.push(name.to_owned());
+
+        for value in files.get(name).into_iter().flatten() {
+            if value.starts_with('#') {
+                resolve(&value[1..], files, visiting, items);
+            } else if let Some(item) = Item::from_identifier(value) {
+                if !items.contains(&item) {
+                    items.push(item);
+                }
+            }
+        }
+
+        visiting.pop();
+    }
+
+    files
+        .keys()
+        .map(|name| {
+            let mut items = vec![];
+            resolve(name, files, &mut vec![], &mut items);
+            (name.clone(), items)
+        })
+        .collect()
+}
+
+fn parse_item(identifier: &str) -> anyhow::Result<Item> {
+    Item::from_identifier(identifier).ok_or_else(|| anyhow!("unknown item {}", identifier))
+}
+
+fn parse_ingredient(file: IngredientFile, tags: &ItemTags) -> anyhow::Result<Ingredient> {
+    let entries = match file {
+        IngredientFile::Single(entry) => vec![entry],
+        IngredientFile::Any(entries) => entries,
+    };
+
+    let mut items = vec![];
+    for entry in entries {
+        match entry {
+            IngredientEntry::Item { item } => items.push(parse_item(&item)?),
+            IngredientEntry::Tag { tag } => items.extend(
+                tags.get(&tag)
+                    .ok_or_else(|| anyhow!("unknown item tag {}", tag))?,
+            ),
+        }
+    }
+
+    if items.is_empty() {
+        return Err(anyhow!("ingredient matches no items"));
+    }
+    Ok(Ingredient(items))
+}
+
+/// Removes the rows and columns of spaces around a pattern,
+/// so that it may be placed anywhere in the crafting grid.
+fn shrink_pattern(pattern: &[String]) -> Vec<Vec<char>> {
+    let rows: Vec<Vec<char>> = pattern.iter().map(|row| row.chars().collect()).collect();
+    let filled = |c: &char| *c != ' ';
+
+    let first_row = rows.iter().position(|row| row.iter().any(filled));
+    let last_row = rows.iter().rposition(|row| row.iter().any(filled));
+    let (first_row, last_row) = match (first_row, last_row) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return vec![],
+    };
+    let rows = &rows[first_row..=last_row];
+
+    let first_column = rows
+        .iter()
+        .filter_map(|row| row.iter().position(filled))
+        .min()
+        .unwrap_or(0);
+    let last_column = rows
+        .iter()
+        .filter_map(|row| row.iter().rposition(filled))
+        .max()
+        .unwrap_or(0);
+
+    rows.iter()
+        .map(|row| {
+            (first_column..=last_column)
+                .map(|x| row.get(x).copied().unwrap_or(' '))
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses a recipe file, returning `None` for
+/// recipes of a type which is not supported.
+fn parse_recipe(id: &str, json: &str, tags: &ItemTags) -> anyhow::Result<Option<Recipe>> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let ty = value
+        .get("type")
+        .and_then(|ty| ty.as_str())
+        .ok_or_else(|| anyhow!("missing recipe type"))?;
+
+    let (group, kind, result) = match ty.trim_start_matches("minecraft:") {
+        "crafting_shaped" => {
+            let file: ShapedFile = serde_json::from_value(value)?;
+            let mut key = HashMap::new();
+            for (symbol, ingredient) in file.key {
+                let symbol = match symbol.chars().next() {
+                    Some(c) if symbol.chars().count() == 1 && c != ' ' => c,
+                    _ => return Err(anyhow!("invalid key symbol {:?}", symbol)),
+                };
+                key.insert(symbol, parse_ingredient(ingredient, tags)?);
+            }
+
+            let rows = shrink_pattern(&file.pattern);
+            let width = rows.first().map_or(0, |row| row.len());
+            let mut pattern = vec![];
+            for symbol in rows.iter().flatten() {
+                pattern.push(match symbol {
+                    ' ' => None,
+                    symbol => Some(
+                        key.get(symbol)
+                            .cloned()
+                            .ok_or_else(|| anyhow!("undefined pattern symbol {:?}", symbol))?,
+                    ),
+                });
+            }
+            if width == 0 {
+                return Err(anyhow!("empty pattern"));
+            }
+
+            let kind = RecipeKind::Shaped {
+                width,
+                height: rows.len(),
+                pattern,
+            };
+            let result = ItemStack::new(parse_item(&file.result.item)?, file.result.count);
+            (file.group, kind, result)
+        }
+        "crafting_shapeless" => {
+            let file: ShapelessFile = serde_json::from_value(value)?;
+            let ingredients = file
+                .ingredients
+                .into_iter()
+                .map(|ingredient| parse_ingredient(ingredient, tags))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let kind = RecipeKind::Shapeless { ingredients };
+            let result = ItemStack::new(parse_item(&file.result.item)?, file.result.count);
+            (file.group, kind, result)
+        }
+        "smelting" => {
+            let file: SmeltingFile = serde_json::from_value(value)?;
+            let kind = RecipeKind::Smelting {
+                ingredient: parse_ingredient(file.ingredient, tags)?,
+                experience: file.experience,
+                cooking_time: file.cooking_time,
+            };
+            (
+                file.group,
+                kind,
+                ItemStack::new(parse_item(&file.result)?, 1),
+            )
+        }
+        "stonecutting" => {
+            let file: StonecuttingFile = serde_json::from_value(value)?;
+            let kind = RecipeKind::Stonecutting {
+                ingredient: parse_ingredient(file.ingredient, tags)?,
+            };
+            let result = ItemStack::new(parse_item(&file.result)?, file.count);
+            (file.group, kind, result)
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(Recipe {
+        id: id.to_owned(),
+        group,
+        kind,
+        result,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags() -> ItemTags {
+        let mut files = HashMap::new();
+        files.insert(
+            "minecraft:planks".to_owned(),
+            vec![
+                "minecraft:oak_planks".to_owned(),
+                "#minecraft:more_planks".to_owned(),
+            ],
+        );
+        files.insert(
+            "minecraft:more_planks".to_owned(),
+            vec!["minecraft:birch_planks".to_owned()],
+        );
+        resolve_tags(&files)
+    }
+
+    #[test]
+    fn nested_tags() {
+        let tags = tags();
+        assert_eq!(
+            tags["minecraft:planks"],
+            vec![Item::OakPlanks, Item::BirchPlanks]
+        );
+        assert_eq!(tags["minecraft:more_planks"], vec![Item::BirchPlanks]);
+    }
+
+    #[test]
+    fn shaped() {
+        let json = r##"{
+            "type": "crafting_shaped",
+            "pattern": [
+                "   ",
+                " # ",
+                " # "
+            ],
+            "key": {
+                "#": { "tag": "minecraft:planks" }
+            },
+            "result": { "item": "minecraft:stick", "count": 4 }
+        }"##;
+        let recipe = parse_recipe("minecraft:stick", json, &tags())
+            .unwrap()
+            .unwrap();
+
+        let planks = Ingredient(vec![Item::OakPlanks, Item::BirchPlanks]);
+        assert_eq!(
+            recipe.kind,
+            RecipeKind::Shaped {
+                width: 1,
+                height: 2,
+                pattern: vec![Some(planks.clone()), Some(planks)],
+            }
+        );
+        assert_eq!(recipe.result, ItemStack::new(Item::Stick, 4));
+    }
+
+    #[test]
+    fn shapeless_and_smelting() {
+        let json = r#"{
+            "type": "minecraft:crafting_shapeless",
+            "group": "pink_dye",
+            "ingredients": [
+                { "item": "minecraft:bone_meal" },
+                [{ "item": "minecraft:rose_red" }, { "item": "minecraft:peony" }]
+            ],
+            "result": { "item": "minecraft:pink_dye", "count": 2 }
+        }"#;
+        let recipe = parse_recipe("minecraft:pink_dye", json, &tags())
+            .unwrap()
+            .unwrap();
+        assert_eq!(recipe.group, "pink_dye");
+        assert_eq!(recipe.ingredients().count(), 2);
+
+        let json = r#"{
+            "type": "smelting",
+            "ingredient": { "item": "minecraft:iron_ore" },
+            "result": "minecraft:iron_ingot",
+            "experience": 0.7
+        }"#;
+        let recipe = parse_recipe("minecraft:iron_ingot", json, &tags())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            recipe.kind,
+            RecipeKind::Smelting {
+                ingredient: Ingredient(vec![Item::IronOre]),
+                experience: 0.7,
+                cooking_time: DEFAULT_COOKING_TIME,
+            }
+        );
+    }
+
+    #[test]
+    fn unsupported_recipes() {
+        let special = r#"{ "type": "crafting_special_firework_rocket" }"#;
+        assert!(parse_recipe("minecraft:firework_rocket", special, &tags())
+            .unwrap()
+            .is_none());
+
+        let unknown_item = r#"{
+            "type": "crafting_shapeless",
+            "ingredients": [{ "item": "minecraft:not_an_item" }],
+            "result": { "item": "minecraft:stone" }
+        }"#;
+        assert!(parse_recipe("minecraft:stone", unknown_item, &tags()).is_err());
+    }
+}
@@ -0,0 +1,500 @@
+//! The scoreboard: objectives, the scores tracked under them,
+//! and the slots in which they are displayed.
+//!
+//! Scores are keyed by entry, which is usually a player name
+//! but may be any string, allowing sidebars to show arbitrary
+//! lines. Methods which change state return the packet which
+//! informs clients of the change.
+
+use ahash::AHashMap;
+use feather_core::network::packets::{
+    DisplayScoreboard, ObjectiveAction, ObjectiveType, ScoreAction, ScoreboardObjective,
+    UpdateScore,
+};
+use feather_core::network::Packet;
+use feather_core::text::{Text, TextRoot};
+use std::fmt::{self, Display, Formatter};
+
+/// Maximum length of an objective name.
+pub const MAX_OBJECTIVE_NAME_LEN: usize = 16;
+/// Maximum length of a score entry.
+pub const MAX_ENTRY_LEN: usize = 40;
+
+/// What an objective's scores count.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Criteria {
+    /// Scores are only changed by commands and plugins.
+    Dummy,
+    /// Like `Dummy`, but players may change their own score.
+    Trigger,
+    DeathCount,
+    PlayerKillCount,
+    TotalKillCount,
+    Health,
+    Food,
+    Air,
+    Armor,
+    Level,
+    Xp,
+}
+
+impl Criteria {
+    /// Returns all criteria.
+    pub fn all() -> &'static [Criteria] {
+        &[
+            Criteria::Dummy,
+            Criteria::Trigger,
+            Criteria::DeathCount,
+            Criteria::PlayerKillCount,
+            Criteria::TotalKillCount,
+            Criteria::Health,
+            Criteria::Food,
+            Criteria::Air,
+            Criteria::Armor,
+            Criteria::Level,
+            Criteria::Xp,
+        ]
+    }
+
+    /// Returns the name of this criteria, as used in commands.
+    pub fn identifier(self) -> &'static str {
+        match self {
+            Criteria::Dummy => "dummy",
+            Criteria::Trigger => "trigger",
+            Criteria::DeathCount => "deathCount",
+            Criteria::PlayerKillCount => "playerKillCount",
+            Criteria::TotalKillCount => "totalKillCount",
+            Criteria::Health => "health",
+            Criteria::Food => "food",
+            Criteria::Air => "air",
+            Criteria::Armor => "armor",
+            Criteria::Level => "level",
+            Criteria::Xp => "xp",
+        }
+    }
+
+    /// Returns the criteria with the given name.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|criteria| criteria.identifier() == identifier)
+    }
+
+    /// Returns whether scores for this criteria mirror the state
+    /// of players, in which case they cannot be set by commands.
+    pub fn is_read_only(self) -> bool {
+        match self {
+            Criteria::Health
+            | Criteria::Food
+            | Criteria::Air
+            | Criteria::Armor
+            | Criteria::Level
+            | Criteria::Xp => true,
+            _ => false,
+        }
+    }
+
+    /// Returns how objectives with this criteria are rendered.
+    pub fn render_type(self) -> ObjectiveType {
+        match self {
+            Criteria::Health => ObjectiveType::Hearts,
+            _ => ObjectiveType::Integer,
+        }
+    }
+}
+
+/// A place on the client's screen where an objective is displayed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DisplaySlot {
+    /// Next to names in the player list.
+    List,
+    Sidebar,
+    /// Below the name tags of players.
+    BelowName,
+}
+
+impl DisplaySlot {
+    /// Returns all display slots.
+    pub fn all() -> &'static [DisplaySlot] {
+        &[
+            DisplaySlot::List,
+            DisplaySlot::Sidebar,
+            DisplaySlot::BelowName,
+        ]
+    }
+
+    /// Returns the name of this slot, as used in commands.
+    pub fn identifier(self) -> &'static str {
+        match self {
+            DisplaySlot::List => "list",
+            DisplaySlot::Sidebar => "sidebar",
+            DisplaySlot::BelowName => "belowName",
+        }
+    }
+
+    /// Returns the slot with the given name.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|slot| slot.identifier() == identifier)
+    }
+
+    /// Returns the position of this slot in the protocol.
+    pub fn id(self) -> i8 {
+        match self {
+            DisplaySlot::List => 0,
+            DisplaySlot::Sidebar => 1,
+            DisplaySlot::BelowName => 2,
+        }
+    }
+}
+
+/// An error returned when a scoreboard operation is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScoreboardError {
+    ObjectiveExists(String),
+    UnknownObjective(String),
+    NameTooLong(String),
+    EntryTooLong(String),
+    /// The objective's scores are maintained by the server.
+    ReadOnly(String),
+}
+
+impl Display for ScoreboardError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ScoreboardError::ObjectiveExists(name) => {
+                write!(f, "An objective named {} already exists", name)
+            }
+            ScoreboardError::UnknownObjective(name) => write!(f, "Unknown objective {}", name),
+            ScoreboardError::NameTooLong(name) => write!(
+                f,
+                "Objective name {} is longer than {} characters",
+                name, MAX_OBJECTIVE_NAME_LEN
+            ),
+            ScoreboardError::EntryTooLong(entry) => write!(
+                f,
+                "Entry {} is longer than {} characters",
+                entry, MAX_ENTRY_LEN
+            ),
+            ScoreboardError::ReadOnly(name) => {
+                write!(f, "Scores for objective {} are read-only", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScoreboardError {}
+
+/// An objective and its scores.
+#[derive(Debug, Clone)]
+pub struct Objective {
+    pub display_name: Text,
+    pub criteria: Criteria,
+    scores: AHashMap<String, i32>,
+}
+
+impl Objective {
+    /// Returns the score of the given entry, if it has one.
+    pub fn score(&self, entry: &str) -> Option<i32> {
+        self.scores.get(entry).copied()
+    }
+
+    /// Returns all entries with a score and their scores.
+    pub fn scores(&self) -> impl Iterator<Item = (&str, i32)> + '_ {
+        self.scores
+            .iter()
+            .map(|(entry, score)| (entry.as_str(), *score))
+    }
+
+    fn action(&self, create: bool) -> ObjectiveAction {
+        let display_name = TextRoot::from(self.display_name.clone()).into();
+        let ty = self.criteria.render_type();
+        if create {
+            ObjectiveAction::Create(display_name, ty)
+        } else {
+            ObjectiveAction::Update(display_name, ty)
+        }
+    }
+}
+
+/// Resource storing the scoreboard.
+#[derive(Debug, Default)]
+pub struct Scoreboard {
+    objectives: AHashMap<String, Objective>,
+    display_slots: AHashMap<DisplaySlot, String>,
+}
+
+impl Scoreboard {
+    /// Returns the objective with the given name.
+    pub fn objective(&self, name: &str) -> Option<&Objective> {
+        self.objectives.get(name)
+    }
+
+    /// Returns all objectives and their names.
+    pub fn objectives(&self) -> impl Iterator<Item = (&str, &Objective)> + '_ {
+        self.objectives
+            .iter()
+            .map(|(name, objective)| (name.as_str(), objective))
+    }
+
+    /// Returns the names of objectives with the given criteria.
+    pub fn objectives_with_criteria(&self, criteria: Criteria) -> Vec<String> {
+        self.objectives()
+            .filter(|(_, objective)| objective.criteria == criteria)
+            .map(|(name, _)| name.to_owned())
+            .collect()
+    }
+
+    /// Returns the name of the objective displayed in `slot`.
+    pub fn displayed(&self, slot: DisplaySlot) -> Option<&str> {
+        self.display_slots.get(&slot).map(String::as_str)
+    }
+
+    pub fn add_objective(
+        &mut self,
+        name: &str,
+        display_name: Text,
+        criteria: Criteria,
+    ) -> Result<ScoreboardObjective, ScoreboardError> {
+        if name.len() > MAX_OBJECTIVE_NAME_LEN {
+            return Err(ScoreboardError::NameTooLong(name.to_owned()));
+        }
+        if self.objectives.contains_key(name) {
+            return Err(ScoreboardError::ObjectiveExists(name.to_owned()));
+        }
+
+        let objective = Objective {
+            display_name,
+            criteria,
+            scores: AHashMap::new(),
+        };
+        let packet = ScoreboardObjective {
+            name: name.to_owned(),
+            action: objective.action(true),
+        };
+        self.objectives.insert(name.to_owned(), objective);
+        Ok(packet)
+    }
+
+    /// Removes an objective, along with its scores and
+    /// any display slots showing it.
+    pub fn remove_objective(&mut self, name: &str) -> Result<ScoreboardObjective, ScoreboardError> {
+        self.objectives
+            .remove(name)
+            .ok_or_else(|| ScoreboardError::UnknownObjective(name.to_owned()))?;
+        self.display_slots.retain(|_, displayed| displayed != name);
+
+        Ok(ScoreboardObjective {
+            name: name.to_owned(),
+            action: ObjectiveAction::Remove,
+        })
+    }
+
+    pub fn set_display_name(
+        &mut self,
+        name: &str,
+        display_name: Text,
+    ) -> Result<ScoreboardObjective, ScoreboardError> {
+        let objective = self.objective_mut(name)?;
+        objective.display_name = display_name;
+
+        Ok(ScoreboardObjective {
+            name: name.to_owned(),
+            action: objective.action(false),
+        })
+    }
+
+    /// Displays an objective in a slot, or clears
+    /// the slot if `name` is `None`.
+    pub fn set_display_slot(
+        &mut self,
+        slot: DisplaySlot,
+        name: Option<&str>,
+    ) -> Result<DisplayScoreboard, ScoreboardError> {
+        match name {
+            Some(name) => {
+                self.objective_mut(name)?;
+                self.display_slots.insert(slot, name.to_owned());
+            }
+            None => {
+                self.display_slots.remove(&slot);
+            }
+        }
+
+        Ok(DisplayScoreboard {
+            position: slot.id(),
+            score_name: name.unwrap_or_default().to_owned(),
+        })
+    }
+
+    /// Sets the score of an entry. Unlike commands, this may
+    /// set scores for read-only criteria.
+    pub fn set_score(
+        &mut self,
+        objective: &str,
+        entry: &str,
+        value: i32,
+    ) -> Result<UpdateScore, ScoreboardError> {
+        if entry.len() > MAX_ENTRY_LEN {
+            return Err(ScoreboardError::EntryTooLong(entry.to_owned()));
+        }
+        self.objective_mut(objective)?
+            .scores
+            .insert(entry.to_owned(), value);
+
+        Ok(UpdateScore {
+            entity_name: entry.to_owned(),
+            objective_name: objective.to_owned(),
+            action: ScoreAction::Update(value),
+        })
+    }
+
+    /// Adds to the score of an entry, which starts at zero
+    /// if it has none. Returns the new score.
+    pub fn add_score(
+        &mut self,
+        objective: &str,
+        entry: &str,
+        amount: i32,
+    ) -> Result<(i32, UpdateScore), ScoreboardError> {
+        let value = self
+            .objective_mut(objective)?
+            .score(entry)
+            .unwrap_or(0)
+            .wrapping_add(amount);
+        let packet = self.set_score(objective, entry, value)?;
+        Ok((value, packet))
+    }
+
+    /// Removes an entry's score for the given objective,
+    /// or for all objectives if `objective` is `None`.
+    pub fn reset_score(
+        &mut self,
+        objective: Option<&str>,
+        entry: &str,
+    ) -> Result<UpdateScore, ScoreboardError> {
+        match objective {
+            Some(objective) => {
+                self.objective_mut(objective)?.scores.remove(entry);
+            }
+            None => {
+                for objective in self.objectives.values_mut() {
+                    objective.scores.remove(entry);
+                }
+            }
+        }
+
+        Ok(UpdateScore {
+            entity_name: entry.to_owned(),
+            objective_name: objective.unwrap_or_default().to_owned(),
+            action: ScoreAction::Remove,
+        })
+    }
+
+    /// Returns the packets which send the whole
+    /// scoreboard to a client that has just joined.
+    pub fn packets(&self) -> Vec<Box<dyn Packet>> {
+        let mut packets: Vec<Box<dyn Packet>> = vec![];
+        for (name, objective) in self.objectives() {
+            packets.push(Box::new(ScoreboardObjective {
+                name: name.to_owned(),
+                action: objective.action(true),
+            }));
+            for (entry, value) in objective.scores() {
+                packets.push(Box::new(UpdateScore {
+                    entity_name: entry.to_owned(),
+                    objective_name: name.to_owned(),
+                    action: ScoreAction::Update(value),
+                }));
+            }
+        }
+        for (slot, name) in &self.display_slots {
+            packets.push(Box::new(DisplayScoreboard {
+                position: slot.id(),
+                score_name: name.clone(),
+            }));
+        }
+        packets
+    }
+
+    fn objective_mut(&mut self, name: &str) -> Result<&mut Objective, ScoreboardError> {
+        self.objectives
+            .get_mut(name)
+            .ok_or_else(|| ScoreboardError::UnknownObjective(name.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn criteria_identifiers_round_trip() {
+        for &criteria in Criteria::all() {
+            assert_eq!(
+                Criteria::from_identifier(criteria.identifier()),
+                Some(criteria)
+            );
+        }
+        assert_eq!(Criteria::from_identifier("kills"), None);
+    }
+
+    #[test]
+    fn objectives_and_scores() {
+        let mut scoreboard = Scoreboard::default();
+        scoreboard
+            .add_objective("kills", Text::from("Kills"), Criteria::PlayerKillCount)
+            .unwrap();
+        assert_eq!(
+            scoreboard
+                .add_objective("kills", Text::from("Kills"), Criteria::Dummy)
+                .err(),
+            Some(ScoreboardError::ObjectiveExists(String::from("kills")))
+        );
+
+        scoreboard.set_score("kills", "a", 3).unwrap();
+        let (value, _) = scoreboard.add_score("kills", "a", 2).unwrap();
+        assert_eq!(value, 5);
+        let (value, _) = scoreboard.add_score("kills", "b", -1).unwrap();
+        assert_eq!(value, -1);
+
+        scoreboard.reset_score(None, "a").unwrap();
+        let objective = scoreboard.objective("kills").unwrap();
+        assert_eq!(objective.score("a"), None);
+        assert_eq!(objective.score("b"), Some(-1));
+    }
+
+    #[test]
+    fn removing_objective_clears_display_slots() {
+        let mut scoreboard = Scoreboard::default();
+        scoreboard
+            .add_objective("health", Text::from("Health"), Criteria::Health)
+            .unwrap();
+        scoreboard
+            .set_display_slot(DisplaySlot::BelowName, Some("health"))
+            .unwrap();
+        assert_eq!(scoreboard.displayed(DisplaySlot::BelowName), Some("health"));
+
+        scoreboard.remove_objective("health").unwrap();
+        assert_eq!(scoreboard.displayed(DisplaySlot::BelowName), None);
+        assert!(scoreboard
+            .set_display_slot(DisplaySlot::Sidebar, Some("health"))
+            .is_err());
+    }
+
+    #[test]
+    fn names_are_limited() {
+        let mut scoreboard = Scoreboard::default();
+        assert!(scoreboard
+            .add_objective("a_very_long_objective", Text::from(""), Criteria::Dummy)
+            .is_err());
+
+        scoreboard
+            .add_objective("lines", Text::from("Lines"), Criteria::Dummy)
+            .unwrap();
+        assert!(scoreboard.set_score("lines", &"x".repeat(41), 0).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Player statistics, as shown in the statistics menu and
+//! stored in `stats/<uuid>.json` in the world directory.
+
+use crate::RunningTasks;
+use feather_core::blocks::BlockKind;
+use feather_core::items::Item;
+use fecs::{Entity, World};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Data version written to statistics files, that of 1.13.2.
+const DATA_VERSION: i32 = 1631;
+
+/// Category of a statistic, which determines what its
+/// identifier names: a block, an item, an entity type,
+/// or one of the `CustomStatistic`s.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StatisticCategory {
+    Mined,
+    Crafted,
+    Used,
+    Broken,
+    PickedUp,
+    Dropped,
+    Killed,
+    KilledBy,
+    Custom,
+}
+
+impl StatisticCategory {
+    /// Returns the category's ID in the statistics packet.
+    pub fn id(self) -> i32 {
+        self as i32
+    }
+
+    pub fn identifier(self) -> &'static str {
+        match self {
+            StatisticCategory::Mined => "minecraft:mined",
+            StatisticCategory::Crafted => "minecraft:crafted",
+            StatisticCategory::Used => "minecraft:used",
+            StatisticCategory::Broken => "minecraft:broken",
+            StatisticCategory::PickedUp => "minecraft:picked_up",
+            StatisticCategory::Dropped => "minecraft:dropped",
+            StatisticCategory::Killed => "minecraft:killed",
+            StatisticCategory::KilledBy => "minecraft:killed_by",
+            StatisticCategory::Custom => "minecraft:custom",
+        }
+    }
+
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        Some(match identifier {
+            "minecraft:mined" => StatisticCategory::Mined,
+            "minecraft:crafted" => StatisticCategory::Crafted,
+            "minecraft:used" => StatisticCategory::Used,
+            "minecraft:broken" => StatisticCategory::Broken,
+            "minecraft:picked_up" => StatisticCategory::PickedUp,
+            "minecraft:dropped" => StatisticCategory::Dropped,
+            "minecraft:killed" => StatisticCategory::Killed,
+            "minecraft:killed_by" => StatisticCategory::KilledBy,
+            "minecraft:custom" => StatisticCategory::Custom,
+            _ => return None,
+        })
+    }
+}
+
+/// The statistics of the `minecraft:custom` category which are tracked.
+///
+/// Discriminants are IDs in the 1.13.2 `custom_stat` registry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CustomStatistic {
+    LeaveGame = 0,
+    /// Despite the name, this counts ticks.
+    PlayOneMinute = 1,
+    TimeSinceDeath = 2,
+    SneakTime = 4,
+    WalkOneCm = 5,
+    CrouchOneCm = 6,
+    SprintOneCm = 7,
+    FallOneCm = 9,
+    FlyOneCm = 11,
+    AviateOneCm = 17,
+    SwimOneCm = 18,
+    Deaths = 28,
+    MobKills = 29,
+    PlayerKills = 31,
+}
+
+impl CustomStatistic {
+    pub fn id(self) -> i32 {
+        self as i32
+    }
+
+    pub fn identifier(self) -> &'static str {
+        match self {
+            CustomStatistic::LeaveGame => "minecraft:leave_game",
+            CustomStatistic::PlayOneMinute => "minecraft:play_one_minute",
+            CustomStatistic::TimeSinceDeath => "minecraft:time_since_death",
+            CustomStatistic::SneakTime => "minecraft:sneak_time",
+            CustomStatistic::WalkOneCm => "minecraft:walk_one_cm",
+            CustomStatistic::CrouchOneCm => "minecraft:crouch_one_cm",
+            CustomStatistic::SprintOneCm => "minecraft:sprint_one_cm",
+            CustomStatistic::FallOneCm => "minecraft:fall_one_cm",
+            CustomStatistic::FlyOneCm => "minecraft:fly_one_cm",
+            CustomStatistic::AviateOneCm => "minecraft:aviate_one_cm",
+            CustomStatistic::SwimOneCm => "minecraft:swim_one_cm",
+            CustomStatistic::Deaths => "minecraft:deaths",
+            CustomStatistic::MobKills => "minecraft:mob_kills",
+            CustomStatistic::PlayerKills => "minecraft:player_kills",
+        }
+    }
+
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        ALL_CUSTOM_STATISTICS
+            .iter()
+            .copied()
+            .find(|stat| stat.identifier() == identifier)
+    }
+}
+
+const ALL_CUSTOM_STATISTICS: [CustomStatistic; 14] = [
+    CustomStatistic::LeaveGame,
+    CustomStatistic::PlayOneMinute,
+    CustomStatistic::TimeSinceDeath,
+    CustomStatistic::SneakTime,
+    CustomStatistic::WalkOneCm,
+    CustomStatistic::CrouchOneCm,
+    CustomStatistic::SprintOneCm,
+    CustomStatistic::FallOneCm,
+    CustomStatistic::FlyOneCm,
+    CustomStatistic::AviateOneCm,
+    CustomStatistic::SwimOneCm,
+    CustomStatistic::Deaths,
+    CustomStatistic::MobKills,
+    CustomStatistic::PlayerKills,
+];
+
+/// A single statistic.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Statistic {
+    Mined(BlockKind),
+    Used(Item),
+    /// Kills of the entity type with the given identifier.
+    Killed(&'static str),
+    /// Deaths to the entity type with the given identifier.
+    KilledBy(&'static str),
+    Custom(CustomStatistic),
+}
+
+impl Statistic {
+    pub fn category(self) -> StatisticCategory {
+        match self {
+            Statistic::Mined(_) => StatisticCategory::Mined,
+            Statistic::Used(_) => StatisticCategory::Used,
+            Statistic::Killed(_) => StatisticCategory::Killed,
+            Statistic::KilledBy(_) => StatisticCategory::KilledBy,
+            Statistic::Custom(_) => StatisticCategory::Custom,
+        }
+    }
+
+    /// Returns the identifier of the block, item, entity
+    /// type or custom statistic this statistic counts.
+    pub fn identifier(self) -> &'static str {
+        match self {
+            Statistic::Mined(block) => block.identifier(),
+            Statistic::Used(item) => item.identifier(),
+            Statistic::Killed(entity) | Statistic::KilledBy(entity) => entity,
+            Statistic::Custom(stat) => stat.identifier(),
+        }
+    }
+}
+
+/// A player's statistics, keyed by category and then identifier
+/// as in the vanilla format. Statistics which are not tracked,
+/// such as those from a vanilla server, are kept as they are.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Statistics(BTreeMap<String, BTreeMap<String, i32>>);
+
+impl Statistics {
+    pub fn get(&self, stat: Statistic) -> i32 {
+        self.0
+            .get(stat.category().identifier())
+            .and_then(|stats| stats.get(stat.identifier()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn set(&mut self, stat: Statistic, value: i32) {
+        self.0
+            .entry(stat.category().identifier().to_owned())
+            .or_default()
+            .insert(stat.identifier().to_owned(), value);
+    }
+
+    /// Adds to a statistic, saturating at `i32::max_value()`.
+    pub fn add(&mut self, stat: Statistic, amount: i32) {
+        let value = self.get(stat).saturating_add(amount);
+        self.set(stat, value);
+    }
+
+    /// Returns all statistics as their category,
+    /// identifier and value.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, i32)> {
+        self.0.iter().flat_map(|(category, stats)| {
+            stats
+                .iter()
+                .map(move |(stat, &value)| (category.as_str(), stat.as_str(), value))
+        })
+    }
+
+    /// Loads a player's statistics, returning no statistics
+    /// if the player has no statistics file.
+    pub async fn load(world_dir: &Path, uuid: Uuid) -> anyhow::Result<Self> {
+        match tokio::fs::read(file_path(world_dir, uuid)).await {
+            Ok(bytes) => {
+                let file: StatisticsFile = serde_json::from_slice(&bytes)?;
+                Ok(file.stats)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes a player's statistics asynchronously
+    /// on the given task manager.
+    pub fn save(&self, world_dir: &Path, uuid: Uuid, tasks: &RunningTasks) {
+        let file = StatisticsFile {
+            stats: self.clone(),
+            data_version: DATA_VERSION,
+        };
+        let world_dir = world_dir.to_owned();
+        tasks.schedule(async move {
+            if let Err(e) = save_file(&world_dir, uuid, &file).await {
+                log::error!("Failed to save statistics for UUID {}: {}", uuid, e);
+            }
+        });
+    }
+}
+
+/// Adds to a statistic of an entity. Entities
+/// other than players have no statistics, and are ignored.
+pub fn add_statistic(world: &mut World, entity: Entity, stat: Statistic, amount: i32) {
+    if world.has::<Statistics>(entity) {
+        world.get_mut::<Statistics>(entity).add(stat, amount);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StatisticsFile {
+    stats: Statistics,
+    #[serde(rename = "DataVersion")]
+    data_version: i32,
+}
+
+async fn save_file(world_dir: &Path, uuid: Uuid, file: &StatisticsFile) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(world_dir.join("stats")).await?;
+    let json = serde_json::to_string(file)?;
+    tokio::fs::write(file_path(world_dir, uuid), json).await?;
+    Ok(())
+}
+
+fn file_path(world_dir: &Path, uuid: Uuid) -> PathBuf {
+    world_dir.join("stats").join(format!("{}.json", uuid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vanilla_format() {
+        let json = r#"{
+            "stats": {
+                "minecraft:mined": { "minecraft:stone": 12 },
+                "minecraft:picked_up": { "minecraft:cobblestone": 12 },
+                "minecraft:custom": { "minecraft:play_one_minute": 2400 }
+            },
+            "DataVersion": 1631
+        }"#;
+        let mut file: StatisticsFile = serde_json::from_str(json).unwrap();
+        let stats = &mut file.stats;
+        assert_eq!(stats.get(Statistic::Mined(BlockKind::Stone)), 12);
+        assert_eq!(
+            stats.get(Statistic::Custom(CustomStatistic::PlayOneMinute)),
+            2400
+        );
+        assert_eq!(stats.get(Statistic::Used(Item::Bow)), 0);
+
+        stats.add(Statistic::Killed("minecraft:zombie"), 1);
+        stats.add(Statistic::Mined(BlockKind::Stone), i32::max_value());
+        assert_eq!(
+            stats.get(Statistic::Mined(BlockKind::Stone)),
+            i32::max_value()
+        );
+        assert_eq!(stats.iter().count(), 4);
+
+        let saved = serde_json::to_value(&file).unwrap();
+        assert_eq!(saved["stats"]["minecraft:killed"]["minecraft:zombie"], 1);
+        assert_eq!(
+            saved["stats"]["minecraft:picked_up"]["minecraft:cobblestone"],
+            12
+        );
+    }
+
+    #[test]
+    fn custom_statistic_identifiers() {
+        for &stat in ALL_CUSTOM_STATISTICS.iter() {
+            assert_eq!(
+                CustomStatistic::from_identifier(stat.identifier()),
+                Some(stat)
+            );
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Tracking of how long ticks take to run, used
+//! to detect when the server is overloaded.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of ticks over which the average is taken.
+const SAMPLES: usize = 100;
+
+/// Resource holding the durations of the most recent ticks.
+#[derive(Debug, Default)]
+pub struct TickTimes {
+    samples: VecDeque<Duration>,
+}
+
+impl TickTimes {
+    /// Records the duration of a tick, discarding the oldest.
+    pub fn record(&mut self, duration: Duration) {
+        if self.samples.len() == SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(duration);
+    }
+
+    /// Returns the average duration of the recorded ticks.
+    pub fn average(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::default();
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn average_of_recent_ticks() {
+        let mut times = TickTimes::default();
+        assert_eq!(times.average(), Duration::default());
+
+        for _ in 0..SAMPLES {
+            times.record(Duration::from_millis(100));
+        }
+        for _ in 0..SAMPLES / 2 {
+            times.record(Duration::from_millis(20));
+        }
+        assert_eq!(times.average(), Duration::from_millis(60));
+    }
+}
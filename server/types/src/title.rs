@@ -0,0 +1,117 @@
+//! Titles, which are shown in large text in the
+//! middle of a player's screen.
+
+use feather_core::network::packets::{Title as TitlePacket, TitleAction};
+use feather_core::text::{Text, TextRoot};
+use smallvec::SmallVec;
+
+/// How long a title takes to fade in, stays on screen,
+/// and takes to fade out, all in ticks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TitleTimes {
+    pub fade_in: u32,
+    pub stay: u32,
+    pub fade_out: u32,
+}
+
+impl Default for TitleTimes {
+    fn default() -> Self {
+        Self {
+            fade_in: 10,
+            stay: 70,
+            fade_out: 20,
+        }
+    }
+}
+
+/// A title to show to a player.
+///
+/// A subtitle is only displayed along with a title, so sending
+/// a subtitle alone shows it the next time a title is sent.
+#[derive(Clone, Debug, Default)]
+pub struct Title {
+    pub title: Option<Text>,
+    pub subtitle: Option<Text>,
+    /// Timings to use, or `None` to keep
+    /// those the player was last sent.
+    pub times: Option<TitleTimes>,
+}
+
+impl Title {
+    /// Creates a title with the given text.
+    pub fn new(title: impl Into<Text>) -> Self {
+        Self {
+            title: Some(title.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_subtitle(mut self, subtitle: impl Into<Text>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    pub fn with_times(mut self, times: TitleTimes) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    /// Returns the packets which show this title. The title
+    /// itself is sent last, as it is what displays the others.
+    pub fn packets(self) -> SmallVec<[TitlePacket; 3]> {
+        let mut packets = SmallVec::new();
+        if let Some(times) = self.times {
+            packets.push(TitlePacket {
+                action: TitleAction::SetTimes {
+                    fade_in: times.fade_in as i32,
+                    stay: times.stay as i32,
+                    fade_out: times.fade_out as i32,
+                },
+            });
+        }
+        if let Some(subtitle) = self.subtitle {
+            packets.push(TitlePacket {
+                action: TitleAction::SetSubtitle(TextRoot::from(subtitle).into()),
+            });
+        }
+        if let Some(title) = self.title {
+            packets.push(TitlePacket {
+                action: TitleAction::SetTitle(TextRoot::from(title).into()),
+            });
+        }
+        packets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_is_sent_last() {
+        let packets = Title::new("Title")
+            .with_subtitle("Subtitle")
+            .with_times(TitleTimes::default())
+            .packets();
+        let ids: Vec<_> = packets
+            .iter()
+            .map(|packet| match packet.action {
+                TitleAction::SetTimes { .. } => "times",
+                TitleAction::SetSubtitle(_) => "subtitle",
+                TitleAction::SetTitle(_) => "title",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(ids, vec!["times", "subtitle", "title"]);
+    }
+
+    #[test]
+    fn subtitle_alone() {
+        let packets = Title {
+            subtitle: Some(Text::from("Subtitle")),
+            ..Default::default()
+        }
+        .packets();
+        assert_eq!(packets.len(), 1);
+    }
+}
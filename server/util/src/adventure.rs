@@ -0,0 +1,155 @@
+//! Adventure mode, in which players may only break and place
+//! blocks allowed by the `CanDestroy` and `CanPlaceOn` tags
+//! of the item they use.
+
+use feather_core::blocks::BlockId;
+use feather_core::items::ItemStack;
+use feather_core::util::Gamemode;
+
+/// Removes the `minecraft:` namespace from an identifier.
+fn strip_namespace(identifier: &str) -> &str {
+    identifier.trim_start_matches("minecraft:")
+}
+
+/// Returns whether a block is in the vanilla block tag with the given name.
+///
+/// Only the tags which are commonly used in `CanDestroy` and
+/// `CanPlaceOn` are known; other tags match no blocks.
+pub fn block_has_tag(tag: &str, block: BlockId) -> bool {
+    let name = strip_namespace(block.identifier());
+    match strip_namespace(tag) {
+        "logs" => name.ends_with("_log") || name.ends_with("_wood"),
+        "planks" => name.ends_with("_planks"),
+        "wool" => name.ends_with("_wool"),
+        "carpets" => name.ends_with("_carpet"),
+        "leaves" => block.is_leaves(),
+        "saplings" => name.ends_with("_sapling"),
+        "sand" => name == "sand" || name == "red_sand",
+        "stone_bricks" => name.ends_with("stone_bricks"),
+        "rails" => name.ends_with("rail"),
+        "buttons" => name.ends_with("_button"),
+        _ => false,
+    }
+}
+
+/// Returns whether a block matches a block predicate of a
+/// `CanDestroy` or `CanPlaceOn` tag.
+///
+/// Predicates name either a block, such as `minecraft:oak_stairs[half=top]`,
+/// or a block tag, such as `#minecraft:logs`. Properties
+/// which a predicate leaves out match any value.
+pub fn block_matches_predicate(predicate: &str, block: BlockId) -> bool {
+    let (name, properties) = match predicate.find('[') {
+        Some(start) if predicate.ends_with(']') => (
+            &predicate[..start],
+            &predicate[start + 1..predicate.len() - 1],
+        ),
+        Some(_) => return false,
+        None => (predicate, ""),
+    };
+
+    let name_matches = if name.starts_with('#') {
+        block_has_tag(&name[1..], block)
+    } else {
+        strip_namespace(name) == strip_namespace(block.identifier())
+    };
+    if !name_matches {
+        return false;
+    }
+
+    let block_properties = block.to_properties_map();
+    properties
+        .split(',')
+        .filter(|property| !property.trim().is_empty())
+        .all(|property| {
+            let mut split = property.splitn(2, '=');
+            let key = split.next().unwrap_or_default().trim();
+            let value = split.next().unwrap_or_default().trim();
+            block_properties.get(key) == Some(&value)
+        })
+}
+
+/// Returns whether a player in the given gamemode, holding `tool`,
+/// may break `block`.
+pub fn can_break_in_gamemode(gamemode: Gamemode, tool: Option<&ItemStack>, block: BlockId) -> bool {
+    if gamemode != Gamemode::Adventure {
+        return true;
+    }
+
+    tool.map_or(false, |tool| {
+        tool.can_destroy()
+            .any(|predicate| block_matches_predicate(predicate, block))
+    })
+}
+
+/// Returns whether a player in the given gamemode may place
+/// `stack` against the block `against`.
+pub fn can_place_in_gamemode(gamemode: Gamemode, stack: &ItemStack, against: BlockId) -> bool {
+    gamemode != Gamemode::Adventure
+        || stack
+            .can_place_on()
+            .any(|predicate| block_matches_predicate(predicate, against))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::items::{Item, ItemNbt};
+
+    #[test]
+    fn predicates() {
+        assert!(block_matches_predicate("minecraft:stone", BlockId::stone()));
+        assert!(block_matches_predicate("stone", BlockId::stone()));
+        assert!(!block_matches_predicate("minecraft:dirt", BlockId::stone()));
+        assert!(block_matches_predicate(
+            "#minecraft:logs",
+            BlockId::oak_log()
+        ));
+        assert!(block_matches_predicate("#planks", BlockId::spruce_planks()));
+        assert!(!block_matches_predicate(
+            "#minecraft:logs",
+            BlockId::stone()
+        ));
+        assert!(!block_matches_predicate(
+            "#minecraft:unknown",
+            BlockId::stone()
+        ));
+        assert!(!block_matches_predicate(
+            "minecraft:stone[",
+            BlockId::stone()
+        ));
+    }
+
+    #[test]
+    fn adventure_restrictions() {
+        let pickaxe = ItemStack::new(Item::DiamondPickaxe, 1);
+        let stone = BlockId::stone();
+
+        assert!(can_break_in_gamemode(Gamemode::Survival, None, stone));
+        assert!(!can_break_in_gamemode(Gamemode::Adventure, None, stone));
+        assert!(!can_break_in_gamemode(
+            Gamemode::Adventure,
+            Some(&pickaxe),
+            stone
+        ));
+
+        assert!(can_place_in_gamemode(Gamemode::Creative, &pickaxe, stone));
+        assert!(!can_place_in_gamemode(Gamemode::Adventure, &pickaxe, stone));
+
+        let pickaxe = pickaxe.with_nbt(ItemNbt {
+            can_destroy: vec![String::from("minecraft:stone")],
+            can_place_on: vec![String::from("#minecraft:logs")],
+            ..Default::default()
+        });
+        assert!(can_break_in_gamemode(
+            Gamemode::Adventure,
+            Some(&pickaxe),
+            stone
+        ));
+        assert!(can_place_in_gamemode(
+            Gamemode::Adventure,
+            &pickaxe,
+            BlockId::oak_log()
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+//! Air supply and drowning.
+
+use crate::{armor_enchantment, has_invulnerable_gamemode};
+use feather_core::entitymeta::EntityMetadata;
+use feather_core::items::Enchantment;
+use feather_core::util::Position;
+use feather_server_types::{
+    player_eye_height, ActiveEffects, Air, BumpVec, DamageCause, EntityDamageRequest, Game, Health,
+    Physics, Player, StatusEffect, MAX_AIR,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+use nalgebra_glm::vec3;
+use rand::Rng;
+
+/// Damage dealt each time a drowning entity runs out of air.
+pub const DROWNING_DAMAGE: f32 = 2.0;
+
+/// Air an entity reaches before taking drowning damage.
+/// Drowning entities are damaged once per second.
+const DROWNING_AIR: i32 = -20;
+
+/// Returns the remaining air of an entity after one tick with
+/// its head underwater, and whether it takes drowning damage.
+///
+/// Each level of Respiration gives a chance for no air to be used.
+pub fn breathe_underwater(air: i32, respiration: u32, rng: &mut impl Rng) -> (i32, bool) {
+    if respiration > 0 && rng.gen_range(0, respiration + 1) > 0 {
+        return (air, false);
+    }
+
+    let air = air - 1;
+    if air <= DROWNING_AIR {
+        (0, true)
+    } else {
+        (air, false)
+    }
+}
+
+/// Returns the height of an entity's eyes above its position.
+pub(crate) fn eye_height(world: &World, entity: Entity) -> Option<f64> {
+    if world.has::<Player>(entity) {
+        Some(player_eye_height(world, entity))
+    } else {
+        let physics = world.try_get::<Physics>(entity)?;
+        Some((physics.bbox.maxs().y - physics.bbox.mins().y) * 0.85)
+    }
+}
+
+/// Returns whether an entity can breathe regardless of
+/// whether its head is in water.
+fn breathes_freely(world: &World, entity: Entity) -> bool {
+    let water_breathing = world
+        .try_get::<ActiveEffects>(entity)
+        .map_or(false, |effects| effects.has(StatusEffect::WaterBreathing));
+    water_breathing || has_invulnerable_gamemode(world, entity)
+}
+
+/// System which uses up the air of living entities whose heads
+/// are underwater, damaging those which run out.
+#[fecs::system]
+pub fn update_air(game: &mut Game, world: &mut World) {
+    let mut changes = BumpVec::new_in(game.bump());
+
+    for (entity, pos) in <Read<Position>>::query()
+        .filter(component::<Health>())
+        .iter_entities(world.inner())
+    {
+        let eyes = match eye_height(world, entity) {
+            Some(height) => *pos + vec3(0.0, height, 0.0),
+            None => continue,
+        };
+        let head_in_water = game
+            .block_at(eyes.block())
+            .map_or(false, |block| block.is_water());
+
+        let air = world.try_get::<Air>(entity).map_or(MAX_AIR, |air| air.0);
+        let (new_air, drowning) = if !head_in_water {
+            (MAX_AIR, false)
+        } else if breathes_freely(world, entity) {
+            (air, false)
+        } else {
+            breathe_underwater(
+                air,
+                armor_enchantment(world, entity, Enchantment::Respiration),
+                &mut *game.rng(),
+            )
+        };
+
+        if new_air != air || drowning || !world.has::<Air>(entity) {
+            changes.push((entity, new_air, drowning));
+        }
+    }
+
+    for (entity, air, drowning) in changes {
+        if world.has::<Air>(entity) {
+            *world.get_mut::<Air>(entity) = Air(air);
+        } else {
+            world.add(entity, Air(air)).unwrap();
+        }
+        if world.has::<EntityMetadata>(entity) {
+            world.get_mut::<EntityMetadata>(entity).set_air(air);
+        }
+
+        if drowning {
+            game.handle(
+                world,
+                EntityDamageRequest {
+                    entity,
+                    damage: DROWNING_DAMAGE,
+                    cause: DamageCause::Drowning,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drowning_after_air_runs_out() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(
+            breathe_underwater(MAX_AIR, 0, &mut rng),
+            (MAX_AIR - 1, false)
+        );
+        assert_eq!(breathe_underwater(-18, 0, &mut rng), (-19, false));
+        assert_eq!(breathe_underwater(-19, 0, &mut rng), (0, true));
+    }
+}
@@ -0,0 +1,252 @@
+//! Armor: equipping it, the armor points and toughness
+//! it grants, and the durability it loses when hit.
+
+use feather_core::inventory::{
+    Inventory, SlotIndex, SLOT_ARMOR_CHEST, SLOT_ARMOR_FEET, SLOT_ARMOR_HEAD, SLOT_ARMOR_LEGS,
+    SLOT_ARMOR_MAX, SLOT_ARMOR_MIN, SLOT_HOTBAR_OFFSET, SLOT_OFFHAND,
+};
+use feather_core::items::Item;
+use feather_core::util::Hand;
+use feather_server_types::{
+    Attribute, AttributeModifier, Attributes, Game, HeldItem, InventoryUpdateEvent,
+    ModifierOperation, Player, ARMOR_MODIFIERS,
+};
+use fecs::{Entity, World};
+use smallvec::{smallvec, SmallVec};
+
+/// Returns the armor slot in which an item is worn, if any.
+pub fn armor_slot(item: Item) -> Option<SlotIndex> {
+    match item {
+        Item::LeatherHelmet
+        | Item::ChainmailHelmet
+        | Item::IronHelmet
+        | Item::DiamondHelmet
+        | Item::GoldenHelmet
+        | Item::TurtleHelmet
+        | Item::CarvedPumpkin
+        | Item::SkeletonSkull
+        | Item::WitherSkeletonSkull
+        | Item::PlayerHead
+        | Item::ZombieHead
+        | Item::CreeperHead
+        | Item::DragonHead => Some(SLOT_ARMOR_HEAD),
+        Item::LeatherChestplate
+        | Item::ChainmailChestplate
+        | Item::IronChestplate
+        | Item::DiamondChestplate
+        | Item::GoldenChestplate
+        | Item::Elytra => Some(SLOT_ARMOR_CHEST),
+        Item::LeatherLeggings
+        | Item::ChainmailLeggings
+        | Item::IronLeggings
+        | Item::DiamondLeggings
+        | Item::GoldenLeggings => Some(SLOT_ARMOR_LEGS),
+        Item::LeatherBoots
+        | Item::ChainmailBoots
+        | Item::IronBoots
+        | Item::DiamondBoots
+        | Item::GoldenBoots => Some(SLOT_ARMOR_FEET),
+        _ => None,
+    }
+}
+
+/// Returns whether an item is equipped by using it.
+/// Pumpkins and heads can only be worn by placing
+/// them in the armor slot.
+fn equipped_on_use(item: Item) -> bool {
+    match item {
+        Item::CarvedPumpkin
+        | Item::SkeletonSkull
+        | Item::WitherSkeletonSkull
+        | Item::PlayerHead
+        | Item::ZombieHead
+        | Item::CreeperHead
+        | Item::DragonHead => false,
+        _ => armor_slot(item).is_some(),
+    }
+}
+
+/// Returns the armor points and armor toughness
+/// granted by an item while it is worn.
+pub fn armor_points(item: Item) -> (f64, f64) {
+    match item {
+        Item::LeatherHelmet | Item::LeatherBoots => (1.0, 0.0),
+        Item::LeatherLeggings => (2.0, 0.0),
+        Item::LeatherChestplate => (3.0, 0.0),
+        Item::GoldenBoots | Item::ChainmailBoots => (1.0, 0.0),
+        Item::GoldenHelmet | Item::ChainmailHelmet | Item::IronHelmet | Item::TurtleHelmet => {
+            (2.0, 0.0)
+        }
+        Item::GoldenLeggings => (3.0, 0.0),
+        Item::ChainmailLeggings => (4.0, 0.0),
+        Item::GoldenChestplate | Item::ChainmailChestplate => (5.0, 0.0),
+        Item::IronBoots => (2.0, 0.0),
+        Item::IronLeggings => (5.0, 0.0),
+        Item::IronChestplate => (6.0, 0.0),
+        Item::DiamondHelmet | Item::DiamondBoots => (3.0, 2.0),
+        Item::DiamondLeggings => (6.0, 2.0),
+        Item::DiamondChestplate => (8.0, 2.0),
+        _ => (0.0, 0.0),
+    }
+}
+
+/// Returns the attribute modifiers applied by an
+/// item while it is worn in its armor slot.
+pub fn armor_modifiers(item: Item) -> SmallVec<[(Attribute, AttributeModifier); 2]> {
+    let slot = match armor_slot(item) {
+        Some(slot) => slot,
+        None => return SmallVec::new(),
+    };
+    let uuid = ARMOR_MODIFIERS[slot - SLOT_ARMOR_MIN];
+
+    let (armor, toughness) = armor_points(item);
+    let modifier = |amount| AttributeModifier {
+        uuid,
+        amount,
+        operation: ModifierOperation::Add,
+    };
+    smallvec![
+        (Attribute::Armor, modifier(armor)),
+        (Attribute::ArmorToughness, modifier(toughness)),
+    ]
+}
+
+/// Updates the armor modifiers of an entity's attributes
+/// to match the armor it wears.
+pub fn update_armor_attributes(world: &mut World, entity: Entity) {
+    let worn: SmallVec<[(SlotIndex, Option<Item>); 4]> = match world.try_get::<Inventory>(entity) {
+        Some(inventory) => (SLOT_ARMOR_MIN..=SLOT_ARMOR_MAX)
+            .map(|slot| (slot, inventory.item_at(slot).map(|stack| stack.ty)))
+            .collect(),
+        None => return,
+    };
+
+    if !world.has::<Attributes>(entity) {
+        world.add(entity, Attributes::new()).unwrap();
+    }
+    let mut attributes = world.get_mut::<Attributes>(entity);
+
+    for (slot, item) in worn {
+        let uuid = ARMOR_MODIFIERS[slot - SLOT_ARMOR_MIN];
+        attributes.remove_modifier(Attribute::Armor, uuid);
+        attributes.remove_modifier(Attribute::ArmorToughness, uuid);
+
+        // Items placed in the wrong slot do not protect.
+        let item = match item {
+            Some(item) if armor_slot(item) == Some(slot) => item,
+            _ => continue,
+        };
+        for (attribute, modifier) in armor_modifiers(item) {
+            attributes.add_modifier(attribute, modifier);
+        }
+    }
+}
+
+/// Updates armor attributes when a player's armor changes.
+#[fecs::event_handler]
+pub fn on_inventory_update_update_armor_attributes(
+    event: &InventoryUpdateEvent,
+    world: &mut World,
+) {
+    if event
+        .slots
+        .iter()
+        .any(|slot| (SLOT_ARMOR_MIN..=SLOT_ARMOR_MAX).contains(slot))
+    {
+        update_armor_attributes(world, event.player);
+    }
+}
+
+/// Equips the armor held in the given hand if its armor
+/// slot is empty. Returns whether the armor was equipped.
+pub fn equip_armor(game: &mut Game, world: &mut World, player: Entity, hand: Hand) -> bool {
+    let hand_slot = match hand {
+        Hand::Main => SLOT_HOTBAR_OFFSET + world.get::<HeldItem>(player).0,
+        Hand::Off => SLOT_OFFHAND,
+    };
+
+    let armor_slot = {
+        let mut inventory = world.get_mut::<Inventory>(player);
+        let stack = match inventory.item_at(hand_slot) {
+            Some(stack) if equipped_on_use(stack.ty) => stack.clone(),
+            _ => return false,
+        };
+        let slot = armor_slot(stack.ty).unwrap();
+        if inventory.item_at(slot).is_some() {
+            return false;
+        }
+
+        inventory.set_item_at(slot, stack);
+        inventory.clear_item_at(hand_slot);
+        slot
+    };
+
+    game.handle(
+        world,
+        InventoryUpdateEvent {
+            slots: smallvec![hand_slot, armor_slot],
+            player,
+        },
+    );
+    true
+}
+
+/// Returns the durability each piece of armor loses
+/// when its wearer takes `damage` before armor reductions.
+pub fn armor_durability_loss(damage: f32) -> u32 {
+    ((damage / 4.0) as u32).max(1)
+}
+
+/// Wears down the armor a player wears when they take `damage`
+/// which armor reduces. Elytra, pumpkins and heads, which
+/// grant no armor points, do not wear down.
+pub fn wear_armor(game: &mut Game, world: &mut World, player: Entity, damage: f32) {
+    if damage <= 0.0 || !world.has::<Player>(player) {
+        return;
+    }
+
+    let slots: SmallVec<[SlotIndex; 4]> = {
+        let inventory = world.get::<Inventory>(player);
+        (SLOT_ARMOR_MIN..=SLOT_ARMOR_MAX)
+            .filter(|slot| {
+                inventory
+                    .item_at(*slot)
+                    .map_or(false, |stack| armor_points(stack.ty).0 > 0.0)
+            })
+            .collect()
+    };
+    let loss = armor_durability_loss(damage);
+    for slot in slots {
+        crate::wear_item(game, world, player, slot, loss);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn full_diamond_armor() {
+        let items = [
+            Item::DiamondHelmet,
+            Item::DiamondChestplate,
+            Item::DiamondLeggings,
+            Item::DiamondBoots,
+        ];
+        let mut attributes = Attributes::new();
+        for item in items.iter() {
+            for (attribute, modifier) in armor_modifiers(*item) {
+                attributes.add_modifier(attribute, modifier);
+            }
+        }
+        assert_eq!(attributes.value(Attribute::Armor), 20.0);
+        assert_eq!(attributes.value(Attribute::ArmorToughness), 8.0);
+    }
+
+    #[test]
+    fn durability_loss() {
+        assert_eq!(armor_durability_loss(1.0), 1);
+        assert_eq!(armor_durability_loss(10.0), 2);
+    }
+}
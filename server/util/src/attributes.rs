@@ -0,0 +1,50 @@
+//! Sending entity attributes to clients.
+
+use feather_core::network::packets::{EntityProperties, EntityProperty, EntityPropertyModifier};
+use feather_server_types::{Attribute, AttributeInstance, Attributes, EntityId, Game};
+use fecs::{Entity, World};
+
+/// Builds the `EntityProperties` packet describing
+/// the given attributes of an entity.
+pub fn entity_properties(
+    world: &World,
+    entity: Entity,
+    attributes: &[Attribute],
+) -> EntityProperties {
+    let instances = world.try_get::<Attributes>(entity);
+
+    let properties = attributes
+        .iter()
+        .map(|&attribute| {
+            let instance = instances
+                .as_ref()
+                .and_then(|instances| instances.instance(attribute).cloned())
+                .unwrap_or_else(|| AttributeInstance::new(attribute.default_value()));
+            EntityProperty {
+                key: attribute.identifier().to_owned(),
+                value: instance.base,
+                modifiers: instance
+                    .modifiers
+                    .iter()
+                    .map(|modifier| EntityPropertyModifier {
+                        uuid: modifier.uuid,
+                        amount: modifier.amount,
+                        operation: modifier.operation.id(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    EntityProperties {
+        entity_id: world.get::<EntityId>(entity).0,
+        properties,
+    }
+}
+
+/// Sends the given attributes of an entity to all players
+/// who can see it, including the entity itself.
+pub fn broadcast_attributes(game: &Game, world: &World, entity: Entity, attributes: &[Attribute]) {
+    let packet = entity_properties(world, entity, attributes);
+    game.broadcast_entity_update(world, packet, entity, None);
+}
@@ -1,7 +1,8 @@
 //! Assorted functionality relating to blocks, including:
 //! * The block notify system, where a block update "notifies"
 //! adjacent blocks of the update. This is used for spawning
-//! falling blocks, for example.
+//! falling blocks and for breaking blocks which have lost
+//! their support, for example.
 //!
 //! The block notify system works as follows: when a block
 //! is updated, `on_block_update_notify_adjacent` is called,
@@ -16,10 +17,10 @@
 //! acts as a sort of event, as other systems can check for these entities
 //! and perform actions based on their components.
 
-use crate::adjacent_blocks;
+use crate::{adjacent_blocks, support, Fluid};
 use feather_core::blocks::{BlockId, BlockKind};
 use feather_core::util::BlockPosition;
-use feather_server_types::{BlockUpdateEvent, Game};
+use feather_server_types::{BlockUpdateCause, BlockUpdateEvent, Game};
 use fecs::{EntityBuilder, World};
 use std::iter;
 
@@ -35,23 +36,46 @@ pub struct BlockNotifyPosition(pub BlockPosition);
 #[derive(Copy, Clone, Debug)]
 pub struct BlockNotifyBlock(pub BlockId);
 
+/// Component storing the cause of the update which
+/// created a block notify entity.
+#[derive(Copy, Clone, Debug)]
+pub struct BlockNotifyCause(pub BlockUpdateCause);
+
 /// Marker component for block notify entities created for falling
 /// blocks, such as sand and gravel.
 #[derive(Copy, Clone, Debug)]
 pub struct BlockNotifyFallingBlock;
 
+/// Marker component for block notify entities created for blocks
+/// which need support from an adjacent block, such as torches.
+/// See `is_supported`.
+#[derive(Copy, Clone, Debug)]
+pub struct BlockNotifySupport;
+
+/// Marker component for block notify entities
+/// created for water and lava, which may flow.
+#[derive(Copy, Clone, Debug)]
+pub struct BlockNotifyFluid;
+
 /// Returns an `EntityBuilder` to create the block notify entity for
 /// the given block type.
-fn notify_entity_for_block(block: BlockId, pos: BlockPosition) -> Option<EntityBuilder> {
+fn notify_entity_for_block(
+    block: BlockId,
+    pos: BlockPosition,
+    cause: BlockUpdateCause,
+) -> Option<EntityBuilder> {
     let builder = EntityBuilder::new()
         .with(BlockNotify)
         .with(BlockNotifyPosition(pos))
-        .with(BlockNotifyBlock(block));
+        .with(BlockNotifyBlock(block))
+        .with(BlockNotifyCause(cause));
 
     match block.kind() {
         BlockKind::Sand | BlockKind::Gravel | BlockKind::RedSand => {
             Some(builder.with(BlockNotifyFallingBlock))
         }
+        _ if support(block).is_some() => Some(builder.with(BlockNotifySupport)),
+        _ if Fluid::of(block).is_some() => Some(builder.with(BlockNotifyFluid)),
         _ => None,
     }
 }
@@ -75,9 +99,47 @@ pub fn on_block_update_notify_adjacent(
             }
         })
         .filter_map(|(adjacent_block, adjacent_pos)| {
-            notify_entity_for_block(adjacent_block, adjacent_pos)
+            notify_entity_for_block(adjacent_block, adjacent_pos, event.cause)
         })
         .for_each(|builder| {
             builder.build().spawn_in(world);
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::chunk::Chunk;
+    use feather_core::position;
+    use feather_core::util::ChunkPosition;
+    use feather_test_framework::Test;
+    use fecs::{IntoQuery, Read};
+
+    #[test]
+    fn notify_entities_keep_update_cause() {
+        let mut test = Test::new();
+        test.game
+            .chunk_map
+            .insert(Chunk::new(ChunkPosition::new(0, 0)));
+        let sand = BlockPosition::new(0, 65, 0);
+        test.game.chunk_map.set_block_at(sand, BlockId::sand());
+
+        let player = test.player("digger", position!(0.5, 66.0, 0.5));
+        let cause = BlockUpdateCause::entity(player);
+        test.handle(
+            BlockUpdateEvent {
+                pos: BlockPosition::new(0, 64, 0),
+                old: BlockId::stone(),
+                new: BlockId::air(),
+                cause,
+            },
+            on_block_update_notify_adjacent,
+        );
+
+        let notifies: Vec<_> = <(Read<BlockNotifyPosition>, Read<BlockNotifyCause>)>::query()
+            .iter(test.world.inner())
+            .map(|(pos, cause)| (pos.0, cause.0))
+            .collect();
+        assert_eq!(notifies, vec![(sand, cause)]);
+    }
+}
@@ -0,0 +1,700 @@
+//! The items dropped by blocks when they are broken.
+//!
+//! Many blocks only drop items when broken with the right tool,
+//! e.g. stone needs a pickaxe and diamond ore an iron pickaxe.
+//! Silk Touch makes some blocks drop themselves instead of their
+//! usual drops, Fortune increases the drops of ores and crops,
+//! and shears collect plants which otherwise drop nothing.
+
+use feather_core::blocks::{BlockId, BlockKind, HalfUpperLower, Part, SlabKind};
+use feather_core::items::{Enchantment, Item, ItemStack};
+use rand::Rng;
+use smallvec::{smallvec, SmallVec};
+
+/// A kind of tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolKind {
+    Pickaxe,
+    Axe,
+    Shovel,
+    Hoe,
+    Sword,
+    Shears,
+}
+
+/// Harvest level of wooden and golden tools.
+pub const HARVEST_LEVEL_WOOD: u8 = 0;
+/// Harvest level of stone tools.
+pub const HARVEST_LEVEL_STONE: u8 = 1;
+/// Harvest level of iron tools.
+pub const HARVEST_LEVEL_IRON: u8 = 2;
+/// Harvest level of diamond tools.
+pub const HARVEST_LEVEL_DIAMOND: u8 = 3;
+
+/// Returns the kind of tool an item is and its harvest level,
+/// or `None` if the item is not a tool.
+pub fn tool(item: Item) -> Option<(ToolKind, u8)> {
+    use Item::*;
+    use ToolKind::*;
+
+    let tool = match item {
+        WoodenPickaxe | GoldenPickaxe => (Pickaxe, HARVEST_LEVEL_WOOD),
+        StonePickaxe => (Pickaxe, HARVEST_LEVEL_STONE),
+        IronPickaxe => (Pickaxe, HARVEST_LEVEL_IRON),
+        DiamondPickaxe => (Pickaxe, HARVEST_LEVEL_DIAMOND),
+        WoodenAxe | GoldenAxe => (Axe, HARVEST_LEVEL_WOOD),
+        StoneAxe => (Axe, HARVEST_LEVEL_STONE),
+        IronAxe => (Axe, HARVEST_LEVEL_IRON),
+        DiamondAxe => (Axe, HARVEST_LEVEL_DIAMOND),
+        WoodenShovel | GoldenShovel => (Shovel, HARVEST_LEVEL_WOOD),
+        StoneShovel => (Shovel, HARVEST_LEVEL_STONE),
+        IronShovel => (Shovel, HARVEST_LEVEL_IRON),
+        DiamondShovel => (Shovel, HARVEST_LEVEL_DIAMOND),
+        WoodenHoe | GoldenHoe => (Hoe, HARVEST_LEVEL_WOOD),
+        StoneHoe => (Hoe, HARVEST_LEVEL_STONE),
+        IronHoe => (Hoe, HARVEST_LEVEL_IRON),
+        DiamondHoe => (Hoe, HARVEST_LEVEL_DIAMOND),
+        WoodenSword | GoldenSword => (Sword, HARVEST_LEVEL_WOOD),
+        StoneSword => (Sword, HARVEST_LEVEL_STONE),
+        IronSword => (Sword, HARVEST_LEVEL_IRON),
+        DiamondSword => (Sword, HARVEST_LEVEL_DIAMOND),
+        Item::Shears => (ToolKind::Shears, HARVEST_LEVEL_WOOD),
+        _ => return None,
+    };
+    Some(tool)
+}
+
+/// Returns the tool and minimum harvest level needed for a block
+/// to drop items, or `None` if the block drops items regardless
+/// of the tool.
+///
+/// Cobwebs are not included, since either a sword or shears
+/// will do; see `block_drops`.
+pub fn required_tool(block: BlockId) -> Option<(ToolKind, u8)> {
+    use BlockKind::*;
+
+    let level = match block.kind() {
+        IronOre | IronBlock | LapisOre | LapisBlock => HARVEST_LEVEL_STONE,
+        GoldOre | GoldBlock | DiamondOre | DiamondBlock | EmeraldOre | EmeraldBlock
+        | RedstoneOre => HARVEST_LEVEL_IRON,
+        Obsidian => HARVEST_LEVEL_DIAMOND,
+        Snow | SnowBlock => return Some((ToolKind::Shovel, HARVEST_LEVEL_WOOD)),
+        kind if is_stone_like(kind, block.identifier()) => HARVEST_LEVEL_WOOD,
+        _ => return None,
+    };
+    Some((ToolKind::Pickaxe, level))
+}
+
+/// Returns whether a block is made of stone or metal,
+/// so that it only drops items when mined with a pickaxe.
+fn is_stone_like(kind: BlockKind, identifier: &str) -> bool {
+    use BlockKind::*;
+
+    match kind {
+        Stone
+        | Granite
+        | PolishedGranite
+        | Diorite
+        | PolishedDiorite
+        | Andesite
+        | PolishedAndesite
+        | Cobblestone
+        | MossyCobblestone
+        | CobblestoneWall
+        | MossyCobblestoneWall
+        | CobblestoneStairs
+        | CobblestoneSlab
+        | StoneSlab
+        | SmoothStone
+        | Sandstone
+        | ChiseledSandstone
+        | CutSandstone
+        | SmoothSandstone
+        | SandstoneStairs
+        | SandstoneSlab
+        | RedSandstone
+        | ChiseledRedSandstone
+        | CutRedSandstone
+        | SmoothRedSandstone
+        | RedSandstoneStairs
+        | RedSandstoneSlab
+        | Bricks
+        | BrickStairs
+        | BrickSlab
+        | StoneBricks
+        | MossyStoneBricks
+        | CrackedStoneBricks
+        | ChiseledStoneBricks
+        | StoneBrickStairs
+        | StoneBrickSlab
+        | NetherBricks
+        | RedNetherBricks
+        | NetherBrickFence
+        | NetherBrickStairs
+        | NetherBrickSlab
+        | Netherrack
+        | NetherQuartzOre
+        | QuartzBlock
+        | ChiseledQuartzBlock
+        | QuartzPillar
+        | QuartzStairs
+        | QuartzSlab
+        | SmoothQuartz
+        | EndStone
+        | EndStoneBricks
+        | PurpurBlock
+        | PurpurPillar
+        | PurpurStairs
+        | PurpurSlab
+        | Prismarine
+        | PrismarineBricks
+        | DarkPrismarine
+        | PrismarineStairs
+        | PrismarineBrickStairs
+        | DarkPrismarineStairs
+        | PrismarineSlab
+        | PrismarineBrickSlab
+        | DarkPrismarineSlab
+        | PetrifiedOakSlab
+        | CoalOre
+        | CoalBlock
+        | RedstoneBlock
+        | MagmaBlock
+        | BoneBlock
+        | Spawner
+        | Furnace
+        | Dispenser
+        | Dropper
+        | Observer
+        | Hopper
+        | Cauldron
+        | BrewingStand
+        | EnchantingTable
+        | EnderChest
+        | Anvil
+        | ChippedAnvil
+        | DamagedAnvil
+        | IronBars
+        | IronDoor
+        | IronTrapdoor
+        | StonePressurePlate
+        | LightWeightedPressurePlate
+        | HeavyWeightedPressurePlate => true,
+        _ => {
+            identifier.ends_with("terracotta")
+                || identifier.ends_with("_concrete")
+                || identifier.ends_with("coral_block")
+        }
+    }
+}
+
+/// Returns whether a block drops items when broken with
+/// the given tool.
+pub fn can_harvest(block: BlockId, tool_stack: Option<&ItemStack>) -> bool {
+    let (required_kind, required_level) = match required_tool(block) {
+        Some(required) => required,
+        None => return true,
+    };
+
+    match tool_stack.and_then(|stack| tool(stack.ty)) {
+        Some((kind, level)) => kind == required_kind && level >= required_level,
+        None => false,
+    }
+}
+
+/// Returns the items dropped when a block is broken with the
+/// given tool, or with no tool (e.g. by losing its support).
+pub fn block_drops(
+    block: BlockId,
+    tool_stack: Option<&ItemStack>,
+    rng: &mut impl Rng,
+) -> SmallVec<[ItemStack; 2]> {
+    if !can_harvest(block, tool_stack) {
+        return SmallVec::new();
+    }
+
+    let silk_touch = tool_stack.map_or(false, |stack| {
+        stack.enchantment_level(Enchantment::SilkTouch) > 0
+    });
+    let fortune = tool_stack.map_or(0, |stack| stack.enchantment_level(Enchantment::Fortune));
+    let tool_kind = tool_stack
+        .and_then(|stack| tool(stack.ty))
+        .map(|(kind, _)| kind);
+
+    if is_secondary_part(block) {
+        return SmallVec::new();
+    }
+
+    if silk_touch && drops_self_with_silk_touch(block) {
+        return single(block_item(block), 1);
+    }
+    if tool_kind == Some(ToolKind::Shears) {
+        if let Some(drops) = shears_drops(block) {
+            return drops;
+        }
+    }
+
+    drops(block, tool_kind, fortune, rng)
+}
+
+/// Returns whether a block is the part of a multi-block
+/// structure which does not drop an item, e.g. the upper half
+/// of a door or the foot of a bed.
+fn is_secondary_part(block: BlockId) -> bool {
+    block.half_upper_lower() == Some(HalfUpperLower::Upper) || block.part() == Some(Part::Foot)
+}
+
+fn drops_self_with_silk_touch(block: BlockId) -> bool {
+    use BlockKind::*;
+
+    match block.kind() {
+        Stone | GrassBlock | Mycelium | Podzol | CoalOre | DiamondOre | EmeraldOre | LapisOre
+        | RedstoneOre | NetherQuartzOre | Glass | GlassPane | Ice | PackedIce | BlueIce
+        | Glowstone | SeaLantern | Melon | Bookshelf | Clay | SnowBlock | Cobweb | EnderChest
+        | TurtleEgg | BrownMushroomBlock | RedMushroomBlock | MushroomStem | OakLeaves
+        | SpruceLeaves | BirchLeaves | JungleLeaves | AcaciaLeaves | DarkOakLeaves
+        | TubeCoralBlock | BrainCoralBlock | BubbleCoralBlock | FireCoralBlock | HornCoralBlock => {
+            true
+        }
+        // Stained glass and panes
+        _ => block.identifier().contains("stained_glass"),
+    }
+}
+
+/// Returns the drops of blocks which are collected with
+/// shears, or `None` if shears do not affect the block.
+fn shears_drops(block: BlockId) -> Option<SmallVec<[ItemStack; 2]>> {
+    use BlockKind::*;
+
+    let drops = match block.kind() {
+        OakLeaves | SpruceLeaves | BirchLeaves | JungleLeaves | AcaciaLeaves | DarkOakLeaves
+        | Grass | Fern | DeadBush | Vine | Cobweb | Seagrass => single(block_item(block), 1),
+        TallGrass => single(Some(Item::Grass), 2),
+        LargeFern => single(Some(Item::Fern), 2),
+        TallSeagrass => single(Some(Item::Seagrass), 2),
+        Tripwire => single(Some(Item::String), 1),
+        _ => return None,
+    };
+    Some(drops)
+}
+
+/// Returns the drops of a block broken without Silk Touch or shears.
+fn drops(
+    block: BlockId,
+    tool_kind: Option<ToolKind>,
+    fortune: u32,
+    rng: &mut impl Rng,
+) -> SmallVec<[ItemStack; 2]> {
+    use BlockKind::*;
+
+    match block.kind() {
+        Air
+        | CaveAir
+        | VoidAir
+        | Water
+        | Lava
+        | BubbleColumn
+        | Fire
+        | NetherPortal
+        | EndPortal
+        | EndGateway
+        | MovingPiston
+        | PistonHead
+        | Bedrock
+        | Barrier
+        | EndPortalFrame
+        | CommandBlock
+        | ChainCommandBlock
+        | RepeatingCommandBlock
+        | StructureBlock
+        | StructureVoid
+        | Spawner
+        | FrostedIce
+        | Cake
+        | InfestedStone
+        | InfestedCobblestone
+        | InfestedStoneBricks
+        | InfestedMossyStoneBricks
+        | InfestedCrackedStoneBricks
+        | InfestedChiseledStoneBricks
+        | Glass
+        | GlassPane
+        | Ice
+        | PackedIce
+        | BlueIce
+        | TurtleEgg
+        | AttachedPumpkinStem
+        | AttachedMelonStem
+        | Seagrass
+        | TallSeagrass
+        | Vine
+        | Tripwire
+        | MushroomStem => SmallVec::new(),
+        _ if block.identifier().contains("stained_glass") => SmallVec::new(),
+
+        Stone => single(Some(Item::Cobblestone), 1),
+        GrassBlock | Mycelium | Podzol | GrassPath | Farmland => single(Some(Item::Dirt), 1),
+        Gravel => {
+            let chance = match fortune {
+                0 => 0.1,
+                1 => 1.0 / 7.0,
+                2 => 0.25,
+                _ => 1.0,
+            };
+            if rng.gen_bool(chance) {
+                single(Some(Item::Flint), 1)
+            } else {
+                single(Some(Item::Gravel), 1)
+            }
+        }
+
+        CoalOre => single(Some(Item::Coal), ore_bonus(1, fortune, rng)),
+        DiamondOre => single(Some(Item::Diamond), ore_bonus(1, fortune, rng)),
+        EmeraldOre => single(Some(Item::Emerald), ore_bonus(1, fortune, rng)),
+        NetherQuartzOre => single(Some(Item::Quartz), ore_bonus(1, fortune, rng)),
+        LapisOre => {
+            let count = rng.gen_range(4, 10);
+            single(Some(Item::LapisLazuli), ore_bonus(count, fortune, rng))
+        }
+        RedstoneOre => {
+            let count = rng.gen_range(4, 6) + rng.gen_range(0, fortune + 1);
+            single(Some(Item::Redstone), count)
+        }
+
+        Glowstone => {
+            let count = rng.gen_range(2, 5) + rng.gen_range(0, fortune + 1);
+            single(Some(Item::GlowstoneDust), count.min(4))
+        }
+        Melon => {
+            let count = rng.gen_range(3, 8) + rng.gen_range(0, fortune + 1);
+            single(Some(Item::MelonSlice), count.min(9))
+        }
+        SeaLantern => {
+            let count = rng.gen_range(2, 4) + rng.gen_range(0, fortune + 1);
+            single(Some(Item::PrismarineCrystals), count.min(5))
+        }
+        Bookshelf => single(Some(Item::Book), 3),
+        Clay => single(Some(Item::ClayBall), 4),
+        SnowBlock => single(Some(Item::Snowball), 4),
+        Snow => single(Some(Item::Snowball), block.layers().unwrap_or(1) as u32),
+        EnderChest => single(Some(Item::Obsidian), 8),
+        Cobweb if tool_kind == Some(ToolKind::Sword) => single(Some(Item::String), 1),
+        Cobweb => SmallVec::new(),
+
+        OakLeaves | SpruceLeaves | BirchLeaves | JungleLeaves | AcaciaLeaves | DarkOakLeaves => {
+            leaves_drops(block.kind(), fortune, rng)
+        }
+        Grass | Fern | TallGrass | LargeFern => {
+            if rng.gen_bool(0.125) {
+                single(Some(Item::WheatSeeds), 1)
+            } else {
+                SmallVec::new()
+            }
+        }
+        DeadBush => single(Some(Item::Stick), rng.gen_range(0, 3)),
+        BrownMushroomBlock => single(Some(Item::BrownMushroom), mushroom_count(rng)),
+        RedMushroomBlock => single(Some(Item::RedMushroom), mushroom_count(rng)),
+        ChorusPlant => single(Some(Item::ChorusFruit), rng.gen_range(0, 2)),
+
+        Wheat => crop_drops(block, 7, Item::Wheat, Item::WheatSeeds, fortune, rng),
+        Beetroots => crop_drops(block, 3, Item::Beetroot, Item::BeetrootSeeds, fortune, rng),
+        Carrots => crop_drops(block, 7, Item::Carrot, Item::Carrot, fortune, rng),
+        Potatoes => {
+            let mut drops = crop_drops(block, 7, Item::Potato, Item::Potato, fortune, rng);
+            if block.age_0_7() == Some(7) && rng.gen_bool(0.02) {
+                drops.push(ItemStack::new(Item::PoisonousPotato, 1));
+            }
+            drops
+        }
+        NetherWart => {
+            let count = if block.age_0_3() == Some(3) {
+                rng.gen_range(2, 5) + rng.gen_range(0, fortune + 1)
+            } else {
+                1
+            };
+            single(Some(Item::NetherWart), count)
+        }
+        Cocoa => {
+            let count = if block.age_0_2() == Some(2) { 3 } else { 1 };
+            single(Some(Item::CocoaBeans), count)
+        }
+        PumpkinStem | MelonStem => {
+            let seeds = if block.kind() == PumpkinStem {
+                Item::PumpkinSeeds
+            } else {
+                Item::MelonSeeds
+            };
+            let age = block.age_0_7().unwrap_or(0);
+            let p = (age + 1) as f64 / 15.0;
+            single(Some(seeds), binomial(3, p, rng))
+        }
+
+        SeaPickle => single(block_item(block), block.pickles().unwrap_or(1) as u32),
+        _ if block.slab_kind() == Some(SlabKind::Double) => single(block_item(block), 2),
+        _ if block.identifier().starts_with("minecraft:potted_") => {
+            let mut drops = single(Some(Item::FlowerPot), 1);
+            let plant = block.identifier().replace("potted_", "");
+            if let Some(plant) = Item::from_identifier(&plant) {
+                drops.push(ItemStack::new(plant, 1));
+            }
+            drops
+        }
+        _ => single(block_item(block), 1),
+    }
+}
+
+/// Returns the item which places a block, which is what most
+/// blocks drop.
+fn block_item(block: BlockId) -> Option<Item> {
+    match block.kind() {
+        BlockKind::WallTorch => Some(Item::Torch),
+        BlockKind::WallSign => Some(Item::Sign),
+        BlockKind::RedstoneWire => Some(Item::Redstone),
+        BlockKind::KelpPlant => Some(Item::Kelp),
+        // e.g. "minecraft:red_wall_banner" drops "minecraft:red_banner"
+        _ => Item::from_identifier(&block.identifier().replace("_wall_", "_")),
+    }
+}
+
+fn single(item: Option<Item>, count: u32) -> SmallVec<[ItemStack; 2]> {
+    match item {
+        Some(item) if count > 0 => smallvec![ItemStack::new(item, count.min(64) as u8)],
+        _ => SmallVec::new(),
+    }
+}
+
+/// Multiplies the drops of an ore by a random factor
+/// which increases with the Fortune level.
+fn ore_bonus(count: u32, fortune: u32, rng: &mut impl Rng) -> u32 {
+    if fortune == 0 {
+        return count;
+    }
+    let multiplier = (rng.gen_range(0, fortune + 2) as i32 - 1).max(0) as u32 + 1;
+    count * multiplier
+}
+
+/// Returns the number of successes in `n` trials
+/// with probability `p`.
+fn binomial(n: u32, p: f64, rng: &mut impl Rng) -> u32 {
+    (0..n).filter(|_| rng.gen_bool(p)).count() as u32
+}
+
+fn mushroom_count(rng: &mut impl Rng) -> u32 {
+    rng.gen_range(-6, 3).max(0) as u32
+}
+
+/// Returns the drops of a crop: the crop and extra seeds when
+/// it is fully grown, or a single seed otherwise. Crops which
+/// are their own seeds, such as carrots, drop at least one.
+fn crop_drops(
+    block: BlockId,
+    max_age: i32,
+    crop: Item,
+    seeds: Item,
+    fortune: u32,
+    rng: &mut impl Rng,
+) -> SmallVec<[ItemStack; 2]> {
+    let age = block.age_0_7().or_else(|| block.age_0_3()).unwrap_or(0);
+    if age < max_age {
+        return single(Some(seeds), 1);
+    }
+
+    let extra = binomial(3 + fortune, 0.5714286, rng);
+    if crop == seeds {
+        single(Some(crop), 1 + extra)
+    } else {
+        let mut drops = single(Some(crop), 1);
+        drops.extend(single(Some(seeds), extra));
+        drops
+    }
+}
+
+fn leaves_drops(kind: BlockKind, fortune: u32, rng: &mut impl Rng) -> SmallVec<[ItemStack; 2]> {
+    let sapling = match kind {
+        BlockKind::OakLeaves => Item::OakSapling,
+        BlockKind::SpruceLeaves => Item::SpruceSapling,
+        BlockKind::BirchLeaves => Item::BirchSapling,
+        BlockKind::JungleLeaves => Item::JungleSapling,
+        BlockKind::AcaciaLeaves => Item::AcaciaSapling,
+        _ => Item::DarkOakSapling,
+    };
+    let sapling_chances: &[f64] = if kind == BlockKind::JungleLeaves {
+        &[1.0 / 40.0, 1.0 / 36.0, 1.0 / 32.0, 1.0 / 24.0, 1.0 / 10.0]
+    } else {
+        &[1.0 / 20.0, 1.0 / 16.0, 1.0 / 12.0, 1.0 / 10.0]
+    };
+    let chance = |chances: &[f64]| chances[(fortune as usize).min(chances.len() - 1)];
+
+    let mut drops = SmallVec::new();
+    if rng.gen_bool(chance(sapling_chances)) {
+        drops.push(ItemStack::new(sapling, 1));
+    }
+    if rng.gen_bool(chance(&[
+        1.0 / 50.0,
+        1.0 / 45.0,
+        1.0 / 40.0,
+        1.0 / 30.0,
+        1.0 / 10.0,
+    ])) {
+        drops.push(ItemStack::new(Item::Stick, rng.gen_range(1, 3)));
+    }
+    if (kind == BlockKind::OakLeaves || kind == BlockKind::DarkOakLeaves)
+        && rng.gen_bool(chance(&[
+            1.0 / 200.0,
+            1.0 / 180.0,
+            1.0 / 160.0,
+            1.0 / 120.0,
+            1.0 / 40.0,
+        ]))
+    {
+        drops.push(ItemStack::new(Item::Apple, 1));
+    }
+    drops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    fn enchanted(item: Item, enchantment: Enchantment) -> ItemStack {
+        let mut stack = ItemStack::new(item, 1);
+        stack.set_enchantment_level(enchantment, 1);
+        stack
+    }
+
+    fn items(drops: SmallVec<[ItemStack; 2]>) -> Vec<(Item, u8)> {
+        drops
+            .into_iter()
+            .map(|stack| (stack.ty, stack.amount))
+            .collect()
+    }
+
+    #[test]
+    fn tool_requirements() {
+        let mut rng = StepRng::new(0, 1);
+        let wooden = ItemStack::new(Item::WoodenPickaxe, 1);
+        let stone = ItemStack::new(Item::StonePickaxe, 1);
+        let iron = ItemStack::new(Item::IronPickaxe, 1);
+
+        assert!(block_drops(BlockId::stone(), None, &mut rng).is_empty());
+        assert_eq!(
+            items(block_drops(BlockId::stone(), Some(&wooden), &mut rng)),
+            vec![(Item::Cobblestone, 1)]
+        );
+        assert!(block_drops(BlockId::diamond_ore(), Some(&stone), &mut rng).is_empty());
+        assert_eq!(
+            items(block_drops(BlockId::diamond_ore(), Some(&iron), &mut rng)),
+            vec![(Item::Diamond, 1)]
+        );
+        assert!(block_drops(BlockId::iron_ore(), Some(&wooden), &mut rng).is_empty());
+        assert!(block_drops(
+            BlockId::stone(),
+            Some(&ItemStack::new(Item::IronShovel, 1)),
+            &mut rng
+        )
+        .is_empty());
+        assert_eq!(
+            items(block_drops(BlockId::dirt(), None, &mut rng)),
+            vec![(Item::Dirt, 1)]
+        );
+    }
+
+    #[test]
+    fn silk_touch() {
+        let mut rng = StepRng::new(0, 1);
+        let pickaxe = enchanted(Item::IronPickaxe, Enchantment::SilkTouch);
+
+        assert_eq!(
+            items(block_drops(BlockId::stone(), Some(&pickaxe), &mut rng)),
+            vec![(Item::Stone, 1)]
+        );
+        assert_eq!(
+            items(block_drops(
+                BlockId::diamond_ore(),
+                Some(&pickaxe),
+                &mut rng
+            )),
+            vec![(Item::DiamondOre, 1)]
+        );
+        assert_eq!(
+            items(block_drops(BlockId::glass(), Some(&pickaxe), &mut rng)),
+            vec![(Item::Glass, 1)]
+        );
+        assert!(block_drops(BlockId::glass(), None, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn fortune_never_reduces_drops() {
+        let mut rng = rand::thread_rng();
+        let mut pickaxe = ItemStack::new(Item::DiamondPickaxe, 1);
+        pickaxe.set_enchantment_level(Enchantment::Fortune, 3);
+
+        for _ in 0..100 {
+            let drops = items(block_drops(
+                BlockId::diamond_ore(),
+                Some(&pickaxe),
+                &mut rng,
+            ));
+            assert_eq!(drops.len(), 1);
+            assert!((1..=4).contains(&drops[0].1));
+        }
+    }
+
+    #[test]
+    fn shears() {
+        let mut rng = StepRng::new(u64::max_value(), 0);
+        let shears = ItemStack::new(Item::Shears, 1);
+
+        assert!(block_drops(BlockId::vine(), None, &mut rng).is_empty());
+        assert_eq!(
+            items(block_drops(BlockId::vine(), Some(&shears), &mut rng)),
+            vec![(Item::Vine, 1)]
+        );
+        assert_eq!(
+            items(block_drops(BlockId::tall_grass(), Some(&shears), &mut rng)),
+            vec![(Item::Grass, 2)]
+        );
+        assert_eq!(
+            items(block_drops(BlockId::cobweb(), Some(&shears), &mut rng)),
+            vec![(Item::Cobweb, 1)]
+        );
+        assert_eq!(
+            items(block_drops(
+                BlockId::cobweb(),
+                Some(&ItemStack::new(Item::IronSword, 1)),
+                &mut rng
+            )),
+            vec![(Item::String, 1)]
+        );
+    }
+
+    #[test]
+    fn supported_block_drops() {
+        let mut rng = StepRng::new(u64::max_value(), 0);
+
+        assert_eq!(
+            items(block_drops(BlockId::wall_torch(), None, &mut rng)),
+            vec![(Item::Torch, 1)]
+        );
+        assert_eq!(
+            items(block_drops(BlockId::rail(), None, &mut rng)),
+            vec![(Item::Rail, 1)]
+        );
+        assert!(block_drops(BlockId::grass(), None, &mut rng).is_empty());
+        assert!(block_drops(
+            BlockId::oak_door().with_half_upper_lower(HalfUpperLower::Upper),
+            None,
+            &mut rng
+        )
+        .is_empty());
+        assert_eq!(
+            items(block_drops(BlockId::oak_door(), None, &mut rng)),
+            vec![(Item::OakDoor, 1)]
+        );
+        assert_eq!(
+            items(block_drops(BlockId::wheat(), None, &mut rng)),
+            vec![(Item::WheatSeeds, 1)]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Rules for blocks which need support from an adjacent
+//! block, such as torches, rails, signs, crops, and doors.
+//!
+//! When a neighboring block is updated, blocks which are no
+//! longer supported are broken and drop their items.
+
+use feather_core::blocks::{BlockId, BlockKind, Face, FacingCardinal, HalfUpperLower};
+use feather_core::util::BlockPosition;
+
+/// How a block is held in place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Support {
+    /// The block requires a solid block below it.
+    SolidBelow,
+    /// The block requires grass, dirt, or farmland below it.
+    Soil,
+    /// The block requires farmland below it (crops).
+    Farmland,
+    /// The block requires soul sand below it.
+    SoulSand,
+    /// The block requires water below it.
+    Water,
+    /// The block requires sand below it or another block of the same kind.
+    Cactus,
+    /// The block requires soil or sand below it or another block of the same kind.
+    SugarCane,
+    /// The block hangs on the solid block behind it, opposite
+    /// to the direction it faces.
+    Wall,
+    /// The block is attached to the floor, a wall, or the ceiling
+    /// according to its `face` property (levers and buttons).
+    Face,
+    /// The block is one half of a two-block-tall block. Each half
+    /// requires the other; the lower half also requires solid
+    /// ground (doors) or soil (tall plants).
+    DoubleBlock,
+}
+
+/// Returns how the given block is supported, or `None`
+/// if the block does not need support.
+pub fn support(block: BlockId) -> Option<Support> {
+    use BlockKind::*;
+
+    let support = match block.kind() {
+        Torch
+        | RedstoneTorch
+        | Sign
+        | Rail
+        | PoweredRail
+        | DetectorRail
+        | ActivatorRail
+        | RedstoneWire
+        | Repeater
+        | Comparator
+        | StonePressurePlate
+        | OakPressurePlate
+        | SprucePressurePlate
+        | BirchPressurePlate
+        | JunglePressurePlate
+        | AcaciaPressurePlate
+        | DarkOakPressurePlate
+        | LightWeightedPressurePlate
+        | HeavyWeightedPressurePlate
+        | Snow
+        | FlowerPot
+        | WhiteCarpet
+        | OrangeCarpet
+        | MagentaCarpet
+        | LightBlueCarpet
+        | YellowCarpet
+        | LimeCarpet
+        | PinkCarpet
+        | GrayCarpet
+        | LightGrayCarpet
+        | CyanCarpet
+        | PurpleCarpet
+        | BlueCarpet
+        | BrownCarpet
+        | GreenCarpet
+        | RedCarpet
+        | BlackCarpet
+        | WhiteBanner
+        | OrangeBanner
+        | MagentaBanner
+        | LightBlueBanner
+        | YellowBanner
+        | LimeBanner
+        | PinkBanner
+        | GrayBanner
+        | LightGrayBanner
+        | CyanBanner
+        | PurpleBanner
+        | BlueBanner
+        | BrownBanner
+        | GreenBanner
+        | RedBanner
+        | BlackBanner
+        | Seagrass => Support::SolidBelow,
+        OakSapling | SpruceSapling | BirchSapling | JungleSapling | AcaciaSapling
+        | DarkOakSapling | Grass | Fern | DeadBush | Dandelion | Poppy | BlueOrchid | Allium
+        | AzureBluet | RedTulip | OrangeTulip | WhiteTulip | PinkTulip | OxeyeDaisy
+        | BrownMushroom | RedMushroom => Support::Soil,
+        Wheat | Carrots | Potatoes | Beetroots | MelonStem | PumpkinStem => Support::Farmland,
+        NetherWart => Support::SoulSand,
+        LilyPad => Support::Water,
+        BlockKind::Cactus => Support::Cactus,
+        BlockKind::SugarCane => Support::SugarCane,
+        WallTorch | RedstoneWallTorch | WallSign | Ladder | TripwireHook | WhiteWallBanner
+        | OrangeWallBanner | MagentaWallBanner | LightBlueWallBanner | YellowWallBanner
+        | LimeWallBanner | PinkWallBanner | GrayWallBanner | LightGrayWallBanner
+        | CyanWallBanner | PurpleWallBanner | BlueWallBanner | BrownWallBanner
+        | GreenWallBanner | RedWallBanner | BlackWallBanner => Support::Wall,
+        Lever | StoneButton | OakButton | SpruceButton | BirchButton | JungleButton
+        | AcaciaButton | DarkOakButton => Support::Face,
+        OakDoor | IronDoor | SpruceDoor | BirchDoor | JungleDoor | AcaciaDoor | DarkOakDoor
+        | Sunflower | Lilac | RoseBush | Peony | TallGrass | LargeFern | TallSeagrass => {
+            Support::DoubleBlock
+        }
+        _ => return None,
+    };
+    Some(support)
+}
+
+/// Returns whether the block at `pos` is supported by its neighbors.
+///
+/// Neighbors in unloaded chunks are assumed to support the block,
+/// so that blocks at chunk borders are not broken.
+pub fn is_supported(
+    block: BlockId,
+    pos: BlockPosition,
+    block_at: impl Fn(BlockPosition) -> Option<BlockId>,
+) -> bool {
+    let support = match support(block) {
+        Some(support) => support,
+        None => return true,
+    };
+
+    let below = pos + BlockPosition::new(0, -1, 0);
+    let above = pos + BlockPosition::new(0, 1, 0);
+    let check = |pos: BlockPosition, f: &dyn Fn(BlockId) -> bool| block_at(pos).map_or(true, f);
+
+    match support {
+        Support::SolidBelow => check(below, &|b| b.is_solid()),
+        Support::Soil => check(below, &is_soil),
+        Support::Farmland => check(below, &|b| b.kind() == BlockKind::Farmland),
+        Support::SoulSand => check(below, &|b| b.kind() == BlockKind::SoulSand),
+        Support::Water => check(below, &|b| b.kind() == BlockKind::Water),
+        Support::Cactus => check(below, &|b| match b.kind() {
+            BlockKind::Cactus | BlockKind::Sand | BlockKind::RedSand => true,
+            _ => false,
+        }),
+        Support::SugarCane => check(below, &|b| match b.kind() {
+            BlockKind::SugarCane | BlockKind::Sand | BlockKind::RedSand => true,
+            _ => is_soil(b),
+        }),
+        Support::Wall => match block.facing_cardinal() {
+            Some(facing) => check(pos - facing_offset(facing), &|b| b.is_solid()),
+            None => true,
+        },
+        Support::Face => {
+            let attached_to = match (block.face(), block.facing_cardinal()) {
+                (Some(Face::Floor), _) => below,
+                (Some(Face::Ceiling), _) => above,
+                (Some(Face::Wall), Some(facing)) => pos - facing_offset(facing),
+                _ => return true,
+            };
+            check(attached_to, &|b| b.is_solid())
+        }
+        Support::DoubleBlock => {
+            let same_kind = |b: BlockId| b.kind() == block.kind();
+            match block.half_upper_lower() {
+                Some(HalfUpperLower::Upper) => check(below, &same_kind),
+                Some(HalfUpperLower::Lower) => {
+                    let ground: &dyn Fn(BlockId) -> bool = match block.kind() {
+                        BlockKind::TallSeagrass => &|b| b.is_solid(),
+                        kind if is_door(kind) => &|b| b.is_solid(),
+                        _ => &is_soil,
+                    };
+                    check(above, &same_kind) && check(below, ground)
+                }
+                None => true,
+            }
+        }
+    }
+}
+
+fn is_soil(block: BlockId) -> bool {
+    match block.kind() {
+        BlockKind::GrassBlock
+        | BlockKind::Dirt
+        | BlockKind::CoarseDirt
+        | BlockKind::Podzol
+        | BlockKind::Farmland => true,
+        _ => false,
+    }
+}
+
+fn is_door(kind: BlockKind) -> bool {
+    match kind {
+        BlockKind::OakDoor
+        | BlockKind::IronDoor
+        | BlockKind::SpruceDoor
+        | BlockKind::BirchDoor
+        | BlockKind::JungleDoor
+        | BlockKind::AcaciaDoor
+        | BlockKind::DarkOakDoor => true,
+        _ => false,
+    }
+}
+
+/// Returns the offset of one block in the direction the given facing points to.
+pub fn facing_offset(facing: FacingCardinal) -> BlockPosition {
+    match facing {
+        FacingCardinal::North => BlockPosition::new(0, 0, -1),
+        FacingCardinal::South => BlockPosition::new(0, 0, 1),
+        FacingCardinal::West => BlockPosition::new(-1, 0, 0),
+        FacingCardinal::East => BlockPosition::new(1, 0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn world(blocks: &[(BlockPosition, BlockId)]) -> impl Fn(BlockPosition) -> Option<BlockId> {
+        let blocks: HashMap<_, _> = blocks.iter().copied().collect();
+        move |pos| Some(blocks.get(&pos).copied().unwrap_or_else(BlockId::air))
+    }
+
+    #[test]
+    fn torch_needs_block_below() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let below = BlockPosition::new(0, 63, 0);
+
+        let supported = world(&[(below, BlockId::stone())]);
+        assert!(is_supported(BlockId::torch(), pos, supported));
+
+        let unsupported = world(&[]);
+        assert!(!is_supported(BlockId::torch(), pos, unsupported));
+    }
+
+    #[test]
+    fn wall_torch_needs_block_behind() {
+        let pos = BlockPosition::new(0, 64, 0);
+        // a torch facing north hangs on the block to its south
+        let torch = BlockId::wall_torch().with_facing_cardinal(FacingCardinal::North);
+
+        let supported = world(&[(BlockPosition::new(0, 64, 1), BlockId::stone())]);
+        assert!(is_supported(torch, pos, supported));
+
+        let wrong_side = world(&[(BlockPosition::new(0, 64, -1), BlockId::stone())]);
+        assert!(!is_supported(torch, pos, wrong_side));
+    }
+
+    #[test]
+    fn crops_need_farmland() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let below = BlockPosition::new(0, 63, 0);
+
+        assert!(is_supported(
+            BlockId::wheat(),
+            pos,
+            world(&[(below, BlockId::farmland())])
+        ));
+        assert!(!is_supported(
+            BlockId::wheat(),
+            pos,
+            world(&[(below, BlockId::dirt())])
+        ));
+    }
+
+    #[test]
+    fn door_halves_support_each_other() {
+        let lower_pos = BlockPosition::new(0, 64, 0);
+        let upper_pos = BlockPosition::new(0, 65, 0);
+        let lower = BlockId::oak_door().with_half_upper_lower(HalfUpperLower::Lower);
+        let upper = BlockId::oak_door().with_half_upper_lower(HalfUpperLower::Upper);
+
+        let complete = world(&[
+            (BlockPosition::new(0, 63, 0), BlockId::stone()),
+            (lower_pos, lower),
+            (upper_pos, upper),
+        ]);
+        assert!(is_supported(lower, lower_pos, &complete));
+        assert!(is_supported(upper, upper_pos, &complete));
+
+        let no_ground = world(&[(lower_pos, lower), (upper_pos, upper)]);
+        assert!(!is_supported(lower, lower_pos, &no_ground));
+
+        let no_lower = world(&[(upper_pos, upper)]);
+        assert!(!is_supported(upper, upper_pos, no_lower));
+    }
+
+    #[test]
+    fn unloaded_neighbors_support() {
+        let pos = BlockPosition::new(0, 64, 0);
+        assert!(is_supported(BlockId::torch(), pos, |_| None));
+    }
+}
@@ -0,0 +1,109 @@
+//! Entities being on fire, which damages them once a second.
+
+use crate::{armor_enchantment, has_invulnerable_gamemode};
+use feather_core::entitymeta::{EntityBitMask, EntityMetadata};
+use feather_core::items::Enchantment;
+use feather_core::util::Position;
+use feather_server_types::{BumpVec, Burning, DamageCause, EntityDamageRequest, Game, TPS};
+use fecs::{Entity, IntoQuery, Read, World, Write};
+
+/// Damage dealt each second to burning entities.
+pub const BURNING_DAMAGE: f32 = 1.0;
+
+/// Returns the number of ticks for which an entity burns when
+/// set on fire for `ticks`, given the level of Fire Protection
+/// on its armor.
+pub fn burn_ticks(ticks: u32, fire_protection: u32) -> u32 {
+    let reduction = (ticks as f32 * 0.15 * fire_protection as f32) as u32;
+    ticks.saturating_sub(reduction)
+}
+
+/// Sets an entity on fire for at least the given number of ticks.
+pub fn set_on_fire(world: &mut World, entity: Entity, ticks: u32) {
+    if has_invulnerable_gamemode(world, entity) {
+        return;
+    }
+
+    let ticks = burn_ticks(
+        ticks,
+        armor_enchantment(world, entity, Enchantment::FireProtection),
+    );
+    if ticks == 0 {
+        return;
+    }
+
+    if world.has::<Burning>(entity) {
+        let mut burning = world.get_mut::<Burning>(entity);
+        burning.0 = burning.0.max(ticks);
+    } else {
+        world.add(entity, Burning(ticks)).unwrap();
+        if world.has::<EntityMetadata>(entity) {
+            world
+                .get_mut::<EntityMetadata>(entity)
+                .set_flag(EntityBitMask::ON_FIRE, true);
+        }
+    }
+}
+
+/// Puts out an entity which is on fire.
+pub fn extinguish(world: &mut World, entity: Entity) {
+    if world.remove::<Burning>(entity).is_ok() && world.has::<EntityMetadata>(entity) {
+        world
+            .get_mut::<EntityMetadata>(entity)
+            .set_flag(EntityBitMask::ON_FIRE, false);
+    }
+}
+
+/// System which damages burning entities and puts
+/// them out once they have burned long enough or
+/// have entered water.
+#[fecs::system]
+pub fn update_burning(game: &mut Game, world: &mut World) {
+    let mut burned = BumpVec::new_in(game.bump());
+    let mut extinguished = BumpVec::new_in(game.bump());
+
+    for (entity, (pos, burning)) in
+        <(Read<Position>, Read<Burning>)>::query().iter_entities(world.inner())
+    {
+        let in_water = game
+            .block_at(pos.block())
+            .map_or(false, |block| block.is_water());
+        if in_water || burning.0 <= 1 {
+            extinguished.push(entity);
+        }
+        if !in_water && burning.0 % TPS as u32 == 0 {
+            burned.push(entity);
+        }
+    }
+
+    for (_, mut burning) in <Write<Burning>>::query().iter_entities_mut(world.inner_mut()) {
+        burning.0 = burning.0.saturating_sub(1);
+    }
+
+    for entity in extinguished {
+        extinguish(world, entity);
+    }
+
+    for entity in burned {
+        game.handle(
+            world,
+            EntityDamageRequest {
+                entity,
+                damage: BURNING_DAMAGE,
+                cause: DamageCause::Burning,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fire_protection_shortens_burning() {
+        assert_eq!(burn_ticks(100, 0), 100);
+        assert_eq!(burn_ticks(100, 4), 40);
+        assert_eq!(burn_ticks(100, 10), 0);
+    }
+}
@@ -7,21 +7,8 @@ use itertools::Itertools;
 #[fecs::event_handler]
 pub fn on_chunk_cross_update_chunk_entities(event: &ChunkCrossEvent, game: &mut Game) {
     if let Some(old) = event.old {
-        if let Some(vec) = game.chunk_entities.0.get_mut(&old) {
-            let index = vec
-                .iter()
-                .find_position(|e| **e == event.entity)
-                .map(|(index, _)| index);
-            if let Some(index) = index {
-                vec.swap_remove(index);
-            }
-        }
-
         game.chunk_entities
-            .0
-            .entry(event.new)
-            .or_default()
-            .push(event.entity);
+            .move_entity(event.entity, old, event.new);
     }
 }
 
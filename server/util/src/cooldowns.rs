@@ -0,0 +1,46 @@
+//! Starting and checking the item cooldowns of entities.
+
+use feather_core::items::Item;
+use feather_core::network::packets::SetCooldown;
+use feather_server_types::{Game, ItemCooldowns, Network};
+use fecs::{Entity, World};
+
+/// Prevents an entity from using an item for the given number
+/// of ticks. Players are sent the cooldown so that their client
+/// greys out the item.
+pub fn start_item_cooldown(game: &Game, world: &mut World, entity: Entity, item: Item, ticks: u64) {
+    if !world.has::<ItemCooldowns>(entity) {
+        world.add(entity, ItemCooldowns::default()).unwrap();
+    }
+    world
+        .get_mut::<ItemCooldowns>(entity)
+        .start(item, game.tick_count, game.tick_count + ticks);
+
+    send_cooldown(world, entity, item, ticks);
+}
+
+/// Ends an entity's cooldown on an item, if it has one.
+pub fn clear_item_cooldown(world: &mut World, entity: Entity, item: Item) {
+    if world.has::<ItemCooldowns>(entity) {
+        world.get_mut::<ItemCooldowns>(entity).clear(item);
+        send_cooldown(world, entity, item, 0);
+    }
+}
+
+/// Returns whether an entity is waiting to use an item again.
+pub fn has_item_cooldown(game: &Game, world: &World, entity: Entity, item: Item) -> bool {
+    world
+        .try_get::<ItemCooldowns>(entity)
+        .map_or(false, |cooldowns| {
+            cooldowns.is_cooling_down(item, game.tick_count)
+        })
+}
+
+fn send_cooldown(world: &World, entity: Entity, item: Item, ticks: u64) {
+    if let Some(network) = world.try_get::<Network>(entity) {
+        network.send(SetCooldown {
+            item_id: item.native_protocol_id(),
+            cooldown_ticks: ticks as i32,
+        });
+    }
+}
@@ -0,0 +1,595 @@
+//! Damage calculation: shields, invulnerability after being hit,
+//! armor, the Resistance effect, and protection enchantments.
+//!
+//! The formulas match vanilla; see
+//! https://minecraft.gamepedia.com/Armor#Damage_protection.
+
+use crate::{
+    apply_thorns, death_drops, difficulty_scaled_damage, disable_shield, disables_shields,
+    is_blocking, is_difficulty_scaled, is_invulnerable, knock_back, send_health, spawn_death_drops,
+    use_totem, wear_armor, wear_shield, worn_armor, ATTACK_KNOCKBACK,
+};
+use feather_core::inventory::{Inventory, SLOT_HOTBAR_OFFSET};
+use feather_core::items::{Enchantment, Item};
+use feather_core::network::packets::EntityStatus;
+use feather_core::util::{Gamemode, Position};
+use feather_server_types::{
+    attribute_value, held_item_modifiers, ActiveEffects, AttackCooldown, Attribute,
+    AttributeModifier, Attributes, BlockingWithShield, DamageCause, DamageOutcome,
+    EntityDamageEvent, EntityDamageRequest, EntityDeathEvent, EntityId, Game, Health, HeldItem,
+    LastDamage, Player, StatusEffect, INVULNERABILITY_TICKS, TPS,
+};
+use fecs::{Entity, World};
+use nalgebra_glm::vec2;
+use parking_lot::Mutex;
+use smallvec::SmallVec;
+use std::sync::Arc;
+
+/// Entity status indicating that an entity was hurt.
+const STATUS_HURT: i8 = 2;
+/// Entity status indicating that an entity died.
+const STATUS_DEATH: i8 = 3;
+/// Entity status indicating that a shield blocked damage.
+const STATUS_SHIELD_BLOCK: i8 = 29;
+
+/// A protection enchantment on a piece of armor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProtectionEnchantment {
+    Protection,
+    FireProtection,
+    BlastProtection,
+    ProjectileProtection,
+    FeatherFalling,
+}
+
+/// Computes the enchantment protection factor of a set of
+/// protection enchantments against damage with the given cause.
+pub fn protection_factor(
+    enchantments: impl IntoIterator<Item = (ProtectionEnchantment, u32)>,
+    cause: &DamageCause,
+) -> u32 {
+    if !cause.reduced_by_protection() {
+        return 0;
+    }
+
+    enchantments
+        .into_iter()
+        .map(|(enchantment, level)| match enchantment {
+            ProtectionEnchantment::Protection => level,
+            ProtectionEnchantment::FireProtection if cause.is_fire() => level * 2,
+            ProtectionEnchantment::BlastProtection if cause.is_explosion() => level * 2,
+            ProtectionEnchantment::ProjectileProtection if cause.is_projectile() => level * 2,
+            ProtectionEnchantment::FeatherFalling if *cause == DamageCause::Fall => level * 3,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Applies the reduction from armor and armor toughness.
+pub fn armor_reduction(damage: f32, armor: f32, toughness: f32) -> f32 {
+    let effective = (armor - damage / (2.0 + toughness / 4.0))
+        .max(armor * 0.2)
+        .min(20.0);
+    damage * (1.0 - effective / 25.0)
+}
+
+/// Applies the reduction from a Resistance effect with the given amplifier.
+pub fn resistance_reduction(damage: f32, amplifier: u8) -> f32 {
+    let reduction = ((u32::from(amplifier) + 1) * 5).min(25);
+    damage * (25 - reduction) as f32 / 25.0
+}
+
+/// Applies the reduction from an enchantment protection factor.
+pub fn protection_reduction(damage: f32, protection_factor: u32) -> f32 {
+    damage * (1.0 - protection_factor.min(20) as f32 / 25.0)
+}
+
+/// Returns whether an entity at `pos`, blocking with a shield,
+/// is facing damage coming from `source`.
+pub fn faces_damage(pos: Position, source: Position) -> bool {
+    let yaw = f64::from(pos.yaw.to_radians());
+    let look = vec2(-yaw.sin(), yaw.cos());
+    let to_source = vec2(source.x - pos.x, source.z - pos.z);
+    look.dot(&to_source) > 0.0
+}
+
+/// Returns the position from which damage originated, if any.
+fn damage_source_position(world: &World, cause: &DamageCause) -> Option<Position> {
+    match cause {
+        DamageCause::EntityAttack(attacker) => world.try_get::<Position>(*attacker).map(|p| *p),
+        DamageCause::Projectile { projectile, .. } => {
+            world.try_get::<Position>(*projectile).map(|p| *p)
+        }
+        DamageCause::Explosion { pos, .. } => Some(*pos),
+        _ => None,
+    }
+}
+
+/// Returns the type of the item held in an entity's main hand.
+fn held_item(world: &World, entity: Entity) -> Option<Item> {
+    let inventory = world.try_get::<Inventory>(entity)?;
+    let held = world.try_get::<HeldItem>(entity)?;
+    inventory
+        .item_at(SLOT_HOTBAR_OFFSET + held.0)
+        .map(|stack| stack.ty)
+}
+
+/// Returns the value of an attribute with the
+/// modifiers of an entity's held item applied.
+fn attribute_value_with_held_item(world: &World, entity: Entity, attribute: Attribute) -> f64 {
+    let modifiers: SmallVec<[AttributeModifier; 2]> = held_item(world, entity)
+        .map(held_item_modifiers)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(a, _)| *a == attribute)
+        .map(|(_, modifier)| modifier)
+        .collect();
+
+    match world.try_get::<Attributes>(entity) {
+        Some(attributes) => attributes.value_with(attribute, &modifiers),
+        None => Attributes::new().value_with(attribute, &modifiers),
+    }
+}
+
+/// Returns the melee attack damage of an entity at full strength,
+/// based on its `generic.attackDamage` attribute, its held item,
+/// and its Strength and Weakness effects.
+pub fn attack_damage(world: &World, attacker: Entity) -> f32 {
+    let mut damage = attribute_value_with_held_item(world, attacker, Attribute::AttackDamage);
+
+    if let Some(effects) = world.try_get::<ActiveEffects>(attacker) {
+        if let Some(amplifier) = effects.amplifier(StatusEffect::Strength) {
+            damage += 3.0 * f64::from(amplifier + 1);
+        }
+        if let Some(amplifier) = effects.amplifier(StatusEffect::Weakness) {
+            damage -= 4.0 * f64::from(amplifier + 1);
+        }
+    }
+
+    damage.max(0.0) as f32
+}
+
+/// Computes the strength of an attack made the given number
+/// of ticks after the attack cooldown was reset, from 0 to 1.
+pub fn attack_strength(ticks: u64, attack_speed: f64) -> f32 {
+    if attack_speed <= 0.0 {
+        return 1.0;
+    }
+    let period = TPS as f64 / attack_speed;
+    ((ticks as f64 + 0.5) / period).max(0.0).min(1.0) as f32
+}
+
+/// Returns the current strength of an entity's attacks,
+/// which recharges according to its `generic.attackSpeed`.
+pub fn attack_charge(game: &Game, world: &World, attacker: Entity) -> f32 {
+    let cooldown = match world.try_get::<AttackCooldown>(attacker) {
+        Some(cooldown) => *cooldown,
+        None => return 1.0,
+    };
+    let speed = attribute_value_with_held_item(world, attacker, Attribute::AttackSpeed);
+    attack_strength(game.tick_count.saturating_sub(cooldown.last_reset), speed)
+}
+
+/// Scales the damage of an attack by its strength.
+pub fn charged_attack_damage(damage: f32, charge: f32) -> f32 {
+    damage * (0.2 + charge * charge * 0.8)
+}
+
+/// Resets an entity's attack cooldown, if it has one.
+pub fn reset_attack_cooldown(game: &Game, world: &mut World, entity: Entity) {
+    if world.has::<AttackCooldown>(entity) {
+        world
+            .get_mut::<AttackCooldown>(entity)
+            .reset(game.tick_count);
+    }
+}
+
+/// Returns the protection enchantments on an entity's armor.
+fn armor_enchantments(
+    world: &World,
+    entity: Entity,
+) -> SmallVec<[(ProtectionEnchantment, u32); 4]> {
+    worn_armor(world, entity)
+        .iter()
+        .flat_map(|stack| stack.enchantments())
+        .filter_map(|(enchantment, level)| {
+            let protection = match enchantment {
+                Enchantment::Protection => ProtectionEnchantment::Protection,
+                Enchantment::FireProtection => ProtectionEnchantment::FireProtection,
+                Enchantment::BlastProtection => ProtectionEnchantment::BlastProtection,
+                Enchantment::ProjectileProtection => ProtectionEnchantment::ProjectileProtection,
+                Enchantment::FeatherFalling => ProtectionEnchantment::FeatherFalling,
+                _ => return None,
+            };
+            Some((protection, level))
+        })
+        .collect()
+}
+
+/// Applies armor, effect, and enchantment reductions to damage.
+fn reduced_damage(world: &World, entity: Entity, mut damage: f32, cause: &DamageCause) -> f32 {
+    if cause.reduced_by_armor() {
+        let armor = attribute_value(world, entity, Attribute::Armor) as f32;
+        let toughness = attribute_value(world, entity, Attribute::ArmorToughness) as f32;
+        damage = armor_reduction(damage, armor, toughness);
+    }
+
+    // Resistance cannot protect from starvation or falling out
+    // of the world, which is also how `/kill` deals its damage.
+    if cause.reduced_by_protection() {
+        if let Some(effects) = world.try_get::<ActiveEffects>(entity) {
+            if let Some(amplifier) = effects.amplifier(StatusEffect::Resistance) {
+                damage = resistance_reduction(damage, amplifier);
+            }
+        }
+    }
+
+    let protection = protection_factor(armor_enchantments(world, entity), cause);
+    protection_reduction(damage, protection)
+}
+
+/// Returns whether an entity is a player in a gamemode which
+/// protects them from damage dealt by their surroundings.
+pub fn has_invulnerable_gamemode(world: &World, entity: Entity) -> bool {
+    world
+        .try_get::<Gamemode>(entity)
+        .map_or(false, |gamemode| is_invulnerable(*gamemode))
+}
+
+/// Handles an `EntityDamageRequest`.
+#[fecs::event_handler]
+pub fn entity_damage_request(event: &EntityDamageRequest, game: &mut Game, world: &mut World) {
+    let entity = event.entity;
+    if !world.is_alive(entity) || world.try_get::<Health>(entity).map_or(true, |h| h.0 <= 0.0) {
+        return;
+    }
+
+    // Creative and spectator players can only be hurt
+    // by falling out of the world.
+    if event.cause != DamageCause::Void && has_invulnerable_gamemode(world, entity) {
+        return;
+    }
+
+    let fire_resistant = world
+        .try_get::<ActiveEffects>(entity)
+        .map_or(false, |effects| effects.has(StatusEffect::FireResistance));
+    if event.cause.is_fire() && fire_resistant {
+        return;
+    }
+
+    let mut damage = event.damage;
+    if world.has::<Player>(entity) && is_difficulty_scaled(world, &event.cause) {
+        damage = difficulty_scaled_damage(game.difficulty(), damage);
+        if damage <= 0.0 {
+            return;
+        }
+    }
+
+    let blocked = damage > 0.0
+        && is_blocking(game, world, entity)
+        && event.cause.blockable()
+        && damage_source_position(world, &event.cause).map_or(false, |source| {
+            faces_damage(*world.get::<Position>(entity), source)
+        });
+    let blocked_damage = damage;
+    if blocked {
+        damage = 0.0;
+    }
+
+    // Entities which were recently damaged only take the
+    // amount by which new damage exceeds the previous damage.
+    let last = world.try_get::<LastDamage>(entity).map(|last| *last);
+    let (damage, new_last) = match last {
+        Some(last) if game.tick_count < last.tick + INVULNERABILITY_TICKS => {
+            if damage <= last.amount {
+                return;
+            }
+            (
+                damage - last.amount,
+                LastDamage {
+                    tick: last.tick,
+                    amount: damage,
+                },
+            )
+        }
+        _ => (
+            damage,
+            LastDamage {
+                tick: game.tick_count,
+                amount: damage,
+            },
+        ),
+    };
+    let hurt_animation = new_last.tick == game.tick_count;
+
+    let outcome = DamageOutcome {
+        damage: reduced_damage(world, entity, damage, &event.cause),
+        blocked,
+        cancelled: false,
+    };
+    let outcome = Arc::new(Mutex::new(outcome));
+    game.handle(
+        world,
+        EntityDamageEvent {
+            entity,
+            cause: event.cause,
+            outcome: Arc::clone(&outcome),
+        },
+    );
+    let outcome = *outcome.lock();
+    if outcome.cancelled {
+        return;
+    }
+
+    if event.cause.reduced_by_armor() {
+        wear_armor(game, world, entity, damage);
+    }
+
+    if outcome.blocked {
+        wear_shield(game, world, entity, blocked_damage);
+        if let DamageCause::EntityAttack(attacker) = event.cause {
+            block_attack(game, world, entity, attacker);
+        }
+    }
+
+    if world.has::<LastDamage>(entity) {
+        *world.get_mut::<LastDamage>(entity) = new_last;
+    } else {
+        world.add(entity, new_last).unwrap();
+    }
+
+    let health = {
+        let mut health = world.get_mut::<Health>(entity);
+        health.0 = (health.0 - outcome.damage.max(0.0)).max(0.0);
+        health.0
+    };
+    let health = if health <= 0.0 && use_totem(game, world, entity, &event.cause) {
+        world.get::<Health>(entity).0
+    } else {
+        health
+    };
+
+    if hurt_animation && !outcome.blocked && health > 0.0 {
+        knock_back_from_source(game, world, entity, &event.cause);
+    }
+
+    // Thorns only retaliates against attackers, not against
+    // other thorns.
+    if !outcome.blocked {
+        match event.cause {
+            DamageCause::EntityAttack(attacker)
+            | DamageCause::Projectile {
+                shooter: Some(attacker),
+                ..
+            } if world.is_alive(attacker) => apply_thorns(game, world, entity, attacker),
+            _ => (),
+        }
+    }
+
+    let entity_id = world.get::<EntityId>(entity).0;
+    let status = if health <= 0.0 {
+        Some(STATUS_DEATH)
+    } else if outcome.blocked {
+        Some(STATUS_SHIELD_BLOCK)
+    } else if hurt_animation {
+        Some(STATUS_HURT)
+    } else {
+        None
+    };
+    if let Some(entity_status) = status {
+        game.broadcast_entity_update(
+            world,
+            EntityStatus {
+                entity_id,
+                entity_status,
+            },
+            entity,
+            None,
+        );
+    }
+
+    send_health(world, entity);
+
+    if health <= 0.0 {
+        let drops = Arc::new(Mutex::new(death_drops(game, world, entity, &event.cause)));
+        game.handle(
+            world,
+            EntityDeathEvent {
+                entity,
+                cause: event.cause,
+                drops: Arc::clone(&drops),
+            },
+        );
+        let drops = drops.lock().clone();
+        spawn_death_drops(game, world, entity, drops);
+
+        if !world.has::<Player>(entity) {
+            game.despawn(entity, world);
+        }
+    }
+}
+
+/// Knocks an entity away from the attacker or projectile which
+/// damaged it. Explosions apply their own knockback.
+fn knock_back_from_source(game: &Game, world: &mut World, entity: Entity, cause: &DamageCause) {
+    match cause {
+        DamageCause::EntityAttack(_) | DamageCause::Projectile { .. } => (),
+        _ => return,
+    }
+    let source = match damage_source_position(world, cause) {
+        Some(source) => source,
+        None => return,
+    };
+    let pos = *world.get::<Position>(entity);
+
+    knock_back(
+        game,
+        world,
+        entity,
+        ATTACK_KNOCKBACK,
+        source.x - pos.x,
+        source.z - pos.z,
+    );
+}
+
+/// Knocks back an attacker whose attack was blocked by a shield
+/// and disables the shield if the attacker used an axe.
+fn block_attack(game: &Game, world: &mut World, entity: Entity, attacker: Entity) {
+    let (pos, attacker_pos) = match world.try_get::<Position>(attacker) {
+        Some(attacker_pos) => (*world.get::<Position>(entity), *attacker_pos),
+        None => return,
+    };
+    knock_back(
+        game,
+        world,
+        attacker,
+        0.5,
+        pos.x - attacker_pos.x,
+        pos.z - attacker_pos.z,
+    );
+
+    if held_item(world, attacker).map_or(false, disables_shields) {
+        disable_shield(game, world, entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::inventory::{SLOT_ARMOR_CHEST, SLOT_ARMOR_HEAD};
+    use feather_core::items::ItemStack;
+    use feather_core::position;
+    use feather_test_framework::Test;
+
+    fn approx_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn armor_reduces_damage() {
+        // full diamond armor: 20 armor, 8 toughness
+        assert!(approx_eq(armor_reduction(10.0, 20.0, 8.0), 2.4));
+        // no armor
+        assert!(approx_eq(armor_reduction(10.0, 0.0, 0.0), 10.0));
+        // high damage overcomes armor down to 20% of its value
+        assert!(approx_eq(armor_reduction(100.0, 10.0, 0.0), 92.0));
+    }
+
+    #[test]
+    fn resistance_reduces_damage() {
+        assert!(approx_eq(resistance_reduction(10.0, 0), 8.0));
+        assert!(approx_eq(resistance_reduction(10.0, 1), 6.0));
+        assert!(approx_eq(resistance_reduction(10.0, 4), 0.0));
+        assert!(approx_eq(resistance_reduction(10.0, 200), 0.0));
+    }
+
+    #[test]
+    fn protection_factor_by_cause() {
+        let enchantments = [
+            (ProtectionEnchantment::Protection, 4),
+            (ProtectionEnchantment::FeatherFalling, 4),
+            (ProtectionEnchantment::BlastProtection, 2),
+        ];
+
+        assert_eq!(
+            protection_factor(enchantments.iter().copied(), &DamageCause::Generic),
+            4
+        );
+        assert_eq!(
+            protection_factor(enchantments.iter().copied(), &DamageCause::Fall),
+            16
+        );
+        assert_eq!(
+            protection_factor(enchantments.iter().copied(), &DamageCause::Void),
+            0
+        );
+
+        // factors above 20 are capped
+        assert!(approx_eq(protection_reduction(10.0, 32), 2.0));
+    }
+
+    #[test]
+    fn attack_strength_recharges() {
+        // swords recharge in 12.5 ticks
+        assert!(approx_eq(attack_strength(0, 1.6), 0.04));
+        assert!(approx_eq(attack_strength(6, 1.6), 0.52));
+        assert!(approx_eq(attack_strength(12, 1.6), 1.0));
+        assert!(approx_eq(attack_strength(1000, 1.6), 1.0));
+
+        assert!(approx_eq(charged_attack_damage(10.0, 0.0), 2.0));
+        assert!(approx_eq(charged_attack_damage(10.0, 0.5), 4.0));
+        assert!(approx_eq(charged_attack_damage(10.0, 1.0), 10.0));
+    }
+
+    #[test]
+    fn shields_block_frontal_damage() {
+        // yaw 0 faces positive Z
+        let pos = position!(0.0, 64.0, 0.0, 0.0, 0.0);
+        assert!(faces_damage(pos, position!(0.0, 64.0, 5.0)));
+        assert!(!faces_damage(pos, position!(0.0, 64.0, -5.0)));
+    }
+
+    #[test]
+    fn invulnerable_gamemodes_only_take_void_damage() {
+        let mut test = Test::new();
+        let player = test.player("", position!(0.0, 64.0, 0.0));
+        *test.world.get_mut::<Gamemode>(player) = Gamemode::Creative;
+
+        test.handle(
+            EntityDamageRequest {
+                entity: player,
+                damage: 5.0,
+                cause: DamageCause::Generic,
+            },
+            entity_damage_request,
+        );
+        assert!(approx_eq(test.world.get::<Health>(player).0, 20.0));
+
+        test.handle(
+            EntityDamageRequest {
+                entity: player,
+                damage: 5.0,
+                cause: DamageCause::Void,
+            },
+            entity_damage_request,
+        );
+        assert!(approx_eq(test.world.get::<Health>(player).0, 15.0));
+
+        *test.world.get_mut::<Gamemode>(player) = Gamemode::Survival;
+        test.game.tick_count += INVULNERABILITY_TICKS;
+        test.handle(
+            EntityDamageRequest {
+                entity: player,
+                damage: 5.0,
+                cause: DamageCause::Generic,
+            },
+            entity_damage_request,
+        );
+        assert!(approx_eq(test.world.get::<Health>(player).0, 10.0));
+    }
+
+    #[test]
+    fn damage_wears_armor() {
+        let mut test = Test::new();
+        let player = test.player("", position!(0.0, 64.0, 0.0));
+        *test.world.get_mut::<Gamemode>(player) = Gamemode::Survival;
+        {
+            let mut inventory = test.world.get_mut::<Inventory>(player);
+            inventory.set_item_at(SLOT_ARMOR_CHEST, ItemStack::new(Item::DiamondChestplate, 1));
+            inventory.set_item_at(SLOT_ARMOR_HEAD, ItemStack::new(Item::CarvedPumpkin, 1));
+        }
+
+        test.handle(
+            EntityDamageRequest {
+                entity: player,
+                damage: 8.0,
+                cause: DamageCause::Generic,
+            },
+            entity_damage_request,
+        );
+
+        let inventory = test.world.get::<Inventory>(player);
+        assert_eq!(inventory.item_at(SLOT_ARMOR_CHEST).unwrap().damage(), 2);
+        assert!(inventory.item_at(SLOT_ARMOR_HEAD).is_some());
+    }
+}
@@ -0,0 +1,70 @@
+//! The world's difficulty: sending it to players, and scaling
+//! the damage mobs deal to players by it.
+
+use feather_core::network::packets::ServerDifficulty;
+use feather_core::util::Difficulty;
+use feather_server_types::{
+    DamageCause, DifficultyChangeEvent, Game, Living, Network, Player, PlayerJoinEvent,
+};
+use fecs::World;
+
+fn difficulty_packet(game: &Game) -> ServerDifficulty {
+    ServerDifficulty {
+        difficulty: game.difficulty().id(),
+    }
+}
+
+/// Event handler for sending the difficulty to players.
+#[fecs::event_handler]
+pub fn on_player_join_send_difficulty(event: &PlayerJoinEvent, game: &Game, world: &mut World) {
+    let network = world.get::<Network>(event.player);
+    network.send(difficulty_packet(game));
+}
+
+/// Event handler which sends the new difficulty to all players.
+#[fecs::event_handler]
+pub fn on_difficulty_change_broadcast(
+    _event: &DifficultyChangeEvent,
+    game: &Game,
+    world: &mut World,
+) {
+    game.broadcast_global(world, difficulty_packet(game), None);
+}
+
+/// Returns whether damage dealt to a player is scaled by the
+/// difficulty: that is, whether it was dealt by an explosion
+/// or by the melee attack of a mob.
+pub fn is_difficulty_scaled(world: &World, cause: &DamageCause) -> bool {
+    match cause {
+        DamageCause::EntityAttack(attacker) => {
+            world.has::<Living>(*attacker) && !world.has::<Player>(*attacker)
+        }
+        DamageCause::Explosion { .. } => true,
+        _ => false,
+    }
+}
+
+/// Scales damage dealt to a player by the difficulty. Mobs
+/// deal no damage on peaceful, less on easy and more on hard.
+pub fn difficulty_scaled_damage(difficulty: Difficulty, damage: f32) -> f32 {
+    match difficulty {
+        Difficulty::Peaceful => 0.0,
+        Difficulty::Easy => damage.min(damage / 2.0 + 1.0),
+        Difficulty::Medium => damage,
+        Difficulty::Hard => damage * 1.5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damage_scales_with_difficulty() {
+        assert_eq!(difficulty_scaled_damage(Difficulty::Peaceful, 6.0), 0.0);
+        assert_eq!(difficulty_scaled_damage(Difficulty::Easy, 6.0), 4.0);
+        assert_eq!(difficulty_scaled_damage(Difficulty::Easy, 1.0), 1.0);
+        assert_eq!(difficulty_scaled_damage(Difficulty::Medium, 6.0), 6.0);
+        assert_eq!(difficulty_scaled_damage(Difficulty::Hard, 6.0), 9.0);
+    }
+}
@@ -0,0 +1,220 @@
+//! How quickly players dig blocks.
+//!
+//! Digging is timed by the client, which only tells the server
+//! when it finishes. Blocks which a player digs in a single tick,
+//! however, are broken as soon as the player starts digging them.
+//!
+//! See https://minecraft.gamepedia.com/Breaking#Speed.
+
+use crate::{can_harvest, efficiency_speed_bonus, required_tool, tool, ToolKind};
+use feather_core::blocks::{BlockId, BlockKind};
+use feather_core::items::{Enchantment, Item, ItemStack};
+use feather_core::util::Position;
+use feather_server_types::{ActiveEffects, Game, StatusEffect, PLAYER_EYE_HEIGHT};
+use fecs::{Entity, World};
+use nalgebra_glm::vec3;
+
+/// Returns the speed of the tools made of an item's material.
+fn material_speed(item: Item) -> f32 {
+    use Item::*;
+
+    match item {
+        WoodenPickaxe | WoodenAxe | WoodenShovel => 2.0,
+        StonePickaxe | StoneAxe | StoneShovel => 4.0,
+        IronPickaxe | IronAxe | IronShovel => 6.0,
+        DiamondPickaxe | DiamondAxe | DiamondShovel => 8.0,
+        GoldenPickaxe | GoldenAxe | GoldenShovel => 12.0,
+        _ => 1.0,
+    }
+}
+
+/// Returns the kind of tool which digs a block faster
+/// than other items, if any.
+fn effective_tool(block: BlockId) -> Option<ToolKind> {
+    use BlockKind::*;
+
+    if let Some((kind, _)) = required_tool(block) {
+        return Some(kind);
+    }
+
+    let identifier = block.identifier();
+    match block.kind() {
+        Dirt | CoarseDirt | Podzol | GrassBlock | GrassPath | Mycelium | Farmland | Sand
+        | RedSand | Gravel | Clay | SoulSand => Some(ToolKind::Shovel),
+        Ice | PackedIce | BlueIce | Rail | PoweredRail | DetectorRail | ActivatorRail => {
+            Some(ToolKind::Pickaxe)
+        }
+        Chest | TrappedChest | CraftingTable | Bookshelf | Jukebox | NoteBlock | Pumpkin
+        | CarvedPumpkin | JackOLantern | Melon | Ladder | Sign | WallSign | DaylightDetector
+        | BrownMushroomBlock | RedMushroomBlock | MushroomStem => Some(ToolKind::Axe),
+        _ if identifier.ends_with("_concrete_powder") => Some(ToolKind::Shovel),
+        _ if is_wooden(identifier) => Some(ToolKind::Axe),
+        _ => None,
+    }
+}
+
+/// Returns whether a block is made of wood, judging by its identifier.
+fn is_wooden(identifier: &str) -> bool {
+    ["oak_", "spruce_", "birch_", "jungle_", "acacia_"]
+        .iter()
+        .any(|wood| identifier.contains(wood))
+        && !identifier.ends_with("_leaves")
+        && !identifier.ends_with("_sapling")
+}
+
+/// Returns the speed at which an item digs a block, including
+/// the bonus from Efficiency, which only applies to tools
+/// which dig the block faster than a hand.
+pub fn tool_dig_speed(tool_stack: Option<&ItemStack>, block: BlockId) -> f32 {
+    let stack = match tool_stack {
+        Some(stack) => stack,
+        None => return 1.0,
+    };
+
+    let kind = block.kind();
+    let speed = match tool(stack.ty) {
+        Some((ToolKind::Shears, _)) if kind == BlockKind::Cobweb || block.is_leaves() => 15.0,
+        Some((ToolKind::Shears, _)) if block.identifier().ends_with("_wool") => 5.0,
+        Some((ToolKind::Sword, _)) if kind == BlockKind::Cobweb => 15.0,
+        Some((ToolKind::Sword, _))
+            if block.is_leaves()
+                || kind == BlockKind::Pumpkin
+                || kind == BlockKind::Melon
+                || kind == BlockKind::Vine
+                || kind == BlockKind::Cocoa =>
+        {
+            1.5
+        }
+        Some((tool_kind, _)) if effective_tool(block) == Some(tool_kind) => {
+            material_speed(stack.ty)
+        }
+        _ => 1.0,
+    };
+
+    if speed > 1.0 {
+        speed + efficiency_speed_bonus(stack.enchantment_level(Enchantment::Efficiency))
+    } else {
+        speed
+    }
+}
+
+/// Returns the speed at which a player digs a block with
+/// the given tool, including the effects of Haste and
+/// Mining Fatigue and the penalties for digging underwater
+/// or while in the air.
+pub fn dig_speed(
+    game: &Game,
+    world: &World,
+    player: Entity,
+    tool_stack: Option<&ItemStack>,
+    block: BlockId,
+) -> f32 {
+    let mut speed = tool_dig_speed(tool_stack, block);
+
+    if let Some(effects) = world.try_get::<ActiveEffects>(player) {
+        if let Some(amplifier) = effects.amplifier(StatusEffect::Haste) {
+            speed *= 1.0 + 0.2 * f32::from(amplifier + 1);
+        }
+        if let Some(amplifier) = effects.amplifier(StatusEffect::MiningFatigue) {
+            speed *= match amplifier {
+                0 => 0.3,
+                1 => 0.09,
+                2 => 0.0027,
+                _ => 0.00081,
+            };
+        }
+    }
+
+    let pos = *world.get::<Position>(player);
+    let eyes = pos + vec3(0.0, PLAYER_EYE_HEIGHT, 0.0);
+    let underwater = game
+        .block_at(eyes.block())
+        .map_or(false, |block| block.is_water());
+    let aqua_affinity = crate::armor_enchantment(world, player, Enchantment::AquaAffinity) > 0;
+    if underwater && !aqua_affinity {
+        speed /= 5.0;
+    }
+    if !pos.on_ground {
+        speed /= 5.0;
+    }
+
+    speed
+}
+
+/// Returns whether a player digging a block at `speed` with
+/// the given tool breaks it in a single tick, and so as soon
+/// as they start digging.
+pub fn digs_instantly(speed: f32, block: BlockId, tool_stack: Option<&ItemStack>) -> bool {
+    let hardness = block.hardness();
+    if hardness < 0.0 {
+        return false;
+    }
+    if hardness == 0.0 {
+        return true;
+    }
+
+    // Blocks which drop nothing with the tool take longer to dig.
+    let divisor = if can_harvest(block, tool_stack) {
+        30.0
+    } else {
+        100.0
+    };
+    speed / hardness / divisor >= 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enchanted(item: Item, efficiency: u32) -> ItemStack {
+        let mut stack = ItemStack::new(item, 1);
+        stack.set_enchantment_level(Enchantment::Efficiency, efficiency);
+        stack
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn efficiency_only_helps_effective_tools() {
+        let stone = BlockId::stone();
+        assert_eq!(tool_dig_speed(None, stone), 1.0);
+        assert_eq!(
+            tool_dig_speed(Some(&enchanted(Item::DiamondPickaxe, 0)), stone),
+            8.0
+        );
+        assert_eq!(
+            tool_dig_speed(Some(&enchanted(Item::DiamondPickaxe, 5)), stone),
+            34.0
+        );
+        assert_eq!(
+            tool_dig_speed(Some(&enchanted(Item::DiamondShovel, 5)), stone),
+            1.0
+        );
+    }
+
+    #[test]
+    fn efficiency_digs_instantly() {
+        let dirt = BlockId::dirt();
+        let plain = enchanted(Item::DiamondShovel, 0);
+        let efficient = enchanted(Item::DiamondShovel, 3);
+        assert!(!digs_instantly(
+            tool_dig_speed(Some(&plain), dirt),
+            dirt,
+            Some(&plain)
+        ));
+        assert!(digs_instantly(
+            tool_dig_speed(Some(&efficient), dirt),
+            dirt,
+            Some(&efficient)
+        ));
+
+        // Stone needs Haste II on top of Efficiency V.
+        let stone = BlockId::stone();
+        let pickaxe = enchanted(Item::DiamondPickaxe, 5);
+        let speed = tool_dig_speed(Some(&pickaxe), stone);
+        assert!(!digs_instantly(speed, stone, Some(&pickaxe)));
+        assert!(digs_instantly(speed * 1.4, stone, Some(&pickaxe)));
+
+        assert!(digs_instantly(1.0, BlockId::grass(), None));
+        assert!(!digs_instantly(1000.0, BlockId::bedrock(), None));
+    }
+}
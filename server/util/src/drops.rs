@@ -0,0 +1,195 @@
+//! The items and experience entities drop when they die.
+
+use crate::held_enchantment;
+use feather_core::inventory::{Inventory, SLOT_CRAFTING_OUTPUT};
+use feather_core::items::{Enchantment, ItemStack};
+use feather_core::util::Position;
+use feather_server_types::{
+    DamageCause, DeathDrops, DeathDropsRequest, Experience, ExperienceReward, Game,
+    InventoryUpdateEvent, LootEntry, MobLoot, Player, DO_MOB_LOOT, KEEP_INVENTORY,
+};
+use fecs::{Entity, World};
+use rand::Rng;
+
+/// Maximum experience dropped by a player.
+const MAX_PLAYER_EXPERIENCE_DROP: u32 = 100;
+
+/// Returns the experience dropped by a player who dies at `level`.
+pub fn player_experience_drop(level: u32) -> u32 {
+    level.saturating_mul(7).min(MAX_PLAYER_EXPERIENCE_DROP)
+}
+
+/// Returns the items dropped by a mob with the given loot
+/// when killed with a weapon enchanted with `looting`.
+pub fn mob_loot(loot: &[LootEntry], looting: u32, rng: &mut impl Rng) -> Vec<ItemStack> {
+    loot.iter()
+        .filter_map(|&LootEntry(item, min, max)| {
+            let amount =
+                rng.gen_range(u32::from(min), u32::from(max) + 1) + rng.gen_range(0, looting + 1);
+            if amount == 0 {
+                None
+            } else {
+                Some(ItemStack::new(item, amount.min(64) as u8))
+            }
+        })
+        .collect()
+}
+
+/// Returns the items a player drops from their inventory.
+///
+/// The crafting output only shows what the grid would craft,
+/// so it is not dropped alongside the ingredients.
+pub fn inventory_drops(inventory: &Inventory) -> Vec<ItemStack> {
+    inventory
+        .items()
+        .iter()
+        .enumerate()
+        .filter(|&(slot, _)| slot != SLOT_CRAFTING_OUTPUT)
+        .filter_map(|(_, stack)| stack.clone())
+        .collect()
+}
+
+/// Returns what an entity killed by `cause` drops
+/// before any `EntityDeathEvent` handlers modify it.
+///
+/// Players drop their inventory and some of their experience
+/// unless `keepInventory` is on. Mobs only drop experience
+/// when killed by a player, and only if `doMobLoot` is on.
+pub fn death_drops(game: &Game, world: &World, entity: Entity, cause: &DamageCause) -> DeathDrops {
+    let mut drops = DeathDrops::default();
+
+    if world.has::<Player>(entity) {
+        if game.game_rules.bool(KEEP_INVENTORY) {
+            return drops;
+        }
+
+        if let Some(inventory) = world.try_get::<Inventory>(entity) {
+            drops.items = inventory_drops(&inventory);
+        }
+        if let Some(experience) = world.try_get::<Experience>(entity) {
+            drops.experience = player_experience_drop(experience.level);
+        }
+    } else if game.game_rules.bool(DO_MOB_LOOT) {
+        // TODO: mob equipment
+        if let Some(loot) = world.try_get::<MobLoot>(entity) {
+            let looting = cause.attacker().map_or(0, |attacker| {
+                held_enchantment(world, attacker, Enchantment::Looting)
+            });
+            drops.items = mob_loot(loot.0, looting, &mut *game.rng());
+        }
+
+        let killed_by_player = cause
+            .attacker()
+            .map_or(false, |attacker| world.has::<Player>(attacker));
+        if let Some(reward) = world.try_get::<ExperienceReward>(entity) {
+            if killed_by_player {
+                drops.experience = game.rng().gen_range(reward.min, reward.max + 1);
+            }
+        }
+    }
+
+    drops
+}
+
+/// Spawns the drops of an entity which died, emptying
+/// the inventory and experience of players.
+pub(crate) fn spawn_death_drops(
+    game: &mut Game,
+    world: &mut World,
+    entity: Entity,
+    drops: DeathDrops,
+) {
+    if drops.cancelled {
+        return;
+    }
+
+    let player = world.has::<Player>(entity);
+    if player && !game.game_rules.bool(KEEP_INVENTORY) {
+        let slots = {
+            let mut inventory = world.get_mut::<Inventory>(entity);
+            (0..inventory.slot_count() as usize)
+                .filter(|&slot| inventory.clear_item_at(slot).is_some())
+                .collect()
+        };
+        game.handle(
+            world,
+            InventoryUpdateEvent {
+                slots,
+                player: entity,
+            },
+        );
+
+        if world.has::<Experience>(entity) {
+            *world.get_mut::<Experience>(entity) = Experience::default();
+        }
+    }
+
+    if drops.items.is_empty() && drops.experience == 0 {
+        return;
+    }
+
+    let position = *world.get::<Position>(entity);
+    game.handle(
+        world,
+        DeathDropsRequest {
+            position,
+            items: drops.items,
+            experience: drops.experience,
+            scatter: player,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::inventory::{InventoryType, SLOT_CRAFTING_INPUT_X0_Y0};
+    use feather_core::items::Item;
+    use rand::rngs::mock::StepRng;
+
+    #[test]
+    fn player_experience_drop_is_capped() {
+        assert_eq!(player_experience_drop(0), 0);
+        assert_eq!(player_experience_drop(5), 35);
+        assert_eq!(player_experience_drop(30), MAX_PLAYER_EXPERIENCE_DROP);
+    }
+
+    #[test]
+    fn looting_adds_to_mob_loot() {
+        let loot = [LootEntry(Item::Leather, 0, 2), LootEntry(Item::Beef, 1, 3)];
+
+        let mut rng = StepRng::new(0, 0);
+        assert_eq!(
+            mob_loot(&loot, 3, &mut rng),
+            vec![ItemStack::new(Item::Beef, 1)]
+        );
+
+        let mut rng = StepRng::new(u64::max_value(), 0);
+        assert_eq!(
+            mob_loot(&loot, 0, &mut rng),
+            vec![
+                ItemStack::new(Item::Leather, 2),
+                ItemStack::new(Item::Beef, 3)
+            ]
+        );
+        assert_eq!(
+            mob_loot(&loot, 3, &mut rng),
+            vec![
+                ItemStack::new(Item::Leather, 5),
+                ItemStack::new(Item::Beef, 6)
+            ]
+        );
+    }
+
+    #[test]
+    fn crafting_output_is_not_dropped() {
+        let mut inventory = Inventory::new(InventoryType::Player, 46);
+        inventory.set_item_at(SLOT_CRAFTING_INPUT_X0_Y0, ItemStack::new(Item::OakLog, 1));
+        inventory.set_item_at(SLOT_CRAFTING_OUTPUT, ItemStack::new(Item::OakPlanks, 4));
+
+        assert_eq!(
+            inventory_drops(&inventory),
+            vec![ItemStack::new(Item::OakLog, 1)]
+        );
+    }
+}
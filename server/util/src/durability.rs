@@ -0,0 +1,65 @@
+//! Wearing down items which have durability.
+
+use crate::{consumes_items, play_sound, unbreaking_prevents_wear, SoundCategory};
+use feather_core::inventory::{Inventory, SlotIndex};
+use feather_core::items::Enchantment;
+use feather_core::util::{Gamemode, Position};
+use feather_server_types::{Game, InventoryUpdateEvent};
+use fecs::{Entity, World};
+use smallvec::smallvec;
+
+/// Wears down the item in a player's inventory slot by `amount`,
+/// breaking it once it runs out of durability. Unbreaking may
+/// prevent each point of wear, and creative players' items
+/// never wear down.
+pub fn wear_item(game: &mut Game, world: &mut World, player: Entity, slot: SlotIndex, amount: u32) {
+    if !consumes_items(*world.get::<Gamemode>(player)) {
+        return;
+    }
+
+    let broken = {
+        let mut inventory = world.get_mut::<Inventory>(player);
+        let stack = match inventory.item_at_mut(slot) {
+            Some(stack) if stack.ty.max_durability().is_some() => stack,
+            _ => return,
+        };
+
+        let unbreaking = stack.enchantment_level(Enchantment::Unbreaking);
+        let armor = crate::armor_slot(stack.ty).is_some();
+        let amount = {
+            let mut rng = game.rng();
+            (0..amount)
+                .filter(|_| !unbreaking_prevents_wear(unbreaking, armor, &mut *rng))
+                .count() as u32
+        };
+        if amount == 0 {
+            return;
+        }
+
+        let broken = stack.wear(amount);
+        if broken {
+            inventory.clear_item_at(slot);
+        }
+        broken
+    };
+
+    if broken {
+        play_sound(
+            game,
+            world,
+            "entity.item.break",
+            SoundCategory::Player,
+            *world.get::<Position>(player),
+            0.8,
+            1.0,
+            None,
+        );
+    }
+    game.handle(
+        world,
+        InventoryUpdateEvent {
+            slots: smallvec![slot],
+            player,
+        },
+    );
+}
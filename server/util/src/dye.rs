@@ -0,0 +1,123 @@
+//! Dye colors, which color wool, sheep and other dyed things.
+
+use feather_core::items::Item;
+
+/// One of the sixteen dye colors, listed in order of their IDs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DyeColor {
+    White,
+    Orange,
+    Magenta,
+    LightBlue,
+    Yellow,
+    Lime,
+    Pink,
+    Gray,
+    LightGray,
+    Cyan,
+    Purple,
+    Blue,
+    Brown,
+    Green,
+    Red,
+    Black,
+}
+
+const COLORS: [DyeColor; 16] = [
+    DyeColor::White,
+    DyeColor::Orange,
+    DyeColor::Magenta,
+    DyeColor::LightBlue,
+    DyeColor::Yellow,
+    DyeColor::Lime,
+    DyeColor::Pink,
+    DyeColor::Gray,
+    DyeColor::LightGray,
+    DyeColor::Cyan,
+    DyeColor::Purple,
+    DyeColor::Blue,
+    DyeColor::Brown,
+    DyeColor::Green,
+    DyeColor::Red,
+    DyeColor::Black,
+];
+
+impl DyeColor {
+    /// Returns the color with the given ID. Only
+    /// the lowest four bits of the ID are used.
+    pub fn from_id(id: u8) -> Self {
+        COLORS[(id & 0x0F) as usize]
+    }
+
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the color of a dye item, or `None`
+    /// if the item is not a dye.
+    pub fn from_dye(item: Item) -> Option<Self> {
+        Some(match item {
+            Item::BoneMeal => DyeColor::White,
+            Item::OrangeDye => DyeColor::Orange,
+            Item::MagentaDye => DyeColor::Magenta,
+            Item::LightBlueDye => DyeColor::LightBlue,
+            Item::DandelionYellow => DyeColor::Yellow,
+            Item::LimeDye => DyeColor::Lime,
+            Item::PinkDye => DyeColor::Pink,
+            Item::GrayDye => DyeColor::Gray,
+            Item::LightGrayDye => DyeColor::LightGray,
+            Item::CyanDye => DyeColor::Cyan,
+            Item::PurpleDye => DyeColor::Purple,
+            Item::LapisLazuli => DyeColor::Blue,
+            Item::CocoaBeans => DyeColor::Brown,
+            Item::CactusGreen => DyeColor::Green,
+            Item::RoseRed => DyeColor::Red,
+            Item::InkSac => DyeColor::Black,
+            _ => return None,
+        })
+    }
+
+    /// Returns the wool block item of this color.
+    pub fn wool(self) -> Item {
+        match self {
+            DyeColor::White => Item::WhiteWool,
+            DyeColor::Orange => Item::OrangeWool,
+            DyeColor::Magenta => Item::MagentaWool,
+            DyeColor::LightBlue => Item::LightBlueWool,
+            DyeColor::Yellow => Item::YellowWool,
+            DyeColor::Lime => Item::LimeWool,
+            DyeColor::Pink => Item::PinkWool,
+            DyeColor::Gray => Item::GrayWool,
+            DyeColor::LightGray => Item::LightGrayWool,
+            DyeColor::Cyan => Item::CyanWool,
+            DyeColor::Purple => Item::PurpleWool,
+            DyeColor::Blue => Item::BlueWool,
+            DyeColor::Brown => Item::BrownWool,
+            DyeColor::Green => Item::GreenWool,
+            DyeColor::Red => Item::RedWool,
+            DyeColor::Black => Item::BlackWool,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_round_trip() {
+        for &color in COLORS.iter() {
+            assert_eq!(DyeColor::from_id(color.id()), color);
+        }
+        // sheared flag in sheep metadata
+        assert_eq!(DyeColor::from_id(0x10 | 14), DyeColor::Red);
+    }
+
+    #[test]
+    fn dye_items() {
+        assert_eq!(DyeColor::from_dye(Item::InkSac), Some(DyeColor::Black));
+        assert_eq!(DyeColor::from_dye(Item::LapisLazuli), Some(DyeColor::Blue));
+        assert_eq!(DyeColor::from_dye(Item::Stone), None);
+        assert_eq!(DyeColor::Cyan.wool(), Item::CyanWool);
+    }
+}
@@ -0,0 +1,127 @@
+//! Adding, removing, and expiring status effects.
+
+use feather_core::network::packets::{EntityEffect, RemoveEntityEffect};
+use feather_server_types::{ActiveEffect, ActiveEffects, BumpVec, EntityId, Game, StatusEffect};
+use fecs::{Entity, IntoQuery, World, Write};
+use smallvec::SmallVec;
+
+/// Adds a status effect to an entity and sends it to clients.
+///
+/// As in vanilla, an active effect is only replaced
+/// by one with a higher amplifier, or by one with the
+/// same amplifier and a longer duration.
+pub fn add_effect(
+    game: &Game,
+    world: &mut World,
+    entity: Entity,
+    effect: StatusEffect,
+    active: ActiveEffect,
+) {
+    if !world.has::<ActiveEffects>(entity) {
+        world.add(entity, ActiveEffects::default()).unwrap();
+    }
+
+    {
+        let mut effects = world.get_mut::<ActiveEffects>(entity);
+        if let Some(existing) = effects.get(effect) {
+            if !replaces(existing, &active) {
+                return;
+            }
+        }
+        effects.0.insert(effect, active);
+    }
+
+    let mut flags = 0;
+    if active.ambient {
+        flags |= 0x01;
+    }
+    if active.show_particles {
+        flags |= 0x02;
+    }
+
+    let packet = EntityEffect {
+        entity_id: world.get::<EntityId>(entity).0,
+        effect_id: effect.id(),
+        amplifier: active.amplifier as i8,
+        duration: active.duration as i32,
+        flags,
+    };
+    game.broadcast_entity_update(world, packet, entity, None);
+}
+
+fn replaces(existing: &ActiveEffect, new: &ActiveEffect) -> bool {
+    new.amplifier > existing.amplifier
+        || (new.amplifier == existing.amplifier && new.duration > existing.duration)
+}
+
+/// Removes a status effect from an entity, if it is active.
+pub fn remove_effect(game: &Game, world: &mut World, entity: Entity, effect: StatusEffect) {
+    let removed = world.has::<ActiveEffects>(entity)
+        && world
+            .get_mut::<ActiveEffects>(entity)
+            .0
+            .remove(&effect)
+            .is_some();
+
+    if removed {
+        broadcast_removal(game, world, entity, effect);
+    }
+}
+
+/// Removes all status effects from an entity.
+pub fn clear_effects(game: &Game, world: &mut World, entity: Entity) {
+    let effects: SmallVec<[StatusEffect; 4]> = match world.try_get::<ActiveEffects>(entity) {
+        Some(effects) => effects.0.keys().copied().collect(),
+        None => return,
+    };
+    for effect in effects {
+        remove_effect(game, world, entity, effect);
+    }
+}
+
+fn broadcast_removal(game: &Game, world: &World, entity: Entity, effect: StatusEffect) {
+    let packet = RemoveEntityEffect {
+        entity_id: world.get::<EntityId>(entity).0,
+        effect_id: effect.id(),
+    };
+    game.broadcast_entity_update(world, packet, entity, None);
+}
+
+/// System which counts down the duration of
+/// status effects and removes expired effects.
+#[fecs::system]
+pub fn tick_effects(game: &mut Game, world: &mut World) {
+    let mut expired = BumpVec::new_in(game.bump());
+
+    for (entity, mut effects) in
+        <Write<ActiveEffects>>::query().iter_entities_mut(world.inner_mut())
+    {
+        effects.0.retain(|effect, active| {
+            if active.duration == 0 {
+                expired.push((entity, *effect));
+                false
+            } else {
+                active.duration -= 1;
+                true
+            }
+        });
+    }
+
+    for (entity, effect) in expired {
+        broadcast_removal(game, world, entity, effect);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stronger_effects_replace_weaker() {
+        let existing = ActiveEffect::new(1, 100);
+        assert!(replaces(&existing, &ActiveEffect::new(2, 10)));
+        assert!(replaces(&existing, &ActiveEffect::new(1, 200)));
+        assert!(!replaces(&existing, &ActiveEffect::new(1, 100)));
+        assert!(!replaces(&existing, &ActiveEffect::new(0, 1000)));
+    }
+}
@@ -0,0 +1,212 @@
+//! Looking up the enchantments on the items an entity holds
+//! and wears, and the effects of combat enchantments.
+//!
+//! See https://minecraft.gamepedia.com/Enchanting#Summary_of_enchantments.
+
+use crate::{set_on_fire, wear_item};
+use feather_core::inventory::{
+    Inventory, SlotIndex, SLOT_ARMOR_MAX, SLOT_ARMOR_MIN, SLOT_HOTBAR_OFFSET,
+};
+use feather_core::items::{Enchantment, ItemStack};
+use feather_server_types::{
+    CreatureAttribute, DamageCause, EntityDamageRequest, Game, HeldItem, Player,
+};
+use fecs::{Entity, World};
+use rand::Rng;
+use smallvec::SmallVec;
+
+/// Number of ticks for which each level of Fire Aspect
+/// sets the target on fire.
+pub const FIRE_ASPECT_TICKS: u32 = 80;
+
+/// Returns the item an entity holds in its main hand.
+pub fn held_stack(world: &World, entity: Entity) -> Option<ItemStack> {
+    let inventory = world.try_get::<Inventory>(entity)?;
+    let held = world.try_get::<HeldItem>(entity)?;
+    inventory.item_at(SLOT_HOTBAR_OFFSET + held.0).cloned()
+}
+
+/// Returns the armor an entity wears.
+pub fn worn_armor(world: &World, entity: Entity) -> SmallVec<[ItemStack; 4]> {
+    match world.try_get::<Inventory>(entity) {
+        Some(inventory) => (SLOT_ARMOR_MIN..=SLOT_ARMOR_MAX)
+            .filter_map(|slot| inventory.item_at(slot).cloned())
+            .collect(),
+        None => SmallVec::new(),
+    }
+}
+
+/// Returns the level of an enchantment on the
+/// item an entity holds in its main hand.
+pub fn held_enchantment(world: &World, entity: Entity, enchantment: Enchantment) -> u32 {
+    held_stack(world, entity).map_or(0, |stack| stack.enchantment_level(enchantment))
+}
+
+/// Returns the highest level of an enchantment
+/// on any piece of an entity's armor.
+pub fn armor_enchantment(world: &World, entity: Entity, enchantment: Enchantment) -> u32 {
+    worn_armor(world, entity)
+        .iter()
+        .map(|stack| stack.enchantment_level(enchantment))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns the extra melee damage dealt by a weapon with the
+/// given enchantments to a target with the given attribute.
+pub fn enchantment_damage_bonus(
+    enchantments: impl IntoIterator<Item = (Enchantment, u32)>,
+    target: Option<CreatureAttribute>,
+) -> f32 {
+    enchantments
+        .into_iter()
+        .filter(|(_, level)| *level > 0)
+        .map(|(enchantment, level)| match (enchantment, target) {
+            (Enchantment::Sharpness, _) => level as f32 * 0.5 + 0.5,
+            (Enchantment::Smite, Some(CreatureAttribute::Undead))
+            | (Enchantment::BaneOfArthropods, Some(CreatureAttribute::Arthropod))
+            | (Enchantment::Impaling, Some(CreatureAttribute::Aquatic)) => level as f32 * 2.5,
+            _ => 0.0,
+        })
+        .sum()
+}
+
+/// Returns the fraction of an attack's damage dealt to
+/// the entities it sweeps, given the level of Sweeping Edge.
+pub fn sweeping_damage_ratio(level: u32) -> f32 {
+    if level == 0 {
+        0.0
+    } else {
+        level as f32 / (level + 1) as f32
+    }
+}
+
+/// Returns the damage a piece of armor with Thorns deals to
+/// an attacker, if its chance to do so succeeds.
+pub fn thorns_damage(level: u32, rng: &mut impl Rng) -> Option<f32> {
+    if level == 0 || rng.gen::<f32>() >= 0.15 * level as f32 {
+        return None;
+    }
+    Some(if level > 10 {
+        (level - 10) as f32
+    } else {
+        rng.gen_range(1, 5) as f32
+    })
+}
+
+/// Damages an entity which attacked `victim` with the
+/// Thorns on each piece of the victim's armor. Each piece
+/// whose Thorns deals damage wears down by 2.
+pub(crate) fn apply_thorns(game: &mut Game, world: &mut World, victim: Entity, attacker: Entity) {
+    let levels: SmallVec<[(SlotIndex, u32); 4]> = match world.try_get::<Inventory>(victim) {
+        Some(inventory) => (SLOT_ARMOR_MIN..=SLOT_ARMOR_MAX)
+            .filter_map(|slot| {
+                let level = inventory
+                    .item_at(slot)?
+                    .enchantment_level(Enchantment::Thorns);
+                Some((slot, level))
+            })
+            .collect(),
+        None => return,
+    };
+
+    for (slot, level) in levels {
+        let damage = thorns_damage(level, &mut *game.rng());
+        if let Some(damage) = damage {
+            if world.has::<Player>(victim) {
+                wear_item(game, world, victim, slot, 2);
+            }
+            game.handle(
+                world,
+                EntityDamageRequest {
+                    entity: attacker,
+                    damage,
+                    cause: DamageCause::Thorns(victim),
+                },
+            );
+        }
+    }
+}
+
+/// Sets the target of a melee attack on fire if the
+/// attacker's weapon has Fire Aspect.
+pub fn apply_fire_aspect(world: &mut World, attacker: Entity, target: Entity) {
+    let level = held_enchantment(world, attacker, Enchantment::FireAspect);
+    if level > 0 {
+        set_on_fire(world, target, level * FIRE_ASPECT_TICKS);
+    }
+}
+
+/// Returns the mining speed added by Efficiency
+/// to a tool which is effective against a block.
+pub fn efficiency_speed_bonus(level: u32) -> f32 {
+    if level == 0 {
+        0.0
+    } else {
+        (level * level + 1) as f32
+    }
+}
+
+/// Returns whether Unbreaking prevents an item from losing
+/// durability when it would otherwise be worn down.
+pub fn unbreaking_prevents_wear(level: u32, armor: bool, rng: &mut impl Rng) -> bool {
+    if level == 0 {
+        return false;
+    }
+    // Armor always has a 60% chance to lose durability.
+    if armor && rng.gen::<f32>() < 0.6 {
+        return false;
+    }
+    rng.gen_range(0, level + 1) > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn damage_bonus_depends_on_target() {
+        let sharpness = [(Enchantment::Sharpness, 5)];
+        let smite = [(Enchantment::Smite, 2)];
+        assert!((enchantment_damage_bonus(sharpness.iter().copied(), None) - 3.0).abs() < 1e-6);
+        assert!(
+            (enchantment_damage_bonus(smite.iter().copied(), Some(CreatureAttribute::Undead))
+                - 5.0)
+                .abs()
+                < 1e-6
+        );
+        assert_eq!(
+            enchantment_damage_bonus(smite.iter().copied(), Some(CreatureAttribute::Arthropod)),
+            0.0
+        );
+
+        let impaling = [(Enchantment::Impaling, 3)];
+        assert_eq!(
+            enchantment_damage_bonus(impaling.iter().copied(), None),
+            0.0
+        );
+        assert_eq!(
+            enchantment_damage_bonus(impaling.iter().copied(), Some(CreatureAttribute::Aquatic)),
+            7.5
+        );
+    }
+
+    #[test]
+    fn thorns_levels() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(thorns_damage(0, &mut rng), None);
+        // Levels above 10 always reflect, dealing level - 10 damage.
+        assert_eq!(thorns_damage(12, &mut rng), Some(2.0));
+        if let Some(damage) = thorns_damage(3, &mut rng) {
+            assert!(damage >= 1.0 && damage <= 4.0);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn sweeping_edge_ratio() {
+        assert_eq!(sweeping_damage_ratio(0), 0.0);
+        assert!((sweeping_damage_ratio(3) - 0.75).abs() < 1e-6);
+    }
+}
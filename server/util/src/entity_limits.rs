@@ -0,0 +1,141 @@
+//! Per-chunk entity limits, configured in the `entity_limits`
+//! section of the config file.
+
+use feather_core::util::ChunkPosition;
+use feather_server_types::{EntityCategory, Game};
+use fecs::{Entity, World};
+
+/// Returns the maximum number of entities of the given
+/// category allowed in a single chunk, or `None` if
+/// the category is unlimited.
+pub fn entity_limit(game: &Game, category: EntityCategory) -> Option<usize> {
+    let limits = &game.config.entity_limits;
+    let limit = match category {
+        EntityCategory::Mob => limits.mobs,
+        EntityCategory::Item => limits.items,
+        EntityCategory::ItemFrame => limits.item_frames,
+    };
+
+    if limit == 0 {
+        None
+    } else {
+        Some(limit)
+    }
+}
+
+/// Returns an iterator over the entities of the given category
+/// in a chunk.
+pub fn entities_of_category<'a>(
+    game: &'a Game,
+    world: &'a World,
+    chunk: ChunkPosition,
+    category: EntityCategory,
+) -> impl Iterator<Item = Entity> + 'a {
+    game.chunk_entities
+        .entities_in_chunk(chunk)
+        .iter()
+        .copied()
+        .filter(move |entity| {
+            world
+                .try_get::<EntityCategory>(*entity)
+                .map(|c| *c == category)
+                .unwrap_or(false)
+        })
+}
+
+/// Returns whether a new entity of the given category may be
+/// spawned in a chunk without exceeding the chunk's limit.
+///
+/// Mob spawning, breeding, spawn eggs, `/summon` and item frame
+/// placement should check this before creating a new entity.
+pub fn can_spawn_in_chunk(
+    game: &Game,
+    world: &World,
+    chunk: ChunkPosition,
+    category: EntityCategory,
+) -> bool {
+    match entity_limit(game, category) {
+        Some(limit) => entities_of_category(game, world, chunk, category).count() < limit,
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::on_entity_spawn_update_chunk_entities;
+    use feather_core::position;
+    use feather_server_types::EntitySpawnEvent;
+    use feather_test_framework::Test;
+    use fecs::EntityBuilder;
+    use std::sync::Arc;
+
+    fn item_frame(test: &mut Test, x: f64) -> Entity {
+        let entity = test.entity(
+            EntityBuilder::new()
+                .with(EntityCategory::ItemFrame)
+                .with(position!(x, 64.0, 0.0)),
+        );
+        test.handle(
+            EntitySpawnEvent { entity },
+            on_entity_spawn_update_chunk_entities,
+        );
+        entity
+    }
+
+    #[test]
+    fn item_frame_limit() {
+        let mut test = Test::new();
+        Arc::make_mut(&mut test.game.config)
+            .entity_limits
+            .item_frames = 2;
+        let chunk = ChunkPosition::new(0, 0);
+
+        assert_eq!(entity_limit(&test.game, EntityCategory::ItemFrame), Some(2));
+        item_frame(&mut test, 1.0);
+        assert!(can_spawn_in_chunk(
+            &test.game,
+            &test.world,
+            chunk,
+            EntityCategory::ItemFrame
+        ));
+        item_frame(&mut test, 2.0);
+        assert!(!can_spawn_in_chunk(
+            &test.game,
+            &test.world,
+            chunk,
+            EntityCategory::ItemFrame
+        ));
+
+        // Other categories and chunks are counted separately.
+        assert!(can_spawn_in_chunk(
+            &test.game,
+            &test.world,
+            chunk,
+            EntityCategory::Mob
+        ));
+        assert!(can_spawn_in_chunk(
+            &test.game,
+            &test.world,
+            ChunkPosition::new(1, 0),
+            EntityCategory::ItemFrame
+        ));
+    }
+
+    #[test]
+    fn zero_disables_limit() {
+        let mut test = Test::new();
+        Arc::make_mut(&mut test.game.config)
+            .entity_limits
+            .item_frames = 0;
+
+        assert_eq!(entity_limit(&test.game, EntityCategory::ItemFrame), None);
+        item_frame(&mut test, 1.0);
+        assert!(can_spawn_in_chunk(
+            &test.game,
+            &test.world,
+            ChunkPosition::new(0, 0),
+            EntityCategory::ItemFrame
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+//! Status effects granted by worn equipment, such as the
+//! Water Breathing given by a turtle shell.
+
+use crate::add_effect;
+use feather_core::inventory::{
+    Inventory, SlotIndex, SLOT_ARMOR_HEAD, SLOT_ARMOR_MAX, SLOT_ARMOR_MIN,
+};
+use feather_core::items::Item;
+use feather_core::util::Position;
+use feather_server_types::{
+    player_eye_height, ActiveEffect, ActiveEffects, BumpVec, Game, Player, StatusEffect,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+
+/// When a worn item grants its effect.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WearCondition {
+    /// The effect is granted as long as the item is worn.
+    Always,
+    /// The effect is granted while the wearer's head is
+    /// out of water, so it starts running out once they dive.
+    HeadOutOfWater,
+}
+
+/// An effect granted by an item while it is worn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WornEffect {
+    /// The armor slot the item must be worn in.
+    pub slot: SlotIndex,
+    pub effect: StatusEffect,
+    pub amplifier: u8,
+    /// Duration to which the effect is refreshed
+    /// while the condition holds.
+    pub duration: u32,
+    pub condition: WearCondition,
+}
+
+/// Returns the effect granted by an item while it is worn, if any.
+pub fn worn_effect(item: Item) -> Option<WornEffect> {
+    match item {
+        Item::TurtleHelmet => Some(WornEffect {
+            slot: SLOT_ARMOR_HEAD,
+            effect: StatusEffect::WaterBreathing,
+            amplifier: 0,
+            duration: 200,
+            condition: WearCondition::HeadOutOfWater,
+        }),
+        _ => None,
+    }
+}
+
+fn condition_holds(game: &Game, world: &World, entity: Entity, condition: WearCondition) -> bool {
+    match condition {
+        WearCondition::Always => true,
+        WearCondition::HeadOutOfWater => {
+            let pos = *world.get::<Position>(entity)
+                + nalgebra_glm::vec3(0.0, player_eye_height(world, entity), 0.0);
+            !game
+                .block_at(pos.block())
+                .map_or(false, |block| block.is_water())
+        }
+    }
+}
+
+/// System which grants the effects of worn equipment.
+#[fecs::system]
+pub fn apply_worn_effects(game: &mut Game, world: &mut World) {
+    let mut granted = BumpVec::new_in(game.bump());
+
+    for (player, inventory) in <Read<Inventory>>::query()
+        .filter(component::<Player>())
+        .iter_entities(world.inner())
+    {
+        for slot in SLOT_ARMOR_MIN..=SLOT_ARMOR_MAX {
+            let worn = match inventory
+                .item_at(slot)
+                .and_then(|stack| worn_effect(stack.ty))
+            {
+                Some(worn) if worn.slot == slot => worn,
+                _ => continue,
+            };
+
+            if condition_holds(game, world, player, worn.condition) {
+                granted.push((player, worn));
+            }
+        }
+    }
+
+    for (player, worn) in granted {
+        // Effects which are already at full duration are topped up
+        // without notifying clients, which would otherwise receive
+        // a packet every tick.
+        if world.has::<ActiveEffects>(player) {
+            let mut effects = world.get_mut::<ActiveEffects>(player);
+            if let Some(active) = effects.0.get_mut(&worn.effect) {
+                if active.amplifier == worn.amplifier && active.duration + 1 >= worn.duration {
+                    active.duration = worn.duration;
+                    continue;
+                }
+            }
+        }
+
+        let active = ActiveEffect {
+            show_particles: false,
+            ..ActiveEffect::new(worn.amplifier, worn.duration)
+        };
+        add_effect(game, world, player, worn.effect, active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turtle_helmet_is_worn_on_head() {
+        let worn = worn_effect(Item::TurtleHelmet).unwrap();
+        assert_eq!(worn.slot, SLOT_ARMOR_HEAD);
+        assert_eq!(worn.effect, StatusEffect::WaterBreathing);
+        assert!(worn_effect(Item::DiamondHelmet).is_none());
+    }
+}
@@ -0,0 +1,134 @@
+//! Tracking how far entities fall and damaging them when they land.
+
+use crate::has_invulnerable_gamemode;
+use feather_core::blocks::BlockKind;
+use feather_core::util::Position;
+use feather_server_types::{
+    ActiveEffects, BumpVec, DamageCause, EntityDamageRequest, FallDistance, Flying, Game, Gliding,
+    Health, PreviousPosition, StatusEffect,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+use nalgebra_glm::vec3;
+
+/// Distance an entity may fall without taking damage.
+pub const SAFE_FALL_DISTANCE: f64 = 3.0;
+
+/// Distance per tick which gliding entities may descend
+/// without accumulating fall distance.
+const SAFE_GLIDE_DESCENT: f64 = 0.5;
+
+/// Computes the damage dealt by a fall of the given distance.
+/// Each level of Jump Boost increases the distance which
+/// can be fallen safely by one block.
+pub fn fall_damage(distance: f64, jump_boost: Option<u8>) -> f32 {
+    let safe = SAFE_FALL_DISTANCE + jump_boost.map_or(0.0, |amplifier| f64::from(amplifier) + 1.0);
+    (distance - safe).ceil().max(0.0) as f32
+}
+
+/// Returns whether an entity's fall is being broken, which resets
+/// its fall distance: it is in water, climbing, slow falling, or flying.
+fn fall_broken(game: &Game, world: &World, entity: Entity, pos: Position) -> bool {
+    let slow_falling = world
+        .try_get::<ActiveEffects>(entity)
+        .map_or(false, |effects| effects.has(StatusEffect::SlowFalling));
+
+    slow_falling
+        || world.has::<Flying>(entity)
+        || game.block_at(pos.block()).map_or(false, |block| {
+            block.is_water() || block.kind() == BlockKind::Ladder || block.kind() == BlockKind::Vine
+        })
+}
+
+/// Returns whether the block an entity landed on prevents fall damage.
+fn cushions_landing(game: &Game, pos: Position) -> bool {
+    let below = (pos + vec3(0.0, -0.2, 0.0)).block();
+    game.block_at(below).map_or(false, |block| {
+        block.is_water()
+            || block.kind() == BlockKind::SlimeBlock
+            || block.kind() == BlockKind::HayBlock
+    })
+}
+
+/// System which updates the fall distance of living entities
+/// and damages those which land after falling too far.
+#[fecs::system]
+pub fn update_fall_distance(game: &mut Game, world: &mut World) {
+    let mut updates = BumpVec::new_in(game.bump());
+    let mut landings = BumpVec::new_in(game.bump());
+
+    for (entity, (pos, previous)) in <(Read<Position>, Read<PreviousPosition>)>::query()
+        .filter(component::<Health>())
+        .iter_entities(world.inner())
+    {
+        let fallen = world.try_get::<FallDistance>(entity).map_or(0.0, |d| d.0);
+
+        let distance = if fall_broken(game, world, entity, *pos) {
+            0.0
+        } else if pos.on_ground {
+            if fallen > 0.0 && !cushions_landing(game, *pos) {
+                landings.push((entity, fallen));
+            }
+            0.0
+        } else if world.has::<Gliding>(entity) && previous.0.y - pos.y < SAFE_GLIDE_DESCENT {
+            // Gliding slowly only counts as a short fall, as in vanilla.
+            1.0
+        } else if pos.y < previous.0.y {
+            fallen + (previous.0.y - pos.y)
+        } else {
+            fallen
+        };
+
+        if distance != fallen || !world.has::<FallDistance>(entity) {
+            updates.push((entity, distance));
+        }
+    }
+
+    for (entity, distance) in updates {
+        if world.has::<FallDistance>(entity) {
+            world.get_mut::<FallDistance>(entity).0 = distance;
+        } else {
+            world.add(entity, FallDistance(distance)).unwrap();
+        }
+    }
+
+    for (entity, distance) in landings {
+        if has_invulnerable_gamemode(world, entity) {
+            continue;
+        }
+
+        let jump_boost = world
+            .try_get::<ActiveEffects>(entity)
+            .and_then(|effects| effects.amplifier(StatusEffect::JumpBoost));
+        let damage = fall_damage(distance, jump_boost);
+        if damage > 0.0 {
+            // Feather Falling is applied along with the other
+            // protection enchantments.
+            game.handle(
+                world,
+                EntityDamageRequest {
+                    entity,
+                    damage,
+                    cause: DamageCause::Fall,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_falls_are_safe() {
+        assert_eq!(fall_damage(3.0, None), 0.0);
+        assert_eq!(fall_damage(3.5, None), 1.0);
+        assert_eq!(fall_damage(10.0, None), 7.0);
+    }
+
+    #[test]
+    fn jump_boost_reduces_fall_damage() {
+        assert_eq!(fall_damage(5.0, Some(0)), 1.0);
+        assert_eq!(fall_damage(5.0, Some(1)), 0.0);
+    }
+}
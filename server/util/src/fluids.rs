@@ -0,0 +1,363 @@
+//! Flowing water and lava.
+//!
+//! Fluids flow in response to block updates: the block notify
+//! system marks fluid blocks next to an updated block with
+//! `BlockNotifyFluid`, and `flow_fluids` processes those notifies
+//! once the fluid's flow delay has passed. Each step of flow updates
+//! the surrounding blocks, which notifies them in turn, so fluids
+//! spread one block per step.
+
+use crate::{BlockNotifyCause, BlockNotifyFluid, BlockNotifyPosition};
+use ahash::AHashSet;
+use feather_core::blocks::{BlockId, BlockKind};
+use feather_core::util::BlockPosition;
+use feather_server_types::{BumpVec, Game};
+use fecs::{component, IntoQuery, Read, World};
+use smallvec::SmallVec;
+
+/// Fluid levels at or above this value denote falling fluid.
+const FALLING: i32 = 8;
+/// The highest level of flowing fluid; fluid at this
+/// level does not spread any further.
+const MAX_LEVEL: i32 = 7;
+
+/// A kind of fluid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Fluid {
+    Water,
+    Lava,
+}
+
+impl Fluid {
+    /// Returns the fluid in the given block, if any.
+    /// Waterlogged blocks contain a water source.
+    pub fn of(block: BlockId) -> Option<Self> {
+        match block.kind() {
+            BlockKind::Water => Some(Fluid::Water),
+            BlockKind::Lava => Some(Fluid::Lava),
+            _ if block.waterlogged() == Some(true) => Some(Fluid::Water),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of ticks between each step of flow.
+    pub fn flow_delay(self) -> u64 {
+        match self {
+            Fluid::Water => 5,
+            Fluid::Lava => 30,
+        }
+    }
+
+    /// Returns how much the level increases
+    /// for each block the fluid spreads.
+    fn level_drop(self) -> i32 {
+        match self {
+            Fluid::Water => 1,
+            Fluid::Lava => 2,
+        }
+    }
+
+    /// Returns the block of this fluid with the given level.
+    pub fn block(self, level: i32) -> BlockId {
+        let block = match self {
+            Fluid::Water => BlockId::water(),
+            Fluid::Lava => BlockId::lava(),
+        };
+        block.with_water_level(level)
+    }
+
+    /// Returns the source block of this fluid.
+    pub fn source(self) -> BlockId {
+        self.block(0)
+    }
+}
+
+/// Returns whether the given block is a fluid source,
+/// including waterlogged blocks.
+pub fn is_fluid_source(block: BlockId) -> bool {
+    Fluid::of(block).is_some() && level(block) == 0
+}
+
+/// Returns the level of a fluid block: 0 for a source,
+/// 1 to 7 for flowing fluid, and 8 for falling fluid.
+fn level(block: BlockId) -> i32 {
+    block.water_level().unwrap_or(0)
+}
+
+/// Returns the level which fluid spreading from
+/// a block with the given level would have.
+fn spread_level(fluid: Fluid, level: i32) -> i32 {
+    // Falling fluid spreads as if it were a source.
+    let level = if level >= FALLING { 0 } else { level };
+    level + fluid.level_drop()
+}
+
+fn horizontal(pos: BlockPosition) -> [BlockPosition; 4] {
+    [
+        pos + BlockPosition::new(1, 0, 0),
+        pos + BlockPosition::new(-1, 0, 0),
+        pos + BlockPosition::new(0, 0, 1),
+        pos + BlockPosition::new(0, 0, -1),
+    ]
+}
+
+/// Computes one step of flow for the fluid `block` at `pos`,
+/// returning the blocks to set. `block_at` is used to look
+/// up surrounding blocks.
+pub fn flow(
+    block: BlockId,
+    pos: BlockPosition,
+    block_at: impl Fn(BlockPosition) -> Option<BlockId>,
+) -> SmallVec<[(BlockPosition, BlockId); 4]> {
+    let mut changes = SmallVec::new();
+    let fluid = match Fluid::of(block) {
+        Some(fluid) => fluid,
+        None => return changes,
+    };
+    let level = level(block);
+    let up = BlockPosition::new(0, 1, 0);
+
+    // Lava touching water hardens into obsidian
+    // if it is a source and cobblestone otherwise.
+    if fluid == Fluid::Lava {
+        let touches_water = horizontal(pos)
+            .iter()
+            .chain(std::iter::once(&(pos + up)))
+            .any(|&pos| block_at(pos).and_then(Fluid::of) == Some(Fluid::Water));
+        if touches_water {
+            let hardened = if level == 0 {
+                BlockId::obsidian()
+            } else {
+                BlockId::cobblestone()
+            };
+            changes.push((pos, hardened));
+            return changes;
+        }
+    }
+
+    // Flowing fluid takes its level from the fluid feeding it
+    // and drains away once nothing does.
+    if level != 0 {
+        let fed = fed_level(fluid, pos, &block_at);
+        if fed != Some(level) {
+            let block = fed.map_or_else(BlockId::air, |level| fluid.block(level));
+            changes.push((pos, block));
+            return changes;
+        }
+    }
+
+    match block_at(pos - up) {
+        Some(below) if below.is_air() => {
+            changes.push((pos - up, fluid.block(FALLING)));
+            return changes;
+        }
+        // Only sources spread sideways on top of
+        // fluid; flowing fluid merges into it.
+        Some(below) if Fluid::of(below) == Some(fluid) && level != 0 => return changes,
+        _ => (),
+    }
+
+    let spread = spread_level(fluid, level);
+    if spread > MAX_LEVEL {
+        return changes;
+    }
+    for &side in horizontal(pos).iter() {
+        if block_at(side).map_or(false, BlockId::is_air) {
+            changes.push((side, fluid.block(spread)));
+        }
+    }
+
+    changes
+}
+
+/// Returns the level which flowing fluid at `pos` should have
+/// according to its neighbors, or `None` if it is not fed.
+fn fed_level(
+    fluid: Fluid,
+    pos: BlockPosition,
+    block_at: &impl Fn(BlockPosition) -> Option<BlockId>,
+) -> Option<i32> {
+    let up = BlockPosition::new(0, 1, 0);
+    if block_at(pos + up).and_then(Fluid::of) == Some(fluid) {
+        return Some(FALLING);
+    }
+
+    let mut sources = 0;
+    let mut fed = None;
+    for &side in horizontal(pos).iter() {
+        let block = match block_at(side) {
+            Some(block) if Fluid::of(block) == Some(fluid) => block,
+            _ => continue,
+        };
+        if level(block) == 0 {
+            sources += 1;
+        }
+        let spread = spread_level(fluid, level(block));
+        fed = Some(fed.map_or(spread, |fed: i32| fed.min(spread)));
+    }
+
+    // Water between two sources forms a new source
+    // if it rests on a solid block or another source.
+    if fluid == Fluid::Water && sources >= 2 {
+        let rests =
+            block_at(pos - up).map_or(false, |below| below.is_solid() || is_fluid_source(below));
+        if rests {
+            return Some(0);
+        }
+    }
+
+    fed.filter(|&level| level <= MAX_LEVEL)
+}
+
+/// System which makes notified fluids flow once their
+/// flow delay has passed. Until then, the notify
+/// entities are kept around.
+#[fecs::system]
+pub fn flow_fluids(game: &mut Game, world: &mut World) {
+    let mut notifies = BumpVec::new_in(game.bump());
+    notifies.extend(
+        <(Read<BlockNotifyPosition>, Read<BlockNotifyCause>)>::query()
+            .filter(component::<BlockNotifyFluid>())
+            .iter_entities(world.inner())
+            .map(|(entity, (pos, cause))| (entity, pos.0, cause.0)),
+    );
+
+    // Positions may be notified several times before
+    // they flow, but each should only flow once.
+    let mut flowed = AHashSet::new();
+
+    for (notify, pos, cause) in notifies {
+        // Use the current block, since it may have
+        // changed since the notify entity was created.
+        let block = game.block_at(pos);
+        let fluid = match block.and_then(Fluid::of) {
+            Some(fluid) => fluid,
+            None => {
+                world.despawn(notify);
+                continue;
+            }
+        };
+        if game.tick_count % fluid.flow_delay() != 0 {
+            continue;
+        }
+
+        world.despawn(notify);
+        if !flowed.insert(pos) {
+            continue;
+        }
+
+        let changes = flow(block.unwrap(), pos, |pos| game.block_at(pos));
+        for (pos, block) in changes {
+            game.set_block_at_with_cause(world, pos, block, cause.neighbor_of());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn world(blocks: &[(BlockPosition, BlockId)]) -> impl Fn(BlockPosition) -> Option<BlockId> {
+        let blocks: HashMap<_, _> = blocks.iter().copied().collect();
+        move |pos| Some(blocks.get(&pos).copied().unwrap_or_else(BlockId::air))
+    }
+
+    fn floor(y: i32) -> Vec<(BlockPosition, BlockId)> {
+        let mut blocks = Vec::new();
+        for x in -2..=2 {
+            for z in -2..=2 {
+                blocks.push((BlockPosition::new(x, y, z), BlockId::stone()));
+            }
+        }
+        blocks
+    }
+
+    #[test]
+    fn source_spreads_sideways_on_ground() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let changes = flow(BlockId::water(), pos, world(&floor(63)));
+
+        assert_eq!(changes.len(), 4);
+        assert!(changes
+            .iter()
+            .all(|(_, block)| *block == BlockId::water().with_water_level(1)));
+    }
+
+    #[test]
+    fn fluid_falls_into_air() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let changes = flow(BlockId::lava(), pos, world(&[]));
+
+        assert_eq!(
+            changes.as_slice(),
+            &[(
+                BlockPosition::new(0, 63, 0),
+                BlockId::lava().with_water_level(8)
+            )]
+        );
+    }
+
+    #[test]
+    fn lava_spreads_less_than_water() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let water = flow(
+            BlockId::water().with_water_level(6),
+            pos,
+            world(&[
+                (
+                    BlockPosition::new(1, 64, 0),
+                    BlockId::water().with_water_level(5),
+                ),
+                (BlockPosition::new(0, 63, 0), BlockId::stone()),
+            ]),
+        );
+        assert!(water
+            .iter()
+            .all(|(_, block)| *block == BlockId::water().with_water_level(7)));
+
+        let lava = flow(
+            BlockId::lava().with_water_level(6),
+            pos,
+            world(&[
+                (
+                    BlockPosition::new(1, 64, 0),
+                    BlockId::lava().with_water_level(4),
+                ),
+                (BlockPosition::new(0, 63, 0), BlockId::stone()),
+            ]),
+        );
+        assert!(lava.is_empty());
+    }
+
+    #[test]
+    fn unfed_fluid_drains() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let changes = flow(BlockId::water().with_water_level(3), pos, world(&floor(63)));
+
+        assert_eq!(changes.as_slice(), &[(pos, BlockId::air())]);
+    }
+
+    #[test]
+    fn water_between_sources_becomes_source() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let mut blocks = floor(63);
+        blocks.push((BlockPosition::new(1, 64, 0), BlockId::water()));
+        blocks.push((BlockPosition::new(-1, 64, 0), BlockId::water()));
+
+        let changes = flow(BlockId::water().with_water_level(1), pos, world(&blocks));
+        assert_eq!(changes.as_slice(), &[(pos, BlockId::water())]);
+    }
+
+    #[test]
+    fn lava_hardens_next_to_water() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let blocks = [(BlockPosition::new(0, 64, 1), BlockId::water())];
+
+        let source = flow(BlockId::lava(), pos, world(&blocks));
+        assert_eq!(source.as_slice(), &[(pos, BlockId::obsidian())]);
+
+        let flowing = flow(BlockId::lava().with_water_level(2), pos, world(&blocks));
+        assert_eq!(flowing.as_slice(), &[(pos, BlockId::cobblestone())]);
+    }
+}
@@ -0,0 +1,273 @@
+//! Foods and the status effects they apply when eaten.
+
+use crate::add_effect;
+use feather_core::items::{Item, ItemStack};
+use feather_server_types::{ActiveEffect, FoodEffect, Game, Hunger, StatusEffect, MAX_FOOD};
+use fecs::{Entity, World};
+use rand::Rng;
+use smallvec::SmallVec;
+
+/// Number of ticks it takes to eat most foods.
+pub const EAT_TICKS: u64 = 32;
+
+/// Returns whether an item can be eaten.
+pub fn is_food(item: Item) -> bool {
+    match item {
+        Item::Apple
+        | Item::MushroomStew
+        | Item::Bread
+        | Item::Porkchop
+        | Item::CookedPorkchop
+        | Item::GoldenApple
+        | Item::EnchantedGoldenApple
+        | Item::Cod
+        | Item::Salmon
+        | Item::TropicalFish
+        | Item::Pufferfish
+        | Item::CookedCod
+        | Item::CookedSalmon
+        | Item::Cookie
+        | Item::MelonSlice
+        | Item::Beef
+        | Item::CookedBeef
+        | Item::Chicken
+        | Item::CookedChicken
+        | Item::RottenFlesh
+        | Item::SpiderEye
+        | Item::Carrot
+        | Item::Potato
+        | Item::BakedPotato
+        | Item::PoisonousPotato
+        | Item::GoldenCarrot
+        | Item::PumpkinPie
+        | Item::Rabbit
+        | Item::CookedRabbit
+        | Item::RabbitStew
+        | Item::Mutton
+        | Item::CookedMutton
+        | Item::ChorusFruit
+        | Item::Beetroot
+        | Item::BeetrootSoup
+        | Item::DriedKelp => true,
+        _ => false,
+    }
+}
+
+/// Returns whether a food can be eaten by players who are not hungry.
+pub fn always_edible(item: Item) -> bool {
+    match item {
+        Item::GoldenApple | Item::EnchantedGoldenApple | Item::ChorusFruit => true,
+        _ => false,
+    }
+}
+
+/// Returns the food points restored by eating a food and
+/// its saturation modifier.
+pub fn food_points(item: Item) -> (i32, f32) {
+    match item {
+        Item::Apple => (4, 0.3),
+        Item::MushroomStew => (6, 0.6),
+        Item::Bread => (5, 0.6),
+        Item::Porkchop => (3, 0.3),
+        Item::CookedPorkchop => (8, 0.8),
+        Item::GoldenApple | Item::EnchantedGoldenApple => (4, 1.2),
+        Item::Cod | Item::Salmon => (2, 0.1),
+        Item::TropicalFish | Item::Pufferfish => (1, 0.1),
+        Item::CookedCod => (5, 0.6),
+        Item::CookedSalmon => (6, 0.8),
+        Item::Cookie => (2, 0.1),
+        Item::MelonSlice => (2, 0.3),
+        Item::Beef => (3, 0.3),
+        Item::CookedBeef => (8, 0.8),
+        Item::Chicken => (2, 0.3),
+        Item::CookedChicken => (6, 0.6),
+        Item::RottenFlesh => (4, 0.1),
+        Item::SpiderEye => (2, 0.8),
+        Item::Carrot => (3, 0.6),
+        Item::Potato => (1, 0.3),
+        Item::BakedPotato => (5, 0.6),
+        Item::PoisonousPotato => (2, 0.3),
+        Item::GoldenCarrot => (6, 1.2),
+        Item::PumpkinPie => (8, 0.3),
+        Item::Rabbit => (3, 0.3),
+        Item::CookedRabbit => (5, 0.6),
+        Item::RabbitStew => (10, 0.6),
+        Item::Mutton => (2, 0.3),
+        Item::CookedMutton => (6, 0.8),
+        Item::ChorusFruit => (4, 0.3),
+        Item::Beetroot => (1, 0.6),
+        Item::BeetrootSoup => (6, 0.6),
+        Item::DriedKelp => (1, 0.3),
+        _ => (0, 0.0),
+    }
+}
+
+/// Restores the food level and saturation given by eating a food.
+/// Saturation never exceeds the new food level.
+pub fn restore_hunger(hunger: &mut Hunger, item: Item) {
+    let (food, saturation_modifier) = food_points(item);
+    hunger.food = (hunger.food + food).min(MAX_FOOD);
+    hunger.saturation =
+        (hunger.saturation + food as f32 * saturation_modifier * 2.0).min(hunger.food as f32);
+}
+
+/// Returns the number of ticks it takes to eat a food.
+pub fn eating_ticks(item: Item) -> u64 {
+    match item {
+        Item::DriedKelp => EAT_TICKS / 2,
+        _ => EAT_TICKS,
+    }
+}
+
+/// Returns the item left over after eating a food.
+pub fn food_leftover(item: Item) -> Option<Item> {
+    match item {
+        Item::MushroomStew | Item::RabbitStew | Item::BeetrootSoup => Some(Item::Bowl),
+        _ => None,
+    }
+}
+
+/// Returns the custom effects stored in a food's NBT.
+pub fn custom_food_effects(stack: &ItemStack) -> SmallVec<[FoodEffect; 2]> {
+    stack
+        .nbt()
+        .map(|nbt| nbt.effects.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|effect| FoodEffect {
+            id: effect.id,
+            duration: effect.duration,
+            amplifier: effect.amplifier.max(0) as u8,
+        })
+        .collect()
+}
+
+/// Returns the effects applied by eating a food, including
+/// those given by its `custom` effects.
+pub fn food_effects(
+    item: Item,
+    custom: &[FoodEffect],
+    rng: &mut impl Rng,
+) -> SmallVec<[(StatusEffect, ActiveEffect); 4]> {
+    use StatusEffect::*;
+
+    // (effect, amplifier, duration, probability)
+    let builtin: &[(StatusEffect, u8, u32, f64)] = match item {
+        Item::GoldenApple => &[(Regeneration, 1, 100, 1.0), (Absorption, 0, 2400, 1.0)],
+        Item::EnchantedGoldenApple => &[
+            (Regeneration, 1, 400, 1.0),
+            (Resistance, 0, 6000, 1.0),
+            (FireResistance, 0, 6000, 1.0),
+            (Absorption, 3, 2400, 1.0),
+        ],
+        Item::Pufferfish => &[
+            (Poison, 3, 1200, 1.0),
+            (Hunger, 2, 300, 1.0),
+            (Nausea, 1, 300, 1.0),
+        ],
+        Item::RottenFlesh => &[(Hunger, 0, 600, 0.8)],
+        Item::Chicken => &[(Hunger, 0, 600, 0.3)],
+        Item::SpiderEye => &[(Poison, 0, 100, 1.0)],
+        Item::PoisonousPotato => &[(Poison, 0, 100, 0.6)],
+        _ => &[],
+    };
+
+    let mut effects: SmallVec<[(StatusEffect, ActiveEffect); 4]> = builtin
+        .iter()
+        .filter(|(_, _, _, probability)| rng.gen_bool(*probability))
+        .map(|&(effect, amplifier, duration, _)| (effect, ActiveEffect::new(amplifier, duration)))
+        .collect();
+
+    effects.extend(custom.iter().filter_map(|custom| {
+        let effect = StatusEffect::from_id(custom.id)?;
+        Some((
+            effect,
+            ActiveEffect::new(custom.amplifier, custom.duration.max(0) as u32),
+        ))
+    }));
+
+    effects
+}
+
+/// Applies the effects of eating a food to an entity.
+pub fn apply_food_effects(game: &Game, world: &mut World, entity: Entity, food: &ItemStack) {
+    let effects = food_effects(food.ty, &custom_food_effects(food), &mut *game.rng());
+    for (effect, active) in effects {
+        add_effect(game, world, entity, effect, active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::items::{FoodEffectNbt, ItemNbt};
+
+    #[test]
+    fn custom_effects_are_read_from_nbt() {
+        let stew = ItemStack::new(Item::MushroomStew, 1).with_nbt(ItemNbt {
+            effects: vec![FoodEffectNbt {
+                id: StatusEffect::Saturation.id(),
+                duration: 7,
+                amplifier: 0,
+            }],
+            ..Default::default()
+        });
+        assert_eq!(
+            custom_food_effects(&stew).as_slice(),
+            &[FoodEffect {
+                id: StatusEffect::Saturation.id(),
+                duration: 7,
+                amplifier: 0,
+            }]
+        );
+        assert!(custom_food_effects(&ItemStack::new(Item::Bread, 1)).is_empty());
+    }
+
+    #[test]
+    fn custom_effects_are_applied() {
+        let custom = [
+            FoodEffect {
+                id: StatusEffect::NightVision.id(),
+                duration: 100,
+                amplifier: 0,
+            },
+            // Unknown effects are ignored.
+            FoodEffect {
+                id: 100,
+                duration: 100,
+                amplifier: 0,
+            },
+        ];
+        let effects = food_effects(Item::Bread, &custom, &mut rand::thread_rng());
+        assert_eq!(
+            effects.as_slice(),
+            &[(StatusEffect::NightVision, ActiveEffect::new(0, 100))]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn eating_restores_hunger() {
+        let mut hunger = Hunger {
+            food: 10,
+            saturation: 0.0,
+            ..Hunger::default()
+        };
+        restore_hunger(&mut hunger, Item::CookedBeef);
+        assert_eq!(hunger.food, 18);
+        assert_eq!(hunger.saturation, 12.8);
+
+        // Food is capped at the maximum and saturation
+        // at the food level.
+        restore_hunger(&mut hunger, Item::RabbitStew);
+        assert_eq!(hunger.food, MAX_FOOD);
+        assert_eq!(hunger.saturation, 20.0);
+    }
+
+    #[test]
+    fn golden_apple_effects() {
+        let effects = food_effects(Item::GoldenApple, &[], &mut rand::thread_rng());
+        assert_eq!(effects.len(), 2);
+        assert_eq!(effects[0].0, StatusEffect::Regeneration);
+    }
+}
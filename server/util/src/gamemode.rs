@@ -0,0 +1,170 @@
+//! Gamemodes, which decide whether players can fly, break
+//! blocks instantly, take damage, and use up items.
+
+use feather_core::network::packets::{
+    ChangeGameState, PlayerAbilitiesClientbound, PlayerInfo, PlayerInfoAction,
+};
+use feather_core::util::Gamemode;
+use feather_server_types::{Flying, Game, GamemodeUpdateEvent, Network, Uuid};
+use fecs::{Entity, World};
+
+/// Reason sent in Change Game State when a player's gamemode changes.
+const GAME_STATE_CHANGE_GAMEMODE: u8 = 3;
+
+/// Flags of the Player Abilities packet.
+const ABILITY_INVULNERABLE: u8 = 0x01;
+const ABILITY_FLYING: u8 = 0x02;
+const ABILITY_ALLOW_FLYING: u8 = 0x04;
+const ABILITY_INSTANT_BREAK: u8 = 0x08;
+
+/// Vanilla flying speed of players, which servers may
+/// change in the configuration.
+pub const FLYING_SPEED: f32 = 0.05;
+
+/// Default walking speed of players, which the client
+/// uses to compute its field of view.
+pub const WALKING_SPEED: f32 = 0.1;
+
+/// Returns whether players in a gamemode may fly.
+pub fn can_fly(gamemode: Gamemode) -> bool {
+    gamemode == Gamemode::Creative || gamemode == Gamemode::Spectator
+}
+
+/// Returns whether players in a gamemode are immune to damage.
+pub fn is_invulnerable(gamemode: Gamemode) -> bool {
+    gamemode == Gamemode::Creative || gamemode == Gamemode::Spectator
+}
+
+/// Returns whether players in a gamemode break blocks instantly.
+pub fn breaks_instantly(gamemode: Gamemode) -> bool {
+    gamemode == Gamemode::Creative
+}
+
+/// Returns whether players in a gamemode use up the
+/// items they place, eat, or throw.
+pub fn consumes_items(gamemode: Gamemode) -> bool {
+    gamemode != Gamemode::Creative
+}
+
+/// Returns whether players in a gamemode may interact with blocks
+/// and items. Spectators pass through the world without changing it.
+pub fn can_interact(gamemode: Gamemode) -> bool {
+    gamemode != Gamemode::Spectator
+}
+
+/// Returns the gamemode in which new players join the world.
+pub fn default_gamemode(game: &Game) -> Gamemode {
+    Gamemode::from_id(game.level.game_type as u8)
+}
+
+/// Builds the Player Abilities packet for a player in the given gamemode.
+pub fn abilities_packet(
+    gamemode: Gamemode,
+    flying: bool,
+    flying_speed: f32,
+) -> PlayerAbilitiesClientbound {
+    let mut flags = 0;
+    if is_invulnerable(gamemode) {
+        flags |= ABILITY_INVULNERABLE;
+    }
+    if can_fly(gamemode) {
+        flags |= ABILITY_ALLOW_FLYING;
+    }
+    // Spectators cannot stop flying.
+    if flying || gamemode == Gamemode::Spectator {
+        flags |= ABILITY_FLYING;
+    }
+    if breaks_instantly(gamemode) {
+        flags |= ABILITY_INSTANT_BREAK;
+    }
+
+    PlayerAbilitiesClientbound {
+        flags,
+        flying_speed,
+        field_of_view_modifier: WALKING_SPEED,
+    }
+}
+
+/// Sends a player their abilities, which tell their client
+/// whether they may fly and how fast.
+pub fn send_abilities(game: &Game, world: &World, player: Entity) {
+    let packet = abilities_packet(
+        *world.get::<Gamemode>(player),
+        world.has::<Flying>(player),
+        game.config.gameplay.flying_speed,
+    );
+    world.get::<Network>(player).send(packet);
+}
+
+/// Starts or stops a player flying.
+pub fn set_flying(world: &mut World, player: Entity, flying: bool) {
+    if flying && !world.has::<Flying>(player) {
+        world.add(player, Flying).unwrap();
+    } else if !flying && world.has::<Flying>(player) {
+        world.remove::<Flying>(player).unwrap();
+    }
+}
+
+/// Changes a player's gamemode, notifying their client and
+/// updating the player list of all players.
+pub fn set_gamemode(game: &mut Game, world: &mut World, player: Entity, gamemode: Gamemode) {
+    let old = *world.get::<Gamemode>(player);
+    if old == gamemode {
+        return;
+    }
+    *world.get_mut::<Gamemode>(player) = gamemode;
+
+    // Spectators always fly, while players in other
+    // gamemodes need to be allowed to.
+    if gamemode == Gamemode::Spectator {
+        set_flying(world, player, true);
+    } else if !can_fly(gamemode) {
+        set_flying(world, player, false);
+    }
+
+    world.get::<Network>(player).send(ChangeGameState {
+        reason: GAME_STATE_CHANGE_GAMEMODE,
+        value: f32::from(gamemode.id()),
+    });
+    send_abilities(game, world, player);
+
+    let packet = PlayerInfo {
+        action: PlayerInfoAction::UpdateGamemode(gamemode),
+        uuid: *world.get::<Uuid>(player),
+    };
+    game.broadcast_global(world, packet, None);
+
+    game.handle(
+        world,
+        GamemodeUpdateEvent {
+            player,
+            old,
+            new: gamemode,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abilities_flags() {
+        assert_eq!(
+            abilities_packet(Gamemode::Survival, false, FLYING_SPEED).flags,
+            0
+        );
+        assert_eq!(
+            abilities_packet(Gamemode::Creative, false, FLYING_SPEED).flags,
+            ABILITY_INVULNERABLE | ABILITY_ALLOW_FLYING | ABILITY_INSTANT_BREAK
+        );
+        assert_eq!(
+            abilities_packet(Gamemode::Creative, true, FLYING_SPEED).flags,
+            ABILITY_INVULNERABLE | ABILITY_FLYING | ABILITY_ALLOW_FLYING | ABILITY_INSTANT_BREAK
+        );
+        assert_eq!(
+            abilities_packet(Gamemode::Spectator, false, FLYING_SPEED).flags,
+            ABILITY_INVULNERABLE | ABILITY_FLYING | ABILITY_ALLOW_FLYING
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Growth of plants fertilized with bone meal.
+
+use feather_core::blocks::{BlockId, BlockKind};
+use feather_core::items::{Item, ItemStack};
+use feather_core::util::BlockPosition;
+use rand::Rng;
+
+/// The effect of bone meal applied to a block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Growth {
+    /// The given blocks should be set. This may be empty
+    /// if the bone meal was used up without effect, as
+    /// saplings only grow some of the time.
+    Blocks(Vec<(BlockPosition, BlockId)>),
+    /// The plant should drop a copy of itself.
+    Drop(ItemStack),
+}
+
+/// Chance for a sapling fertilized with bone meal to grow.
+const SAPLING_GROWTH_CHANCE: f64 = 0.45;
+
+/// Number of attempts made to place plants
+/// around a fertilized grass block.
+const GRASS_ATTEMPTS: u32 = 128;
+
+/// Returns the effect of applying bone meal to `block` at `pos`,
+/// or `None` if bone meal can't be used on it. `block_at` is
+/// used to look up surrounding blocks.
+pub fn fertilize(
+    block: BlockId,
+    pos: BlockPosition,
+    block_at: impl Fn(BlockPosition) -> Option<BlockId>,
+    rng: &mut impl Rng,
+) -> Option<Growth> {
+    use BlockKind::*;
+
+    match block.kind() {
+        Wheat | Carrots | Potatoes | MelonStem | PumpkinStem => {
+            let age = block.age_0_7()?;
+            grow(age, 7, rng).map(|age| single(pos, block.with_age_0_7(age)))
+        }
+        Beetroots => {
+            let age = block.age_0_3()?;
+            grow(age, 3, rng).map(|age| single(pos, block.with_age_0_3(age)))
+        }
+        Cocoa => {
+            let age = block.age_0_2()?;
+            if age >= 2 {
+                return None;
+            }
+            Some(single(pos, block.with_age_0_2(age + 1)))
+        }
+        OakSapling | SpruceSapling | BirchSapling | JungleSapling | AcaciaSapling
+        | DarkOakSapling => {
+            if !rng.gen_bool(SAPLING_GROWTH_CHANCE) {
+                return Some(Growth::Blocks(vec![]));
+            }
+            if block.stage() == Some(0) {
+                return Some(single(pos, block.with_stage(1)));
+            }
+            Some(Growth::Blocks(
+                tree(block.kind(), pos, block_at, rng).unwrap_or_default(),
+            ))
+        }
+        GrassBlock => {
+            let up = BlockPosition::new(0, 1, 0);
+            if !block_at(pos + up).map_or(false, BlockId::is_air) {
+                return None;
+            }
+            Some(Growth::Blocks(grass_plants(pos, block_at, rng)))
+        }
+        Sunflower | Lilac | RoseBush | Peony => {
+            let item = Item::from_identifier(block.identifier())?;
+            Some(Growth::Drop(ItemStack::new(item, 1)))
+        }
+        _ => None,
+    }
+}
+
+fn single(pos: BlockPosition, block: BlockId) -> Growth {
+    Growth::Blocks(vec![(pos, block)])
+}
+
+/// Advances a crop by two to five growth stages.
+/// Returns `None` if it is fully grown already.
+fn grow(age: i32, max_age: i32, rng: &mut impl Rng) -> Option<i32> {
+    if age >= max_age {
+        return None;
+    }
+    Some((age + rng.gen_range(2, 6)).min(max_age))
+}
+
+/// Computes the blocks of a tree grown from the sapling at
+/// `pos`, or `None` if there is no room for it.
+fn tree(
+    sapling: BlockKind,
+    pos: BlockPosition,
+    block_at: impl Fn(BlockPosition) -> Option<BlockId>,
+    rng: &mut impl Rng,
+) -> Option<Vec<(BlockPosition, BlockId)>> {
+    let (log, leaves, min_height, extra_height) = match sapling {
+        BlockKind::OakSapling => (BlockId::oak_log(), BlockId::oak_leaves(), 4, 3),
+        BlockKind::SpruceSapling => (BlockId::spruce_log(), BlockId::spruce_leaves(), 6, 4),
+        BlockKind::BirchSapling => (BlockId::birch_log(), BlockId::birch_leaves(), 5, 3),
+        BlockKind::JungleSapling => (BlockId::jungle_log(), BlockId::jungle_leaves(), 4, 7),
+        BlockKind::AcaciaSapling => (BlockId::acacia_log(), BlockId::acacia_leaves(), 5, 3),
+        // TODO: dark oak trees grow from four saplings
+        // planted in a square.
+        _ => return None,
+    };
+    let height = min_height + rng.gen_range(0, extra_height);
+    if pos.y + height + 1 >= 256 {
+        return None;
+    }
+
+    let at = |x, y, z| pos + BlockPosition::new(x, y, z);
+    let has_room = (1..=height).all(|y| {
+        block_at(at(0, y, 0)).map_or(false, |block| {
+            block.is_air() || block.kind() == leaves.kind()
+        })
+    });
+    if !has_room {
+        return None;
+    }
+
+    let mut blocks = Vec::new();
+
+    // Leaves cover the top of the trunk in layers which
+    // narrow towards the top, with corners randomly missing.
+    for y in height - 3..=height {
+        let layer = y - height;
+        let radius = 1 - layer / 2;
+        for x in -radius..=radius {
+            for z in -radius..=radius {
+                let corner = x.abs() == radius && z.abs() == radius;
+                if corner && (layer == 0 || rng.gen_bool(0.5)) {
+                    continue;
+                }
+                if x == 0 && z == 0 && y < height {
+                    continue; // the trunk
+                }
+                if !block_at(at(x, y, z)).map_or(false, BlockId::is_air) {
+                    continue;
+                }
+
+                // The distance to the nearest log, which
+                // keeps leaves from decaying.
+                let distance = x.abs() + z.abs() + (y >= height) as i32;
+                blocks.push((at(x, y, z), leaves.with_distance(distance.min(7))));
+            }
+        }
+    }
+
+    blocks.extend((0..height).map(|y| (at(0, y, 0), log)));
+    Some(blocks)
+}
+
+/// Computes the grass and flowers which grow
+/// around a fertilized grass block.
+fn grass_plants(
+    pos: BlockPosition,
+    block_at: impl Fn(BlockPosition) -> Option<BlockId>,
+    rng: &mut impl Rng,
+) -> Vec<(BlockPosition, BlockId)> {
+    let up = BlockPosition::new(0, 1, 0);
+    let mut plants: Vec<(BlockPosition, BlockId)> = Vec::new();
+
+    'attempts: for attempt in 0..GRASS_ATTEMPTS {
+        // Later attempts wander further from the grass block.
+        let mut target = pos + up;
+        for _ in 0..attempt / 16 {
+            let dy = rng.gen_range(-1, 2) * rng.gen_range(0, 3) / 2;
+            target = target + BlockPosition::new(rng.gen_range(-1, 2), dy, rng.gen_range(-1, 2));
+
+            let on_grass =
+                block_at(target - up).map_or(false, |below| below.kind() == BlockKind::GrassBlock);
+            let blocked = block_at(target).map_or(true, BlockId::is_solid);
+            if !on_grass || blocked {
+                continue 'attempts;
+            }
+        }
+
+        let free = block_at(target).map_or(false, BlockId::is_air)
+            && !plants.iter().any(|(pos, _)| *pos == target);
+        if !free {
+            continue;
+        }
+
+        let plant = match rng.gen_range(0, 16) {
+            0 => BlockId::dandelion(),
+            1 => BlockId::poppy(),
+            _ => BlockId::grass(),
+        };
+        plants.push((target, plant));
+    }
+
+    plants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+    use std::collections::HashMap;
+
+    fn world(blocks: &[(BlockPosition, BlockId)]) -> impl Fn(BlockPosition) -> Option<BlockId> {
+        let blocks: HashMap<_, _> = blocks.iter().copied().collect();
+        move |pos| Some(blocks.get(&pos).copied().unwrap_or_else(BlockId::air))
+    }
+
+    #[test]
+    fn crops_grow_until_fully_grown() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let mut rng = StepRng::new(0, 0);
+
+        assert_eq!(
+            fertilize(BlockId::wheat(), pos, world(&[]), &mut rng),
+            Some(Growth::Blocks(vec![(
+                pos,
+                BlockId::wheat().with_age_0_7(2)
+            )]))
+        );
+        assert_eq!(
+            fertilize(BlockId::wheat().with_age_0_7(6), pos, world(&[]), &mut rng),
+            Some(Growth::Blocks(vec![(
+                pos,
+                BlockId::wheat().with_age_0_7(7)
+            )]))
+        );
+        assert_eq!(
+            fertilize(BlockId::wheat().with_age_0_7(7), pos, world(&[]), &mut rng),
+            None
+        );
+    }
+
+    #[test]
+    fn sapling_grows_into_tree() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let mut rng = StepRng::new(0, 0);
+        let sapling = BlockId::oak_sapling().with_stage(1);
+
+        let blocks = match fertilize(sapling, pos, world(&[]), &mut rng) {
+            Some(Growth::Blocks(blocks)) => blocks,
+            growth => panic!("unexpected growth {:?}", growth),
+        };
+        for y in 0..4 {
+            assert!(blocks.contains(&(pos + BlockPosition::new(0, y, 0), BlockId::oak_log())));
+        }
+        assert!(blocks
+            .iter()
+            .any(|(_, block)| block.kind() == BlockKind::OakLeaves));
+    }
+
+    #[test]
+    fn tree_needs_room() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let mut rng = StepRng::new(0, 0);
+        let sapling = BlockId::oak_sapling().with_stage(1);
+        let roof = world(&[(BlockPosition::new(0, 66, 0), BlockId::stone())]);
+
+        assert_eq!(
+            fertilize(sapling, pos, roof, &mut rng),
+            Some(Growth::Blocks(vec![]))
+        );
+    }
+
+    #[test]
+    fn tall_flowers_drop_copy() {
+        let pos = BlockPosition::new(0, 64, 0);
+        let mut rng = StepRng::new(0, 0);
+
+        assert_eq!(
+            fertilize(BlockId::sunflower(), pos, world(&[]), &mut rng),
+            Some(Growth::Drop(ItemStack::new(Item::Sunflower, 1)))
+        );
+        assert_eq!(fertilize(BlockId::poppy(), pos, world(&[]), &mut rng), None);
+    }
+}
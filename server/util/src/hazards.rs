@@ -0,0 +1,76 @@
+//! Damage dealt to entities by their surroundings:
+//! suffocating inside blocks and falling out of the world.
+
+use crate::{eye_height, has_invulnerable_gamemode};
+use feather_core::blocks::BlockId;
+use feather_core::util::Position;
+use feather_server_types::{BumpVec, DamageCause, EntityDamageRequest, Game, Health};
+use fecs::{component, IntoQuery, Read, World};
+use nalgebra_glm::vec3;
+
+/// Damage dealt each tick to entities whose eyes are inside a block.
+/// Invulnerability after being damaged limits this to twice a second.
+pub const SUFFOCATION_DAMAGE: f32 = 1.0;
+
+/// Damage dealt each tick to entities below `VOID_Y`.
+pub const VOID_DAMAGE: f32 = 4.0;
+
+/// Height below which entities take void damage.
+pub const VOID_Y: f64 = -64.0;
+
+/// Returns whether entities suffocate with their eyes inside a block.
+pub fn suffocates(block: BlockId) -> bool {
+    block.is_opaque() && !block.is_fluid()
+}
+
+/// System which damages living entities which are
+/// suffocating or have fallen into the void.
+#[fecs::system]
+pub fn apply_environmental_damage(game: &mut Game, world: &mut World) {
+    let mut damaged = BumpVec::new_in(game.bump());
+
+    for (entity, pos) in <Read<Position>>::query()
+        .filter(component::<Health>())
+        .iter_entities(world.inner())
+    {
+        // The void damages players in every gamemode.
+        if pos.y < VOID_Y {
+            damaged.push((entity, VOID_DAMAGE, DamageCause::Void));
+            continue;
+        }
+
+        if has_invulnerable_gamemode(world, entity) {
+            continue;
+        }
+        let eyes = match eye_height(world, entity) {
+            Some(height) => *pos + vec3(0.0, height, 0.0),
+            None => continue,
+        };
+        if game.block_at(eyes.block()).map_or(false, suffocates) {
+            damaged.push((entity, SUFFOCATION_DAMAGE, DamageCause::Suffocation));
+        }
+    }
+
+    for (entity, damage, cause) in damaged {
+        game.handle(
+            world,
+            EntityDamageRequest {
+                entity,
+                damage,
+                cause,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suffocating_blocks() {
+        assert!(suffocates(BlockId::stone()));
+        assert!(!suffocates(BlockId::air()));
+        assert!(!suffocates(BlockId::water()));
+    }
+}
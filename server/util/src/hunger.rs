@@ -0,0 +1,252 @@
+//! Natural regeneration of health from food, starvation,
+//! and sending players their health and hunger.
+
+use crate::has_invulnerable_gamemode;
+use feather_core::network::packets::UpdateHealth;
+use feather_core::util::Difficulty;
+use feather_server_types::{
+    attribute_value, Attribute, BumpVec, DamageCause, EntityDamageRequest, Game, Health, Hunger,
+    Network, EXHAUSTION_PER_FOOD, MAX_FOOD, NATURAL_REGENERATION,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+
+/// Food level at or above which players regenerate health.
+pub const REGENERATION_FOOD: i32 = 18;
+
+/// Ticks between regenerating health from saturation,
+/// when players have a full food level.
+const SATURATED_REGENERATION_TICKS: u32 = 10;
+
+/// Ticks between regenerating health from food.
+const REGENERATION_TICKS: u32 = 80;
+
+/// Exhaustion added by regenerating a half-heart from food.
+const REGENERATION_EXHAUSTION: f32 = 6.0;
+
+/// Damage dealt to starving players every `REGENERATION_TICKS`.
+pub const STARVATION_DAMAGE: f32 = 1.0;
+
+/// Sends a player their health, food level, and saturation.
+pub fn send_health(world: &World, player: Entity) {
+    let network = match world.try_get::<Network>(player) {
+        Some(network) => network,
+        None => return,
+    };
+    let hunger = world
+        .try_get::<Hunger>(player)
+        .map_or_else(Hunger::default, |hunger| *hunger);
+
+    network.send(UpdateHealth {
+        health: world.get::<Health>(player).0,
+        food: hunger.food,
+        food_saturation: hunger.saturation,
+    });
+}
+
+/// Heals an entity by the given amount, up to its maximum health.
+pub fn heal(world: &mut World, entity: Entity, amount: f32) {
+    let max_health = attribute_value(world, entity, Attribute::MaxHealth) as f32;
+    {
+        let mut health = world.get_mut::<Health>(entity);
+        if health.0 <= 0.0 {
+            return;
+        }
+        health.0 = (health.0 + amount).min(max_health);
+    }
+    send_health(world, entity);
+}
+
+/// Uses up saturation, then food, for each `EXHAUSTION_PER_FOOD`
+/// of exhaustion. Returns whether hunger changed.
+///
+/// Players do not lose food on peaceful difficulty.
+pub fn use_exhaustion(hunger: &mut Hunger, peaceful: bool) -> bool {
+    if hunger.exhaustion <= EXHAUSTION_PER_FOOD {
+        return false;
+    }
+
+    hunger.exhaustion -= EXHAUSTION_PER_FOOD;
+    if hunger.saturation > 0.0 {
+        hunger.saturation = (hunger.saturation - 1.0).max(0.0);
+    } else if !peaceful {
+        hunger.food = (hunger.food - 1).max(0);
+    }
+    true
+}
+
+/// Advances natural regeneration by a tick, returning the
+/// health regenerated and exhausting the player accordingly.
+pub fn regenerate(hunger: &mut Hunger, can_heal: bool) -> f32 {
+    if !can_heal || hunger.food < REGENERATION_FOOD {
+        hunger.regeneration_timer = 0;
+        return 0.0;
+    }
+
+    hunger.regeneration_timer += 1;
+
+    // Players with a full food level heal quickly,
+    // using up their saturation.
+    if hunger.food >= MAX_FOOD && hunger.saturation > 0.0 {
+        if hunger.regeneration_timer >= SATURATED_REGENERATION_TICKS {
+            hunger.regeneration_timer = 0;
+            let used = hunger.saturation.min(REGENERATION_EXHAUSTION);
+            hunger.add_exhaustion(used);
+            return used / REGENERATION_EXHAUSTION;
+        }
+    } else if hunger.regeneration_timer >= REGENERATION_TICKS {
+        hunger.regeneration_timer = 0;
+        hunger.add_exhaustion(REGENERATION_EXHAUSTION);
+        return 1.0;
+    }
+
+    0.0
+}
+
+/// Advances starvation by a tick for a player with no food left,
+/// returning whether they should take starvation damage. The timer
+/// is shared with regeneration, as players cannot do both.
+///
+/// Starvation stops at 10 health on easy and peaceful difficulty
+/// and at 1 health on normal, but can kill on hard.
+pub fn starve(hunger: &mut Hunger, health: f32, difficulty: Difficulty) -> bool {
+    if hunger.food > 0 {
+        return false;
+    }
+
+    hunger.regeneration_timer += 1;
+    if hunger.regeneration_timer < REGENERATION_TICKS {
+        return false;
+    }
+    hunger.regeneration_timer = 0;
+
+    match difficulty {
+        Difficulty::Hard => true,
+        Difficulty::Medium => health > 1.0,
+        Difficulty::Easy | Difficulty::Peaceful => health > 10.0,
+    }
+}
+
+/// System which uses up exhaustion, regenerates the health
+/// of players who are well fed and damages those who starve.
+#[fecs::system]
+pub fn update_hunger(game: &mut Game, world: &mut World) {
+    let natural_regeneration = game.game_rules.bool(NATURAL_REGENERATION);
+    let difficulty = game.difficulty();
+    let peaceful = difficulty == Difficulty::Peaceful;
+
+    let mut players = BumpVec::new_in(game.bump());
+    for (player, _) in <Read<Hunger>>::query()
+        .filter(component::<Health>())
+        .iter_entities(world.inner())
+    {
+        players.push(player);
+    }
+
+    for player in players {
+        let health = world.get::<Health>(player).0;
+        let max_health = attribute_value(world, player, Attribute::MaxHealth) as f32;
+        let can_heal = natural_regeneration && health > 0.0 && health < max_health;
+
+        let starves = !has_invulnerable_gamemode(world, player);
+
+        let (changed, amount, starving) = {
+            let mut hunger = world.get_mut::<Hunger>(player);
+            let changed = use_exhaustion(&mut hunger, peaceful);
+            if hunger.food == 0 {
+                let starving = starves && starve(&mut hunger, health, difficulty);
+                (changed, 0.0, starving)
+            } else {
+                (changed, regenerate(&mut hunger, can_heal), false)
+            }
+        };
+
+        if starving {
+            game.handle(
+                world,
+                EntityDamageRequest {
+                    entity: player,
+                    damage: STARVATION_DAMAGE,
+                    cause: DamageCause::Starvation,
+                },
+            );
+        } else if amount > 0.0 {
+            heal(world, player, amount);
+        } else if changed {
+            send_health(world, player);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exhaustion_uses_saturation_then_food() {
+        let mut hunger = Hunger {
+            saturation: 0.5,
+            exhaustion: 8.5,
+            ..Hunger::default()
+        };
+        assert!(use_exhaustion(&mut hunger, false));
+        assert_eq!(hunger.saturation, 0.0);
+        assert_eq!(hunger.food, MAX_FOOD);
+
+        assert!(use_exhaustion(&mut hunger, false));
+        assert_eq!(hunger.food, MAX_FOOD - 1);
+        assert!(!use_exhaustion(&mut hunger, false));
+    }
+
+    #[test]
+    fn slow_regeneration_when_not_saturated() {
+        let mut hunger = Hunger {
+            food: REGENERATION_FOOD,
+            saturation: 0.0,
+            ..Hunger::default()
+        };
+        for _ in 1..REGENERATION_TICKS {
+            assert_eq!(regenerate(&mut hunger, true), 0.0);
+        }
+        assert_eq!(regenerate(&mut hunger, true), 1.0);
+        assert_eq!(hunger.exhaustion, REGENERATION_EXHAUSTION);
+
+        hunger.food = REGENERATION_FOOD - 1;
+        assert_eq!(regenerate(&mut hunger, true), 0.0);
+        assert_eq!(hunger.regeneration_timer, 0);
+    }
+
+    #[test]
+    fn starvation_limits_by_difficulty() {
+        let mut hunger = Hunger {
+            food: 0,
+            saturation: 0.0,
+            ..Hunger::default()
+        };
+        for _ in 1..REGENERATION_TICKS {
+            assert!(!starve(&mut hunger, 20.0, Difficulty::Easy));
+        }
+        assert!(starve(&mut hunger, 20.0, Difficulty::Easy));
+
+        let mut starved = |health, difficulty| {
+            hunger.regeneration_timer = REGENERATION_TICKS - 1;
+            starve(&mut hunger, health, difficulty)
+        };
+        assert!(!starved(10.0, Difficulty::Easy));
+        assert!(starved(10.0, Difficulty::Medium));
+        assert!(!starved(1.0, Difficulty::Medium));
+        assert!(starved(1.0, Difficulty::Hard));
+    }
+
+    #[test]
+    fn fast_regeneration_uses_saturation() {
+        let mut hunger = Hunger {
+            saturation: 3.0,
+            ..Hunger::default()
+        };
+        for _ in 1..SATURATED_REGENERATION_TICKS {
+            regenerate(&mut hunger, true);
+        }
+        assert_eq!(regenerate(&mut hunger, true), 0.5);
+        assert_eq!(hunger.exhaustion, 3.0);
+    }
+}
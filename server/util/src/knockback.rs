@@ -0,0 +1,92 @@
+//! Knocking entities back.
+
+use feather_core::util::Position;
+use feather_server_types::{attribute_value, Attribute, Game, Velocity};
+use fecs::{Entity, World};
+use nalgebra_glm::{vec3, DVec3};
+
+/// Strength of the knockback dealt by an attack.
+pub const ATTACK_KNOCKBACK: f64 = 0.4;
+
+/// Strength of the extra knockback added by each level of the
+/// Knockback enchantment and by attacking while sprinting.
+pub const EXTRA_KNOCKBACK: f64 = 0.5;
+
+/// Returns the fraction of knockback an entity resists, given by
+/// its `generic.knockbackResistance` attribute.
+pub fn knockback_resistance(world: &World, entity: Entity) -> f64 {
+    attribute_value(world, entity, Attribute::KnockbackResistance)
+}
+
+/// Knocks an entity back with the given strength, away
+/// from the horizontal direction `(dx, dz)`.
+///
+/// The strength is reduced by the entity's knockback resistance.
+pub fn knock_back(game: &Game, world: &mut World, entity: Entity, strength: f64, dx: f64, dz: f64) {
+    let strength = resisted_strength(strength, knockback_resistance(world, entity));
+    if strength <= 0.0 {
+        return;
+    }
+
+    let on_ground = match world.try_get::<Position>(entity) {
+        Some(pos) => pos.on_ground,
+        None => return,
+    };
+    let velocity = world
+        .try_get::<Velocity>(entity)
+        .map_or_else(|| vec3(0.0, 0.0, 0.0), |velocity| velocity.0);
+
+    game.set_velocity(
+        world,
+        entity,
+        knockback_velocity(velocity, on_ground, strength, dx, dz),
+    );
+}
+
+/// Returns the strength of knockback after resistance is applied.
+fn resisted_strength(strength: f64, resistance: f64) -> f64 {
+    strength * (1.0 - resistance.max(0.0).min(1.0))
+}
+
+/// Computes the velocity of an entity after being knocked back.
+fn knockback_velocity(velocity: DVec3, on_ground: bool, strength: f64, dx: f64, dz: f64) -> DVec3 {
+    let length = (dx * dx + dz * dz).sqrt();
+    let mut result = velocity / 2.0;
+    if length > 0.0 {
+        result.x -= dx / length * strength;
+        result.z -= dz / length * strength;
+    }
+
+    if on_ground {
+        result.y = (result.y + strength).min(0.4);
+    } else {
+        result.y = velocity.y;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knocked_away_from_source() {
+        let velocity = knockback_velocity(vec3(0.0, 0.0, 0.0), true, 0.5, 1.0, 0.0);
+        assert!(velocity.x < 0.0);
+        assert!(velocity.z.abs() < 1e-9);
+        // vertical knockback is capped
+        assert!((velocity.y - 0.4).abs() < 1e-9);
+
+        let airborne = knockback_velocity(vec3(0.0, -0.2, 0.0), false, 0.5, 0.0, 1.0);
+        assert!((airborne.y + 0.2).abs() < 1e-9);
+        assert!((airborne.z + 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn resistance_reduces_knockback() {
+        assert!((resisted_strength(0.4, 0.0) - 0.4).abs() < 1e-9);
+        assert!((resisted_strength(0.4, 0.5) - 0.2).abs() < 1e-9);
+        assert!(resisted_strength(0.4, 1.0).abs() < 1e-9);
+    }
+}
@@ -6,15 +6,82 @@ use arrayvec::ArrayVec;
 use feather_core::util::{BlockPosition, ChunkPosition, Position};
 use nalgebra_glm::{vec3, DVec3};
 
+mod adventure;
+pub use adventure::*;
+mod air;
+pub use air::*;
+mod armor;
+pub use armor::*;
+mod attributes;
+pub use attributes::*;
 mod block;
 pub use block::*;
+mod block_drops;
+pub use block_drops::*;
+mod block_support;
+pub use block_support::*;
+mod burning;
+pub use burning::*;
 mod chunk_entities;
 pub use chunk_entities::*;
+mod cooldowns;
+pub use cooldowns::*;
+mod damage;
+pub use damage::*;
+mod difficulty;
+pub use difficulty::*;
+mod digging;
+pub use digging::*;
+mod effects;
+pub use effects::*;
+mod enchantments;
+pub use enchantments::*;
+mod drops;
+pub use drops::*;
+mod durability;
+pub use durability::*;
+mod dye;
+pub use dye::*;
+mod equipment;
+pub use equipment::*;
+mod entity_limits;
+pub use entity_limits::*;
+mod fall;
+pub use fall::*;
+mod fluids;
+pub use fluids::*;
+mod food;
+pub use food::*;
+mod gamemode;
+pub use gamemode::*;
+mod growth;
+pub use growth::*;
+mod hazards;
+pub use hazards::*;
+mod hunger;
+pub use hunger::*;
+mod knockback;
+pub use knockback::*;
 mod time;
 pub use time::*;
+mod totem;
+pub use totem::*;
 mod load;
 pub use load::*;
+mod maps;
+pub use maps::*;
+mod scoreboard;
+pub use scoreboard::*;
+mod shield;
+pub use shield::*;
+mod plugin_message;
+pub use plugin_message::*;
+mod sound;
+pub use sound::*;
+mod sprint;
+pub use sprint::*;
 
+pub use feather_server_types::protocol_velocity;
 use feather_server_types::Game;
 use fecs::{Entity, World};
 use rand::Rng;
@@ -70,17 +137,6 @@ pub fn adjacent_blocks(pos: BlockPosition) -> ArrayVec<[BlockPosition; 6]> {
     .collect()
 }
 
-/// Converts float-based velocity in blocks per tick
-/// to the format used by the protocol.
-pub fn protocol_velocity(vel: DVec3) -> (i16, i16, i16) {
-    // These are in units of 1/8000 block per tick.
-    (
-        (vel.x * 8000.0) as i16,
-        (vel.y * 8000.0) as i16,
-        (vel.z * 8000.0) as i16,
-    )
-}
-
 /// Returns all entities within the given distance of the given
 /// position.
 ///
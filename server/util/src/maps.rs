@@ -0,0 +1,391 @@
+//! Rendering of terrain onto map items.
+//!
+//! Each map pixel holds a color byte of `base * 4 + shade`, where
+//! `base` is one of the map colors below and `shade` brightens or
+//! darkens it to show slopes.
+
+use feather_core::blocks::{BlockId, BlockKind};
+use feather_core::util::BlockPosition;
+
+/// Number of pixels along each side of a map.
+pub const MAP_SIZE: i32 = 128;
+
+/// The largest supported map scale.
+pub const MAX_MAP_SCALE: i8 = 4;
+
+/// Base map colors. The index of each is its ID.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MapColor {
+    None,
+    Grass,
+    Sand,
+    Wool,
+    Fire,
+    Ice,
+    Metal,
+    Plant,
+    Snow,
+    Clay,
+    Dirt,
+    Stone,
+    Water,
+    Wood,
+    Quartz,
+    Orange,
+    Magenta,
+    LightBlue,
+    Yellow,
+    Lime,
+    Pink,
+    Gray,
+    LightGray,
+    Cyan,
+    Purple,
+    Blue,
+    Brown,
+    Green,
+    Red,
+    Black,
+    Gold,
+    Diamond,
+    Lapis,
+    Emerald,
+    Podzol,
+    Nether,
+    WhiteTerracotta,
+}
+
+/// Shades applied to a base color.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MapShade {
+    Dark,
+    Normal,
+    Light,
+}
+
+impl MapColor {
+    /// Returns the color byte for this color with the given shade.
+    pub fn shaded(self, shade: MapShade) -> u8 {
+        if self == MapColor::None {
+            return 0;
+        }
+        self as u8 * 4 + shade as u8
+    }
+}
+
+/// The dye colors, in the order used by map colors.
+const DYE_COLORS: [&str; 16] = [
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "light_gray",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+
+/// Returns the base color ID of a block which is
+/// colored with a dye, such as wool or terracotta.
+fn dyed_color(name: &str) -> Option<u8> {
+    let (dye, material) = DYE_COLORS
+        .iter()
+        .enumerate()
+        .find(|(_, dye)| name.starts_with(*dye))
+        .map(|(i, dye)| (i as u8, &name[dye.len()..]))?;
+
+    match material {
+        "_terracotta" | "_glazed_terracotta" => Some(MapColor::WhiteTerracotta as u8 + dye),
+        "_wool"
+        | "_carpet"
+        | "_concrete"
+        | "_concrete_powder"
+        | "_bed"
+        | "_banner"
+        | "_wall_banner"
+        | "_shulker_box"
+        | "_stained_glass"
+        | "_stained_glass_pane" => Some(match dye {
+            0 => MapColor::Snow as u8,
+            dye => MapColor::Orange as u8 + dye - 1,
+        }),
+        _ => None,
+    }
+}
+
+/// Returns the base color ID with which a block is drawn on maps.
+pub fn map_color(block: BlockId) -> u8 {
+    use BlockKind::*;
+
+    let name = block.identifier().trim_start_matches("minecraft:");
+    if let Some(color) = dyed_color(name) {
+        return color;
+    }
+
+    let color = match block.kind() {
+        Air | CaveAir | VoidAir | Glass | GlassPane => MapColor::None,
+        GrassBlock | SlimeBlock => MapColor::Grass,
+        Sand | EndStone | Glowstone => MapColor::Sand,
+        Cobweb => MapColor::Wool,
+        Lava | Tnt | Fire | RedstoneBlock => MapColor::Fire,
+        Ice | PackedIce | BlueIce => MapColor::Ice,
+        IronBlock | Anvil => MapColor::Metal,
+        Snow | SnowBlock => MapColor::Snow,
+        Clay => MapColor::Clay,
+        Dirt | CoarseDirt | Farmland | GrassPath => MapColor::Dirt,
+        Water | BubbleColumn | Kelp | Seagrass => MapColor::Water,
+        QuartzBlock => MapColor::Quartz,
+        RedSand | Terracotta | Pumpkin => MapColor::Orange,
+        Mycelium => MapColor::Purple,
+        SoulSand => MapColor::Brown,
+        Melon | LilyPad | Cactus => MapColor::Green,
+        Obsidian => MapColor::Black,
+        GoldBlock => MapColor::Gold,
+        DiamondBlock => MapColor::Diamond,
+        LapisBlock => MapColor::Lapis,
+        EmeraldBlock => MapColor::Emerald,
+        Podzol => MapColor::Podzol,
+        Netherrack | NetherBricks => MapColor::Nether,
+        _ if block.is_leaves() || name.ends_with("_sapling") => MapColor::Plant,
+        _ if name.starts_with("spruce_") => MapColor::Podzol,
+        _ if name.starts_with("birch_") || name.contains("sandstone") => MapColor::Sand,
+        _ if name.starts_with("jungle_") || name.contains("granite") => MapColor::Dirt,
+        _ if name.starts_with("acacia_") => MapColor::Orange,
+        _ if name.starts_with("dark_oak_") => MapColor::Brown,
+        _ if name.contains("oak_") || name.ends_with("_log") || name == "bookshelf" => {
+            MapColor::Wood
+        }
+        _ if name.contains("diorite") || name.contains("quartz") => MapColor::Quartz,
+        _ if block.is_solid() => MapColor::Stone,
+        // Remaining non-solid blocks are plants,
+        // torches, rails and the like.
+        _ if name.contains("torch") || name.contains("rail") => MapColor::None,
+        _ => MapColor::Plant,
+    };
+    color as u8
+}
+
+/// Returns the color byte of a pixel from its base color,
+/// the height of its surface and that of the pixel to its north.
+/// Slopes rising towards the south are drawn lighter.
+pub fn shade_pixel(base: u8, height: i32, north_height: i32) -> u8 {
+    if base == 0 {
+        return 0;
+    }
+    let shade = match height.cmp(&north_height) {
+        std::cmp::Ordering::Greater => MapShade::Light,
+        std::cmp::Ordering::Equal => MapShade::Normal,
+        std::cmp::Ordering::Less => MapShade::Dark,
+    };
+    base * 4 + shade as u8
+}
+
+/// Returns the color byte of a water pixel, which is shaded by
+/// the depth of the water rather than the slope. Alternate pixels
+/// are offset to dither the edges of deeper water.
+pub fn shade_water(depth: i32, x: i32, z: i32) -> u8 {
+    let dither = ((x + z) & 1) as f64 * 0.2;
+    let depth = f64::from(depth) * 0.1 + dither;
+    let shade = if depth < 0.5 {
+        MapShade::Light
+    } else if depth > 0.9 {
+        MapShade::Dark
+    } else {
+        MapShade::Normal
+    };
+    MapColor::Water.shaded(shade)
+}
+
+/// The surface of a column of blocks as drawn on a map.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MapSurface {
+    /// Base color of the topmost visible block.
+    pub color: u8,
+    /// Height of the surface.
+    pub height: i32,
+    /// Depth of water above the surface, if it is water.
+    pub water_depth: i32,
+}
+
+/// Finds the topmost visible block of the column at `x`, `z`,
+/// searching down from `top`. Returns `None` if the column is
+/// not loaded.
+pub fn map_surface(
+    x: i32,
+    z: i32,
+    top: i32,
+    block_at: impl Fn(BlockPosition) -> Option<BlockId>,
+) -> Option<MapSurface> {
+    let mut y = top.min(255);
+    loop {
+        let block = block_at(BlockPosition::new(x, y, z))?;
+        let color = map_color(block);
+        if color != 0 || y <= 0 {
+            let mut water_depth = 0;
+            if color == MapColor::Water as u8 {
+                while y - water_depth > 0 {
+                    let below = block_at(BlockPosition::new(x, y - water_depth - 1, z))?;
+                    if map_color(below) != MapColor::Water as u8 {
+                        break;
+                    }
+                    water_depth += 1;
+                }
+            }
+            return Some(MapSurface {
+                color,
+                height: y,
+                water_depth,
+            });
+        }
+        y -= 1;
+    }
+}
+
+/// Returns the center of a map with the given scale created at `x`, `z`.
+/// Maps are aligned to a grid, so that maps of the same scale
+/// created next to each other line up.
+pub fn map_center(x: i32, z: i32, scale: i8) -> (i32, i32) {
+    let size = MAP_SIZE << scale;
+    let align = |coord: i32| {
+        let cell = (coord + MAP_SIZE / 2).div_euclid(size);
+        cell * size + size / 2 - MAP_SIZE / 2
+    };
+    (align(x), align(z))
+}
+
+/// Map icon types used by the server.
+pub const MAP_ICON_PLAYER: i32 = 0;
+pub const MAP_ICON_PLAYER_OFF_MAP: i32 = 6;
+/// The first banner icon type; the following
+/// fifteen are ordered as the dye colors.
+pub const MAP_ICON_WHITE_BANNER: i32 = 10;
+
+/// Returns the icon coordinates on a map of the given position,
+/// along with whether the position lies on the map. Positions off
+/// the map are clamped to its edge.
+pub fn map_icon_position(center: (i32, i32), scale: i8, x: f64, z: f64) -> (i8, i8, bool) {
+    let scale = f64::from(1 << scale);
+    let x = (x - f64::from(center.0)) / scale;
+    let z = (z - f64::from(center.1)) / scale;
+    let half = f64::from(MAP_SIZE / 2);
+    let on_map = x >= -half && x < half && z >= -half && z < half;
+
+    // Icon coordinates have twice the resolution of pixels.
+    let to_icon = |coord: f64| (coord * 2.0 + 0.5).max(-128.0).min(127.0) as i8;
+    (to_icon(x), to_icon(z), on_map)
+}
+
+/// Converts a yaw in degrees to an icon direction
+/// in sixteenths of a turn.
+pub fn map_icon_direction(yaw: f32) -> i8 {
+    (f64::from(yaw).rem_euclid(360.0) * 16.0 / 360.0 + 0.5) as i8 % 16
+}
+
+/// Returns the dye color of a banner block, e.g. `white`,
+/// or `None` if the block is not a banner.
+pub fn banner_color(block: BlockId) -> Option<&'static str> {
+    let name = block.identifier().trim_start_matches("minecraft:");
+    let dye = if name.ends_with("_wall_banner") {
+        name.trim_end_matches("_wall_banner")
+    } else if name.ends_with("_banner") {
+        name.trim_end_matches("_banner")
+    } else {
+        return None;
+    };
+    DYE_COLORS.iter().copied().find(|color| *color == dye)
+}
+
+/// Returns the icon type of a banner marker with the given dye color.
+pub fn banner_icon(color: &str) -> Option<i32> {
+    let index = DYE_COLORS.iter().position(|dye| *dye == color)?;
+    Some(MAP_ICON_WHITE_BANNER + index as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn world(blocks: &[(BlockPosition, BlockId)]) -> impl Fn(BlockPosition) -> Option<BlockId> {
+        let blocks: HashMap<_, _> = blocks.iter().copied().collect();
+        move |pos| Some(blocks.get(&pos).copied().unwrap_or_else(BlockId::air))
+    }
+
+    #[test]
+    fn block_colors() {
+        assert_eq!(map_color(BlockId::air()), MapColor::None as u8);
+        assert_eq!(map_color(BlockId::grass_block()), MapColor::Grass as u8);
+        assert_eq!(map_color(BlockId::stone()), MapColor::Stone as u8);
+        assert_eq!(map_color(BlockId::white_wool()), MapColor::Snow as u8);
+        assert_eq!(map_color(BlockId::red_wool()), MapColor::Red as u8);
+        assert_eq!(
+            map_color(BlockId::light_gray_terracotta()),
+            MapColor::WhiteTerracotta as u8 + 8
+        );
+    }
+
+    #[test]
+    fn slopes_are_shaded() {
+        let grass = MapColor::Grass as u8;
+        assert_eq!(shade_pixel(grass, 65, 64), grass * 4 + 2);
+        assert_eq!(shade_pixel(grass, 64, 64), grass * 4 + 1);
+        assert_eq!(shade_pixel(grass, 63, 64), grass * 4);
+        assert_eq!(shade_pixel(0, 63, 64), 0);
+    }
+
+    #[test]
+    fn surface_skips_air_and_measures_water() {
+        let blocks = world(&[
+            (BlockPosition::new(0, 60, 0), BlockId::sand()),
+            (BlockPosition::new(0, 61, 0), BlockId::water()),
+            (BlockPosition::new(0, 62, 0), BlockId::water()),
+        ]);
+
+        assert_eq!(
+            map_surface(0, 0, 100, blocks),
+            Some(MapSurface {
+                color: MapColor::Water as u8,
+                height: 62,
+                water_depth: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn centers_align_to_grid() {
+        assert_eq!(map_center(0, 0, 0), (0, 0));
+        assert_eq!(map_center(63, -64, 0), (0, 0));
+        assert_eq!(map_center(64, -65, 0), (128, -128));
+        assert_eq!(map_center(100, 100, 1), (64, 64));
+    }
+
+    #[test]
+    fn icon_positions() {
+        assert_eq!(map_icon_position((0, 0), 0, 0.0, 0.0), (0, 0, true));
+        assert_eq!(map_icon_position((0, 0), 0, 10.0, -10.0), (20, -19, true));
+        assert_eq!(map_icon_position((0, 0), 0, 200.0, 0.0), (127, 0, false));
+        assert_eq!(map_icon_direction(90.0), 4);
+        assert_eq!(map_icon_direction(-90.0), 12);
+    }
+
+    #[test]
+    fn banner_markers() {
+        assert_eq!(
+            banner_color(BlockId::light_blue_wall_banner()),
+            Some("light_blue")
+        );
+        assert_eq!(banner_color(BlockId::stone()), None);
+        assert_eq!(banner_icon("white"), Some(MAP_ICON_WHITE_BANNER));
+        assert_eq!(banner_icon("black"), Some(MAP_ICON_WHITE_BANNER + 15));
+    }
+}
@@ -0,0 +1,93 @@
+//! Sending messages on plugin channels, which proxies and
+//! modded clients use to exchange custom data with the server.
+
+use feather_core::network::packets::PluginMessageClientbound;
+use feather_server_types::{Network, PluginChannels};
+use fecs::{Entity, World};
+
+/// Channel on which the client and server announce their brand.
+pub const CHANNEL_BRAND: &str = "minecraft:brand";
+/// Channel on which a client registers channels it listens on.
+pub const CHANNEL_REGISTER: &str = "minecraft:register";
+/// Channel on which a client unregisters channels.
+pub const CHANNEL_UNREGISTER: &str = "minecraft:unregister";
+
+/// Sends a message on a plugin channel to a player.
+pub fn send_plugin_message(world: &World, player: Entity, channel: &str, data: Vec<u8>) {
+    world.get::<Network>(player).send(PluginMessageClientbound {
+        channel: channel.to_owned(),
+        data,
+    });
+}
+
+/// Returns whether a player's client has registered a plugin channel.
+///
+/// Channels in the `minecraft` namespace are understood
+/// by every client and need not be registered.
+pub fn has_plugin_channel(world: &World, player: Entity, channel: &str) -> bool {
+    channel.starts_with("minecraft:")
+        || world
+            .try_get::<PluginChannels>(player)
+            .map_or(false, |channels| channels.0.contains(channel))
+}
+
+/// Parses the channel names in a register or unregister
+/// message, which are separated by null bytes.
+pub fn parse_channel_list(data: &[u8]) -> impl Iterator<Item = String> + '_ {
+    data.split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+}
+
+/// Encodes a string as it is sent in brand messages:
+/// prefixed by its length as a VarInt.
+pub fn encode_plugin_string(s: &str) -> Vec<u8> {
+    let mut data = Vec::with_capacity(s.len() + 5);
+    let mut len = s.len() as u32;
+    loop {
+        let byte = (len & 0x7F) as u8;
+        len >>= 7;
+        if len == 0 {
+            data.push(byte);
+            break;
+        }
+        data.push(byte | 0x80);
+    }
+    data.extend_from_slice(s.as_bytes());
+    data
+}
+
+/// Decodes a string encoded by `encode_plugin_string`,
+/// or returns `None` if it is malformed.
+pub fn decode_plugin_string(data: &[u8]) -> Option<String> {
+    let mut len = 0usize;
+    for (i, &byte) in data.iter().enumerate().take(5) {
+        len |= usize::from(byte & 0x7F) << (7 * i);
+        if byte & 0x80 == 0 {
+            let bytes = data.get(i + 1..i + 1 + len)?;
+            return String::from_utf8(bytes.to_vec()).ok();
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_list() {
+        let channels: Vec<String> = parse_channel_list(b"bungeecord:main\0wecui:cui\0").collect();
+        assert_eq!(channels, vec!["bungeecord:main", "wecui:cui"]);
+    }
+
+    #[test]
+    fn plugin_string_round_trip() {
+        assert_eq!(encode_plugin_string("Feather"), b"\x07Feather".to_vec());
+        let long = "a".repeat(200);
+        let encoded = encode_plugin_string(&long);
+        assert_eq!(&encoded[..2], &[0xC8, 0x01]);
+        assert_eq!(decode_plugin_string(&encoded), Some(long));
+        assert_eq!(decode_plugin_string(b"\x07Feat"), None);
+    }
+}
@@ -0,0 +1,212 @@
+//! Changing the scoreboard and informing players of the changes,
+//! plus tracking scores for criteria maintained by the server.
+//!
+//! Gameplay systems should change the scoreboard through these
+//! functions rather than the `Scoreboard` resource directly, so
+//! that clients stay in sync with it.
+
+use crate::attribute_value;
+use feather_core::network::Packet;
+use feather_core::text::Text;
+use feather_server_types::{
+    Air, Attribute, BumpVec, Criteria, DisplaySlot, EntityDeathEvent, Experience, Game, Health,
+    Hunger, Name, Network, Player, PlayerJoinEvent, Scoreboard, ScoreboardError,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+use std::sync::Arc;
+
+fn change<P: Packet>(
+    game: &Game,
+    world: &World,
+    f: impl FnOnce(&mut Scoreboard) -> Result<P, ScoreboardError>,
+) -> Result<(), ScoreboardError> {
+    let packet = f(&mut game.resources.get_mut::<Scoreboard>())?;
+    game.broadcast_global(world, packet, None);
+    Ok(())
+}
+
+/// Adds an objective with the given criteria.
+pub fn add_objective(
+    game: &Game,
+    world: &World,
+    name: &str,
+    display_name: Text,
+    criteria: Criteria,
+) -> Result<(), ScoreboardError> {
+    change(game, world, |scoreboard| {
+        scoreboard.add_objective(name, display_name, criteria)
+    })
+}
+
+pub fn remove_objective(game: &Game, world: &World, name: &str) -> Result<(), ScoreboardError> {
+    change(game, world, |scoreboard| scoreboard.remove_objective(name))
+}
+
+pub fn set_objective_display_name(
+    game: &Game,
+    world: &World,
+    name: &str,
+    display_name: Text,
+) -> Result<(), ScoreboardError> {
+    change(game, world, |scoreboard| {
+        scoreboard.set_display_name(name, display_name)
+    })
+}
+
+/// Displays an objective in the given slot, or
+/// clears the slot if `name` is `None`.
+pub fn set_display_slot(
+    game: &Game,
+    world: &World,
+    slot: DisplaySlot,
+    name: Option<&str>,
+) -> Result<(), ScoreboardError> {
+    change(game, world, |scoreboard| {
+        scoreboard.set_display_slot(slot, name)
+    })
+}
+
+pub fn set_score(
+    game: &Game,
+    world: &World,
+    objective: &str,
+    entry: &str,
+    value: i32,
+) -> Result<(), ScoreboardError> {
+    change(game, world, |scoreboard| {
+        scoreboard.set_score(objective, entry, value)
+    })
+}
+
+/// Adds to an entry's score, returning the new score.
+pub fn add_score(
+    game: &Game,
+    world: &World,
+    objective: &str,
+    entry: &str,
+    amount: i32,
+) -> Result<i32, ScoreboardError> {
+    let mut value = 0;
+    change(game, world, |scoreboard| {
+        let (new, packet) = scoreboard.add_score(objective, entry, amount)?;
+        value = new;
+        Ok(packet)
+    })?;
+    Ok(value)
+}
+
+/// Removes an entry's score for the given objective,
+/// or for all objectives if `objective` is `None`.
+pub fn reset_score(
+    game: &Game,
+    world: &World,
+    objective: Option<&str>,
+    entry: &str,
+) -> Result<(), ScoreboardError> {
+    change(game, world, |scoreboard| {
+        scoreboard.reset_score(objective, entry)
+    })
+}
+
+/// Adds one to the scores of `entity` for all objectives
+/// with the given criteria. Does nothing for entities
+/// other than players, which have no entry.
+fn increment_criteria(game: &Game, world: &World, entity: Entity, criteria: Criteria) {
+    let name = match world.try_get::<Name>(entity) {
+        Some(name) if world.has::<Player>(entity) => name.0.clone(),
+        _ => return,
+    };
+
+    let objectives = game
+        .resources
+        .get::<Scoreboard>()
+        .objectives_with_criteria(criteria);
+    for objective in objectives {
+        let _ = add_score(game, world, &objective, &name, 1);
+    }
+}
+
+/// Returns the score of a player for a read-only criteria.
+fn criteria_value(world: &World, player: Entity, criteria: Criteria) -> i32 {
+    match criteria {
+        Criteria::Health => world.get::<Health>(player).0.ceil() as i32,
+        Criteria::Food => world
+            .try_get::<Hunger>(player)
+            .map_or(0, |hunger| hunger.food),
+        Criteria::Air => world.try_get::<Air>(player).map_or(0, |air| air.0),
+        Criteria::Armor => attribute_value(world, player, Attribute::Armor) as i32,
+        Criteria::Level => world
+            .try_get::<Experience>(player)
+            .map_or(0, |experience| experience.level as i32),
+        Criteria::Xp => world
+            .try_get::<Experience>(player)
+            .map_or(0, |experience| experience.total as i32),
+        _ => 0,
+    }
+}
+
+/// Event handler for sending the scoreboard to players who join.
+#[fecs::event_handler]
+pub fn on_player_join_send_scoreboard(event: &PlayerJoinEvent, game: &Game, world: &mut World) {
+    let network = world.get::<Network>(event.player);
+    for packet in game.resources.get::<Scoreboard>().packets() {
+        network.send_boxed(packet);
+    }
+}
+
+/// Event handler for counting deaths and kills.
+#[fecs::event_handler]
+pub fn on_entity_death_update_scores(event: &EntityDeathEvent, game: &Game, world: &mut World) {
+    increment_criteria(game, world, event.entity, Criteria::DeathCount);
+
+    let killer = match event.cause.attacker() {
+        Some(killer) if world.is_alive(killer) => killer,
+        _ => return,
+    };
+    increment_criteria(game, world, killer, Criteria::TotalKillCount);
+    if world.has::<Player>(event.entity) {
+        increment_criteria(game, world, killer, Criteria::PlayerKillCount);
+    }
+}
+
+/// System which updates the scores of players for criteria
+/// which mirror their state, such as health.
+#[fecs::system]
+pub fn update_criteria_scores(game: &mut Game, world: &mut World) {
+    let resources = Arc::clone(&game.resources);
+    let mut scoreboard = resources.get_mut::<Scoreboard>();
+
+    let mut tracked = BumpVec::new_in(game.bump());
+    for (name, objective) in scoreboard.objectives() {
+        if objective.criteria.is_read_only() {
+            tracked.push((name.to_owned(), objective.criteria));
+        }
+    }
+    if tracked.is_empty() {
+        return;
+    }
+
+    let mut players = BumpVec::new_in(game.bump());
+    for (player, name) in <Read<Name>>::query()
+        .filter(component::<Player>() & component::<Health>())
+        .iter_entities(world.inner())
+    {
+        players.push((player, name.0.clone()));
+    }
+
+    for (player, name) in players {
+        for (objective, criteria) in &tracked {
+            let value = criteria_value(world, player, *criteria);
+            let current = scoreboard
+                .objective(objective)
+                .and_then(|objective| objective.score(&name));
+            if current == Some(value) {
+                continue;
+            }
+
+            if let Ok(packet) = scoreboard.set_score(objective, &name, value) {
+                game.broadcast_global(world, packet, None);
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Raising, lowering, disabling, and wearing down shields.
+
+use crate::{has_item_cooldown, start_item_cooldown, wear_item};
+use feather_core::entitymeta::EntityMetadata;
+use feather_core::inventory::{Inventory, SLOT_HOTBAR_OFFSET, SLOT_OFFHAND};
+use feather_core::items::Item;
+use feather_core::network::packets::EntityStatus;
+use feather_core::util::Hand;
+use feather_server_types::{
+    BlockingWithShield, EntityId, Game, HeldItem, Player, SHIELD_DISABLE_TICKS,
+};
+use fecs::{Entity, World};
+
+/// Entity status indicating that an entity's shield was disabled.
+const STATUS_SHIELD_DISABLED: i8 = 30;
+
+/// Raises the shield held in the given hand.
+/// Does nothing while the shield is disabled.
+pub fn raise_shield(game: &Game, world: &mut World, entity: Entity, hand: Hand) {
+    if has_item_cooldown(game, world, entity, Item::Shield) {
+        return;
+    }
+
+    let blocking = BlockingWithShield {
+        hand,
+        raised_at: game.tick_count,
+    };
+    if world.has::<BlockingWithShield>(entity) {
+        *world.get_mut::<BlockingWithShield>(entity) = blocking;
+    } else {
+        world.add(entity, blocking).unwrap();
+    }
+    set_active_hand(world, entity, Some(hand));
+}
+
+/// Lowers an entity's shield, if it is raised.
+pub fn lower_shield(world: &mut World, entity: Entity) {
+    if world.has::<BlockingWithShield>(entity) {
+        world.remove::<BlockingWithShield>(entity).unwrap();
+        set_active_hand(world, entity, None);
+    }
+}
+
+/// Returns whether an entity has raised a shield
+/// for long enough that it blocks damage.
+pub fn is_blocking(game: &Game, world: &World, entity: Entity) -> bool {
+    world
+        .try_get::<BlockingWithShield>(entity)
+        .map_or(false, |blocking| blocking.is_active(game.tick_count))
+}
+
+/// Lowers an entity's shield and prevents it from
+/// being raised again for a few seconds.
+pub fn disable_shield(game: &Game, world: &mut World, entity: Entity) {
+    lower_shield(world, entity);
+    start_item_cooldown(game, world, entity, Item::Shield, SHIELD_DISABLE_TICKS);
+
+    let entity_id = world.get::<EntityId>(entity).0;
+    game.broadcast_entity_update(
+        world,
+        EntityStatus {
+            entity_id,
+            entity_status: STATUS_SHIELD_DISABLED,
+        },
+        entity,
+        None,
+    );
+}
+
+fn set_active_hand(world: &mut World, entity: Entity, hand: Option<Hand>) {
+    if world.has::<EntityMetadata>(entity) {
+        world
+            .get_mut::<EntityMetadata>(entity)
+            .set_active_hand(hand);
+    }
+}
+
+/// Wears down the shield an entity blocked `damage` with.
+/// Only damage of at least three points wears shields.
+pub fn wear_shield(game: &mut Game, world: &mut World, entity: Entity, damage: f32) {
+    if damage < 3.0 || !world.has::<Player>(entity) {
+        return;
+    }
+    let slot = match world.try_get::<BlockingWithShield>(entity).map(|b| b.hand) {
+        Some(Hand::Main) => SLOT_HOTBAR_OFFSET + world.get::<HeldItem>(entity).0,
+        Some(Hand::Off) => SLOT_OFFHAND,
+        None => return,
+    };
+
+    wear_item(game, world, entity, slot, 1 + damage as u32);
+    if world.get::<Inventory>(entity).item_at(slot).is_none() {
+        lower_shield(world, entity);
+    }
+}
+
+/// Returns whether an item disables shields that it hits.
+pub fn disables_shields(item: Item) -> bool {
+    match item {
+        Item::WoodenAxe | Item::StoneAxe | Item::IronAxe | Item::GoldenAxe | Item::DiamondAxe => {
+            true
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,60 @@
+//! Playing sounds to nearby players.
+
+use feather_core::network::packets::NamedSoundEffect;
+use feather_core::util::Position;
+use feather_server_types::Game;
+use fecs::{Entity, World};
+
+/// The category of a sound, which determines
+/// which volume slider on the client applies to it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SoundCategory {
+    Master,
+    Music,
+    Record,
+    Weather,
+    Block,
+    Hostile,
+    Neutral,
+    Player,
+    Ambient,
+    Voice,
+}
+
+impl SoundCategory {
+    /// Returns the protocol ID of this category.
+    pub fn id(self) -> i32 {
+        self as i32
+    }
+}
+
+/// Plays the sound with the given name at `pos` for all players
+/// who can see it, except `neq`, which is normally the player
+/// who caused the sound and has played it already.
+///
+/// `name` is the sound's identifier without the `minecraft:`
+/// namespace, e.g. `item.bucket.fill`.
+#[allow(clippy::too_many_arguments)]
+pub fn play_sound(
+    game: &Game,
+    world: &World,
+    name: &str,
+    category: SoundCategory,
+    pos: Position,
+    volume: f32,
+    pitch: f32,
+    neq: Option<Entity>,
+) {
+    // Sound positions are sent as
+    // fixed-point numbers with 3 fraction bits.
+    let packet = NamedSoundEffect {
+        sound_name: format!("minecraft:{}", name),
+        sound_category: category.id(),
+        effect_pos_x: (pos.x * 8.0) as i32,
+        effect_pos_y: (pos.y * 8.0) as i32,
+        effect_pos_z: (pos.z * 8.0) as i32,
+        volume,
+        pitch,
+    };
+    game.broadcast_chunk_update(world, packet, pos.chunk(), neq);
+}
@@ -0,0 +1,143 @@
+//! Sprinting, which speeds players up at the cost of exhaustion.
+
+use crate::{broadcast_attributes, can_fly};
+use feather_core::entitymeta::{EntityBitMask, EntityMetadata};
+use feather_core::util::{Gamemode, Position};
+use feather_server_types::{
+    Attribute, AttributeModifier, Attributes, BumpVec, Game, Hunger, ModifierOperation,
+    PreviousPosition, Sprinting, SPRINTING_SPEED_MODIFIER,
+};
+use fecs::{component, Entity, IntoQuery, Read, World};
+
+/// Food level at or below which players cannot sprint.
+pub const SPRINT_HUNGER_THRESHOLD: i32 = 6;
+
+/// Fraction by which sprinting increases movement speed.
+const SPRINT_SPEED_BOOST: f64 = 0.3;
+
+/// Exhaustion added for each block sprinted.
+const SPRINT_EXHAUSTION: f32 = 0.1;
+
+/// Exhaustion added by jumping while walking and while sprinting.
+const JUMP_EXHAUSTION: f32 = 0.05;
+const SPRINT_JUMP_EXHAUSTION: f32 = 0.2;
+
+/// Returns whether a player has enough food to sprint.
+/// Players who can fly may always sprint.
+pub fn can_sprint(world: &World, player: Entity) -> bool {
+    let flying = world
+        .try_get::<Gamemode>(player)
+        .map_or(false, |gamemode| can_fly(*gamemode));
+    flying
+        || world
+            .try_get::<Hunger>(player)
+            .map_or(true, |hunger| hunger.food > SPRINT_HUNGER_THRESHOLD)
+}
+
+/// Starts or stops a player sprinting, updating their
+/// movement speed and metadata. Players who are too
+/// hungry are not allowed to start sprinting.
+pub fn set_sprinting(game: &Game, world: &mut World, player: Entity, sprinting: bool) {
+    let sprinting = sprinting && can_sprint(world, player);
+
+    if sprinting && !world.has::<Sprinting>(player) {
+        world.add(player, Sprinting).unwrap();
+    } else if !sprinting && world.has::<Sprinting>(player) {
+        world.remove::<Sprinting>(player).unwrap();
+    }
+
+    if !world.has::<Attributes>(player) {
+        world.add(player, Attributes::new()).unwrap();
+    }
+    {
+        let mut attributes = world.get_mut::<Attributes>(player);
+        if sprinting {
+            attributes.add_modifier(
+                Attribute::MovementSpeed,
+                AttributeModifier {
+                    uuid: SPRINTING_SPEED_MODIFIER,
+                    amount: SPRINT_SPEED_BOOST,
+                    operation: ModifierOperation::Multiply,
+                },
+            );
+        } else {
+            attributes.remove_modifier(Attribute::MovementSpeed, SPRINTING_SPEED_MODIFIER);
+        }
+    }
+    broadcast_attributes(game, world, player, &[Attribute::MovementSpeed]);
+
+    world
+        .get_mut::<EntityMetadata>(player)
+        .set_flag(EntityBitMask::SPRINTING, sprinting);
+}
+
+/// Computes the exhaustion added by a player's movement
+/// during a tick, from `old` to `new`.
+pub fn movement_exhaustion(old: Position, new: Position, sprinting: bool) -> f32 {
+    let mut exhaustion = 0.0;
+
+    if sprinting && new.on_ground {
+        let distance = (new.x - old.x).hypot(new.z - old.z);
+        exhaustion += distance as f32 * SPRINT_EXHAUSTION;
+    }
+
+    let jumped = old.on_ground && !new.on_ground && new.y > old.y;
+    if jumped {
+        exhaustion += if sprinting {
+            SPRINT_JUMP_EXHAUSTION
+        } else {
+            JUMP_EXHAUSTION
+        };
+    }
+
+    exhaustion
+}
+
+/// System which exhausts players as they move and stops
+/// players sprinting once they become too hungry.
+#[fecs::system]
+pub fn update_sprinting(game: &mut Game, world: &mut World) {
+    let mut exhausted = BumpVec::new_in(game.bump());
+    let mut stopped = BumpVec::new_in(game.bump());
+
+    for (player, (pos, previous)) in <(Read<Position>, Read<PreviousPosition>)>::query()
+        .filter(component::<Hunger>())
+        .iter_entities(world.inner())
+    {
+        let sprinting = world.has::<Sprinting>(player);
+        if sprinting && !can_sprint(world, player) {
+            stopped.push(player);
+        }
+
+        let exhaustion = movement_exhaustion(previous.0, *pos, sprinting);
+        if exhaustion > 0.0 {
+            exhausted.push((player, exhaustion));
+        }
+    }
+
+    for (player, exhaustion) in exhausted {
+        world.get_mut::<Hunger>(player).add_exhaustion(exhaustion);
+    }
+
+    for player in stopped {
+        set_sprinting(game, world, player, false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use feather_core::position;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn sprinting_costs_exhaustion() {
+        let old = position!(0.0, 64.0, 0.0, 0.0, 0.0, true);
+        let new = position!(3.0, 64.0, 4.0, 0.0, 0.0, true);
+        assert!((movement_exhaustion(old, new, true) - 0.5).abs() < 1e-6);
+        assert_eq!(movement_exhaustion(old, new, false), 0.0);
+
+        let jump = position!(0.0, 64.5, 0.0, 0.0, 0.0, false);
+        assert!((movement_exhaustion(old, jump, false) - JUMP_EXHAUSTION).abs() < 1e-6);
+    }
+}
@@ -1,25 +1,49 @@
 //! Handles world time.
 
 use feather_core::network::packets::TimeUpdate;
-use feather_server_types::{Game, Network, PlayerJoinEvent};
+use feather_server_types::{
+    Game, GameRule, GameRuleChangeEvent, Network, PlayerJoinEvent, DO_DAYLIGHT_CYCLE,
+};
 use fecs::World;
 
 /// System for incrementing time each tick.
 #[fecs::system]
 pub fn increment_time(game: &mut Game) {
-    game.time.0 += 1;
+    let daylight_cycle = game.game_rules.bool(DO_DAYLIGHT_CYCLE);
+    game.time.tick(daylight_cycle);
+}
+
+fn time_packet(game: &Game) -> TimeUpdate {
+    let mut time_of_day = game.time.time_of_day() as i64;
+    // A negative time of day stops clients from
+    // advancing the sun between time updates.
+    if !game.game_rules.bool(DO_DAYLIGHT_CYCLE) {
+        time_of_day = if time_of_day == 0 { -1 } else { -time_of_day };
+    }
+    TimeUpdate {
+        world_age: game.time.world_age() as i64,
+        time_of_day,
+    }
+}
+
+/// Sends the time to all players, as is
+/// needed after the time is changed.
+pub fn broadcast_time(game: &Game, world: &World) {
+    game.broadcast_global(world, time_packet(game), None);
 }
 
 /// Event handler for sending world time to players.
 #[fecs::event_handler]
 pub fn on_player_join_send_time(event: &PlayerJoinEvent, game: &Game, world: &mut World) {
     let network = world.get::<Network>(event.player);
+    network.send(time_packet(game));
+}
 
-    // Send time to player.
-    let packet = TimeUpdate {
-        world_age: game.time.world_age() as i64,
-        time_of_day: game.time.time_of_day() as i64,
-    };
-
-    network.send(packet);
+/// Event handler for resending the time when
+/// the daylight cycle is toggled.
+#[fecs::event_handler]
+pub fn on_game_rule_change_send_time(event: &GameRuleChangeEvent, game: &Game, world: &mut World) {
+    if event.rule == GameRule::Bool(DO_DAYLIGHT_CYCLE) {
+        broadcast_time(game, world);
+    }
 }
@@ -0,0 +1,169 @@
+//! Totems of undying, which save entities holding them from death.
+
+use crate::{add_effect, clear_effects};
+use feather_core::inventory::{Inventory, SLOT_HOTBAR_OFFSET, SLOT_OFFHAND};
+use feather_core::items::Item;
+use feather_core::network::packets::EntityStatus;
+use feather_server_types::{
+    ActiveEffect, DamageCause, EntityId, Game, Health, HeldItem, InventoryUpdateEvent, StatusEffect,
+};
+use fecs::{Entity, World};
+use smallvec::smallvec;
+
+/// Entity status which plays the totem of undying animation.
+const STATUS_TOTEM: i8 = 35;
+
+/// Uses up a totem of undying held by an entity which took fatal
+/// damage from `cause`. If the entity holds a totem in either hand,
+/// it survives with one health and receives the totem's effects.
+///
+/// Returns whether a totem was used.
+pub fn use_totem(game: &mut Game, world: &mut World, entity: Entity, cause: &DamageCause) -> bool {
+    // Totems cannot save entities which fell out of the world.
+    if *cause == DamageCause::Void {
+        return false;
+    }
+
+    let slot = {
+        let (inventory, held) = match (
+            world.try_get::<Inventory>(entity),
+            world.try_get::<HeldItem>(entity),
+        ) {
+            (Some(inventory), Some(held)) => (inventory, held.0),
+            _ => return false,
+        };
+        let holds_totem = |slot| {
+            inventory
+                .item_at(slot)
+                .map_or(false, |stack| stack.ty == Item::TotemOfUndying)
+        };
+
+        let main_hand = SLOT_HOTBAR_OFFSET + held;
+        if holds_totem(main_hand) {
+            main_hand
+        } else if holds_totem(SLOT_OFFHAND) {
+            SLOT_OFFHAND
+        } else {
+            return false;
+        }
+    };
+
+    world.get_mut::<Inventory>(entity).clear_item_at(slot);
+    game.handle(
+        world,
+        InventoryUpdateEvent {
+            slots: smallvec![slot],
+            player: entity,
+        },
+    );
+
+    world.get_mut::<Health>(entity).0 = 1.0;
+    clear_effects(game, world, entity);
+    add_effect(
+        game,
+        world,
+        entity,
+        StatusEffect::Regeneration,
+        ActiveEffect::new(1, 900),
+    );
+    add_effect(
+        game,
+        world,
+        entity,
+        StatusEffect::Absorption,
+        ActiveEffect::new(1, 100),
+    );
+
+    let entity_id = world.get::<EntityId>(entity).0;
+    game.broadcast_entity_update(
+        world,
+        EntityStatus {
+            entity_id,
+            entity_status: STATUS_TOTEM,
+        },
+        entity,
+        None,
+    );
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity_damage_request;
+    use feather_core::items::ItemStack;
+    use feather_core::position;
+    use feather_core::util::Gamemode;
+    use feather_server_types::{ActiveEffects, EntityDamageRequest, INVULNERABILITY_TICKS};
+    use feather_test_framework::Test;
+
+    fn player_holding_totem(test: &mut Test, slot: usize) -> Entity {
+        let player = test.player("", position!(0.0, 64.0, 0.0));
+        *test.world.get_mut::<Gamemode>(player) = Gamemode::Survival;
+        test.world
+            .get_mut::<Inventory>(player)
+            .set_item_at(slot, ItemStack::new(Item::TotemOfUndying, 1));
+        player
+    }
+
+    fn damage(test: &mut Test, player: Entity, cause: DamageCause) {
+        test.handle(
+            EntityDamageRequest {
+                entity: player,
+                damage: 30.0,
+                cause,
+            },
+            entity_damage_request,
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn totem_saves_from_fatal_damage() {
+        let mut test = Test::new();
+        let player = player_holding_totem(&mut test, SLOT_OFFHAND);
+
+        damage(&mut test, player, DamageCause::Generic);
+
+        assert_eq!(test.world.get::<Health>(player).0, 1.0);
+        assert!(test
+            .world
+            .get::<Inventory>(player)
+            .item_at(SLOT_OFFHAND)
+            .is_none());
+        let effects = test.world.get::<ActiveEffects>(player);
+        assert_eq!(effects.amplifier(StatusEffect::Regeneration), Some(1));
+        assert_eq!(effects.amplifier(StatusEffect::Absorption), Some(1));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn totem_is_used_once() {
+        let mut test = Test::new();
+        let player = player_holding_totem(&mut test, SLOT_HOTBAR_OFFSET);
+
+        damage(&mut test, player, DamageCause::Generic);
+        assert_eq!(test.world.get::<Health>(player).0, 1.0);
+
+        test.game.tick_count += INVULNERABILITY_TICKS;
+        damage(&mut test, player, DamageCause::Generic);
+        assert_eq!(test.world.get::<Health>(player).0, 0.0);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn totem_does_not_save_from_void() {
+        let mut test = Test::new();
+        let player = player_holding_totem(&mut test, SLOT_HOTBAR_OFFSET);
+
+        damage(&mut test, player, DamageCause::Void);
+
+        assert_eq!(test.world.get::<Health>(player).0, 0.0);
+        assert!(test
+            .world
+            .get::<Inventory>(player)
+            .item_at(SLOT_HOTBAR_OFFSET)
+            .is_some());
+    }
+}
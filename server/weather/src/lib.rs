@@ -1,5 +1,9 @@
 use feather_core::network::packets::ChangeGameState;
-use feather_server_types::{Game, Network, PlayerJoinEvent, Weather, WeatherChangeEvent};
+use feather_core::util::BlockPosition;
+use feather_server_types::{
+    Game, LightningStrikeRequest, Network, PlayerJoinEvent, Weather, WeatherChangeEvent,
+    WeatherRequest, DO_WEATHER_CYCLE,
+};
 use fecs::{Entity, World};
 use rand::Rng;
 
@@ -8,6 +12,10 @@ const TICKS_HALF_DAY: i32 = TICKS_DAY / 2;
 const TICKS_WEEK: i32 = TICKS_DAY * 7;
 // const THUNDER_FACTOR: i32 = 10;
 
+/// Chance per tick of lightning striking each
+/// loaded chunk during a thunderstorm.
+const LIGHTNING_CHANCE: u32 = 100_000;
+
 #[allow(unused)]
 pub fn clear_weather(game: &mut Game) {
     let duration = game
@@ -18,6 +26,10 @@ pub fn clear_weather(game: &mut Game) {
 
 #[fecs::system]
 pub fn update_weather(game: &mut Game, world: &mut World) {
+    if !game.game_rules.bool(DO_WEATHER_CYCLE) {
+        return;
+    }
+
     if game.level.clear_weather_time >= 0 {
         game.level.clear_weather_time -= 1;
         return;
@@ -62,6 +74,45 @@ pub fn update_weather(game: &mut Game, world: &mut World) {
     }
 }
 
+/// System which strikes lightning at random
+/// positions during thunderstorms.
+#[fecs::system]
+pub fn strike_thunderstorm_lightning(game: &mut Game, world: &mut World) {
+    if get_weather(game) != Weather::Thunder {
+        return;
+    }
+
+    let mut strikes = vec![];
+    for chunk in game.chunk_map.iter_chunks() {
+        let mut rng = game.rng();
+        if rng.gen_range(0, LIGHTNING_CHANCE) != 0 {
+            continue;
+        }
+
+        let chunk = chunk.read();
+        let x = rng.gen_range(0, 16);
+        let z = rng.gen_range(0, 16);
+        // TODO: only strike in biomes where it rains
+        let y = i32::from(chunk.heightmap(x, z).motion_blocking()) + 1;
+
+        let origin = chunk.position();
+        strikes.push(BlockPosition::new(
+            origin.x * 16 + x as i32,
+            y,
+            origin.z * 16 + z as i32,
+        ));
+    }
+
+    for pos in strikes {
+        game.handle(
+            world,
+            LightningStrikeRequest {
+                pos: pos.position(),
+            },
+        );
+    }
+}
+
 pub fn get_weather(game: &Game) -> Weather {
     if game.level.clear_weather_time > 0 {
         Weather::Clear
@@ -74,6 +125,23 @@ pub fn get_weather(game: &Game) -> Weather {
     }
 }
 
+/// Returns whether nothing above the given block would
+/// shelter it from rain or lightning.
+pub fn is_exposed_to_sky(game: &Game, pos: BlockPosition) -> bool {
+    let chunk = match game.chunk_map.chunk_at(pos.chunk()) {
+        Some(chunk) => chunk,
+        None => return false,
+    };
+    let (x, z) = (pos.x.rem_euclid(16) as usize, pos.z.rem_euclid(16) as usize);
+    pos.y > i32::from(chunk.heightmap(x, z).motion_blocking())
+}
+
+/// Returns whether it is raining on the given block.
+pub fn is_rained_on(game: &Game, pos: BlockPosition) -> bool {
+    // TODO: take biomes without rain into account
+    get_weather(game) != Weather::Clear && is_exposed_to_sky(game, pos)
+}
+
 pub fn set_weather(game: &mut Game, weather: Weather, duration: i32) -> Weather {
     let from = get_weather(game);
     match weather {
@@ -96,6 +164,39 @@ pub fn set_weather(game: &mut Game, weather: Weather, duration: i32) -> Weather
     from
 }
 
+/// Handles a `WeatherRequest`.
+#[fecs::event_handler]
+pub fn weather_request(event: &WeatherRequest, game: &mut Game, world: &mut World) {
+    let from = set_weather(game, Weather::Clear, 0);
+    match event.weather {
+        Weather::Clear => {
+            set_weather(game, Weather::Clear, event.duration);
+        }
+        // Rain or thunder lasts for the duration, and
+        // is followed by the usual weather cycle.
+        weather => {
+            game.level.clear_weather_time = 0;
+            set_weather(game, Weather::Rain, event.duration);
+            if weather == Weather::Thunder {
+                set_weather(game, Weather::Thunder, event.duration);
+            } else {
+                game.level.thunder_time = event.duration;
+            }
+        }
+    }
+
+    if from != event.weather {
+        game.handle(
+            world,
+            WeatherChangeEvent {
+                from,
+                to: event.weather,
+                duration: event.duration,
+            },
+        );
+    }
+}
+
 #[fecs::event_handler]
 pub fn on_player_join_send_weather(event: &PlayerJoinEvent, game: &Game, world: &mut World) {
     send_weather(world, event.player, get_weather(game));